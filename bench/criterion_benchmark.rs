@@ -1,5 +1,3 @@
-use std::path::Path;
-
 use cairo_vm::{
     cairo_run,
     hint_processor::builtin_hint_processor::builtin_hint_processor_definition::BuiltinHintProcessor,
@@ -27,16 +25,17 @@ const BENCH_PATH: &str = "cairo_programs/benchmarks/";
 
 pub fn criterion_benchmarks(c: &mut Criterion) {
     let mut hint_executor = BuiltinHintProcessor::new_empty();
+    let cairo_run_config = cairo_run::CairoRunConfig {
+        layout: "all",
+        ..Default::default()
+    };
     for benchmark_name in build_bench_strings() {
+        let program_content = std::fs::read(&benchmark_name.1).unwrap();
         c.bench_function(&benchmark_name.0, |b| {
             b.iter(|| {
                 cairo_run::cairo_run(
-                    black_box(Path::new(&benchmark_name.1)),
-                    "main",
-                    false,
-                    false,
-                    "all",
-                    false,
+                    black_box(&program_content),
+                    &cairo_run_config,
                     &mut hint_executor,
                 )
             })
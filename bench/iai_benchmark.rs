@@ -1,28 +1,28 @@
-use std::path::Path;
-
 use cairo_vm::{
-    cairo_run::cairo_run,
+    cairo_run::{cairo_run, CairoRunConfig},
     hint_processor::builtin_hint_processor::builtin_hint_processor_definition::BuiltinHintProcessor,
-    vm::errors::cairo_run_errors::CairoRunError, vm::runners::cairo_runner::CairoRunner,
+    vm::errors::cairo_run_errors::CairoRunError,
+    vm::{runners::cairo_runner::CairoRunner, vm_core::VirtualMachine},
 };
 use iai::{black_box, main};
 
 macro_rules! iai_bench_expand_prog {
     ($val: ident) => {
-        fn $val() -> Result<CairoRunner, CairoRunError> {
+        fn $val() -> Result<(CairoRunner, VirtualMachine), CairoRunError> {
             let mut hint_executor = BuiltinHintProcessor::new_empty();
-            let path = Path::new(concat!(
+            let cairo_run_config = CairoRunConfig {
+                layout: "all",
+                ..Default::default()
+            };
+            let program_content = std::fs::read(concat!(
                 "cairo_programs/benchmarks/",
                 stringify!($val),
                 ".json"
-            ));
+            ))
+            .unwrap();
             cairo_run(
-                black_box(path),
-                "main",
-                false,
-                false,
-                "all",
-                false,
+                black_box(&program_content),
+                &cairo_run_config,
                 &mut hint_executor,
             )
         }
@@ -0,0 +1,39 @@
+%builtins ec_op
+
+from starkware.cairo.common.alloc import alloc
+from starkware.cairo.common.cairo_builtins import EcOpBuiltin
+from starkware.cairo.common.ec import chained_ec_op, ec_op, recover_y
+from starkware.cairo.common.ec_point import EcPoint
+
+func main{ec_op_ptr: EcOpBuiltin*}() {
+    // Generator of the STARK curve
+    let (g) = recover_y(874739451078007766457464989774322083649278607533249481151382481072868806602);
+    assert g.y = 152666792071518830868575557812948353041420400780739481342941381225525861407;
+
+    // 2 * G
+    let g_double = EcPoint(
+        3324833730090626974525872402899302150520188025637965566623476530814354734325,
+        3147007486456030910661996439995670279305852583596209647900952752170983517249,
+    );
+
+    // ec_op: G + 34 * (2 * G)
+    let (r) = ec_op(g, 34, g_double);
+    assert r = EcPoint(
+        2041935610366333495289549667855642145463145852024302889769951573607041975609,
+        1361043972710961609389903675264650945079696026169775927056696055085395965111,
+    );
+
+    // chained_ec_op: G + 34 * (2 * G) + 5 * G
+    let (m: felt*) = alloc();
+    assert m[0] = 34;
+    assert m[1] = 5;
+    let (q: EcPoint*) = alloc();
+    assert q[0] = g_double;
+    assert q[1] = g;
+    let (r) = chained_ec_op(g, m, q, 2);
+    assert r = EcPoint(
+        2561705565735490875365491926712585740465723512085576632538362570037859938633,
+        1759454756028909345917425599882931360470003235959119905656386523130018572637,
+    );
+    return ();
+}
@@ -0,0 +1,14 @@
+%builtins range_check
+
+func check_range{range_check_ptr}(num) {
+    // Check that 0 <= num < 2**64.
+    [range_check_ptr] = num;
+    assert [range_check_ptr + 1] = 2 ** 64 - 1 - num;
+    let range_check_ptr = range_check_ptr + 2;
+    return ();
+}
+
+func main{range_check_ptr}() {
+    check_range(7);
+    return ();
+}
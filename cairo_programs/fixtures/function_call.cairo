@@ -0,0 +1,10 @@
+func myfunc(a: felt) -> (r: felt) {
+    let b = a * 2;
+    return (r=b);
+}
+
+func main() {
+    let a = 1;
+    let b = myfunc(a);
+    return ();
+}
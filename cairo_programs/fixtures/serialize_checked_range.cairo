@@ -0,0 +1,17 @@
+%builtins output range_check
+
+from starkware.cairo.common.serialize import serialize_word
+
+func check_range{range_check_ptr}(num) -> (num: felt) {
+    // Check that 0 <= num < 2**64.
+    [range_check_ptr] = num;
+    assert [range_check_ptr + 1] = 2 ** 64 - 1 - num;
+    let range_check_ptr = range_check_ptr + 2;
+    return (num=num);
+}
+
+func main{output_ptr: felt*, range_check_ptr: felt}() {
+    let (num) = check_range(7);
+    serialize_word(num);
+    return ();
+}
@@ -0,0 +1,11 @@
+%builtins output
+
+from starkware.cairo.common.serialize import serialize_word
+
+func main{output_ptr: felt*}() {
+    let a = 1;
+    serialize_word(a);
+    let b = 17 * a;
+    serialize_word(b);
+    return ();
+}
@@ -0,0 +1,10 @@
+func main() {
+    alloc_locals;
+    tempvar x = 1000;
+    tempvar y = 2000;
+    tempvar sum = x + y;
+    tempvar product = sum * y;
+    local z = product + 5;
+    assert z = 6000005;
+    return ();
+}
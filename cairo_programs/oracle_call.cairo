@@ -0,0 +1,10 @@
+func square_plus_one(x: felt) -> felt {
+    return x * x + 1;
+}
+
+func main() {
+    let a = square_plus_one(7);
+    let b = square_plus_one(a);
+    assert b = 2501;
+    return ();
+}
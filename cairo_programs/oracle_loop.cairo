@@ -0,0 +1,18 @@
+func countdown(n: felt) -> felt {
+    if (n == 0) {
+        return 99;
+    }
+    return countdown(n - 1);
+}
+
+func main() {
+    tempvar n = 5;
+
+    loop:
+    tempvar n = n - 1;
+    jmp loop if n != 0;
+
+    let res = countdown(3);
+    assert res = 99;
+    return ();
+}
@@ -0,0 +1,23 @@
+%builtins range_check
+
+from starkware.cairo.common.dict import dict_read, dict_squash, dict_write
+from starkware.cairo.common.dict_access import DictAccess
+
+// Moves the value stored at key to key + 1, doubling it, and squashes the dictionary.
+// The dictionary is created and seeded by the caller, before the run.
+func update{range_check_ptr}(dict_start: DictAccess*, key: felt) -> (
+    squashed_dict_start: DictAccess*, squashed_dict_end: DictAccess*
+) {
+    let dict_ptr = dict_start;
+    let (value) = dict_read{dict_ptr=dict_ptr}(key=key);
+    dict_write{dict_ptr=dict_ptr}(key=key, new_value=0);
+    dict_write{dict_ptr=dict_ptr}(key=key + 1, new_value=value * 2);
+    let (squashed_dict_start, squashed_dict_end) = dict_squash(
+        dict_accesses_start=dict_start, dict_accesses_end=dict_ptr
+    );
+    return (squashed_dict_start=squashed_dict_start, squashed_dict_end=squashed_dict_end);
+}
+
+func main{range_check_ptr}() {
+    return ();
+}
@@ -0,0 +1,27 @@
+%builtins output pedersen range_check
+
+from starkware.cairo.common.cairo_builtins import HashBuiltin
+from starkware.cairo.common.hash import hash2
+from starkware.cairo.common.serialize import serialize_word
+from starkware.starknet.common.storage import normalize_address
+
+// starknet_keccak('balance')
+const BALANCE_SELECTOR = 0x206f38f7e4f15e87567361213c28f235cccdaa1d7fd34c9db1dfe9489c6a091;
+
+// Computes the address of balance[account], as generated for the storage variable
+// @storage_var func balance(account: felt) -> (res: felt)
+func balance_addr{pedersen_ptr: HashBuiltin*, range_check_ptr}(account: felt) -> (res: felt) {
+    let res = BALANCE_SELECTOR;
+    let (res) = hash2{hash_ptr=pedersen_ptr}(res, account);
+    let (res) = normalize_address(addr=res);
+    return (res=res);
+}
+
+func main{output_ptr: felt*, pedersen_ptr: HashBuiltin*, range_check_ptr}() {
+    let (addr) = balance_addr(1234);
+    serialize_word(addr);
+    // Addresses above ADDR_BOUND wrap around
+    let (addr) = normalize_address(addr=-1);
+    serialize_word(addr);
+    return ();
+}
@@ -1,4 +1,4 @@
-use cairo_vm::cairo_run::cairo_run;
+use cairo_vm::cairo_run::{cairo_run, CairoRunConfig};
 use cairo_vm::hint_processor::builtin_hint_processor::builtin_hint_processor_definition::{
     BuiltinHintProcessor, HintFunc,
 };
@@ -9,7 +9,6 @@ use cairo_vm::serde::deserialize_program::ApTracking;
 use cairo_vm::vm::{errors::hint_errors::HintError, vm_core::VirtualMachine};
 use num_bigint::BigInt;
 use std::collections::HashMap;
-use std::path::Path;
 use std::rc::Rc;
 
 // Create the function that implements the custom hint
@@ -36,14 +35,11 @@ fn main() {
     hint_processor.add_hint(String::from("print(ids.a)"), Rc::new(hint));
 
     //Run the cairo program
-    cairo_run(
-        Path::new("custom_hint.json"),
-        "main",
-        false,
-        false,
-        "all",
-        false,
-        &mut hint_processor,
-    )
-    .expect("Couldn't run program");
+    let program_content = std::fs::read("custom_hint.json").unwrap();
+    let cairo_run_config = CairoRunConfig {
+        layout: "all",
+        ..Default::default()
+    };
+    cairo_run(&program_content, &cairo_run_config, &mut hint_processor)
+        .expect("Couldn't run program");
 }
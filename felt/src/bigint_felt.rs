@@ -2,7 +2,7 @@ use lazy_static::lazy_static;
 use num_bigint::{BigInt, BigUint, ToBigInt, U64Digits};
 use num_integer::Integer;
 use num_traits::{Bounded, FromPrimitive, Num, One, Pow, Signed, ToPrimitive, Zero};
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use std::{
     convert::Into,
     fmt,
@@ -24,7 +24,7 @@ lazy_static! {
         .expect("Conversion BigUint -> BigInt can't fail");
 }
 
-#[derive(Eq, Hash, PartialEq, PartialOrd, Ord, Clone, Deserialize, Default)]
+#[derive(Eq, Hash, PartialEq, PartialOrd, Ord, Clone, Serialize, Deserialize, Default)]
 pub struct FeltBigInt(BigUint);
 
 macro_rules! from_integer {
@@ -730,6 +730,8 @@ impl fmt::Display for ParseFeltError {
     }
 }
 
+impl std::error::Error for ParseFeltError {}
+
 #[macro_export]
 macro_rules! felt_str {
     ($val: expr) => {
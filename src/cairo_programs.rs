@@ -0,0 +1,81 @@
+//! Compiled programs embedded in the test binary, so that unit tests don't depend on the
+//! working directory nor on running `make` beforehand.
+//! The fixtures in `cairo_programs/fixtures` are checked against their sources by
+//! `tests/check_fixtures.py` (`make check_fixtures`), while the ones in
+//! `cairo_programs/manually_compiled` were assembled by hand.
+
+use crate::types::program::Program;
+
+macro_rules! fixtures {
+    ($( $name:literal => $path:literal ),* $(,)?) => {
+        /// The names of all the available fixtures.
+        pub(crate) const FIXTURES: &[&str] = &[$( $name ),*];
+
+        /// Returns the compiled JSON of the fixture called `name`.
+        pub(crate) fn fixture(name: &str) -> &'static [u8] {
+            match name {
+                $( $name => include_bytes!(concat!("../cairo_programs/", $path)), )*
+                _ => panic!("Unknown fixture: {name}"),
+            }
+        }
+    };
+}
+
+fixtures! {
+    // no builtins, main calls myfunc(1), which returns 1 * 2
+    "function_call" => "fixtures/function_call.json",
+    // %builtins range_check, writes two cells to check that 7 < 2**64
+    "check_range" => "fixtures/check_range.json",
+    // %builtins output, writes 1 and 17 to the output
+    "serialize_word" => "fixtures/serialize_word.json",
+    // %builtins output range_check, checks that 7 < 2**64 and writes it to the output
+    "serialize_checked_range" => "fixtures/serialize_checked_range.json",
+    // %builtins range_check, uses more range check units than a small run has
+    "range_check_heavy" => "manually_compiled/range_check_heavy.json",
+    // check_range compiled with --proof_mode, main is called from __start__ and the run ends
+    // in the __end__ loop
+    "check_range_proof_mode" => "manually_compiled/check_range_proof_mode.json",
+    // struct.cairo, whose main only returns. Its data is copied from the memory dump that
+    // cairo-run wrote for it (`trace_memory/cairo_memory_struct`)
+    "struct" => "manually_compiled/struct.json",
+    // main writes 7 and returns, unless its hint jumps to the SKIP_TARGET constant, a label
+    // after which 42 is written instead
+    "label_constant" => "manually_compiled/label_constant.json",
+    // %builtins poseidon, asserts that the first output of the permutation of (x, y, 2) is the
+    // poseidon hash of x and y
+    "poseidon_hash" => "manually_compiled/poseidon_hash.json",
+    // main returns, sum(a, b) returns a + b and mul_first(array: felt*, n) returns array[0] * n
+    "entrypoint_args" => "manually_compiled/entrypoint_args.json",
+    // main allocates a segment with a hint and writes 5 to its cells 0 and 2, leaving a hole
+    "memory_holes" => "manually_compiled/memory_holes.json",
+    // main counts down from 499 to 0 in a loop of two instructions, which takes 1000 steps
+    "countdown" => "manually_compiled/countdown.json",
+}
+
+impl Program {
+    /// Loads the fixture called `name`, with `main` as entrypoint.
+    pub(crate) fn from_fixture(name: &str) -> Program {
+        Program::from_reader(fixture(name), Some("main"))
+            .unwrap_or_else(|error| panic!("Invalid fixture {name}: {error}"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn all_fixtures_can_be_loaded() {
+        for name in FIXTURES {
+            let program = Program::from_fixture(name);
+            assert!(program.main.is_some(), "{name} has no main");
+            assert!(!program.data.is_empty(), "{name} has no data");
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "Unknown fixture: fibonacci")]
+    fn unknown_fixture() {
+        fixture("fibonacci");
+    }
+}
@@ -5,53 +5,93 @@ use crate::{
         errors::{
             cairo_run_errors::CairoRunError, runner_errors::RunnerError, vm_exception::VmException,
         },
-        runners::cairo_runner::CairoRunner,
+        runners::cairo_runner::{CairoRunner, RunResources},
+        security::verify_secure_runner,
         trace::trace_entry::RelocatedTraceEntry,
         vm_core::VirtualMachine,
     },
 };
-use felt::{Felt, FeltOps};
+use felt::{Felt, FeltOps, PRIME_STR};
+use num_bigint::BigUint;
 use std::{
     fs::File,
-    io::{self, BufWriter, Error, ErrorKind, Write},
+    io::{self, BufWriter, Error, ErrorKind, Read, Write},
     path::Path,
 };
 
+pub struct CairoRunConfig<'a> {
+    pub entrypoint: &'a str,
+    pub trace_enabled: bool,
+    pub print_output: bool,
+    /// Relocates the memory at the end of the run. The memory is also relocated when the trace
+    /// is enabled, as it's relocated along with it.
+    pub relocate_mem: bool,
+    pub layout: &'a str,
+    pub proof_mode: bool,
+    /// Runs the security checks of `verify_secure_runner` after the run. Defaults to true
+    /// outside of proof mode.
+    pub secure_run: Option<bool>,
+}
+
+impl<'a> Default for CairoRunConfig<'a> {
+    fn default() -> Self {
+        CairoRunConfig {
+            entrypoint: "main",
+            trace_enabled: false,
+            print_output: false,
+            relocate_mem: false,
+            layout: "plain",
+            proof_mode: false,
+            secure_run: None,
+        }
+    }
+}
+
+/// Runs a compiled program from its json contents, returning the runner and the vm once the
+/// run ended: initialize -> run_until_pc -> end_run -> security checks -> relocation, as set by
+/// the config.
 pub fn cairo_run(
-    path: &Path,
-    entrypoint: &str,
-    trace_enabled: bool,
-    print_output: bool,
-    layout: &str,
-    proof_mode: bool,
+    program_content: &[u8],
+    cairo_run_config: &CairoRunConfig,
     hint_executor: &mut dyn HintProcessor,
-) -> Result<CairoRunner, CairoRunError> {
-    let program = match Program::from_file(path, Some(entrypoint)) {
-        Ok(program) => program,
-        Err(error) => return Err(CairoRunError::Program(error)),
-    };
-
-    let mut cairo_runner = CairoRunner::new(&program, layout, proof_mode)?;
-    let mut vm = VirtualMachine::new(trace_enabled);
+) -> Result<(CairoRunner, VirtualMachine), CairoRunError> {
+    let program = Program::from_reader(program_content, Some(cairo_run_config.entrypoint))?;
+
+    let secure_run = cairo_run_config
+        .secure_run
+        .unwrap_or(!cairo_run_config.proof_mode);
+
+    let mut cairo_runner = CairoRunner::new(
+        &program,
+        cairo_run_config.layout,
+        cairo_run_config.proof_mode,
+    )?;
+    let mut vm = VirtualMachine::new(cairo_run_config.trace_enabled);
     let end = cairo_runner.initialize(&mut vm)?;
 
     cairo_runner
-        .run_until_pc(end, &mut vm, hint_executor)
+        .run_until_pc(end, &mut RunResources::default(), &mut vm, hint_executor)
         .map_err(|err| VmException::from_vm_error(&cairo_runner, &vm, err))?;
     cairo_runner.end_run(false, false, &mut vm, hint_executor)?;
 
     vm.verify_auto_deductions()?;
-    if proof_mode {
-        cairo_runner.read_return_values(&vm)?;
+    // The stop pointers of the builtins are needed by the security checks
+    cairo_runner.read_return_values(&mut vm)?;
+    if cairo_run_config.proof_mode {
         cairo_runner.finalize_segments(&mut vm)?;
     }
-    cairo_runner.relocate(&mut vm)?;
+    if secure_run {
+        verify_secure_runner(&cairo_runner, true, &mut vm)?;
+    }
+    if cairo_run_config.relocate_mem || cairo_run_config.trace_enabled {
+        cairo_runner.relocate(&mut vm, None)?;
+    }
 
-    if print_output {
+    if cairo_run_config.print_output {
         write_output(&mut cairo_runner, &mut vm)?;
     }
 
-    Ok(cairo_runner)
+    Ok((cairo_runner, vm))
 }
 
 pub fn write_output(
@@ -76,22 +116,62 @@ pub fn write_binary_trace(
     let file = File::create(trace_file)?;
     let mut buffer = BufWriter::new(file);
 
+    write_binary_trace_to(relocated_trace, &mut buffer)?;
+    buffer.flush()
+}
+
+/// Writes a trace in the binary format of `write_binary_trace` to any writer, such as a buffer
+/// or a socket. The writer isn't flushed.
+pub fn write_binary_trace_to(
+    relocated_trace: &[RelocatedTraceEntry],
+    writer: &mut impl Write,
+) -> io::Result<()> {
     for (i, entry) in relocated_trace.iter().enumerate() {
-        bincode::serialize_into(&mut buffer, entry).map_err(|e| {
+        bincode::serialize_into(&mut *writer, entry).map_err(|e| {
             Error::new(
                 ErrorKind::Other,
                 format!("Failed to dump trace at position {i}, serialize error: {e}"),
             )
         })?;
     }
+    Ok(())
+}
 
-    buffer.flush()
+/// Reads a binary trace file as written by `write_binary_trace` (or by the original
+/// Cairo VM's `--trace_file` option).
+pub fn read_binary_trace(trace_file: &Path) -> io::Result<Vec<RelocatedTraceEntry>> {
+    const ENTRY_SIZE: usize = 3 * 8;
+
+    let mut bytes = Vec::new();
+    File::open(trace_file)?.read_to_end(&mut bytes)?;
+    if bytes.len() % ENTRY_SIZE != 0 {
+        return Err(Error::new(
+            ErrorKind::InvalidData,
+            format!(
+                "Trace file size {} is not a multiple of the entry size {ENTRY_SIZE}",
+                bytes.len()
+            ),
+        ));
+    }
+
+    bytes
+        .chunks_exact(ENTRY_SIZE)
+        .enumerate()
+        .map(|(i, entry)| {
+            bincode::deserialize(entry).map_err(|e| {
+                Error::new(
+                    ErrorKind::InvalidData,
+                    format!("Failed to read trace at position {i}, deserialize error: {e}"),
+                )
+            })
+        })
+        .collect()
 }
 
 /*
    Writes a binary memory file with the relocated memory as input.
    The memory pairs (address, value) are encoded and concatenated in the file
-   given by the path `memory_file`.
+   given by the path `memory_file`. The holes in the memory are skipped.
 
    * address -> 8-byte encoded
    * value -> 32-byte encoded
@@ -103,37 +183,103 @@ pub fn write_binary_memory(
     let file = File::create(memory_file)?;
     let mut buffer = BufWriter::new(file);
 
-    // initialize bytes vector that will be dumped to file
-    let mut memory_bytes: Vec<u8> = Vec::new();
-
     for (i, memory_cell) in relocated_memory.iter().enumerate() {
-        match memory_cell {
-            None => continue,
-            Some(unwrapped_memory_cell) => {
-                encode_relocated_memory(&mut memory_bytes, i, unwrapped_memory_cell);
-            }
+        if let Some(memory_cell) = memory_cell {
+            encode_relocated_memory(&mut buffer, i, memory_cell)?;
         }
     }
 
-    buffer.write_all(&memory_bytes)?;
     buffer.flush()
 }
 
+/// Highest address accepted by `read_binary_memory`. The relocated memory is allocated up to
+/// the highest address of the file, so that a file can't make the reader allocate more than
+/// `MAX_BINARY_MEMORY_ADDRESS + 1` cells.
+pub const MAX_BINARY_MEMORY_ADDRESS: usize = 1 << 28;
+
+/// Reads a binary memory file as written by `write_binary_memory` (or by the original
+/// Cairo VM's `--memory_file` option), returning the value of each address.
+/// Fails with `ErrorKind::InvalidData` if an address is above `MAX_BINARY_MEMORY_ADDRESS` or
+/// appears twice, or if a value isn't below the Cairo prime.
+pub fn read_binary_memory(memory_file: &Path) -> io::Result<Vec<Option<Felt>>> {
+    const ADDR_SIZE: usize = 8;
+    const CELL_SIZE: usize = ADDR_SIZE + 32;
+
+    let prime = BigUint::parse_bytes(PRIME_STR[2..].as_bytes(), 16).unwrap();
+
+    let mut bytes = Vec::new();
+    File::open(memory_file)?.read_to_end(&mut bytes)?;
+    if bytes.len() % CELL_SIZE != 0 {
+        return Err(Error::new(
+            ErrorKind::InvalidData,
+            format!(
+                "Memory file size {} is not a multiple of the cell size {CELL_SIZE}",
+                bytes.len()
+            ),
+        ));
+    }
+
+    let mut relocated_memory = Vec::new();
+    for cell in bytes.chunks_exact(CELL_SIZE) {
+        let (addr_bytes, value_bytes) = cell.split_at(ADDR_SIZE);
+        let mut addr = [0; ADDR_SIZE];
+        addr.copy_from_slice(addr_bytes);
+        let addr = u64::from_le_bytes(addr);
+        let len = usize::try_from(addr)
+            .ok()
+            .filter(|addr| *addr <= MAX_BINARY_MEMORY_ADDRESS)
+            .and_then(|addr| addr.checked_add(1))
+            .ok_or_else(|| {
+                Error::new(
+                    ErrorKind::InvalidData,
+                    format!(
+                        "Memory address {addr} is above the maximum address {MAX_BINARY_MEMORY_ADDRESS}"
+                    ),
+                )
+            })?;
+        let addr = len - 1;
+        let value = BigUint::from_bytes_le(value_bytes);
+        if value >= prime {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                format!(
+                    "Value {value} at memory address {addr} is not below the prime {PRIME_STR}"
+                ),
+            ));
+        }
+        if relocated_memory.len() < len {
+            relocated_memory.resize(len, None);
+        }
+        if relocated_memory[addr].is_some() {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                format!("Memory address {addr} appears twice"),
+            ));
+        }
+        relocated_memory[addr] = Some(Felt::from(value));
+    }
+    Ok(relocated_memory)
+}
+
 // encodes a given memory cell.
-fn encode_relocated_memory(memory_bytes: &mut Vec<u8>, addr: usize, memory_cell: &Felt) {
-    // append memory address to bytes vector using a 8 bytes representation
-    let mut addr_bytes = (addr as u64).to_le_bytes().to_vec();
-    memory_bytes.append(&mut addr_bytes);
+fn encode_relocated_memory(
+    writer: &mut impl Write,
+    addr: usize,
+    memory_cell: &Felt,
+) -> io::Result<()> {
+    // write the memory address using a 8 bytes representation
+    writer.write_all(&(addr as u64).to_le_bytes())?;
 
-    // append memory value at address using a 32 bytes representation
+    // write the memory value at address using a 32 bytes representation
     let mut value_bytes = memory_cell.to_signed_bytes_le();
     value_bytes.resize(32, 0);
-    memory_bytes.append(&mut value_bytes);
+    writer.write_all(&value_bytes)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::vm::errors::trace_errors::TraceError;
     use crate::{
         hint_processor::{
             builtin_hint_processor::builtin_hint_processor_definition::BuiltinHintProcessor,
@@ -142,7 +288,6 @@ mod tests {
         utils::test_utils::*,
     };
     use felt::NewFelt;
-    use std::io::Read;
 
     fn run_test_program(
         program_path: &Path,
@@ -158,7 +303,7 @@ mod tests {
             .map_err(CairoRunError::Runner)?;
 
         assert!(cairo_runner
-            .run_until_pc(end, &mut vm, hint_processor)
+            .run_until_pc(end, &mut RunResources::default(), &mut vm, hint_processor)
             .is_ok());
 
         Ok((cairo_runner, vm))
@@ -174,9 +319,14 @@ mod tests {
 
         let end = cairo_runner.initialize(&mut vm).unwrap();
         assert!(cairo_runner
-            .run_until_pc(end, &mut vm, &mut hint_processor)
+            .run_until_pc(
+                end,
+                &mut RunResources::default(),
+                &mut vm,
+                &mut hint_processor
+            )
             .is_ok());
-        assert!(cairo_runner.relocate(&mut vm).is_ok());
+        assert!(cairo_runner.relocate(&mut vm, None).is_ok());
         // `main` returns without doing nothing, but `not_main` sets `[ap]` to `1`
         // Memory location was found empirically and simply hardcoded
         assert_eq!(cairo_runner.relocated_memory[2], Some(Felt::new(123)));
@@ -205,14 +355,10 @@ mod tests {
         // a compiled program with no `data` key.
         // it should fail when the program is loaded.
         let mut hint_processor = BuiltinHintProcessor::new_empty();
-        let no_data_program_path = Path::new("cairo_programs/no_data_program.json");
+        let program_content = std::fs::read("cairo_programs/no_data_program.json").unwrap();
         assert!(cairo_run(
-            no_data_program_path,
-            "main",
-            false,
-            false,
-            "plain",
-            false,
+            &program_content,
+            &CairoRunConfig::default(),
             &mut hint_processor
         )
         .is_err());
@@ -223,14 +369,10 @@ mod tests {
         // a compiled program with no main scope
         // it should fail when trying to run initialize_main_entrypoint.
         let mut hint_processor = BuiltinHintProcessor::new_empty();
-        let no_main_program_path = Path::new("cairo_programs/no_main_program.json");
+        let program_content = std::fs::read("cairo_programs/no_main_program.json").unwrap();
         assert!(cairo_run(
-            no_main_program_path,
-            "main",
-            false,
-            false,
-            "plain",
-            false,
+            &program_content,
+            &CairoRunConfig::default(),
             &mut hint_processor
         )
         .is_err());
@@ -241,19 +383,82 @@ mod tests {
         // the program invalid_memory.json has an invalid memory cell and errors when trying to
         // decode the instruction.
         let mut hint_processor = BuiltinHintProcessor::new_empty();
-        let invalid_memory = Path::new("cairo_programs/invalid_memory.json");
+        let program_content = std::fs::read("cairo_programs/invalid_memory.json").unwrap();
         assert!(cairo_run(
-            invalid_memory,
-            "main",
-            false,
-            false,
-            "plain",
-            false,
+            &program_content,
+            &CairoRunConfig::default(),
             &mut hint_processor
         )
         .is_err());
     }
 
+    #[test]
+    fn cairo_run_with_config() {
+        let mut hint_processor = BuiltinHintProcessor::new_empty();
+        let program_content = std::fs::read("cairo_programs/struct.json").unwrap();
+        let cairo_run_config = CairoRunConfig {
+            trace_enabled: true,
+            relocate_mem: true,
+            ..Default::default()
+        };
+        let (cairo_runner, _vm) =
+            cairo_run(&program_content, &cairo_run_config, &mut hint_processor).unwrap();
+        assert!(!cairo_runner.relocated_memory.is_empty());
+
+        let mut buffer = Vec::new();
+        write_binary_trace_to(cairo_runner.get_relocated_trace().unwrap(), &mut buffer).unwrap();
+        assert_eq!(
+            buffer,
+            std::fs::read("cairo_programs/trace_memory/cairo_trace_struct").unwrap()
+        );
+    }
+
+    #[test]
+    fn cairo_run_without_relocation() {
+        let mut hint_processor = BuiltinHintProcessor::new_empty();
+        let program_content = std::fs::read("cairo_programs/struct.json").unwrap();
+        let (cairo_runner, vm) = cairo_run(
+            &program_content,
+            &CairoRunConfig::default(),
+            &mut hint_processor,
+        )
+        .unwrap();
+        assert!(cairo_runner.relocated_memory.is_empty());
+        assert_eq!(
+            cairo_runner.get_relocated_trace(),
+            Err(TraceError::TraceNotRelocated)
+        );
+        assert!(vm.trace.is_none());
+    }
+
+    #[test]
+    fn cairo_run_with_custom_entrypoint() {
+        let mut hint_processor = BuiltinHintProcessor::new_empty();
+        let program_content = std::fs::read("cairo_programs/not_main.json").unwrap();
+        let cairo_run_config = CairoRunConfig {
+            entrypoint: "not_main",
+            relocate_mem: true,
+            ..Default::default()
+        };
+        let (cairo_runner, _vm) =
+            cairo_run(&program_content, &cairo_run_config, &mut hint_processor).unwrap();
+        assert_eq!(cairo_runner.relocated_memory[2], Some(Felt::new(123)));
+    }
+
+    #[test]
+    fn cairo_run_with_invalid_layout() {
+        let mut hint_processor = BuiltinHintProcessor::new_empty();
+        let program_content = std::fs::read("cairo_programs/struct.json").unwrap();
+        let cairo_run_config = CairoRunConfig {
+            layout: "invalid layout",
+            ..Default::default()
+        };
+        assert!(matches!(
+            cairo_run(&program_content, &cairo_run_config, &mut hint_processor),
+            Err(CairoRunError::Runner(RunnerError::InvalidLayoutName(_)))
+        ));
+    }
+
     #[test]
     fn write_output_program() {
         let program_path = Path::new("cairo_programs/bitwise_output.json");
@@ -275,7 +480,7 @@ mod tests {
         let (mut cairo_runner, mut vm) = cairo_runner_result.unwrap();
 
         // relocate memory so we can dump it to file
-        assert!(cairo_runner.relocate(&mut vm).is_ok());
+        assert!(cairo_runner.relocate(&mut vm, None).is_ok());
         assert!(vm.trace.is_some());
         assert!(cairo_runner.relocated_trace.is_some());
 
@@ -288,6 +493,183 @@ mod tests {
         assert!(compare_files(cairo_rs_trace_path, expected_trace_path).is_ok());
     }
 
+    #[test]
+    fn write_binary_trace_to_buffer() {
+        let program = Program::from_fixture("struct");
+        let mut hint_processor = BuiltinHintProcessor::new_empty();
+        let mut cairo_runner = cairo_runner!(program);
+        let mut vm = vm!(true);
+        let end = cairo_runner.initialize(&mut vm).unwrap();
+        cairo_runner
+            .run_until_pc(
+                end,
+                &mut RunResources::default(),
+                &mut vm,
+                &mut hint_processor,
+            )
+            .unwrap();
+        assert_eq!(
+            cairo_runner.get_relocated_trace(),
+            Err(TraceError::TraceNotRelocated)
+        );
+        cairo_runner.relocate(&mut vm, None).unwrap();
+
+        let mut buffer = Vec::new();
+        write_binary_trace_to(cairo_runner.get_relocated_trace().unwrap(), &mut buffer).unwrap();
+        // Trace written by cairo-run for the same program
+        assert_eq!(
+            buffer,
+            std::fs::read("cairo_programs/trace_memory/cairo_trace_struct").unwrap()
+        );
+    }
+
+    #[test]
+    fn read_binary_trace_file() {
+        let trace_path = Path::new("cairo_programs/trace_memory/cairo_trace_struct");
+        assert_eq!(
+            read_binary_trace(trace_path).unwrap(),
+            vec![RelocatedTraceEntry {
+                ap: 4,
+                fp: 4,
+                pc: 1
+            }]
+        );
+    }
+
+    #[test]
+    fn read_binary_trace_roundtrip() {
+        let trace = vec![
+            RelocatedTraceEntry {
+                ap: 14,
+                fp: 14,
+                pc: 1,
+            },
+            RelocatedTraceEntry {
+                ap: 15,
+                fp: 14,
+                pc: 3,
+            },
+        ];
+        let trace_path = std::env::temp_dir().join("cairo_rs_read_binary_trace_roundtrip.trace");
+        write_binary_trace(&trace, &trace_path).unwrap();
+        assert_eq!(read_binary_trace(&trace_path).unwrap(), trace);
+        std::fs::remove_file(trace_path).unwrap();
+    }
+
+    #[test]
+    fn read_binary_trace_truncated_file() {
+        let trace_path = std::env::temp_dir().join("cairo_rs_read_binary_trace_truncated.trace");
+        std::fs::write(&trace_path, [0; 30]).unwrap();
+        assert_eq!(
+            read_binary_trace(&trace_path).unwrap_err().kind(),
+            ErrorKind::InvalidData
+        );
+        std::fs::remove_file(trace_path).unwrap();
+    }
+
+    #[test]
+    fn read_binary_memory_roundtrip() {
+        let memory = vec![
+            None,
+            Some(Felt::new(0x480680017fff8000_u64)),
+            None,
+            Some(Felt::new(-1)),
+        ];
+        let memory_path = std::env::temp_dir().join("cairo_rs_read_binary_memory_roundtrip.memory");
+        write_binary_memory(&memory, &memory_path).unwrap();
+        assert_eq!(read_binary_memory(&memory_path).unwrap(), memory);
+        std::fs::remove_file(memory_path).unwrap();
+    }
+
+    fn read_binary_memory_cells(
+        name: &str,
+        cells: &[(u64, [u8; 32])],
+    ) -> io::Result<Vec<Option<Felt>>> {
+        let mut bytes = Vec::new();
+        for (addr, value) in cells {
+            bytes.extend_from_slice(&addr.to_le_bytes());
+            bytes.extend_from_slice(value);
+        }
+        let memory_path = std::env::temp_dir().join(name);
+        std::fs::write(&memory_path, bytes).unwrap();
+        let memory = read_binary_memory(&memory_path);
+        std::fs::remove_file(memory_path).unwrap();
+        memory
+    }
+
+    #[test]
+    fn read_binary_memory_address_above_maximum() {
+        let memory = read_binary_memory_cells(
+            "cairo_rs_read_binary_memory_address_above_maximum.memory",
+            &[(MAX_BINARY_MEMORY_ADDRESS as u64 + 1, [0; 32])],
+        );
+        assert_eq!(memory.unwrap_err().kind(), ErrorKind::InvalidData);
+        let memory = read_binary_memory_cells(
+            "cairo_rs_read_binary_memory_address_overflow.memory",
+            &[(u64::MAX, [0; 32])],
+        );
+        assert_eq!(memory.unwrap_err().kind(), ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn read_binary_memory_duplicate_address() {
+        let memory = read_binary_memory_cells(
+            "cairo_rs_read_binary_memory_duplicate_address.memory",
+            &[(1, [1; 32]), (2, [0; 32]), (1, [1; 32])],
+        );
+        assert_eq!(memory.unwrap_err().kind(), ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn read_binary_memory_value_not_below_prime() {
+        // The prime, 2^251 + 17 * 2^192 + 1, in little endian
+        let mut prime = [0; 32];
+        prime[0] = 1;
+        prime[24] = 17;
+        prime[31] = 0x08;
+        let mut below_prime = prime;
+        below_prime[0] = 0;
+        assert_eq!(
+            read_binary_memory_cells(
+                "cairo_rs_read_binary_memory_value_below_prime.memory",
+                &[(1, below_prime)],
+            )
+            .unwrap(),
+            vec![None, Some(Felt::new(-1))]
+        );
+        let memory = read_binary_memory_cells(
+            "cairo_rs_read_binary_memory_value_not_below_prime.memory",
+            &[(1, prime)],
+        );
+        assert_eq!(memory.unwrap_err().kind(), ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn write_binary_memory_skips_holes() {
+        let memory = vec![None, None, Some(Felt::new(0x0102_u64)), None];
+        let memory_path =
+            std::env::temp_dir().join("cairo_rs_write_binary_memory_skips_holes.memory");
+        write_binary_memory(&memory, &memory_path).unwrap();
+
+        let mut expected = vec![0; 40];
+        expected[0] = 2;
+        expected[8] = 0x02;
+        expected[9] = 0x01;
+        assert_eq!(std::fs::read(&memory_path).unwrap(), expected);
+        std::fs::remove_file(memory_path).unwrap();
+    }
+
+    #[test]
+    fn read_binary_memory_truncated_file() {
+        let memory_path = std::env::temp_dir().join("cairo_rs_read_binary_memory_truncated.memory");
+        std::fs::write(&memory_path, [0; 50]).unwrap();
+        assert_eq!(
+            read_binary_memory(&memory_path).unwrap_err().kind(),
+            ErrorKind::InvalidData
+        );
+        std::fs::remove_file(memory_path).unwrap();
+    }
+
     #[test]
     fn write_binary_memory_file() {
         let program_path = Path::new("cairo_programs/struct.json");
@@ -300,7 +682,7 @@ mod tests {
         let (mut cairo_runner, mut vm) = cairo_runner_result.unwrap();
 
         // relocate memory so we can dump it to file
-        assert!(cairo_runner.relocate(&mut vm).is_ok());
+        assert!(cairo_runner.relocate(&mut vm, None).is_ok());
 
         // write cairo_rs vm memory file
         assert!(write_binary_memory(&cairo_runner.relocated_memory, cairo_rs_memory_path).is_ok());
@@ -318,7 +700,12 @@ mod tests {
         let mut vm = vm!();
         let end = cairo_runner.initialize(&mut vm).unwrap();
         assert!(cairo_runner
-            .run_until_pc(end, &mut vm, &mut hint_processor)
+            .run_until_pc(
+                end,
+                &mut RunResources::default(),
+                &mut vm,
+                &mut hint_processor
+            )
             .is_ok());
         assert!(vm.trace.is_none());
     }
@@ -2,17 +2,16 @@ use crate::{
     hint_processor::{
         builtin_hint_processor::{
             blake2s_hash::{blake2s_compress, IV},
-            hint_utils::{get_ptr_from_var_name, get_relocatable_from_var_name},
+            hint_utils::{
+                get_integer_from_var_name, get_ptr_from_var_name, get_relocatable_from_var_name,
+            },
         },
         hint_processor_definition::HintReference,
         hint_processor_utils::felt_to_u32,
     },
     serde::deserialize_program::ApTracking,
     types::relocatable::{MaybeRelocatable, Relocatable},
-    vm::{
-        errors::{hint_errors::HintError, vm_errors::VirtualMachineError},
-        vm_core::VirtualMachine,
-    },
+    vm::{errors::hint_errors::HintError, vm_core::VirtualMachine},
 };
 use felt::{Felt, NewFelt};
 use num_traits::ToPrimitive;
@@ -38,9 +37,6 @@ fn get_maybe_relocatable_array_from_u32(array: &Vec<u32>) -> Vec<MaybeRelocatabl
     new_array
 }
 
-fn get_maybe_relocatable_array_from_felt(array: &[Felt]) -> Vec<MaybeRelocatable> {
-    array.iter().map(MaybeRelocatable::from).collect()
-}
 /*Helper function for the Cairo blake2s() implementation.
 Computes the blake2s compress function and fills the value in the right position.
 output_ptr should point to the middle of an instance, right after initial_state, message, t, f,
@@ -55,8 +51,7 @@ fn compute_blake2s_func(vm: &mut VirtualMachine, output_rel: Relocatable) -> Res
     let new_state =
         get_maybe_relocatable_array_from_u32(&blake2s_compress(&h, &message, t, 0, f, 0));
     let output_ptr = MaybeRelocatable::RelocatableValue(output_rel);
-    vm.load_data(&output_ptr, &new_state)
-        .map_err(VirtualMachineError::MemoryError)?;
+    vm.load_data(&output_ptr, &new_state)?;
     Ok(())
 }
 
@@ -73,6 +68,28 @@ pub fn compute_blake2s(
     compute_blake2s_func(vm, output)
 }
 
+// cairo-lang defines the number of packed instances in either of these modules, depending on
+// the version
+const N_PACKED_INSTANCES: [&str; 2] = [
+    "starkware.cairo.common.cairo_blake2s.blake2s.N_PACKED_INSTANCES",
+    "starkware.cairo.common.cairo_blake2s.packed_blake2s.N_PACKED_INSTANCES",
+];
+// Used for programs that don't define the constant, as in the versions that hardcoded it
+const DEFAULT_N_PACKED_INSTANCES: usize = 7;
+
+fn get_n_packed_instances(constants: &HashMap<String, Felt>) -> Result<usize, HintError> {
+    match N_PACKED_INSTANCES
+        .iter()
+        .find_map(|name| constants.get(*name))
+    {
+        Some(n_packed_instances) => n_packed_instances
+            .to_usize()
+            .filter(|n| *n < 20)
+            .ok_or_else(|| HintError::InvalidNPackedInstances(n_packed_instances.clone())),
+        None => Ok(DEFAULT_N_PACKED_INSTANCES),
+    }
+}
+
 /* Implements Hint:
     # Add dummy pairs of input and output.
     from starkware.cairo.common.cairo_blake2s.blake2s_utils import IV, blake2s_compress
@@ -94,43 +111,69 @@ pub fn compute_blake2s(
     )
     padding = (modified_iv + message + [0, 0xffffffff] + output) * (_n_packed_instances - 1)
     segments.write_arg(ids.blake2s_ptr_end, padding)
+
+   Along with its variants which name the input size BLAKE2S_INPUT_CHUNK_SIZE_FELTS, or place
+   the message before the initial state (`message_first`).
 */
 pub fn finalize_blake2s(
     vm: &mut VirtualMachine,
     ids_data: &HashMap<String, HintReference>,
     ap_tracking: &ApTracking,
+    constants: &HashMap<String, Felt>,
+    message_first: bool,
 ) -> Result<(), HintError> {
-    const N_PACKED_INSTANCES: usize = 7;
+    let n_packed_instances = get_n_packed_instances(constants)?;
     let blake2s_ptr_end = get_ptr_from_var_name("blake2s_ptr_end", vm, ids_data, ap_tracking)?;
     let message: [u32; 16] = [0; 16];
     let mut modified_iv = IV;
     modified_iv[0] = IV[0] ^ 0x01010020;
     let output = blake2s_compress(&modified_iv, &message, 0, 0, 0xffffffff, 0);
-    let mut padding = modified_iv.to_vec();
-    padding.extend(message);
+    let mut padding = Vec::<u32>::new();
+    if message_first {
+        padding.extend(message);
+        padding.extend(modified_iv);
+    } else {
+        padding.extend(modified_iv);
+        padding.extend(message);
+    }
     padding.extend([0, 0xffffffff]);
     padding.extend(output);
-    let padding = padding.as_slice();
-    let mut full_padding = Vec::<u32>::with_capacity(padding.len() * N_PACKED_INSTANCES);
-    for _ in 0..N_PACKED_INSTANCES - 1 {
-        full_padding.extend_from_slice(padding);
-    }
+    let full_padding = padding.repeat(n_packed_instances.saturating_sub(1));
     let data = get_maybe_relocatable_array_from_u32(&full_padding);
-    vm.load_data(&MaybeRelocatable::RelocatableValue(blake2s_ptr_end), &data)
-        .map_err(VirtualMachineError::MemoryError)?;
+    vm.load_data(&MaybeRelocatable::RelocatableValue(blake2s_ptr_end), &data)?;
     Ok(())
 }
 
+// Splits a value into `n_words` 32-bit words, starting from the least significant one, or
+// from the most significant one if `bigend` is set
+fn split_into_u32_words(value: &Felt, n_words: u32, bigend: bool) -> Vec<MaybeRelocatable> {
+    const B: u32 = 32;
+    let mask = Felt::new(u32::MAX);
+    (0..n_words)
+        .map(|i| {
+            let shift = if bigend { B * (n_words - 1 - i) } else { B * i };
+            MaybeRelocatable::from((value >> shift) & &mask)
+        })
+        .collect()
+}
+
 /* Implements Hint:
     B = 32
     MASK = 2 ** 32 - 1
     segments.write_arg(ids.data, [(ids.low >> (B * i)) & MASK for i in range(4)])
     segments.write_arg(ids.data + 4, [(ids.high >> (B * i)) & MASK for i in range(4)])
+
+   And, if `bigend` is set:
+    B = 32
+    MASK = 2 ** 32 - 1
+    segments.write_arg(ids.data, [(ids.high >> (B * (3 - i))) & MASK for i in range(4)])
+    segments.write_arg(ids.data + 4, [(ids.low >> (B * (3 - i))) & MASK for i in range(4)])
 */
 pub fn blake2s_add_uint256(
     vm: &mut VirtualMachine,
     ids_data: &HashMap<String, HintReference>,
     ap_tracking: &ApTracking,
+    bigend: bool,
 ) -> Result<(), HintError> {
     //Get variables from ids
     let data_ptr = get_ptr_from_var_name("data", vm, ids_data, ap_tracking)?;
@@ -138,80 +181,34 @@ pub fn blake2s_add_uint256(
     let high_addr = get_relocatable_from_var_name("high", vm, ids_data, ap_tracking)?;
     let low = vm.get_integer(&low_addr)?.into_owned();
     let high = vm.get_integer(&high_addr)?.into_owned();
-    //Main logic
-    //Declare constant
-    const MASK: u32 = u32::MAX;
-    const B: u32 = 32;
-    //Convert MASK to felt
-    let mask = Felt::new(MASK);
-    //Build first batch of data
-    let mut inner_data = Vec::<Felt>::new();
-    for i in 0..4 {
-        inner_data.push((&low >> (B * i)) & &mask);
-    }
-    //Insert first batch of data
-    let data = get_maybe_relocatable_array_from_felt(&inner_data);
-    vm.load_data(&MaybeRelocatable::RelocatableValue(data_ptr), &data)
-        .map_err(VirtualMachineError::MemoryError)?;
-    //Build second batch of data
-    let mut inner_data = Vec::<Felt>::new();
-    for i in 0..4 {
-        inner_data.push((&high >> (B * i)) & &mask);
-    }
-    //Insert second batch of data
-    let data = get_maybe_relocatable_array_from_felt(&inner_data);
-    vm.load_data(
-        &MaybeRelocatable::RelocatableValue(data_ptr).add_usize(4),
-        &data,
-    )
-    .map_err(VirtualMachineError::MemoryError)?;
+    //The most significant half goes first in big endian
+    let (first, second) = if bigend { (high, low) } else { (low, high) };
+    let mut data = split_into_u32_words(&first, 4, bigend);
+    data.extend(split_into_u32_words(&second, 4, bigend));
+    vm.load_data(&MaybeRelocatable::RelocatableValue(data_ptr), &data)?;
     Ok(())
 }
 
 /* Implements Hint:
     B = 32
     MASK = 2 ** 32 - 1
-    segments.write_arg(ids.data, [(ids.high >> (B * (3 - i))) & MASK for i in range(4)])
-    segments.write_arg(ids.data + 4, [(ids.low >> (B * (3 - i))) & MASK for i in range(4)])
+    segments.write_arg(ids.data, [(ids.num >> (B * i)) & MASK for i in range(8)])
+
+   And, if `bigend` is set:
+    B = 32
+    MASK = 2 ** 32 - 1
+    segments.write_arg(ids.data, [(ids.num >> (B * (7 - i))) & MASK for i in range(8)])
 */
-pub fn blake2s_add_uint256_bigend(
+pub fn blake2s_add_felt(
     vm: &mut VirtualMachine,
     ids_data: &HashMap<String, HintReference>,
     ap_tracking: &ApTracking,
+    bigend: bool,
 ) -> Result<(), HintError> {
-    //Get variables from ids
     let data_ptr = get_ptr_from_var_name("data", vm, ids_data, ap_tracking)?;
-    let low_addr = get_relocatable_from_var_name("low", vm, ids_data, ap_tracking)?;
-    let high_addr = get_relocatable_from_var_name("high", vm, ids_data, ap_tracking)?;
-    let low = vm.get_integer(&low_addr)?.into_owned();
-    let high = vm.get_integer(&high_addr)?.into_owned();
-    //Main logic
-    //Declare constant
-    const MASK: u32 = u32::MAX;
-    const B: u32 = 32;
-    //Convert MASK to felt
-    let mask = Felt::new(MASK);
-    //Build first batch of data
-    let mut inner_data = Vec::<Felt>::new();
-    for i in 0..4 {
-        inner_data.push((&high >> (B * (3 - i))) & &mask);
-    }
-    //Insert first batch of data
-    let data = get_maybe_relocatable_array_from_felt(&inner_data);
-    vm.load_data(&MaybeRelocatable::RelocatableValue(data_ptr), &data)
-        .map_err(VirtualMachineError::MemoryError)?;
-    //Build second batch of data
-    let mut inner_data = Vec::<Felt>::new();
-    for i in 0..4 {
-        inner_data.push((&low >> (B * (3 - i))) & &mask);
-    }
-    //Insert second batch of data
-    let data = get_maybe_relocatable_array_from_felt(&inner_data);
-    vm.load_data(
-        &MaybeRelocatable::RelocatableValue(data_ptr).add_usize(4),
-        &data,
-    )
-    .map_err(VirtualMachineError::MemoryError)?;
+    let num = get_integer_from_var_name("num", vm, ids_data, ap_tracking)?;
+    let data = split_into_u32_words(num.as_ref(), 8, bigend);
+    vm.load_data(&MaybeRelocatable::RelocatableValue(data_ptr), &data)?;
     Ok(())
 }
 
@@ -221,15 +218,19 @@ mod tests {
     use crate::{
         any_box,
         hint_processor::{
-            builtin_hint_processor::builtin_hint_processor_definition::{
-                BuiltinHintProcessor, HintProcessorData,
+            builtin_hint_processor::{
+                builtin_hint_processor_definition::{BuiltinHintProcessor, HintProcessorData},
+                hint_code,
             },
             hint_processor_definition::HintProcessor,
         },
         relocatable,
         types::exec_scope::ExecutionScopes,
         utils::test_utils::*,
-        vm::{errors::memory_errors::MemoryError, vm_memory::memory::Memory},
+        vm::{
+            errors::{memory_errors::MemoryError, vm_errors::VirtualMachineError},
+            vm_memory::memory::Memory,
+        },
     };
     use std::any::Any;
 
@@ -535,4 +536,222 @@ mod tests {
         ];
         assert_eq!(vm.memory.get(&MaybeRelocatable::from((2, 8))), Ok(None));
     }
+
+    // modified_iv and the output of blake2s_compress in the padding written by cairo-lang's
+    // finalize hint (the same words as in finalize_blake2s_valid)
+    const MODIFIED_IV: [u32; 8] = [
+        1795745351, 3144134277, 1013904242, 2773480762, 1359893119, 2600822924, 528734635,
+        1541459225,
+    ];
+    const FINALIZE_OUTPUT: [u32; 8] = [
+        813310313, 2491453561, 3491828193, 2085238082, 1219908895, 514171180, 4245497115,
+        4193177630,
+    ];
+
+    /// Runs a finalize hint and returns the data it wrote after blake2s_ptr_end.
+    fn run_finalize_blake2s(
+        hint_code: &str,
+        constants: &HashMap<String, Felt>,
+    ) -> Result<Vec<u32>, HintError> {
+        let mut vm = vm!();
+        vm.run_context.fp = 1;
+        vm.memory = memory![((1, 0), (2, 0))];
+        add_segments!(vm, 1);
+        let ids_data = ids_data!["blake2s_ptr_end"];
+        run_hint!(vm, ids_data, hint_code, exec_scopes_ref!(), constants)?;
+        let size = vm.memory.data[2].len();
+        Ok(vm
+            .memory
+            .get_integer_range(&relocatable!(2, 0), size)
+            .unwrap()
+            .iter()
+            .map(|word| word.to_u32().unwrap())
+            .collect())
+    }
+
+    #[test]
+    fn finalize_blake2s_versions_write_the_same_padding() {
+        // padding = (modified_iv + message + [0, 0xffffffff] + output) * (N_PACKED_INSTANCES - 1)
+        let instance = [
+            &MODIFIED_IV[..],
+            &[0; 16],
+            &[0, 0xffffffff],
+            &FINALIZE_OUTPUT,
+        ]
+        .concat();
+        let expected = instance.repeat(6);
+        assert_eq!(
+            run_finalize_blake2s(hint_code::BLAKE2S_FINALIZE, &HashMap::new()),
+            Ok(expected.clone())
+        );
+        assert_eq!(
+            run_finalize_blake2s(hint_code::BLAKE2S_FINALIZE_V2, &HashMap::new()),
+            Ok(expected)
+        );
+    }
+
+    #[test]
+    fn finalize_blake2s_message_first() {
+        // padding = (message + modified_iv + [0, 0xffffffff] + output) * (N_PACKED_INSTANCES - 1)
+        let instance = [
+            &[0; 16],
+            &MODIFIED_IV[..],
+            &[0, 0xffffffff],
+            &FINALIZE_OUTPUT,
+        ]
+        .concat();
+        assert_eq!(
+            run_finalize_blake2s(hint_code::BLAKE2S_FINALIZE_V3, &HashMap::new()),
+            Ok(instance.repeat(6))
+        );
+    }
+
+    #[test]
+    fn finalize_blake2s_n_packed_instances_from_constants() {
+        let constants = HashMap::from([(
+            String::from("starkware.cairo.common.cairo_blake2s.packed_blake2s.N_PACKED_INSTANCES"),
+            Felt::new(8),
+        )]);
+        let instance = [
+            &MODIFIED_IV[..],
+            &[0; 16],
+            &[0, 0xffffffff],
+            &FINALIZE_OUTPUT,
+        ]
+        .concat();
+        assert_eq!(
+            run_finalize_blake2s(hint_code::BLAKE2S_FINALIZE, &constants),
+            Ok(instance.repeat(7))
+        );
+    }
+
+    #[test]
+    fn finalize_blake2s_invalid_n_packed_instances() {
+        let constants = HashMap::from([(
+            String::from("starkware.cairo.common.cairo_blake2s.blake2s.N_PACKED_INSTANCES"),
+            Felt::new(20),
+        )]);
+        assert_eq!(
+            run_finalize_blake2s(hint_code::BLAKE2S_FINALIZE, &constants),
+            Err(HintError::InvalidNPackedInstances(Felt::new(20)))
+        );
+    }
+
+    /// Runs an add_uint256 hint with the given ids and returns the data it wrote.
+    fn run_blake2s_add_uint256(hint_code: &str, high: u128, low: u128) -> Vec<Felt> {
+        let mut vm = vm!();
+        vm.run_context.fp = 3;
+        vm.memory = memory![((1, 0), (2, 0))];
+        vm.insert_value(&relocatable!(1, 1), Felt::new(high))
+            .unwrap();
+        vm.insert_value(&relocatable!(1, 2), Felt::new(low))
+            .unwrap();
+        vm.segments.add(&mut vm.memory);
+        let ids_data = ids_data!["data", "high", "low"];
+        assert_eq!(run_hint!(vm, ids_data, hint_code), Ok(()));
+        vm.get_integer_range(&relocatable!(2, 0), 8)
+            .unwrap()
+            .into_iter()
+            .map(Cow::into_owned)
+            .collect()
+    }
+
+    /// Runs an add_felt hint with the given ids and returns the data it wrote.
+    fn run_blake2s_add_felt(hint_code: &str, num: &Felt) -> Vec<Felt> {
+        let mut vm = vm!();
+        vm.run_context.fp = 2;
+        vm.memory = memory![((1, 0), (2, 0))];
+        vm.insert_value(&relocatable!(1, 1), num).unwrap();
+        vm.segments.add(&mut vm.memory);
+        let ids_data = ids_data!["data", "num"];
+        assert_eq!(run_hint!(vm, ids_data, hint_code), Ok(()));
+        vm.get_integer_range(&relocatable!(2, 0), 8)
+            .unwrap()
+            .into_iter()
+            .map(Cow::into_owned)
+            .collect()
+    }
+
+    // high and low halves of the inputs of the add_uint256 and add_felt tests below. The expected
+    // words were computed by running the Python code of each hint on them
+    const HIGH: u128 = 0x0123456789abcdeffedcba9876543210;
+    const LOW: u128 = 0xdeadbeefcafebabe0011223344556677;
+
+    #[test]
+    fn blake2s_add_uint256_versions_write_the_same_data() {
+        let expected = [
+            0x44556677_u32,
+            0x112233,
+            0xcafebabe,
+            0xdeadbeef,
+            0x76543210,
+            0xfedcba98,
+            0x89abcdef,
+            0x1234567,
+        ]
+        .map(Felt::new)
+        .to_vec();
+        assert_eq!(
+            run_blake2s_add_uint256(hint_code::BLAKE2S_ADD_UINT256, HIGH, LOW),
+            expected
+        );
+        assert_eq!(
+            run_blake2s_add_uint256(hint_code::BLAKE2S_ADD_UINT256_UNCLOSED, HIGH, LOW),
+            expected
+        );
+    }
+
+    #[test]
+    fn blake2s_add_uint256_bigend() {
+        assert_eq!(
+            run_blake2s_add_uint256(hint_code::BLAKE2S_ADD_UINT256_BIGEND, HIGH, LOW),
+            [
+                0x1234567_u32,
+                0x89abcdef,
+                0xfedcba98,
+                0x76543210,
+                0xdeadbeef,
+                0xcafebabe,
+                0x112233,
+                0x44556677
+            ]
+            .map(Felt::new)
+            .to_vec()
+        );
+    }
+
+    #[test]
+    fn blake2s_add_felt_valid() {
+        let num = (Felt::new(HIGH) << 128_u32) + Felt::new(LOW);
+        assert_eq!(
+            run_blake2s_add_felt(hint_code::BLAKE2S_ADD_FELT, &num),
+            [
+                0x44556677_u32,
+                0x112233,
+                0xcafebabe,
+                0xdeadbeef,
+                0x76543210,
+                0xfedcba98,
+                0x89abcdef,
+                0x1234567
+            ]
+            .map(Felt::new)
+            .to_vec()
+        );
+        assert_eq!(
+            run_blake2s_add_felt(hint_code::BLAKE2S_ADD_FELT_BIGEND, &num),
+            [
+                0x1234567_u32,
+                0x89abcdef,
+                0xfedcba98,
+                0x76543210,
+                0xdeadbeef,
+                0xcafebabe,
+                0x112233,
+                0x44556677
+            ]
+            .map(Felt::new)
+            .to_vec()
+        );
+    }
 }
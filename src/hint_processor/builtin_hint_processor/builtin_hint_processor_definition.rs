@@ -2,7 +2,7 @@ use crate::{
     hint_processor::{
         builtin_hint_processor::{
             blake2s_utils::{
-                blake2s_add_uint256, blake2s_add_uint256_bigend, compute_blake2s, finalize_blake2s,
+                blake2s_add_felt, blake2s_add_uint256, compute_blake2s, finalize_blake2s,
             },
             cairo_keccak::keccak_hints::{
                 block_permutation, cairo_keccak_finalize, compare_bytes_in_word_nondet,
@@ -12,7 +12,12 @@ use crate::{
                 default_dict_new, dict_new, dict_read, dict_squash_copy_dict,
                 dict_squash_update_ptr, dict_update, dict_write,
             },
+            ec_utils::{
+                chained_ec_op_random_ec_point_hint, ec_add_slope_hint, ec_double_slope_hint,
+                random_ec_point_hint, recover_y_hint,
+            },
             find_element_hint::{find_element, search_sorted_lower},
+            hint_cache::HintCache,
             hint_code,
             keccak_utils::{unsafe_keccak, unsafe_keccak_finalize},
             math_utils::*,
@@ -44,6 +49,7 @@ use crate::{
                 squash_dict_inner_next_key, squash_dict_inner_skip_loop,
                 squash_dict_inner_used_accesses_assert,
             },
+            starknet_hint_utils::{is_250_bits, is_addr_bounded},
             uint256_utils::{
                 split_64, uint256_add, uint256_signed_nn, uint256_sqrt, uint256_unsigned_div_rem,
             },
@@ -55,11 +61,130 @@ use crate::{
         hint_processor_definition::{HintProcessor, HintReference},
     },
     serde::deserialize_program::ApTracking,
-    types::exec_scope::ExecutionScopes,
-    vm::{errors::hint_errors::HintError, vm_core::VirtualMachine},
+    types::{exec_scope::ExecutionScopes, program::Program},
+    vm::{
+        errors::hint_errors::HintError, runners::cairo_runner::RunResources,
+        vm_core::VirtualMachine,
+    },
 };
 use felt::Felt;
-use std::{any::Any, collections::HashMap, rc::Rc};
+use std::{
+    any::Any,
+    collections::{HashMap, HashSet},
+    rc::Rc,
+};
+
+#[cfg(feature = "hint-profiling")]
+use crate::hint_processor::builtin_hint_processor::hint_profiling::{self, HintProfile};
+#[cfg(feature = "hint-profiling")]
+use std::time::Instant;
+
+// The code of every hint implemented by execute_hint_uncached, to find the unknown hints of a
+// program without running it
+const BUILTIN_HINT_CODES: [&str; 102] = [
+    hint_code::ADD_SEGMENT,
+    hint_code::IS_NN,
+    hint_code::IS_NN_OUT_OF_RANGE,
+    hint_code::ASSERT_LE_FELT,
+    hint_code::ASSERT_LE_FELT_EXCLUDED_2,
+    hint_code::ASSERT_LE_FELT_EXCLUDED_1,
+    hint_code::ASSERT_LE_FELT_EXCLUDED_0,
+    hint_code::IS_LE_FELT,
+    hint_code::ASSERT_250_BITS,
+    hint_code::IS_POSITIVE,
+    hint_code::SPLIT_INT_ASSERT_RANGE,
+    hint_code::SPLIT_INT,
+    hint_code::ASSERT_NOT_EQUAL,
+    hint_code::ASSERT_NN,
+    hint_code::SQRT,
+    hint_code::ASSERT_NOT_ZERO,
+    hint_code::VM_EXIT_SCOPE,
+    hint_code::MEMCPY_ENTER_SCOPE,
+    hint_code::MEMSET_ENTER_SCOPE,
+    hint_code::MEMCPY_CONTINUE_COPYING,
+    hint_code::MEMSET_CONTINUE_LOOP,
+    hint_code::SPLIT_FELT,
+    hint_code::UNSIGNED_DIV_REM,
+    hint_code::SIGNED_DIV_REM,
+    hint_code::ASSERT_LT_FELT,
+    hint_code::FIND_ELEMENT,
+    hint_code::SEARCH_SORTED_LOWER,
+    hint_code::POW,
+    hint_code::SET_ADD,
+    hint_code::DICT_NEW,
+    hint_code::DICT_READ,
+    hint_code::DICT_WRITE,
+    hint_code::DEFAULT_DICT_NEW,
+    hint_code::SQUASH_DICT_INNER_FIRST_ITERATION,
+    hint_code::USORT_ENTER_SCOPE,
+    hint_code::USORT_BODY,
+    hint_code::USORT_VERIFY,
+    hint_code::USORT_VERIFY_MULTIPLICITY_ASSERT,
+    hint_code::USORT_VERIFY_MULTIPLICITY_BODY,
+    hint_code::BLAKE2S_COMPUTE,
+    hint_code::VERIFY_ZERO,
+    hint_code::NONDET_BIGINT3,
+    hint_code::REDUCE,
+    hint_code::BLAKE2S_FINALIZE,
+    hint_code::BLAKE2S_FINALIZE_V2,
+    hint_code::BLAKE2S_FINALIZE_V3,
+    hint_code::BLAKE2S_ADD_UINT256,
+    hint_code::BLAKE2S_ADD_UINT256_UNCLOSED,
+    hint_code::BLAKE2S_ADD_UINT256_BIGEND,
+    hint_code::BLAKE2S_ADD_FELT,
+    hint_code::BLAKE2S_ADD_FELT_BIGEND,
+    hint_code::UNSAFE_KECCAK,
+    hint_code::UNSAFE_KECCAK_FINALIZE,
+    hint_code::SQUASH_DICT_INNER_SKIP_LOOP,
+    hint_code::SQUASH_DICT_INNER_CHECK_ACCESS_INDEX,
+    hint_code::SQUASH_DICT_INNER_CONTINUE_LOOP,
+    hint_code::SQUASH_DICT_INNER_ASSERT_LEN_KEYS,
+    hint_code::SQUASH_DICT_INNER_LEN_ASSERT,
+    hint_code::SQUASH_DICT_INNER_USED_ACCESSES_ASSERT,
+    hint_code::SQUASH_DICT_INNER_NEXT_KEY,
+    hint_code::SQUASH_DICT,
+    hint_code::VM_ENTER_SCOPE,
+    hint_code::DICT_UPDATE,
+    hint_code::DICT_SQUASH_COPY_DICT,
+    hint_code::DICT_SQUASH_UPDATE_PTR,
+    hint_code::UINT256_ADD,
+    hint_code::SPLIT_64,
+    hint_code::UINT256_SQRT,
+    hint_code::UINT256_SIGNED_NN,
+    hint_code::UINT256_UNSIGNED_DIV_REM,
+    hint_code::BIGINT_TO_UINT256,
+    hint_code::IS_ZERO_PACK,
+    hint_code::IS_ZERO_NONDET,
+    hint_code::IS_ZERO_ASSIGN_SCOPE_VARS,
+    hint_code::DIV_MOD_N_PACKED_DIVMOD,
+    hint_code::DIV_MOD_N_SAFE_DIV,
+    hint_code::GET_POINT_FROM_X,
+    hint_code::EC_NEGATE,
+    hint_code::EC_DOUBLE_SCOPE,
+    hint_code::COMPUTE_SLOPE,
+    hint_code::EC_DOUBLE_ASSIGN_NEW_X,
+    hint_code::EC_DOUBLE_ASSIGN_NEW_Y,
+    hint_code::KECCAK_WRITE_ARGS,
+    hint_code::COMPARE_BYTES_IN_WORD_NONDET,
+    hint_code::SHA256_MAIN,
+    hint_code::SHA256_INPUT,
+    hint_code::SHA256_FINALIZE,
+    hint_code::COMPARE_KECCAK_FULL_RATE_IN_BYTES_NONDET,
+    hint_code::BLOCK_PERMUTATION,
+    hint_code::CAIRO_KECCAK_FINALIZE,
+    hint_code::FAST_EC_ADD_ASSIGN_NEW_X,
+    hint_code::FAST_EC_ADD_ASSIGN_NEW_Y,
+    hint_code::EC_MUL_INNER,
+    hint_code::RELOCATE_SEGMENT,
+    hint_code::TEMPORARY_ARRAY,
+    hint_code::RANDOM_EC_POINT,
+    hint_code::CHAINED_EC_OP_RANDOM_EC_POINT,
+    hint_code::RECOVER_Y,
+    hint_code::STARK_EC_DOUBLE_SLOPE,
+    hint_code::STARK_EC_ADD_SLOPE,
+    hint_code::IS_250_BITS,
+    hint_code::IS_ADDR_BOUNDED,
+];
 
 pub struct HintProcessorData {
     pub code: String,
@@ -92,35 +217,119 @@ pub struct HintFunc(
 );
 pub struct BuiltinHintProcessor {
     pub extra_hints: HashMap<String, Rc<HintFunc>>,
+    ///When set, the cacheable hints are served from this cache, see `HintCache`
+    pub hint_cache: Option<HintCache>,
+    ///When set, the unknown hints whose code is in this set are skipped instead of failing the
+    ///run, to see how far a program gets without them. A skipped hint counts as executed, so the
+    ///hints after it at the same pc still run.
+    pub skip_unknown_hints: Option<HashSet<String>>,
+    #[cfg(feature = "hint-profiling")]
+    profile: HintProfile,
 }
 impl BuiltinHintProcessor {
     pub fn new_empty() -> Self {
         BuiltinHintProcessor {
             extra_hints: HashMap::new(),
+            hint_cache: None,
+            skip_unknown_hints: None,
+            #[cfg(feature = "hint-profiling")]
+            profile: HintProfile::default(),
         }
     }
 
     pub fn new(extra_hints: HashMap<String, Rc<HintFunc>>) -> Self {
-        BuiltinHintProcessor { extra_hints }
+        BuiltinHintProcessor {
+            extra_hints,
+            hint_cache: None,
+            skip_unknown_hints: None,
+            #[cfg(feature = "hint-profiling")]
+            profile: HintProfile::default(),
+        }
     }
 
     pub fn add_hint(&mut self, hint_code: String, hint_func: Rc<HintFunc>) {
         self.extra_hints.insert(hint_code, hint_func);
     }
+
+    ///Returns the pc and code of every hint of the program that is neither a builtin hint nor
+    ///added with `add_hint`, sorted by pc, so that all the missing hints of a program can be
+    ///found before running it.
+    pub fn audit_hints(&self, program: &Program) -> Vec<(usize, String)> {
+        let mut unknown_hints: Vec<(usize, String)> = program
+            .hints
+            .iter()
+            .flat_map(|(pc, hints)| hints.iter().map(move |hint| (*pc, &hint.code)))
+            .filter(|(_, code)| !self.is_known_hint(code))
+            .map(|(pc, code)| (pc, code.clone()))
+            .collect();
+        // The hints of a pc are kept in program order
+        unknown_hints.sort_by_key(|(pc, _)| *pc);
+        unknown_hints
+    }
+
+    fn is_known_hint(&self, code: &str) -> bool {
+        self.extra_hints.contains_key(code) || BUILTIN_HINT_CODES.contains(&code)
+    }
+
+    ///Returns the execution metrics of the hints executed so far, keyed by hint code (for the
+    ///hints added with `add_hint`, the code they were added with).
+    #[cfg(feature = "hint-profiling")]
+    pub fn get_profile(&self) -> &HintProfile {
+        &self.profile
+    }
 }
 
 impl HintProcessor for BuiltinHintProcessor {
+    // The result is only bound to record the profile once the hint has run
+    #[cfg_attr(not(feature = "hint-profiling"), allow(clippy::let_and_return))]
     fn execute_hint(
         &mut self,
         vm: &mut VirtualMachine,
         exec_scopes: &mut ExecutionScopes,
         hint_data: &Box<dyn Any>,
         constants: &HashMap<String, Felt>,
+        _run_resources: &mut RunResources,
     ) -> Result<(), HintError> {
         let hint_data = hint_data
             .downcast_ref::<HintProcessorData>()
             .ok_or(HintError::WrongHintData)?;
 
+        #[cfg(feature = "hint-profiling")]
+        let (start_time, start_allocated_bytes) =
+            (Instant::now(), hint_profiling::allocated_bytes());
+
+        let result = match self.hint_cache.take() {
+            Some(mut cache) if cache.is_cacheable(&hint_data.code) => {
+                let result = cache.execute(vm, hint_data, |vm| {
+                    self.execute_hint_uncached(vm, exec_scopes, hint_data, constants)
+                });
+                self.hint_cache = Some(cache);
+                result
+            }
+            cache => {
+                self.hint_cache = cache;
+                self.execute_hint_uncached(vm, exec_scopes, hint_data, constants)
+            }
+        };
+
+        #[cfg(feature = "hint-profiling")]
+        self.profile.record(
+            &hint_data.code,
+            start_time.elapsed(),
+            hint_profiling::allocated_bytes() - start_allocated_bytes,
+        );
+        result
+    }
+}
+
+impl BuiltinHintProcessor {
+    fn execute_hint_uncached(
+        &mut self,
+        vm: &mut VirtualMachine,
+        exec_scopes: &mut ExecutionScopes,
+        hint_data: &HintProcessorData,
+        constants: &HashMap<String, Felt>,
+    ) -> Result<(), HintError> {
         if let Some(hint_func) = self.extra_hints.get(&hint_data.code) {
             return hint_func.0(
                 vm,
@@ -149,7 +358,7 @@ impl HintProcessor for BuiltinHintProcessor {
             hint_code::ASSERT_LE_FELT_EXCLUDED_0 => assert_le_felt_excluded_0(vm, exec_scopes),
             hint_code::IS_LE_FELT => is_le_felt(vm, &hint_data.ids_data, &hint_data.ap_tracking),
             hint_code::ASSERT_250_BITS => {
-                assert_250_bit(vm, &hint_data.ids_data, &hint_data.ap_tracking)
+                assert_250_bit(vm, &hint_data.ids_data, &hint_data.ap_tracking, constants)
             }
             hint_code::IS_POSITIVE => is_positive(vm, &hint_data.ids_data, &hint_data.ap_tracking),
             hint_code::SPLIT_INT_ASSERT_RANGE => {
@@ -180,7 +389,9 @@ impl HintProcessor for BuiltinHintProcessor {
             hint_code::MEMSET_CONTINUE_LOOP => {
                 memset_continue_loop(vm, exec_scopes, &hint_data.ids_data, &hint_data.ap_tracking)
             }
-            hint_code::SPLIT_FELT => split_felt(vm, &hint_data.ids_data, &hint_data.ap_tracking),
+            hint_code::SPLIT_FELT => {
+                split_felt(vm, &hint_data.ids_data, &hint_data.ap_tracking, constants)
+            }
             hint_code::UNSIGNED_DIV_REM => {
                 unsigned_div_rem(vm, &hint_data.ids_data, &hint_data.ap_tracking)
             }
@@ -248,14 +459,31 @@ impl HintProcessor for BuiltinHintProcessor {
                 &hint_data.ap_tracking,
                 constants,
             ),
-            hint_code::BLAKE2S_FINALIZE => {
-                finalize_blake2s(vm, &hint_data.ids_data, &hint_data.ap_tracking)
-            }
-            hint_code::BLAKE2S_ADD_UINT256 => {
-                blake2s_add_uint256(vm, &hint_data.ids_data, &hint_data.ap_tracking)
+            hint_code::BLAKE2S_FINALIZE | hint_code::BLAKE2S_FINALIZE_V2 => finalize_blake2s(
+                vm,
+                &hint_data.ids_data,
+                &hint_data.ap_tracking,
+                constants,
+                false,
+            ),
+            hint_code::BLAKE2S_FINALIZE_V3 => finalize_blake2s(
+                vm,
+                &hint_data.ids_data,
+                &hint_data.ap_tracking,
+                constants,
+                true,
+            ),
+            hint_code::BLAKE2S_ADD_UINT256 | hint_code::BLAKE2S_ADD_UINT256_UNCLOSED => {
+                blake2s_add_uint256(vm, &hint_data.ids_data, &hint_data.ap_tracking, false)
             }
             hint_code::BLAKE2S_ADD_UINT256_BIGEND => {
-                blake2s_add_uint256_bigend(vm, &hint_data.ids_data, &hint_data.ap_tracking)
+                blake2s_add_uint256(vm, &hint_data.ids_data, &hint_data.ap_tracking, true)
+            }
+            hint_code::BLAKE2S_ADD_FELT => {
+                blake2s_add_felt(vm, &hint_data.ids_data, &hint_data.ap_tracking, false)
+            }
+            hint_code::BLAKE2S_ADD_FELT_BIGEND => {
+                blake2s_add_felt(vm, &hint_data.ids_data, &hint_data.ap_tracking, true)
             }
             hint_code::UNSAFE_KECCAK => {
                 unsafe_keccak(vm, exec_scopes, &hint_data.ids_data, &hint_data.ap_tracking)
@@ -430,7 +658,30 @@ impl HintProcessor for BuiltinHintProcessor {
             hint_code::TEMPORARY_ARRAY => {
                 temporary_array(vm, &hint_data.ids_data, &hint_data.ap_tracking)
             }
-            code => Err(HintError::UnknownHint(code.to_string())),
+            hint_code::RANDOM_EC_POINT => {
+                random_ec_point_hint(vm, &hint_data.ids_data, &hint_data.ap_tracking)
+            }
+            hint_code::CHAINED_EC_OP_RANDOM_EC_POINT => chained_ec_op_random_ec_point_hint(
+                vm,
+                exec_scopes,
+                &hint_data.ids_data,
+                &hint_data.ap_tracking,
+            ),
+            hint_code::RECOVER_Y => recover_y_hint(vm, &hint_data.ids_data, &hint_data.ap_tracking),
+            hint_code::STARK_EC_DOUBLE_SLOPE => {
+                ec_double_slope_hint(vm, &hint_data.ids_data, &hint_data.ap_tracking)
+            }
+            hint_code::STARK_EC_ADD_SLOPE => {
+                ec_add_slope_hint(vm, &hint_data.ids_data, &hint_data.ap_tracking)
+            }
+            hint_code::IS_250_BITS => is_250_bits(vm, &hint_data.ids_data, &hint_data.ap_tracking),
+            hint_code::IS_ADDR_BOUNDED => {
+                is_addr_bounded(vm, &hint_data.ids_data, &hint_data.ap_tracking, constants)
+            }
+            code => match &self.skip_unknown_hints {
+                Some(skipped_hints) if skipped_hints.contains(code) => Ok(()),
+                _ => Err(HintError::UnknownHint(code.to_string())),
+            },
         }
     }
 }
@@ -441,13 +692,18 @@ mod tests {
     use crate::{
         any_box,
         hint_processor::hint_processor_definition::HintProcessor,
-        types::{exec_scope::ExecutionScopes, relocatable::MaybeRelocatable},
+        serde::deserialize_program::{FlowTrackingData, HintParams},
+        types::{
+            exec_scope::ExecutionScopes,
+            relocatable::{MaybeRelocatable, Relocatable},
+        },
         utils::test_utils::*,
         vm::{
             errors::{
                 exec_scope_errors::ExecScopeError, memory_errors::MemoryError,
                 vm_errors::VirtualMachineError,
             },
+            runners::cairo_runner::CairoRunner,
             vm_core::VirtualMachine,
             vm_memory::memory::Memory,
         },
@@ -839,7 +1095,8 @@ mod tests {
                 &mut vm,
                 exec_scopes,
                 &any_box!(hint_data),
-                &HashMap::new()
+                &HashMap::new(),
+                &mut RunResources::default()
             ),
             Ok(())
         );
@@ -851,10 +1108,132 @@ mod tests {
                 &mut vm,
                 exec_scopes,
                 &any_box!(hint_data),
-                &HashMap::new()
+                &HashMap::new(),
+                &mut RunResources::default()
             ),
             Ok(())
         );
         assert_eq!(exec_scopes.data.len(), 3);
     }
+
+    fn hint_params(code: &str) -> HintParams {
+        HintParams {
+            code: code.to_string(),
+            accessible_scopes: Vec::new(),
+            flow_tracking_data: FlowTrackingData {
+                ap_tracking: ApTracking::new(),
+                reference_ids: HashMap::new(),
+            },
+        }
+    }
+
+    #[test]
+    fn audit_hints_reports_unknown_hints() {
+        let program = program!(
+            hints = HashMap::from([
+                (
+                    0,
+                    vec![
+                        hint_params(hint_code::ADD_SEGMENT),
+                        hint_params("memory[ap] = compute_secret()"),
+                    ]
+                ),
+                (4, vec![hint_params("enter_scope_custom_a")]),
+                (2, vec![hint_params("print(ids.x)")]),
+            ]),
+        );
+        let mut hint_processor = BuiltinHintProcessor::new_empty();
+        hint_processor.add_hint(
+            String::from("enter_scope_custom_a"),
+            Rc::new(HintFunc(Box::new(enter_scope))),
+        );
+        assert_eq!(
+            hint_processor.audit_hints(&program),
+            vec![
+                (0, String::from("memory[ap] = compute_secret()")),
+                (2, String::from("print(ids.x)")),
+            ]
+        );
+    }
+
+    #[test]
+    fn builtin_hint_codes_are_known() {
+        let mut hint_processor = BuiltinHintProcessor::new_empty();
+        for code in BUILTIN_HINT_CODES {
+            let mut vm = vm!();
+            let hint_data = any_box!(HintProcessorData::new_default(
+                code.to_string(),
+                HashMap::new()
+            ));
+            assert_ne!(
+                hint_processor.execute_hint(
+                    &mut vm,
+                    exec_scopes_ref!(),
+                    &hint_data,
+                    &HashMap::new(),
+                    &mut RunResources::default()
+                ),
+                Err(HintError::UnknownHint(code.to_string()))
+            );
+        }
+    }
+
+    #[test]
+    fn skip_unknown_hints_run() {
+        let secret_hint = "memory[ap + 1] = compute_secret()";
+        let print_hint = "print('reached')";
+        let program = program!(
+            data = vec![
+                mayberelocatable!(0x40780017fff7fff), // ap += 2
+                mayberelocatable!(2),
+                mayberelocatable!(0x400680017fff7fff), // [ap + (-1)] = 7
+                mayberelocatable!(7),
+                mayberelocatable!(0x208b7fff7fff7ffe), // ret
+            ],
+            hints = HashMap::from([
+                (
+                    0,
+                    vec![
+                        hint_params(secret_hint),
+                        hint_params(hint_code::ADD_SEGMENT)
+                    ]
+                ),
+                (2, vec![hint_params(print_hint)]),
+            ]),
+            main = Some(0),
+        );
+        let run = |hint_processor: &mut BuiltinHintProcessor| {
+            let mut cairo_runner = cairo_runner!(program);
+            let mut vm = vm!();
+            let end = cairo_runner.initialize(&mut vm).unwrap();
+            cairo_runner
+                .run_until_pc(end, &mut RunResources::default(), &mut vm, hint_processor)
+                .map(|_| vm)
+        };
+
+        let mut hint_processor = BuiltinHintProcessor::new_empty();
+        assert_eq!(
+            run(&mut hint_processor).err(),
+            Some(VirtualMachineError::Hint(
+                0,
+                Box::new(HintError::UnknownHint(secret_hint.to_string()))
+            ))
+        );
+
+        hint_processor.skip_unknown_hints = Some(HashSet::from([
+            secret_hint.to_string(),
+            print_hint.to_string(),
+        ]));
+        let vm = run(&mut hint_processor).unwrap();
+        // The hint after the skipped one still ran, while the cell of the skipped hint holds the
+        // value asserted by the program instead of the secret
+        assert_eq!(
+            vm.get_relocatable(&Relocatable::from((1, 2))),
+            Ok(Relocatable::from((
+                vm.segments.num_segments as isize - 1,
+                0
+            )))
+        );
+        check_memory![vm.memory, ((1, 3), 7)];
+    }
 }
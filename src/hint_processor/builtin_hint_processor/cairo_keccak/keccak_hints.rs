@@ -49,12 +49,10 @@ pub fn keccak_write_args(
     let high_args = [high & Felt::new(u64::MAX), high >> 64];
 
     let low_args: Vec<_> = low_args.into_iter().map(MaybeRelocatable::from).collect();
-    vm.write_arg(&inputs_ptr, &low_args)
-        .map_err(VirtualMachineError::MemoryError)?;
+    vm.write_arg(&inputs_ptr, &low_args)?;
 
     let high_args: Vec<_> = high_args.into_iter().map(MaybeRelocatable::from).collect();
-    vm.write_arg(&inputs_ptr.add(2_i32), &high_args)
-        .map_err(VirtualMachineError::MemoryError)?;
+    vm.write_arg(&inputs_ptr.add(2_i32), &high_args)?;
 
     Ok(())
 }
@@ -143,12 +141,10 @@ pub fn block_permutation(
     let keccak_ptr = get_ptr_from_var_name("keccak_ptr", vm, ids_data, ap_tracking)?;
 
     let keccak_state_size_felts = keccak_state_size_felts.to_usize().unwrap();
-    let values = vm
-        .get_range(
-            &MaybeRelocatable::RelocatableValue(keccak_ptr.sub_usize(keccak_state_size_felts)?),
-            keccak_state_size_felts,
-        )
-        .map_err(VirtualMachineError::MemoryError)?;
+    let values = vm.get_range(
+        &MaybeRelocatable::RelocatableValue(keccak_ptr.sub_usize(keccak_state_size_felts)?),
+        keccak_state_size_felts,
+    )?;
 
     let mut u64_values = maybe_reloc_vec_to_u64_array(&values)?
         .try_into()
@@ -160,8 +156,7 @@ pub fn block_permutation(
 
     let bigint_values = u64_array_to_mayberelocatable_vec(&u64_values);
 
-    vm.write_arg(&keccak_ptr, &bigint_values)
-        .map_err(VirtualMachineError::MemoryError)?;
+    vm.write_arg(&keccak_ptr, &bigint_values)?;
 
     Ok(())
 }
@@ -220,8 +215,7 @@ pub fn cairo_keccak_finalize(
 
     let keccak_ptr_end = get_ptr_from_var_name("keccak_ptr_end", vm, ids_data, ap_tracking)?;
 
-    vm.write_arg(&keccak_ptr_end, &padding)
-        .map_err(VirtualMachineError::MemoryError)?;
+    vm.write_arg(&keccak_ptr_end, &padding)?;
 
     Ok(())
 }
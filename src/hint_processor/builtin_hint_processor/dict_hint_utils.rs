@@ -267,6 +267,7 @@ mod tests {
         utils::test_utils::*,
         vm::{errors::memory_errors::MemoryError, vm_core::VirtualMachine},
     };
+    use num_bigint::BigInt;
     use std::collections::HashMap;
 
     #[test]
@@ -360,6 +361,45 @@ mod tests {
         check_dict_ptr!(&exec_scopes, 2, (2, 3));
     }
 
+    #[test]
+    fn run_dict_read_and_write_seeded_dict() {
+        let mut vm = vm!();
+        add_segments!(vm, 2);
+        let mut dict_manager = DictManager::new();
+        let dict_ptr = dict_manager
+            .new_dict_from(
+                &mut vm,
+                HashMap::from([(BigInt::from(5), MaybeRelocatable::from(12))]),
+            )
+            .unwrap();
+        let mut exec_scopes = ExecutionScopes::new();
+        exec_scopes.insert_dict_manager(Rc::new(RefCell::new(dict_manager)));
+        //value = dict_read(key=5)
+        vm.run_context.fp = 3;
+        vm.insert_value(&(1, 0).into(), 5).unwrap();
+        vm.insert_value(&(1, 2).into(), dict_ptr).unwrap();
+        let ids_data = ids_data!["key", "value", "dict_ptr"];
+        assert_eq!(
+            run_hint!(vm, ids_data, hint_code::DICT_READ, &mut exec_scopes),
+            Ok(())
+        );
+        check_memory![vm.memory, ((1, 1), 12)];
+        //dict_write(key=5, new_value=20)
+        vm.run_context.fp = 6;
+        vm.insert_value(&(1, 3).into(), 5).unwrap();
+        vm.insert_value(&(1, 4).into(), 20).unwrap();
+        vm.insert_value(&(1, 5).into(), dict_ptr + 3_usize)
+            .unwrap();
+        let ids_data = ids_data!["key", "new_value", "dict_ptr"];
+        assert_eq!(
+            run_hint!(vm, ids_data, hint_code::DICT_WRITE, &mut exec_scopes),
+            Ok(())
+        );
+        check_memory![vm.memory, ((2, 4), 12)];
+        check_dictionary![exec_scopes, 2, (5, 20)];
+        check_dict_ptr!(exec_scopes, 2, (2, 6));
+    }
+
     #[test]
     fn run_dict_read_invalid_key() {
         let hint_code = "dict_tracker = __dict_manager.get_tracker(ids.dict_ptr)\ndict_tracker.current_ptr += ids.DictAccess.SIZE\nids.value = dict_tracker.data[ids.key]";
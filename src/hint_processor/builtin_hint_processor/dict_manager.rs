@@ -1,5 +1,9 @@
 use std::collections::HashMap;
 
+use felt::Felt;
+use num_bigint::BigInt;
+use serde::{Deserialize, Serialize};
+
 use crate::{
     types::relocatable::{MaybeRelocatable, Relocatable},
     vm::{
@@ -10,14 +14,14 @@ use crate::{
     },
 };
 
-#[derive(PartialEq, Eq, Debug, Clone)]
+#[derive(PartialEq, Eq, Debug, Clone, Serialize, Deserialize)]
 ///Manages dictionaries in a Cairo program.
 ///Uses the segment index to associate the corresponding python dict with the Cairo dict.
 pub struct DictManager {
     pub trackers: HashMap<isize, DictTracker>,
 }
 
-#[derive(PartialEq, Eq, Debug, Clone)]
+#[derive(PartialEq, Eq, Debug, Clone, Serialize, Deserialize)]
 ///Tracks the python dict associated with a Cairo dict.
 pub struct DictTracker {
     //Dictionary.
@@ -26,10 +30,11 @@ pub struct DictTracker {
     pub current_ptr: Relocatable,
 }
 
-#[derive(PartialEq, Eq, Debug, Clone)]
+#[derive(PartialEq, Eq, Debug, Clone, Serialize, Deserialize)]
 pub enum Dictionary {
-    SimpleDictionary(HashMap<MaybeRelocatable, MaybeRelocatable>),
+    SimpleDictionary(#[serde(with = "dict_entries")] HashMap<MaybeRelocatable, MaybeRelocatable>),
     DefaultDictionary {
+        #[serde(with = "dict_entries")]
         dict: HashMap<MaybeRelocatable, MaybeRelocatable>,
         default_value: MaybeRelocatable,
     },
@@ -95,6 +100,21 @@ impl DictManager {
         Ok(MaybeRelocatable::RelocatableValue(base))
     }
 
+    /// Creates a new Cairo dictionary holding the given contents, like `new_dict` does for the
+    /// dict_new hint. Meant to seed a dictionary from Rust before the run; the dict manager can
+    /// then be handed to the hints with `ExecutionScopes::insert_dict_manager`.
+    pub fn new_dict_from(
+        &mut self,
+        vm: &mut VirtualMachine,
+        initial: HashMap<BigInt, MaybeRelocatable>,
+    ) -> Result<Relocatable, HintError> {
+        let initial_dict = initial
+            .into_iter()
+            .map(|(key, value)| (MaybeRelocatable::from(Felt::from(key)), value))
+            .collect();
+        Ok(self.new_dict(vm, initial_dict)?.get_relocatable()?)
+    }
+
     //Creates a new Cairo default dictionary
     pub fn new_default_dict(
         &mut self,
@@ -207,6 +227,33 @@ impl DictTracker {
     }
 }
 
+// The keys of a dictionary aren't strings, so dictionaries are serialized as lists of
+// (key, value) entries, sorted by key so that the output doesn't depend on the hash order.
+mod dict_entries {
+    use super::*;
+    use serde::{Deserializer, Serializer};
+    use std::cmp::Ordering;
+
+    pub(super) fn serialize<S: Serializer>(
+        dict: &HashMap<MaybeRelocatable, MaybeRelocatable>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        let mut entries: Vec<_> = dict.iter().collect();
+        entries.sort_by(|(a, _), (b, _)| a.partial_cmp(b).unwrap_or(Ordering::Equal));
+        entries.serialize(serializer)
+    }
+
+    pub(super) fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<HashMap<MaybeRelocatable, MaybeRelocatable>, D::Error> {
+        Ok(
+            Vec::<(MaybeRelocatable, MaybeRelocatable)>::deserialize(deserializer)?
+                .into_iter()
+                .collect(),
+        )
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -317,6 +364,34 @@ mod tests {
         assert_eq!(vm.segments.num_segments, 1);
     }
 
+    #[test]
+    fn dict_manager_new_dict_from() {
+        let mut dict_manager = DictManager::new();
+        let mut vm = vm!();
+        add_segments!(vm, 2);
+        let base = dict_manager.new_dict_from(
+            &mut vm,
+            HashMap::from([
+                (BigInt::from(5), MaybeRelocatable::from(7)),
+                (BigInt::from(-1), MaybeRelocatable::from((1, 0))),
+            ]),
+        );
+        assert_eq!(base, Ok(relocatable!(2, 0)));
+        assert_eq!(
+            dict_manager.trackers.get(&2),
+            Some(&DictTracker::new_with_initial(
+                &relocatable!(2, 0),
+                HashMap::from([
+                    (MaybeRelocatable::from(5), MaybeRelocatable::from(7)),
+                    (
+                        MaybeRelocatable::from(Felt::from(BigInt::from(-1))),
+                        MaybeRelocatable::from((1, 0))
+                    ),
+                ])
+            ))
+        );
+    }
+
     #[test]
     fn dict_manager_new_dict_empty_same_segment() {
         let mut dict_manager = DictManager::new();
@@ -371,4 +446,24 @@ mod tests {
             Some(&MaybeRelocatable::from(7))
         );
     }
+
+    #[test]
+    fn dict_manager_serde_round_trip() {
+        let mut default_dict =
+            DictTracker::new_default_dict(&relocatable!(2, 0), &MaybeRelocatable::from(7), None);
+        default_dict.insert_value(&MaybeRelocatable::from(3), &MaybeRelocatable::from((5, 1)));
+        default_dict.current_ptr = relocatable!(2, 3);
+        let mut simple_dict = DictTracker::new_empty(&relocatable!(3, 0));
+        simple_dict.insert_value(&MaybeRelocatable::from(9), &MaybeRelocatable::from(1));
+        simple_dict.insert_value(&MaybeRelocatable::from(4), &MaybeRelocatable::from(2));
+        let dict_manager = DictManager {
+            trackers: HashMap::from([(2, default_dict), (3, simple_dict)]),
+        };
+
+        let serialized = serde_json::to_string(&dict_manager).unwrap();
+        assert_eq!(
+            serde_json::from_str::<DictManager>(&serialized).unwrap(),
+            dict_manager
+        );
+    }
 }
@@ -0,0 +1,514 @@
+use crate::{
+    hint_processor::{
+        builtin_hint_processor::hint_utils::{
+            get_integer_from_var_name, get_ptr_from_var_name, get_relocatable_from_var_name,
+            insert_value_from_var_name,
+        },
+        hint_processor_definition::HintReference,
+    },
+    math_utils::{ec_double_slope, line_slope, sqrt_prime},
+    serde::deserialize_program::ApTracking,
+    types::exec_scope::ExecutionScopes,
+    vm::{errors::hint_errors::HintError, vm_core::VirtualMachine},
+};
+use felt::{Felt, FeltOps, PRIME_STR};
+use lazy_static::lazy_static;
+use num_bigint::{BigInt, BigUint};
+use num_integer::Integer;
+use num_traits::{Num, ToPrimitive, Zero};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+
+// Parameters of the STARK curve: y^2 = x^3 + ALPHA * x + BETA (mod FIELD_PRIME).
+// See starkware.crypto.signature.signature.
+const ALPHA: u32 = 1;
+lazy_static! {
+    static ref BETA: BigUint = BigUint::from_str_radix(
+        "6f21413efbe40de150e596d72f7a8c5609ad26c15c915c1f4cdfcb99cee9e89",
+        16
+    )
+    .unwrap();
+    static ref FIELD_PRIME: BigUint = BigUint::from_str_radix(&PRIME_STR[2..], 16).unwrap();
+}
+
+// Reads the (x, y) coordinates of the EcPoint stored in the given ids variable.
+fn get_point_from_var_name(
+    name: &str,
+    vm: &VirtualMachine,
+    ids_data: &HashMap<String, HintReference>,
+    ap_tracking: &ApTracking,
+) -> Result<(Felt, Felt), HintError> {
+    let point_addr = get_relocatable_from_var_name(name, vm, ids_data, ap_tracking)?;
+    Ok((
+        vm.get_integer(&point_addr)?.into_owned(),
+        vm.get_integer(&(point_addr + 1_usize))?.into_owned(),
+    ))
+}
+
+// Writes the (x, y) coordinates into the EcPoint stored in the given ids variable.
+fn insert_point_from_var_name(
+    name: &str,
+    point: (Felt, Felt),
+    vm: &mut VirtualMachine,
+    ids_data: &HashMap<String, HintReference>,
+    ap_tracking: &ApTracking,
+) -> Result<(), HintError> {
+    let point_addr = get_relocatable_from_var_name(name, vm, ids_data, ap_tracking)?;
+    vm.insert_value(&point_addr, point.0)?;
+    vm.insert_value(&(point_addr + 1_usize), point.1)?;
+    Ok(())
+}
+
+// Equivalent to starkware.python.utils.to_bytes: the 32-byte big-endian representation of value.
+fn to_bytes(value: &Felt) -> Vec<u8> {
+    let bytes = value.to_bytes_be();
+    let mut padded = vec![0_u8; 32 - bytes.len()];
+    padded.extend(bytes);
+    padded
+}
+
+/// Returns the y coordinate of the point of the STARK curve with the given x coordinate, if any.
+/// Of the two possible values, the smallest one is returned.
+pub fn recover_y(x: &BigUint) -> Option<BigUint> {
+    let y_squared = (x.modpow(&BigUint::from(3_u32), &FIELD_PRIME) + ALPHA * x + &*BETA)
+        .mod_floor(&FIELD_PRIME);
+    sqrt_prime(&y_squared, &FIELD_PRIME)
+}
+
+/// Returns a non-zero point of the STARK curve, created deterministically from the seed.
+/// Matches starkware.python.math_utils.random_ec_point.
+pub fn random_ec_point(seed: &[u8]) -> Result<(BigUint, BigUint), HintError> {
+    let seed = Sha256::digest(seed);
+    for i in 0..100_u8 {
+        // Python's i.to_bytes(10, "little")
+        let mut counter = [0_u8; 10];
+        counter[0] = i;
+        let mut hasher = Sha256::new();
+        hasher.update(&seed[1..]);
+        hasher.update(counter);
+        let x = BigUint::from_bytes_be(&hasher.finalize()).mod_floor(&FIELD_PRIME);
+        if let Some(y) = recover_y(&x) {
+            let y = if seed[0] & 1 == 1 && !y.is_zero() {
+                &*FIELD_PRIME - y
+            } else {
+                y
+            };
+            return Ok((x, y));
+        }
+    }
+    Err(HintError::RandomEcPointNotOnCurve)
+}
+
+/*
+Implements hint:
+%{
+    from starkware.crypto.signature.signature import ALPHA, BETA, FIELD_PRIME
+    from starkware.python.math_utils import random_ec_point
+    from starkware.python.utils import to_bytes
+
+    # Define a seed for random_ec_point that's dependent on all the input, so that:
+    #   (1) The added point s is deterministic.
+    #   (2) It's hard to choose inputs for which the builtin will fail.
+    seed = b"".join(map(to_bytes, [ids.p.x, ids.p.y, ids.m, ids.q.x, ids.q.y]))
+    ids.s.x, ids.s.y = random_ec_point(FIELD_PRIME, ALPHA, BETA, seed)
+%}
+*/
+pub fn random_ec_point_hint(
+    vm: &mut VirtualMachine,
+    ids_data: &HashMap<String, HintReference>,
+    ap_tracking: &ApTracking,
+) -> Result<(), HintError> {
+    let p = get_point_from_var_name("p", vm, ids_data, ap_tracking)?;
+    let q = get_point_from_var_name("q", vm, ids_data, ap_tracking)?;
+    let m = get_integer_from_var_name("m", vm, ids_data, ap_tracking)?;
+    let seed: Vec<u8> = [&p.0, &p.1, m.as_ref(), &q.0, &q.1]
+        .into_iter()
+        .flat_map(to_bytes)
+        .collect();
+    let (x, y) = random_ec_point(&seed)?;
+    insert_point_from_var_name("s", (x.into(), y.into()), vm, ids_data, ap_tracking)
+}
+
+/*
+Implements hint:
+%{
+    from starkware.crypto.signature.signature import ALPHA, BETA, FIELD_PRIME
+    from starkware.python.math_utils import random_ec_point
+    from starkware.python.utils import to_bytes
+
+    n_elms = ids.len
+    assert isinstance(n_elms, int) and n_elms >= 0, \
+        f'Invalid value for len. Got: {n_elms}.'
+    if '__chained_ec_op_max_len' in globals():
+        assert n_elms <= __chained_ec_op_max_len, \
+            f'chained_ec_op() can only be used with len<={__chained_ec_op_max_len}. ' \
+            f'Got: n_elms={n_elms}.'
+
+    # Define a seed for random_ec_point that's dependent on all the input, so that:
+    #   (1) The added point s is deterministic.
+    #   (2) It's hard to choose inputs for which the builtin will fail.
+    seed = b"".join(
+        map(
+            to_bytes,
+            [
+                ids.p.x,
+                ids.p.y,
+                *memory.get_range(ids.m, n_elms),
+                *memory.get_range(ids.q.address_, 2 * n_elms),
+            ],
+        )
+    )
+    ids.s.x, ids.s.y = random_ec_point(FIELD_PRIME, ALPHA, BETA, seed)
+%}
+*/
+pub fn chained_ec_op_random_ec_point_hint(
+    vm: &mut VirtualMachine,
+    exec_scopes: &mut ExecutionScopes,
+    ids_data: &HashMap<String, HintReference>,
+    ap_tracking: &ApTracking,
+) -> Result<(), HintError> {
+    let n_elms = get_integer_from_var_name("len", vm, ids_data, ap_tracking)?;
+    if let Ok(max_len) = exec_scopes.get_ref::<Felt>("__chained_ec_op_max_len") {
+        if n_elms.as_ref() > max_len {
+            return Err(HintError::ChainedEcOpMaxLen(
+                max_len.clone(),
+                n_elms.into_owned(),
+            ));
+        }
+    }
+    let n_elms = n_elms.to_usize().ok_or(HintError::BigintToUsizeFail)?;
+    let p = get_point_from_var_name("p", vm, ids_data, ap_tracking)?;
+    let m = get_ptr_from_var_name("m", vm, ids_data, ap_tracking)?;
+    let q = get_ptr_from_var_name("q", vm, ids_data, ap_tracking)?;
+    let mut seed = [to_bytes(&p.0), to_bytes(&p.1)].concat();
+    for value in vm
+        .get_integer_range(&m, n_elms)?
+        .iter()
+        .chain(vm.get_integer_range(&q, 2 * n_elms)?.iter())
+    {
+        seed.extend(to_bytes(value));
+    }
+    let (x, y) = random_ec_point(&seed)?;
+    insert_point_from_var_name("s", (x.into(), y.into()), vm, ids_data, ap_tracking)
+}
+
+/*
+Implements hint:
+%{
+    from starkware.crypto.signature.signature import ALPHA, BETA, FIELD_PRIME
+    from starkware.python.math_utils import recover_y
+    ids.p.x = ids.x
+    # This raises an exception if `x` is not on the curve.
+    ids.p.y = recover_y(ids.x, ALPHA, BETA, FIELD_PRIME)
+%}
+*/
+pub fn recover_y_hint(
+    vm: &mut VirtualMachine,
+    ids_data: &HashMap<String, HintReference>,
+    ap_tracking: &ApTracking,
+) -> Result<(), HintError> {
+    let x = get_integer_from_var_name("x", vm, ids_data, ap_tracking)?.into_owned();
+    let y =
+        recover_y(&x.to_biguint()).ok_or_else(|| HintError::RecoverYPointNotOnCurve(x.clone()))?;
+    insert_point_from_var_name("p", (x, y.into()), vm, ids_data, ap_tracking)
+}
+
+/*
+Implements hint:
+%{
+    from starkware.crypto.signature.signature import ALPHA, FIELD_PRIME
+    from starkware.python.math_utils import ec_double_slope
+
+    ids.slope = ec_double_slope(point=(ids.p.x, ids.p.y), alpha=ALPHA, p=FIELD_PRIME)
+%}
+*/
+pub fn ec_double_slope_hint(
+    vm: &mut VirtualMachine,
+    ids_data: &HashMap<String, HintReference>,
+    ap_tracking: &ApTracking,
+) -> Result<(), HintError> {
+    let (x, y) = get_point_from_var_name("p", vm, ids_data, ap_tracking)?;
+    if y.is_zero() {
+        return Err(HintError::EcDoubleYZero(x));
+    }
+    let slope = ec_double_slope(
+        &(x.to_bigint(), y.to_bigint()),
+        &BigInt::from(ALPHA),
+        &BigInt::from(FIELD_PRIME.clone()),
+    );
+    insert_value_from_var_name("slope", Felt::from(slope), vm, ids_data, ap_tracking)
+}
+
+/*
+Implements hint:
+%{
+    from starkware.crypto.signature.signature import FIELD_PRIME
+    from starkware.python.math_utils import line_slope
+
+    ids.slope = line_slope(point1=(ids.p.x, ids.p.y), point2=(ids.q.x, ids.q.y), p=FIELD_PRIME)
+%}
+*/
+pub fn ec_add_slope_hint(
+    vm: &mut VirtualMachine,
+    ids_data: &HashMap<String, HintReference>,
+    ap_tracking: &ApTracking,
+) -> Result<(), HintError> {
+    let p = get_point_from_var_name("p", vm, ids_data, ap_tracking)?;
+    let q = get_point_from_var_name("q", vm, ids_data, ap_tracking)?;
+    if p.0 == q.0 {
+        return Err(HintError::EcAddSameX(p.0));
+    }
+    let slope = line_slope(
+        &(p.0.to_bigint(), p.1.to_bigint()),
+        &(q.0.to_bigint(), q.1.to_bigint()),
+        &BigInt::from(FIELD_PRIME.clone()),
+    );
+    insert_value_from_var_name("slope", Felt::from(slope), vm, ids_data, ap_tracking)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        any_box,
+        hint_processor::{
+            builtin_hint_processor::{
+                builtin_hint_processor_definition::{BuiltinHintProcessor, HintProcessorData},
+                hint_code,
+            },
+            hint_processor_definition::HintProcessor,
+        },
+        types::{
+            exec_scope::ExecutionScopes,
+            relocatable::{MaybeRelocatable, Relocatable},
+        },
+        utils::test_utils::*,
+        vm::{errors::memory_errors::MemoryError, vm_memory::memory::Memory},
+    };
+    use felt::{felt_str, NewFelt};
+    use std::any::Any;
+
+    // Generator of the STARK curve
+    fn g() -> (Felt, Felt) {
+        (
+            felt_str!(
+                "874739451078007766457464989774322083649278607533249481151382481072868806602"
+            ),
+            felt_str!(
+                "152666792071518830868575557812948353041420400780739481342941381225525861407"
+            ),
+        )
+    }
+
+    // 2 * G
+    fn g_double() -> (Felt, Felt) {
+        (
+            felt_str!(
+                "3324833730090626974525872402899302150520188025637965566623476530814354734325"
+            ),
+            felt_str!(
+                "3147007486456030910661996439995670279305852583596209647900952752170983517249"
+            ),
+        )
+    }
+
+    fn insert_point(vm: &mut VirtualMachine, addr: Relocatable, point: (Felt, Felt)) {
+        vm.insert_value(&addr, point.0).unwrap();
+        vm.insert_value(&(addr + 1_usize), point.1).unwrap();
+    }
+
+    #[test]
+    fn recover_y_generator() {
+        assert_eq!(recover_y(&g().0.to_biguint()), Some(g().1.to_biguint()));
+    }
+
+    #[test]
+    fn recover_y_not_on_curve() {
+        assert_eq!(recover_y(&BigUint::from(5_u32)), None);
+    }
+
+    #[test]
+    fn run_random_ec_point_hint() {
+        let mut vm = vm!();
+        add_segments!(vm, 2);
+        vm.run_context.fp = 7;
+        insert_point(&mut vm, (1, 0).into(), g());
+        vm.insert_value(&(1, 2).into(), Felt::new(34)).unwrap();
+        insert_point(&mut vm, (1, 3).into(), g_double());
+        let ids_data = non_continuous_ids_data![("p", -7), ("m", -5), ("q", -4), ("s", -2)];
+        assert_eq!(run_hint!(vm, ids_data, hint_code::RANDOM_EC_POINT), Ok(()));
+        assert_eq!(
+            vm.get_integer(&(1, 5).into()).unwrap().as_ref(),
+            &felt_str!(
+                "3424775508269258497997645515134841848877868292372883317028148450263490305452"
+            )
+        );
+        assert_eq!(
+            vm.get_integer(&(1, 6).into()).unwrap().as_ref(),
+            &felt_str!(
+                "2342274260174237325949726439376448744211041363980509199293252659198851695407"
+            )
+        );
+    }
+
+    #[test]
+    fn run_random_ec_point_hint_negated_y() {
+        // The first byte of the hashed seed is odd, so the negated y coordinate is used
+        let mut vm = vm!();
+        add_segments!(vm, 2);
+        vm.run_context.fp = 7;
+        insert_point(&mut vm, (1, 0).into(), g());
+        vm.insert_value(&(1, 2).into(), Felt::new(1)).unwrap();
+        insert_point(&mut vm, (1, 3).into(), g_double());
+        let ids_data = non_continuous_ids_data![("p", -7), ("m", -5), ("q", -4), ("s", -2)];
+        assert_eq!(run_hint!(vm, ids_data, hint_code::RANDOM_EC_POINT), Ok(()));
+        assert_eq!(
+            vm.get_integer(&(1, 5).into()).unwrap().as_ref(),
+            &felt_str!(
+                "1244245497437247963593718126118940324744212480521418777166707386702035922385"
+            )
+        );
+        assert_eq!(
+            vm.get_integer(&(1, 6).into()).unwrap().as_ref(),
+            &felt_str!(
+                "2130133231234074486583285192836975086733840945365689561169217050401118181808"
+            )
+        );
+    }
+
+    #[test]
+    fn run_chained_ec_op_random_ec_point_hint() {
+        let mut vm = vm!();
+        add_segments!(vm, 4);
+        vm.run_context.fp = 7;
+        // p
+        insert_point(&mut vm, (1, 0).into(), g());
+        // m = [34, 5]
+        vm.insert_value(&(1, 2).into(), Relocatable::from((2, 0)))
+            .unwrap();
+        vm.insert_value(&(2, 0).into(), Felt::new(34)).unwrap();
+        vm.insert_value(&(2, 1).into(), Felt::new(5)).unwrap();
+        // q = [2 * G, G]
+        vm.insert_value(&(1, 3).into(), Relocatable::from((3, 0)))
+            .unwrap();
+        insert_point(&mut vm, (3, 0).into(), g_double());
+        insert_point(&mut vm, (3, 2).into(), g());
+        // len
+        vm.insert_value(&(1, 4).into(), Felt::new(2)).unwrap();
+        let ids_data =
+            non_continuous_ids_data![("p", -7), ("m", -5), ("q", -4), ("len", -3), ("s", -2)];
+        assert_eq!(
+            run_hint!(vm, ids_data, hint_code::CHAINED_EC_OP_RANDOM_EC_POINT),
+            Ok(())
+        );
+        assert_eq!(
+            vm.get_integer(&(1, 5).into()).unwrap().as_ref(),
+            &felt_str!(
+                "1165551558178181089313093682377503707173249423049899263214370875279940634997"
+            )
+        );
+        assert_eq!(
+            vm.get_integer(&(1, 6).into()).unwrap().as_ref(),
+            &felt_str!(
+                "2627281986049307805425188784885366345304202182380959917252378012501154007759"
+            )
+        );
+    }
+
+    #[test]
+    fn run_chained_ec_op_random_ec_point_hint_max_len_exceeded() {
+        let mut vm = vm!();
+        add_segments!(vm, 2);
+        vm.run_context.fp = 7;
+        vm.insert_value(&(1, 4).into(), Felt::new(2)).unwrap();
+        let ids_data =
+            non_continuous_ids_data![("p", -7), ("m", -5), ("q", -4), ("len", -3), ("s", -2)];
+        let mut exec_scopes = scope![("__chained_ec_op_max_len", Felt::new(1))];
+        assert_eq!(
+            run_hint!(
+                vm,
+                ids_data,
+                hint_code::CHAINED_EC_OP_RANDOM_EC_POINT,
+                &mut exec_scopes
+            ),
+            Err(HintError::ChainedEcOpMaxLen(Felt::new(1), Felt::new(2)))
+        );
+    }
+
+    #[test]
+    fn run_recover_y_hint() {
+        let mut vm = vm!();
+        add_segments!(vm, 2);
+        vm.run_context.fp = 3;
+        vm.insert_value(&(1, 0).into(), g().0).unwrap();
+        let ids_data = non_continuous_ids_data![("x", -3), ("p", -2)];
+        assert_eq!(run_hint!(vm, ids_data, hint_code::RECOVER_Y), Ok(()));
+        assert_eq!(vm.get_integer(&(1, 1).into()).unwrap().as_ref(), &g().0);
+        assert_eq!(vm.get_integer(&(1, 2).into()).unwrap().as_ref(), &g().1);
+    }
+
+    #[test]
+    fn run_recover_y_hint_not_on_curve() {
+        let mut vm = vm!();
+        vm.memory = memory![((1, 0), 5)];
+        vm.run_context.fp = 3;
+        let ids_data = non_continuous_ids_data![("x", -3), ("p", -2)];
+        assert_eq!(
+            run_hint!(vm, ids_data, hint_code::RECOVER_Y),
+            Err(HintError::RecoverYPointNotOnCurve(Felt::new(5)))
+        );
+    }
+
+    #[test]
+    fn run_ec_double_slope_hint() {
+        let mut vm = vm!();
+        add_segments!(vm, 2);
+        vm.run_context.fp = 3;
+        insert_point(&mut vm, (1, 0).into(), g());
+        let ids_data = non_continuous_ids_data![("p", -3), ("slope", -1)];
+        assert_eq!(
+            run_hint!(vm, ids_data, hint_code::STARK_EC_DOUBLE_SLOPE),
+            Ok(())
+        );
+        assert_eq!(
+            vm.get_integer(&(1, 2).into()).unwrap().as_ref(),
+            &felt_str!(
+                "1644912686482877511055657113108787980633360754587022214691994702617817720336"
+            )
+        );
+    }
+
+    #[test]
+    fn run_ec_add_slope_hint() {
+        let mut vm = vm!();
+        add_segments!(vm, 2);
+        vm.run_context.fp = 5;
+        insert_point(&mut vm, (1, 0).into(), g());
+        insert_point(&mut vm, (1, 2).into(), g_double());
+        let ids_data = non_continuous_ids_data![("p", -5), ("q", -3), ("slope", -1)];
+        assert_eq!(
+            run_hint!(vm, ids_data, hint_code::STARK_EC_ADD_SLOPE),
+            Ok(())
+        );
+        assert_eq!(
+            vm.get_integer(&(1, 4).into()).unwrap().as_ref(),
+            &felt_str!(
+                "3079698363690691973781581404839687257245297727866833285021158374995271916698"
+            )
+        );
+    }
+
+    #[test]
+    fn run_ec_add_slope_hint_same_x() {
+        let mut vm = vm!();
+        add_segments!(vm, 2);
+        vm.run_context.fp = 5;
+        insert_point(&mut vm, (1, 0).into(), g());
+        insert_point(&mut vm, (1, 2).into(), g());
+        let ids_data = non_continuous_ids_data![("p", -5), ("q", -3), ("slope", -1)];
+        assert_eq!(
+            run_hint!(vm, ids_data, hint_code::STARK_EC_ADD_SLOPE),
+            Err(HintError::EcAddSameX(g().0))
+        );
+    }
+}
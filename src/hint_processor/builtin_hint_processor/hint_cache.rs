@@ -0,0 +1,334 @@
+use crate::{
+    hint_processor::{
+        builtin_hint_processor::{builtin_hint_processor_definition::HintProcessorData, hint_code},
+        hint_processor_utils::compute_addr_from_reference,
+    },
+    types::relocatable::{MaybeRelocatable, Relocatable},
+    vm::{errors::hint_errors::HintError, vm_core::VirtualMachine},
+};
+use std::{
+    collections::{hash_map::DefaultHasher, HashMap, HashSet},
+    hash::{Hash, Hasher},
+};
+
+// The hints cached by default: pure splitters whose writes are fully determined by the values of
+// their ids, and which neither use the execution scopes nor add segments
+const DEFAULT_CACHEABLE_HINTS: [&str; 4] = [
+    hint_code::SPLIT_FELT,
+    hint_code::SPLIT_INT,
+    hint_code::SPLIT_64,
+    hint_code::SQRT,
+];
+
+#[derive(Debug, PartialEq, Eq, Hash)]
+struct HintCacheKey {
+    code_hash: u64,
+    pc: Relocatable,
+    ap: Relocatable,
+    fp: Relocatable,
+    // The value of each ids variable, sorted by name
+    inputs: Vec<Option<MaybeRelocatable>>,
+}
+
+///Caches the memory writes of hints whose results only depend on their ids, so that executing
+///the same entrypoint again (e.g. once to estimate its fee and once for real) replays them
+///instead of running the hints.
+///
+///An entry is only reused when the hint runs at the same pc, ap and fp and its ids hold the same
+///values, as the cached writes are kept at their absolute addresses.
+///Only the hints marked as cacheable are cached. A hint must not be marked as such if it reads
+///memory other than its ids, uses the execution scopes, or adds segments.
+#[derive(Debug, Default)]
+pub struct HintCache {
+    cacheable: HashSet<String>,
+    entries: HashMap<HintCacheKey, Vec<(Relocatable, MaybeRelocatable)>>,
+    hits: usize,
+    misses: usize,
+}
+
+impl HintCache {
+    ///Creates an empty cache, with the builtin splitting hints marked as cacheable.
+    pub fn new() -> Self {
+        let mut cache = HintCache::default();
+        for code in DEFAULT_CACHEABLE_HINTS {
+            cache.mark_cacheable(code);
+        }
+        cache
+    }
+
+    pub fn mark_cacheable(&mut self, code: &str) {
+        self.cacheable.insert(code.to_string());
+    }
+
+    pub fn is_cacheable(&self, code: &str) -> bool {
+        self.cacheable.contains(code)
+    }
+
+    ///The number of hint executions served from the cache.
+    pub fn hits(&self) -> usize {
+        self.hits
+    }
+
+    ///The number of executions of cacheable hints which had to run the hint.
+    pub fn misses(&self) -> usize {
+        self.misses
+    }
+
+    fn key(vm: &VirtualMachine, hint_data: &HintProcessorData) -> HintCacheKey {
+        let mut hasher = DefaultHasher::new();
+        hint_data.code.hash(&mut hasher);
+        let mut names: Vec<_> = hint_data.ids_data.keys().collect();
+        names.sort();
+        let inputs = names
+            .into_iter()
+            .map(|name| {
+                compute_addr_from_reference(&hint_data.ids_data[name], vm, &hint_data.ap_tracking)
+                    .ok()
+                    .and_then(|addr| vm.memory.get(&addr).ok().flatten())
+                    .map(|value| value.into_owned())
+            })
+            .collect();
+        HintCacheKey {
+            code_hash: hasher.finish(),
+            pc: *vm.get_pc(),
+            ap: vm.get_ap(),
+            fp: vm.get_fp(),
+            inputs,
+        }
+    }
+
+    ///Executes a cacheable hint, either by replaying the writes of a previous execution with the
+    ///same inputs, or by calling `execute` and recording its writes.
+    pub(crate) fn execute(
+        &mut self,
+        vm: &mut VirtualMachine,
+        hint_data: &HintProcessorData,
+        execute: impl FnOnce(&mut VirtualMachine) -> Result<(), HintError>,
+    ) -> Result<(), HintError> {
+        let key = HintCache::key(vm, hint_data);
+        if let Some(writes) = self.entries.get(&key) {
+            self.hits += 1;
+            for (addr, value) in writes {
+                vm.insert_value(addr, value.clone())?;
+            }
+            return Ok(());
+        }
+        self.misses += 1;
+        vm.memory.start_recording_writes();
+        let result = execute(vm);
+        let written = vm.memory.stop_recording_writes();
+        result?;
+        // The recorded cells were just written, so they are always found
+        let writes = written
+            .into_iter()
+            .filter_map(|addr| {
+                let value = vm.memory.get(&addr).ok().flatten()?;
+                Some((addr, value.into_owned()))
+            })
+            .collect();
+        self.entries.insert(key, writes);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        any_box,
+        hint_processor::{
+            builtin_hint_processor::builtin_hint_processor_definition::{
+                BuiltinHintProcessor, HintFunc,
+            },
+            hint_processor_definition::{HintProcessor, HintReference},
+        },
+        serde::deserialize_program::{
+            ApTracking, FlowTrackingData, HintParams, OffsetValue, Reference, ReferenceManager,
+            ValueAddress,
+        },
+        types::{exec_scope::ExecutionScopes, instruction::Register, program::Program},
+        utils::test_utils::*,
+        vm::{
+            errors::memory_errors::MemoryError,
+            runners::cairo_runner::{CairoRunner, RunResources},
+            vm_core::VirtualMachine,
+            vm_memory::memory::Memory,
+        },
+    };
+    use felt::{felt_str, Felt, NewFelt};
+    use std::{any::Any, rc::Rc};
+
+    // The constants split_felt checks PRIME - 1 against
+    fn split_felt_constants() -> HashMap<String, Felt> {
+        HashMap::from([
+            (
+                String::from("starkware.cairo.common.math.split_felt.MAX_HIGH"),
+                felt_str!("10633823966279327296825105735305134080"),
+            ),
+            (
+                String::from("starkware.cairo.common.math.split_felt.MAX_LOW"),
+                Felt::new(0),
+            ),
+        ])
+    }
+
+    // [ap] = 2**128 + 7, ap++
+    // %{ split_felt(value=[fp], low=[fp + 1], high=[fp + 2]) %}
+    // ap += 2
+    fn split_felt_program() -> Program {
+        let reference = |offset| Reference {
+            ap_tracking_data: ApTracking::new(),
+            pc: Some(2),
+            value_address: ValueAddress {
+                offset1: OffsetValue::Reference(Register::FP, offset, false),
+                offset2: OffsetValue::Value(0),
+                dereference: true,
+                value_type: String::from("felt"),
+            },
+        };
+        program!(
+            data = vec![
+                mayberelocatable!(0x480680017fff8000),
+                MaybeRelocatable::from(Felt::new(u128::MAX) + Felt::new(8)),
+                mayberelocatable!(0x40780017fff7fff),
+                mayberelocatable!(2),
+            ],
+            hints = HashMap::from([(
+                2,
+                vec![HintParams {
+                    code: hint_code::SPLIT_FELT.to_string(),
+                    accessible_scopes: Vec::new(),
+                    flow_tracking_data: FlowTrackingData {
+                        ap_tracking: ApTracking::new(),
+                        reference_ids: HashMap::from([
+                            (String::from("__main__.main.value"), 0),
+                            (String::from("__main__.main.low"), 1),
+                            (String::from("__main__.main.high"), 2),
+                        ]),
+                    },
+                }],
+            )]),
+            reference_manager = ReferenceManager {
+                references: vec![reference(0), reference(1), reference(2)],
+            },
+            constants = split_felt_constants(),
+            main = Some(0),
+        )
+    }
+
+    fn run_program(program: &Program, hint_processor: &mut BuiltinHintProcessor) -> VirtualMachine {
+        let mut cairo_runner = cairo_runner!(program);
+        let mut vm = vm!();
+        cairo_runner.initialize(&mut vm).unwrap();
+        cairo_runner
+            .run_until_pc(
+                Relocatable::from((0, 4)),
+                &mut RunResources::default(),
+                &mut vm,
+                hint_processor,
+            )
+            .unwrap();
+        vm
+    }
+
+    #[test]
+    fn run_program_twice_with_shared_cache() {
+        let program = split_felt_program();
+        let uncached_vm = run_program(&program, &mut BuiltinHintProcessor::new_empty());
+
+        let mut hint_processor = BuiltinHintProcessor::new_empty();
+        hint_processor.hint_cache = Some(HintCache::new());
+        let first_vm = run_program(&program, &mut hint_processor);
+        let second_vm = run_program(&program, &mut hint_processor);
+
+        let cache = hint_processor.hint_cache.as_ref().unwrap();
+        assert_eq!(cache.misses(), 1);
+        assert_eq!(cache.hits(), 1);
+        let fp = second_vm.get_fp();
+        assert_eq!(
+            second_vm.get_integer(&(fp + 1_usize)).unwrap().as_ref(),
+            &Felt::new(7)
+        );
+        assert_eq!(
+            second_vm.get_integer(&(fp + 2_usize)).unwrap().as_ref(),
+            &Felt::new(1)
+        );
+        assert_eq!(first_vm.memory.data, uncached_vm.memory.data);
+        assert_eq!(second_vm.memory.data, uncached_vm.memory.data);
+    }
+
+    #[test]
+    fn cached_hint_with_other_inputs_runs_again() {
+        let mut hint_processor = BuiltinHintProcessor::new_empty();
+        hint_processor.hint_cache = Some(HintCache::new());
+        let ids_data = ids_data!["value", "low", "high"];
+        let hint_data = any_box!(HintProcessorData::new_default(
+            hint_code::SPLIT_FELT.to_string(),
+            ids_data
+        ));
+        for value in [5, 6] {
+            let mut vm = vm!();
+            vm.run_context.fp = 3;
+            add_segments!(vm, 2);
+            vm.memory = memory![((1, 0), value)];
+            assert_eq!(
+                hint_processor.execute_hint(
+                    &mut vm,
+                    exec_scopes_ref!(),
+                    &hint_data,
+                    &split_felt_constants(),
+                    &mut RunResources::default()
+                ),
+                Ok(())
+            );
+            check_memory![vm.memory, ((1, 1), value), ((1, 2), 0)];
+        }
+        let cache = hint_processor.hint_cache.as_ref().unwrap();
+        assert_eq!(cache.misses(), 2);
+        assert_eq!(cache.hits(), 0);
+    }
+
+    fn count_executions(
+        _vm: &mut VirtualMachine,
+        exec_scopes: &mut ExecutionScopes,
+        _ids_data: &HashMap<String, HintReference>,
+        _ap_tracking: &ApTracking,
+        _constants: &HashMap<String, Felt>,
+    ) -> Result<(), HintError> {
+        let executions = exec_scopes.get::<usize>("executions").unwrap_or(0);
+        exec_scopes.insert_value("executions", executions + 1);
+        Ok(())
+    }
+
+    #[test]
+    fn scope_mutating_hint_is_never_cached() {
+        let hint_code = "executions = globals().get('executions', 0) + 1";
+        let mut hint_processor = BuiltinHintProcessor::new_empty();
+        hint_processor.add_hint(
+            String::from(hint_code),
+            Rc::new(HintFunc(Box::new(count_executions))),
+        );
+        hint_processor.hint_cache = Some(HintCache::new());
+        let hint_data = any_box!(HintProcessorData::new_default(
+            String::from(hint_code),
+            HashMap::new()
+        ));
+        let mut vm = vm!();
+        let exec_scopes = exec_scopes_ref!();
+        for _ in 0..2 {
+            assert_eq!(
+                hint_processor.execute_hint(
+                    &mut vm,
+                    exec_scopes,
+                    &hint_data,
+                    &HashMap::new(),
+                    &mut RunResources::default()
+                ),
+                Ok(())
+            );
+        }
+        assert_eq!(exec_scopes.get::<usize>("executions"), Ok(2));
+        let cache = hint_processor.hint_cache.as_ref().unwrap();
+        assert_eq!((cache.hits(), cache.misses()), (0, 0));
+    }
+}
@@ -349,9 +349,57 @@ output = blake2s_compress(
 padding = (modified_iv + message + [0, 0xffffffff] + output) * (_n_packed_instances - 1)
 segments.write_arg(ids.blake2s_ptr_end, padding)"#;
 
+pub(crate) const BLAKE2S_FINALIZE_V2: &str = r#"# Add dummy pairs of input and output.
+from starkware.cairo.common.cairo_blake2s.blake2s_utils import IV, blake2s_compress
+
+_n_packed_instances = int(ids.N_PACKED_INSTANCES)
+assert 0 <= _n_packed_instances < 20
+_blake2s_input_chunk_size_felts = int(ids.BLAKE2S_INPUT_CHUNK_SIZE_FELTS)
+assert 0 <= _blake2s_input_chunk_size_felts < 100
+
+message = [0] * _blake2s_input_chunk_size_felts
+modified_iv = [IV[0] ^ 0x01010020] + IV[1:]
+output = blake2s_compress(
+    message=message,
+    h=modified_iv,
+    t0=0,
+    t1=0,
+    f0=0xffffffff,
+    f1=0,
+)
+padding = (modified_iv + message + [0, 0xffffffff] + output) * (_n_packed_instances - 1)
+segments.write_arg(ids.blake2s_ptr_end, padding)"#;
+
+pub(crate) const BLAKE2S_FINALIZE_V3: &str = r#"# Add dummy pairs of input and output.
+from starkware.cairo.common.cairo_blake2s.blake2s_utils import IV, blake2s_compress
+
+_n_packed_instances = int(ids.N_PACKED_INSTANCES)
+assert 0 <= _n_packed_instances < 20
+_blake2s_input_chunk_size_felts = int(ids.INPUT_BLOCK_FELTS)
+assert 0 <= _blake2s_input_chunk_size_felts < 100
+
+message = [0] * _blake2s_input_chunk_size_felts
+modified_iv = [IV[0] ^ 0x01010020] + IV[1:]
+output = blake2s_compress(
+    message=message,
+    h=modified_iv,
+    t0=0,
+    t1=0,
+    f0=0xffffffff,
+    f1=0,
+)
+padding = (message + modified_iv + [0, 0xffffffff] + output) * (_n_packed_instances - 1)
+segments.write_arg(ids.blake2s_ptr_end, padding)"#;
+
 pub(crate) const BLAKE2S_ADD_UINT256: &str = r#"B = 32
 MASK = 2 ** 32 - 1
 segments.write_arg(ids.data, [(ids.low >> (B * i)) & MASK for i in range(4)])
+segments.write_arg(ids.data + 4, [(ids.high >> (B * i)) & MASK for i in range(4)])"#;
+
+// BLAKE2S_ADD_UINT256 without its closing parenthesis, which previous versions of the VM matched
+pub(crate) const BLAKE2S_ADD_UINT256_UNCLOSED: &str = r#"B = 32
+MASK = 2 ** 32 - 1
+segments.write_arg(ids.data, [(ids.low >> (B * i)) & MASK for i in range(4)])
 segments.write_arg(ids.data + 4, [(ids.high >> (B * i)) & MASK for i in range(4)]"#;
 
 pub(crate) const BLAKE2S_ADD_UINT256_BIGEND: &str = r#"B = 32
@@ -359,6 +407,14 @@ MASK = 2 ** 32 - 1
 segments.write_arg(ids.data, [(ids.high >> (B * (3 - i))) & MASK for i in range(4)])
 segments.write_arg(ids.data + 4, [(ids.low >> (B * (3 - i))) & MASK for i in range(4)])"#;
 
+pub(crate) const BLAKE2S_ADD_FELT: &str = r#"B = 32
+MASK = 2 ** 32 - 1
+segments.write_arg(ids.data, [(ids.num >> (B * i)) & MASK for i in range(8)])"#;
+
+pub(crate) const BLAKE2S_ADD_FELT_BIGEND: &str = r#"B = 32
+MASK = 2 ** 32 - 1
+segments.write_arg(ids.data, [(ids.num >> (B * (7 - i))) & MASK for i in range(8)])"#;
+
 pub(crate) const NONDET_BIGINT3: &str = r#"from starkware.cairo.common.cairo_secp.secp_utils import split
 
 segments.write_arg(ids.res.address_, split(value))"#;
@@ -539,3 +595,66 @@ pub(crate) const RELOCATE_SEGMENT: &str =
     r#"memory.add_relocation_rule(src_ptr=ids.src_ptr, dest_ptr=ids.dest_ptr)"#;
 
 pub(crate) const TEMPORARY_ARRAY: &str = r#"ids.temporary_array = segments.add_temp_segment()"#;
+
+pub(crate) const RANDOM_EC_POINT: &str = r#"from starkware.crypto.signature.signature import ALPHA, BETA, FIELD_PRIME
+from starkware.python.math_utils import random_ec_point
+from starkware.python.utils import to_bytes
+
+# Define a seed for random_ec_point that's dependent on all the input, so that:
+#   (1) The added point s is deterministic.
+#   (2) It's hard to choose inputs for which the builtin will fail.
+seed = b"".join(map(to_bytes, [ids.p.x, ids.p.y, ids.m, ids.q.x, ids.q.y]))
+ids.s.x, ids.s.y = random_ec_point(FIELD_PRIME, ALPHA, BETA, seed)"#;
+
+pub(crate) const CHAINED_EC_OP_RANDOM_EC_POINT: &str = r#"from starkware.crypto.signature.signature import ALPHA, BETA, FIELD_PRIME
+from starkware.python.math_utils import random_ec_point
+from starkware.python.utils import to_bytes
+
+n_elms = ids.len
+assert isinstance(n_elms, int) and n_elms >= 0, \
+    f'Invalid value for len. Got: {n_elms}.'
+if '__chained_ec_op_max_len' in globals():
+    assert n_elms <= __chained_ec_op_max_len, \
+        f'chained_ec_op() can only be used with len<={__chained_ec_op_max_len}. ' \
+        f'Got: n_elms={n_elms}.'
+
+# Define a seed for random_ec_point that's dependent on all the input, so that:
+#   (1) The added point s is deterministic.
+#   (2) It's hard to choose inputs for which the builtin will fail.
+seed = b"".join(
+    map(
+        to_bytes,
+        [
+            ids.p.x,
+            ids.p.y,
+            *memory.get_range(ids.m, n_elms),
+            *memory.get_range(ids.q.address_, 2 * n_elms),
+        ],
+    )
+)
+ids.s.x, ids.s.y = random_ec_point(FIELD_PRIME, ALPHA, BETA, seed)"#;
+
+pub(crate) const RECOVER_Y: &str = r#"from starkware.crypto.signature.signature import ALPHA, BETA, FIELD_PRIME
+from starkware.python.math_utils import recover_y
+ids.p.x = ids.x
+# This raises an exception if `x` is not on the curve.
+ids.p.y = recover_y(ids.x, ALPHA, BETA, FIELD_PRIME)"#;
+
+pub(crate) const STARK_EC_DOUBLE_SLOPE: &str = r#"from starkware.crypto.signature.signature import ALPHA, FIELD_PRIME
+from starkware.python.math_utils import ec_double_slope
+
+ids.slope = ec_double_slope(point=(ids.p.x, ids.p.y), alpha=ALPHA, p=FIELD_PRIME)"#;
+
+pub(crate) const STARK_EC_ADD_SLOPE: &str = r#"from starkware.crypto.signature.signature import FIELD_PRIME
+from starkware.python.math_utils import line_slope
+
+ids.slope = line_slope(point1=(ids.p.x, ids.p.y), point2=(ids.q.x, ids.q.y), p=FIELD_PRIME)"#;
+
+pub(crate) const IS_250_BITS: &str = r#"ids.is_250 = 1 if ids.addr < 2**250 else 0"#;
+
+pub(crate) const IS_ADDR_BOUNDED: &str = r#"# Verify the assumptions on the relationship between 2**250, ADDR_BOUND and PRIME.
+ADDR_BOUND = ids.ADDR_BOUND % PRIME
+assert (2**250 < ADDR_BOUND <= 2**251) and (2 * 2**250 < PRIME) and (
+        ADDR_BOUND * 2 > PRIME), \
+    'normalize_address() cannot be used with the current constants.'
+ids.is_small = 1 if ids.addr < ADDR_BOUND else 0"#;
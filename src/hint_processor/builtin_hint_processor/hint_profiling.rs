@@ -0,0 +1,219 @@
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    collections::HashMap,
+    fmt,
+    sync::atomic::{AtomicU64, Ordering},
+    time::Duration,
+};
+
+static ALLOCATED_BYTES: AtomicU64 = AtomicU64::new(0);
+
+///A global allocator counting the bytes allocated, so that the hint profile includes the
+///allocations of each hint. Without it, the allocated bytes of every hint are reported as 0.
+///
+///```ignore
+///#[global_allocator]
+///static ALLOC: CountingAllocator = CountingAllocator;
+///```
+pub struct CountingAllocator;
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATED_BYTES.fetch_add(layout.size() as u64, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+// The bytes allocated so far through the CountingAllocator
+pub(crate) fn allocated_bytes() -> u64 {
+    ALLOCATED_BYTES.load(Ordering::Relaxed)
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct HintStats {
+    pub count: usize,
+    pub total_time: Duration,
+    pub max_time: Duration,
+    pub allocated_bytes: u64,
+}
+
+///The execution metrics of each hint, keyed by the code the hint was registered with.
+#[derive(Debug, Default)]
+pub struct HintProfile {
+    stats: HashMap<String, HintStats>,
+}
+
+impl HintProfile {
+    pub(crate) fn record(&mut self, hint_code: &str, time: Duration, allocated_bytes: u64) {
+        let stats = self.stats.entry(hint_code.to_string()).or_default();
+        stats.count += 1;
+        stats.total_time += time;
+        stats.max_time = stats.max_time.max(time);
+        stats.allocated_bytes += allocated_bytes;
+    }
+
+    pub fn get(&self, hint_code: &str) -> Option<&HintStats> {
+        self.stats.get(hint_code)
+    }
+
+    ///Returns the metrics of every executed hint, the most time consuming first.
+    pub fn sorted_by_total_time(&self) -> Vec<(&str, &HintStats)> {
+        let mut stats: Vec<_> = self
+            .stats
+            .iter()
+            .map(|(hint_code, stats)| (hint_code.as_str(), stats))
+            .collect();
+        stats.sort_by(|(code_a, a), (code_b, b)| {
+            b.total_time.cmp(&a.total_time).then(code_a.cmp(code_b))
+        });
+        stats
+    }
+}
+
+///Renders a line per hint, the most time consuming first. Hints are shown by the first line of
+///their code.
+impl fmt::Display for HintProfile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "count\ttotal\tmax\tallocated\thint")?;
+        for (hint_code, stats) in self.sorted_by_total_time() {
+            let mut lines = hint_code.lines();
+            let first_line = lines.next().unwrap_or_default();
+            let ellipsis = if lines.next().is_some() { " ..." } else { "" };
+            writeln!(
+                f,
+                "{}\t{:?}\t{:?}\t{}\t{first_line}{ellipsis}",
+                stats.count, stats.total_time, stats.max_time, stats.allocated_bytes
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vm::runners::cairo_runner::RunResources;
+    use crate::{
+        any_box,
+        hint_processor::{
+            builtin_hint_processor::{
+                builtin_hint_processor_definition::{
+                    BuiltinHintProcessor, HintFunc, HintProcessorData,
+                },
+                hint_code,
+            },
+            hint_processor_definition::{HintProcessor, HintReference},
+        },
+        serde::deserialize_program::ApTracking,
+        types::{
+            exec_scope::ExecutionScopes,
+            relocatable::{MaybeRelocatable, Relocatable},
+        },
+        utils::test_utils::*,
+        vm::{errors::hint_errors::HintError, vm_core::VirtualMachine},
+    };
+    use felt::Felt;
+    use std::{any::Any, rc::Rc};
+
+    #[test]
+    fn record_accumulates_per_hint() {
+        let mut profile = HintProfile::default();
+        profile.record("a", Duration::from_millis(3), 8);
+        profile.record("b", Duration::from_millis(5), 0);
+        profile.record("a", Duration::from_millis(4), 16);
+        assert_eq!(
+            profile.get("a"),
+            Some(&HintStats {
+                count: 2,
+                total_time: Duration::from_millis(7),
+                max_time: Duration::from_millis(4),
+                allocated_bytes: 24,
+            })
+        );
+        assert_eq!(
+            profile
+                .sorted_by_total_time()
+                .into_iter()
+                .map(|(hint_code, _)| hint_code)
+                .collect::<Vec<_>>(),
+            vec!["a", "b"]
+        );
+        assert_eq!(
+            profile.to_string(),
+            "count\ttotal\tmax\tallocated\thint\n2\t7ms\t4ms\t24\ta\n1\t5ms\t5ms\t0\tb\n"
+        );
+    }
+
+    fn count_dicts(
+        _vm: &mut VirtualMachine,
+        exec_scopes: &mut ExecutionScopes,
+        _ids_data: &HashMap<String, HintReference>,
+        _ap_tracking: &ApTracking,
+        _constants: &HashMap<String, Felt>,
+    ) -> Result<(), HintError> {
+        let n_dicts = exec_scopes.get_dict_manager()?.borrow().trackers.len();
+        exec_scopes.insert_value("n_dicts", n_dicts);
+        Ok(())
+    }
+
+    #[test]
+    fn profile_dict_hints() {
+        let custom_hint_code = "n_dicts = len(__dict_manager.trackers)";
+        let mut hint_processor = BuiltinHintProcessor::new_empty();
+        hint_processor.add_hint(
+            String::from(custom_hint_code),
+            Rc::new(HintFunc(Box::new(count_dicts))),
+        );
+        let mut vm = vm!();
+        let mut exec_scopes = ExecutionScopes::new();
+        let mut run = |vm: &mut VirtualMachine, code: &str, ids_data| {
+            let hint_data = any_box!(HintProcessorData::new_default(code.to_string(), ids_data));
+            hint_processor.execute_hint(
+                vm,
+                &mut exec_scopes,
+                &hint_data,
+                &HashMap::new(),
+                &mut RunResources::default(),
+            )
+        };
+
+        // dict_ptr = default_dict_new(default_value=2)
+        add_segments!(vm, 2);
+        vm.insert_value(&(1, 0).into(), 2).unwrap();
+        run_context!(vm, 0, 1, 1);
+        let ids_data = ids_data!["default_value"];
+        assert_eq!(run(&mut vm, hint_code::DEFAULT_DICT_NEW, ids_data), Ok(()));
+        // dict_write(dict_ptr, key, new_value) for the keys 0, 1 and 2, each in a new frame
+        for key in 0..3 {
+            let frame = 2 + 3 * key;
+            vm.insert_value(&(1, frame).into(), key).unwrap();
+            vm.insert_value(&(1, frame + 1).into(), key + 10).unwrap();
+            vm.insert_value(&(1, frame + 2).into(), Relocatable::from((2, 3 * key)))
+                .unwrap();
+            vm.run_context.fp = frame + 3;
+            let ids_data = ids_data!["key", "new_value", "dict_ptr"];
+            assert_eq!(run(&mut vm, hint_code::DICT_WRITE, ids_data), Ok(()));
+        }
+        // value = dict_read(dict_ptr, key=1)
+        vm.insert_value(&(1, 11).into(), 1).unwrap();
+        vm.insert_value(&(1, 13).into(), Relocatable::from((2, 9)))
+            .unwrap();
+        vm.run_context.fp = 14;
+        let ids_data = ids_data!["key", "value", "dict_ptr"];
+        assert_eq!(run(&mut vm, hint_code::DICT_READ, ids_data), Ok(()));
+        check_memory![vm.memory, ((1, 12), 11)];
+        assert_eq!(run(&mut vm, custom_hint_code, HashMap::new()), Ok(()));
+
+        let profile = hint_processor.get_profile();
+        let count = |hint_code| profile.get(hint_code).map(|stats| stats.count);
+        assert_eq!(count(hint_code::DEFAULT_DICT_NEW), Some(1));
+        assert_eq!(count(hint_code::DICT_WRITE), Some(3));
+        assert_eq!(count(hint_code::DICT_READ), Some(1));
+        assert_eq!(count(custom_hint_code), Some(1));
+        assert_eq!(profile.sorted_by_total_time().len(), 4);
+    }
+}
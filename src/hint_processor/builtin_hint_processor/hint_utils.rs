@@ -1,15 +1,19 @@
-use felt::Felt;
+use felt::{Felt, FeltOps};
+use num_bigint::BigInt;
+use num_traits::One;
 
 use crate::hint_processor::hint_processor_definition::HintReference;
 use crate::hint_processor::hint_processor_utils::compute_addr_from_reference;
 use crate::hint_processor::hint_processor_utils::{
     get_integer_from_reference, get_maybe_relocatable_from_reference,
 };
-use crate::serde::deserialize_program::ApTracking;
+use crate::serde::deserialize_program::{ApTracking, Identifier};
+use crate::types::exec_scope::ExecutionScopes;
 use crate::types::relocatable::MaybeRelocatable;
 use crate::types::relocatable::Relocatable;
 use crate::vm::errors::hint_errors::HintError;
 use crate::vm::vm_core::VirtualMachine;
+use serde_json::Value;
 use std::borrow::Cow;
 use std::collections::HashMap;
 
@@ -35,6 +39,46 @@ pub fn insert_value_into_ap(
         .map_err(HintError::Internal)
 }
 
+//Returns the value of the constant with the given full name. The constants whose value is a
+//label are returned as the address of the label in the program segment
+pub fn get_constant_value(
+    name: &str,
+    vm: &VirtualMachine,
+    constants: &HashMap<String, Felt>,
+) -> Result<MaybeRelocatable, HintError> {
+    match constants.get(name) {
+        Some(value) => Ok(MaybeRelocatable::from(value)),
+        None => vm
+            .label_constants
+            .get(name)
+            .map(MaybeRelocatable::from)
+            .ok_or_else(|| HintError::UnknownConstant(name.to_string())),
+    }
+}
+
+//Returns the value of the constant with the given full name (`<module>.<name>`).
+//Programs that define the hint's function in their own module (e.g. `__main__`) hold the constant
+//under that module instead, so a single constant with the same short name is accepted too.
+//Fails if more than one constant has that short name, as the hint can't tell which one it uses
+pub fn get_constant_from_var_name<'a>(
+    var_name: &'static str,
+    constants: &'a HashMap<String, Felt>,
+) -> Result<&'a Felt, HintError> {
+    if let Some(value) = constants.get(var_name) {
+        return Ok(value);
+    }
+    let short_name = var_name.rsplit('.').next();
+    let mut matches = constants
+        .iter()
+        .filter(|(name, _)| name.rsplit('.').next() == short_name)
+        .map(|(_, value)| value);
+    match (matches.next(), matches.next()) {
+        (Some(value), None) => Ok(value),
+        (None, _) => Err(HintError::MissingConstant(var_name)),
+        (Some(_), Some(_)) => Err(HintError::AmbiguousConstant(var_name)),
+    }
+}
+
 //Returns the Relocatable value stored in the given ids variable
 pub fn get_ptr_from_var_name(
     var_name: &str,
@@ -114,18 +158,165 @@ pub fn get_reference_from_var_name<'a>(
     ids_data.get(var_name).ok_or(HintError::FailedToGetIds)
 }
 
+//Reads n consecutive integer values starting from ptr
+pub fn read_felts(
+    vm: &VirtualMachine,
+    ptr: &Relocatable,
+    n: usize,
+) -> Result<Vec<BigInt>, HintError> {
+    Ok(vm
+        .get_integer_range(ptr, n)?
+        .iter()
+        .map(|felt| felt.to_bigint())
+        .collect())
+}
+
+//Writes the given values into consecutive cells starting from ptr
+//Returns the address right after the last written value
+pub fn write_felts(
+    vm: &mut VirtualMachine,
+    ptr: &Relocatable,
+    values: &[BigInt],
+) -> Result<Relocatable, HintError> {
+    let data = values
+        .iter()
+        .map(|value| MaybeRelocatable::from(Felt::from(value)))
+        .collect();
+    let end = vm.load_data(&MaybeRelocatable::from(ptr), &data)?;
+    Ok(end.get_relocatable()?)
+}
+
+//Fails if a limb of the struct stored in var_name isn't below 2**limb_bits
+//The check is skipped when the skip_limb_bounds_checks feature is enabled
+pub fn check_limb_bounds(
+    var_name: &str,
+    limbs: &[&Felt],
+    limb_bits: usize,
+) -> Result<(), HintError> {
+    if cfg!(feature = "skip_limb_bounds_checks") {
+        return Ok(());
+    }
+    let bound = Felt::one() << limb_bits;
+    match limbs.iter().position(|limb| **limb >= bound) {
+        Some(limb_index) => Err(HintError::LimbOutOfBounds {
+            struct_name: var_name.to_string(),
+            limb_index,
+            value: limbs[limb_index].clone(),
+            bound,
+        }),
+        None => Ok(()),
+    }
+}
+
+//Reads the given fields of the struct identified by struct_path, which is stored at ptr
+//Returns a map from field name to its value
+pub fn read_struct(
+    vm: &VirtualMachine,
+    ptr: &Relocatable,
+    fields: &[&str],
+    identifiers: &HashMap<String, Identifier>,
+    struct_path: &str,
+) -> Result<HashMap<String, BigInt>, HintError> {
+    let members = identifiers
+        .get(struct_path)
+        .and_then(|identifier| identifier.members.as_ref())
+        .ok_or_else(|| HintError::UnknownStruct(struct_path.to_string()))?;
+    // The cells of the struct, up to its last member
+    let size = members
+        .values()
+        .map(|member| member.offset + 1)
+        .max()
+        .unwrap_or_default();
+    let cells = vm.memory.get_range(&ptr.into(), size)?;
+    let mut values = HashMap::new();
+    for field in fields {
+        let member = members.get(*field).ok_or_else(|| {
+            HintError::MissingStructMember(struct_path.to_string(), field.to_string())
+        })?;
+        let addr = ptr + member.offset;
+        if cells[member.offset].is_none() {
+            // A missing member followed by a written one is a hole, otherwise the struct was
+            // cut short
+            return Err(
+                if cells[member.offset..].iter().any(|cell| cell.is_some()) {
+                    HintError::StructMemberHole(struct_path.to_string(), field.to_string(), addr)
+                } else {
+                    HintError::StructMemberOutOfBounds(
+                        struct_path.to_string(),
+                        field.to_string(),
+                        addr,
+                    )
+                },
+            );
+        }
+        values.insert(field.to_string(), vm.get_integer(&addr)?.to_bigint());
+    }
+    Ok(values)
+}
+
+//The name of the scope variable holding the input given with CairoRunner::set_program_input
+pub const PROGRAM_INPUT: &str = "program_input";
+
+//Returns the program input, which is kept in the main scope
+pub fn get_program_input(exec_scopes: &ExecutionScopes) -> Result<&Value, HintError> {
+    exec_scopes.get_ref::<Value>(PROGRAM_INPUT)
+}
+
+//Returns program_input[key], which must be an integer
+pub fn get_program_input_as_bigint(
+    exec_scopes: &ExecutionScopes,
+    key: &str,
+) -> Result<BigInt, HintError> {
+    get_program_input(exec_scopes)?
+        .get(key)
+        .and_then(value_to_bigint)
+        .ok_or_else(|| HintError::InvalidProgramInput(key.to_string()))
+}
+
+//Returns program_input[key], which must be an array of integers
+pub fn get_program_input_as_array(
+    exec_scopes: &ExecutionScopes,
+    key: &str,
+) -> Result<Vec<BigInt>, HintError> {
+    get_program_input(exec_scopes)?
+        .get(key)
+        .and_then(Value::as_array)
+        .and_then(|values| values.iter().map(value_to_bigint).collect())
+        .ok_or_else(|| HintError::InvalidProgramInput(key.to_string()))
+}
+
+fn value_to_bigint(value: &Value) -> Option<BigInt> {
+    match value {
+        Value::Number(number) => number.to_string().parse().ok(),
+        _ => None,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use felt::NewFelt;
+    use std::any::Any;
+    use std::rc::Rc;
 
     use super::*;
     use crate::{
-        hint_processor::hint_processor_definition::HintReference,
+        any_box,
+        hint_processor::{
+            builtin_hint_processor::builtin_hint_processor_definition::{
+                BuiltinHintProcessor, HintFunc, HintProcessorData,
+            },
+            hint_processor_definition::{HintProcessor, HintReference},
+        },
         relocatable,
-        serde::deserialize_program::OffsetValue,
+        serde::deserialize_program::{Member, OffsetValue},
+        types::{
+            exec_scope::ExecutionScopes,
+            program::{ConstValue, Program},
+        },
         utils::test_utils::*,
         vm::{
             errors::{memory_errors::MemoryError, vm_errors::VirtualMachineError},
+            runners::cairo_runner::{CairoRunner, RunResources},
             vm_core::VirtualMachine,
             vm_memory::memory::Memory,
         },
@@ -252,4 +443,414 @@ mod tests {
             )))
         );
     }
+
+    fn echo_request_identifiers() -> HashMap<String, Identifier> {
+        HashMap::from([(
+            String::from("__main__.EchoRequest"),
+            Identifier {
+                pc: None,
+                type_: Some(String::from("struct")),
+                value: None,
+                full_name: Some(String::from("__main__.EchoRequest")),
+                members: Some(HashMap::from([
+                    (
+                        String::from("selector"),
+                        Member {
+                            cairo_type: String::from("felt"),
+                            offset: 0,
+                        },
+                    ),
+                    (
+                        String::from("value"),
+                        Member {
+                            cairo_type: String::from("felt"),
+                            offset: 1,
+                        },
+                    ),
+                ])),
+            },
+        )])
+    }
+
+    #[test]
+    fn read_felts_valid() {
+        let mut vm = vm!();
+        vm.memory = memory![((1, 0), 1), ((1, 1), 2), ((1, 2), 3)];
+        assert_eq!(
+            read_felts(&vm, &relocatable!(1, 0), 3),
+            Ok(vec![BigInt::from(1), BigInt::from(2), BigInt::from(3)])
+        );
+    }
+
+    #[test]
+    fn read_felts_hole() {
+        let mut vm = vm!();
+        vm.memory = memory![((1, 0), 1), ((1, 2), 3)];
+        assert_eq!(
+            read_felts(&vm, &relocatable!(1, 0), 3),
+            Err(HintError::Internal(VirtualMachineError::ExpectedInteger(
+                MaybeRelocatable::from((1, 1))
+            )))
+        );
+    }
+
+    #[test]
+    fn write_felts_returns_next_address() {
+        let mut vm = vm!();
+        add_segments!(vm, 2);
+        assert_eq!(
+            write_felts(
+                &mut vm,
+                &relocatable!(1, 3),
+                &[BigInt::from(7), BigInt::from(8)]
+            ),
+            Ok(relocatable!(1, 5))
+        );
+        check_memory![vm.memory, ((1, 3), 7), ((1, 4), 8)];
+    }
+
+    #[test]
+    fn read_struct_valid() {
+        let mut vm = vm!();
+        vm.memory = memory![((1, 0), 5), ((1, 1), 6)];
+        assert_eq!(
+            read_struct(
+                &vm,
+                &relocatable!(1, 0),
+                &["value", "selector"],
+                &echo_request_identifiers(),
+                "__main__.EchoRequest"
+            ),
+            Ok(HashMap::from([
+                (String::from("selector"), BigInt::from(5)),
+                (String::from("value"), BigInt::from(6))
+            ]))
+        );
+    }
+
+    #[test]
+    fn read_struct_in_temporary_segment() {
+        let mut vm = vm!();
+        let ptr = vm.add_temporary_segment();
+        vm.insert_value(&ptr, Felt::new(5)).unwrap();
+        vm.insert_value(&(ptr + 1), Felt::new(6)).unwrap();
+        assert_eq!(
+            read_struct(
+                &vm,
+                &ptr,
+                &["selector"],
+                &echo_request_identifiers(),
+                "__main__.EchoRequest"
+            ),
+            Ok(HashMap::from([(String::from("selector"), BigInt::from(5))]))
+        );
+    }
+
+    #[test]
+    fn read_struct_unknown_struct() {
+        let vm = vm!();
+        assert_eq!(
+            read_struct(
+                &vm,
+                &relocatable!(1, 0),
+                &["value"],
+                &echo_request_identifiers(),
+                "__main__.EchoResponse"
+            ),
+            Err(HintError::UnknownStruct(String::from(
+                "__main__.EchoResponse"
+            )))
+        );
+    }
+
+    #[test]
+    fn read_struct_missing_member() {
+        let mut vm = vm!();
+        vm.memory = memory![((1, 0), 5), ((1, 1), 6)];
+        assert_eq!(
+            read_struct(
+                &vm,
+                &relocatable!(1, 0),
+                &["selector", "len"],
+                &echo_request_identifiers(),
+                "__main__.EchoRequest"
+            ),
+            Err(HintError::MissingStructMember(
+                String::from("__main__.EchoRequest"),
+                String::from("len")
+            ))
+        );
+    }
+
+    #[test]
+    fn read_struct_member_out_of_bounds() {
+        let mut vm = vm!();
+        vm.memory = memory![((1, 0), 5)];
+        assert_eq!(
+            read_struct(
+                &vm,
+                &relocatable!(1, 0),
+                &["selector", "value"],
+                &echo_request_identifiers(),
+                "__main__.EchoRequest"
+            ),
+            Err(HintError::StructMemberOutOfBounds(
+                String::from("__main__.EchoRequest"),
+                String::from("value"),
+                relocatable!(1, 1)
+            ))
+        );
+    }
+
+    #[test]
+    fn read_struct_member_hole() {
+        let mut vm = vm!();
+        vm.memory = memory![((1, 1), 6), ((1, 2), 7)];
+        assert_eq!(
+            read_struct(
+                &vm,
+                &relocatable!(1, 0),
+                &["value", "selector"],
+                &echo_request_identifiers(),
+                "__main__.EchoRequest"
+            ),
+            Err(HintError::StructMemberHole(
+                String::from("__main__.EchoRequest"),
+                String::from("selector"),
+                relocatable!(1, 0)
+            ))
+        );
+    }
+
+    // Custom syscall handler: reads an EchoRequest at syscall_ptr, writes back its value
+    // right after it and stores the advanced syscall pointer in the execution scope.
+    fn echo_syscall(
+        vm: &mut VirtualMachine,
+        exec_scopes: &mut ExecutionScopes,
+        ids_data: &HashMap<String, HintReference>,
+        ap_tracking: &ApTracking,
+        _constants: &HashMap<String, Felt>,
+    ) -> Result<(), HintError> {
+        let syscall_ptr = get_ptr_from_var_name("syscall_ptr", vm, ids_data, ap_tracking)?;
+        let request = read_struct(
+            vm,
+            &syscall_ptr,
+            &["selector", "value"],
+            &echo_request_identifiers(),
+            "__main__.EchoRequest",
+        )?;
+        let response = read_felts(vm, &syscall_ptr, 2)?;
+        assert_eq!(response[1], request["value"]);
+        let next_syscall_ptr = write_felts(vm, &(syscall_ptr + 2_usize), &response[1..])?;
+        exec_scopes.insert_value("syscall_ptr", next_syscall_ptr);
+        Ok(())
+    }
+
+    #[test]
+    fn run_echo_syscall_custom_hint() {
+        let mut hint_processor = BuiltinHintProcessor::new_empty();
+        hint_processor.add_hint(
+            String::from("syscall_handler.echo(syscall_ptr=ids.syscall_ptr)"),
+            Rc::new(HintFunc(Box::new(echo_syscall))),
+        );
+        let mut vm = vm!();
+        add_segments!(vm, 3);
+        vm.run_context.fp = 1;
+        // syscall_ptr = (2, 0); EchoRequest(selector='echo', value=42)
+        vm.memory = memory![((1, 0), (2, 0)), ((2, 0), 1701013615), ((2, 1), 42)];
+        let exec_scopes = exec_scopes_ref!();
+        let hint_data = HintProcessorData::new_default(
+            String::from("syscall_handler.echo(syscall_ptr=ids.syscall_ptr)"),
+            ids_data!["syscall_ptr"],
+        );
+        assert_eq!(
+            hint_processor.execute_hint(
+                &mut vm,
+                exec_scopes,
+                &any_box!(hint_data),
+                &HashMap::new(),
+                &mut RunResources::default()
+            ),
+            Ok(())
+        );
+        check_memory![vm.memory, ((2, 2), 42)];
+        assert_eq!(
+            exec_scopes.get::<Relocatable>("syscall_ptr"),
+            Ok(relocatable!(2, 3))
+        );
+    }
+
+    // Jumps to the label held by the SKIP_TARGET constant
+    fn jump_to_skip_target(
+        vm: &mut VirtualMachine,
+        _exec_scopes: &mut ExecutionScopes,
+        _ids_data: &HashMap<String, HintReference>,
+        _ap_tracking: &ApTracking,
+        constants: &HashMap<String, Felt>,
+    ) -> Result<(), HintError> {
+        let target = get_constant_value("__main__.SKIP_TARGET", vm, constants)?;
+        vm.set_pc(target.get_relocatable()?);
+        Ok(())
+    }
+
+    #[test]
+    fn run_hint_jumping_to_label_constant() {
+        let program = Program::from_fixture("label_constant");
+        assert_eq!(
+            program.get_constant("__main__.SKIP_TARGET"),
+            Some(ConstValue::Label(3))
+        );
+        assert_eq!(
+            program.get_constant("__main__.ANSWER"),
+            Some(ConstValue::Felt(Felt::new(42)))
+        );
+
+        let mut hint_processor = BuiltinHintProcessor::new_empty();
+        hint_processor.add_hint(
+            String::from("jump_to(SKIP_TARGET)"),
+            Rc::new(HintFunc(Box::new(jump_to_skip_target))),
+        );
+        let mut cairo_runner = cairo_runner!(program);
+        let mut vm = vm!();
+        let end = cairo_runner.initialize(&mut vm).unwrap();
+        assert_eq!(
+            get_constant_value("__main__.SKIP_TARGET", &vm, cairo_runner.get_constants()),
+            Ok(MaybeRelocatable::from((0, 3)))
+        );
+        cairo_runner
+            .run_until_pc(
+                end,
+                &mut RunResources::default(),
+                &mut vm,
+                &mut hint_processor,
+            )
+            .unwrap();
+        // The first instruction, writing 7, was skipped
+        check_memory![vm.memory, ((1, 2), 42)];
+    }
+
+    #[test]
+    fn get_unknown_constant_value() {
+        let vm = vm!();
+        assert_eq!(
+            get_constant_value("__main__.MISSING", &vm, &HashMap::new()),
+            Err(HintError::UnknownConstant(String::from("__main__.MISSING")))
+        );
+    }
+
+    #[test]
+    fn get_program_input_values() {
+        let mut exec_scopes = ExecutionScopes::new();
+        let program_input: Value = serde_json::from_str(
+            r#"{"n": 10, "prime": 3618502788666131213697322783095070105623107215331596699973092056135872020481, "values": [1, -2, 3], "name": "fib"}"#,
+        )
+        .unwrap();
+        exec_scopes.insert_value(PROGRAM_INPUT, program_input);
+        assert_eq!(
+            get_program_input_as_bigint(&exec_scopes, "n"),
+            Ok(BigInt::from(10))
+        );
+        // Integers don't lose precision
+        assert_eq!(
+            get_program_input_as_bigint(&exec_scopes, "prime"),
+            Ok(crate::utils::CAIRO_PRIME.clone())
+        );
+        assert_eq!(
+            get_program_input_as_array(&exec_scopes, "values"),
+            Ok(vec![BigInt::from(1), BigInt::from(-2), BigInt::from(3)])
+        );
+        assert_eq!(
+            get_program_input_as_bigint(&exec_scopes, "name"),
+            Err(HintError::InvalidProgramInput(String::from("name")))
+        );
+        assert_eq!(
+            get_program_input_as_array(&exec_scopes, "n"),
+            Err(HintError::InvalidProgramInput(String::from("n")))
+        );
+        assert_eq!(
+            get_program_input_as_bigint(&exec_scopes, "missing"),
+            Err(HintError::InvalidProgramInput(String::from("missing")))
+        );
+    }
+
+    #[test]
+    fn get_program_input_not_set() {
+        let exec_scopes = ExecutionScopes::new();
+        assert_eq!(
+            get_program_input_as_bigint(&exec_scopes, "n"),
+            Err(HintError::VariableNotInScopeError(String::from(
+                PROGRAM_INPUT
+            )))
+        );
+    }
+
+    #[test]
+    fn get_constant_from_var_name_exact_match() {
+        let constants = HashMap::from([
+            (
+                String::from("starkware.cairo.common.math.assert_250_bit.SHIFT"),
+                Felt::new(1_u128 << 127),
+            ),
+            (
+                String::from("starkware.cairo.common.uint256.SHIFT"),
+                Felt::new(1_u128 << 64),
+            ),
+        ]);
+        assert_eq!(
+            get_constant_from_var_name(
+                "starkware.cairo.common.math.assert_250_bit.SHIFT",
+                &constants
+            ),
+            Ok(&Felt::new(1_u128 << 127))
+        );
+        assert_eq!(
+            get_constant_from_var_name("starkware.cairo.common.uint256.SHIFT", &constants),
+            Ok(&Felt::new(1_u128 << 64))
+        );
+    }
+
+    #[test]
+    fn get_constant_from_var_name_matches_last_segment() {
+        let constants = HashMap::from([
+            (
+                String::from("starkware.cairo.common.cairo_secp.constants.SECP_REM"),
+                Felt::new(19),
+            ),
+            (String::from("__main__.SHIFT"), Felt::new(1_u128 << 127)),
+        ]);
+        assert_eq!(
+            get_constant_from_var_name(
+                "starkware.cairo.common.math.assert_250_bit.SHIFT",
+                &constants
+            ),
+            Ok(&Felt::new(1_u128 << 127))
+        );
+        // A suffix of the last segment isn't enough
+        assert_eq!(
+            get_constant_from_var_name("starkware.cairo.common.math.REM", &constants),
+            Err(HintError::MissingConstant(
+                "starkware.cairo.common.math.REM"
+            ))
+        );
+    }
+
+    #[test]
+    fn get_constant_from_var_name_ambiguous() {
+        let constants = HashMap::from([
+            (String::from("__main__.SHIFT"), Felt::new(1_u128 << 127)),
+            (
+                String::from("starkware.cairo.common.uint256.SHIFT"),
+                Felt::new(1_u128 << 64),
+            ),
+        ]);
+        assert_eq!(
+            get_constant_from_var_name(
+                "starkware.cairo.common.math.assert_250_bit.SHIFT",
+                &constants
+            ),
+            Err(HintError::AmbiguousConstant(
+                "starkware.cairo.common.math.assert_250_bit.SHIFT"
+            ))
+        );
+    }
 }
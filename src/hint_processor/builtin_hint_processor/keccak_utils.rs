@@ -161,9 +161,7 @@ pub fn unsafe_keccak_finalize(
         .ok_or(VirtualMachineError::BigintToUsizeFail)?;
 
     let mut keccak_input = Vec::new();
-    let range = vm
-        .get_range(&maybe_rel_start_ptr, n_elems)
-        .map_err(VirtualMachineError::MemoryError)?;
+    let range = vm.get_range(&maybe_rel_start_ptr, n_elems)?;
 
     check_no_nones_in_range(&range)?;
 
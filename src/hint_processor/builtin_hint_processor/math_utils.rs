@@ -2,24 +2,25 @@ use crate::{
     any_box,
     hint_processor::{
         builtin_hint_processor::hint_utils::{
-            get_address_from_var_name, get_integer_from_var_name, get_ptr_from_var_name,
-            insert_value_from_var_name, insert_value_into_ap,
+            get_address_from_var_name, get_constant_from_var_name, get_integer_from_var_name,
+            get_ptr_from_var_name, insert_value_from_var_name, insert_value_into_ap,
         },
         hint_processor_definition::HintReference,
     },
     math_utils::isqrt,
     serde::deserialize_program::ApTracking,
     types::{exec_scope::ExecutionScopes, relocatable::MaybeRelocatable},
+    utils::{as_int, is_positive_signed, CAIRO_PRIME},
     vm::{
         errors::{hint_errors::HintError, vm_errors::VirtualMachineError},
         vm_core::VirtualMachine,
     },
 };
 use felt::{Felt, FeltOps, NewFelt, PRIME_STR};
-use num_bigint::BigUint;
+use num_bigint::{BigInt, BigUint};
 use num_integer::Integer;
 use num_traits::One;
-use num_traits::{Num, Signed, Zero};
+use num_traits::{Bounded, Num, Signed, Zero};
 use std::{
     any::Any,
     collections::HashMap,
@@ -318,16 +319,17 @@ pub fn is_positive(
     ap_tracking: &ApTracking,
 ) -> Result<(), HintError> {
     let value = get_integer_from_var_name("value", vm, ids_data, ap_tracking)?;
+    let int_value: BigInt = value.to_biguint().into();
     let range_check_builtin = vm.get_range_check_builtin()?;
     //Main logic (assert a is positive)
     match &range_check_builtin._bound {
-        Some(bound) if &value.abs() > bound => {
+        Some(bound) if as_int(&int_value, &CAIRO_PRIME).abs() >= bound.to_biguint().into() => {
             return Err(HintError::ValueOutsideValidRange(value.into_owned()))
         }
         _ => {}
     };
 
-    let result = if value.is_positive() {
+    let result = if is_positive_signed(&int_value, &CAIRO_PRIME) {
         Felt::one()
     } else {
         Felt::zero()
@@ -348,7 +350,23 @@ pub fn split_felt(
     vm: &mut VirtualMachine,
     ids_data: &HashMap<String, HintReference>,
     ap_tracking: &ApTracking,
+    constants: &HashMap<String, Felt>,
 ) -> Result<(), HintError> {
+    const MAX_HIGH: &str = "starkware.cairo.common.math.split_felt.MAX_HIGH";
+    const MAX_LOW: &str = "starkware.cairo.common.math.split_felt.MAX_LOW";
+
+    let max_high = get_constant_from_var_name(MAX_HIGH, constants)?.to_biguint();
+    let max_low = get_constant_from_var_name(MAX_LOW, constants)?.to_biguint();
+    //assert ids.MAX_HIGH < 2**128 and ids.MAX_LOW < 2**128
+    //assert PRIME - 1 == ids.MAX_HIGH * 2**128 + ids.MAX_LOW
+    if max_high.bits() > 128
+        || max_low.bits() > 128
+        || (max_high << 128_u32) + max_low != Felt::max_value().to_biguint()
+    {
+        return Err(HintError::AssertionFailed(String::from(
+            "assert PRIME - 1 == ids.MAX_HIGH * 2**128 + ids.MAX_LOW",
+        )));
+    }
     let value = get_integer_from_var_name("value", vm, ids_data, ap_tracking)?;
     let value = value.as_ref();
     //Main logic
@@ -421,12 +439,12 @@ pub fn signed_div_rem(
         _ => {}
     }
 
-    let int_value = value.to_bigint();
-    let int_div = div.to_bigint();
-    let int_bound = bound.to_bigint();
+    let int_value = as_int(&value.to_biguint().into(), &CAIRO_PRIME);
+    let int_div: BigInt = div.to_biguint().into();
+    let int_bound: BigInt = bound.to_biguint().into();
     let (q, r) = int_value.div_mod_floor(&int_div);
 
-    if int_bound.abs() < q.abs() {
+    if q < -&int_bound || q >= int_bound {
         return Err(HintError::OutOfValidRange(Felt::new(q), bound.into_owned()));
     }
 
@@ -487,17 +505,21 @@ pub fn assert_250_bit(
     vm: &mut VirtualMachine,
     ids_data: &HashMap<String, HintReference>,
     ap_tracking: &ApTracking,
+    constants: &HashMap<String, Felt>,
 ) -> Result<(), HintError> {
-    //Declare constant values
-    let upper_bound = Felt::one().shl(250u32);
-    let shift = Felt::one().shl(128u32);
+    const UPPER_BOUND: &str = "starkware.cairo.common.math.assert_250_bit.UPPER_BOUND";
+    const SHIFT: &str = "starkware.cairo.common.math.assert_250_bit.SHIFT";
+
+    let upper_bound = get_constant_from_var_name(UPPER_BOUND, constants)?;
+    let shift = get_constant_from_var_name(SHIFT, constants)?;
     let value = get_integer_from_var_name("value", vm, ids_data, ap_tracking)?;
     //Main logic
-    //can be deleted
-    if value.as_ref() > &upper_bound {
+    if as_int(&value.to_biguint().into(), &CAIRO_PRIME).mod_floor(&CAIRO_PRIME)
+        >= upper_bound.to_bigint()
+    {
         return Err(HintError::ValueOutside250BitRange(value.into_owned()));
     }
-    let (high, low) = value.div_rem(&shift);
+    let (high, low) = value.div_rem(shift);
     insert_value_from_var_name("high", high, vm, ids_data, ap_tracking)?;
     insert_value_from_var_name("low", low, vm, ids_data, ap_tracking)
 }
@@ -1313,6 +1335,50 @@ mod tests {
         );
     }
 
+    #[test]
+    fn run_is_positive_hint_at_range_check_bound() {
+        let hint_code = "from starkware.cairo.common.math_utils import is_positive\nids.is_positive = 1 if is_positive(\n    value=ids.value, prime=PRIME, rc_bound=range_check_builtin.bound) else 0";
+        let mut vm = vm_with_range_check!();
+        //Initialize fp
+        vm.run_context.fp = 2;
+        //Insert ids.value into memory
+        //ids.value = 2**128, the range check bound
+        vm.memory = memory![((1, 0), ("340282366920938463463374607431768211456", 10))];
+        let ids_data = ids_data!["value", "is_positive"];
+        //Execute the hint
+        assert_eq!(
+            run_hint!(vm, ids_data, hint_code),
+            Err(HintError::ValueOutsideValidRange(felt_str!(
+                "340282366920938463463374607431768211456"
+            )))
+        );
+    }
+
+    #[test]
+    fn run_is_positive_hint_at_negative_range_check_bound() {
+        let hint_code = "from starkware.cairo.common.math_utils import is_positive\nids.is_positive = 1 if is_positive(\n    value=ids.value, prime=PRIME, rc_bound=range_check_builtin.bound) else 0";
+        let mut vm = vm_with_range_check!();
+        //Initialize fp
+        vm.run_context.fp = 2;
+        //Insert ids.value into memory
+        //ids.value = -2**128
+        vm.memory = memory![(
+            (1, 0),
+            (
+                "3618502788666131213697322783095070105282824848410658236509717448704103809025",
+                10
+            )
+        )];
+        let ids_data = ids_data!["value", "is_positive"];
+        //Execute the hint
+        assert_eq!(
+            run_hint!(vm, ids_data, hint_code),
+            Err(HintError::ValueOutsideValidRange(felt_str!(
+                "3618502788666131213697322783095070105282824848410658236509717448704103809025"
+            )))
+        );
+    }
+
     #[test]
     fn run_is_positive_hint_is_positive_not_empty() {
         let hint_code ="from starkware.cairo.common.math_utils import is_positive\nids.is_positive = 1 if is_positive(\n    value=ids.value, prime=PRIME, rc_bound=range_check_builtin.bound) else 0";
@@ -1537,6 +1603,40 @@ mod tests {
         )
     }
 
+    #[test]
+    fn signed_div_rem_quotient_at_bound() {
+        let hint_code = "from starkware.cairo.common.math_utils import as_int, assert_integer\n\nassert_integer(ids.div)\nassert 0 < ids.div <= PRIME // range_check_builtin.bound, \\\n    f'div={hex(ids.div)} is out of the valid range.'\n\nassert_integer(ids.bound)\nassert ids.bound <= range_check_builtin.bound // 2, \\\n    f'bound={hex(ids.bound)} is out of the valid range.'\n\nint_value = as_int(ids.value, PRIME)\nq, ids.r = divmod(int_value, ids.div)\n\nassert -ids.bound <= q < ids.bound, \\\n    f'{int_value} / {ids.div} = {q} is out of the range [{-ids.bound}, {ids.bound}).'\n\nids.biased_q = q + ids.bound";
+        let mut vm = vm_with_range_check!();
+        //Initialize fp
+        vm.run_context.fp = 6;
+        //Insert ids into memory
+        vm.memory = memory![((1, 3), 1), ((1, 4), 29), ((1, 5), 29)];
+        //Create ids
+        let ids_data = ids_data!["r", "biased_q", "range_check_ptr", "div", "value", "bound"];
+        //Execute the hint
+        assert_eq!(
+            run_hint!(vm, ids_data, hint_code),
+            Err(HintError::OutOfValidRange(Felt::new(29), Felt::new(29)))
+        )
+    }
+
+    #[test]
+    fn signed_div_rem_quotient_at_negative_bound() {
+        let hint_code = "from starkware.cairo.common.math_utils import as_int, assert_integer\n\nassert_integer(ids.div)\nassert 0 < ids.div <= PRIME // range_check_builtin.bound, \\\n    f'div={hex(ids.div)} is out of the valid range.'\n\nassert_integer(ids.bound)\nassert ids.bound <= range_check_builtin.bound // 2, \\\n    f'bound={hex(ids.bound)} is out of the valid range.'\n\nint_value = as_int(ids.value, PRIME)\nq, ids.r = divmod(int_value, ids.div)\n\nassert -ids.bound <= q < ids.bound, \\\n    f'{int_value} / {ids.div} = {q} is out of the range [{-ids.bound}, {ids.bound}).'\n\nids.biased_q = q + ids.bound";
+        let mut vm = vm_with_range_check!();
+        //Initialize fp
+        vm.run_context.fp = 6;
+        //Insert ids into memory
+        vm.memory = memory![((1, 3), 1), ((1, 4), (-29)), ((1, 5), 29)];
+        //Create ids
+        let ids_data = ids_data!["r", "biased_q", "range_check_ptr", "div", "value", "bound"];
+        //Execute the hint
+        assert_eq!(run_hint!(vm, ids_data, hint_code), Ok(()));
+        //Check hint memory inserts
+        //ids.r, ids.biased_q
+        check_memory![vm.memory, ((1, 0), 0), ((1, 1), 0)];
+    }
+
     #[test]
     fn signed_div_rem_no_range_check_builtin() {
         let hint_code = "from starkware.cairo.common.math_utils import as_int, assert_integer\n\nassert_integer(ids.div)\nassert 0 < ids.div <= PRIME // range_check_builtin.bound, \\\n    f'div={hex(ids.div)} is out of the valid range.'\n\nassert_integer(ids.bound)\nassert ids.bound <= range_check_builtin.bound // 2, \\\n    f'bound={hex(ids.bound)} is out of the valid range.'\n\nint_value = as_int(ids.value, PRIME)\nq, ids.r = divmod(int_value, ids.div)\n\nassert -ids.bound <= q < ids.bound, \\\n    f'{int_value} / {ids.div} = {q} is out of the range [{-ids.bound}, {ids.bound}).'\n\nids.biased_q = q + ids.bound";
@@ -1595,6 +1695,32 @@ mod tests {
         )
     }
 
+    fn assert_250_bit_constants() -> HashMap<String, Felt> {
+        HashMap::from([
+            (
+                "starkware.cairo.common.math.assert_250_bit.UPPER_BOUND".to_string(),
+                Felt::one().shl(250_u32),
+            ),
+            (
+                "starkware.cairo.common.math.assert_250_bit.SHIFT".to_string(),
+                Felt::one().shl(128_u32),
+            ),
+        ])
+    }
+
+    fn split_felt_constants() -> HashMap<String, Felt> {
+        HashMap::from([
+            (
+                "starkware.cairo.common.math.split_felt.MAX_HIGH".to_string(),
+                felt_str!("10633823966279327296825105735305134080"),
+            ),
+            (
+                "starkware.cairo.common.math.split_felt.MAX_LOW".to_string(),
+                Felt::zero(),
+            ),
+        ])
+    }
+
     #[test]
     fn run_assert_250_bit_valid() {
         let hint_code = "from starkware.cairo.common.math_utils import as_int\n\n# Correctness check.\nvalue = as_int(ids.value, PRIME) % PRIME\nassert value < ids.UPPER_BOUND, f'{value} is outside of the range [0, 2**250).'\n\n# Calculation for the assertion.\nids.high, ids.low = divmod(ids.value, ids.SHIFT)";
@@ -1606,7 +1732,16 @@ mod tests {
         //Create ids
         let ids_data = ids_data!["value", "high", "low"];
         //Execute the hint
-        assert_eq!(run_hint!(vm, ids_data, hint_code), Ok(()));
+        assert_eq!(
+            run_hint!(
+                vm,
+                ids_data,
+                hint_code,
+                exec_scopes_ref!(),
+                &assert_250_bit_constants()
+            ),
+            Ok(())
+        );
         //Hint would return an error if the assertion fails
         //Check ids.high and ids.low values
         check_memory![vm.memory, ((1, 1), 0), ((1, 2), 1)];
@@ -1631,11 +1766,81 @@ mod tests {
         let ids_data = ids_data!["value", "high", "low"];
         //Execute the hint
         assert_eq!(
-            run_hint!(vm, ids_data, hint_code),
+            run_hint!(
+                vm,
+                ids_data,
+                hint_code,
+                exec_scopes_ref!(),
+                &assert_250_bit_constants()
+            ),
             Err(HintError::ValueOutside250BitRange(Felt::one().shl(251_u32)))
         );
     }
 
+    #[test]
+    fn run_assert_250_bit_upper_bound() {
+        let hint_code = "from starkware.cairo.common.math_utils import as_int\n\n# Correctness check.\nvalue = as_int(ids.value, PRIME) % PRIME\nassert value < ids.UPPER_BOUND, f'{value} is outside of the range [0, 2**250).'\n\n# Calculation for the assertion.\nids.high, ids.low = divmod(ids.value, ids.SHIFT)";
+        let mut vm = vm!();
+        //Initialize fp
+        vm.run_context.fp = 3;
+        //Insert ids into memory
+        //ids.value
+        vm.memory = memory![(
+            (1, 0),
+            (
+                "1809251394333065553493296640760748560207343510400633813116524750123642650624",
+                10
+            )
+        )];
+        //Create ids
+        let ids_data = ids_data!["value", "high", "low"];
+        //Execute the hint
+        assert_eq!(
+            run_hint!(
+                vm,
+                ids_data,
+                hint_code,
+                exec_scopes_ref!(),
+                &assert_250_bit_constants()
+            ),
+            Err(HintError::ValueOutside250BitRange(Felt::one().shl(250_u32)))
+        );
+    }
+
+    #[test]
+    fn run_assert_250_bit_missing_constant() {
+        let hint_code = "from starkware.cairo.common.math_utils import as_int\n\n# Correctness check.\nvalue = as_int(ids.value, PRIME) % PRIME\nassert value < ids.UPPER_BOUND, f'{value} is outside of the range [0, 2**250).'\n\n# Calculation for the assertion.\nids.high, ids.low = divmod(ids.value, ids.SHIFT)";
+        let mut vm = vm!();
+        vm.run_context.fp = 3;
+        vm.memory = memory![((1, 0), 1)];
+        let ids_data = ids_data!["value", "high", "low"];
+        assert_eq!(
+            run_hint!(vm, ids_data, hint_code),
+            Err(HintError::MissingConstant(
+                "starkware.cairo.common.math.assert_250_bit.UPPER_BOUND"
+            ))
+        );
+    }
+
+    #[test]
+    fn run_assert_250_bit_shift_from_constants() {
+        let hint_code = "from starkware.cairo.common.math_utils import as_int\n\n# Correctness check.\nvalue = as_int(ids.value, PRIME) % PRIME\nassert value < ids.UPPER_BOUND, f'{value} is outside of the range [0, 2**250).'\n\n# Calculation for the assertion.\nids.high, ids.low = divmod(ids.value, ids.SHIFT)";
+        let mut vm = vm!();
+        vm.run_context.fp = 3;
+        vm.memory = memory![((1, 0), 1000)];
+        let ids_data = ids_data!["value", "high", "low"];
+        let mut constants = assert_250_bit_constants();
+        constants.insert(
+            "starkware.cairo.common.math.assert_250_bit.SHIFT".to_string(),
+            Felt::new(256),
+        );
+        assert_eq!(
+            run_hint!(vm, ids_data, hint_code, exec_scopes_ref!(), &constants),
+            Ok(())
+        );
+        check_memory![vm.memory, ((1, 1), 3), ((1, 2), 232)];
+    }
+
     #[test]
     fn run_split_felt_ok() {
         let hint_code =
@@ -1655,7 +1860,16 @@ mod tests {
             ("high".to_string(), HintReference::new(-3, 1, true, true)),
         ]);
         //Execute the hint
-        assert_eq!(run_hint!(vm, ids_data, hint_code), Ok(()));
+        assert_eq!(
+            run_hint!(
+                vm,
+                ids_data,
+                hint_code,
+                exec_scopes_ref!(),
+                &split_felt_constants()
+            ),
+            Ok(())
+        );
         //Check hint memory inserts
         check_memory![
             vm.memory,
@@ -1664,6 +1878,32 @@ mod tests {
         ];
     }
 
+    #[test]
+    fn run_split_felt_inconsistent_constants() {
+        let hint_code =
+        "from starkware.cairo.common.math_utils import assert_integer\nassert ids.MAX_HIGH < 2**128 and ids.MAX_LOW < 2**128\nassert PRIME - 1 == ids.MAX_HIGH * 2**128 + ids.MAX_LOW\nassert_integer(ids.value)\nids.low = ids.value & ((1 << 128) - 1)\nids.high = ids.value >> 128";
+        let mut vm = vm_with_range_check!();
+        vm.memory = memory![((1, 3), 5), ((1, 4), (2, 0))];
+        add_segments!(vm, 1);
+        vm.run_context.fp = 7;
+        let ids_data = HashMap::from([
+            ("value".to_string(), HintReference::new_simple(-4)),
+            ("low".to_string(), HintReference::new(-3, 0, true, true)),
+            ("high".to_string(), HintReference::new(-3, 1, true, true)),
+        ]);
+        let mut constants = split_felt_constants();
+        constants.insert(
+            "starkware.cairo.common.math.split_felt.MAX_LOW".to_string(),
+            Felt::one(),
+        );
+        assert_eq!(
+            run_hint!(vm, ids_data, hint_code, exec_scopes_ref!(), &constants),
+            Err(HintError::AssertionFailed(String::from(
+                "assert PRIME - 1 == ids.MAX_HIGH * 2**128 + ids.MAX_LOW"
+            )))
+        );
+    }
+
     #[test]
     fn run_split_felt_incorrect_ids() {
         let hint_code =
@@ -1680,7 +1920,13 @@ mod tests {
         let ids_data = ids_data!["low"];
         //Execute the hint
         assert_eq!(
-            run_hint!(vm, ids_data, hint_code),
+            run_hint!(
+                vm,
+                ids_data,
+                hint_code,
+                exec_scopes_ref!(),
+                &split_felt_constants()
+            ),
             Err(HintError::FailedToGetIds)
         );
     }
@@ -1706,7 +1952,13 @@ mod tests {
 
         //Execute the hint
         assert_eq!(
-            run_hint!(vm, ids_data, hint_code),
+            run_hint!(
+                vm,
+                ids_data,
+                hint_code,
+                exec_scopes_ref!(),
+                &split_felt_constants()
+            ),
             Err(HintError::Internal(VirtualMachineError::MemoryError(
                 MemoryError::InconsistentMemory(
                     MaybeRelocatable::from((2, 0)),
@@ -1738,7 +1990,13 @@ mod tests {
         ]);
         //Execute the hint
         assert_eq!(
-            run_hint!(vm, ids_data, hint_code),
+            run_hint!(
+                vm,
+                ids_data,
+                hint_code,
+                exec_scopes_ref!(),
+                &split_felt_constants()
+            ),
             Err(HintError::Internal(VirtualMachineError::MemoryError(
                 MemoryError::InconsistentMemory(
                     MaybeRelocatable::from((2, 1)),
@@ -1765,7 +2023,13 @@ mod tests {
         ]);
         //Execute the hint
         assert_eq!(
-            run_hint!(vm, ids_data, hint_code),
+            run_hint!(
+                vm,
+                ids_data,
+                hint_code,
+                exec_scopes_ref!(),
+                &split_felt_constants()
+            ),
             Err(HintError::Internal(VirtualMachineError::ExpectedInteger(
                 MaybeRelocatable::from((1, 3))
             )))
@@ -4,18 +4,24 @@ pub mod builtin_hint_processor_definition;
 pub mod cairo_keccak;
 pub mod dict_hint_utils;
 pub mod dict_manager;
+pub mod ec_utils;
 pub mod find_element_hint;
+pub mod hint_cache;
 pub mod hint_code;
+#[cfg(feature = "hint-profiling")]
+pub mod hint_profiling;
 pub mod hint_utils;
 pub mod keccak_utils;
 pub mod math_utils;
 pub mod memcpy_hint_utils;
 pub mod memset_utils;
 pub mod pow_utils;
+pub mod scope_dict;
 pub mod secp;
 pub mod segments;
 pub mod set;
 pub mod sha256_utils;
 pub mod squash_dict_utils;
+pub mod starknet_hint_utils;
 pub mod uint256_utils;
 pub mod usort;
@@ -0,0 +1,400 @@
+use crate::{
+    hint_processor::{
+        builtin_hint_processor::{
+            builtin_hint_processor_definition::HintFunc,
+            hint_utils::{
+                get_integer_from_var_name, get_ptr_from_var_name, insert_value_from_var_name,
+            },
+        },
+        hint_processor_definition::HintReference,
+    },
+    serde::deserialize_program::ApTracking,
+    types::{exec_scope::ExecutionScopes, relocatable::MaybeRelocatable},
+    vm::{
+        errors::{hint_errors::HintError, vm_errors::VirtualMachineError},
+        vm_core::VirtualMachine,
+    },
+};
+use felt::Felt;
+use num_traits::{One, ToPrimitive, Zero};
+use std::collections::HashMap;
+
+///A felt to felt map kept in the execution scopes, such as the jump destinations of an
+///interpreter written in Cairo (`jumpdests` in `ids.next_offset = jumpdests[ids.dest]`).
+///
+///The hints operating on these maps are generic over the names of the scope variable and of the
+///ids, and are registered under the exact code of each program with
+///`BuiltinHintProcessor::add_hint`:
+///```ignore
+///hint_processor.add_hint(
+///    String::from("ids.next_offset = jumpdests[ids.dest]"),
+///    Rc::new(scope_dict_read("jumpdests", "dest", "next_offset", None)),
+///);
+///```
+pub type ScopeDict = HashMap<Felt, Felt>;
+
+///Implements `dict_name = {array[i].key: array[i].value for i in range(len)}`: builds the map
+///from `len` (key, value) pairs starting at `ids.array`. Later pairs overwrite earlier ones.
+pub fn scope_dict_new_from_array(dict_name: &str, array: &str, len: &str) -> HintFunc {
+    let (dict_name, array, len) = (dict_name.to_string(), array.to_string(), len.to_string());
+    HintFunc(Box::new(
+        move |vm: &mut VirtualMachine,
+              exec_scopes: &mut ExecutionScopes,
+              ids_data: &HashMap<String, HintReference>,
+              ap_tracking: &ApTracking,
+              _constants: &HashMap<String, Felt>| {
+            let array_ptr = get_ptr_from_var_name(&array, vm, ids_data, ap_tracking)?;
+            let len = get_integer_from_var_name(&len, vm, ids_data, ap_tracking)?
+                .to_usize()
+                .ok_or(VirtualMachineError::BigintToUsizeFail)?;
+            let pairs = vm.get_integer_range(&array_ptr, 2 * len)?;
+            let dict: ScopeDict = pairs
+                .chunks(2)
+                .map(|pair| (pair[0].clone().into_owned(), pair[1].clone().into_owned()))
+                .collect();
+            exec_scopes.insert_value(&dict_name, dict);
+            Ok(())
+        },
+    ))
+}
+
+///Implements `dict_name = dict(list_name)`, where `list_name` is a `Vec<(Felt, Felt)>` of
+///(key, value) pairs in the current scope, e.g. a list taken from the program input.
+pub fn scope_dict_new_from_scope_list(dict_name: &str, list_name: &str) -> HintFunc {
+    let (dict_name, list_name) = (dict_name.to_string(), list_name.to_string());
+    HintFunc(Box::new(
+        move |_vm: &mut VirtualMachine,
+              exec_scopes: &mut ExecutionScopes,
+              _ids_data: &HashMap<String, HintReference>,
+              _ap_tracking: &ApTracking,
+              _constants: &HashMap<String, Felt>| {
+            let dict: ScopeDict = exec_scopes
+                .get_ref::<Vec<(Felt, Felt)>>(&list_name)?
+                .iter()
+                .cloned()
+                .collect();
+            exec_scopes.insert_value(&dict_name, dict);
+            Ok(())
+        },
+    ))
+}
+
+///Implements `ids.value = dict_name[ids.key]`, or `dict_name.get(ids.key, default)` when a
+///default is given.
+pub fn scope_dict_read(dict_name: &str, key: &str, value: &str, default: Option<Felt>) -> HintFunc {
+    let (dict_name, key, value) = (dict_name.to_string(), key.to_string(), value.to_string());
+    HintFunc(Box::new(
+        move |vm: &mut VirtualMachine,
+              exec_scopes: &mut ExecutionScopes,
+              ids_data: &HashMap<String, HintReference>,
+              ap_tracking: &ApTracking,
+              _constants: &HashMap<String, Felt>| {
+            let key = get_integer_from_var_name(&key, vm, ids_data, ap_tracking)?;
+            let dict = exec_scopes.get_ref::<ScopeDict>(&dict_name)?;
+            let found = match (dict.get(key.as_ref()), &default) {
+                (Some(found), _) | (None, Some(found)) => found.clone(),
+                (None, None) => {
+                    return Err(HintError::NoValueForKey(MaybeRelocatable::from(
+                        key.into_owned(),
+                    )))
+                }
+            };
+            insert_value_from_var_name(&value, found, vm, ids_data, ap_tracking)
+        },
+    ))
+}
+
+///Implements `ids.contains = 1 if ids.key in dict_name else 0`.
+pub fn scope_dict_contains(dict_name: &str, key: &str, contains: &str) -> HintFunc {
+    let (dict_name, key, contains) = (dict_name.to_string(), key.to_string(), contains.to_string());
+    HintFunc(Box::new(
+        move |vm: &mut VirtualMachine,
+              exec_scopes: &mut ExecutionScopes,
+              ids_data: &HashMap<String, HintReference>,
+              ap_tracking: &ApTracking,
+              _constants: &HashMap<String, Felt>| {
+            let key = get_integer_from_var_name(&key, vm, ids_data, ap_tracking)?;
+            let result = if exec_scopes
+                .get_ref::<ScopeDict>(&dict_name)?
+                .contains_key(key.as_ref())
+            {
+                Felt::one()
+            } else {
+                Felt::zero()
+            };
+            insert_value_from_var_name(&contains, result, vm, ids_data, ap_tracking)
+        },
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vm::runners::cairo_runner::RunResources;
+    use crate::{
+        any_box,
+        hint_processor::{
+            builtin_hint_processor::builtin_hint_processor_definition::{
+                BuiltinHintProcessor, HintProcessorData,
+            },
+            hint_processor_definition::HintProcessor,
+        },
+        types::relocatable::Relocatable,
+        utils::test_utils::*,
+        vm::{errors::memory_errors::MemoryError, vm_memory::memory::Memory},
+    };
+    use felt::NewFelt;
+    use std::{any::Any, rc::Rc};
+
+    const NEW_JUMPDESTS: &str =
+        "jumpdests = {ids.dests[i].key: ids.dests[i].value for i in range(ids.n_dests)}";
+    const READ_JUMPDEST: &str = "ids.next_offset = jumpdests[ids.dest]";
+    const READ_JUMPDEST_OR_ZERO: &str = "ids.next_offset = jumpdests.get(ids.dest, 0)";
+    const IS_JUMPDEST: &str = "ids.is_valid = 1 if ids.dest in jumpdests else 0";
+
+    fn jumpdests_hint_processor() -> BuiltinHintProcessor {
+        let mut hint_processor = BuiltinHintProcessor::new_empty();
+        hint_processor.add_hint(
+            String::from(NEW_JUMPDESTS),
+            Rc::new(scope_dict_new_from_array("jumpdests", "dests", "n_dests")),
+        );
+        hint_processor.add_hint(
+            String::from(READ_JUMPDEST),
+            Rc::new(scope_dict_read("jumpdests", "dest", "next_offset", None)),
+        );
+        hint_processor.add_hint(
+            String::from(READ_JUMPDEST_OR_ZERO),
+            Rc::new(scope_dict_read(
+                "jumpdests",
+                "dest",
+                "next_offset",
+                Some(Felt::zero()),
+            )),
+        );
+        hint_processor.add_hint(
+            String::from(IS_JUMPDEST),
+            Rc::new(scope_dict_contains("jumpdests", "dest", "is_valid")),
+        );
+        hint_processor
+    }
+
+    fn run(
+        hint_processor: &mut BuiltinHintProcessor,
+        vm: &mut VirtualMachine,
+        exec_scopes: &mut ExecutionScopes,
+        code: &str,
+        ids_data: HashMap<String, HintReference>,
+    ) -> Result<(), HintError> {
+        let hint_data = any_box!(HintProcessorData::new_default(code.to_string(), ids_data));
+        hint_processor.execute_hint(
+            vm,
+            exec_scopes,
+            &hint_data,
+            &HashMap::new(),
+            &mut RunResources::default(),
+        )
+    }
+
+    // Runs the hint building the jumpdests {3: 10, 7: 20, 9: 30} from an array with a duplicate
+    fn new_jumpdests(
+        hint_processor: &mut BuiltinHintProcessor,
+    ) -> (VirtualMachine, ExecutionScopes) {
+        let mut vm = vm!();
+        vm.memory = memory![
+            ((1, 0), (2, 0)),
+            ((1, 1), 4),
+            ((2, 0), 3),
+            ((2, 1), 10),
+            ((2, 2), 7),
+            ((2, 3), 20),
+            ((2, 4), 9),
+            ((2, 5), 5),
+            ((2, 6), 9),
+            ((2, 7), 30)
+        ];
+        run_context!(vm, 0, 2, 2);
+        let mut exec_scopes = ExecutionScopes::new();
+        let ids_data = ids_data!["dests", "n_dests"];
+        assert_eq!(
+            run(
+                hint_processor,
+                &mut vm,
+                &mut exec_scopes,
+                NEW_JUMPDESTS,
+                ids_data
+            ),
+            Ok(())
+        );
+        (vm, exec_scopes)
+    }
+
+    #[test]
+    fn new_from_array() {
+        let mut hint_processor = jumpdests_hint_processor();
+        let (_, exec_scopes) = new_jumpdests(&mut hint_processor);
+        assert_eq!(
+            exec_scopes.get_ref::<ScopeDict>("jumpdests"),
+            Ok(&HashMap::from([
+                (Felt::new(3), Felt::new(10)),
+                (Felt::new(7), Felt::new(20)),
+                (Felt::new(9), Felt::new(30)),
+            ]))
+        );
+    }
+
+    #[test]
+    fn new_from_scope_list() {
+        let mut vm = vm!();
+        let mut exec_scopes = ExecutionScopes::new();
+        exec_scopes.insert_value(
+            "program_jumpdests",
+            vec![(Felt::new(3), Felt::new(10)), (Felt::new(7), Felt::new(20))],
+        );
+        let mut hint_processor = BuiltinHintProcessor::new_empty();
+        let code = "jumpdests = dict(program_input['jumpdests'])";
+        hint_processor.add_hint(
+            String::from(code),
+            Rc::new(scope_dict_new_from_scope_list(
+                "jumpdests",
+                "program_jumpdests",
+            )),
+        );
+        assert_eq!(
+            run(
+                &mut hint_processor,
+                &mut vm,
+                &mut exec_scopes,
+                code,
+                HashMap::new()
+            ),
+            Ok(())
+        );
+        assert_eq!(
+            exec_scopes.get_ref::<ScopeDict>("jumpdests"),
+            Ok(&HashMap::from([
+                (Felt::new(3), Felt::new(10)),
+                (Felt::new(7), Felt::new(20)),
+            ]))
+        );
+    }
+
+    #[test]
+    fn new_from_scope_list_missing_list() {
+        let mut vm = vm!();
+        let mut exec_scopes = ExecutionScopes::new();
+        let mut hint_processor = BuiltinHintProcessor::new_empty();
+        let code = "jumpdests = dict(program_input['jumpdests'])";
+        hint_processor.add_hint(
+            String::from(code),
+            Rc::new(scope_dict_new_from_scope_list(
+                "jumpdests",
+                "program_jumpdests",
+            )),
+        );
+        assert_eq!(
+            run(
+                &mut hint_processor,
+                &mut vm,
+                &mut exec_scopes,
+                code,
+                HashMap::new()
+            ),
+            Err(HintError::VariableNotInScopeError(String::from(
+                "program_jumpdests"
+            )))
+        );
+    }
+
+    #[test]
+    fn read_hit_and_miss() {
+        let mut hint_processor = jumpdests_hint_processor();
+        let (mut vm, mut exec_scopes) = new_jumpdests(&mut hint_processor);
+        // ids.dest at (1, 2) and ids.next_offset at (1, 3)
+        vm.insert_value(&(1, 2).into(), 7).unwrap();
+        run_context!(vm, 0, 4, 4);
+        let ids_data = ids_data!["dest", "next_offset"];
+        assert_eq!(
+            run(
+                &mut hint_processor,
+                &mut vm,
+                &mut exec_scopes,
+                READ_JUMPDEST,
+                ids_data.clone()
+            ),
+            Ok(())
+        );
+        check_memory![vm.memory, ((1, 3), 20)];
+
+        // A missing key without a default is an error
+        vm.memory.data[1].truncate(2);
+        vm.insert_value(&(1, 2).into(), 8).unwrap();
+        assert_eq!(
+            run(
+                &mut hint_processor,
+                &mut vm,
+                &mut exec_scopes,
+                READ_JUMPDEST,
+                ids_data.clone()
+            ),
+            Err(HintError::NoValueForKey(MaybeRelocatable::from(Felt::new(
+                8
+            ))))
+        );
+        // and writes the default otherwise
+        assert_eq!(
+            run(
+                &mut hint_processor,
+                &mut vm,
+                &mut exec_scopes,
+                READ_JUMPDEST_OR_ZERO,
+                ids_data
+            ),
+            Ok(())
+        );
+        check_memory![vm.memory, ((1, 3), 0)];
+    }
+
+    #[test]
+    fn contains() {
+        let mut hint_processor = jumpdests_hint_processor();
+        let (mut vm, mut exec_scopes) = new_jumpdests(&mut hint_processor);
+        let ids_data = ids_data!["dest", "is_valid"];
+        run_context!(vm, 0, 4, 4);
+        for (dest, is_valid) in [(9, 1), (5, 0)] {
+            vm.memory.data[1].truncate(2);
+            vm.insert_value(&(1, 2).into(), dest).unwrap();
+            assert_eq!(
+                run(
+                    &mut hint_processor,
+                    &mut vm,
+                    &mut exec_scopes,
+                    IS_JUMPDEST,
+                    ids_data.clone()
+                ),
+                Ok(())
+            );
+            check_memory![vm.memory, ((1, 3), is_valid)];
+        }
+    }
+
+    #[test]
+    fn read_without_dict() {
+        let mut hint_processor = jumpdests_hint_processor();
+        let mut vm = vm!();
+        vm.memory = memory![((1, 0), 7)];
+        run_context!(vm, 0, 2, 2);
+        let mut exec_scopes = ExecutionScopes::new();
+        assert_eq!(
+            run(
+                &mut hint_processor,
+                &mut vm,
+                &mut exec_scopes,
+                READ_JUMPDEST,
+                ids_data!["dest", "next_offset"]
+            ),
+            Err(HintError::VariableNotInScopeError(String::from(
+                "jumpdests"
+            )))
+        );
+    }
+}
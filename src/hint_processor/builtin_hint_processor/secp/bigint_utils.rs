@@ -1,20 +1,46 @@
 use crate::{
     hint_processor::{
         builtin_hint_processor::{
-            hint_utils::{get_relocatable_from_var_name, insert_value_from_var_name},
+            hint_utils::{
+                check_limb_bounds, get_relocatable_from_var_name, insert_value_from_var_name,
+            },
             secp::secp_utils::{split, BASE_86},
         },
         hint_processor_definition::HintReference,
     },
     serde::deserialize_program::ApTracking,
-    types::{exec_scope::ExecutionScopes, relocatable::MaybeRelocatable},
-    vm::{
-        errors::{hint_errors::HintError, vm_errors::VirtualMachineError},
-        vm_core::VirtualMachine,
-    },
+    types::exec_scope::ExecutionScopes,
+    vm::{errors::hint_errors::HintError, vm_core::VirtualMachine},
 };
 use felt::{Felt, NewFelt};
 use std::collections::HashMap;
+
+/// The three 86-bit limbs of a Cairo `BigInt3` struct.
+#[derive(Debug, PartialEq, Eq)]
+pub struct BigInt3 {
+    pub d0: Felt,
+    pub d1: Felt,
+    pub d2: Felt,
+}
+
+impl BigInt3 {
+    /// Writes the limbs into the struct stored in `var_name`, failing with
+    /// `HintError::LimbOutOfBounds` if any of them isn't below 2**86.
+    pub fn insert_from_var_name(
+        self,
+        var_name: &str,
+        vm: &mut VirtualMachine,
+        ids_data: &HashMap<String, HintReference>,
+        ap_tracking: &ApTracking,
+    ) -> Result<(), HintError> {
+        check_limb_bounds(var_name, &[&self.d0, &self.d1, &self.d2], 86)?;
+        let addr = get_relocatable_from_var_name(var_name, vm, ids_data, ap_tracking)?;
+        vm.insert_value(&addr, self.d0)?;
+        vm.insert_value(&(addr + 1_i32), self.d1)?;
+        vm.insert_value(&(addr + 2_i32), self.d2)?;
+        Ok(())
+    }
+}
 /*
 Implements hint:
 %{
@@ -30,18 +56,17 @@ pub fn nondet_bigint3(
     ap_tracking: &ApTracking,
     constants: &HashMap<String, Felt>,
 ) -> Result<(), HintError> {
-    let res_reloc = get_relocatable_from_var_name("res", vm, ids_data, ap_tracking)?;
     let value = exec_scopes
         .get_ref::<num_bigint::BigInt>("value")?
         .to_biguint()
         .ok_or(HintError::BigIntToBigUintFail)?;
-    let arg: Vec<MaybeRelocatable> = split(&value, constants)?
-        .into_iter()
-        .map(|n| MaybeRelocatable::from(Felt::new(n)))
-        .collect();
-    vm.write_arg(&res_reloc, &arg)
-        .map_err(VirtualMachineError::MemoryError)?;
-    Ok(())
+    let [d0, d1, d2] = split(&value, constants)?;
+    BigInt3 {
+        d0: Felt::new(d0),
+        d1: Felt::new(d1),
+        d2: Felt::new(d2),
+    }
+    .insert_from_var_name("res", vm, ids_data, ap_tracking)
 }
 
 // Implements hint
@@ -78,7 +103,7 @@ mod tests {
     use crate::utils::test_utils::*;
     use crate::vm::runners::builtin_runner::RangeCheckBuiltinRunner;
     use crate::vm::vm_core::VirtualMachine;
-    use num_traits::One;
+    use num_traits::{One, Zero};
     use std::any::Any;
     use std::ops::Shl;
 
@@ -147,4 +172,26 @@ mod tests {
             Err(HintError::BigIntToBigUintFail)
         );
     }
+
+    #[test]
+    fn insert_bigint3_limb_out_of_bounds() {
+        let mut vm = vm_with_range_check!();
+        add_segments!(vm, 2);
+        run_context!(vm, 0, 6, 6);
+        let ids_data = non_continuous_ids_data![("res", 5)];
+        let bigint = BigInt3 {
+            d0: Felt::one(),
+            d1: Felt::one().shl(86_u32),
+            d2: Felt::zero(),
+        };
+        assert_eq!(
+            bigint.insert_from_var_name("res", &mut vm, &ids_data, &ApTracking::default()),
+            Err(HintError::LimbOutOfBounds {
+                struct_name: String::from("res"),
+                limb_index: 1,
+                value: Felt::one().shl(86_u32),
+                bound: Felt::one().shl(86_u32),
+            })
+        );
+    }
 }
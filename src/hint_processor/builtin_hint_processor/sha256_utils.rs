@@ -7,7 +7,7 @@ use crate::{
     },
     serde::deserialize_program::ApTracking,
     types::relocatable::MaybeRelocatable,
-    vm::errors::{hint_errors::HintError, vm_errors::VirtualMachineError},
+    vm::errors::hint_errors::HintError,
     vm::vm_core::VirtualMachine,
 };
 use felt::{Felt, NewFelt};
@@ -73,8 +73,7 @@ pub fn sha256_main(
 
     let output_base = get_ptr_from_var_name("output", vm, ids_data, ap_tracking)?;
 
-    vm.write_arg(&output_base, &output)
-        .map_err(VirtualMachineError::MemoryError)?;
+    vm.write_arg(&output_base, &output)?;
     Ok(())
 }
 
@@ -109,8 +108,7 @@ pub fn sha256_finalize(
         padding.extend_from_slice(output.as_slice());
     }
 
-    vm.write_arg(&sha256_ptr_end, &padding)
-        .map_err(VirtualMachineError::MemoryError)?;
+    vm.write_arg(&sha256_ptr_end, &padding)?;
     Ok(())
 }
 
@@ -0,0 +1,228 @@
+use crate::{
+    hint_processor::{
+        builtin_hint_processor::hint_utils::{
+            get_integer_from_var_name, insert_value_from_var_name,
+        },
+        hint_processor_definition::HintReference,
+    },
+    math_utils::pedersen_hash,
+    serde::deserialize_program::ApTracking,
+    vm::{errors::hint_errors::HintError, vm_core::VirtualMachine},
+};
+use felt::{Felt, FeltOps};
+use lazy_static::lazy_static;
+use num_bigint::BigUint;
+use num_integer::Integer;
+use num_traits::{Bounded, One, Zero};
+use sha3::{Digest, Keccak256};
+use std::collections::HashMap;
+
+const ADDR_BOUND_CONSTANT: &str = "starkware.starknet.common.storage.ADDR_BOUND";
+
+lazy_static! {
+    // The bound of the storage addresses, see starkware.starknet.public.abi.
+    static ref ADDR_BOUND: BigUint = (BigUint::one() << 251_u32) - 256_u32;
+}
+
+/*
+Implements hint:
+%{ ids.is_250 = 1 if ids.addr < 2**250 else 0 %}
+*/
+pub fn is_250_bits(
+    vm: &mut VirtualMachine,
+    ids_data: &HashMap<String, HintReference>,
+    ap_tracking: &ApTracking,
+) -> Result<(), HintError> {
+    let addr = get_integer_from_var_name("addr", vm, ids_data, ap_tracking)?;
+    let is_250 = if addr.bits() <= 250 {
+        Felt::one()
+    } else {
+        Felt::zero()
+    };
+    insert_value_from_var_name("is_250", is_250, vm, ids_data, ap_tracking)
+}
+
+/*
+Implements hint:
+%{
+    # Verify the assumptions on the relationship between 2**250, ADDR_BOUND and PRIME.
+    ADDR_BOUND = ids.ADDR_BOUND % PRIME
+    assert (2**250 < ADDR_BOUND <= 2**251) and (2 * 2**250 < PRIME) and (
+            ADDR_BOUND * 2 > PRIME), \
+        'normalize_address() cannot be used with the current constants.'
+    ids.is_small = 1 if ids.addr < ADDR_BOUND else 0
+%}
+*/
+pub fn is_addr_bounded(
+    vm: &mut VirtualMachine,
+    ids_data: &HashMap<String, HintReference>,
+    ap_tracking: &ApTracking,
+    constants: &HashMap<String, Felt>,
+) -> Result<(), HintError> {
+    let addr = get_integer_from_var_name("addr", vm, ids_data, ap_tracking)?;
+    let addr_bound = constants
+        .get(ADDR_BOUND_CONSTANT)
+        .ok_or(HintError::MissingConstant(ADDR_BOUND_CONSTANT))?
+        .to_biguint();
+    let prime = Felt::max_value().to_biguint() + 1_u32;
+    let lower_bound = BigUint::one() << 250_u32;
+    let upper_bound = BigUint::one() << 251_u32;
+    if !(lower_bound < addr_bound
+        && addr_bound <= upper_bound
+        && upper_bound < prime
+        && &addr_bound * 2_u32 > prime)
+    {
+        return Err(HintError::AssertionFailed(String::from(
+            "normalize_address() cannot be used with the current constants.",
+        )));
+    }
+    let is_small = if addr.to_biguint() < addr_bound {
+        Felt::one()
+    } else {
+        Felt::zero()
+    };
+    insert_value_from_var_name("is_small", is_small, vm, ids_data, ap_tracking)
+}
+
+/// Returns the keccak256 of the data, truncated to 250 bits so that it fits in a felt.
+/// Matches starkware.starknet.public.abi.starknet_keccak.
+pub fn starknet_keccak(data: &[u8]) -> Felt {
+    let mut hash = Keccak256::digest(data);
+    hash[0] &= 0x03;
+    Felt::from_bytes_be(&hash)
+}
+
+/// Returns the address of the storage variable with the given name, at the given keys.
+/// Matches starkware.starknet.public.abi.get_storage_var_address: the pedersen hash chain of
+/// the keys, starting from the keccak of the name, reduced modulo ADDR_BOUND.
+pub fn get_storage_var_address(storage_var_name: &str, keys: &[Felt]) -> Result<Felt, HintError> {
+    let mut res = starknet_keccak(storage_var_name.as_bytes());
+    for key in keys {
+        res = pedersen_hash(&res, key)?;
+    }
+    Ok(Felt::from(res.to_biguint().mod_floor(&ADDR_BOUND)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        any_box,
+        hint_processor::{
+            builtin_hint_processor::{
+                builtin_hint_processor_definition::{BuiltinHintProcessor, HintProcessorData},
+                hint_code,
+            },
+            hint_processor_definition::HintProcessor,
+        },
+        types::{exec_scope::ExecutionScopes, relocatable::MaybeRelocatable},
+        utils::test_utils::*,
+        vm::{errors::memory_errors::MemoryError, vm_memory::memory::Memory},
+    };
+    use felt::{felt_str, NewFelt};
+    use std::any::Any;
+
+    fn addr_bound_constants(addr_bound: Felt) -> HashMap<String, Felt> {
+        HashMap::from([(ADDR_BOUND_CONSTANT.to_string(), addr_bound)])
+    }
+
+    #[test]
+    fn run_is_250_bits() {
+        for (addr, is_250) in [
+            (Felt::new(5), 1),
+            ((Felt::one() << 250_u32) - 1_u32, 1),
+            (Felt::one() << 250_u32, 0),
+            (Felt::new(-1), 0),
+        ] {
+            let mut vm = vm!();
+            vm.run_context.fp = 2;
+            add_segments!(vm, 2);
+            vm.insert_value(&(1, 0).into(), addr).unwrap();
+            let ids_data = ids_data!["addr", "is_250"];
+            assert_eq!(run_hint!(vm, ids_data, hint_code::IS_250_BITS), Ok(()));
+            check_memory![vm.memory, ((1, 1), is_250)];
+        }
+    }
+
+    #[test]
+    fn run_is_addr_bounded() {
+        let addr_bound = Felt::from(ADDR_BOUND.clone());
+        for (addr, is_small) in [
+            (Felt::new(5), 1),
+            (addr_bound.clone() - 1_u32, 1),
+            (addr_bound.clone(), 0),
+            (Felt::new(-1), 0),
+        ] {
+            let mut vm = vm!();
+            vm.run_context.fp = 2;
+            add_segments!(vm, 2);
+            vm.insert_value(&(1, 0).into(), addr).unwrap();
+            let ids_data = ids_data!["addr", "is_small"];
+            assert_eq!(
+                run_hint!(
+                    vm,
+                    ids_data,
+                    hint_code::IS_ADDR_BOUNDED,
+                    exec_scopes_ref!(),
+                    &addr_bound_constants(addr_bound.clone())
+                ),
+                Ok(())
+            );
+            check_memory![vm.memory, ((1, 1), is_small)];
+        }
+    }
+
+    #[test]
+    fn run_is_addr_bounded_invalid_constant() {
+        let mut vm = vm!();
+        vm.run_context.fp = 2;
+        vm.memory = memory![((1, 0), 5)];
+        add_segments!(vm, 1);
+        let ids_data = ids_data!["addr", "is_small"];
+        assert_eq!(
+            run_hint!(
+                vm,
+                ids_data,
+                hint_code::IS_ADDR_BOUNDED,
+                exec_scopes_ref!(),
+                &addr_bound_constants(Felt::one() << 250_u32)
+            ),
+            Err(HintError::AssertionFailed(String::from(
+                "normalize_address() cannot be used with the current constants."
+            )))
+        );
+    }
+
+    #[test]
+    fn run_is_addr_bounded_missing_constant() {
+        let mut vm = vm!();
+        vm.run_context.fp = 2;
+        vm.memory = memory![((1, 0), 5)];
+        add_segments!(vm, 1);
+        let ids_data = ids_data!["addr", "is_small"];
+        assert_eq!(
+            run_hint!(vm, ids_data, hint_code::IS_ADDR_BOUNDED),
+            Err(HintError::MissingConstant(ADDR_BOUND_CONSTANT))
+        );
+    }
+
+    #[test]
+    fn starknet_keccak_of_selector() {
+        assert_eq!(
+            starknet_keccak(b"__execute__"),
+            felt_str!(
+                "15d40a3d6ca2ac30f4031e42be28da9b056fef9bb7357ac5e85627ee876e5ad",
+                16
+            )
+        );
+    }
+
+    #[test]
+    fn storage_var_address_without_keys() {
+        // Without keys the address is the keccak of the name, which is already below ADDR_BOUND
+        assert_eq!(
+            get_storage_var_address("balance", &[]),
+            Ok(starknet_keccak(b"balance"))
+        );
+    }
+}
@@ -1,7 +1,7 @@
 use crate::{
     hint_processor::builtin_hint_processor::hint_utils::{
-        get_integer_from_var_name, get_relocatable_from_var_name, insert_value_from_var_name,
-        insert_value_into_ap,
+        check_limb_bounds, get_integer_from_var_name, get_relocatable_from_var_name,
+        insert_value_from_var_name, insert_value_into_ap,
     },
     hint_processor::hint_processor_definition::HintReference,
     math_utils::isqrt,
@@ -9,12 +9,46 @@ use crate::{
     vm::{errors::hint_errors::HintError, vm_core::VirtualMachine},
 };
 use felt::{Felt, FeltOps, NewFelt};
+use num_bigint::BigUint;
 use num_integer::div_rem;
 use num_traits::{One, Signed, Zero};
 use std::{
     collections::HashMap,
     ops::{Shl, Shr},
 };
+
+/// The two 128-bit limbs of a Cairo `Uint256` struct.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Uint256 {
+    pub low: Felt,
+    pub high: Felt,
+}
+
+impl Uint256 {
+    /// Splits a value into its low 128 bits and the remaining high bits.
+    pub fn split(value: &BigUint) -> Self {
+        Uint256 {
+            low: Felt::new(value & BigUint::from(u128::MAX)),
+            high: Felt::new(value >> 128_u32),
+        }
+    }
+
+    /// Writes the limbs into the struct stored in `var_name`, failing with
+    /// `HintError::LimbOutOfBounds` if any of them isn't below 2**128.
+    pub fn insert_from_var_name(
+        self,
+        var_name: &str,
+        vm: &mut VirtualMachine,
+        ids_data: &HashMap<String, HintReference>,
+        ap_tracking: &ApTracking,
+    ) -> Result<(), HintError> {
+        check_limb_bounds(var_name, &[&self.low, &self.high], 128)?;
+        let addr = get_relocatable_from_var_name(var_name, vm, ids_data, ap_tracking)?;
+        vm.insert_value(&addr, self.low)?;
+        vm.insert_value(&(addr + 1_i32), self.high)?;
+        Ok(())
+    }
+}
 /*
 Implements hint:
 %{
@@ -103,7 +137,6 @@ pub fn uint256_sqrt(
     ap_tracking: &ApTracking,
 ) -> Result<(), HintError> {
     let n_addr = get_relocatable_from_var_name("n", vm, ids_data, ap_tracking)?;
-    let root_addr = get_relocatable_from_var_name("root", vm, ids_data, ap_tracking)?;
     let n_low = vm.get_integer(&n_addr)?;
     let n_high = vm.get_integer(&(n_addr + 1_usize))?;
     let n_low = n_low.as_ref();
@@ -125,9 +158,11 @@ pub fn uint256_sqrt(
             &root
         )));
     }
-    vm.insert_value(&root_addr, Felt::new(root))?;
-    vm.insert_value(&(root_addr + 1_i32), Felt::zero())
-        .map_err(HintError::Internal)
+    Uint256 {
+        low: Felt::new(root),
+        high: Felt::zero(),
+    }
+    .insert_from_var_name("root", vm, ids_data, ap_tracking)
 }
 
 /*
@@ -171,17 +206,11 @@ pub fn uint256_unsigned_div_rem(
 ) -> Result<(), HintError> {
     let a_addr = get_relocatable_from_var_name("a", vm, ids_data, ap_tracking)?;
     let div_addr = get_relocatable_from_var_name("div", vm, ids_data, ap_tracking)?;
-    let quotient_addr = get_relocatable_from_var_name("quotient", vm, ids_data, ap_tracking)?;
-    let remainder_addr = get_relocatable_from_var_name("remainder", vm, ids_data, ap_tracking)?;
 
     let a_low = vm.get_integer(&a_addr)?;
     let a_high = vm.get_integer(&(a_addr + 1_usize))?;
     let div_low = vm.get_integer(&div_addr)?;
     let div_high = vm.get_integer(&(div_addr + 1_usize))?;
-    let a_low = a_low.as_ref();
-    let a_high = a_high.as_ref();
-    let div_low = div_low.as_ref();
-    let div_high = div_high.as_ref();
 
     //Main logic
     //a = (ids.a.high << 128) + ids.a.low
@@ -193,26 +222,16 @@ pub fn uint256_unsigned_div_rem(
     //ids.remainder.low = remainder & ((1 << 128) - 1)
     //ids.remainder.high = remainder >> 128
 
-    let a = &a_high.shl(128_usize) + a_low;
-    let div = &div_high.shl(128_usize) + div_low;
+    //As in python, a and div are computed as integers rather than field elements, so that
+    //out of range limbs are caught when writing the results instead of wrapping around
+    let a = a_high.to_biguint().shl(128_u32) + a_low.to_biguint();
+    let div = div_high.to_biguint().shl(128_u32) + div_low.to_biguint();
     //a and div will always be positive numbers
     //Then, Rust div_rem equals Python divmod
     let (quotient, remainder) = div_rem(a, div);
-    let quotient_low = &quotient & &Felt::new(u128::MAX);
-    let quotient_high = quotient.shr(128);
-
-    let remainder_low = &remainder & &Felt::new(u128::MAX);
-    let remainder_high = remainder.shr(128);
-
-    //Insert ids.quotient.low
-    vm.insert_value(&quotient_addr, quotient_low)?;
-    //Insert ids.quotient.high
-    vm.insert_value(&(quotient_addr + 1_i32), quotient_high)?;
-    //Insert ids.remainder.low
-    vm.insert_value(&remainder_addr, remainder_low)?;
-    //Insert ids.remainder.high
-    vm.insert_value(&(remainder_addr + 1_i32), remainder_high)?;
-    Ok(())
+
+    Uint256::split(&quotient).insert_from_var_name("quotient", vm, ids_data, ap_tracking)?;
+    Uint256::split(&remainder).insert_from_var_name("remainder", vm, ids_data, ap_tracking)
 }
 
 #[cfg(test)]
@@ -513,6 +532,33 @@ mod tests {
         ];
     }
 
+    #[test]
+    fn run_unsigned_div_rem_quotient_high_out_of_bounds() {
+        let hint_code = "a = (ids.a.high << 128) + ids.a.low\ndiv = (ids.div.high << 128) + ids.div.low\nquotient, remainder = divmod(a, div)\n\nids.quotient.low = quotient & ((1 << 128) - 1)\nids.quotient.high = quotient >> 128\nids.remainder.low = remainder & ((1 << 128) - 1)\nids.remainder.high = remainder >> 128";
+        let mut vm = vm_with_range_check!();
+        //Initialize fp
+        vm.run_context.fp = 10;
+        //Create hint_data
+        let ids_data =
+            non_continuous_ids_data![("a", -6), ("div", -4), ("quotient", 0), ("remainder", 2)];
+        //Insert ids into memory, a.high is corrupted and doesn't fit in 128 bits
+        vm.memory = memory![((1, 4), 0), ((1, 6), 1), ((1, 7), 0)];
+        let a_high = Felt::one() << 200_usize;
+        vm.insert_value(&(1, 5).into(), a_high.clone()).unwrap();
+        //Execute the hint
+        assert_eq!(
+            run_hint!(vm, ids_data, hint_code),
+            Err(HintError::LimbOutOfBounds {
+                struct_name: String::from("quotient"),
+                limb_index: 1,
+                value: a_high,
+                bound: Felt::one() << 128_usize,
+            })
+        );
+        //Nothing is written when a limb is out of bounds
+        assert_eq!(vm.memory.get(&MaybeRelocatable::from((1, 10))), Ok(None));
+    }
+
     #[test]
     fn run_unsigned_div_rem_invalid_memory_insert() {
         let hint_code = "a = (ids.a.high << 128) + ids.a.low\ndiv = (ids.div.high << 128) + ids.div.low\nquotient, remainder = divmod(a, div)\n\nids.quotient.low = quotient & ((1 << 128) - 1)\nids.quotient.high = quotient >> 128\nids.remainder.low = remainder & ((1 << 128) - 1)\nids.remainder.high = remainder >> 128";
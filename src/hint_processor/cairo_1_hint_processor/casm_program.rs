@@ -0,0 +1,178 @@
+use crate::{
+    hint_processor::cairo_1_hint_processor::{hint::Hint, hint_processor::hint_code},
+    serde::deserialize_program::{
+        deserialize_array_of_bigint_hex, ApTracking, FlowTrackingData, HintParams, ReferenceManager,
+    },
+    types::{
+        errors::program_errors::ProgramError, program::Program, relocatable::MaybeRelocatable,
+    },
+};
+use felt::PRIME_STR;
+use serde::Deserialize;
+use std::{
+    collections::HashMap,
+    fs::File,
+    io::{BufReader, Read},
+    path::Path,
+};
+
+/// A program compiled from Sierra to CASM, as found in the `.casm` output of the Cairo 1
+/// compiler or in a compiled contract class (whose entry points are ignored).
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct CasmProgram {
+    pub prime: String,
+    #[serde(deserialize_with = "deserialize_array_of_bigint_hex")]
+    pub bytecode: Vec<MaybeRelocatable>,
+    /// The hints of the program, grouped by the pc they run at.
+    pub hints: Vec<(usize, Vec<Hint>)>,
+}
+
+impl CasmProgram {
+    pub fn from_file(path: &Path) -> Result<CasmProgram, ProgramError> {
+        let file = File::open(path)?;
+        CasmProgram::from_reader(BufReader::new(file))
+    }
+
+    pub fn from_reader(reader: impl Read) -> Result<CasmProgram, ProgramError> {
+        let casm_program: CasmProgram = serde_json::from_reader(reader)?;
+        if casm_program.prime != PRIME_STR {
+            return Err(ProgramError::PrimeDiffers(casm_program.prime));
+        }
+        Ok(casm_program)
+    }
+
+    /// Builds a `Program` which can be run with a `Cairo1HintProcessor` created from the same
+    /// hints. CASM files don't list the builtins used, so they have to be provided.
+    pub fn to_program(&self, builtins: Vec<String>) -> Result<Program, ProgramError> {
+        let hints = self
+            .hints
+            .iter()
+            .map(|(pc, hints)| {
+                let hint_params = hints
+                    .iter()
+                    .map(|hint| HintParams {
+                        code: hint_code(hint),
+                        accessible_scopes: Vec::new(),
+                        flow_tracking_data: FlowTrackingData {
+                            ap_tracking: ApTracking::new(),
+                            reference_ids: HashMap::new(),
+                        },
+                    })
+                    .collect();
+                (*pc, hint_params)
+            })
+            .collect();
+
+        Program::new(
+            builtins,
+            self.prime.clone(),
+            self.bytecode.clone(),
+            None,
+            hints,
+            ReferenceManager {
+                references: Vec::new(),
+            },
+            HashMap::new(),
+            Vec::new(),
+            None,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hint_processor::cairo_1_hint_processor::hint::{CellRef, ResOperand};
+    use crate::types::instruction::Register;
+    use felt::{felt_str, Felt, NewFelt};
+
+    const CASM_PROGRAM: &str = r#"{
+        "prime": "0x800000000000011000000000000000000000000000000000000000000000001",
+        "bytecode": ["0x480680017fff8000", "0x800000000000011000000000000000000000000000000000000000000000000", "0x208b7fff7fff7ffe"],
+        "hints": [[0, [{"AllocSegment": {"dst": {"register": "AP", "offset": 0}}}]]]
+    }"#;
+
+    #[test]
+    fn deserialize_casm_program() {
+        let casm_program = CasmProgram::from_reader(CASM_PROGRAM.as_bytes()).unwrap();
+        assert_eq!(
+            casm_program.bytecode,
+            vec![
+                MaybeRelocatable::from(Felt::new(0x480680017fff8000_i64)),
+                MaybeRelocatable::from(felt_str!(
+                    "800000000000011000000000000000000000000000000000000000000000000",
+                    16
+                )),
+                MaybeRelocatable::from(Felt::new(0x208b7fff7fff7ffe_i64)),
+            ]
+        );
+        assert_eq!(
+            casm_program.hints,
+            vec![(
+                0,
+                vec![Hint::AllocSegment {
+                    dst: CellRef {
+                        register: Register::AP,
+                        offset: 0
+                    }
+                }]
+            )]
+        );
+    }
+
+    #[test]
+    fn deserialize_casm_program_wrong_prime() {
+        let casm_program = CasmProgram::from_reader(
+            CASM_PROGRAM
+                .replace(
+                    "0x800000000000011000000000000000000000000000000000000000000000001",
+                    "0x11",
+                )
+                .as_bytes(),
+        );
+        assert!(matches!(
+            casm_program,
+            Err(ProgramError::PrimeDiffers(prime)) if prime == "0x11"
+        ));
+    }
+
+    #[test]
+    fn deserialize_casm_program_unknown_hint() {
+        let casm_program = CasmProgram::from_reader(
+            CASM_PROGRAM
+                .replace("AllocSegment", "SystemCall")
+                .as_bytes(),
+        );
+        assert!(matches!(casm_program, Err(ProgramError::Parse(_))));
+    }
+
+    #[test]
+    fn casm_program_to_program() {
+        let casm_program = CasmProgram::from_reader(CASM_PROGRAM.as_bytes()).unwrap();
+        let program = casm_program
+            .to_program(vec![String::from("range_check")])
+            .unwrap();
+        assert_eq!(program.builtins, vec![String::from("range_check")]);
+        assert_eq!(program.data, casm_program.bytecode);
+        assert_eq!(program.main, None);
+        assert_eq!(program.hints.len(), 1);
+        assert_eq!(
+            program.hints[&0][0].code,
+            r#"{"AllocSegment":{"dst":{"register":"AP","offset":0}}}"#
+        );
+    }
+
+    #[test]
+    fn casm_program_from_file() {
+        let casm_program =
+            CasmProgram::from_file(Path::new("cairo_programs/cairo-1-programs/fibonacci.casm"))
+                .unwrap();
+        assert!(matches!(
+            casm_program.hints[0].1[0],
+            Hint::TestLessThanOrEqual {
+                rhs: ResOperand::Deref(_),
+                ..
+            }
+        ));
+    }
+}
@@ -0,0 +1,219 @@
+use std::collections::HashMap;
+
+use felt::Felt;
+
+use crate::{
+    types::relocatable::{MaybeRelocatable, Relocatable},
+    vm::{errors::hint_errors::HintError, vm_core::VirtualMachine},
+};
+
+/// Tracks the contents of a single Cairo 1 dictionary.
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct DictTrackerExecScope {
+    /// The data of the dictionary.
+    data: HashMap<Felt, MaybeRelocatable>,
+    /// The index of the dictionary in the dict_infos segment.
+    idx: usize,
+}
+
+impl DictTrackerExecScope {
+    /// Creates a new tracker placed in index `idx` of the dict_infos segment.
+    pub fn new(idx: usize) -> Self {
+        DictTrackerExecScope {
+            data: HashMap::new(),
+            idx,
+        }
+    }
+}
+
+/// Allocates and tracks all the dictionaries created during a Cairo 1 run.
+/// Dictionaries are identified by the segment index of their accesses segment.
+#[derive(PartialEq, Eq, Debug, Clone, Default)]
+pub struct DictManagerExecScope {
+    trackers: HashMap<isize, DictTrackerExecScope>,
+}
+
+impl DictManagerExecScope {
+    pub const DICT_DEFAULT_VALUE: usize = 0;
+
+    /// Allocates a new segment for a new dictionary and returns the start of the segment.
+    pub fn new_default_dict(&mut self, vm: &mut VirtualMachine) -> Result<Relocatable, HintError> {
+        let dict_segment = vm.add_memory_segment();
+        if self.trackers.contains_key(&dict_segment.segment_index) {
+            return Err(HintError::CantCreateDictionaryOnTakenSegment(
+                dict_segment.segment_index,
+            ));
+        }
+        self.trackers.insert(
+            dict_segment.segment_index,
+            DictTrackerExecScope::new(self.trackers.len()),
+        );
+        Ok(dict_segment)
+    }
+
+    fn get_dict_tracker(&self, dict_end: &Relocatable) -> Result<&DictTrackerExecScope, HintError> {
+        self.trackers
+            .get(&dict_end.segment_index)
+            .ok_or(HintError::NoDictTracker(dict_end.segment_index))
+    }
+
+    fn get_dict_tracker_mut(
+        &mut self,
+        dict_end: &Relocatable,
+    ) -> Result<&mut DictTrackerExecScope, HintError> {
+        self.trackers
+            .get_mut(&dict_end.segment_index)
+            .ok_or(HintError::NoDictTracker(dict_end.segment_index))
+    }
+
+    /// Returns the index in the dict_infos segment of the dictionary `dict_end` points to.
+    pub fn get_dict_infos_index(&self, dict_end: &Relocatable) -> Result<usize, HintError> {
+        Ok(self.get_dict_tracker(dict_end)?.idx)
+    }
+
+    /// Sets the value of `key` in the dictionary `dict_end` points to.
+    pub fn insert_to_tracker(
+        &mut self,
+        dict_end: &Relocatable,
+        key: Felt,
+        value: MaybeRelocatable,
+    ) -> Result<(), HintError> {
+        self.get_dict_tracker_mut(dict_end)?.data.insert(key, value);
+        Ok(())
+    }
+
+    /// Returns the value of `key` in the dictionary `dict_end` points to, or the default value
+    /// if the key was never written.
+    pub fn get_from_tracker(
+        &self,
+        dict_end: &Relocatable,
+        key: &Felt,
+    ) -> Result<MaybeRelocatable, HintError> {
+        Ok(self
+            .get_dict_tracker(dict_end)?
+            .data
+            .get(key)
+            .cloned()
+            .unwrap_or_else(|| Self::DICT_DEFAULT_VALUE.into()))
+    }
+}
+
+/// Keeps the state of the dict squash hints between the different hints of a squash.
+#[derive(PartialEq, Eq, Debug, Clone, Default)]
+pub struct DictSquashExecScope {
+    /// Maps each key to the indices of the accesses to it, in reverse order.
+    pub access_indices: HashMap<Felt, Vec<Felt>>,
+    /// The keys left to squash, in descending order.
+    pub keys: Vec<Felt>,
+}
+
+impl DictSquashExecScope {
+    /// Returns the key currently being squashed.
+    pub fn current_key(&self) -> Option<Felt> {
+        self.keys.last().cloned()
+    }
+
+    /// Removes the current key and its access indices, which should only contain the last access.
+    pub fn pop_current_key(&mut self) -> Result<Felt, HintError> {
+        let key = self.current_key().ok_or(HintError::EmptyKeys)?;
+        match self.access_indices.get(&key) {
+            Some(key_accesses) if key_accesses.len() == 1 => (),
+            Some(_) => return Err(HintError::CurrentAccessIndicesNotEmpty),
+            None => return Err(HintError::NoKeyInAccessIndices(key)),
+        }
+        self.access_indices.remove(&key);
+        self.keys.pop();
+        Ok(key)
+    }
+
+    /// Returns the access indices of the current key.
+    pub fn current_access_indices(&mut self) -> Result<&mut Vec<Felt>, HintError> {
+        let key = self.current_key().ok_or(HintError::EmptyKeys)?;
+        self.access_indices
+            .get_mut(&key)
+            .ok_or(HintError::NoKeyInAccessIndices(key))
+    }
+
+    /// Returns the next access index of the current key.
+    pub fn current_access_index(&mut self) -> Result<Felt, HintError> {
+        self.current_access_indices()?
+            .last()
+            .cloned()
+            .ok_or(HintError::EmptyCurrentAccessIndices)
+    }
+
+    /// Removes and returns the next access index of the current key.
+    pub fn pop_current_access_index(&mut self) -> Result<Felt, HintError> {
+        self.current_access_indices()?
+            .pop()
+            .ok_or(HintError::EmptyCurrentAccessIndices)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::relocatable;
+    use crate::utils::test_utils::*;
+    use felt::NewFelt;
+
+    #[test]
+    fn new_default_dict_tracks_index() {
+        let mut vm = vm!();
+        let mut dict_manager = DictManagerExecScope::default();
+        let first = dict_manager.new_default_dict(&mut vm).unwrap();
+        let second = dict_manager.new_default_dict(&mut vm).unwrap();
+        assert_eq!(first, relocatable!(0, 0));
+        assert_eq!(second, relocatable!(1, 0));
+        assert_eq!(
+            dict_manager.get_dict_infos_index(&relocatable!(0, 6)),
+            Ok(0)
+        );
+        assert_eq!(
+            dict_manager.get_dict_infos_index(&relocatable!(1, 3)),
+            Ok(1)
+        );
+    }
+
+    #[test]
+    fn get_from_tracker_defaults_to_zero() {
+        let mut vm = vm!();
+        let mut dict_manager = DictManagerExecScope::default();
+        let dict = dict_manager.new_default_dict(&mut vm).unwrap();
+        assert_eq!(
+            dict_manager.get_from_tracker(&dict, &Felt::new(5)),
+            Ok(MaybeRelocatable::from(0))
+        );
+        dict_manager
+            .insert_to_tracker(&dict, Felt::new(5), MaybeRelocatable::from(17))
+            .unwrap();
+        assert_eq!(
+            dict_manager.get_from_tracker(&(dict + 3_usize), &Felt::new(5)),
+            Ok(MaybeRelocatable::from(17))
+        );
+    }
+
+    #[test]
+    fn access_unknown_dict() {
+        let dict_manager = DictManagerExecScope::default();
+        assert_eq!(
+            dict_manager.get_from_tracker(&relocatable!(3, 0), &Felt::new(1)),
+            Err(HintError::NoDictTracker(3))
+        );
+    }
+
+    #[test]
+    fn pop_current_key_with_pending_accesses() {
+        let mut squash = DictSquashExecScope {
+            access_indices: HashMap::from([(Felt::new(1), vec![Felt::new(2), Felt::new(0)])]),
+            keys: vec![Felt::new(1)],
+        };
+        assert_eq!(
+            squash.pop_current_key(),
+            Err(HintError::CurrentAccessIndicesNotEmpty)
+        );
+        assert_eq!(squash.pop_current_access_index(), Ok(Felt::new(0)));
+        assert_eq!(squash.pop_current_key(), Ok(Felt::new(1)));
+        assert_eq!(squash.current_key(), None);
+    }
+}
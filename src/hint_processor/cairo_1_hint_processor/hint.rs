@@ -0,0 +1,277 @@
+use crate::types::instruction::Register;
+use num_bigint::BigInt;
+use num_traits::Num;
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+
+/// A memory cell, addressed relative to `ap` or `fp`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct CellRef {
+    pub register: Register,
+    pub offset: i16,
+}
+
+/// An integer encoded as a (possibly negative) hexadecimal string, as found in CASM files.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BigIntAsHex {
+    pub value: BigInt,
+}
+
+impl Serialize for BigIntAsHex {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let hex = if self.value.sign() == num_bigint::Sign::Minus {
+            format!("-0x{:x}", -&self.value)
+        } else {
+            format!("0x{:x}", self.value)
+        };
+        serializer.serialize_str(&hex)
+    }
+}
+
+impl<'de> Deserialize<'de> for BigIntAsHex {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let hex = String::deserialize(deserializer)?;
+        let (negative, digits) = match hex.strip_prefix('-') {
+            Some(digits) => (true, digits),
+            None => (false, hex.as_str()),
+        };
+        let digits = digits
+            .strip_prefix("0x")
+            .ok_or_else(|| de::Error::custom("hex prefix error"))?;
+        let value = BigInt::from_str_radix(digits, 16).map_err(de::Error::custom)?;
+        Ok(BigIntAsHex {
+            value: if negative { -value } else { value },
+        })
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub enum Operation {
+    Add,
+    Mul,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub enum DerefOrImmediate {
+    Deref(CellRef),
+    Immediate(BigIntAsHex),
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct BinOpOperand {
+    pub op: Operation,
+    pub a: CellRef,
+    pub b: DerefOrImmediate,
+}
+
+/// An operand of a hint, which can be evaluated to a value (or to a pointer, for buffers).
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub enum ResOperand {
+    Deref(CellRef),
+    DoubleDeref(CellRef, i16),
+    Immediate(BigIntAsHex),
+    BinOp(BinOpOperand),
+}
+
+/// The hints emitted by the Sierra to CASM compiler.
+/// Unlike Cairo 0 hints, these aren't python code but structured data, and are serialized
+/// using the name of the hint as tag, ie: `{"AllocSegment":{"dst":{"register":"AP","offset":0}}}`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub enum Hint {
+    AllocSegment {
+        dst: CellRef,
+    },
+    TestLessThan {
+        lhs: ResOperand,
+        rhs: ResOperand,
+        dst: CellRef,
+    },
+    TestLessThanOrEqual {
+        lhs: ResOperand,
+        rhs: ResOperand,
+        dst: CellRef,
+    },
+    WideMul128 {
+        lhs: ResOperand,
+        rhs: ResOperand,
+        high: CellRef,
+        low: CellRef,
+    },
+    DivMod {
+        lhs: ResOperand,
+        rhs: ResOperand,
+        quotient: CellRef,
+        remainder: CellRef,
+    },
+    SquareRoot {
+        value: ResOperand,
+        dst: CellRef,
+    },
+    LinearSplit {
+        value: ResOperand,
+        scalar: ResOperand,
+        max_x: ResOperand,
+        x: CellRef,
+        y: CellRef,
+    },
+    AllocFelt252Dict {
+        segment_arena_ptr: ResOperand,
+    },
+    Felt252DictEntryInit {
+        dict_ptr: ResOperand,
+        key: ResOperand,
+    },
+    Felt252DictEntryUpdate {
+        dict_ptr: ResOperand,
+        value: ResOperand,
+    },
+    GetSegmentArenaIndex {
+        dict_end_ptr: ResOperand,
+        dict_index: CellRef,
+    },
+    InitSquashData {
+        dict_accesses: ResOperand,
+        ptr_diff: ResOperand,
+        n_accesses: ResOperand,
+        big_keys: CellRef,
+        first_key: CellRef,
+    },
+    GetCurrentAccessIndex {
+        range_check_ptr: ResOperand,
+    },
+    ShouldSkipSquashLoop {
+        should_skip_loop: CellRef,
+    },
+    GetCurrentAccessDelta {
+        index_delta_minus1: CellRef,
+    },
+    ShouldContinueSquashLoop {
+        should_continue: CellRef,
+    },
+    GetNextDictKey {
+        next_key: CellRef,
+    },
+    AssertLeFindSmallArcs {
+        range_check_ptr: ResOperand,
+        a: ResOperand,
+        b: ResOperand,
+    },
+    AssertLeIsFirstArcExcluded {
+        skip_exclude_a_flag: CellRef,
+    },
+    AssertLeIsSecondArcExcluded {
+        skip_exclude_b_minus_a: CellRef,
+    },
+    // Hints kept by the compiler for backwards compatibility
+    Felt252DictRead {
+        dict_ptr: ResOperand,
+        key: ResOperand,
+        value_dst: CellRef,
+    },
+    Felt252DictWrite {
+        dict_ptr: ResOperand,
+        key: ResOperand,
+        value: ResOperand,
+    },
+    AssertCurrentAccessIndicesIsEmpty,
+    AssertAllAccessesUsed {
+        n_used_accesses: CellRef,
+    },
+    AssertAllKeysUsed,
+    AssertLeAssertThirdArcExcluded,
+    AssertLtAssertValidInput {
+        a: ResOperand,
+        b: ResOperand,
+    },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn deserialize_alloc_segment() {
+        let hint: Hint =
+            serde_json::from_str(r#"{"AllocSegment":{"dst":{"register":"AP","offset":0}}}"#)
+                .unwrap();
+        assert_eq!(
+            hint,
+            Hint::AllocSegment {
+                dst: CellRef {
+                    register: Register::AP,
+                    offset: 0
+                }
+            }
+        );
+    }
+
+    #[test]
+    fn deserialize_res_operands() {
+        let hint: Hint = serde_json::from_str(
+            r#"{"TestLessThan":{
+                "lhs":{"BinOp":{"op":"Add","a":{"register":"FP","offset":-3},"b":{"Immediate":"-0x1"}}},
+                "rhs":{"DoubleDeref":[{"register":"AP","offset":-1},2]},
+                "dst":{"register":"AP","offset":0}
+            }}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            hint,
+            Hint::TestLessThan {
+                lhs: ResOperand::BinOp(BinOpOperand {
+                    op: Operation::Add,
+                    a: CellRef {
+                        register: Register::FP,
+                        offset: -3
+                    },
+                    b: DerefOrImmediate::Immediate(BigIntAsHex {
+                        value: BigInt::from(-1)
+                    }),
+                }),
+                rhs: ResOperand::DoubleDeref(
+                    CellRef {
+                        register: Register::AP,
+                        offset: -1
+                    },
+                    2
+                ),
+                dst: CellRef {
+                    register: Register::AP,
+                    offset: 0
+                },
+            }
+        );
+    }
+
+    #[test]
+    fn deserialize_unit_hint() {
+        let hint: Hint = serde_json::from_str(r#""AssertAllKeysUsed""#).unwrap();
+        assert_eq!(hint, Hint::AssertAllKeysUsed);
+    }
+
+    #[test]
+    fn deserialize_immediate_without_prefix_fails() {
+        let hint: Result<Hint, _> = serde_json::from_str(
+            r#"{"SquareRoot":{"value":{"Immediate":"10"},"dst":{"register":"AP","offset":0}}}"#,
+        );
+        assert!(hint.is_err());
+    }
+
+    #[test]
+    fn serialize_and_deserialize_hint() {
+        let hint = Hint::SquareRoot {
+            value: ResOperand::Immediate(BigIntAsHex {
+                value: BigInt::from(-26),
+            }),
+            dst: CellRef {
+                register: Register::FP,
+                offset: 1,
+            },
+        };
+        let serialized = serde_json::to_string(&hint).unwrap();
+        assert_eq!(
+            serialized,
+            r#"{"SquareRoot":{"value":{"Immediate":"-0x1a"},"dst":{"register":"FP","offset":1}}}"#
+        );
+        assert_eq!(serde_json::from_str::<Hint>(&serialized).unwrap(), hint);
+    }
+}
@@ -0,0 +1,1061 @@
+use crate::{
+    any_box,
+    hint_processor::{
+        cairo_1_hint_processor::{
+            dict_manager::{DictManagerExecScope, DictSquashExecScope},
+            hint::{BinOpOperand, CellRef, DerefOrImmediate, Hint, Operation, ResOperand},
+        },
+        hint_processor_definition::{HintProcessor, HintReference},
+    },
+    serde::deserialize_program::ApTracking,
+    types::{
+        exec_scope::ExecutionScopes,
+        instruction::Register,
+        relocatable::{MaybeRelocatable, Relocatable},
+    },
+    vm::{
+        errors::{hint_errors::HintError, vm_errors::VirtualMachineError},
+        runners::cairo_runner::RunResources,
+        vm_core::VirtualMachine,
+    },
+};
+use felt::{Felt, FeltOps, NewFelt};
+use num_bigint::BigUint;
+use num_traits::{One, ToPrimitive, Zero};
+use std::{any::Any, collections::HashMap};
+
+const DICT_MANAGER_SCOPE: &str = "dict_manager_exec_scope";
+const DICT_SQUASH_SCOPE: &str = "dict_squash_exec_scope";
+const EXCLUDED_ARC: &str = "excluded_arc";
+
+/// Size of each access in a dict segment: key, previous value and new value.
+const DICT_ACCESS_SIZE: usize = 3;
+/// Size of the info of each dict in the segment arena: start, end and finalization index.
+const DICT_INFO_SIZE: usize = 3;
+
+/// HintProcessor for the structured hints of programs compiled from Cairo 1 (Sierra) to CASM.
+/// The hints of a CASM program are stored in `Program::hints` as their JSON encoding, which is
+/// used to find them back when compiling them.
+pub struct Cairo1HintProcessor {
+    string_to_hint: HashMap<String, Hint>,
+}
+
+impl Cairo1HintProcessor {
+    /// Creates a processor for the hints of a CASM program, given as a list of hints per pc.
+    pub fn new(hints: &[(usize, Vec<Hint>)]) -> Self {
+        Cairo1HintProcessor {
+            string_to_hint: hints
+                .iter()
+                .flat_map(|(_, hints)| hints)
+                .map(|hint| (hint_code(hint), hint.clone()))
+                .collect(),
+        }
+    }
+
+    /// Executes a single Cairo 1 hint.
+    pub fn execute(
+        &self,
+        vm: &mut VirtualMachine,
+        exec_scopes: &mut ExecutionScopes,
+        hint: &Hint,
+    ) -> Result<(), HintError> {
+        match hint {
+            Hint::AllocSegment { dst } => {
+                let segment = vm.add_memory_segment();
+                insert_value_to_cellref(vm, dst, segment)
+            }
+            Hint::TestLessThan { lhs, rhs, dst } => {
+                let result = get_val(vm, lhs)? < get_val(vm, rhs)?;
+                insert_value_to_cellref(vm, dst, Felt::new(result as u8))
+            }
+            Hint::TestLessThanOrEqual { lhs, rhs, dst } => {
+                let result = get_val(vm, lhs)? <= get_val(vm, rhs)?;
+                insert_value_to_cellref(vm, dst, Felt::new(result as u8))
+            }
+            Hint::WideMul128 {
+                lhs,
+                rhs,
+                high,
+                low,
+            } => {
+                let product = get_val(vm, lhs)?.to_biguint() * get_val(vm, rhs)?.to_biguint();
+                insert_value_to_cellref(vm, high, Felt::from(&product >> 128_u32))?;
+                insert_value_to_cellref(vm, low, Felt::from(product & BigUint::from(u128::MAX)))
+            }
+            Hint::DivMod {
+                lhs,
+                rhs,
+                quotient,
+                remainder,
+            } => {
+                let lhs = get_val(vm, lhs)?.to_biguint();
+                let rhs = get_val(vm, rhs)?.to_biguint();
+                if rhs.is_zero() {
+                    return Err(VirtualMachineError::DividedByZero.into());
+                }
+                insert_value_to_cellref(vm, quotient, Felt::from(&lhs / &rhs))?;
+                insert_value_to_cellref(vm, remainder, Felt::from(lhs % rhs))
+            }
+            Hint::SquareRoot { value, dst } => {
+                let root = get_val(vm, value)?.sqrt();
+                insert_value_to_cellref(vm, dst, root)
+            }
+            Hint::LinearSplit {
+                value,
+                scalar,
+                max_x,
+                x,
+                y,
+            } => {
+                let value = get_val(vm, value)?.to_biguint();
+                let scalar = get_val(vm, scalar)?.to_biguint();
+                let max_x = get_val(vm, max_x)?.to_biguint();
+                if scalar.is_zero() {
+                    return Err(VirtualMachineError::DividedByZero.into());
+                }
+                let x_value = (&value / &scalar).min(max_x);
+                let y_value = value - &x_value * scalar;
+                insert_value_to_cellref(vm, x, Felt::from(x_value))?;
+                insert_value_to_cellref(vm, y, Felt::from(y_value))
+            }
+            Hint::AllocFelt252Dict { segment_arena_ptr } => {
+                alloc_felt_252_dict(vm, exec_scopes, segment_arena_ptr)
+            }
+            Hint::Felt252DictEntryInit { dict_ptr, key } => {
+                let dict_address = extract_relocatable(vm, dict_ptr)?;
+                let key = get_val(vm, key)?;
+                let prev_value = exec_scopes
+                    .get_ref::<DictManagerExecScope>(DICT_MANAGER_SCOPE)?
+                    .get_from_tracker(&dict_address, &key)?;
+                vm.insert_value(&(dict_address + 1_usize), prev_value)
+                    .map_err(HintError::Internal)
+            }
+            Hint::Felt252DictEntryUpdate { dict_ptr, value } => {
+                let (dict_base, dict_offset) = extract_buffer(dict_ptr)?;
+                let dict_address = get_ptr(vm, dict_base, &dict_offset)?;
+                // The entry being updated is the last one, its key is 3 cells before its end
+                let key = get_double_deref_val(vm, dict_base, &(dict_offset - Felt::new(3)))?;
+                let value = get_maybe(vm, value)?;
+                exec_scopes
+                    .get_mut_ref::<DictManagerExecScope>(DICT_MANAGER_SCOPE)?
+                    .insert_to_tracker(&dict_address, key, value)
+            }
+            Hint::GetSegmentArenaIndex {
+                dict_end_ptr,
+                dict_index,
+            } => {
+                let dict_address = extract_relocatable(vm, dict_end_ptr)?;
+                let dict_infos_index = exec_scopes
+                    .get_ref::<DictManagerExecScope>(DICT_MANAGER_SCOPE)?
+                    .get_dict_infos_index(&dict_address)?;
+                insert_value_to_cellref(vm, dict_index, Felt::new(dict_infos_index))
+            }
+            Hint::InitSquashData {
+                dict_accesses,
+                n_accesses,
+                first_key,
+                big_keys,
+                ..
+            } => init_squash_data(
+                vm,
+                exec_scopes,
+                dict_accesses,
+                n_accesses,
+                first_key,
+                big_keys,
+            ),
+            Hint::GetCurrentAccessIndex { range_check_ptr } => {
+                let range_check_ptr = extract_relocatable(vm, range_check_ptr)?;
+                let current_access_index = exec_scopes
+                    .get_mut_ref::<DictSquashExecScope>(DICT_SQUASH_SCOPE)?
+                    .current_access_index()?;
+                vm.insert_value(&range_check_ptr, current_access_index)
+                    .map_err(HintError::Internal)
+            }
+            Hint::ShouldSkipSquashLoop { should_skip_loop } => {
+                // The loop checks each pair of consecutive accesses, so it is skipped when only
+                // one access is left
+                let should_skip = exec_scopes
+                    .get_mut_ref::<DictSquashExecScope>(DICT_SQUASH_SCOPE)?
+                    .current_access_indices()?
+                    .len()
+                    <= 1;
+                insert_value_to_cellref(vm, should_skip_loop, Felt::new(should_skip as u8))
+            }
+            Hint::GetCurrentAccessDelta { index_delta_minus1 } => {
+                let squash_scope =
+                    exec_scopes.get_mut_ref::<DictSquashExecScope>(DICT_SQUASH_SCOPE)?;
+                let prev_access_index = squash_scope.pop_current_access_index()?;
+                let index_delta_minus1_value =
+                    squash_scope.current_access_index()? - prev_access_index - Felt::one();
+                insert_value_to_cellref(vm, index_delta_minus1, index_delta_minus1_value)
+            }
+            Hint::ShouldContinueSquashLoop { should_continue } => {
+                let should_continue_loop = exec_scopes
+                    .get_mut_ref::<DictSquashExecScope>(DICT_SQUASH_SCOPE)?
+                    .current_access_indices()?
+                    .len()
+                    > 1;
+                insert_value_to_cellref(vm, should_continue, Felt::new(should_continue_loop as u8))
+            }
+            Hint::GetNextDictKey { next_key } => {
+                let squash_scope =
+                    exec_scopes.get_mut_ref::<DictSquashExecScope>(DICT_SQUASH_SCOPE)?;
+                squash_scope.pop_current_key()?;
+                let key = squash_scope.current_key().ok_or(HintError::EmptyKeys)?;
+                insert_value_to_cellref(vm, next_key, key)
+            }
+            Hint::AssertLeFindSmallArcs {
+                range_check_ptr,
+                a,
+                b,
+            } => assert_le_find_small_arcs(vm, exec_scopes, range_check_ptr, a, b),
+            Hint::AssertLeIsFirstArcExcluded {
+                skip_exclude_a_flag,
+            } => {
+                let excluded_arc: i32 = exec_scopes.get(EXCLUDED_ARC)?;
+                insert_value_to_cellref(
+                    vm,
+                    skip_exclude_a_flag,
+                    Felt::new((excluded_arc != 0) as u8),
+                )
+            }
+            Hint::AssertLeIsSecondArcExcluded {
+                skip_exclude_b_minus_a,
+            } => {
+                let excluded_arc: i32 = exec_scopes.get(EXCLUDED_ARC)?;
+                insert_value_to_cellref(
+                    vm,
+                    skip_exclude_b_minus_a,
+                    Felt::new((excluded_arc != 1) as u8),
+                )
+            }
+            Hint::Felt252DictRead {
+                dict_ptr,
+                key,
+                value_dst,
+            } => {
+                let dict_address = extract_relocatable(vm, dict_ptr)?;
+                let key = get_val(vm, key)?;
+                let value = exec_scopes
+                    .get_ref::<DictManagerExecScope>(DICT_MANAGER_SCOPE)?
+                    .get_from_tracker(&dict_address, &key)?;
+                insert_value_to_cellref(vm, value_dst, value)
+            }
+            Hint::Felt252DictWrite {
+                dict_ptr,
+                key,
+                value,
+            } => {
+                let dict_address = extract_relocatable(vm, dict_ptr)?;
+                let key = get_val(vm, key)?;
+                let value = get_maybe(vm, value)?;
+                let dict_manager =
+                    exec_scopes.get_mut_ref::<DictManagerExecScope>(DICT_MANAGER_SCOPE)?;
+                let prev_value = dict_manager.get_from_tracker(&dict_address, &key)?;
+                vm.insert_value(&(dict_address + 1_usize), prev_value)?;
+                dict_manager.insert_to_tracker(&dict_address, key, value)
+            }
+            // These hints only perform assertions which are already enforced by the code
+            Hint::AssertCurrentAccessIndicesIsEmpty
+            | Hint::AssertAllAccessesUsed { .. }
+            | Hint::AssertAllKeysUsed
+            | Hint::AssertLeAssertThirdArcExcluded
+            | Hint::AssertLtAssertValidInput { .. } => Ok(()),
+        }
+    }
+}
+
+impl HintProcessor for Cairo1HintProcessor {
+    fn execute_hint(
+        &mut self,
+        vm: &mut VirtualMachine,
+        exec_scopes: &mut ExecutionScopes,
+        hint_data: &Box<dyn Any>,
+        _constants: &HashMap<String, Felt>,
+        _run_resources: &mut RunResources,
+    ) -> Result<(), HintError> {
+        let hint = hint_data
+            .downcast_ref::<Hint>()
+            .ok_or(HintError::WrongHintData)?;
+        self.execute(vm, exec_scopes, hint)
+    }
+
+    fn compile_hint(
+        &self,
+        hint_code: &str,
+        _ap_tracking_data: &ApTracking,
+        _reference_ids: &HashMap<String, usize>,
+        _references: &HashMap<usize, HintReference>,
+    ) -> Result<Box<dyn Any>, VirtualMachineError> {
+        let hint = self
+            .string_to_hint
+            .get(hint_code)
+            .ok_or_else(|| VirtualMachineError::CompileHintFail(hint_code.to_string()))?;
+        Ok(any_box!(hint.clone()))
+    }
+}
+
+/// Returns the code under which a Cairo 1 hint is stored in a `Program`.
+pub fn hint_code(hint: &Hint) -> String {
+    // Serializing the hint types can't fail, as they are only made of structs, enums and strings
+    serde_json::to_string(hint).unwrap_or_default()
+}
+
+fn alloc_felt_252_dict(
+    vm: &mut VirtualMachine,
+    exec_scopes: &mut ExecutionScopes,
+    segment_arena_ptr: &ResOperand,
+) -> Result<(), HintError> {
+    // The segment arena ends with [dict_infos_base, n_dicts, n_destructed]
+    let segment_arena = extract_relocatable(vm, segment_arena_ptr)?;
+    let n_dicts = vm
+        .get_integer(&segment_arena.sub_usize(2)?)?
+        .to_usize()
+        .ok_or(HintError::BigintToUsizeFail)?;
+    let dict_infos_base = vm.get_relocatable(&segment_arena.sub_usize(3)?)?;
+
+    if exec_scopes
+        .get_ref::<DictManagerExecScope>(DICT_MANAGER_SCOPE)
+        .is_err()
+    {
+        exec_scopes.assign_or_update_variable(
+            DICT_MANAGER_SCOPE,
+            any_box!(DictManagerExecScope::default()),
+        );
+    }
+    let new_dict_segment = exec_scopes
+        .get_mut_ref::<DictManagerExecScope>(DICT_MANAGER_SCOPE)?
+        .new_default_dict(vm)?;
+    vm.insert_value(
+        &(dict_infos_base + DICT_INFO_SIZE * n_dicts),
+        new_dict_segment,
+    )
+    .map_err(HintError::Internal)
+}
+
+fn init_squash_data(
+    vm: &mut VirtualMachine,
+    exec_scopes: &mut ExecutionScopes,
+    dict_accesses: &ResOperand,
+    n_accesses: &ResOperand,
+    first_key: &CellRef,
+    big_keys: &CellRef,
+) -> Result<(), HintError> {
+    let dict_accesses = extract_relocatable(vm, dict_accesses)?;
+    let n_accesses = get_val(vm, n_accesses)?
+        .to_usize()
+        .ok_or(HintError::BigintToUsizeFail)?;
+
+    let mut squash_scope = DictSquashExecScope::default();
+    for i in 0..n_accesses {
+        let key = vm
+            .get_integer(&(dict_accesses + i * DICT_ACCESS_SIZE))?
+            .into_owned();
+        squash_scope
+            .access_indices
+            .entry(key)
+            .or_default()
+            .push(Felt::new(i));
+    }
+    // Accesses are popped from the back, so they are kept in reverse order
+    for indices in squash_scope.access_indices.values_mut() {
+        indices.reverse();
+    }
+    squash_scope.keys = squash_scope.access_indices.keys().cloned().collect();
+    squash_scope.keys.sort_by(|a, b| b.cmp(a));
+
+    // big_keys tells the code whether all the keys fit in a range check
+    let range_check_bound = Felt::new(u128::MAX) + Felt::one();
+    let biggest_key = squash_scope.keys.first().ok_or(HintError::EmptyKeys)?;
+    insert_value_to_cellref(
+        vm,
+        big_keys,
+        Felt::new((*biggest_key >= range_check_bound) as u8),
+    )?;
+    let first = squash_scope.current_key().ok_or(HintError::EmptyKeys)?;
+    insert_value_to_cellref(vm, first_key, first)?;
+
+    exec_scopes.assign_or_update_variable(DICT_SQUASH_SCOPE, any_box!(squash_scope));
+    Ok(())
+}
+
+fn assert_le_find_small_arcs(
+    vm: &mut VirtualMachine,
+    exec_scopes: &mut ExecutionScopes,
+    range_check_ptr: &ResOperand,
+    a: &ResOperand,
+    b: &ResOperand,
+) -> Result<(), HintError> {
+    let a = get_val(vm, a)?;
+    let b = get_val(vm, b)?;
+    let mut lengths_and_indices = [
+        (a.clone(), 0_i32),
+        (b.clone() - a, 1_i32),
+        (Felt::new(-1) - b, 2_i32),
+    ];
+    lengths_and_indices.sort();
+    exec_scopes.assign_or_update_variable(EXCLUDED_ARC, any_box!(lengths_and_indices[2].1));
+
+    // ceil((PRIME / 3) / 2 ** 128)
+    let prime_over_3_high = BigUint::from(3544607988759775765608368578435044694_u128);
+    // ceil((PRIME / 2) / 2 ** 128)
+    let prime_over_2_high = BigUint::from(5316911983139663648412552867652567041_u128);
+    let range_check_ptr = extract_relocatable(vm, range_check_ptr)?;
+    let first_arc = lengths_and_indices[0].0.to_biguint();
+    let second_arc = lengths_and_indices[1].0.to_biguint();
+    let values = [
+        &first_arc % &prime_over_3_high,
+        first_arc / prime_over_3_high,
+        &second_arc % &prime_over_2_high,
+        second_arc / prime_over_2_high,
+    ];
+    for (i, value) in values.into_iter().enumerate() {
+        vm.insert_value(&(range_check_ptr + i), Felt::from(value))?;
+    }
+    Ok(())
+}
+
+fn cell_ref_to_relocatable(cell_ref: &CellRef, vm: &VirtualMachine) -> Relocatable {
+    let base = match cell_ref.register {
+        Register::AP => vm.get_ap(),
+        Register::FP => vm.get_fp(),
+    };
+    base + cell_ref.offset as i32
+}
+
+fn insert_value_to_cellref(
+    vm: &mut VirtualMachine,
+    cell_ref: &CellRef,
+    value: impl Into<MaybeRelocatable>,
+) -> Result<(), HintError> {
+    vm.insert_value(&cell_ref_to_relocatable(cell_ref, vm), value)
+        .map_err(HintError::Internal)
+}
+
+fn get_cell_val(vm: &VirtualMachine, cell_ref: &CellRef) -> Result<Felt, HintError> {
+    Ok(vm
+        .get_integer(&cell_ref_to_relocatable(cell_ref, vm))?
+        .into_owned())
+}
+
+fn get_maybe_from_addr(
+    vm: &VirtualMachine,
+    addr: Relocatable,
+) -> Result<MaybeRelocatable, HintError> {
+    vm.get_maybe(&addr)?
+        .ok_or_else(|| VirtualMachineError::MemoryGet(addr.into()).into())
+}
+
+/// Returns the pointer stored in `cell_ref`, plus `offset`.
+fn get_ptr(
+    vm: &VirtualMachine,
+    cell_ref: &CellRef,
+    offset: &Felt,
+) -> Result<Relocatable, HintError> {
+    Ok(vm
+        .get_relocatable(&cell_ref_to_relocatable(cell_ref, vm))?
+        .add_int(offset)?)
+}
+
+fn get_double_deref_val(
+    vm: &VirtualMachine,
+    cell_ref: &CellRef,
+    offset: &Felt,
+) -> Result<Felt, HintError> {
+    Ok(vm
+        .get_integer(&get_ptr(vm, cell_ref, offset)?)?
+        .into_owned())
+}
+
+/// Splits an operand pointing to a buffer into the cell holding the pointer and an offset.
+fn extract_buffer(buffer: &ResOperand) -> Result<(&CellRef, Felt), HintError> {
+    match buffer {
+        ResOperand::Deref(cell_ref) => Ok((cell_ref, Felt::zero())),
+        ResOperand::BinOp(BinOpOperand {
+            op: Operation::Add,
+            a,
+            b: DerefOrImmediate::Immediate(offset),
+        }) => Ok((a, Felt::from(&offset.value))),
+        _ => Err(HintError::InvalidBufferOperand(format!("{buffer:?}"))),
+    }
+}
+
+fn extract_relocatable(vm: &VirtualMachine, buffer: &ResOperand) -> Result<Relocatable, HintError> {
+    let (cell_ref, offset) = extract_buffer(buffer)?;
+    get_ptr(vm, cell_ref, &offset)
+}
+
+fn get_deref_or_immediate(
+    vm: &VirtualMachine,
+    operand: &DerefOrImmediate,
+) -> Result<Felt, HintError> {
+    match operand {
+        DerefOrImmediate::Deref(cell_ref) => get_cell_val(vm, cell_ref),
+        DerefOrImmediate::Immediate(value) => Ok(Felt::from(&value.value)),
+    }
+}
+
+/// Evaluates an operand which is expected to be an integer.
+fn get_val(vm: &VirtualMachine, operand: &ResOperand) -> Result<Felt, HintError> {
+    match operand {
+        ResOperand::Deref(cell_ref) => get_cell_val(vm, cell_ref),
+        ResOperand::DoubleDeref(cell_ref, offset) => {
+            get_double_deref_val(vm, cell_ref, &Felt::new(*offset))
+        }
+        ResOperand::Immediate(value) => Ok(Felt::from(&value.value)),
+        ResOperand::BinOp(bin_op) => {
+            let a = get_cell_val(vm, &bin_op.a)?;
+            let b = get_deref_or_immediate(vm, &bin_op.b)?;
+            Ok(match bin_op.op {
+                Operation::Add => a + b,
+                Operation::Mul => a * b,
+            })
+        }
+    }
+}
+
+/// Evaluates an operand which may be either an integer or a pointer.
+fn get_maybe(vm: &VirtualMachine, operand: &ResOperand) -> Result<MaybeRelocatable, HintError> {
+    match operand {
+        ResOperand::Deref(cell_ref) => {
+            get_maybe_from_addr(vm, cell_ref_to_relocatable(cell_ref, vm))
+        }
+        ResOperand::DoubleDeref(cell_ref, offset) => {
+            get_maybe_from_addr(vm, get_ptr(vm, cell_ref, &Felt::new(*offset))?)
+        }
+        ResOperand::Immediate(value) => Ok(Felt::from(&value.value).into()),
+        ResOperand::BinOp(bin_op) => {
+            let b = get_deref_or_immediate(vm, &bin_op.b)?;
+            match bin_op.op {
+                Operation::Add => {
+                    let a = get_maybe_from_addr(vm, cell_ref_to_relocatable(&bin_op.a, vm))?;
+                    Ok(a.add_int(&b)?)
+                }
+                Operation::Mul => Ok((get_cell_val(vm, &bin_op.a)? * b).into()),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hint_processor::cairo_1_hint_processor::hint::BigIntAsHex;
+    use crate::utils::test_utils::*;
+    use crate::vm::vm_memory::memory::Memory;
+    use crate::{relocatable, vm::errors::memory_errors::MemoryError};
+    use num_bigint::BigInt;
+
+    fn ap(offset: i16) -> CellRef {
+        CellRef {
+            register: Register::AP,
+            offset,
+        }
+    }
+
+    fn fp(offset: i16) -> CellRef {
+        CellRef {
+            register: Register::FP,
+            offset,
+        }
+    }
+
+    fn immediate(value: i128) -> ResOperand {
+        ResOperand::Immediate(BigIntAsHex {
+            value: BigInt::from(value),
+        })
+    }
+
+    fn deref_plus(cell_ref: CellRef, value: i128) -> ResOperand {
+        ResOperand::BinOp(BinOpOperand {
+            op: Operation::Add,
+            a: cell_ref,
+            b: DerefOrImmediate::Immediate(BigIntAsHex {
+                value: BigInt::from(value),
+            }),
+        })
+    }
+
+    fn run_hints(
+        vm: &mut VirtualMachine,
+        exec_scopes: &mut ExecutionScopes,
+        hints: &[Hint],
+    ) -> Result<(), HintError> {
+        let mut hint_processor = Cairo1HintProcessor::new(&[(0, hints.to_vec())]);
+        for hint in hints {
+            let hint_data = hint_processor
+                .compile_hint(
+                    &hint_code(hint),
+                    &ApTracking::new(),
+                    &HashMap::new(),
+                    &HashMap::new(),
+                )
+                .unwrap();
+            hint_processor.execute_hint(
+                vm,
+                exec_scopes,
+                &hint_data,
+                &HashMap::new(),
+                &mut RunResources::default(),
+            )?;
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn compile_unknown_hint() {
+        let hint_processor = Cairo1HintProcessor::new(&[]);
+        let hint = Hint::AllocSegment { dst: ap(0) };
+        assert_eq!(
+            hint_processor
+                .compile_hint(
+                    &hint_code(&hint),
+                    &ApTracking::new(),
+                    &HashMap::new(),
+                    &HashMap::new()
+                )
+                .err(),
+            Some(VirtualMachineError::CompileHintFail(hint_code(&hint)))
+        );
+    }
+
+    #[test]
+    fn execute_wrong_hint_data() {
+        let mut vm = vm!();
+        let mut hint_processor = Cairo1HintProcessor::new(&[]);
+        assert_eq!(
+            hint_processor.execute_hint(
+                &mut vm,
+                exec_scopes_ref!(),
+                &any_box!(String::from("AllocSegment")),
+                &HashMap::new(),
+                &mut RunResources::default()
+            ),
+            Err(HintError::WrongHintData)
+        );
+    }
+
+    #[test]
+    fn alloc_segment() {
+        let mut vm = vm!();
+        add_segments!(vm, 2);
+        vm.set_ap(0);
+        run_hints(
+            &mut vm,
+            exec_scopes_ref!(),
+            &[Hint::AllocSegment { dst: ap(0) }],
+        )
+        .unwrap();
+        assert_eq!(vm.segments.num_segments, 3);
+        check_memory![vm.memory, ((1, 0), (2, 0))];
+    }
+
+    #[test]
+    fn test_less_than_and_less_than_or_equal() {
+        let mut vm = vm!();
+        vm.memory = memory![((1, 0), 7), ((1, 1), 7)];
+        vm.set_ap(2);
+        vm.set_fp(2);
+        run_hints(
+            &mut vm,
+            exec_scopes_ref!(),
+            &[
+                Hint::TestLessThan {
+                    lhs: ResOperand::Deref(fp(-2)),
+                    rhs: ResOperand::Deref(fp(-1)),
+                    dst: ap(0),
+                },
+                Hint::TestLessThanOrEqual {
+                    lhs: ResOperand::Deref(fp(-2)),
+                    rhs: ResOperand::Deref(fp(-1)),
+                    dst: ap(1),
+                },
+                Hint::TestLessThan {
+                    lhs: immediate(-1),
+                    rhs: deref_plus(fp(-1), 3),
+                    dst: ap(2),
+                },
+            ],
+        )
+        .unwrap();
+        check_memory![vm.memory, ((1, 2), 0), ((1, 3), 1), ((1, 4), 0)];
+    }
+
+    #[test]
+    fn wide_mul_128() {
+        let mut vm = vm!();
+        add_segments!(vm, 2);
+        vm.set_ap(0);
+        run_hints(
+            &mut vm,
+            exec_scopes_ref!(),
+            &[Hint::WideMul128 {
+                lhs: immediate(i128::MAX),
+                rhs: immediate(1 << 64),
+                high: ap(0),
+                low: ap(1),
+            }],
+        )
+        .unwrap();
+        // (2**127 - 1) * 2**64 = (2**63 - 1) * 2**128 + 2**128 - 2**64
+        assert_eq!(
+            vm.memory.get_integer(&relocatable!(1, 0)).unwrap().as_ref(),
+            &Felt::new(i64::MAX)
+        );
+        assert_eq!(
+            vm.memory.get_integer(&relocatable!(1, 1)).unwrap().as_ref(),
+            &(Felt::new(u128::MAX) - Felt::new(u64::MAX))
+        );
+    }
+
+    #[test]
+    fn div_mod() {
+        let mut vm = vm!();
+        vm.memory = memory![((1, 0), 47), ((1, 1), 5)];
+        vm.set_ap(2);
+        run_hints(
+            &mut vm,
+            exec_scopes_ref!(),
+            &[Hint::DivMod {
+                lhs: ResOperand::Deref(ap(-2)),
+                rhs: ResOperand::Deref(ap(-1)),
+                quotient: ap(0),
+                remainder: ap(1),
+            }],
+        )
+        .unwrap();
+        check_memory![vm.memory, ((1, 2), 9), ((1, 3), 2)];
+    }
+
+    #[test]
+    fn div_mod_by_zero() {
+        let mut vm = vm!();
+        vm.memory = memory![((1, 0), 47)];
+        vm.set_ap(1);
+        assert_eq!(
+            run_hints(
+                &mut vm,
+                exec_scopes_ref!(),
+                &[Hint::DivMod {
+                    lhs: ResOperand::Deref(ap(-1)),
+                    rhs: immediate(0),
+                    quotient: ap(0),
+                    remainder: ap(1),
+                }],
+            ),
+            Err(HintError::Internal(VirtualMachineError::DividedByZero))
+        );
+    }
+
+    #[test]
+    fn square_root() {
+        let mut vm = vm!();
+        vm.memory = memory![((1, 0), 80)];
+        vm.set_ap(1);
+        run_hints(
+            &mut vm,
+            exec_scopes_ref!(),
+            &[Hint::SquareRoot {
+                value: ResOperand::Deref(ap(-1)),
+                dst: ap(0),
+            }],
+        )
+        .unwrap();
+        check_memory![vm.memory, ((1, 0), 80), ((1, 1), 8)];
+    }
+
+    #[test]
+    fn linear_split() {
+        let mut vm = vm!();
+        add_segments!(vm, 2);
+        vm.set_ap(0);
+        run_hints(
+            &mut vm,
+            exec_scopes_ref!(),
+            &[
+                Hint::LinearSplit {
+                    value: immediate(1000),
+                    scalar: immediate(7),
+                    max_x: immediate(1000),
+                    x: ap(0),
+                    y: ap(1),
+                },
+                Hint::LinearSplit {
+                    value: immediate(1000),
+                    scalar: immediate(7),
+                    max_x: immediate(100),
+                    x: ap(2),
+                    y: ap(3),
+                },
+            ],
+        )
+        .unwrap();
+        check_memory![
+            vm.memory,
+            ((1, 0), 142),
+            ((1, 1), 6),
+            ((1, 2), 100),
+            ((1, 3), 300)
+        ];
+    }
+
+    #[test]
+    fn double_deref_operand() {
+        let mut vm = vm!();
+        vm.memory = memory![((1, 0), (2, 0)), ((2, 2), 9)];
+        vm.set_ap(1);
+        run_hints(
+            &mut vm,
+            exec_scopes_ref!(),
+            &[Hint::TestLessThan {
+                lhs: immediate(8),
+                rhs: ResOperand::DoubleDeref(ap(-1), 2),
+                dst: ap(0),
+            }],
+        )
+        .unwrap();
+        check_memory![vm.memory, ((1, 1), 1)];
+    }
+
+    #[test]
+    fn read_and_write_deprecated_dict_hints() {
+        let mut vm = vm!();
+        add_segments!(vm, 3);
+        // Segment arena: [dict_infos, n_dicts, n_destructed], followed by a pointer to its end
+        memory_from_memory!(
+            vm.memory,
+            (((1, 0), (2, 0)), ((1, 1), 0), ((1, 2), 0), ((1, 3), (1, 3)))
+        );
+        vm.set_ap(4);
+        let mut exec_scopes = ExecutionScopes::new();
+        run_hints(
+            &mut vm,
+            &mut exec_scopes,
+            &[Hint::AllocFelt252Dict {
+                segment_arena_ptr: ResOperand::Deref(ap(-1)),
+            }],
+        )
+        .unwrap();
+        // The new dict is placed in the first dict_info slot
+        check_memory![vm.memory, ((2, 0), (3, 0))];
+
+        // Write key 5 -> 12 on the dict, then read it back
+        memory_from_memory!(vm.memory, (((1, 4), (3, 0)), ((1, 5), 5), ((1, 6), 12)));
+        vm.set_ap(7);
+        run_hints(
+            &mut vm,
+            &mut exec_scopes,
+            &[
+                Hint::Felt252DictWrite {
+                    dict_ptr: ResOperand::Deref(ap(-3)),
+                    key: ResOperand::Deref(ap(-2)),
+                    value: ResOperand::Deref(ap(-1)),
+                },
+                Hint::Felt252DictRead {
+                    dict_ptr: ResOperand::Deref(ap(-3)),
+                    key: ResOperand::Deref(ap(-2)),
+                    value_dst: ap(0),
+                },
+            ],
+        )
+        .unwrap();
+        // The previous value is written on the dict access, the new one is read back
+        check_memory![vm.memory, ((3, 1), 0), ((1, 7), 12)];
+    }
+
+    #[test]
+    fn dict_entry_without_dict_manager() {
+        let mut vm = vm!();
+        vm.memory = memory![((1, 0), (2, 0)), ((1, 1), 3)];
+        vm.set_ap(2);
+        assert_eq!(
+            run_hints(
+                &mut vm,
+                exec_scopes_ref!(),
+                &[Hint::Felt252DictEntryInit {
+                    dict_ptr: ResOperand::Deref(ap(-2)),
+                    key: ResOperand::Deref(ap(-1)),
+                }],
+            ),
+            Err(HintError::VariableNotInScopeError(
+                DICT_MANAGER_SCOPE.to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn invalid_buffer_operand() {
+        let mut vm = vm!();
+        vm.memory = memory![((1, 0), (2, 0))];
+        vm.set_ap(1);
+        let segment_arena_ptr = ResOperand::DoubleDeref(ap(-1), 0);
+        assert_eq!(
+            run_hints(
+                &mut vm,
+                exec_scopes_ref!(),
+                &[Hint::AllocFelt252Dict {
+                    segment_arena_ptr: segment_arena_ptr.clone()
+                }],
+            ),
+            Err(HintError::InvalidBufferOperand(format!(
+                "{segment_arena_ptr:?}"
+            )))
+        );
+    }
+
+    #[test]
+    fn squash_data_hints() {
+        let mut vm = vm!();
+        // Accesses: key 4 at index 0 and 2, key 1 at index 1
+        vm.memory = memory![
+            ((1, 0), (2, 0)),
+            ((1, 1), 3),
+            ((2, 0), 4),
+            ((2, 1), 0),
+            ((2, 2), 1),
+            ((2, 3), 1),
+            ((2, 4), 0),
+            ((2, 5), 2),
+            ((2, 6), 4),
+            ((2, 7), 1),
+            ((2, 8), 3)
+        ];
+        vm.set_fp(2);
+        vm.set_ap(2);
+        let mut exec_scopes = ExecutionScopes::new();
+        run_hints(
+            &mut vm,
+            &mut exec_scopes,
+            &[
+                Hint::InitSquashData {
+                    dict_accesses: ResOperand::Deref(fp(-2)),
+                    ptr_diff: immediate(9),
+                    n_accesses: ResOperand::Deref(fp(-1)),
+                    big_keys: ap(0),
+                    first_key: ap(1),
+                },
+                Hint::ShouldSkipSquashLoop {
+                    should_skip_loop: ap(2),
+                },
+                Hint::GetNextDictKey { next_key: ap(3) },
+                Hint::ShouldSkipSquashLoop {
+                    should_skip_loop: ap(4),
+                },
+                Hint::GetCurrentAccessDelta {
+                    index_delta_minus1: ap(5),
+                },
+                Hint::ShouldContinueSquashLoop {
+                    should_continue: ap(6),
+                },
+            ],
+        )
+        .unwrap();
+        check_memory![
+            vm.memory,
+            ((1, 2), 0),
+            ((1, 3), 1),
+            ((1, 4), 1),
+            ((1, 5), 4),
+            ((1, 6), 0),
+            ((1, 7), 1),
+            ((1, 8), 0)
+        ];
+        // Both accesses of key 4 were processed, there is no key left
+        assert_eq!(
+            run_hints(
+                &mut vm,
+                &mut exec_scopes,
+                &[Hint::GetNextDictKey { next_key: ap(9) }]
+            ),
+            Err(HintError::EmptyKeys)
+        );
+    }
+
+    #[test]
+    fn get_current_access_index() {
+        let mut vm = vm!();
+        add_segments!(vm, 3);
+        memory_from_memory!(vm.memory, (((1, 0), (2, 0))));
+        vm.set_ap(1);
+        let mut exec_scopes = ExecutionScopes::new();
+        exec_scopes.assign_or_update_variable(
+            DICT_SQUASH_SCOPE,
+            any_box!(DictSquashExecScope {
+                access_indices: HashMap::from([(Felt::new(3), vec![Felt::new(5), Felt::new(2)])]),
+                keys: vec![Felt::new(3)],
+            }),
+        );
+        run_hints(
+            &mut vm,
+            &mut exec_scopes,
+            &[Hint::GetCurrentAccessIndex {
+                range_check_ptr: ResOperand::Deref(ap(-1)),
+            }],
+        )
+        .unwrap();
+        check_memory![vm.memory, ((2, 0), 2)];
+    }
+
+    #[test]
+    fn assert_le_find_small_arcs() {
+        let mut vm = vm!();
+        add_segments!(vm, 3);
+        memory_from_memory!(vm.memory, (((1, 0), (2, 0))));
+        vm.set_ap(1);
+        let mut exec_scopes = ExecutionScopes::new();
+        run_hints(
+            &mut vm,
+            &mut exec_scopes,
+            &[
+                Hint::AssertLeFindSmallArcs {
+                    range_check_ptr: deref_plus(ap(-1), 0),
+                    a: immediate(3),
+                    b: immediate(10),
+                },
+                Hint::AssertLeIsFirstArcExcluded {
+                    skip_exclude_a_flag: ap(0),
+                },
+                Hint::AssertLeIsSecondArcExcluded {
+                    skip_exclude_b_minus_a: ap(1),
+                },
+            ],
+        )
+        .unwrap();
+        // The arcs are 3, 7 and PRIME - 11, the biggest one is excluded
+        assert_eq!(exec_scopes.get::<i32>(EXCLUDED_ARC), Ok(2));
+        check_memory![
+            vm.memory,
+            ((2, 0), 3),
+            ((2, 1), 0),
+            ((2, 2), 7),
+            ((2, 3), 0),
+            ((1, 1), 1),
+            ((1, 2), 1)
+        ];
+    }
+
+    #[test]
+    fn write_to_used_cell() {
+        let mut vm = vm!();
+        vm.memory = memory![((1, 0), 1)];
+        vm.set_ap(0);
+        assert_eq!(
+            run_hints(
+                &mut vm,
+                exec_scopes_ref!(),
+                &[Hint::SquareRoot {
+                    value: immediate(9),
+                    dst: ap(0),
+                }],
+            ),
+            Err(HintError::Internal(VirtualMachineError::MemoryError(
+                MemoryError::InconsistentMemory(
+                    MaybeRelocatable::from((1, 0)),
+                    MaybeRelocatable::from(1),
+                    MaybeRelocatable::from(3)
+                )
+            )))
+        );
+    }
+}
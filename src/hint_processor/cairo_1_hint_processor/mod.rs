@@ -0,0 +1,4 @@
+pub mod casm_program;
+pub mod dict_manager;
+pub mod hint;
+pub mod hint_processor;
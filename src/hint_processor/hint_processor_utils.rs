@@ -71,8 +71,7 @@ pub fn get_maybe_relocatable_from_reference(
     //Then calculate address
     let var_addr = compute_addr_from_reference(hint_reference, vm, ap_tracking)?;
     let value = if hint_reference.dereference {
-        vm.get_maybe(&var_addr)
-            .map_err(|error| HintError::Internal(VirtualMachineError::MemoryError(error)))?
+        vm.get_maybe(&var_addr)?
     } else {
         return Ok(MaybeRelocatable::from(var_addr));
     };
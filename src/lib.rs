@@ -1,4 +1,6 @@
 #![deny(warnings)]
+#[cfg(test)]
+mod cairo_programs;
 pub mod cairo_run;
 pub mod hint_processor;
 pub mod math_utils;
@@ -6,3 +8,5 @@ pub mod serde;
 pub mod types;
 pub mod utils;
 pub mod vm;
+
+pub use vm::errors::cairo_vm_errors::CairoVmError;
@@ -1,9 +1,9 @@
 #![deny(warnings)]
 use cairo_vm::cairo_run;
 use cairo_vm::hint_processor::builtin_hint_processor::builtin_hint_processor_definition::BuiltinHintProcessor;
+use cairo_vm::types::errors::program_errors::ProgramError;
 use cairo_vm::vm::errors::cairo_run_errors::CairoRunError;
 use cairo_vm::vm::errors::runner_errors::RunnerError;
-use cairo_vm::vm::errors::trace_errors::TraceError;
 use clap::{Parser, ValueHint};
 use std::path::PathBuf;
 
@@ -32,13 +32,23 @@ struct Args {
     layout: String,
     #[structopt(long = "--proof_mode")]
     proof_mode: bool,
+    #[structopt(long = "--secure_run")]
+    secure_run: Option<bool>,
 }
 
 fn validate_layout(value: &str) -> Result<(), String> {
     match value {
-        "plain" | "small" | "dex" | "bitwise" | "perpetual_with_bitwise" | "recursive" | "all" => {
-            Ok(())
-        }
+        "plain"
+        | "small"
+        | "dex"
+        | "bitwise"
+        | "perpetual_with_bitwise"
+        | "recursive"
+        | "starknet"
+        | "starknet_with_keccak"
+        | "all_cairo"
+        | "all_solidity"
+        | "all" => Ok(()),
         _ => Err(format!("{} is not a valid layout", value)),
     }
 }
@@ -47,27 +57,30 @@ fn main() -> Result<(), CairoRunError> {
     let args = Args::parse();
     let trace_enabled = args.trace_file.is_some();
     let mut hint_executor = BuiltinHintProcessor::new_empty();
-    let cairo_runner = match cairo_run::cairo_run(
-        &args.filename,
-        &args.entrypoint,
+    let cairo_run_config = cairo_run::CairoRunConfig {
+        entrypoint: &args.entrypoint,
         trace_enabled,
-        args.print_output,
-        &args.layout,
-        args.proof_mode,
-        &mut hint_executor,
-    ) {
-        Ok(runner) => runner,
-        Err(error) => {
-            println!("{}", error);
-            return Err(error);
-        }
+        print_output: args.print_output,
+        relocate_mem: args.memory_file.is_some(),
+        layout: &args.layout,
+        proof_mode: args.proof_mode,
+        secure_run: args.secure_run,
     };
+    let program_content = std::fs::read(&args.filename)
+        .map_err(|error| CairoRunError::Program(ProgramError::IO(error)))?;
+    let (cairo_runner, _vm) =
+        match cairo_run::cairo_run(&program_content, &cairo_run_config, &mut hint_executor) {
+            Ok(runner) => runner,
+            Err(error) => {
+                println!("{}", error);
+                return Err(error);
+            }
+        };
 
     if let Some(trace_path) = args.trace_file {
         let relocated_trace = cairo_runner
-            .relocated_trace
-            .as_ref()
-            .ok_or(CairoRunError::Trace(TraceError::TraceNotEnabled))?;
+            .get_relocated_trace()
+            .map_err(CairoRunError::Trace)?;
         match cairo_run::write_binary_trace(relocated_trace, &trace_path) {
             Ok(()) => (),
             Err(_e) => return Err(CairoRunError::Runner(RunnerError::WriteFail)),
@@ -97,6 +110,10 @@ mod tests {
             "bitwise",
             "perpetual_with_bitwise",
             "recursive",
+            "starknet",
+            "starknet_with_keccak",
+            "all_cairo",
+            "all_solidity",
             "all",
         ];
 
@@ -1,10 +1,33 @@
-use crate::vm::errors::vm_errors::VirtualMachineError;
-use felt::Felt;
+use crate::vm::errors::{runner_errors::RunnerError, vm_errors::VirtualMachineError};
+use felt::{Felt, FeltOps};
 use num_bigint::{BigInt, BigUint};
 use num_integer::Integer;
 use num_traits::{One, Signed, Zero};
+use starknet_crypto::FieldElement;
 use std::ops::Shr;
 
+///Returns the pedersen hash of x and y, as computed by the pedersen builtin.
+pub fn pedersen_hash(x: &Felt, y: &Felt) -> Result<Felt, RunnerError> {
+    let to_field_element = |value: &Felt| {
+        FieldElement::from_dec_str(&value.to_str_radix(10))
+            .map_err(|_| RunnerError::FailedStringConversion)
+    };
+    let hash = starknet_crypto::pedersen_hash(&to_field_element(x)?, &to_field_element(y)?);
+    Ok(Felt::from_bytes_be(&hash.to_bytes_be()))
+}
+
+///Applies the Hades permutation of the Starknet Poseidon hash to the state, as computed by the
+///poseidon builtin.
+pub fn poseidon_permute(state: &[Felt; 3]) -> Result<[Felt; 3], RunnerError> {
+    let mut field_state = [FieldElement::ZERO; 3];
+    for (field_element, value) in field_state.iter_mut().zip(state) {
+        *field_element = FieldElement::from_dec_str(&value.to_str_radix(10))
+            .map_err(|_| RunnerError::FailedStringConversion)?;
+    }
+    starknet_crypto::poseidon_permute_comp(&mut field_state);
+    Ok(field_state.map(|field_element| Felt::from_bytes_be(&field_element.to_bytes_be())))
+}
+
 ///Returns the integer square root of the nonnegative integer n.
 ///This is the floor of the exact square root of n.
 ///Unlike math.sqrt(), this function doesn't have rounding error issues.
@@ -175,13 +198,108 @@ pub fn ec_double_slope(point: &(BigInt, BigInt), alpha: &BigInt, prime: &BigInt)
     )
 }
 
+/// Returns true if n is a quadratic residue modulo the prime p (zero included).
+pub fn is_quad_residue(n: &BigUint, p: &BigUint) -> bool {
+    let n = n.mod_floor(p);
+    n.is_zero() || n.modpow(&((p - 1_u32).shr(1_u32)), p).is_one()
+}
+
+/// Returns the smallest square root of n modulo the odd prime p, or None if n is not a
+/// quadratic residue modulo p.
+/// Uses the Tonelli-Shanks algorithm.
+pub fn sqrt_prime(n: &BigUint, p: &BigUint) -> Option<BigUint> {
+    let n = n.mod_floor(p);
+    if n.is_zero() {
+        return Some(n);
+    }
+    if !is_quad_residue(&n, p) {
+        return None;
+    }
+    // Write p - 1 as q * 2^s, with q odd
+    let mut q = p - 1_u32;
+    let mut s = 0_u32;
+    while q.is_even() {
+        q >>= 1_u32;
+        s += 1;
+    }
+    // Find a quadratic non-residue
+    let mut z = BigUint::from(2_u32);
+    while is_quad_residue(&z, p) {
+        z += 1_u32;
+    }
+
+    let mut m = s;
+    let mut c = z.modpow(&q, p);
+    let mut t = n.modpow(&q, p);
+    let mut r = n.modpow(&((&q + 1_u32).shr(1_u32)), p);
+    while !t.is_one() {
+        // Find the least i such that t^(2^i) = 1
+        let mut i = 0_u32;
+        let mut t_pow = t.clone();
+        while !t_pow.is_one() {
+            t_pow = (&t_pow * &t_pow).mod_floor(p);
+            i += 1;
+        }
+        let b = c.modpow(&(BigUint::one() << (m - i - 1)), p);
+        m = i;
+        c = (&b * &b).mod_floor(p);
+        t = (t * &c).mod_floor(p);
+        r = (r * b).mod_floor(p);
+    }
+    let other_root = p - &r;
+    Some(r.min(other_root))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::utils::test_utils::*;
-    use felt::NewFelt;
+    use felt::{felt_str, NewFelt};
     use num_traits::Num;
 
+    #[test]
+    fn pedersen_hash_test_vector() {
+        assert_eq!(
+            pedersen_hash(
+                &felt_str!(
+                    "3d937c035c878245caf64531a5756109c53068da139362728feb561405371cb",
+                    16
+                ),
+                &felt_str!(
+                    "208a0a10250e382e1e4bbe2880906c2791bf6275695e02fbbc6aeff9cd8b31a",
+                    16
+                )
+            ),
+            Ok(felt_str!(
+                "30e480bed5fe53fa909cc0f8c4d99b8f9f2c016be4c41e13a4848797979c662",
+                16
+            ))
+        );
+    }
+
+    #[test]
+    fn poseidon_permute_test_vector() {
+        // The poseidon hash of x and y is the first element of the permutation of (x, y, 2)
+        let state = [
+            felt_str!(
+                "b662f9017fa7956fd70e26129b1833e10ad000fd37b4d9f4e0ce6884b7bbe",
+                16
+            ),
+            felt_str!(
+                "1fe356bf76102cdae1bfbdc173602ead228b12904c00dad9cf16e035468bea",
+                16
+            ),
+            Felt::new(2),
+        ];
+        assert_eq!(
+            poseidon_permute(&state).unwrap()[0],
+            felt_str!(
+                "75540825a6ecc5dc7d7c2f5f868164182742227f1367d66c43ee51ec7937a81",
+                16
+            )
+        );
+    }
+
     #[test]
     fn calculate_divmod_a() {
         let a = bigint_str!(
@@ -571,4 +689,40 @@ mod tests {
         let n = BigUint::zero();
         assert_eq!(isqrt(&n), Ok(BigUint::zero()));
     }
+
+    #[test]
+    fn is_quad_residue_small_prime() {
+        let p = BigUint::from(13_u32);
+        let residues: Vec<u32> = (0..13_u32)
+            .filter(|n| is_quad_residue(&BigUint::from(*n), &p))
+            .collect();
+        assert_eq!(residues, vec![0, 1, 3, 4, 9, 10, 12]);
+    }
+
+    #[test]
+    fn sqrt_prime_small_prime() {
+        let p = BigUint::from(13_u32);
+        assert_eq!(
+            sqrt_prime(&BigUint::from(10_u32), &p),
+            Some(BigUint::from(6_u32))
+        );
+        assert_eq!(sqrt_prime(&BigUint::from(0_u32), &p), Some(BigUint::zero()));
+        assert_eq!(sqrt_prime(&BigUint::from(5_u32), &p), None);
+    }
+
+    #[test]
+    fn sqrt_prime_cairo_prime() {
+        let p = BigUint::from_str_radix(&felt::PRIME_STR[2..], 16).unwrap();
+        let n = BigUint::from(25_u32);
+        assert_eq!(sqrt_prime(&n, &p), Some(BigUint::from(5_u32)));
+        let n = biguint_str!(
+            "250010409339969182472858266163833826078883643678600464430415398085445902158"
+        );
+        assert_eq!(
+            sqrt_prime(&n, &p),
+            Some(biguint_str!(
+                "152666792071518830868575557812948353041420400780739481342941381225525861407"
+            ))
+        );
+    }
 }
@@ -1,7 +1,9 @@
 use crate::{
     serde::deserialize_utils,
     types::{
-        errors::program_errors::ProgramError, instruction::Register, program::Program,
+        errors::program_errors::ProgramError,
+        instruction::Register,
+        program::{extract_constants, IdCache, Program},
         relocatable::MaybeRelocatable,
     },
 };
@@ -311,6 +313,31 @@ pub fn deserialize_program_json(reader: impl Read) -> Result<ProgramJson, Progra
     Ok(program_json)
 }
 
+/// Returns the pc of the function (or label) named `entrypoint` in the `__main__` module.
+/// If there's none, the error lists the functions of the module.
+pub(crate) fn get_entrypoint_pc(
+    identifiers: &HashMap<String, Identifier>,
+    entrypoint: &str,
+) -> Result<usize, ProgramError> {
+    if let Some(pc) = identifiers
+        .get(&format!("__main__.{entrypoint}"))
+        .and_then(|identifier| identifier.pc)
+    {
+        return Ok(pc);
+    }
+    let mut available = identifiers
+        .iter()
+        .filter(|(_, identifier)| identifier.type_.as_deref() == Some("function"))
+        .filter_map(|(name, _)| name.strip_prefix("__main__."))
+        .map(String::from)
+        .collect::<Vec<_>>();
+    available.sort_unstable();
+    Err(ProgramError::EntrypointNotFound {
+        name: entrypoint.to_string(),
+        available,
+    })
+}
+
 pub fn deserialize_program(
     reader: impl Read,
     entrypoint: Option<&str>,
@@ -322,13 +349,7 @@ pub fn deserialize_program(
     }
 
     let entrypoint_pc = match entrypoint {
-        Some(entrypoint) => match program_json
-            .identifiers
-            .get(&format!("__main__.{entrypoint}"))
-        {
-            Some(entrypoint_identifier) => entrypoint_identifier.pc,
-            None => return Err(ProgramError::EntrypointNotFound(entrypoint.to_string())),
-        },
+        Some(entrypoint) => Some(get_entrypoint_pc(&program_json.identifiers, entrypoint)?),
         None => None,
     };
 
@@ -341,24 +362,13 @@ pub fn deserialize_program(
         None => None,
     };
 
+    let (constants, label_constants) = extract_constants(&program_json.identifiers)?;
     Ok(Program {
         builtins: program_json.builtins,
         prime: PRIME_STR.to_string(),
         data: program_json.data,
-        constants: {
-            let mut constants = HashMap::new();
-            for (key, value) in program_json.identifiers.iter() {
-                if value.type_.as_deref() == Some("const") {
-                    let value = value
-                        .value
-                        .clone()
-                        .ok_or_else(|| ProgramError::ConstWithoutValue(key.to_owned()))?;
-                    constants.insert(key.to_owned(), value);
-                }
-            }
-
-            constants
-        },
+        constants,
+        label_constants,
         main: entrypoint_pc,
         start,
         end,
@@ -373,6 +383,7 @@ pub fn deserialize_program(
         instruction_locations: program_json
             .debug_info
             .map(|debug_info| debug_info.instruction_locations),
+        cached_id: IdCache::default(),
     })
 }
 
@@ -715,7 +726,56 @@ mod tests {
         assert!(deserialization_result.is_err());
         assert!(matches!(
             deserialization_result,
-            Err(ProgramError::EntrypointNotFound(_))
+            Err(ProgramError::EntrypointNotFound { .. })
+        ));
+    }
+
+    #[test]
+    fn deserialize_entrypoint_without_pc_gives_error() {
+        let file = File::open("cairo_programs/manually_compiled/valid_program_a.json").unwrap();
+        let reader = BufReader::new(file);
+
+        let error = deserialize_program(reader, Some("main.SIZEOF_LOCALS")).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Entrypoint main.SIZEOF_LOCALS not found (available functions: main)"
+        );
+    }
+
+    #[test]
+    fn get_entrypoint_pc_lists_main_functions() {
+        let identifier = |pc, type_: &str| Identifier {
+            pc,
+            type_: Some(type_.to_string()),
+            value: None,
+            full_name: None,
+            members: None,
+        };
+        let identifiers = HashMap::from([
+            ("__main__.main".to_string(), identifier(Some(0), "function")),
+            ("__main__.fib".to_string(), identifier(Some(9), "function")),
+            (
+                "__main__.fib.end".to_string(),
+                identifier(Some(15), "label"),
+            ),
+            ("__main__.fib.Args".to_string(), identifier(None, "struct")),
+            (
+                "starkware.cairo.common.alloc.alloc".to_string(),
+                identifier(Some(30), "function"),
+            ),
+        ]);
+
+        assert_eq!(get_entrypoint_pc(&identifiers, "fib").unwrap(), 9);
+        assert_eq!(get_entrypoint_pc(&identifiers, "fib.end").unwrap(), 15);
+        assert!(matches!(
+            get_entrypoint_pc(&identifiers, "alloc"),
+            Err(ProgramError::EntrypointNotFound { name, available })
+                if name == "alloc" && available == ["fib", "main"]
+        ));
+        assert!(matches!(
+            get_entrypoint_pc(&identifiers, "fib.Args"),
+            Err(ProgramError::EntrypointNotFound { name, available })
+                if name == "fib.Args" && available == ["fib", "main"]
         ));
     }
 
@@ -45,6 +45,20 @@ impl fmt::Display for ReferenceParseError {
     }
 }
 
+impl std::error::Error for ReferenceParseError {}
+
+impl From<ParseIntError> for ReferenceParseError {
+    fn from(error: ParseIntError) -> Self {
+        ReferenceParseError::IntError(error)
+    }
+}
+
+impl From<ParseFeltError> for ReferenceParseError {
+    fn from(error: ParseFeltError) -> Self {
+        ReferenceParseError::FeltError(error)
+    }
+}
+
 // Checks if the hex string has an odd length.
 // If that is the case, prepends '0' to it.
 pub fn maybe_add_padding(mut hex: String) -> String {
@@ -8,8 +8,12 @@ pub enum ProgramError {
     IO(#[from] io::Error),
     #[error(transparent)]
     Parse(#[from] serde_json::Error),
-    #[error("Entrypoint {0} not found")]
-    EntrypointNotFound(String),
+    /// Holds the functions of the `__main__` module which are available as entrypoints
+    #[error("Entrypoint {name} not found (available functions: {})", .available.join(", "))]
+    EntrypointNotFound {
+        name: String,
+        available: Vec<String>,
+    },
     #[error("Constant {0} has no value")]
     ConstWithoutValue(String),
     #[error("Expected prime {PRIME_STR}, got {0}")]
@@ -22,8 +26,14 @@ mod tests {
 
     #[test]
     fn format_entrypoint_not_found_error() {
-        let error = ProgramError::EntrypointNotFound(String::from("my_function"));
+        let error = ProgramError::EntrypointNotFound {
+            name: String::from("my_function"),
+            available: vec![String::from("fib"), String::from("main")],
+        };
         let formatted_error = format!("{}", error);
-        assert_eq!(formatted_error, "Entrypoint my_function not found");
+        assert_eq!(
+            formatted_error,
+            "Entrypoint my_function not found (available functions: fib, main)"
+        );
     }
 }
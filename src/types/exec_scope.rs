@@ -1,14 +1,55 @@
 use crate::{
     any_box,
     hint_processor::builtin_hint_processor::dict_manager::DictManager,
+    types::relocatable::MaybeRelocatable,
     vm::errors::{exec_scope_errors::ExecScopeError, hint_errors::HintError},
 };
-use std::{any::Any, cell::RefCell, collections::HashMap, rc::Rc};
+use felt::Felt;
+use serde::{Deserialize, Serialize};
+use std::{
+    any::Any,
+    cell::RefCell,
+    cmp::Ordering,
+    collections::{BTreeMap, HashMap},
+    rc::Rc,
+};
 
 pub struct ExecutionScopes {
     pub data: Vec<HashMap<String, Box<dyn Any>>>,
 }
 
+///A scope variable of one of the types the builtin hints store, such as the dict manager, the
+///state of the dict squashing and of usort, or the maximum sizes of keccak and usort.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(tag = "type", content = "value")]
+pub enum KnownScopeValue {
+    ///The index of the dict manager in `SerializedExecutionScopes::dict_managers`, so that the
+    ///scopes sharing a dict manager still share it once restored
+    DictManager(usize),
+    Felt(Felt),
+    U64(u64),
+    FeltList(Vec<Felt>),
+    U64List(Vec<u64>),
+    ///A `HashMap<Felt, Vec<Felt>>`, such as the access indices of squash_dict, sorted by key
+    FeltToFeltList(Vec<(Felt, Vec<Felt>)>),
+    ///A `HashMap<Felt, Vec<u64>>`, such as the positions of usort, sorted by key
+    FeltToU64List(Vec<(Felt, Vec<u64>)>),
+    ///A `HashMap<MaybeRelocatable, MaybeRelocatable>`, such as the initial_dict of dict_squash,
+    ///sorted by key
+    MaybeRelocatableMap(Vec<(MaybeRelocatable, MaybeRelocatable)>),
+}
+
+///The execution scopes reduced to their variables of known types, see
+///`ExecutionScopes::serialize_known_types`.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct SerializedExecutionScopes {
+    pub dict_managers: Vec<DictManager>,
+    ///The variables of each scope, from the main scope to the current one
+    pub scopes: Vec<BTreeMap<String, KnownScopeValue>>,
+    ///The names of the variables left out because their type isn't known
+    pub non_serializable: Vec<String>,
+}
+
 impl ExecutionScopes {
     pub fn new() -> ExecutionScopes {
         ExecutionScopes {
@@ -152,6 +193,11 @@ impl ExecutionScopes {
         val.ok_or_else(|| HintError::VariableNotInScopeError("dict_manager".to_string()))
     }
 
+    ///Stores the dict manager in the current scope, where the dict hints look for it
+    pub fn insert_dict_manager(&mut self, dict_manager: Rc<RefCell<DictManager>>) {
+        self.insert_value("dict_manager", dict_manager);
+    }
+
     ///Returns a mutable reference to the value in the current execution scope that matches the name and is of the given type
     pub fn get_mut_dict_ref<K: Any, V: Any>(
         &mut self,
@@ -175,6 +221,110 @@ impl ExecutionScopes {
     pub fn insert_value<T: 'static>(&mut self, name: &str, value: T) {
         self.assign_or_update_variable(name, any_box!(value));
     }
+
+    ///Extracts the variables of every scope whose type is a `KnownScopeValue`, so that the hint
+    ///state can be saved along with the memory and restored with `restore_known_types`.
+    ///The names of the other variables are listed in `non_serializable`, as a run restored
+    ///without them may not behave as the original one.
+    pub fn serialize_known_types(&self) -> SerializedExecutionScopes {
+        let mut serialized = SerializedExecutionScopes::default();
+        let mut dict_managers: Vec<&Rc<RefCell<DictManager>>> = Vec::new();
+        for scope in &self.data {
+            let mut variables = BTreeMap::new();
+            for (name, value) in scope {
+                let known_value =
+                    if let Some(dict_manager) = value.downcast_ref::<Rc<RefCell<DictManager>>>() {
+                        let index = match dict_managers
+                            .iter()
+                            .position(|d| Rc::ptr_eq(d, dict_manager))
+                        {
+                            Some(index) => index,
+                            None => {
+                                dict_managers.push(dict_manager);
+                                serialized.dict_managers.push(dict_manager.borrow().clone());
+                                dict_managers.len() - 1
+                            }
+                        };
+                        KnownScopeValue::DictManager(index)
+                    } else if let Some(value) = value.downcast_ref::<Felt>() {
+                        KnownScopeValue::Felt(value.clone())
+                    } else if let Some(value) = value.downcast_ref::<u64>() {
+                        KnownScopeValue::U64(*value)
+                    } else if let Some(value) = value.downcast_ref::<Vec<Felt>>() {
+                        KnownScopeValue::FeltList(value.clone())
+                    } else if let Some(value) = value.downcast_ref::<Vec<u64>>() {
+                        KnownScopeValue::U64List(value.clone())
+                    } else if let Some(value) = value.downcast_ref::<HashMap<Felt, Vec<Felt>>>() {
+                        KnownScopeValue::FeltToFeltList(sorted_entries(value))
+                    } else if let Some(value) = value.downcast_ref::<HashMap<Felt, Vec<u64>>>() {
+                        KnownScopeValue::FeltToU64List(sorted_entries(value))
+                    } else if let Some(value) =
+                        value.downcast_ref::<HashMap<MaybeRelocatable, MaybeRelocatable>>()
+                    {
+                        KnownScopeValue::MaybeRelocatableMap(sorted_entries(value))
+                    } else {
+                        serialized.non_serializable.push(name.clone());
+                        continue;
+                    };
+                variables.insert(name.clone(), known_value);
+            }
+            serialized.scopes.push(variables);
+        }
+        serialized.non_serializable.sort();
+        serialized.non_serializable.dedup();
+        serialized
+    }
+
+    ///Rebuilds the execution scopes saved by `serialize_known_types`.
+    pub fn restore_known_types(
+        serialized: SerializedExecutionScopes,
+    ) -> Result<ExecutionScopes, ExecScopeError> {
+        let dict_managers: Vec<_> = serialized
+            .dict_managers
+            .into_iter()
+            .map(|dict_manager| Rc::new(RefCell::new(dict_manager)))
+            .collect();
+        let mut data = Vec::with_capacity(serialized.scopes.len());
+        for variables in serialized.scopes {
+            let mut scope = HashMap::new();
+            for (name, value) in variables {
+                let value = match value {
+                    KnownScopeValue::DictManager(index) => match dict_managers.get(index) {
+                        Some(dict_manager) => any_box!(dict_manager.clone()),
+                        None => return Err(ExecScopeError::MissingDictManager(name, index)),
+                    },
+                    KnownScopeValue::Felt(value) => any_box!(value),
+                    KnownScopeValue::U64(value) => any_box!(value),
+                    KnownScopeValue::FeltList(value) => any_box!(value),
+                    KnownScopeValue::U64List(value) => any_box!(value),
+                    KnownScopeValue::FeltToFeltList(value) => {
+                        any_box!(value.into_iter().collect::<HashMap<_, _>>())
+                    }
+                    KnownScopeValue::FeltToU64List(value) => {
+                        any_box!(value.into_iter().collect::<HashMap<_, _>>())
+                    }
+                    KnownScopeValue::MaybeRelocatableMap(value) => {
+                        any_box!(value.into_iter().collect::<HashMap<_, _>>())
+                    }
+                };
+                scope.insert(name, value);
+            }
+            data.push(scope);
+        }
+        if data.is_empty() {
+            data.push(HashMap::new());
+        }
+        Ok(ExecutionScopes { data })
+    }
+}
+
+fn sorted_entries<K: Clone + PartialOrd, V: Clone>(map: &HashMap<K, V>) -> Vec<(K, V)> {
+    let mut entries: Vec<_> = map
+        .iter()
+        .map(|(key, value)| (key.clone(), value.clone()))
+        .collect();
+    entries.sort_by(|(a, _), (b, _)| a.partial_cmp(b).unwrap_or(Ordering::Equal));
+    entries
 }
 
 impl Default for ExecutionScopes {
@@ -186,6 +336,10 @@ impl Default for ExecutionScopes {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::{
+        hint_processor::builtin_hint_processor::dict_manager::DictTracker,
+        types::relocatable::Relocatable,
+    };
     use felt::{Felt, NewFelt};
     use num_traits::One;
 
@@ -447,4 +601,93 @@ mod tests {
         assert!(scopes.get_any_boxed_mut("no_variable").is_err());
         assert!(scopes.get_any_boxed_ref("no_variable").is_err());
     }
+
+    #[test]
+    fn serialize_and_restore_known_types() {
+        let mut dict_manager = DictManager::new();
+        let mut tracker = DictTracker::new_empty(&Relocatable::from((2, 0)));
+        tracker.insert_value(&MaybeRelocatable::from(5), &MaybeRelocatable::from(10));
+        tracker.current_ptr = Relocatable::from((2, 3));
+        dict_manager.trackers.insert(2, tracker);
+        let dict_manager = Rc::new(RefCell::new(dict_manager));
+
+        let mut scopes = ExecutionScopes::new();
+        scopes.insert_dict_manager(dict_manager.clone());
+        scopes.insert_value("usort_max_size", 10_u64);
+        scopes.insert_value("not_serializable", String::from("text"));
+        scopes.enter_scope(HashMap::from([
+            (String::from("dict_manager"), any_box!(dict_manager.clone())),
+            (
+                String::from("access_indices"),
+                any_box!(HashMap::from([(Felt::new(5), vec![Felt::new(0)])])),
+            ),
+            (String::from("keys"), any_box!(Vec::<Felt>::new())),
+            (
+                String::from("initial_dict"),
+                any_box!(HashMap::from([(
+                    MaybeRelocatable::from(5),
+                    MaybeRelocatable::from((3, 0))
+                )])),
+            ),
+            (String::from("key"), any_box!(Felt::new(5))),
+        ]));
+
+        let serialized = scopes.serialize_known_types();
+        assert_eq!(
+            serialized.non_serializable,
+            vec![String::from("not_serializable")]
+        );
+        assert_eq!(serialized.dict_managers.len(), 1);
+        assert_eq!(
+            serialized.scopes[1]["access_indices"],
+            KnownScopeValue::FeltToFeltList(vec![(Felt::new(5), vec![Felt::new(0)])])
+        );
+
+        let json = serde_json::to_string(&serialized).unwrap();
+        let restored =
+            ExecutionScopes::restore_known_types(serde_json::from_str(&json).unwrap()).unwrap();
+        assert_eq!(restored.data.len(), 2);
+        assert_eq!(restored.get::<Felt>("key"), Ok(Felt::new(5)));
+        assert_eq!(
+            restored.get_ref::<HashMap<MaybeRelocatable, MaybeRelocatable>>("initial_dict"),
+            Ok(&HashMap::from([(
+                MaybeRelocatable::from(5),
+                MaybeRelocatable::from((3, 0))
+            )]))
+        );
+        assert_eq!(
+            restored.get_ref::<HashMap<Felt, Vec<Felt>>>("access_indices"),
+            Ok(&HashMap::from([(Felt::new(5), vec![Felt::new(0)])]))
+        );
+        // Both scopes still share the same dict manager
+        let inner_dict_manager = restored.get_dict_manager().unwrap();
+        let outer_dict_manager = restored.data[0]["dict_manager"]
+            .downcast_ref::<Rc<RefCell<DictManager>>>()
+            .unwrap();
+        assert!(Rc::ptr_eq(&inner_dict_manager, outer_dict_manager));
+        assert_eq!(*inner_dict_manager.borrow(), *dict_manager.borrow());
+        assert_eq!(
+            restored.data[0]["usort_max_size"].downcast_ref::<u64>(),
+            Some(&10)
+        );
+        assert!(!restored.data[0].contains_key("not_serializable"));
+    }
+
+    #[test]
+    fn restore_missing_dict_manager() {
+        let serialized = SerializedExecutionScopes {
+            scopes: vec![BTreeMap::from([(
+                String::from("dict_manager"),
+                KnownScopeValue::DictManager(0),
+            )])],
+            ..Default::default()
+        };
+        assert_eq!(
+            ExecutionScopes::restore_known_types(serialized).err(),
+            Some(ExecScopeError::MissingDictManager(
+                String::from("dict_manager"),
+                0
+            ))
+        );
+    }
 }
@@ -4,6 +4,7 @@ pub(crate) const INPUT_CELLS_PER_BITWISE: u32 = 2;
 #[derive(Clone, Debug, PartialEq)]
 pub(crate) struct BitwiseInstanceDef {
     pub(crate) ratio: u32,
+    pub(crate) ratio_den: u32,
     pub(crate) total_n_bits: u32,
 }
 
@@ -11,6 +12,7 @@ impl BitwiseInstanceDef {
     pub(crate) fn default() -> Self {
         BitwiseInstanceDef {
             ratio: 256,
+            ratio_den: 1,
             total_n_bits: 251,
         }
     }
@@ -18,6 +20,7 @@ impl BitwiseInstanceDef {
     pub(crate) fn new(ratio: u32) -> Self {
         BitwiseInstanceDef {
             ratio,
+            ratio_den: 1,
             total_n_bits: 251,
         }
     }
@@ -51,6 +54,7 @@ mod tests {
     fn test_new() {
         let builtin_instance = BitwiseInstanceDef {
             ratio: 8,
+            ratio_den: 1,
             total_n_bits: 251,
         };
         assert_eq!(BitwiseInstanceDef::new(8), builtin_instance);
@@ -60,6 +64,7 @@ mod tests {
     fn test_default() {
         let builtin_instance = BitwiseInstanceDef {
             ratio: 256,
+            ratio_den: 1,
             total_n_bits: 251,
         };
         assert_eq!(BitwiseInstanceDef::default(), builtin_instance);
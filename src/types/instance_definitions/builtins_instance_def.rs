@@ -1,7 +1,8 @@
 use super::{
     bitwise_instance_def::BitwiseInstanceDef, ec_op_instance_def::EcOpInstanceDef,
     ecdsa_instance_def::EcdsaInstanceDef, keccak_instance_def::KeccakInstanceDef,
-    pedersen_instance_def::PedersenInstanceDef, range_check_instance_def::RangeCheckInstanceDef,
+    pedersen_instance_def::PedersenInstanceDef, poseidon_instance_def::PoseidonInstanceDef,
+    range_check_instance_def::RangeCheckInstanceDef,
 };
 
 #[derive(Debug, PartialEq)]
@@ -13,6 +14,8 @@ pub(crate) struct BuiltinsInstanceDef {
     pub(crate) bitwise: Option<BitwiseInstanceDef>,
     pub(crate) ec_op: Option<EcOpInstanceDef>,
     pub(crate) keccak: Option<KeccakInstanceDef>,
+    pub(crate) poseidon: Option<PoseidonInstanceDef>,
+    pub(crate) range_check96: Option<RangeCheckInstanceDef>,
 }
 
 impl BuiltinsInstanceDef {
@@ -25,6 +28,8 @@ impl BuiltinsInstanceDef {
             bitwise: None,
             ec_op: None,
             keccak: None,
+            poseidon: None,
+            range_check96: None,
         }
     }
 
@@ -37,6 +42,8 @@ impl BuiltinsInstanceDef {
             bitwise: None,
             ec_op: None,
             keccak: None,
+            poseidon: None,
+            range_check96: None,
         }
     }
 
@@ -49,6 +56,8 @@ impl BuiltinsInstanceDef {
             bitwise: None,
             ec_op: None,
             keccak: None,
+            poseidon: None,
+            range_check96: None,
         }
     }
 
@@ -61,6 +70,8 @@ impl BuiltinsInstanceDef {
             bitwise: Some(BitwiseInstanceDef::new(64)),
             ec_op: Some(EcOpInstanceDef::new(1024)),
             keccak: None,
+            poseidon: None,
+            range_check96: None,
         }
     }
 
@@ -73,6 +84,8 @@ impl BuiltinsInstanceDef {
             bitwise: Some(BitwiseInstanceDef::new(8)),
             ec_op: None,
             keccak: None,
+            poseidon: None,
+            range_check96: None,
         }
     }
 
@@ -85,6 +98,64 @@ impl BuiltinsInstanceDef {
             bitwise: Some(BitwiseInstanceDef::new(16)),
             ec_op: None,
             keccak: Some(KeccakInstanceDef::new(2048)),
+            poseidon: None,
+            range_check96: None,
+        }
+    }
+
+    pub(crate) fn starknet() -> BuiltinsInstanceDef {
+        BuiltinsInstanceDef {
+            _output: true,
+            pedersen: Some(PedersenInstanceDef::new(32, 1)),
+            range_check: Some(RangeCheckInstanceDef::new(16, 8)),
+            _ecdsa: Some(EcdsaInstanceDef::new(2048)),
+            bitwise: Some(BitwiseInstanceDef::new(64)),
+            ec_op: Some(EcOpInstanceDef::new(1024)),
+            keccak: None,
+            poseidon: Some(PoseidonInstanceDef::new(32)),
+            range_check96: None,
+        }
+    }
+
+    pub(crate) fn starknet_with_keccak() -> BuiltinsInstanceDef {
+        BuiltinsInstanceDef {
+            _output: true,
+            pedersen: Some(PedersenInstanceDef::new(32, 1)),
+            range_check: Some(RangeCheckInstanceDef::new(16, 8)),
+            _ecdsa: Some(EcdsaInstanceDef::new(2048)),
+            bitwise: Some(BitwiseInstanceDef::new(64)),
+            ec_op: Some(EcOpInstanceDef::new(1024)),
+            keccak: Some(KeccakInstanceDef::new(2048)),
+            poseidon: Some(PoseidonInstanceDef::new(32)),
+            range_check96: None,
+        }
+    }
+
+    pub(crate) fn all_cairo() -> BuiltinsInstanceDef {
+        BuiltinsInstanceDef {
+            _output: true,
+            pedersen: Some(PedersenInstanceDef::new(256, 1)),
+            range_check: Some(RangeCheckInstanceDef::default()),
+            _ecdsa: Some(EcdsaInstanceDef::new(2048)),
+            bitwise: Some(BitwiseInstanceDef::new(16)),
+            ec_op: Some(EcOpInstanceDef::new(1024)),
+            keccak: Some(KeccakInstanceDef::new(2048)),
+            poseidon: Some(PoseidonInstanceDef::new(256)),
+            range_check96: None,
+        }
+    }
+
+    pub(crate) fn all_solidity() -> BuiltinsInstanceDef {
+        BuiltinsInstanceDef {
+            _output: true,
+            pedersen: Some(PedersenInstanceDef::default()),
+            range_check: Some(RangeCheckInstanceDef::default()),
+            _ecdsa: Some(EcdsaInstanceDef::default()),
+            bitwise: Some(BitwiseInstanceDef::default()),
+            ec_op: Some(EcOpInstanceDef::default()),
+            keccak: None,
+            poseidon: None,
+            range_check96: None,
         }
     }
 
@@ -97,6 +168,8 @@ impl BuiltinsInstanceDef {
             bitwise: Some(BitwiseInstanceDef::default()),
             ec_op: Some(EcOpInstanceDef::default()),
             keccak: None,
+            poseidon: Some(PoseidonInstanceDef::default()),
+            range_check96: Some(RangeCheckInstanceDef::range_check96()),
         }
     }
 }
@@ -171,6 +244,58 @@ mod tests {
         assert!(builtins.ec_op.is_none());
     }
 
+    #[test]
+    fn get_builtins_starknet() {
+        let builtins = BuiltinsInstanceDef::starknet();
+        assert!(builtins._output);
+        assert!(builtins.pedersen.is_some());
+        assert!(builtins.range_check.is_some());
+        assert!(builtins._ecdsa.is_some());
+        assert!(builtins.bitwise.is_some());
+        assert!(builtins.ec_op.is_some());
+        assert!(builtins.keccak.is_none());
+        assert!(builtins.poseidon.is_some());
+    }
+
+    #[test]
+    fn get_builtins_starknet_with_keccak() {
+        let builtins = BuiltinsInstanceDef::starknet_with_keccak();
+        assert!(builtins._output);
+        assert!(builtins.pedersen.is_some());
+        assert!(builtins.range_check.is_some());
+        assert!(builtins._ecdsa.is_some());
+        assert!(builtins.bitwise.is_some());
+        assert!(builtins.ec_op.is_some());
+        assert!(builtins.keccak.is_some());
+        assert!(builtins.poseidon.is_some());
+    }
+
+    #[test]
+    fn get_builtins_all_cairo() {
+        let builtins = BuiltinsInstanceDef::all_cairo();
+        assert!(builtins._output);
+        assert!(builtins.pedersen.is_some());
+        assert!(builtins.range_check.is_some());
+        assert!(builtins._ecdsa.is_some());
+        assert!(builtins.bitwise.is_some());
+        assert!(builtins.ec_op.is_some());
+        assert!(builtins.keccak.is_some());
+        assert!(builtins.poseidon.is_some());
+    }
+
+    #[test]
+    fn get_builtins_all_solidity() {
+        let builtins = BuiltinsInstanceDef::all_solidity();
+        assert!(builtins._output);
+        assert!(builtins.pedersen.is_some());
+        assert!(builtins.range_check.is_some());
+        assert!(builtins._ecdsa.is_some());
+        assert!(builtins.bitwise.is_some());
+        assert!(builtins.ec_op.is_some());
+        assert!(builtins.keccak.is_none());
+        assert!(builtins.poseidon.is_none());
+    }
+
     #[test]
     fn get_builtins_all() {
         let builtins = BuiltinsInstanceDef::all();
@@ -180,5 +305,7 @@ mod tests {
         assert!(builtins._ecdsa.is_some());
         assert!(builtins.bitwise.is_some());
         assert!(builtins.ec_op.is_some());
+        assert!(builtins.poseidon.is_some());
+        assert!(builtins.range_check96.is_some());
     }
 }
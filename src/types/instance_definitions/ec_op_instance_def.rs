@@ -4,24 +4,27 @@ pub(crate) const INPUT_CELLS_PER_EC_OP: u32 = 5;
 #[derive(Clone, Debug, PartialEq)]
 pub(crate) struct EcOpInstanceDef {
     pub(crate) ratio: u32,
+    pub(crate) ratio_den: u32,
     pub(crate) scalar_height: u32,
-    pub(crate) _scalar_bits: u32,
+    pub(crate) scalar_bits: u32,
 }
 
 impl EcOpInstanceDef {
     pub(crate) fn default() -> Self {
         EcOpInstanceDef {
             ratio: 256,
+            ratio_den: 1,
             scalar_height: 256,
-            _scalar_bits: 252,
+            scalar_bits: 252,
         }
     }
 
     pub(crate) fn new(ratio: u32) -> Self {
         EcOpInstanceDef {
             ratio,
+            ratio_den: 1,
             scalar_height: 256,
-            _scalar_bits: 252,
+            scalar_bits: 252,
         }
     }
 
@@ -54,8 +57,9 @@ mod tests {
     fn test_new() {
         let builtin_instance = EcOpInstanceDef {
             ratio: 8,
+            ratio_den: 1,
             scalar_height: 256,
-            _scalar_bits: 252,
+            scalar_bits: 252,
         };
         assert_eq!(EcOpInstanceDef::new(8), builtin_instance);
     }
@@ -64,8 +68,9 @@ mod tests {
     fn test_default() {
         let builtin_instance = EcOpInstanceDef {
             ratio: 256,
+            ratio_den: 1,
             scalar_height: 256,
-            _scalar_bits: 252,
+            scalar_bits: 252,
         };
         assert_eq!(EcOpInstanceDef::default(), builtin_instance);
     }
@@ -4,6 +4,7 @@ pub(crate) const _INPUT_CELLS_PER_SIGNATURE: u32 = 2;
 #[derive(Debug, PartialEq)]
 pub(crate) struct EcdsaInstanceDef {
     pub(crate) ratio: u32,
+    pub(crate) ratio_den: u32,
     pub(crate) _repetitions: u32,
     pub(crate) _height: u32,
     pub(crate) _n_hash_bits: u32,
@@ -13,6 +14,7 @@ impl EcdsaInstanceDef {
     pub(crate) fn default() -> Self {
         EcdsaInstanceDef {
             ratio: 512,
+            ratio_den: 1,
             _repetitions: 1,
             _height: 256,
             _n_hash_bits: 251,
@@ -22,6 +24,7 @@ impl EcdsaInstanceDef {
     pub(crate) fn new(ratio: u32) -> Self {
         EcdsaInstanceDef {
             ratio,
+            ratio_den: 1,
             _repetitions: 1,
             _height: 256,
             _n_hash_bits: 251,
@@ -57,6 +60,7 @@ mod tests {
     fn test_new() {
         let builtin_instance = EcdsaInstanceDef {
             ratio: 8,
+            ratio_den: 1,
             _repetitions: 1,
             _height: 256,
             _n_hash_bits: 251,
@@ -68,6 +72,7 @@ mod tests {
     fn test_default() {
         let builtin_instance = EcdsaInstanceDef {
             ratio: 512,
+            ratio_den: 1,
             _repetitions: 1,
             _height: 256,
             _n_hash_bits: 251,
@@ -1,6 +1,7 @@
 #[derive(Clone, Debug, PartialEq)]
 pub(crate) struct KeccakInstanceDef {
     pub(crate) _ratio: u32,
+    pub(crate) ratio_den: u32,
     pub(crate) _state_rep: Vec<u32>,
     pub(crate) _instance_per_component: u32,
 }
@@ -10,6 +11,7 @@ impl Default for KeccakInstanceDef {
         Self {
             // _ratio should be equal to 2 ** 11 -> 2048
             _ratio: 2048,
+            ratio_den: 1,
             _state_rep: vec![200; 8],
             _instance_per_component: 16,
         }
@@ -53,6 +55,7 @@ mod tests {
     fn test_new() {
         let builtin_instance = KeccakInstanceDef {
             _ratio: 2048,
+            ratio_den: 1,
             _state_rep: vec![200; 8],
             _instance_per_component: 16,
         };
@@ -63,6 +66,7 @@ mod tests {
     fn test_default() {
         let builtin_instance = KeccakInstanceDef {
             _ratio: 2048,
+            ratio_den: 1,
             _state_rep: vec![200; 8],
             _instance_per_component: 16,
         };
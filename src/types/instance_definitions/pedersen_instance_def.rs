@@ -6,33 +6,36 @@ pub(crate) const INPUT_CELLS_PER_HASH: u32 = 2;
 #[derive(Debug, PartialEq)]
 pub(crate) struct PedersenInstanceDef {
     pub(crate) ratio: u32,
+    pub(crate) ratio_den: u32,
     pub(crate) _repetitions: u32,
     pub(crate) _element_height: u32,
     pub(crate) _element_bits: u32,
     pub(crate) _n_inputs: u32,
-    pub(crate) _hash_limit: BigInt,
+    pub(crate) hash_limit: BigInt,
 }
 
 impl PedersenInstanceDef {
     pub(crate) fn default() -> Self {
         PedersenInstanceDef {
             ratio: 8,
+            ratio_den: 1,
             _repetitions: 4,
             _element_height: 256,
             _element_bits: 252,
             _n_inputs: 2,
-            _hash_limit: BigInt::new(Sign::Plus, vec![1, 0, 0, 0, 0, 0, 17, 134217728]),
+            hash_limit: BigInt::new(Sign::Plus, vec![1, 0, 0, 0, 0, 0, 17, 134217728]),
         }
     }
 
     pub(crate) fn new(ratio: u32, _repetitions: u32) -> Self {
         PedersenInstanceDef {
             ratio,
+            ratio_den: 1,
             _repetitions,
             _element_height: 256,
             _element_bits: 252,
             _n_inputs: 2,
-            _hash_limit: BigInt::new(Sign::Plus, vec![1, 0, 0, 0, 0, 0, 17, 134217728]),
+            hash_limit: BigInt::new(Sign::Plus, vec![1, 0, 0, 0, 0, 0, 17, 134217728]),
         }
     }
 
@@ -65,11 +68,12 @@ mod tests {
     fn test_new() {
         let builtin_instance = PedersenInstanceDef {
             ratio: 10,
+            ratio_den: 1,
             _repetitions: 2,
             _element_height: 256,
             _element_bits: 252,
             _n_inputs: 2,
-            _hash_limit: BigInt::new(Sign::Plus, vec![1, 0, 0, 0, 0, 0, 17, 134217728]),
+            hash_limit: BigInt::new(Sign::Plus, vec![1, 0, 0, 0, 0, 0, 17, 134217728]),
         };
         assert_eq!(PedersenInstanceDef::new(10, 2), builtin_instance);
     }
@@ -78,11 +82,12 @@ mod tests {
     fn test_default() {
         let builtin_instance = PedersenInstanceDef {
             ratio: 8,
+            ratio_den: 1,
             _repetitions: 4,
             _element_height: 256,
             _element_bits: 252,
             _n_inputs: 2,
-            _hash_limit: BigInt::new(Sign::Plus, vec![1, 0, 0, 0, 0, 0, 17, 134217728]),
+            hash_limit: BigInt::new(Sign::Plus, vec![1, 0, 0, 0, 0, 0, 17, 134217728]),
         };
         assert_eq!(PedersenInstanceDef::default(), builtin_instance);
     }
@@ -1,8 +1,11 @@
 pub(crate) const CELLS_PER_RANGE_CHECK: u32 = 1;
+// The range_check96 builtin checks that its cells fit in 6 parts of 16 bits
+pub(crate) const N_PARTS_RANGE_CHECK96: u32 = 6;
 
 #[derive(Debug, PartialEq)]
 pub(crate) struct RangeCheckInstanceDef {
     pub(crate) ratio: u32,
+    pub(crate) ratio_den: u32,
     pub(crate) n_parts: u32,
 }
 
@@ -10,12 +13,25 @@ impl RangeCheckInstanceDef {
     pub(crate) fn default() -> Self {
         RangeCheckInstanceDef {
             ratio: 8,
+            ratio_den: 1,
             n_parts: 8,
         }
     }
 
     pub(crate) fn new(ratio: u32, n_parts: u32) -> Self {
-        RangeCheckInstanceDef { ratio, n_parts }
+        RangeCheckInstanceDef {
+            ratio,
+            ratio_den: 1,
+            n_parts,
+        }
+    }
+
+    pub(crate) fn range_check96() -> Self {
+        RangeCheckInstanceDef {
+            ratio: 8,
+            ratio_den: 1,
+            n_parts: N_PARTS_RANGE_CHECK96,
+        }
     }
 
     pub(crate) fn _cells_per_builtin(&self) -> u32 {
@@ -47,6 +63,7 @@ mod tests {
     fn test_new() {
         let builtin_instance = RangeCheckInstanceDef {
             ratio: 10,
+            ratio_den: 1,
             n_parts: 10,
         };
         assert_eq!(RangeCheckInstanceDef::new(10, 10), builtin_instance);
@@ -56,6 +73,7 @@ mod tests {
     fn test_default() {
         let builtin_instance = RangeCheckInstanceDef {
             ratio: 8,
+            ratio_den: 1,
             n_parts: 8,
         };
         assert_eq!(RangeCheckInstanceDef::default(), builtin_instance);
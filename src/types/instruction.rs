@@ -1,10 +1,12 @@
-use felt::Felt;
+use std::fmt;
+
+use felt::{Felt, FeltOps};
 use num_traits::ToPrimitive;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
 use crate::vm::decoding::decoder::decode_instruction;
 
-#[derive(Deserialize, Debug, PartialEq, Eq, Clone)]
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
 pub enum Register {
     AP,
     FP,
@@ -82,6 +84,86 @@ impl Instruction {
     }
 }
 
+fn fmt_register(register: &Register) -> &'static str {
+    match register {
+        Register::AP => "ap",
+        Register::FP => "fp",
+    }
+}
+
+fn fmt_memory_access(base: &str, offset: isize) -> String {
+    match offset {
+        0 => format!("[{base}]"),
+        offset if offset < 0 => format!("[{base} + ({offset})]"),
+        offset => format!("[{base} + {offset}]"),
+    }
+}
+
+impl Instruction {
+    fn fmt_op0(&self) -> String {
+        fmt_memory_access(fmt_register(&self.op0_register), self.off1)
+    }
+
+    fn fmt_op1(&self) -> String {
+        match self.op1_addr {
+            Op1Addr::Imm => match self.imm {
+                Some(ref imm) => imm.to_bigint().to_string(),
+                None => "?".to_string(),
+            },
+            Op1Addr::AP => fmt_memory_access("ap", self.off2),
+            Op1Addr::FP => fmt_memory_access("fp", self.off2),
+            Op1Addr::Op0 => fmt_memory_access(&self.fmt_op0(), self.off2),
+        }
+    }
+
+    fn fmt_res(&self) -> String {
+        // Negative immediates are parenthesized when used as an operand
+        let op1 = match self.fmt_op1() {
+            op1 if op1.starts_with('-') => format!("({op1})"),
+            op1 => op1,
+        };
+        match self.res {
+            Res::Op1 => self.fmt_op1(),
+            Res::Add => format!("{} + {op1}", self.fmt_op0()),
+            Res::Mul => format!("{} * {op1}", self.fmt_op0()),
+            Res::Unconstrained => "?".to_string(),
+        }
+    }
+}
+
+/// Disassembles the instruction into (approximate) Cairo assembly,
+/// e.g. `[ap] = [fp + (-3)] + 5; ap++`.
+impl fmt::Display for Instruction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let dst = fmt_memory_access(fmt_register(&self.dst_register), self.off0);
+        match self.opcode {
+            Opcode::Call => {
+                let mode = if self.pc_update == PcUpdate::JumpRel {
+                    "rel"
+                } else {
+                    "abs"
+                };
+                return write!(f, "call {mode} {}", self.fmt_op1());
+            }
+            Opcode::Ret => return write!(f, "ret"),
+            Opcode::AssertEq => write!(f, "{dst} = {}", self.fmt_res())?,
+            Opcode::NOp => match self.pc_update {
+                PcUpdate::Jump => write!(f, "jmp abs {}", self.fmt_res())?,
+                PcUpdate::JumpRel => write!(f, "jmp rel {}", self.fmt_res())?,
+                PcUpdate::Jnz => write!(f, "jmp rel {} if {dst} != 0", self.fmt_op1())?,
+                PcUpdate::Regular if self.ap_update == ApUpdate::Add => {
+                    return write!(f, "ap += {}", self.fmt_res())
+                }
+                PcUpdate::Regular => write!(f, "nop")?,
+            },
+        }
+        match self.ap_update {
+            ApUpdate::Add1 => write!(f, "; ap++"),
+            _ => Ok(()),
+        }
+    }
+}
+
 // Returns True if the given instruction looks like a call instruction.
 pub(crate) fn is_call_instruction(encoded_instruction: &Felt, imm: Option<&Felt>) -> bool {
     let encoded_i64_instruction: i64 = match encoded_instruction.to_i64() {
@@ -117,4 +199,40 @@ mod tests {
         let encoded_instruction = Felt::new(4612671187288031229_i64);
         assert!(!is_call_instruction(&encoded_instruction, None));
     }
+
+    fn disassemble(encoded_instruction: i64, imm: Option<i64>) -> String {
+        let imm = imm.map(Felt::new);
+        decode_instruction(encoded_instruction, imm.as_ref())
+            .unwrap()
+            .to_string()
+    }
+
+    #[test]
+    fn display_assert_eq_instructions() {
+        assert_eq!(disassemble(0x480680017fff8000, Some(5)), "[ap] = 5; ap++");
+        assert_eq!(
+            disassemble(0x48307fff7ffe8000, None),
+            "[ap] = [ap + (-2)] + [ap + (-1)]; ap++"
+        );
+        assert_eq!(
+            disassemble(0x484a7ffd7ffd8000, None),
+            "[ap] = [fp + (-3)] * [fp + (-3)]; ap++"
+        );
+        assert_eq!(
+            disassemble(0x4826800180008000, Some(-1)),
+            "[ap] = [fp] + (-1); ap++"
+        );
+    }
+
+    #[test]
+    fn display_control_flow_instructions() {
+        assert_eq!(disassemble(0x1104800180018000, Some(4)), "call rel 4");
+        assert_eq!(disassemble(0x208b7fff7fff7ffe, None), "ret");
+        assert_eq!(disassemble(0x10780017fff7fff, Some(4)), "jmp rel 4");
+        assert_eq!(
+            disassemble(0x20680017fff7fff, Some(-2)),
+            "jmp rel -2 if [ap + (-1)] != 0"
+        );
+        assert_eq!(disassemble(0x40780017fff7fff, Some(3)), "ap += 3");
+    }
 }
@@ -101,6 +101,62 @@ impl CairoLayout {
         }
     }
 
+    pub(crate) fn starknet_instance() -> CairoLayout {
+        CairoLayout {
+            _name: String::from("starknet"),
+            _cpu_component_step: 1,
+            rc_units: 4,
+            builtins: BuiltinsInstanceDef::starknet(),
+            _public_memory_fraction: 8,
+            _memory_units_per_step: 8,
+            diluted_pool_instance_def: Some(DilutedPoolInstanceDef::new(2, 4, 16)),
+            _n_trace_colums: 10,
+            _cpu_instance_def: CpuInstanceDef::default(),
+        }
+    }
+
+    pub(crate) fn starknet_with_keccak_instance() -> CairoLayout {
+        CairoLayout {
+            _name: String::from("starknet_with_keccak"),
+            _cpu_component_step: 1,
+            rc_units: 4,
+            builtins: BuiltinsInstanceDef::starknet_with_keccak(),
+            _public_memory_fraction: 8,
+            _memory_units_per_step: 8,
+            diluted_pool_instance_def: Some(DilutedPoolInstanceDef::new(4, 4, 16)),
+            _n_trace_colums: 15,
+            _cpu_instance_def: CpuInstanceDef::default(),
+        }
+    }
+
+    pub(crate) fn all_cairo_instance() -> CairoLayout {
+        CairoLayout {
+            _name: String::from("all_cairo"),
+            _cpu_component_step: 1,
+            rc_units: 4,
+            builtins: BuiltinsInstanceDef::all_cairo(),
+            _public_memory_fraction: 8,
+            _memory_units_per_step: 8,
+            diluted_pool_instance_def: Some(DilutedPoolInstanceDef::default()),
+            _n_trace_colums: 11,
+            _cpu_instance_def: CpuInstanceDef::default(),
+        }
+    }
+
+    pub(crate) fn all_solidity_instance() -> CairoLayout {
+        CairoLayout {
+            _name: String::from("all_solidity"),
+            _cpu_component_step: 1,
+            rc_units: 8,
+            builtins: BuiltinsInstanceDef::all_solidity(),
+            _public_memory_fraction: 8,
+            _memory_units_per_step: 8,
+            diluted_pool_instance_def: Some(DilutedPoolInstanceDef::default()),
+            _n_trace_colums: 27,
+            _cpu_instance_def: CpuInstanceDef::default(),
+        }
+    }
+
     pub(crate) fn all_instance() -> CairoLayout {
         CairoLayout {
             _name: String::from("all"),
@@ -219,6 +275,78 @@ mod tests {
         assert_eq!(layout._cpu_instance_def, CpuInstanceDef::default());
     }
 
+    #[test]
+    fn get_starknet_instance() {
+        let layout = CairoLayout::starknet_instance();
+        let builtins = BuiltinsInstanceDef::starknet();
+        assert_eq!(&layout._name, "starknet");
+        assert_eq!(layout._cpu_component_step, 1);
+        assert_eq!(layout.rc_units, 4);
+        assert_eq!(layout.builtins, builtins);
+        assert_eq!(layout._public_memory_fraction, 8);
+        assert_eq!(layout._memory_units_per_step, 8);
+        assert_eq!(
+            layout.diluted_pool_instance_def,
+            Some(DilutedPoolInstanceDef::new(2, 4, 16))
+        );
+        assert_eq!(layout._n_trace_colums, 10);
+        assert_eq!(layout._cpu_instance_def, CpuInstanceDef::default());
+    }
+
+    #[test]
+    fn get_starknet_with_keccak_instance() {
+        let layout = CairoLayout::starknet_with_keccak_instance();
+        let builtins = BuiltinsInstanceDef::starknet_with_keccak();
+        assert_eq!(&layout._name, "starknet_with_keccak");
+        assert_eq!(layout._cpu_component_step, 1);
+        assert_eq!(layout.rc_units, 4);
+        assert_eq!(layout.builtins, builtins);
+        assert_eq!(layout._public_memory_fraction, 8);
+        assert_eq!(layout._memory_units_per_step, 8);
+        assert_eq!(
+            layout.diluted_pool_instance_def,
+            Some(DilutedPoolInstanceDef::new(4, 4, 16))
+        );
+        assert_eq!(layout._n_trace_colums, 15);
+        assert_eq!(layout._cpu_instance_def, CpuInstanceDef::default());
+    }
+
+    #[test]
+    fn get_all_cairo_instance() {
+        let layout = CairoLayout::all_cairo_instance();
+        let builtins = BuiltinsInstanceDef::all_cairo();
+        assert_eq!(&layout._name, "all_cairo");
+        assert_eq!(layout._cpu_component_step, 1);
+        assert_eq!(layout.rc_units, 4);
+        assert_eq!(layout.builtins, builtins);
+        assert_eq!(layout._public_memory_fraction, 8);
+        assert_eq!(layout._memory_units_per_step, 8);
+        assert_eq!(
+            layout.diluted_pool_instance_def,
+            Some(DilutedPoolInstanceDef::default())
+        );
+        assert_eq!(layout._n_trace_colums, 11);
+        assert_eq!(layout._cpu_instance_def, CpuInstanceDef::default());
+    }
+
+    #[test]
+    fn get_all_solidity_instance() {
+        let layout = CairoLayout::all_solidity_instance();
+        let builtins = BuiltinsInstanceDef::all_solidity();
+        assert_eq!(&layout._name, "all_solidity");
+        assert_eq!(layout._cpu_component_step, 1);
+        assert_eq!(layout.rc_units, 8);
+        assert_eq!(layout.builtins, builtins);
+        assert_eq!(layout._public_memory_fraction, 8);
+        assert_eq!(layout._memory_units_per_step, 8);
+        assert_eq!(
+            layout.diluted_pool_instance_def,
+            Some(DilutedPoolInstanceDef::default())
+        );
+        assert_eq!(layout._n_trace_colums, 27);
+        assert_eq!(layout._cpu_instance_def, CpuInstanceDef::default());
+    }
+
     #[test]
     fn get_all_instance() {
         let layout = CairoLayout::all_instance();
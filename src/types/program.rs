@@ -5,19 +5,32 @@ use crate::{
     },
     types::{errors::program_errors::ProgramError, relocatable::MaybeRelocatable},
 };
-use felt::{Felt, PRIME_STR};
+use felt::{Felt, FeltOps, PRIME_STR};
+use once_cell::sync::OnceCell;
+use sha2::{Digest, Sha256};
 use std::{
     fs::File,
+    hash::{Hash, Hasher},
     io::{BufReader, Read},
     {collections::HashMap, path::Path},
 };
 
+/// The value of a constant of a program.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ConstValue {
+    Felt(Felt),
+    /// A label, as a pc relative to the program base
+    Label(usize),
+}
+
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct Program {
     pub builtins: Vec<String>,
     pub prime: String,
     pub data: Vec<MaybeRelocatable>,
     pub constants: HashMap<String, Felt>,
+    /// The constants whose value is a label, as a pc relative to the program base
+    pub label_constants: HashMap<String, usize>,
     pub main: Option<usize>,
     //start and end labels will only be used in proof-mode
     pub start: Option<usize>,
@@ -27,8 +40,29 @@ pub struct Program {
     pub identifiers: HashMap<String, Identifier>,
     pub error_message_attributes: Vec<Attribute>,
     pub instruction_locations: Option<HashMap<usize, InstructionLocation>>,
+    pub(crate) cached_id: IdCache,
+}
+
+/// The id of a program, computed on the first call to `Program::id`.
+/// It isn't part of the program's contents: it is left out of the comparisons, and clones start
+/// with an empty cache, as the fields of the clone may be changed before its id is computed.
+#[derive(Debug, Default)]
+pub(crate) struct IdCache(OnceCell<[u8; 32]>);
+
+impl Clone for IdCache {
+    fn clone(&self) -> Self {
+        IdCache::default()
+    }
+}
+
+impl PartialEq for IdCache {
+    fn eq(&self, _other: &Self) -> bool {
+        true
+    }
 }
 
+impl Eq for IdCache {}
+
 impl Program {
     #[allow(clippy::too_many_arguments)]
     pub fn new(
@@ -42,24 +76,13 @@ impl Program {
         error_message_attributes: Vec<Attribute>,
         instruction_locations: Option<HashMap<usize, InstructionLocation>>,
     ) -> Result<Program, ProgramError> {
+        let (constants, label_constants) = extract_constants(&identifiers)?;
         Ok(Self {
             builtins,
             prime,
             data,
-            constants: {
-                let mut constants = HashMap::new();
-                for (key, value) in identifiers.iter() {
-                    if value.type_.as_deref() == Some("const") {
-                        let value = value
-                            .value
-                            .clone()
-                            .ok_or_else(|| ProgramError::ConstWithoutValue(key.to_owned()))?;
-                        constants.insert(key.to_owned(), value);
-                    }
-                }
-
-                constants
-            },
+            constants,
+            label_constants,
             main,
             start: None,
             end: None,
@@ -68,6 +91,7 @@ impl Program {
             identifiers,
             error_message_attributes,
             instruction_locations,
+            cached_id: IdCache::default(),
         })
     }
 
@@ -84,6 +108,186 @@ impl Program {
     ) -> Result<Program, ProgramError> {
         deserialize_program(reader, entrypoint)
     }
+
+    /// Returns the value of the constant with the given full name, if any.
+    pub fn get_constant(&self, name: &str) -> Option<ConstValue> {
+        match self.constants.get(name) {
+            Some(value) => Some(ConstValue::Felt(value.clone())),
+            None => self
+                .label_constants
+                .get(name)
+                .copied()
+                .map(ConstValue::Label),
+        }
+    }
+
+    /// Returns the identifier with the given full name, such as `__main__.main`, if any.
+    pub fn get_identifier(&self, name: &str) -> Option<&Identifier> {
+        self.identifiers.get(name)
+    }
+
+    /// Returns a SHA-256 hash of the program's contents (prime, builtins, data, hints with their
+    /// flow tracking data, references, identifiers and proof mode start and end), computed on the
+    /// first call and cached afterwards.
+    /// `main` is left out as it is the entrypoint chosen when loading the program, so loading the
+    /// same program with different entrypoints yields the same id. The constants are derived from
+    /// the identifiers, and the error messages and instruction locations are debug information,
+    /// so they are left out too.
+    /// As the id is cached, changes made to the program after the first call are not reflected.
+    /// Clones start with an empty cache, so changes made to a clone are.
+    pub fn id(&self) -> [u8; 32] {
+        *self.cached_id.0.get_or_init(|| self.compute_id())
+    }
+
+    fn compute_id(&self) -> [u8; 32] {
+        let mut hasher = Sha256::new();
+        // Every field is length-prefixed so that different programs can't produce the same input
+        let mut update = |bytes: &[u8]| {
+            hasher.update(bytes.len().to_be_bytes());
+            hasher.update(bytes);
+        };
+
+        update(self.prime.as_bytes());
+        update(&self.builtins.len().to_be_bytes());
+        for builtin in &self.builtins {
+            update(builtin.as_bytes());
+        }
+        update(&self.data.len().to_be_bytes());
+        for value in &self.data {
+            match value {
+                MaybeRelocatable::Int(num) => update(&num.to_bytes_be()),
+                MaybeRelocatable::RelocatableValue(rel) => {
+                    update(&rel.segment_index.to_be_bytes());
+                    update(&rel.offset.to_be_bytes());
+                }
+            }
+        }
+
+        let mut hints: Vec<_> = self.hints.iter().collect();
+        hints.sort_by_key(|(pc, _)| **pc);
+        update(&hints.len().to_be_bytes());
+        for (pc, hint_list) in hints {
+            update(&pc.to_be_bytes());
+            update(&hint_list.len().to_be_bytes());
+            for hint in hint_list {
+                update(hint.code.as_bytes());
+                update(&hint.accessible_scopes.len().to_be_bytes());
+                for scope in &hint.accessible_scopes {
+                    update(scope.as_bytes());
+                }
+                let flow_tracking_data = &hint.flow_tracking_data;
+                update(&flow_tracking_data.ap_tracking.group.to_be_bytes());
+                update(&flow_tracking_data.ap_tracking.offset.to_be_bytes());
+                let mut reference_ids: Vec<_> = flow_tracking_data.reference_ids.iter().collect();
+                reference_ids.sort();
+                update(&reference_ids.len().to_be_bytes());
+                for (name, id) in reference_ids {
+                    update(name.as_bytes());
+                    update(&id.to_be_bytes());
+                }
+            }
+        }
+
+        let references = &self.reference_manager.references;
+        update(&references.len().to_be_bytes());
+        for reference in references {
+            update(&reference.ap_tracking_data.group.to_be_bytes());
+            update(&reference.ap_tracking_data.offset.to_be_bytes());
+            update_optional(
+                &mut update,
+                reference.pc.map(|pc| pc.to_be_bytes()).as_ref(),
+            );
+            update(format!("{:?}", reference.value_address).as_bytes());
+        }
+
+        update_optional(&mut update, self.start.map(|pc| pc.to_be_bytes()).as_ref());
+        update_optional(&mut update, self.end.map(|pc| pc.to_be_bytes()).as_ref());
+
+        let mut identifiers: Vec<_> = self.identifiers.iter().collect();
+        identifiers.sort_by_key(|(name, _)| *name);
+        update(&identifiers.len().to_be_bytes());
+        for (name, identifier) in identifiers {
+            update(name.as_bytes());
+            update_optional(&mut update, identifier.type_.as_ref());
+            update_optional(
+                &mut update,
+                identifier.pc.map(|pc| pc.to_be_bytes()).as_ref(),
+            );
+            update_optional(
+                &mut update,
+                identifier
+                    .value
+                    .as_ref()
+                    .map(|value| value.to_bytes_be())
+                    .as_ref(),
+            );
+            update_optional(&mut update, identifier.full_name.as_ref());
+            update_optional(
+                &mut update,
+                identifier
+                    .members
+                    .as_ref()
+                    .map(|members| members.len().to_be_bytes())
+                    .as_ref(),
+            );
+            let mut members: Vec<_> = identifier.members.iter().flatten().collect();
+            members.sort_by_key(|(name, _)| *name);
+            for (name, member) in members {
+                update(name.as_bytes());
+                update(member.cairo_type.as_bytes());
+                update(&member.offset.to_be_bytes());
+            }
+        }
+
+        hasher.finalize().into()
+    }
+}
+
+// Hashes a presence tag before an optional value, so that a missing value doesn't produce the
+// same input as an empty or zero one
+fn update_optional(update: &mut impl FnMut(&[u8]), value: Option<&impl AsRef<[u8]>>) {
+    match value {
+        Some(value) => {
+            update(&[1]);
+            update(value.as_ref());
+        }
+        None => update(&[0]),
+    }
+}
+
+// The felt constants and the label constants of a program
+type ProgramConstants = (HashMap<String, Felt>, HashMap<String, usize>);
+
+// Splits the constants of the program into the felt ones and the label ones.
+// Constants usually hold a felt value, but a hand-patched program may give a constant a pc
+// instead, making it a label.
+pub(crate) fn extract_constants(
+    identifiers: &HashMap<String, Identifier>,
+) -> Result<ProgramConstants, ProgramError> {
+    let mut constants = HashMap::new();
+    let mut label_constants = HashMap::new();
+    for (key, value) in identifiers.iter() {
+        if value.type_.as_deref() == Some("const") {
+            match (&value.value, value.pc) {
+                (Some(felt), _) => {
+                    constants.insert(key.to_owned(), felt.clone());
+                }
+                (None, Some(pc)) => {
+                    label_constants.insert(key.to_owned(), pc);
+                }
+                (None, None) => return Err(ProgramError::ConstWithoutValue(key.to_owned())),
+            }
+        }
+    }
+    Ok((constants, label_constants))
+}
+
+// Equal programs have the same id, as it is computed from their contents
+#[allow(clippy::derive_hash_xor_eq)]
+impl Hash for Program {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.id().hash(state)
+    }
 }
 
 impl Default for Program {
@@ -93,6 +297,7 @@ impl Default for Program {
             prime: PRIME_STR.to_string(),
             data: Vec::new(),
             constants: HashMap::new(),
+            label_constants: HashMap::new(),
             main: None,
             start: None,
             end: None,
@@ -103,13 +308,17 @@ impl Default for Program {
             identifiers: HashMap::new(),
             error_message_attributes: Vec::new(),
             instruction_locations: None,
+            cached_id: IdCache::default(),
         }
     }
 }
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::serde::deserialize_program::{ApTracking, FlowTrackingData};
+    use crate::serde::deserialize_program::{
+        ApTracking, FlowTrackingData, Member, OffsetValue, Reference, ValueAddress,
+    };
+    use crate::types::instruction::Register;
     use crate::utils::test_utils::mayberelocatable;
     use felt::{felt_str, NewFelt};
     use num_traits::Zero;
@@ -484,6 +693,27 @@ mod tests {
         assert_eq!(program.constants, constants);
     }
 
+    #[test]
+    fn get_identifier() {
+        let program = Program::from_file(
+            Path::new("cairo_programs/manually_compiled/valid_program_a.json"),
+            Some("main"),
+        )
+        .unwrap();
+
+        let main = program.get_identifier("__main__.main").unwrap();
+        assert_eq!(main.pc, Some(0));
+        assert_eq!(main.type_.as_deref(), Some("function"));
+        assert_eq!(
+            program
+                .get_identifier("__main__.main.SIZEOF_LOCALS")
+                .unwrap()
+                .value,
+            Some(Felt::zero())
+        );
+        assert_eq!(program.get_identifier("__main__.fib"), None);
+    }
+
     #[test]
     fn default_program() {
         let program = Program {
@@ -491,6 +721,7 @@ mod tests {
             prime: PRIME_STR.to_string(),
             data: Vec::new(),
             constants: HashMap::new(),
+            label_constants: HashMap::new(),
             main: None,
             start: None,
             end: None,
@@ -501,8 +732,291 @@ mod tests {
             identifiers: HashMap::new(),
             error_message_attributes: Vec::new(),
             instruction_locations: None,
+            cached_id: IdCache::default(),
         };
 
         assert_eq!(program, Program::default())
     }
+
+    #[test]
+    fn program_id_same_file_loaded_twice() {
+        let program_a = Program::from_file(
+            Path::new("cairo_programs/manually_compiled/valid_program_a.json"),
+            Some("main"),
+        )
+        .unwrap();
+        let program_b = Program::from_file(
+            Path::new("cairo_programs/manually_compiled/valid_program_a.json"),
+            Some("main"),
+        )
+        .unwrap();
+
+        assert_eq!(program_a.id(), program_b.id());
+        assert_eq!(program_a, program_b);
+    }
+
+    #[test]
+    fn program_id_unaffected_by_entrypoint() {
+        let program_a = Program::from_file(
+            Path::new("cairo_programs/manually_compiled/valid_program_a.json"),
+            Some("main"),
+        )
+        .unwrap();
+        let file = File::open("cairo_programs/manually_compiled/valid_program_a.json").unwrap();
+        let program_b = Program::from_reader(BufReader::new(file), None).unwrap();
+
+        assert_ne!(program_a.main, program_b.main);
+        assert_eq!(program_a.id(), program_b.id());
+    }
+
+    #[test]
+    fn program_id_changes_with_data() {
+        let program_a = Program::from_file(
+            Path::new("cairo_programs/manually_compiled/valid_program_a.json"),
+            Some("main"),
+        )
+        .unwrap();
+        let mut program_b = Program::from_file(
+            Path::new("cairo_programs/manually_compiled/valid_program_a.json"),
+            Some("main"),
+        )
+        .unwrap();
+        program_b.data[0] = mayberelocatable!(1);
+
+        assert_ne!(program_a.id(), program_b.id());
+        assert_ne!(program_a, program_b);
+    }
+
+    #[test]
+    fn program_id_of_modified_clone() {
+        let program = Program::from_file(
+            Path::new("cairo_programs/manually_compiled/valid_program_a.json"),
+            Some("main"),
+        )
+        .unwrap();
+        let id = program.id();
+        let mut clone = program.clone();
+        clone.data[0] = mayberelocatable!(1);
+
+        assert_ne!(clone.id(), id);
+        assert_eq!(program.id(), id);
+    }
+
+    #[test]
+    fn program_id_changes_with_references_and_proof_mode_labels() {
+        let program = Program::from_file(
+            Path::new("cairo_programs/manually_compiled/valid_program_a.json"),
+            Some("main"),
+        )
+        .unwrap();
+
+        let mut with_reference = program.clone();
+        with_reference.reference_manager.references.push(Reference {
+            ap_tracking_data: ApTracking::new(),
+            pc: Some(0),
+            value_address: ValueAddress {
+                offset1: OffsetValue::Reference(Register::FP, -3, false),
+                offset2: OffsetValue::Value(0),
+                dereference: true,
+                value_type: "felt".to_string(),
+            },
+        });
+        let mut moved_hint = program.clone();
+        moved_hint.hints.get_mut(&0).unwrap()[0]
+            .flow_tracking_data
+            .ap_tracking
+            .offset += 1;
+        let mut with_end = program.clone();
+        with_end.end = Some(program.data.len());
+
+        assert_ne!(with_reference.id(), program.id());
+        assert_ne!(moved_hint.id(), program.id());
+        assert_ne!(with_end.id(), program.id());
+    }
+
+    #[test]
+    fn program_hash_matches_id() {
+        use std::collections::hash_map::DefaultHasher;
+
+        let program = Program::from_file(
+            Path::new("cairo_programs/manually_compiled/valid_program_a.json"),
+            Some("main"),
+        )
+        .unwrap();
+        let (mut hasher_a, mut hasher_b) = (DefaultHasher::new(), DefaultHasher::new());
+        program.hash(&mut hasher_a);
+        program.clone().hash(&mut hasher_b);
+
+        assert_eq!(hasher_a.finish(), hasher_b.finish());
+    }
+
+    #[test]
+    fn program_eq_compares_contents() {
+        let program = Program::from_file(
+            Path::new("cairo_programs/manually_compiled/valid_program_a.json"),
+            Some("main"),
+        )
+        .unwrap();
+        // The cached id doesn't take part in the comparison
+        program.id();
+        assert_eq!(program, program.clone());
+
+        // Programs with the same id but different entrypoints or debug information differ
+        let file = File::open("cairo_programs/manually_compiled/valid_program_a.json").unwrap();
+        let without_main = Program::from_reader(BufReader::new(file), None).unwrap();
+        let mut without_locations = program.clone();
+        without_locations.instruction_locations = None;
+
+        assert_eq!(without_main.id(), program.id());
+        assert_ne!(without_main, program);
+        assert_eq!(without_locations.id(), program.id());
+        assert_ne!(without_locations, program);
+    }
+
+    #[test]
+    fn program_id_distinguishes_missing_values_from_zero_values() {
+        let program = Program::from_file(
+            Path::new("cairo_programs/manually_compiled/valid_program_a.json"),
+            Some("main"),
+        )
+        .unwrap();
+        assert_eq!(program.start, None);
+        let reference = Reference {
+            ap_tracking_data: ApTracking::new(),
+            pc: None,
+            value_address: ValueAddress {
+                offset1: OffsetValue::Reference(Register::FP, -3, false),
+                offset2: OffsetValue::Value(0),
+                dereference: true,
+                value_type: "felt".to_string(),
+            },
+        };
+        let identifier = Identifier {
+            pc: None,
+            type_: None,
+            value: None,
+            full_name: None,
+            members: None,
+        };
+
+        let mut with_start = program.clone();
+        with_start.start = Some(0);
+        let mut with_reference = program.clone();
+        with_reference
+            .reference_manager
+            .references
+            .push(reference.clone());
+        let mut with_reference_pc = program.clone();
+        with_reference_pc
+            .reference_manager
+            .references
+            .push(Reference {
+                pc: Some(0),
+                ..reference
+            });
+        let mut with_identifier = program.clone();
+        with_identifier
+            .identifiers
+            .insert("__main__.x".to_string(), identifier.clone());
+        let mut with_identifier_pc = program.clone();
+        with_identifier_pc.identifiers.insert(
+            "__main__.x".to_string(),
+            Identifier {
+                pc: Some(0),
+                ..identifier.clone()
+            },
+        );
+        let mut with_identifier_type = program.clone();
+        with_identifier_type.identifiers.insert(
+            "__main__.x".to_string(),
+            Identifier {
+                type_: Some(String::new()),
+                ..identifier
+            },
+        );
+
+        assert_ne!(with_start.id(), program.id());
+        assert_ne!(with_reference_pc.id(), with_reference.id());
+        assert_ne!(with_identifier_pc.id(), with_identifier.id());
+        assert_ne!(with_identifier_type.id(), with_identifier.id());
+    }
+
+    #[test]
+    fn program_id_separates_accessible_scopes() {
+        let program = Program::from_file(
+            Path::new("cairo_programs/manually_compiled/valid_program_a.json"),
+            Some("main"),
+        )
+        .unwrap();
+
+        let mut joined_scopes = program.clone();
+        joined_scopes.hints.get_mut(&0).unwrap()[0].accessible_scopes =
+            vec!["__main__,__main__.main".to_string()];
+        let mut split_scopes = program;
+        split_scopes.hints.get_mut(&0).unwrap()[0].accessible_scopes =
+            vec!["__main__".to_string(), "__main__.main".to_string()];
+
+        assert_ne!(joined_scopes.id(), split_scopes.id());
+    }
+
+    #[test]
+    fn program_id_changes_with_identifier_full_name_and_members() {
+        let program = Program::from_file(
+            Path::new("cairo_programs/manually_compiled/valid_program_a.json"),
+            Some("main"),
+        )
+        .unwrap();
+        let identifier = Identifier {
+            pc: None,
+            type_: Some("struct".to_string()),
+            value: None,
+            full_name: Some("__main__.Point".to_string()),
+            members: Some(HashMap::from([(
+                "x".to_string(),
+                Member {
+                    cairo_type: "felt".to_string(),
+                    offset: 0,
+                },
+            )])),
+        };
+
+        let mut with_identifier = program.clone();
+        with_identifier
+            .identifiers
+            .insert("__main__.Point".to_string(), identifier.clone());
+        let mut renamed = program.clone();
+        renamed.identifiers.insert(
+            "__main__.Point".to_string(),
+            Identifier {
+                full_name: Some("__main__.Vector".to_string()),
+                ..identifier.clone()
+            },
+        );
+        let mut moved_member = program.clone();
+        moved_member.identifiers.insert(
+            "__main__.Point".to_string(),
+            Identifier {
+                members: Some(HashMap::from([(
+                    "x".to_string(),
+                    Member {
+                        cairo_type: "felt".to_string(),
+                        offset: 1,
+                    },
+                )])),
+                ..identifier.clone()
+            },
+        );
+        let mut without_members = program;
+        without_members.identifiers.insert(
+            "__main__.Point".to_string(),
+            Identifier {
+                members: Some(HashMap::new()),
+                ..identifier
+            },
+        );
+
+        assert_ne!(renamed.id(), with_identifier.id());
+        assert_ne!(moved_member.id(), with_identifier.id());
+        assert_ne!(without_members.id(), with_identifier.id());
+    }
 }
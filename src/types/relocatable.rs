@@ -4,18 +4,19 @@ use crate::{
 };
 use felt::{Felt, NewFelt};
 use num_traits::{FromPrimitive, ToPrimitive, Zero};
+use serde::{Deserialize, Serialize};
 use std::{
     fmt::{self, Display},
     ops::Add,
 };
 
-#[derive(Eq, Hash, PartialEq, PartialOrd, Clone, Copy, Debug)]
+#[derive(Eq, Hash, PartialEq, PartialOrd, Clone, Copy, Debug, Serialize, Deserialize)]
 pub struct Relocatable {
     pub segment_index: isize,
     pub offset: usize,
 }
 
-#[derive(Eq, Hash, PartialEq, PartialOrd, Clone, Debug)]
+#[derive(Eq, Hash, PartialEq, PartialOrd, Clone, Debug, Serialize, Deserialize)]
 pub enum MaybeRelocatable {
     RelocatableValue(Relocatable),
     Int(Felt),
@@ -660,6 +661,7 @@ mod tests {
     }
 
     #[test]
+    #[allow(clippy::op_ref)]
     fn relocatable_add_i32() {
         let reloc = relocatable!(1, 5);
 
@@ -669,6 +671,7 @@ mod tests {
 
     #[test]
     #[should_panic]
+    #[allow(clippy::op_ref)]
     fn relocatable_add_i32_with_overflow() {
         let reloc = relocatable!(1, 1);
 
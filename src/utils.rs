@@ -1,6 +1,14 @@
 use crate::types::relocatable::Relocatable;
-use felt::Felt;
-use std::ops::Shr;
+use felt::PRIME_STR;
+use lazy_static::lazy_static;
+use num_bigint::BigInt;
+use num_traits::{Num, Signed};
+
+lazy_static! {
+    /// The Cairo prime as a BigInt, to be used with `as_int`.
+    pub static ref CAIRO_PRIME: BigInt = BigInt::from_str_radix(&PRIME_STR[2..], 16)
+        .expect("PRIME_STR is a valid hex number");
+}
 
 #[macro_export]
 macro_rules! relocatable {
@@ -41,17 +49,22 @@ pub fn from_relocatable_to_indexes(relocatable: &Relocatable) -> (usize, usize)
     }
 }
 
-///Converts val to an integer in the range (-prime/2, prime/2) which is
-///equivalent to val modulo prime.
-pub fn to_field_element(num: Felt, prime: Felt) -> Felt {
-    let half_prime = prime.shr(1);
-    if num > half_prime {
-        num - half_prime
+///Returns the lift of value, a field element in the range [0, prime), as an integer in the range
+///[-prime/2, prime/2). Matches `as_int` from starkware.cairo.common.math_utils, so prime // 2
+///itself is lifted to a negative number.
+pub fn as_int(value: &BigInt, prime: &BigInt) -> BigInt {
+    if value < &(prime >> 1_u32) {
+        value.clone()
     } else {
-        num
+        value - prime
     }
 }
 
+///Returns true if the lift of value given by `as_int` is strictly positive.
+pub fn is_positive_signed(value: &BigInt, prime: &BigInt) -> bool {
+    as_int(value, prime).is_positive()
+}
+
 #[cfg(test)]
 #[macro_use]
 pub mod test_utils {
@@ -221,9 +234,6 @@ pub mod test_utils {
         ($program:expr, $layout:expr, $proof_mode:expr) => {
             CairoRunner::new(&$program, $layout, $proof_mode).unwrap()
         };
-        ($program:expr, $layout:expr, $proof_mode:expr) => {
-            CairoRunner::new(&program, $layout.to_string(), proof_mode).unwrap()
-        };
     }
     pub(crate) use cairo_runner;
 
@@ -236,19 +246,7 @@ pub mod test_utils {
         ( $( $builtin_name: expr ),* ) => {
             Program {
                 builtins: vec![$( $builtin_name.to_string() ),*],
-                prime: "0x800000000000011000000000000000000000000000000000000000000000001".to_string(),
-                data: Vec::new(),
-                constants: HashMap::new(),
-                main: None,
-                start: None,
-                end: None,
-                hints: HashMap::new(),
-                reference_manager: ReferenceManager {
-                    references: Vec::new(),
-                },
-                identifiers: HashMap::new(),
-                error_message_attributes: Vec::new(),
-                instruction_locations: None,
+                ..Default::default()
             }
         };
         // Custom program definition
@@ -349,7 +347,13 @@ pub mod test_utils {
         ($vm:expr, $ids_data:expr, $hint_code:expr, $exec_scopes:expr, $constants:expr) => {{
             let hint_data = HintProcessorData::new_default($hint_code.to_string(), $ids_data);
             let mut hint_processor = BuiltinHintProcessor::new_empty();
-            hint_processor.execute_hint(&mut $vm, $exec_scopes, &any_box!(hint_data), $constants)
+            hint_processor.execute_hint(
+                &mut $vm,
+                $exec_scopes,
+                &any_box!(hint_data),
+                $constants,
+                &mut $crate::vm::runners::cairo_runner::RunResources::default(),
+            )
         }};
         ($vm:expr, $ids_data:expr, $hint_code:expr, $exec_scopes:expr) => {{
             let hint_data = HintProcessorData::new_default($hint_code.to_string(), $ids_data);
@@ -359,6 +363,7 @@ pub mod test_utils {
                 $exec_scopes,
                 &any_box!(hint_data),
                 &HashMap::new(),
+                &mut $crate::vm::runners::cairo_runner::RunResources::default(),
             )
         }};
         ($vm:expr, $ids_data:expr, $hint_code:expr) => {{
@@ -369,6 +374,7 @@ pub mod test_utils {
                 exec_scopes_ref!(),
                 &any_box!(hint_data),
                 &HashMap::new(),
+                &mut $crate::vm::runners::cairo_runner::RunResources::default(),
             )
         }};
     }
@@ -528,8 +534,8 @@ mod test {
             vm_core::VirtualMachine, vm_memory::memory::Memory,
         },
     };
-    use felt::{Felt, NewFelt};
-    use num_traits::One;
+    use felt::{Felt, FeltOps, NewFelt};
+    use num_traits::{Bounded, One};
     use std::{any::Any, cell::RefCell, collections::HashMap, rc::Rc};
 
     use super::*;
@@ -857,6 +863,74 @@ mod test {
         assert_eq!((0, 5), from_relocatable_to_indexes(&reloc_3));
     }
 
+    #[test]
+    fn as_int_zero() {
+        assert_eq!(as_int(&BigInt::from(0), &CAIRO_PRIME), BigInt::from(0));
+        assert!(!is_positive_signed(&BigInt::from(0), &CAIRO_PRIME));
+    }
+
+    #[test]
+    fn as_int_below_half_prime() {
+        let value: BigInt = &*CAIRO_PRIME / 2 - 1;
+        assert_eq!(as_int(&value, &CAIRO_PRIME), value);
+        assert!(is_positive_signed(&value, &CAIRO_PRIME));
+    }
+
+    #[test]
+    fn as_int_half_prime() {
+        let value: BigInt = &*CAIRO_PRIME / 2;
+        assert_eq!(
+            as_int(&value, &CAIRO_PRIME),
+            -(&*CAIRO_PRIME + BigInt::one()) / 2
+        );
+        assert!(!is_positive_signed(&value, &CAIRO_PRIME));
+    }
+
+    #[test]
+    fn as_int_half_prime_plus_one() {
+        let value: BigInt = &*CAIRO_PRIME / 2 + 1;
+        assert_eq!(
+            as_int(&value, &CAIRO_PRIME),
+            -(&*CAIRO_PRIME - BigInt::one()) / 2
+        );
+        assert!(!is_positive_signed(&value, &CAIRO_PRIME));
+    }
+
+    #[test]
+    fn as_int_prime_minus_one() {
+        let value: BigInt = &*CAIRO_PRIME - 1;
+        assert_eq!(as_int(&value, &CAIRO_PRIME), BigInt::from(-1));
+        assert!(!is_positive_signed(&value, &CAIRO_PRIME));
+    }
+
+    #[test]
+    fn as_int_small_prime() {
+        let prime = BigInt::from(7);
+        let lifted: Vec<BigInt> = (0..7).map(|i| as_int(&BigInt::from(i), &prime)).collect();
+        let expected: Vec<BigInt> = [0, 1, 2, -4, -3, -2, -1]
+            .into_iter()
+            .map(BigInt::from)
+            .collect();
+        assert_eq!(lifted, expected);
+    }
+
+    #[test]
+    fn as_int_matches_felt_to_bigint() {
+        for value in [
+            Felt::new(0),
+            Felt::new(1),
+            Felt::new(-1),
+            Felt::max_value() >> 1_u32,
+            (Felt::max_value() >> 1_u32) + 1_u32,
+            (Felt::max_value() >> 1_u32) + 2_u32,
+        ] {
+            assert_eq!(
+                as_int(&value.to_biguint().into(), &CAIRO_PRIME),
+                value.to_bigint()
+            );
+        }
+    }
+
     #[test]
     fn program_macro() {
         let program = Program {
@@ -864,6 +938,7 @@ mod test {
             prime: "0x800000000000011000000000000000000000000000000000000000000000001".to_string(),
             data: Vec::new(),
             constants: HashMap::new(),
+            label_constants: HashMap::new(),
             main: None,
             start: None,
             end: None,
@@ -874,6 +949,7 @@ mod test {
             identifiers: HashMap::new(),
             error_message_attributes: Vec::new(),
             instruction_locations: None,
+            cached_id: crate::types::program::IdCache::default(),
         };
 
         assert_eq!(program, program!())
@@ -886,6 +962,7 @@ mod test {
             prime: "0x800000000000011000000000000000000000000000000000000000000000001".to_string(),
             data: Vec::new(),
             constants: HashMap::new(),
+            label_constants: HashMap::new(),
             main: None,
             start: None,
             end: None,
@@ -896,6 +973,7 @@ mod test {
             identifiers: HashMap::new(),
             error_message_attributes: Vec::new(),
             instruction_locations: None,
+            cached_id: crate::types::program::IdCache::default(),
         };
 
         assert_eq!(program, program!["range_check"])
@@ -908,6 +986,7 @@ mod test {
             prime: "0x800000000000011000000000000000000000000000000000000000000000001".to_string(),
             data: Vec::new(),
             constants: HashMap::new(),
+            label_constants: HashMap::new(),
             main: Some(2),
             start: None,
             end: None,
@@ -918,6 +997,7 @@ mod test {
             identifiers: HashMap::new(),
             error_message_attributes: Vec::new(),
             instruction_locations: None,
+            cached_id: crate::types::program::IdCache::default(),
         };
 
         assert_eq!(
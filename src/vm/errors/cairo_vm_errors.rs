@@ -0,0 +1,113 @@
+use thiserror::Error;
+
+use crate::{
+    serde::deserialize_utils::ReferenceParseError,
+    types::errors::program_errors::ProgramError,
+    vm::errors::{
+        cairo_run_errors::CairoRunError, exec_scope_errors::ExecScopeError, hint_errors::HintError,
+        memory_errors::MemoryError, runner_errors::RunnerError, trace_errors::TraceError,
+        verification_errors::VerificationError, vm_errors::VirtualMachineError,
+        vm_exception::VmException,
+    },
+};
+use felt::ParseFeltError;
+
+///Any error of the crate, for applications that only need to propagate them with `?`.
+#[derive(Debug, Error)]
+pub enum CairoVmError {
+    #[error(transparent)]
+    Program(#[from] ProgramError),
+    #[error(transparent)]
+    ReferenceParse(#[from] ReferenceParseError),
+    #[error(transparent)]
+    ParseFelt(#[from] ParseFeltError),
+    #[error(transparent)]
+    CairoRun(#[from] CairoRunError),
+    #[error(transparent)]
+    VirtualMachine(#[from] VirtualMachineError),
+    #[error(transparent)]
+    VmException(#[from] VmException),
+    #[error(transparent)]
+    Runner(#[from] RunnerError),
+    #[error(transparent)]
+    Memory(#[from] MemoryError),
+    #[error(transparent)]
+    Trace(#[from] TraceError),
+    #[error(transparent)]
+    Hint(#[from] HintError),
+    #[error(transparent)]
+    ExecScope(#[from] ExecScopeError),
+    #[error(transparent)]
+    Verification(#[from] VerificationError),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        types::program::Program,
+        utils::test_utils::*,
+        vm::{runners::cairo_runner::CairoRunner, vm_core::VirtualMachine},
+    };
+    use std::io;
+
+    // Fails to compile if one of the crate's errors can't be propagated as a CairoVmError
+    fn propagate_each_error(error: u8) -> Result<(), CairoVmError> {
+        match error {
+            0 => Err(ProgramError::IO(io::Error::from(io::ErrorKind::NotFound)))?,
+            1 => Err(ReferenceParseError::InvalidStringError(String::new()))?,
+            2 => Err(ParseFeltError)?,
+            3 => Err(CairoRunError::VirtualMachine(
+                VirtualMachineError::Unexpected,
+            ))?,
+            4 => Err(VirtualMachineError::Unexpected)?,
+            5 => Err(VmException::from_vm_error(
+                &cairo_runner!(program!()),
+                &vm!(),
+                VirtualMachineError::NoImm,
+            ))?,
+            6 => Err(RunnerError::NoExecBase)?,
+            7 => Err(MemoryError::FoundNonInt)?,
+            8 => Err(TraceError::TraceNotEnabled)?,
+            9 => Err(HintError::WrongHintData)?,
+            10 => Err(ExecScopeError::ExitMainScopeError)?,
+            11 => Err(VerificationError::EmptyTrace)?,
+            _ => Ok(()),
+        }
+    }
+
+    #[test]
+    fn propagate_errors_as_cairo_vm_error() {
+        assert!(matches!(
+            propagate_each_error(7),
+            Err(CairoVmError::Memory(MemoryError::FoundNonInt))
+        ));
+        assert!(matches!(
+            propagate_each_error(9),
+            Err(CairoVmError::Hint(HintError::WrongHintData))
+        ));
+        assert_eq!(
+            propagate_each_error(11).unwrap_err().to_string(),
+            "The trace is empty"
+        );
+        assert!(propagate_each_error(12).is_ok());
+    }
+
+    #[test]
+    fn nested_conversions() {
+        let error: HintError = MemoryError::FoundNonInt.into();
+        assert_eq!(
+            error,
+            HintError::Internal(VirtualMachineError::MemoryError(MemoryError::FoundNonInt))
+        );
+        // Converting it back into a VirtualMachineError doesn't nest it into a Hint error
+        assert_eq!(
+            VirtualMachineError::from(error),
+            VirtualMachineError::MemoryError(MemoryError::FoundNonInt)
+        );
+        assert_eq!(
+            VirtualMachineError::from(HintError::WrongHintData),
+            VirtualMachineError::Hint(0, Box::new(HintError::WrongHintData))
+        );
+    }
+}
@@ -6,4 +6,6 @@ pub enum ExecScopeError {
     ExitMainScopeError,
     #[error("Every enter_scope() requires a corresponding exit_scope().")]
     NoScopeError,
+    #[error("Variable {0} refers to the missing dict manager {1}")]
+    MissingDictManager(String, usize),
 }
@@ -4,7 +4,10 @@ use thiserror::Error;
 
 use crate::types::relocatable::{MaybeRelocatable, Relocatable};
 
-use super::{exec_scope_errors::ExecScopeError, vm_errors::VirtualMachineError};
+use super::{
+    exec_scope_errors::ExecScopeError, memory_errors::MemoryError, runner_errors::RunnerError,
+    vm_errors::VirtualMachineError,
+};
 
 #[derive(Debug, PartialEq, Error)]
 pub enum HintError {
@@ -18,6 +21,10 @@ pub enum HintError {
     CustomHint(String),
     #[error("Missing constant: {0}")]
     MissingConstant(&'static str),
+    #[error("More than one constant matches {0}")]
+    AmbiguousConstant(&'static str),
+    #[error("Unknown constant: {0}")]
+    UnknownConstant(String),
     #[error("Fail to get constants for hint execution")]
     FailedToGetConstant,
     #[error("Arc too big, {0} must be <= {1} and {2} <= {3}")]
@@ -126,6 +133,8 @@ pub enum HintError {
     InvalidKeccakStateSizeFelts(Felt),
     #[error("Expected size to be in range from [0, 10), got: {0}")]
     InvalidBlockSize(Felt),
+    #[error("Expected N_PACKED_INSTANCES to be in range from [0, 20), got: {0}")]
+    InvalidNPackedInstances(Felt),
     #[error("Couldn't convert BigInt to u32")]
     BigintToU32Fail,
     #[error("BigInt to BigUint failed, BigInt is negative")]
@@ -144,4 +153,47 @@ pub enum HintError {
     NonLeFelt(Felt, Felt),
     #[error("Unknown Hint: {0}")]
     UnknownHint(String),
+    #[error(
+        "Failed to recover y coordinate: x = {0} is not the x coordinate of a point on the curve"
+    )]
+    RecoverYPointNotOnCurve(Felt),
+    #[error("Random ec point not on curve")]
+    RandomEcPointNotOnCurve,
+    #[error("chained_ec_op() can only be used with len<={0}. Got: n_elms={1}.")]
+    ChainedEcOpMaxLen(Felt, Felt),
+    #[error("ec_double: the y coordinate of the point (x = {0}) must be non-zero")]
+    EcDoubleYZero(Felt),
+    #[error("ec_add: the points must have different x coordinates, got x = {0}")]
+    EcAddSameX(Felt),
+    #[error("Unknown struct: {0}")]
+    UnknownStruct(String),
+    #[error("Struct {0} has no member named {1}")]
+    MissingStructMember(String, String),
+    #[error("Member {1} of struct {0} is out of bounds: {2}")]
+    StructMemberOutOfBounds(String, String, Relocatable),
+    #[error("Member {1} of struct {0} at {2} has no value")]
+    StructMemberHole(String, String, Relocatable),
+    #[error("Expected a cell or a cell plus an immediate as buffer operand, got: {0}")]
+    InvalidBufferOperand(String),
+    #[error("program_input['{0}'] is missing or doesn't have the expected type")]
+    InvalidProgramInput(String),
+    #[error("Limb {limb_index} of {struct_name} is {value}, which is not below {bound}")]
+    LimbOutOfBounds {
+        struct_name: String,
+        limb_index: usize,
+        value: Felt,
+        bound: Felt,
+    },
+}
+
+impl From<MemoryError> for HintError {
+    fn from(error: MemoryError) -> Self {
+        HintError::Internal(VirtualMachineError::MemoryError(error))
+    }
+}
+
+impl From<RunnerError> for HintError {
+    fn from(error: RunnerError) -> Self {
+        HintError::Internal(VirtualMachineError::RunnerError(error))
+    }
 }
@@ -1,6 +1,7 @@
 use thiserror::Error;
 
 use crate::types::relocatable::{MaybeRelocatable, Relocatable};
+use felt::Felt;
 
 #[derive(Debug, PartialEq, Eq, Error)]
 pub enum MemoryError {
@@ -8,13 +9,35 @@ pub enum MemoryError {
     UnallocatedSegment(usize, usize),
     #[error("Memory addresses must be relocatable")]
     AddressNotRelocatable,
-    #[error("Range-check validation failed, number is out of valid range")]
-    NumOutOfBounds,
+    #[error("Range-check validation failed, number {value} at {addr} is out of valid range")]
+    NumOutOfBounds { addr: Relocatable, value: Felt },
+    #[error(
+        "Keccak validation failed, input cell {addr} holds {value}, which isn't below {bound}"
+    )]
+    KeccakInputOutOfBounds {
+        addr: Relocatable,
+        value: Felt,
+        bound: Felt,
+    },
+    #[error("Address {addr} is out of the bounds of its segment, of size {size}")]
+    AddressOutOfSegmentBounds { addr: Relocatable, size: usize },
+    #[error(
+        "Segment {segment_index} is limited to {size} cells, but offset {offset} was written {}",
+        .pc.map_or_else(|| String::from("outside of a step"), |pc| format!("at pc {pc}"))
+    )]
+    SegmentSizeExceeded {
+        segment_index: usize,
+        offset: usize,
+        size: usize,
+        pc: Option<Relocatable>,
+    },
+    #[error("Can't get {n_ret} return values, ap is {ap}")]
+    ReturnValuesOutOfBounds { ap: Relocatable, n_ret: usize },
     #[error("Range-check validation failed, encountered non-int value")]
     FoundNonInt,
     #[error("Inconsistent memory assignment at address {0:?}. {1:?} != {2:?}")]
     InconsistentMemory(MaybeRelocatable, MaybeRelocatable, MaybeRelocatable),
-    #[error("compute_effective_sizes should be called before relocate_segments")]
+    #[error("compute_effective_sizes should be called first")]
     EffectiveSizesNotCalled,
     #[error("Inconsistent Relocation")]
     Relocation,
@@ -26,24 +49,55 @@ pub enum MemoryError {
     AddressNotInTemporarySegment(isize),
     #[error("Temporary segment found while relocating (flattening), segment: {0}")]
     TemporarySegmentInRelocation(isize),
+    #[error("Temporary segment {0} has no relocation rule, but is referenced from {1:?}")]
+    UnrelocatedTemporarySegment(isize, Vec<Relocatable>),
+    #[error("The builtin segment {0} must be relocated to the start of a segment, not to {1}")]
+    BuiltinSegmentRelocatedWithOffset(isize, Relocatable),
     #[error("The TemporarySegment: {0} doesn't have a relocation address")]
     NonZeroOffset(usize),
     #[error("Attempt to overwrite a relocation rule, segment: {0}")]
     DuplicatedRelocation(isize),
     #[error("accessed_addresses is None.")]
     MissingAccessedAddresses,
-    #[error("Segment effective sizes haven't been calculated.")]
-    MissingSegmentUsedSizes,
+    #[error("Effective size of segment {0} hasn't been calculated.")]
+    MissingSegmentUsedSizes(usize),
     #[error("Segment at index {0} either doesn't exist or is not finalized.")]
     SegmentNotFinalized(usize),
+    #[error("Segment {segment_index} can't be finalized to {size} cells, it uses {used_size}")]
+    FinalizedSizeBelowUsedSize {
+        segment_index: usize,
+        size: usize,
+        used_size: usize,
+    },
+    #[error("Segment {0} can't be relocated at address 0, relocated addresses start at 1")]
+    ZeroRelocationAddress(usize),
+    #[error("Segments {0} and {1} overlap once relocated")]
+    SegmentRelocationOverlap(usize, usize),
     #[error("Invalid memory value at address {0:?}: {1:?}")]
     InvalidMemoryValue(Relocatable, MaybeRelocatable),
     #[error("Found a memory gap when calling get_continuous_range")]
     GetRangeMemoryGap,
-    #[error("Error calculating builtin memory units")]
-    ErrorCalculatingMemoryUnits,
-    #[error("Number of steps is insufficient in the builtin.")]
-    InsufficientAllocatedCells,
+    #[error("Error calculating memory units of the {0} builtin")]
+    ErrorCalculatingMemoryUnits(&'static str),
+    #[error("The {builtin} builtin uses {used} cells, but only {allocated} are allocated")]
+    InsufficientAllocatedCells {
+        builtin: &'static str,
+        used: usize,
+        allocated: usize,
+    },
+    #[error("Number of steps must be at least {min_step} for the {builtin} builtin")]
+    MinStepNotReached {
+        builtin: &'static str,
+        min_step: usize,
+    },
+    #[error("Insufficient diluted units: {unused} unused, but {required} are required")]
+    InsufficientDilutedUnits { unused: usize, required: usize },
+    #[error("The builtins use {used} diluted units, but only {available} are available")]
+    DilutedUnitsExceeded { used: usize, available: usize },
+    #[error("Insufficient memory units: {unused} unused, but there are {holes} memory holes")]
+    InsufficientMemoryUnits { unused: u32, holes: usize },
+    #[error("Insufficient range check units: {0} used, but only {1} available")]
+    InsufficientRangeCheckUnits(usize, usize),
     #[error("Missing memory cells for builtin {0}")]
     MissingMemoryCells(&'static str),
     #[error("Missing memory cells for builtin {0}: {1:?}")]
@@ -62,4 +116,6 @@ pub enum MemoryError {
     ErrorVerifyingSignature,
     #[error("Couldn't obtain a mutable accessed offset")]
     CantGetMutAccessedOffset,
+    #[error("Can't insert into segment #{0}; the segment is frozen")]
+    FrozenSegment(isize),
 }
@@ -1,8 +1,9 @@
-use std::collections::HashSet;
+use std::collections::BTreeSet;
 
 use super::memory_errors::MemoryError;
-use crate::types::relocatable::MaybeRelocatable;
+use crate::types::relocatable::{MaybeRelocatable, Relocatable};
 use felt::Felt;
+use num_bigint::BigUint;
 use thiserror::Error;
 
 #[derive(Debug, PartialEq, Eq, Error)]
@@ -43,20 +44,26 @@ pub enum RunnerError {
     MemoryGet(MaybeRelocatable),
     #[error(transparent)]
     FailedMemoryGet(MemoryError),
-    #[error("EcOpBuiltin: m should be at most {0}")]
-    EcOpBuiltinScalarLimit(Felt),
+    #[error("EcOpBuiltin: m should be smaller than {1}, got {0}")]
+    EcOpScalarTooLarge(Felt, BigUint),
+    #[error("Pedersen input at address {0:?} should be smaller than {2}, got {1}")]
+    PedersenInputOverHashLimit(MaybeRelocatable, Felt, BigUint),
     #[error("Given builtins are not in appropiate order")]
     DisorderedBuiltins,
     #[error("Expected integer at address {0:?} to be smaller than 2^{1}, Got {2}")]
     IntegerBiggerThanPowerOfTwo(MaybeRelocatable, u32, Felt),
     #[error("{0}")]
     EcOpSameXCoordinate(String),
-    #[error("EcOpBuiltin: point {0:?} is not on the curve")]
-    PointNotOnCurve((usize, usize)),
+    #[error("EcOpBuiltin: point ({}, {}) is not on the curve", (.0).0, (.0).1)]
+    PointNotOnCurve((Felt, Felt)),
     #[error("Builtin(s) {0:?} not present in layout {1}")]
-    NoBuiltinForInstance(HashSet<String>, String),
+    NoBuiltinForInstance(BTreeSet<String>, String),
     #[error("Invalid layout {0}")]
     InvalidLayoutName(String),
+    #[error("Program prime {0} is not supported, it must be at most the Cairo prime")]
+    UnsupportedPrime(String),
+    #[error("Programs with a prime other than the Cairo prime can't use {0}, which work modulo the Cairo prime")]
+    UnsupportedWithCustomPrime(String),
     #[error("Run has already ended.")]
     RunAlreadyFinished,
     #[error("Run must be ended before calling finalize_segments.")]
@@ -64,15 +71,25 @@ pub enum RunnerError {
     #[error("Builtin {0} not included.")]
     BuiltinNotIncluded(String),
     #[error("Builtin segment name collision on '{0}'")]
-    BuiltinSegmentNameCollision(&'static str),
+    BuiltinSegmentNameCollision(String),
     #[error("Error while finalizing segments: {0}")]
     FinalizeSegements(MemoryError),
     #[error("finalize_segments called but proof_mode is not enabled")]
     FinalizeSegmentsNoProofMode,
-    #[error("Final stack error")]
-    FinalStack,
-    #[error("Invalid stop pointer for {0} ")]
-    InvalidStopPointer(String),
+    #[error("run_until_next_power_of_2 called but proof_mode is not enabled")]
+    PaddingNoProofMode,
+    #[error("Final stack error in the {0} builtin")]
+    FinalStack(&'static str),
+    #[error(
+        "Main returned fewer values than there are builtins, the stop pointer of the {0} builtin is missing"
+    )]
+    NoStopPointer(&'static str),
+    #[error("Invalid stop pointer for {builtin}: expected {expected}, found {found}")]
+    InvalidStopPointer {
+        builtin: &'static str,
+        expected: Relocatable,
+        found: Relocatable,
+    },
     #[error("Running in proof-mode but no __start__ label found, try compiling with proof-mode")]
     NoProgramStart,
     #[error("Running in proof-mode but no __end__ label found, try compiling with proof-mode")]
@@ -81,6 +98,8 @@ pub enum RunnerError {
     SliceToArrayError,
     #[error("Missing builtin: {0}")]
     MissingBuiltin(String),
+    #[error("The segments of the {0} builtin were already initialized")]
+    BuiltinAlreadyInitialized(&'static str),
     #[error("Cannot add the return values to the public memory after segment finalization.")]
     FailedAddingReturnValues,
     #[error("Missing execution public memory")]
@@ -93,6 +112,28 @@ pub enum RunnerError {
     FoundNonInt,
     #[error("{0} is not divisible by {1}")]
     SafeDivFailUsize(usize, usize),
+    #[error("Stop pointer {1} for the {0} builtin is not in its segment {2}")]
+    StopPointerSegmentMismatch(&'static str, Relocatable, isize),
+    #[error("The {0} builtin doesn't support getting or setting its state")]
+    StateNotSupported(&'static str),
+    #[error("The snapshot can't be restored into the {0} builtin, it was taken from another one")]
+    InvalidSnapshot(&'static str),
+    #[error("Can't compute the range check limits of an empty trace")]
+    NoRangeCheckLimits,
+    #[error("The segment base {0} doesn't refer to an empty segment that was already added")]
+    InvalidSegmentBase(Relocatable),
+    #[error("Child programs must be loaded in the program segment, not at {0}")]
+    ChildProgramOutsideProgramSegment(Relocatable),
+    #[error("The child program loaded at {0} overlaps the program memory already loaded")]
+    ChildProgramOverlap(Relocatable),
+    #[error("The additional data doesn't match the {0} builtin")]
+    InvalidAdditionalData(&'static str),
+    #[error("Page start {0} is not in the output segment {1}")]
+    PageNotOnSegment(Relocatable, isize),
+    #[error("Page {0} ends after the {1} cells of the output segment")]
+    PageOutOfBounds(usize, usize),
+    #[error("The return fp and pc of the run aren't relocatable values below the initial fp")]
+    NoReturnPointers,
     #[error(transparent)]
     MemoryError(#[from] MemoryError),
 }
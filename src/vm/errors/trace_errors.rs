@@ -1,4 +1,4 @@
-use crate::vm::errors::memory_errors::MemoryError;
+use crate::{types::relocatable::Relocatable, vm::errors::memory_errors::MemoryError};
 use thiserror::Error;
 
 #[derive(Debug, PartialEq, Eq, Error)]
@@ -7,10 +7,16 @@ pub enum TraceError {
     TraceNotEnabled,
     #[error("Trace is already relocated")]
     AlreadyRelocated,
+    #[error("Trace is not relocated, relocate must be called first")]
+    TraceNotRelocated,
     #[error("Trace register must be relocatable")]
     RegNotRelocatable,
     #[error("No relocation found for this segment")]
     NoRelocationFound,
+    #[error("Couldn't decode the instruction executed at {0}")]
+    InvalidInstruction(Relocatable),
+    #[error("Couldn't resolve operand {0} of the instruction executed at {1}")]
+    UnresolvedOperand(&'static str, Relocatable),
     #[error(transparent)]
     MemoryError(#[from] MemoryError),
 }
@@ -0,0 +1,47 @@
+use thiserror::Error;
+
+use crate::vm::errors::{runner_errors::RunnerError, vm_errors::VirtualMachineError};
+use felt::Felt;
+
+#[derive(Debug, PartialEq, Error)]
+pub enum VerificationError {
+    #[error("The trace is empty")]
+    EmptyTrace,
+    #[error("The memory at address {0} doesn't hold the program data")]
+    ProgramMismatch(usize),
+    #[error("Step {step}: pc {pc} is outside of the program")]
+    PcOutOfProgram { step: usize, pc: usize },
+    #[error("Step {step}: pc {pc} doesn't point to a valid instruction")]
+    InvalidInstruction { step: usize, pc: usize },
+    #[error("Step {step}: the address of {operand} is out of the memory")]
+    InvalidOperandAddress { step: usize, operand: &'static str },
+    #[error("Step {step}: {operand} at address {addr} is missing from the memory")]
+    MissingOperand {
+        step: usize,
+        operand: &'static str,
+        addr: usize,
+    },
+    #[error("Step {step}: assert_eq failed, dst is {dst} but res is {res}")]
+    AssertEqFailed { step: usize, dst: Felt, res: Felt },
+    #[error("Step {step}: call didn't save fp {fp}, dst is {dst}")]
+    CallFpNotSaved { step: usize, fp: usize, dst: Felt },
+    #[error("Step {step}: call didn't save the return pc {return_pc}, op0 is {op0}")]
+    CallReturnPcNotSaved {
+        step: usize,
+        return_pc: usize,
+        op0: Felt,
+    },
+    #[error("Step {step}: {register} should be {expected} after the instruction, but the trace has {found}")]
+    RegisterMismatch {
+        step: usize,
+        register: &'static str,
+        expected: Felt,
+        found: usize,
+    },
+    #[error("The pointers to the {0} builtin segment are invalid")]
+    InvalidBuiltinPointers(String),
+    #[error("Invalid {0} builtin segment: {1}")]
+    InvalidBuiltinSegment(String, VirtualMachineError),
+    #[error(transparent)]
+    Runner(#[from] RunnerError),
+}
@@ -7,6 +7,7 @@ use crate::{
 };
 use felt::Felt;
 use num_bigint::{BigInt, BigUint};
+use std::fmt;
 use thiserror::Error;
 
 #[derive(Debug, PartialEq, Error)]
@@ -57,6 +58,8 @@ pub enum VirtualMachineError {
     DiffIndexSub,
     #[error("Inconsistent auto-deduction for builtin {0}, expected {1}, got {2:?}")]
     InconsistentAutoDeduction(String, MaybeRelocatable, Option<MaybeRelocatable>),
+    #[error("Found {1} inconsistent auto-deductions:{}", format_mismatches(.0, .1))]
+    InconsistentAutoDeductions(Vec<AutoDeductionMismatch>, usize),
     #[error(transparent)]
     RunnerError(#[from] RunnerError),
     #[error("Invalid hint encoding at pc: {0}")]
@@ -67,6 +70,8 @@ pub enum VirtualMachineError {
     NoRangeCheckBuiltin,
     #[error("Expected ecdsa builtin to be present")]
     NoSignatureBuiltin,
+    #[error("Expected output builtin to be present")]
+    NoOutputBuiltin,
     #[error("Failed to retrieve value from address {0}")]
     MemoryGet(MaybeRelocatable),
     #[error("Expected integer at address {0}")]
@@ -119,6 +124,8 @@ pub enum VirtualMachineError {
     CantSubOffset(usize, usize),
     #[error("Execution reached the end of the program. Requested remaining steps: {0}.")]
     EndOfProgram(usize),
+    #[error("Execution ran out of steps before reaching its end, after running {0} steps")]
+    UnfinishedExecution(usize),
     #[error(transparent)]
     TracerError(#[from] TraceError),
     #[error(transparent)]
@@ -136,3 +143,54 @@ pub enum VirtualMachineError {
     #[error("Unexpected Failure")]
     Unexpected,
 }
+
+///Unwraps the errors of the VM raised from a hint. Other hint errors are taken as raised by the
+///first hint of their pc.
+impl From<HintError> for VirtualMachineError {
+    fn from(error: HintError) -> Self {
+        match error {
+            HintError::Internal(error) => error,
+            error => VirtualMachineError::Hint(0, Box::new(error)),
+        }
+    }
+}
+
+/// A builtin memory cell whose value differs from the one deduced by the builtin.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AutoDeductionMismatch {
+    pub builtin: String,
+    pub address: Relocatable,
+    pub expected: MaybeRelocatable,
+    pub actual: MaybeRelocatable,
+}
+
+impl AutoDeductionMismatch {
+    pub(crate) fn into_error(self) -> VirtualMachineError {
+        VirtualMachineError::InconsistentAutoDeduction(
+            self.builtin,
+            self.expected,
+            Some(self.actual),
+        )
+    }
+}
+
+impl fmt::Display for AutoDeductionMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} builtin at {}: expected {}, got {}",
+            self.builtin, self.address, self.expected, self.actual
+        )
+    }
+}
+
+fn format_mismatches(mismatches: &[AutoDeductionMismatch], total: &usize) -> String {
+    let mut message: String = mismatches
+        .iter()
+        .map(|mismatch| format!("\n    {mismatch}"))
+        .collect();
+    if *total > mismatches.len() {
+        message.push_str(&format!("\n    ... and {} more", total - mismatches.len()));
+    }
+    message
+}
@@ -5,6 +5,8 @@ use std::{
     path::Path,
 };
 
+use felt::FeltOps;
+use num_bigint::{BigInt, Sign};
 use thiserror::Error;
 
 use crate::{
@@ -14,7 +16,10 @@ use crate::{
     },
     serde::deserialize_program::{ApTracking, Attribute, Location, OffsetValue},
     types::{instruction::Register, relocatable::MaybeRelocatable},
-    vm::{runners::cairo_runner::CairoRunner, vm_core::VirtualMachine},
+    vm::{
+        runners::cairo_runner::CairoRunner,
+        vm_core::{VirtualMachine, MAX_TRACEBACK_ENTRIES},
+    },
 };
 
 use super::vm_errors::VirtualMachineError;
@@ -24,6 +29,7 @@ pub struct VmException {
     inst_location: Option<Location>,
     inner_exc: VirtualMachineError,
     error_attr_value: Option<String>,
+    assert_message: Option<String>,
     traceback: Option<String>,
 }
 
@@ -43,6 +49,7 @@ impl VmException {
         VmException {
             pc,
             inst_location: get_location(pc, runner, hint_index),
+            assert_message: get_assert_message(&error, error_attr_value.is_some()),
             inner_exc: error,
             error_attr_value,
             traceback: get_traceback(vm, runner),
@@ -50,6 +57,41 @@ impl VmException {
     }
 }
 
+// Formats a failed ASSERT_EQ instruction as `assertion failed: lhs != rhs (reason: '...')`,
+// provided that its lhs can be decoded as a short string. Single characters are only decoded when an error message
+// attribute is present, as small values such as 42 ('*') are seldom meant as a reason.
+fn get_assert_message(error: &VirtualMachineError, has_error_attr: bool) -> Option<String> {
+    match error {
+        VirtualMachineError::DiffAssertValues(lhs @ MaybeRelocatable::Int(num), rhs) => {
+            let reason = decode_panic_reason(&[num.to_bigint()]);
+            (reason.len() > 1 || (has_error_attr && !reason.is_empty()))
+                .then(|| format!("assertion failed: {lhs} != {rhs} (reason: '{reason}')"))
+        }
+        _ => None,
+    }
+}
+
+/// Decodes a sequence of felts as consecutive Cairo short strings.
+/// Felts that are not valid short strings (printable ascii, up to 31 characters) are skipped.
+pub fn decode_panic_reason(felts: &[BigInt]) -> String {
+    felts
+        .iter()
+        .filter_map(decode_short_string)
+        .collect::<Vec<String>>()
+        .concat()
+}
+
+fn decode_short_string(felt: &BigInt) -> Option<String> {
+    let (sign, bytes) = felt.to_bytes_be();
+    if sign != Sign::Plus || bytes.len() > 31 {
+        return None;
+    }
+    bytes
+        .iter()
+        .all(|byte| (0x20..0x7f).contains(byte))
+        .then(|| bytes.iter().map(|byte| *byte as char).collect())
+}
+
 pub fn get_error_attr_value(
     pc: usize,
     runner: &CairoRunner,
@@ -86,7 +128,8 @@ pub fn get_location(
 // Returns the traceback at the current pc.
 pub fn get_traceback(vm: &VirtualMachine, runner: &CairoRunner) -> Option<String> {
     let mut traceback = String::new();
-    for (_fp, traceback_pc) in vm.get_traceback_entries() {
+    for entry in vm.get_traceback_entries(MAX_TRACEBACK_ENTRIES) {
+        let traceback_pc = entry.call_pc;
         if let Some(ref attr) = get_error_attr_value(traceback_pc.offset, runner, vm) {
             traceback.push_str(attr)
         }
@@ -188,7 +231,10 @@ fn get_value_from_simple_reference(
 impl Display for VmException {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         // Build initial message
-        let message = format!("Error at pc=0:{}:\n{}", self.pc, self.inner_exc);
+        let message = match self.assert_message {
+            Some(ref assert_message) => format!("Error at pc=0:{}:\n{}", self.pc, assert_message),
+            None => format!("Error at pc=0:{}:\n{}", self.pc, self.inner_exc),
+        };
         let mut error_msg = String::new();
         // Add error attribute value
         if let Some(ref string) = self.error_attr_value {
@@ -271,6 +317,7 @@ impl Location {
 }
 #[cfg(test)]
 mod test {
+    use felt::{Felt, NewFelt};
     use std::collections::HashMap;
     use std::path::Path;
 
@@ -278,9 +325,13 @@ mod test {
     use crate::serde::deserialize_program::{
         Attribute, HintLocation, InputFile, InstructionLocation,
     };
+    use crate::types::exec_scope::ExecutionScopes;
     use crate::types::program::Program;
     use crate::types::relocatable::Relocatable;
     use crate::utils::test_utils::*;
+    use crate::vm::errors::memory_errors::MemoryError;
+    use crate::vm::runners::cairo_runner::RunResources;
+    use crate::vm::vm_memory::memory::Memory;
 
     use super::*;
     #[test]
@@ -308,6 +359,7 @@ mod test {
             inst_location: Some(location),
             inner_exc: VirtualMachineError::NoImm,
             error_attr_value: None,
+            assert_message: None,
             traceback: None,
         };
         assert_eq!(
@@ -364,6 +416,7 @@ mod test {
                 Relocatable::from((0, 4)),
             ),
             error_attr_value: None,
+            assert_message: None,
             traceback: None,
         };
         assert_eq!(
@@ -388,6 +441,7 @@ mod test {
                 Relocatable::from((0, 4)),
             ),
             error_attr_value: Some(String::from("Error message: Block may fail\n")),
+            assert_message: None,
             traceback: None,
         };
         assert_eq!(
@@ -422,6 +476,7 @@ mod test {
                 Relocatable::from((0, 4)),
             ),
             error_attr_value: None,
+            assert_message: None,
             traceback: None,
         };
         assert_eq!(
@@ -468,6 +523,7 @@ mod test {
                 Relocatable::from((0, 4)),
             ),
             error_attr_value: None,
+            assert_message: None,
             traceback: None,
         };
         assert_eq!(
@@ -606,7 +662,12 @@ mod test {
 
         let end = cairo_runner.initialize(&mut vm).unwrap();
         assert!(cairo_runner
-            .run_until_pc(end, &mut vm, &mut hint_processor)
+            .run_until_pc(
+                end,
+                &mut RunResources::default(),
+                &mut vm,
+                &mut hint_processor
+            )
             .is_err());
         let expected_traceback = String::from("Cairo traceback (most recent call last):\ncairo_programs/bad_programs/bad_dict_update.cairo:10:5: (pc=0:34)\n    dict_update{dict_ptr=my_dict}(key=2, prev_value=3, new_value=4);\n    ^*************************************************************^\n");
         assert_eq!(get_traceback(&vm, &cairo_runner), Some(expected_traceback));
@@ -626,7 +687,12 @@ mod test {
 
         let end = cairo_runner.initialize(&mut vm).unwrap();
         assert!(cairo_runner
-            .run_until_pc(end, &mut vm, &mut hint_processor)
+            .run_until_pc(
+                end,
+                &mut RunResources::default(),
+                &mut vm,
+                &mut hint_processor
+            )
             .is_err());
         let expected_traceback = String::from("Cairo traceback (most recent call last):\ncairo_programs/bad_programs/bad_usort.cairo:91:48: (pc=0:97)\n    let (output_len, output, multiplicities) = usort(input_len=3, input=input_array);\n                                               ^***********************************^\ncairo_programs/bad_programs/bad_usort.cairo:36:5: (pc=0:30)\n    verify_usort{output=output}(\n    ^**************************^\ncairo_programs/bad_programs/bad_usort.cairo:64:5: (pc=0:60)\n    verify_multiplicity(multiplicity=multiplicity, input_len=input_len, input=input, value=value);\n    ^*******************************************************************************************^\n");
         assert_eq!(get_traceback(&vm, &cairo_runner), Some(expected_traceback));
@@ -761,7 +827,12 @@ cairo_programs/bad_programs/bad_range_check.cairo:11:5: (pc=0:6)
 
         let end = cairo_runner.initialize(&mut vm).unwrap();
         let error = cairo_runner
-            .run_until_pc(end, &mut vm, &mut hint_processor)
+            .run_until_pc(
+                end,
+                &mut RunResources::default(),
+                &mut vm,
+                &mut hint_processor,
+            )
             .unwrap_err();
         let vm_excepction = VmException::from_vm_error(&cairo_runner, &vm, error);
         assert_eq!(vm_excepction.to_string(), expected_error_string);
@@ -796,7 +867,12 @@ cairo_programs/bad_programs/bad_usort.cairo:64:5: (pc=0:60)
 
         let end = cairo_runner.initialize(&mut vm).unwrap();
         let error = cairo_runner
-            .run_until_pc(end, &mut vm, &mut hint_processor)
+            .run_until_pc(
+                end,
+                &mut RunResources::default(),
+                &mut vm,
+                &mut hint_processor,
+            )
             .unwrap_err();
         let vm_excepction = VmException::from_vm_error(&cairo_runner, &vm, error);
         assert_eq!(vm_excepction.to_string(), expected_error_string);
@@ -879,4 +955,111 @@ cairo_programs/bad_programs/bad_usort.cairo:64:5: (pc=0:60)
             )
         );
     }
+
+    #[test]
+    fn decode_panic_reason_short_strings() {
+        let felts = [
+            BigInt::from_bytes_be(Sign::Plus, b"not "),
+            BigInt::from_bytes_be(Sign::Plus, b"one"),
+        ];
+        assert_eq!(decode_panic_reason(&felts), String::from("not one"));
+    }
+
+    #[test]
+    fn decode_panic_reason_skips_non_printable_felts() {
+        let felts = [BigInt::from(1), BigInt::from(-5), BigInt::from(0x6f6b)];
+        assert_eq!(decode_panic_reason(&felts), String::from("ok"));
+    }
+
+    #[test]
+    fn vm_exception_display_assert_eq_with_short_string_reason() {
+        let mut vm = vm!();
+        // [ap] = 1 while [ap] was set to the short string 'not one'
+        vm.memory = memory![
+            ((0, 0), 0x480680017fff8000_i64),
+            ((0, 1), 1),
+            ((1, 1), 5),
+            ((1, 2), 31084791989235301_i64)
+        ];
+        run_context!(vm, 0, 2, 2);
+        let mut hint_processor = BuiltinHintProcessor::new_empty();
+        let error = vm
+            .step(
+                &mut hint_processor,
+                exec_scopes_ref!(),
+                &HashMap::new(),
+                &HashMap::new(),
+                &mut RunResources::default(),
+            )
+            .unwrap_err();
+        assert_eq!(
+            vm.get_last_assert_operands(),
+            Some((
+                &MaybeRelocatable::from(Felt::new(31084791989235301_i64)),
+                &MaybeRelocatable::from(Felt::new(5)),
+                &MaybeRelocatable::from(Felt::new(1)),
+            ))
+        );
+        let runner = cairo_runner!(program!());
+        let vm_excep = VmException::from_vm_error(&runner, &vm, error);
+        assert_eq!(
+            vm_excep.to_string(),
+            String::from(
+                "Error at pc=0:0:\nassertion failed: 31084791989235301 != 1 (reason: 'not one')\n"
+            )
+        );
+    }
+
+    #[test]
+    fn vm_exception_display_assert_eq_without_reason() {
+        let runner = cairo_runner!(program!());
+        let error = VirtualMachineError::DiffAssertValues(
+            MaybeRelocatable::from((1, 2)),
+            MaybeRelocatable::from(Felt::new(1)),
+        );
+        let vm_excep = VmException::from_vm_error(&runner, &vm!(), error);
+        assert_eq!(
+            vm_excep.to_string(),
+            String::from("Error at pc=0:0:\nAn ASSERT_EQ instruction failed: 1:2 != 1.\n")
+        );
+    }
+
+    #[test]
+    fn vm_exception_display_assert_eq_single_char_without_error_attr() {
+        let runner = cairo_runner!(program!());
+        // 42 would decode as '*', which is not meant as a reason
+        let error = VirtualMachineError::DiffAssertValues(
+            MaybeRelocatable::from(Felt::new(42)),
+            MaybeRelocatable::from(Felt::new(1)),
+        );
+        let vm_excep = VmException::from_vm_error(&runner, &vm!(), error);
+        assert_eq!(
+            vm_excep.to_string(),
+            String::from("Error at pc=0:0:\nAn ASSERT_EQ instruction failed: 42 != 1.\n")
+        );
+    }
+
+    #[test]
+    fn vm_exception_display_assert_eq_single_char_with_error_attr() {
+        let attributes = vec![Attribute {
+            name: String::from("Error message"),
+            start_pc: 0,
+            end_pc: 1,
+            value: String::from("Invalid grade"),
+            flow_tracking_data: None,
+        }];
+        let program = program!(error_message_attributes = attributes,);
+        let runner = cairo_runner!(program);
+        let error = VirtualMachineError::DiffAssertValues(
+            MaybeRelocatable::from(Felt::new(0x46)),
+            MaybeRelocatable::from(Felt::new(0x41)),
+        );
+        let vm_excep = VmException::from_vm_error(&runner, &vm!(), error);
+        assert_eq!(
+            vm_excep.to_string(),
+            String::from(
+                "Error message: Invalid grade\nError at pc=0:0:\nassertion failed: 70 != 65 (reason: 'F')\n"
+            )
+        );
+    }
 }
@@ -4,5 +4,6 @@ pub mod errors;
 pub mod runners;
 pub mod security;
 pub mod trace;
+pub mod verification;
 pub mod vm_core;
 pub mod vm_memory;
@@ -0,0 +1,146 @@
+use felt::{Felt, FeltOps};
+use serde::{Serialize, Serializer};
+use std::collections::BTreeMap;
+
+/// The private input of the AIR, as consumed by the prover: the inputs of each builtin
+/// instance, keyed by the builtin's name.
+#[derive(Serialize, Clone, Debug, PartialEq, Eq)]
+pub struct AirPrivateInput {
+    #[serde(flatten)]
+    pub builtins: BTreeMap<String, Vec<PrivateInput>>,
+}
+
+/// The inputs of a builtin instance. The shape of each variant, and its serialization, match
+/// the private input generated by cairo-lang for the corresponding builtins.
+#[derive(Serialize, Clone, Debug, PartialEq, Eq)]
+#[serde(untagged)]
+pub enum PrivateInput {
+    /// range_check
+    Value(PrivateInputValue),
+    /// pedersen and bitwise
+    Pair(PrivateInputPair),
+    EcOp(PrivateInputEcOp),
+    PoseidonState(PrivateInputPoseidonState),
+    KeccakState(PrivateInputKeccakState),
+    Signature(PrivateInputSignature),
+}
+
+#[derive(Serialize, Clone, Debug, PartialEq, Eq)]
+pub struct PrivateInputValue {
+    pub index: usize,
+    #[serde(serialize_with = "serialize_felt_hex")]
+    pub value: Felt,
+}
+
+#[derive(Serialize, Clone, Debug, PartialEq, Eq)]
+pub struct PrivateInputPair {
+    pub index: usize,
+    #[serde(serialize_with = "serialize_felt_hex")]
+    pub x: Felt,
+    #[serde(serialize_with = "serialize_felt_hex")]
+    pub y: Felt,
+}
+
+#[derive(Serialize, Clone, Debug, PartialEq, Eq)]
+pub struct PrivateInputEcOp {
+    pub index: usize,
+    #[serde(serialize_with = "serialize_felt_hex")]
+    pub p_x: Felt,
+    #[serde(serialize_with = "serialize_felt_hex")]
+    pub p_y: Felt,
+    #[serde(serialize_with = "serialize_felt_hex")]
+    pub m: Felt,
+    #[serde(serialize_with = "serialize_felt_hex")]
+    pub q_x: Felt,
+    #[serde(serialize_with = "serialize_felt_hex")]
+    pub q_y: Felt,
+}
+
+#[derive(Serialize, Clone, Debug, PartialEq, Eq)]
+pub struct PrivateInputPoseidonState {
+    pub index: usize,
+    #[serde(serialize_with = "serialize_felt_hex")]
+    pub input_s0: Felt,
+    #[serde(serialize_with = "serialize_felt_hex")]
+    pub input_s1: Felt,
+    #[serde(serialize_with = "serialize_felt_hex")]
+    pub input_s2: Felt,
+}
+
+#[derive(Serialize, Clone, Debug, PartialEq, Eq)]
+pub struct PrivateInputKeccakState {
+    pub index: usize,
+    #[serde(serialize_with = "serialize_felt_hex")]
+    pub input_s0: Felt,
+    #[serde(serialize_with = "serialize_felt_hex")]
+    pub input_s1: Felt,
+    #[serde(serialize_with = "serialize_felt_hex")]
+    pub input_s2: Felt,
+    #[serde(serialize_with = "serialize_felt_hex")]
+    pub input_s3: Felt,
+    #[serde(serialize_with = "serialize_felt_hex")]
+    pub input_s4: Felt,
+    #[serde(serialize_with = "serialize_felt_hex")]
+    pub input_s5: Felt,
+    #[serde(serialize_with = "serialize_felt_hex")]
+    pub input_s6: Felt,
+    #[serde(serialize_with = "serialize_felt_hex")]
+    pub input_s7: Felt,
+}
+
+#[derive(Serialize, Clone, Debug, PartialEq, Eq)]
+pub struct PrivateInputSignature {
+    pub index: usize,
+    #[serde(serialize_with = "serialize_felt_hex")]
+    pub pubkey: Felt,
+    #[serde(serialize_with = "serialize_felt_hex")]
+    pub msg: Felt,
+    pub signature_input: SignatureInput,
+}
+
+/// An ECDSA signature as the prover expects it: `w` is the inverse of `s` modulo the order of
+/// the curve.
+#[derive(Serialize, Clone, Debug, PartialEq, Eq)]
+pub struct SignatureInput {
+    #[serde(serialize_with = "serialize_felt_hex")]
+    pub r: Felt,
+    #[serde(serialize_with = "serialize_felt_hex")]
+    pub w: Felt,
+}
+
+fn serialize_felt_hex<S: Serializer>(value: &Felt, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(&format!("0x{}", value.to_str_radix(16)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use felt::NewFelt;
+
+    #[test]
+    fn serialize_air_private_input() {
+        let private_input = AirPrivateInput {
+            builtins: BTreeMap::from([
+                (
+                    String::from("pedersen"),
+                    vec![PrivateInput::Pair(PrivateInputPair {
+                        index: 0,
+                        x: Felt::new(0x20),
+                        y: Felt::new(0x48),
+                    })],
+                ),
+                (
+                    String::from("range_check"),
+                    vec![PrivateInput::Value(PrivateInputValue {
+                        index: 1,
+                        value: Felt::new(0xabc),
+                    })],
+                ),
+            ]),
+        };
+        assert_eq!(
+            serde_json::to_string(&private_input).unwrap(),
+            r#"{"pedersen":[{"index":0,"x":"0x20","y":"0x48"}],"range_check":[{"index":1,"value":"0xabc"}]}"#
+        );
+    }
+}
@@ -1,5 +1,4 @@
 use crate::{
-    math_utils::safe_div_usize,
     types::{
         instance_definitions::bitwise_instance_def::{
             BitwiseInstanceDef, CELLS_PER_BITWISE, INPUT_CELLS_PER_BITWISE,
@@ -8,6 +7,10 @@ use crate::{
     },
     vm::{
         errors::{memory_errors::MemoryError, runner_errors::RunnerError},
+        runners::{
+            air_private_input::{PrivateInput, PrivateInputPair},
+            builtin_runner::{get_allocated_instances, get_instances_inputs, SegmentAddresses},
+        },
         vm_core::VirtualMachine,
         vm_memory::{memory::Memory, memory_segments::MemorySegmentManager},
     },
@@ -18,25 +21,31 @@ use num_integer::div_ceil;
 #[derive(Debug, Clone)]
 pub struct BitwiseBuiltinRunner {
     ratio: u32,
+    pub(crate) ratio_den: u32,
     pub base: isize,
+    pub(crate) initialized: bool,
     pub(crate) cells_per_instance: u32,
     pub(crate) n_input_cells: u32,
     bitwise_builtin: BitwiseInstanceDef,
     pub(crate) stop_ptr: Option<usize>,
-    pub(crate) _included: bool,
+    pub(crate) included: bool,
     instances_per_component: u32,
 }
 
 impl BitwiseBuiltinRunner {
+    pub const NAME: &str = "bitwise";
+
     pub(crate) fn new(instance_def: &BitwiseInstanceDef, include: bool) -> Self {
         BitwiseBuiltinRunner {
             base: 0,
+            initialized: false,
             ratio: instance_def.ratio,
+            ratio_den: instance_def.ratio_den,
             cells_per_instance: CELLS_PER_BITWISE,
             n_input_cells: INPUT_CELLS_PER_BITWISE,
             bitwise_builtin: instance_def.clone(),
             stop_ptr: None,
-            _included: include,
+            included: include,
             instances_per_component: 1,
         }
     }
@@ -45,12 +54,17 @@ impl BitwiseBuiltinRunner {
         &mut self,
         segments: &mut MemorySegmentManager,
         memory: &mut Memory,
-    ) {
-        self.base = segments.add(memory).segment_index
+    ) -> Result<(), RunnerError> {
+        if self.initialized {
+            return Err(RunnerError::BuiltinAlreadyInitialized(Self::NAME));
+        }
+        self.base = segments.add(memory).segment_index;
+        self.initialized = true;
+        Ok(())
     }
 
     pub fn initial_stack(&self) -> Vec<MaybeRelocatable> {
-        if self._included {
+        if self.included {
             vec![MaybeRelocatable::from((self.base, 0))]
         } else {
             vec![]
@@ -61,10 +75,18 @@ impl BitwiseBuiltinRunner {
         self.base
     }
 
+    pub fn is_initialized(&self) -> bool {
+        self.initialized
+    }
+
     pub fn ratio(&self) -> u32 {
         self.ratio
     }
 
+    pub fn ratio_den(&self) -> u32 {
+        self.ratio_den
+    }
+
     pub fn add_validation_rule(&self, _memory: &mut Memory) -> Result<(), RunnerError> {
         Ok(())
     }
@@ -116,41 +138,66 @@ impl BitwiseBuiltinRunner {
     }
 
     pub fn get_allocated_memory_units(&self, vm: &VirtualMachine) -> Result<usize, MemoryError> {
-        let value = safe_div_usize(vm.current_step, self.ratio as usize)
-            .map_err(|_| MemoryError::ErrorCalculatingMemoryUnits)?;
+        let value =
+            get_allocated_instances(Self::NAME, vm.current_step, self.ratio, self.ratio_den)?;
         Ok(self.cells_per_instance as usize * value)
     }
 
-    pub fn get_memory_segment_addresses(&self) -> (&'static str, (isize, Option<usize>)) {
-        ("bitwise", (self.base, self.stop_ptr))
+    pub fn air_private_input(&self, memory: &Memory) -> Vec<PrivateInput> {
+        get_instances_inputs::<2>(memory, self.base, self.cells_per_instance)
+            .into_iter()
+            .map(|(index, [x, y])| PrivateInput::Pair(PrivateInputPair { index, x, y }))
+            .collect()
+    }
+
+    pub fn get_memory_segment_addresses(&self) -> Vec<SegmentAddresses> {
+        vec![SegmentAddresses::new(Self::NAME, self.base, self.stop_ptr)]
     }
 
     pub fn get_used_cells(&self, vm: &VirtualMachine) -> Result<usize, MemoryError> {
         let base = self.base();
+        let index = base
+            .try_into()
+            .map_err(|_| MemoryError::AddressInTemporarySegment(base))?;
         vm.segments
-            .get_segment_used_size(
-                base.try_into()
-                    .map_err(|_| MemoryError::AddressInTemporarySegment(base))?,
-            )
-            .ok_or(MemoryError::MissingSegmentUsedSizes)
+            .get_segment_used_size(index)
+            .ok_or(MemoryError::MissingSegmentUsedSizes(index))
+    }
+
+    pub fn get_used_cells_mid_run(&self, vm: &VirtualMachine) -> Result<usize, MemoryError> {
+        let base = self.base();
+        let index = base
+            .try_into()
+            .map_err(|_| MemoryError::AddressInTemporarySegment(base))?;
+        vm.segments
+            .get_segment_used_size_mid_run(index, &vm.memory)
+            .ok_or(MemoryError::UnallocatedSegment(index, vm.memory.data.len()))
     }
 
     pub fn get_used_cells_and_allocated_size(
         &self,
         vm: &VirtualMachine,
     ) -> Result<(usize, usize), MemoryError> {
-        let ratio = self.ratio as usize;
         let cells_per_instance = self.cells_per_instance;
-        let min_step = ratio * self.instances_per_component as usize;
+        let min_step = div_ceil(
+            self.ratio as usize * self.instances_per_component as usize,
+            self.ratio_den as usize,
+        );
         if vm.current_step < min_step {
-            Err(MemoryError::InsufficientAllocatedCells)
+            Err(MemoryError::MinStepNotReached {
+                builtin: Self::NAME,
+                min_step,
+            })
         } else {
             let used = self.get_used_cells(vm)?;
             let size = cells_per_instance as usize
-                * safe_div_usize(vm.current_step, ratio)
-                    .map_err(|_| MemoryError::InsufficientAllocatedCells)?;
+                * get_allocated_instances(Self::NAME, vm.current_step, self.ratio, self.ratio_den)?;
             if used > size {
-                return Err(MemoryError::InsufficientAllocatedCells);
+                return Err(MemoryError::InsufficientAllocatedCells {
+                    builtin: Self::NAME,
+                    used,
+                    allocated: size,
+                });
             }
             Ok((used, size))
         }
@@ -175,40 +222,41 @@ impl BitwiseBuiltinRunner {
     }
 
     pub fn final_stack(
-        &self,
+        &mut self,
         vm: &VirtualMachine,
         pointer: Relocatable,
-    ) -> Result<(Relocatable, usize), RunnerError> {
-        if self._included {
-            if let Ok(stop_pointer) =
-                vm.get_relocatable(&(pointer.sub_usize(1)).map_err(|_| RunnerError::FinalStack)?)
-            {
-                if self.base() != stop_pointer.segment_index {
-                    return Err(RunnerError::InvalidStopPointer("bitwise".to_string()));
-                }
-                let stop_ptr = stop_pointer.offset;
+    ) -> Result<Relocatable, RunnerError> {
+        if self.included {
+            if let Ok(stop_pointer) = vm.get_relocatable(
+                &(pointer.sub_usize(1)).map_err(|_| RunnerError::FinalStack(Self::NAME))?,
+            ) {
                 let num_instances = self
                     .get_used_instances(vm)
-                    .map_err(|_| RunnerError::FinalStack)?;
+                    .map_err(|_| RunnerError::FinalStack(Self::NAME))?;
                 let used_cells = num_instances * self.cells_per_instance as usize;
-                if stop_ptr != used_cells {
-                    return Err(RunnerError::InvalidStopPointer("bitwise".to_string()));
+                let expected = Relocatable::from((self.base(), used_cells));
+                if stop_pointer != expected {
+                    return Err(RunnerError::InvalidStopPointer {
+                        builtin: Self::NAME,
+                        expected,
+                        found: stop_pointer,
+                    });
                 }
-                Ok((
-                    pointer.sub_usize(1).map_err(|_| RunnerError::FinalStack)?,
-                    stop_ptr,
-                ))
+                self.stop_ptr = Some(stop_pointer.offset);
+                Ok(pointer
+                    .sub_usize(1)
+                    .map_err(|_| RunnerError::FinalStack(Self::NAME))?)
             } else {
-                Err(RunnerError::FinalStack)
+                Err(RunnerError::FinalStack(Self::NAME))
             }
         } else {
-            let stop_ptr = self.base() as usize;
-            Ok((pointer, stop_ptr))
+            self.stop_ptr = Some(0);
+            Ok(pointer)
         }
     }
 
     pub fn get_used_instances(&self, vm: &VirtualMachine) -> Result<usize, MemoryError> {
-        let used_cells = self.get_used_cells(vm)?;
+        let used_cells = self.get_used_cells_mid_run(vm)?;
         Ok(div_ceil(used_cells, self.cells_per_instance as usize))
     }
 }
@@ -220,9 +268,12 @@ mod tests {
     use crate::vm::{runners::builtin_runner::BuiltinRunner, vm_core::VirtualMachine};
     use crate::{
         hint_processor::builtin_hint_processor::builtin_hint_processor_definition::BuiltinHintProcessor,
-        types::program::Program, utils::test_utils::*, vm::runners::cairo_runner::CairoRunner,
+        types::program::Program,
+        utils::test_utils::*,
+        vm::runners::cairo_runner::{CairoRunner, RunResources},
     };
     use felt::{Felt, NewFelt};
+    use num_traits::One;
 
     #[test]
     fn get_used_instances() {
@@ -244,7 +295,7 @@ mod tests {
 
     #[test]
     fn final_stack() {
-        let builtin = BitwiseBuiltinRunner::new(&BitwiseInstanceDef::new(10), true);
+        let mut builtin = BitwiseBuiltinRunner::new(&BitwiseInstanceDef::new(10), true);
 
         let mut vm = vm!();
 
@@ -261,13 +312,14 @@ mod tests {
 
         assert_eq!(
             builtin.final_stack(&vm, pointer).unwrap(),
-            (Relocatable::from((2, 1)), 0)
+            Relocatable::from((2, 1))
         );
+        assert_eq!(builtin.stop_ptr, Some(0));
     }
 
     #[test]
     fn final_stack_error_stop_pointer() {
-        let builtin = BitwiseBuiltinRunner::new(&BitwiseInstanceDef::new(10), true);
+        let mut builtin = BitwiseBuiltinRunner::new(&BitwiseInstanceDef::new(10), true);
 
         let mut vm = vm!();
 
@@ -284,13 +336,17 @@ mod tests {
 
         assert_eq!(
             builtin.final_stack(&vm, pointer),
-            Err(RunnerError::InvalidStopPointer("bitwise".to_string()))
+            Err(RunnerError::InvalidStopPointer {
+                builtin: "bitwise",
+                expected: Relocatable::from((0, 1000)),
+                found: Relocatable::from((0, 0))
+            })
         );
     }
 
     #[test]
     fn final_stack_error_when_not_included() {
-        let builtin = BitwiseBuiltinRunner::new(&BitwiseInstanceDef::new(10), false);
+        let mut builtin = BitwiseBuiltinRunner::new(&BitwiseInstanceDef::new(10), false);
 
         let mut vm = vm!();
 
@@ -307,13 +363,14 @@ mod tests {
 
         assert_eq!(
             builtin.final_stack(&vm, pointer).unwrap(),
-            (Relocatable::from((2, 2)), 0)
+            Relocatable::from((2, 2))
         );
+        assert_eq!(builtin.stop_ptr, Some(0));
     }
 
     #[test]
     fn final_stack_error_non_relocatable() {
-        let builtin = BitwiseBuiltinRunner::new(&BitwiseInstanceDef::new(10), true);
+        let mut builtin = BitwiseBuiltinRunner::new(&BitwiseInstanceDef::new(10), true);
 
         let mut vm = vm!();
 
@@ -330,7 +387,7 @@ mod tests {
 
         assert_eq!(
             builtin.final_stack(&vm, pointer),
-            Err(RunnerError::FinalStack)
+            Err(RunnerError::FinalStack("bitwise"))
         );
     }
 
@@ -343,29 +400,7 @@ mod tests {
 
         vm.segments.segment_used_sizes = Some(vec![0]);
 
-        let program = program!(
-            builtins = vec![String::from("pedersen")],
-            data = vec_data!(
-                (4612671182993129469_i64),
-                (5189976364521848832_i64),
-                (18446744073709551615_i128),
-                (5199546496550207487_i64),
-                (4612389712311386111_i64),
-                (5198983563776393216_i64),
-                (2),
-                (2345108766317314046_i64),
-                (5191102247248822272_i64),
-                (5189976364521848832_i64),
-                (7),
-                (1226245742482522112_i64),
-                ((
-                    "3618502788666131213697322783095070105623107215331596699973092056135872020470",
-                    10
-                )),
-                (2345108766317314046_i64)
-            ),
-            main = Some(8),
-        );
+        let program = Program::from_fixture("check_range");
 
         let mut cairo_runner = cairo_runner!(program);
 
@@ -374,7 +409,12 @@ mod tests {
         let address = cairo_runner.initialize(&mut vm).unwrap();
 
         cairo_runner
-            .run_until_pc(address, &mut vm, &mut hint_processor)
+            .run_until_pc(
+                address,
+                &mut RunResources::default(),
+                &mut vm,
+                &mut hint_processor,
+            )
             .unwrap();
 
         assert_eq!(builtin.get_used_cells_and_allocated_size(&vm), Ok((0, 5)));
@@ -386,29 +426,7 @@ mod tests {
 
         let mut vm = vm!();
 
-        let program = program!(
-            builtins = vec![String::from("output"), String::from("bitwise")],
-            data = vec_data!(
-                (4612671182993129469_i64),
-                (5189976364521848832_i64),
-                (18446744073709551615_i128),
-                (5199546496550207487_i64),
-                (4612389712311386111_i64),
-                (5198983563776393216_i64),
-                (2),
-                (2345108766317314046_i64),
-                (5191102247248822272_i64),
-                (5189976364521848832_i64),
-                (7),
-                (1226245742482522112_i64),
-                ((
-                    "3618502788666131213697322783095070105623107215331596699973092056135872020470",
-                    10
-                )),
-                (2345108766317314046_i64)
-            ),
-            main = Some(8),
-        );
+        let program = Program::from_fixture("check_range");
 
         let mut cairo_runner = cairo_runner!(program);
 
@@ -417,7 +435,12 @@ mod tests {
         let address = cairo_runner.initialize(&mut vm).unwrap();
 
         cairo_runner
-            .run_until_pc(address, &mut vm, &mut hint_processor)
+            .run_until_pc(
+                address,
+                &mut RunResources::default(),
+                &mut vm,
+                &mut hint_processor,
+            )
             .unwrap();
 
         assert_eq!(builtin.get_allocated_memory_units(&vm), Ok(5));
@@ -447,6 +470,67 @@ mod tests {
         assert_eq!(result, Ok(Some(MaybeRelocatable::from(Felt::new(14)))));
     }
 
+    #[test]
+    fn deduce_memory_cell_bitwise_input_at_the_limit() {
+        let memory = memory![
+            (
+                (0, 5),
+                (
+                    "3618502788666131106986593281521497120414687020801267626233049500247285301247",
+                    10
+                )
+            ),
+            ((0, 6), 12)
+        ];
+        let builtin = BitwiseBuiltinRunner::new(&BitwiseInstanceDef::default(), true);
+        let result = builtin.deduce_memory_cell(&Relocatable::from((0, 7)), &memory);
+        assert_eq!(result, Ok(Some(MaybeRelocatable::from(Felt::new(12)))));
+    }
+
+    #[test]
+    fn deduce_memory_cell_bitwise_input_over_the_limit() {
+        let memory = memory![
+            ((0, 5), 12),
+            (
+                (0, 6),
+                (
+                    "3618502788666131106986593281521497120414687020801267626233049500247285301248",
+                    10
+                )
+            )
+        ];
+        let builtin = BitwiseBuiltinRunner::new(&BitwiseInstanceDef::default(), true);
+        let result = builtin.deduce_memory_cell(&Relocatable::from((0, 8)), &memory);
+        assert_eq!(
+            result,
+            Err(RunnerError::IntegerBiggerThanPowerOfTwo(
+                MaybeRelocatable::from((0, 6)),
+                251,
+                Felt::one() << 251_usize
+            ))
+        );
+    }
+
+    #[test]
+    fn deduce_memory_cell_bitwise_input_over_the_limit_of_the_instance_def() {
+        let memory = memory![((0, 5), 256), ((0, 6), 12)];
+        let instance_def = BitwiseInstanceDef {
+            ratio: 256,
+            ratio_den: 1,
+            total_n_bits: 8,
+        };
+        let builtin = BitwiseBuiltinRunner::new(&instance_def, true);
+        let result = builtin.deduce_memory_cell(&Relocatable::from((0, 9)), &memory);
+        assert_eq!(
+            result,
+            Err(RunnerError::IntegerBiggerThanPowerOfTwo(
+                MaybeRelocatable::from((0, 5)),
+                8,
+                Felt::new(256)
+            ))
+        );
+    }
+
     #[test]
     fn deduce_memory_cell_bitwise_for_preset_memory_incorrect_offset() {
         let memory = memory![((0, 3), 10), ((0, 4), 12), ((0, 5), 0)];
@@ -463,13 +547,37 @@ mod tests {
         assert_eq!(result, Ok(None));
     }
 
+    #[test]
+    fn deduce_memory_cell_bitwise_both_inputs_missing() {
+        let memory = memory![((0, 2), 0)];
+        let builtin = BitwiseBuiltinRunner::new(&BitwiseInstanceDef::default(), true);
+        let result = builtin.deduce_memory_cell(&Relocatable::from((0, 3)), &memory);
+        assert_eq!(result, Ok(None));
+    }
+
+    #[test]
+    fn deduce_memory_cell_bitwise_one_input_missing() {
+        let memory = memory![((0, 0), 10)];
+        let builtin = BitwiseBuiltinRunner::new(&BitwiseInstanceDef::default(), true);
+        let result = builtin.deduce_memory_cell(&Relocatable::from((0, 4)), &memory);
+        assert_eq!(result, Ok(None));
+    }
+
+    #[test]
+    fn deduce_memory_cell_bitwise_relocatable_input() {
+        let memory = memory![((0, 0), 10), ((0, 1), (1, 2))];
+        let builtin = BitwiseBuiltinRunner::new(&BitwiseInstanceDef::default(), true);
+        let result = builtin.deduce_memory_cell(&Relocatable::from((0, 2)), &memory);
+        assert_eq!(result, Ok(None));
+    }
+
     #[test]
     fn get_memory_segment_addresses() {
         let builtin = BitwiseBuiltinRunner::new(&BitwiseInstanceDef::default(), true);
 
         assert_eq!(
             builtin.get_memory_segment_addresses(),
-            ("bitwise", (0, None)),
+            vec![SegmentAddresses::new("bitwise", 0, None)],
         );
     }
 
@@ -483,7 +591,7 @@ mod tests {
 
         assert_eq!(
             builtin.get_memory_accesses(&vm),
-            Err(MemoryError::MissingSegmentUsedSizes),
+            Err(MemoryError::MissingSegmentUsedSizes(0)),
         );
     }
 
@@ -508,6 +616,7 @@ mod tests {
         let mut vm = vm!();
 
         vm.segments.segment_used_sizes = Some(vec![4]);
+        vm.memory = memory![((0, 0), 1), ((0, 1), 2), ((0, 2), 3), ((0, 3), 4)];
         assert_eq!(
             builtin.get_memory_accesses(&vm),
             Ok(vec![
@@ -519,6 +628,22 @@ mod tests {
         );
     }
 
+    #[test]
+    fn get_memory_accesses_skips_unwritten_cells() {
+        let builtin = BuiltinRunner::Bitwise(BitwiseBuiltinRunner::new(
+            &BitwiseInstanceDef::default(),
+            true,
+        ));
+        let mut vm = vm!();
+
+        vm.segments.segment_used_sizes = Some(vec![4]);
+        vm.memory = memory![((0, 0), 1), ((0, 3), 4)];
+        assert_eq!(
+            builtin.get_memory_accesses(&vm),
+            Ok(vec![(builtin.base(), 0).into(), (builtin.base(), 3).into()]),
+        );
+    }
+
     #[test]
     fn get_used_cells_missing_segment_used_sizes() {
         let builtin = BuiltinRunner::Bitwise(BitwiseBuiltinRunner::new(
@@ -529,7 +654,7 @@ mod tests {
 
         assert_eq!(
             builtin.get_used_cells(&vm),
-            Err(MemoryError::MissingSegmentUsedSizes)
+            Err(MemoryError::MissingSegmentUsedSizes(0))
         );
     }
 
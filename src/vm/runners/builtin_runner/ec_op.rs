@@ -1,42 +1,67 @@
-use crate::math_utils::{ec_add, ec_double, safe_div_usize};
+use crate::math_utils::{ec_add, ec_double};
 use crate::types::instance_definitions::ec_op_instance_def::{
     EcOpInstanceDef, CELLS_PER_EC_OP, INPUT_CELLS_PER_EC_OP,
 };
 use crate::types::relocatable::{MaybeRelocatable, Relocatable};
 use crate::vm::errors::memory_errors::MemoryError;
 use crate::vm::errors::runner_errors::RunnerError;
+use crate::vm::runners::air_private_input::{PrivateInput, PrivateInputEcOp};
+use crate::vm::runners::builtin_runner::{
+    get_allocated_instances, get_instances_inputs, SegmentAddresses,
+};
 use crate::vm::vm_core::VirtualMachine;
 use crate::vm::vm_memory::memory::Memory;
 use crate::vm::vm_memory::memory_segments::MemorySegmentManager;
 use felt::{Felt, FeltOps, NewFelt};
-use num_bigint::BigInt;
+use lazy_static::lazy_static;
+use num_bigint::{BigInt, BigUint};
 use num_integer::{div_ceil, Integer};
 use num_traits::{Num, One, Pow, Zero};
 use std::borrow::Cow;
+use std::cell::RefCell;
+use std::collections::HashMap;
+
+lazy_static! {
+    // The coefficients of the STARK curve, y^2 = x^3 + alpha * x + beta
+    static ref ALPHA: Felt = Felt::one();
+    static ref BETA: Felt = (Felt::new(0x6f21413efbe40de150e596d72f7a8c5_u128) << 128_usize)
+        + Felt::new(0x609ad26c15c915c1f4cdfcb99cee9e89_u128);
+}
 
 #[derive(Debug, Clone)]
 pub struct EcOpBuiltinRunner {
     ratio: u32,
+    pub(crate) ratio_den: u32,
     pub base: isize,
+    pub(crate) initialized: bool,
     pub(crate) cells_per_instance: u32,
     pub(crate) n_input_cells: u32,
     ec_op_builtin: EcOpInstanceDef,
     pub(crate) stop_ptr: Option<usize>,
-    _included: bool,
+    pub(crate) included: bool,
     instances_per_component: u32,
+    // The output cells of the instances already computed, so that deducing the other output of
+    // an instance doesn't compute the scalar multiplication again
+    // Therefore need interior mutability
+    cache: RefCell<HashMap<Relocatable, Felt>>,
 }
 
 impl EcOpBuiltinRunner {
+    pub const NAME: &str = "ec_op";
+
     pub(crate) fn new(instance_def: &EcOpInstanceDef, included: bool) -> Self {
         EcOpBuiltinRunner {
             base: 0,
+            initialized: false,
             ratio: instance_def.ratio,
+            ratio_den: instance_def.ratio_den,
             n_input_cells: INPUT_CELLS_PER_EC_OP,
             cells_per_instance: CELLS_PER_EC_OP,
             ec_op_builtin: instance_def.clone(),
             stop_ptr: None,
-            _included: included,
+            included,
             instances_per_component: 1,
+            cache: RefCell::new(HashMap::new()),
         }
     }
     ///Returns True if the point (x, y) is on the elliptic curve defined as
@@ -52,6 +77,8 @@ impl EcOpBuiltinRunner {
     /// Mimics the operation of the AIR, so that this function fails whenever the builtin AIR
     /// would not yield a correct result, i.e. when any part of the computation attempts to add
     /// two points with the same x coordinate.
+    /// The AIR consumes a bit of m per row, for at most `height` rows. The rows after the last
+    /// set bit leave the partial sum unchanged, so they are skipped.
     fn ec_op_impl(
         partial_sum: (Felt, Felt),
         doubled_point: (Felt, Felt),
@@ -60,10 +87,14 @@ impl EcOpBuiltinRunner {
         prime: &BigInt,
         height: u32,
     ) -> Result<(BigInt, BigInt), RunnerError> {
-        let mut slope = m.clone().to_bigint();
+        // m is read unsigned, as to_bigint would map the scalars over prime / 2 to negative values
+        let mut slope = m.to_biguint();
         let mut partial_sum_b = (partial_sum.0.to_bigint(), partial_sum.1.to_bigint());
         let mut doubled_point_b = (doubled_point.0.to_bigint(), doubled_point.1.to_bigint());
         for _ in 0..height {
+            if slope.is_zero() {
+                return Ok(partial_sum_b);
+            }
             if (doubled_point_b.0.clone() - partial_sum_b.0.clone()).is_zero() {
                 return Err(RunnerError::EcOpSameXCoordinate(Self::format_ec_op_error(
                     partial_sum_b,
@@ -71,11 +102,20 @@ impl EcOpBuiltinRunner {
                     doubled_point_b,
                 )));
             };
-            if !(slope.clone() & &BigInt::one()).is_zero() {
+            if slope.bit(0) {
                 partial_sum_b = ec_add(partial_sum_b, doubled_point_b.clone(), prime);
             }
-            doubled_point_b = ec_double(doubled_point_b, alpha, prime);
             slope = slope.clone() >> 1_u32;
+            // The point is only doubled if there are bits left to add it to
+            if !slope.is_zero() {
+                doubled_point_b = ec_double(doubled_point_b, alpha, prime);
+            }
+        }
+        if !slope.is_zero() {
+            return Err(RunnerError::EcOpScalarTooLarge(
+                m.clone(),
+                BigUint::one() << height,
+            ));
         }
         Ok(partial_sum_b)
     }
@@ -84,12 +124,17 @@ impl EcOpBuiltinRunner {
         &mut self,
         segments: &mut MemorySegmentManager,
         memory: &mut Memory,
-    ) {
-        self.base = segments.add(memory).segment_index
+    ) -> Result<(), RunnerError> {
+        if self.initialized {
+            return Err(RunnerError::BuiltinAlreadyInitialized(Self::NAME));
+        }
+        self.base = segments.add(memory).segment_index;
+        self.initialized = true;
+        Ok(())
     }
 
     pub fn initial_stack(&self) -> Vec<MaybeRelocatable> {
-        if self._included {
+        if self.included {
             vec![MaybeRelocatable::from((self.base, 0))]
         } else {
             vec![]
@@ -100,10 +145,22 @@ impl EcOpBuiltinRunner {
         self.base
     }
 
+    pub fn is_initialized(&self) -> bool {
+        self.initialized
+    }
+
+    pub(crate) fn clear_cache(&mut self) {
+        self.cache.get_mut().clear();
+    }
+
     pub fn ratio(&self) -> u32 {
         self.ratio
     }
 
+    pub fn ratio_den(&self) -> u32 {
+        self.ratio_den
+    }
+
     pub fn add_validation_rule(&self, _memory: &mut Memory) -> Result<(), RunnerError> {
         Ok(())
     }
@@ -116,10 +173,6 @@ impl EcOpBuiltinRunner {
         //Constant values declared here
         const EC_POINT_INDICES: [(usize, usize); 3] = [(0, 1), (2, 3), (5, 6)];
         const OUTPUT_INDICES: (usize, usize) = EC_POINT_INDICES[2];
-        let alpha: Felt = Felt::one();
-        let beta_low: Felt = Felt::new(0x609ad26c15c915c1f4cdfcb99cee9e89_u128);
-        let beta_high: Felt = Felt::new(0x6f21413efbe40de150e596d72f7a8c5_u128);
-        let beta: Felt = (beta_high << 128_usize) + beta_low;
 
         let index = address
             .offset
@@ -128,9 +181,12 @@ impl EcOpBuiltinRunner {
         if index != OUTPUT_INDICES.0 && index != OUTPUT_INDICES.1 {
             return Ok(None);
         }
+        if let Some(value) = self.cache.borrow().get(address) {
+            return Ok(Some(value.into()));
+        }
         let instance = MaybeRelocatable::from((address.segment_index, address.offset - index));
         //All input cells should be filled, and be integer values
-        //If an input cell is not filled, return None
+        //If an input cell is not filled or is a relocatable, return None
         let mut input_cells = Vec::<Cow<Felt>>::with_capacity(self.n_input_cells as usize);
         for i in 0..self.n_input_cells as usize {
             match memory
@@ -142,27 +198,26 @@ impl EcOpBuiltinRunner {
                     input_cells.push(match addr {
                         Cow::Borrowed(MaybeRelocatable::Int(num)) => Cow::Borrowed(num),
                         Cow::Owned(MaybeRelocatable::Int(num)) => Cow::Owned(num),
-                        _ => return Err(RunnerError::ExpectedInteger(instance.add_usize(i))),
+                        _ => return Ok(None),
                     });
                 }
             };
         }
-        //Assert that m is under the limit defined by scalar_limit.
-        /*if input_cells[M_INDEX].as_ref() >= &self.ec_op_builtin.scalar_limit {
-            return Err(RunnerError::EcOpBuiltinScalarLimit(
-                self.ec_op_builtin.scalar_limit.clone(),
+        //Assert that m is under the limit defined by scalar_bits.
+        //The limit is compared as an integer, as it may exceed the prime
+        let scalar_limit = BigUint::one() << self.ec_op_builtin.scalar_bits;
+        if input_cells[4].to_biguint() >= scalar_limit {
+            return Err(RunnerError::EcOpScalarTooLarge(
+                input_cells[4].clone().into_owned(),
+                scalar_limit,
             ));
-        }*/
+        }
 
         // Assert that if the current address is part of a point, the point is on the curve
         for pair in &EC_POINT_INDICES[0..1] {
-            if !EcOpBuiltinRunner::point_on_curve(
-                input_cells[pair.0].as_ref(),
-                input_cells[pair.1].as_ref(),
-                &alpha,
-                &beta,
-            ) {
-                return Err(RunnerError::PointNotOnCurve(*pair));
+            let (x, y) = (input_cells[pair.0].as_ref(), input_cells[pair.1].as_ref());
+            if !EcOpBuiltinRunner::point_on_curve(x, y, &ALPHA, &BETA) {
+                return Err(RunnerError::PointNotOnCurve((x.clone(), y.clone())));
             };
         }
         let prime = BigInt::from_str_radix(&felt::PRIME_STR[2..], 16)
@@ -177,94 +232,148 @@ impl EcOpBuiltinRunner {
                 input_cells[3].as_ref().to_owned(),
             ),
             input_cells[4].as_ref(),
-            &alpha.to_bigint(),
+            &ALPHA.to_bigint(),
             &prime,
             self.ec_op_builtin.scalar_height,
         )?;
-        match index - self.n_input_cells as usize {
-            0 => Ok(Some(MaybeRelocatable::Int(Felt::new(result.0)))),
-            _ => Ok(Some(MaybeRelocatable::Int(Felt::new(result.1)))),
-            //Default case corresponds to 1, as there are no other possible cases
-        }
+        let instance_start = address.offset - index;
+        let mut cache = self.cache.borrow_mut();
+        cache.insert(
+            (address.segment_index, instance_start + OUTPUT_INDICES.0).into(),
+            Felt::new(result.0),
+        );
+        cache.insert(
+            (address.segment_index, instance_start + OUTPUT_INDICES.1).into(),
+            Felt::new(result.1),
+        );
+        Ok(cache.get(address).map(MaybeRelocatable::from))
     }
 
     pub fn get_allocated_memory_units(&self, vm: &VirtualMachine) -> Result<usize, MemoryError> {
-        let value = safe_div_usize(vm.current_step, self.ratio as usize)
-            .map_err(|_| MemoryError::ErrorCalculatingMemoryUnits)?;
+        let value =
+            get_allocated_instances(Self::NAME, vm.current_step, self.ratio, self.ratio_den)?;
         Ok(self.cells_per_instance as usize * value)
     }
 
-    pub fn get_memory_segment_addresses(&self) -> (&'static str, (isize, Option<usize>)) {
-        ("ec_op", (self.base, self.stop_ptr))
+    /// Checks that both input points of every instance lie on the curve, as the AIR can't
+    /// verify the operation otherwise.
+    pub fn run_additional_security_checks(&self, vm: &VirtualMachine) -> Result<(), RunnerError> {
+        let instances = get_instances_inputs::<4>(&vm.memory, self.base, self.cells_per_instance);
+        for (_, [p_x, p_y, q_x, q_y]) in instances {
+            for (x, y) in [(p_x, p_y), (q_x, q_y)] {
+                if !EcOpBuiltinRunner::point_on_curve(&x, &y, &ALPHA, &BETA) {
+                    return Err(RunnerError::PointNotOnCurve((x, y)));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    pub fn air_private_input(&self, memory: &Memory) -> Vec<PrivateInput> {
+        get_instances_inputs::<5>(memory, self.base, self.cells_per_instance)
+            .into_iter()
+            .map(|(index, [p_x, p_y, q_x, q_y, m])| {
+                PrivateInput::EcOp(PrivateInputEcOp {
+                    index,
+                    p_x,
+                    p_y,
+                    m,
+                    q_x,
+                    q_y,
+                })
+            })
+            .collect()
+    }
+
+    pub fn get_memory_segment_addresses(&self) -> Vec<SegmentAddresses> {
+        vec![SegmentAddresses::new(Self::NAME, self.base, self.stop_ptr)]
     }
 
     pub fn get_used_cells(&self, vm: &VirtualMachine) -> Result<usize, MemoryError> {
         let base = self.base();
+        let index = base
+            .try_into()
+            .map_err(|_| MemoryError::AddressInTemporarySegment(base))?;
         vm.segments
-            .get_segment_used_size(
-                base.try_into()
-                    .map_err(|_| MemoryError::AddressInTemporarySegment(base))?,
-            )
-            .ok_or(MemoryError::MissingSegmentUsedSizes)
+            .get_segment_used_size(index)
+            .ok_or(MemoryError::MissingSegmentUsedSizes(index))
+    }
+
+    pub fn get_used_cells_mid_run(&self, vm: &VirtualMachine) -> Result<usize, MemoryError> {
+        let base = self.base();
+        let index = base
+            .try_into()
+            .map_err(|_| MemoryError::AddressInTemporarySegment(base))?;
+        vm.segments
+            .get_segment_used_size_mid_run(index, &vm.memory)
+            .ok_or(MemoryError::UnallocatedSegment(index, vm.memory.data.len()))
     }
 
     pub fn get_used_cells_and_allocated_size(
         &self,
         vm: &VirtualMachine,
     ) -> Result<(usize, usize), MemoryError> {
-        let ratio = self.ratio as usize;
         let cells_per_instance = self.cells_per_instance;
-        let min_step = ratio * self.instances_per_component as usize;
+        let min_step = div_ceil(
+            self.ratio as usize * self.instances_per_component as usize,
+            self.ratio_den as usize,
+        );
         if vm.current_step < min_step {
-            Err(MemoryError::InsufficientAllocatedCells)
+            Err(MemoryError::MinStepNotReached {
+                builtin: Self::NAME,
+                min_step,
+            })
         } else {
             let used = self.get_used_cells(vm)?;
             let size = cells_per_instance as usize
-                * safe_div_usize(vm.current_step, ratio)
-                    .map_err(|_| MemoryError::InsufficientAllocatedCells)?;
+                * get_allocated_instances(Self::NAME, vm.current_step, self.ratio, self.ratio_den)?;
             if used > size {
-                return Err(MemoryError::InsufficientAllocatedCells);
+                return Err(MemoryError::InsufficientAllocatedCells {
+                    builtin: Self::NAME,
+                    used,
+                    allocated: size,
+                });
             }
             Ok((used, size))
         }
     }
 
     pub fn get_used_instances(&self, vm: &VirtualMachine) -> Result<usize, MemoryError> {
-        let used_cells = self.get_used_cells(vm)?;
+        let used_cells = self.get_used_cells_mid_run(vm)?;
         Ok(div_ceil(used_cells, self.cells_per_instance as usize))
     }
 
     pub fn final_stack(
-        &self,
+        &mut self,
         vm: &VirtualMachine,
         pointer: Relocatable,
-    ) -> Result<(Relocatable, usize), RunnerError> {
-        if self._included {
-            if let Ok(stop_pointer) =
-                vm.get_relocatable(&(pointer.sub_usize(1)).map_err(|_| RunnerError::FinalStack)?)
-            {
-                if self.base() != stop_pointer.segment_index {
-                    return Err(RunnerError::InvalidStopPointer("ec_op".to_string()));
-                }
-                let stop_ptr = stop_pointer.offset;
+    ) -> Result<Relocatable, RunnerError> {
+        if self.included {
+            if let Ok(stop_pointer) = vm.get_relocatable(
+                &(pointer.sub_usize(1)).map_err(|_| RunnerError::FinalStack(Self::NAME))?,
+            ) {
                 let num_instances = self
                     .get_used_instances(vm)
-                    .map_err(|_| RunnerError::FinalStack)?;
+                    .map_err(|_| RunnerError::FinalStack(Self::NAME))?;
                 let used_cells = num_instances * self.cells_per_instance as usize;
-                if stop_ptr != used_cells {
-                    return Err(RunnerError::InvalidStopPointer("ec_op".to_string()));
+                let expected = Relocatable::from((self.base(), used_cells));
+                if stop_pointer != expected {
+                    return Err(RunnerError::InvalidStopPointer {
+                        builtin: Self::NAME,
+                        expected,
+                        found: stop_pointer,
+                    });
                 }
-
-                Ok((
-                    pointer.sub_usize(1).map_err(|_| RunnerError::FinalStack)?,
-                    stop_ptr,
-                ))
+                self.stop_ptr = Some(stop_pointer.offset);
+                Ok(pointer
+                    .sub_usize(1)
+                    .map_err(|_| RunnerError::FinalStack(Self::NAME))?)
             } else {
-                Err(RunnerError::FinalStack)
+                Err(RunnerError::FinalStack(Self::NAME))
             }
         } else {
-            let stop_ptr = self.base() as usize;
-            Ok((pointer, stop_ptr))
+            self.stop_ptr = Some(0);
+            Ok(pointer)
         }
     }
 
@@ -287,9 +396,11 @@ mod tests {
     use crate::hint_processor::builtin_hint_processor::builtin_hint_processor_definition::BuiltinHintProcessor;
     use crate::types::program::Program;
     use crate::utils::test_utils::*;
-    use crate::vm::runners::cairo_runner::CairoRunner;
+    use crate::vm::runners::cairo_runner::{CairoRunner, RunResources};
     use crate::vm::{
-        errors::{memory_errors::MemoryError, runner_errors::RunnerError},
+        errors::{
+            memory_errors::MemoryError, runner_errors::RunnerError, vm_errors::VirtualMachineError,
+        },
         runners::builtin_runner::BuiltinRunner,
         vm_core::VirtualMachine,
     };
@@ -316,7 +427,7 @@ mod tests {
 
     #[test]
     fn final_stack() {
-        let builtin = EcOpBuiltinRunner::new(&EcOpInstanceDef::new(10), true);
+        let mut builtin = EcOpBuiltinRunner::new(&EcOpInstanceDef::new(10), true);
 
         let mut vm = vm!();
 
@@ -333,13 +444,14 @@ mod tests {
 
         assert_eq!(
             builtin.final_stack(&vm, pointer).unwrap(),
-            (Relocatable::from((2, 1)), 0)
+            Relocatable::from((2, 1))
         );
+        assert_eq!(builtin.stop_ptr, Some(0));
     }
 
     #[test]
     fn final_stack_error_stop_pointer() {
-        let builtin = EcOpBuiltinRunner::new(&EcOpInstanceDef::new(10), true);
+        let mut builtin = EcOpBuiltinRunner::new(&EcOpInstanceDef::new(10), true);
 
         let mut vm = vm!();
 
@@ -356,13 +468,17 @@ mod tests {
 
         assert_eq!(
             builtin.final_stack(&vm, pointer),
-            Err(RunnerError::InvalidStopPointer("ec_op".to_string()))
+            Err(RunnerError::InvalidStopPointer {
+                builtin: "ec_op",
+                expected: Relocatable::from((0, 1001)),
+                found: Relocatable::from((0, 0))
+            })
         );
     }
 
     #[test]
     fn final_stack_error_when_not_included() {
-        let builtin = EcOpBuiltinRunner::new(&EcOpInstanceDef::new(10), false);
+        let mut builtin = EcOpBuiltinRunner::new(&EcOpInstanceDef::new(10), false);
 
         let mut vm = vm!();
 
@@ -379,13 +495,14 @@ mod tests {
 
         assert_eq!(
             builtin.final_stack(&vm, pointer).unwrap(),
-            (Relocatable::from((2, 2)), 0)
+            Relocatable::from((2, 2))
         );
+        assert_eq!(builtin.stop_ptr, Some(0));
     }
 
     #[test]
     fn final_stack_error_non_relocatable() {
-        let builtin = EcOpBuiltinRunner::new(&EcOpInstanceDef::new(10), true);
+        let mut builtin = EcOpBuiltinRunner::new(&EcOpInstanceDef::new(10), true);
 
         let mut vm = vm!();
 
@@ -402,7 +519,7 @@ mod tests {
 
         assert_eq!(
             builtin.final_stack(&vm, pointer),
-            Err(RunnerError::FinalStack)
+            Err(RunnerError::FinalStack("ec_op"))
         );
     }
 
@@ -414,29 +531,7 @@ mod tests {
 
         vm.segments.segment_used_sizes = Some(vec![0]);
 
-        let program = program!(
-            builtins = vec![String::from("pedersen")],
-            data = vec_data!(
-                (4612671182993129469_i64),
-                (5189976364521848832_i64),
-                (18446744073709551615_i128),
-                (5199546496550207487_i64),
-                (4612389712311386111_i64),
-                (5198983563776393216_i64),
-                (2),
-                (2345108766317314046_i64),
-                (5191102247248822272_i64),
-                (5189976364521848832_i64),
-                (7),
-                (1226245742482522112_i64),
-                ((
-                    "3618502788666131213697322783095070105623107215331596699973092056135872020470",
-                    10
-                )),
-                (2345108766317314046_i64)
-            ),
-            main = Some(8),
-        );
+        let program = Program::from_fixture("check_range");
         let mut cairo_runner = cairo_runner!(program);
 
         let mut hint_processor = BuiltinHintProcessor::new_empty();
@@ -444,7 +539,12 @@ mod tests {
         let address = cairo_runner.initialize(&mut vm).unwrap();
 
         cairo_runner
-            .run_until_pc(address, &mut vm, &mut hint_processor)
+            .run_until_pc(
+                address,
+                &mut RunResources::default(),
+                &mut vm,
+                &mut hint_processor,
+            )
             .unwrap();
 
         assert_eq!(builtin.get_used_cells_and_allocated_size(&vm), Ok((0, 7)));
@@ -456,29 +556,7 @@ mod tests {
 
         let mut vm = vm!();
 
-        let program = program!(
-            builtins = vec![String::from("ec_op")],
-            data = vec_data!(
-                (4612671182993129469_i64),
-                (5189976364521848832_i64),
-                (18446744073709551615_i128),
-                (5199546496550207487_i64),
-                (4612389712311386111_i64),
-                (5198983563776393216_i64),
-                (2),
-                (2345108766317314046_i64),
-                (5191102247248822272_i64),
-                (5189976364521848832_i64),
-                (7),
-                (1226245742482522112_i64),
-                ((
-                    "3618502788666131213697322783095070105623107215331596699973092056135872020470",
-                    10
-                )),
-                (2345108766317314046_i64)
-            ),
-            main = Some(8),
-        );
+        let program = Program::from_fixture("check_range");
 
         let mut cairo_runner = cairo_runner!(program);
 
@@ -487,7 +565,12 @@ mod tests {
         let address = cairo_runner.initialize(&mut vm).unwrap();
 
         cairo_runner
-            .run_until_pc(address, &mut vm, &mut hint_processor)
+            .run_until_pc(
+                address,
+                &mut RunResources::default(),
+                &mut vm,
+                &mut hint_processor,
+            )
             .unwrap();
 
         assert_eq!(builtin.get_allocated_memory_units(&vm), Ok(7));
@@ -728,6 +811,144 @@ mod tests {
         );
     }
 
+    #[test]
+    fn deduce_memory_cell_ec_op_caches_the_instance_outputs() {
+        let memory = memory![
+            (
+                (3, 0),
+                (
+                    "2962412995502985605007699495352191122971573493113767820301112397466445942584",
+                    10
+                )
+            ),
+            (
+                (3, 1),
+                (
+                    "214950771763870898744428659242275426967582168179217139798831865603966154129",
+                    10
+                )
+            ),
+            (
+                (3, 2),
+                (
+                    "874739451078007766457464989774322083649278607533249481151382481072868806602",
+                    10
+                )
+            ),
+            (
+                (3, 3),
+                (
+                    "152666792071518830868575557812948353041420400780739481342941381225525861407",
+                    10
+                )
+            ),
+            ((3, 4), 34)
+        ];
+        let builtin = EcOpBuiltinRunner::new(&EcOpInstanceDef::default(), true);
+
+        let y = builtin.deduce_memory_cell(&Relocatable::from((3, 6)), &memory);
+        assert_eq!(builtin.cache.borrow().len(), 2);
+
+        // Deducing again, even once the inputs are gone, reads the cached outputs
+        let empty_memory = memory![((3, 0), 0)];
+        assert_eq!(
+            builtin.deduce_memory_cell(&Relocatable::from((3, 6)), &empty_memory),
+            y
+        );
+        assert_eq!(
+            builtin.deduce_memory_cell(&Relocatable::from((3, 5)), &empty_memory),
+            Ok(Some(MaybeRelocatable::from(felt_str!(
+                "2778063437308421278851140253538604815869848682781135193774472480292420096757"
+            ))))
+        );
+        assert_eq!(builtin.cache.borrow().len(), 2);
+    }
+
+    #[test]
+    fn run_security_checks_points_on_curve() {
+        let builtin: BuiltinRunner =
+            EcOpBuiltinRunner::new(&EcOpInstanceDef::default(), true).into();
+        let mut vm = vm!();
+        vm.memory = memory![
+            (
+                (0, 0),
+                (
+                    "2962412995502985605007699495352191122971573493113767820301112397466445942584",
+                    10
+                )
+            ),
+            (
+                (0, 1),
+                (
+                    "214950771763870898744428659242275426967582168179217139798831865603966154129",
+                    10
+                )
+            ),
+            (
+                (0, 2),
+                (
+                    "874739451078007766457464989774322083649278607533249481151382481072868806602",
+                    10
+                )
+            ),
+            (
+                (0, 3),
+                (
+                    "152666792071518830868575557812948353041420400780739481342941381225525861407",
+                    10
+                )
+            ),
+            ((0, 4), 34),
+            (
+                (0, 5),
+                (
+                    "2778063437308421278851140253538604815869848682781135193774472480292420096757",
+                    10
+                )
+            ),
+            (
+                (0, 6),
+                (
+                    "3598390311618116577316045819420613574162151407434885460365915347732568210029",
+                    10
+                )
+            )
+        ];
+        assert_eq!(builtin.run_security_checks(&mut vm), Ok(()));
+    }
+
+    #[test]
+    fn run_security_checks_point_not_on_curve() {
+        let builtin: BuiltinRunner =
+            EcOpBuiltinRunner::new(&EcOpInstanceDef::default(), true).into();
+        let mut vm = vm!();
+        vm.memory = memory![
+            (
+                (0, 0),
+                (
+                    "2962412995502985605007699495352191122971573493113767820301112397466445942584",
+                    10
+                )
+            ),
+            (
+                (0, 1),
+                (
+                    "214950771763870898744428659242275426967582168179217139798831865603966154129",
+                    10
+                )
+            ),
+            ((0, 2), 1),
+            ((0, 3), 2),
+            ((0, 4), 34)
+        ];
+        assert_eq!(
+            builtin.run_security_checks(&mut vm),
+            Err(VirtualMachineError::RunnerError(
+                RunnerError::PointNotOnCurve((Felt::new(1), Felt::new(2)))
+            ))
+        );
+    }
+
     #[test]
     fn deduce_memory_cell_ec_op_for_preset_memory_unfilled_input_cells() {
         let memory = memory![
@@ -851,13 +1072,14 @@ mod tests {
 
         assert_eq!(
             builtin.deduce_memory_cell(&Relocatable::from((3, 6)), &memory),
-            Err(RunnerError::ExpectedInteger(MaybeRelocatable::from((3, 3))))
+            Ok(None)
         );
     }
 
-    #[test]
-    fn deduce_memory_cell_ec_op_for_preset_memory_m_over_scalar_limit() {
-        let memory = memory![
+    // The memory of an ec_op instance computing P + m * Q, with the P and Q of
+    // deduce_memory_cell_ec_op_for_preset_memory_valid
+    fn ec_op_memory(m: Felt) -> Memory {
+        let mut memory = memory![
             (
                 (3, 0),
                 (
@@ -885,39 +1107,141 @@ mod tests {
                     "152666792071518830868575557812948353041420400780739481342941381225525861407",
                     10
                 )
+            )
+        ];
+        memory
+            .insert(&Relocatable::from((3, 4)), &MaybeRelocatable::from(m))
+            .unwrap();
+        memory
+    }
+
+    // An instance with a small scalar limit, as the default one, 2**252, exceeds the prime
+    fn small_scalar_instance_def() -> EcOpInstanceDef {
+        EcOpInstanceDef {
+            ratio: 256,
+            ratio_den: 1,
+            scalar_height: 256,
+            scalar_bits: 8,
+        }
+    }
+
+    #[test]
+    fn deduce_memory_cell_ec_op_for_preset_memory_m_over_scalar_limit() {
+        for m in [256, 257] {
+            let memory = ec_op_memory(Felt::new(m));
+            let builtin = EcOpBuiltinRunner::new(&small_scalar_instance_def(), true);
+
+            let error = builtin.deduce_memory_cell(&Relocatable::from((3, 6)), &memory);
+            assert_eq!(
+                error,
+                Err(RunnerError::EcOpScalarTooLarge(
+                    Felt::new(m),
+                    BigUint::from(256_u32)
+                ))
+            );
+        }
+    }
+
+    #[test]
+    fn deduce_memory_cell_ec_op_scalar_height_of_the_instance_def() {
+        // The scalar is under the limit of scalar_bits, but its bits can't all be added in
+        // scalar_height steps
+        let instance_def = EcOpInstanceDef {
+            scalar_height: 8,
+            ..EcOpInstanceDef::default()
+        };
+        let builtin = EcOpBuiltinRunner::new(&instance_def, true);
+        let memory = ec_op_memory(Felt::new(255));
+        assert!(matches!(
+            builtin.deduce_memory_cell(&Relocatable::from((3, 6)), &memory),
+            Ok(Some(_))
+        ));
+
+        let builtin = EcOpBuiltinRunner::new(&instance_def, true);
+        let memory = ec_op_memory(Felt::new(256));
+        assert_eq!(
+            builtin.deduce_memory_cell(&Relocatable::from((3, 6)), &memory),
+            Err(RunnerError::EcOpScalarTooLarge(
+                Felt::new(256),
+                BigUint::from(256_u32)
+            ))
+        );
+    }
+
+    #[test]
+    fn deduce_memory_cell_ec_op_for_preset_memory_m_under_scalar_limit() {
+        // The expected points were computed with cairo-lang's ec_op_impl (from
+        // starkware/cairo/lang/builtins/ec/ec_op_builtin_runner.py), which also yields the output
+        // of deduce_memory_cell_ec_op_for_preset_memory_valid for m = 34
+        for (instance_def, m, x, y) in [
+            // The largest scalar under the limit
+            (
+                small_scalar_instance_def(),
+                Felt::new(255),
+                "1808686960948070850054257873693417151135236634647645054644206775384778758362",
+                "253418892805507570421066144992031001556089942476084956227779081582531596990",
             ),
-            //Scalar Limit + 1
+            // The largest felt, and an arbitrary one, are under the default limit
             (
-                (3, 4),
-                (
-                    "3618502788666131213697322783095070105623107215331596699973092056135872020482",
-                    10
-                )
+                EcOpInstanceDef::default(),
+                Felt::zero() - Felt::one(),
+                "756624533868665322949834283579147304336594905989560095256624999302135560320",
+                "163915726923271219645838854477366861578225926188989078065631851804557912515",
             ),
             (
-                (3, 5),
-                (
-                    "2778063437308421278851140253538604815869848682781135193774472480292420096757",
-                    10
-                )
-            )
-        ];
-        let builtin = EcOpBuiltinRunner::new(&EcOpInstanceDef::default(), true);
+                EcOpInstanceDef::default(),
+                felt_str!(
+                    "1809251394333065553493296640760748560207343510400633813116524750123642650623"
+                ),
+                "2358520385912620116895195702641007324773977037566623839447976901996453773720",
+                "2800740617867431964158352703248068269073947837995836228767119740656751120253",
+            ),
+        ] {
+            let memory = ec_op_memory(m);
+            let builtin = EcOpBuiltinRunner::new(&instance_def, true);
+
+            assert_eq!(
+                builtin.deduce_memory_cell(&Relocatable::from((3, 5)), &memory),
+                Ok(Some(MaybeRelocatable::from(felt_str!(x))))
+            );
+            assert_eq!(
+                builtin.deduce_memory_cell(&Relocatable::from((3, 6)), &memory),
+                Ok(Some(MaybeRelocatable::from(felt_str!(y))))
+            );
+        }
+    }
 
-        let _error = builtin.deduce_memory_cell(&Relocatable::from((3, 6)), &memory);
-        /*assert_eq!(
-            error,
-            Err(RunnerError::EcOpBuiltinScalarLimit(
-                builtin.ec_op_builtin.scalar_limit
+    #[test]
+    fn compute_ec_op_impl_m_over_height() {
+        let partial_sum = (Felt::one(), Felt::new(9));
+        let doubled_point = (Felt::new(2), Felt::new(12));
+        let prime = bigint_str!(
+            "3618502788666131213697322783095070105623107215331596699973092056135872020481"
+        );
+        assert_eq!(
+            EcOpBuiltinRunner::ec_op_impl(
+                partial_sum,
+                doubled_point,
+                &Felt::new(16),
+                &bigint!(1),
+                &prime,
+                4
+            ),
+            Err(RunnerError::EcOpScalarTooLarge(
+                Felt::new(16),
+                BigUint::from(16_u32)
             ))
-        );*/
+        );
     }
 
     #[test]
     fn get_memory_segment_addresses() {
         let builtin = EcOpBuiltinRunner::new(&EcOpInstanceDef::default(), true);
 
-        assert_eq!(builtin.get_memory_segment_addresses(), ("ec_op", (0, None)));
+        assert_eq!(
+            builtin.get_memory_segment_addresses(),
+            vec![SegmentAddresses::new("ec_op", 0, None)]
+        );
     }
 
     #[test]
@@ -928,7 +1252,7 @@ mod tests {
 
         assert_eq!(
             builtin.get_memory_accesses(&vm),
-            Err(MemoryError::MissingSegmentUsedSizes),
+            Err(MemoryError::MissingSegmentUsedSizes(0)),
         );
     }
 
@@ -949,6 +1273,7 @@ mod tests {
         let mut vm = vm!();
 
         vm.segments.segment_used_sizes = Some(vec![4]);
+        vm.memory = memory![((0, 0), 1), ((0, 1), 2), ((0, 2), 3), ((0, 3), 4)];
         assert_eq!(
             builtin.get_memory_accesses(&vm),
             Ok(vec![
@@ -960,6 +1285,20 @@ mod tests {
         );
     }
 
+    #[test]
+    fn get_memory_accesses_skips_unwritten_cells() {
+        let builtin =
+            BuiltinRunner::EcOp(EcOpBuiltinRunner::new(&EcOpInstanceDef::default(), true));
+        let mut vm = vm!();
+
+        vm.segments.segment_used_sizes = Some(vec![4]);
+        vm.memory = memory![((0, 0), 1), ((0, 3), 4)];
+        assert_eq!(
+            builtin.get_memory_accesses(&vm),
+            Ok(vec![(builtin.base(), 0).into(), (builtin.base(), 3).into()]),
+        );
+    }
+
     #[test]
     fn get_used_cells_missing_segment_used_sizes() {
         let builtin =
@@ -968,7 +1307,7 @@ mod tests {
 
         assert_eq!(
             builtin.get_used_cells(&vm),
-            Err(MemoryError::MissingSegmentUsedSizes)
+            Err(MemoryError::MissingSegmentUsedSizes(0))
         );
     }
 
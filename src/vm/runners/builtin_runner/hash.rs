@@ -1,44 +1,58 @@
 use std::cell::RefCell;
 
-use crate::math_utils::safe_div_usize;
+use crate::math_utils::pedersen_hash;
 use crate::types::instance_definitions::pedersen_instance_def::{
-    CELLS_PER_HASH, INPUT_CELLS_PER_HASH,
+    PedersenInstanceDef, CELLS_PER_HASH, INPUT_CELLS_PER_HASH,
 };
 use crate::types::relocatable::{MaybeRelocatable, Relocatable};
 use crate::vm::errors::memory_errors::MemoryError;
 use crate::vm::errors::runner_errors::RunnerError;
+use crate::vm::runners::air_private_input::{PrivateInput, PrivateInputPair};
+use crate::vm::runners::builtin_runner::{
+    get_allocated_instances, get_instances_inputs, SegmentAddresses,
+};
 use crate::vm::vm_core::VirtualMachine;
 use crate::vm::vm_memory::memory::Memory;
 use crate::vm::vm_memory::memory_segments::MemorySegmentManager;
-use felt::{Felt, FeltOps};
+use felt::FeltOps;
+use num_bigint::BigUint;
 use num_integer::{div_ceil, Integer};
-use starknet_crypto::{pedersen_hash, FieldElement};
 
 #[derive(Debug, Clone)]
 pub struct HashBuiltinRunner {
     pub base: isize,
+    pub(crate) initialized: bool,
     ratio: u32,
+    pub(crate) ratio_den: u32,
     pub(crate) cells_per_instance: u32,
     pub(crate) n_input_cells: u32,
     pub(crate) stop_ptr: Option<usize>,
-    pub(crate) _included: bool,
+    pub(crate) included: bool,
     instances_per_component: u32,
+    // The inputs of an instance must be smaller than this limit
+    hash_limit: BigUint,
+    // Whether the cell at each offset of the segment was already deduced, growing on demand.
     // This act as a cache to optimize calls to deduce_memory_cell
     // Therefore need interior mutability
-    pub(self) verified_addresses: RefCell<Vec<Relocatable>>,
+    pub(self) verified_addresses: RefCell<Vec<bool>>,
 }
 
 impl HashBuiltinRunner {
-    pub fn new(ratio: u32, included: bool) -> Self {
+    pub const NAME: &str = "pedersen";
+
+    pub(crate) fn new(instance_def: &PedersenInstanceDef, included: bool) -> Self {
         HashBuiltinRunner {
             base: 0,
-            ratio,
+            initialized: false,
+            ratio: instance_def.ratio,
+            ratio_den: instance_def.ratio_den,
             cells_per_instance: CELLS_PER_HASH,
             n_input_cells: INPUT_CELLS_PER_HASH,
             stop_ptr: None,
             verified_addresses: RefCell::new(Vec::new()),
-            _included: included,
+            included,
             instances_per_component: 1,
+            hash_limit: instance_def.hash_limit.magnitude().clone(),
         }
     }
 
@@ -46,12 +60,17 @@ impl HashBuiltinRunner {
         &mut self,
         segments: &mut MemorySegmentManager,
         memory: &mut Memory,
-    ) {
-        self.base = segments.add(memory).segment_index
+    ) -> Result<(), RunnerError> {
+        if self.initialized {
+            return Err(RunnerError::BuiltinAlreadyInitialized(Self::NAME));
+        }
+        self.base = segments.add(memory).segment_index;
+        self.initialized = true;
+        Ok(())
     }
 
     pub fn initial_stack(&self) -> Vec<MaybeRelocatable> {
-        if self._included {
+        if self.included {
             vec![MaybeRelocatable::from((self.base, 0))]
         } else {
             vec![]
@@ -62,159 +81,243 @@ impl HashBuiltinRunner {
         self.base
     }
 
+    pub fn is_initialized(&self) -> bool {
+        self.initialized
+    }
+
     pub fn ratio(&self) -> u32 {
         self.ratio
     }
 
+    pub fn ratio_den(&self) -> u32 {
+        self.ratio_den
+    }
+
     pub fn add_validation_rule(&self, _memory: &mut Memory) -> Result<(), RunnerError> {
         Ok(())
     }
 
+    /// Returns true if the output cell at the given offset of the segment was already deduced.
+    pub fn is_verified(&self, offset: usize) -> bool {
+        self.verified_addresses
+            .borrow()
+            .get(offset)
+            .copied()
+            .unwrap_or(false)
+    }
+
+    pub fn get_additional_data(&self) -> Vec<Relocatable> {
+        self.verified_addresses
+            .borrow()
+            .iter()
+            .enumerate()
+            .filter(|(_, verified)| **verified)
+            .map(|(offset, _)| Relocatable::from((self.base, offset)))
+            .collect()
+    }
+
+    pub(crate) fn clear_verified_addresses(&mut self) {
+        self.verified_addresses.get_mut().clear();
+    }
+
+    // Marks the given addresses as verified, so that they aren't deduced again. The addresses
+    // must be in the builtin's segment
+    pub fn extend_additional_data(&mut self, addresses: &[Relocatable]) -> Result<(), RunnerError> {
+        if addresses
+            .iter()
+            .any(|address| address.segment_index != self.base)
+        {
+            return Err(RunnerError::InvalidAdditionalData(Self::NAME));
+        }
+        let verified_addresses = self.verified_addresses.get_mut();
+        for address in addresses {
+            mark_verified(verified_addresses, address.offset);
+        }
+        Ok(())
+    }
+
     pub fn deduce_memory_cell(
         &self,
         address: &Relocatable,
         memory: &Memory,
+    ) -> Result<Option<MaybeRelocatable>, RunnerError> {
+        if self.is_verified(address.offset) {
+            return Ok(None);
+        }
+        let result = self.hash_cell(address, memory)?;
+        if result.is_some() {
+            mark_verified(&mut self.verified_addresses.borrow_mut(), address.offset);
+        }
+        Ok(result)
+    }
+
+    // Computes the hash of the instance of the given output cell, without recording the address
+    // as verified
+    pub(crate) fn hash_cell(
+        &self,
+        address: &Relocatable,
+        memory: &Memory,
     ) -> Result<Option<MaybeRelocatable>, RunnerError> {
         if address
             .offset
             .mod_floor(&(self.cells_per_instance as usize))
             != 2
-            || self.verified_addresses.borrow().contains(address)
         {
             return Ok(None);
         };
 
-        let num_a = memory.get(&MaybeRelocatable::RelocatableValue(Relocatable {
-            segment_index: address.segment_index,
-            offset: address.offset - 1,
-        }));
-        let num_b = memory.get(&MaybeRelocatable::RelocatableValue(Relocatable {
-            segment_index: address.segment_index,
-            offset: address.offset - 2,
-        }));
+        let a_addr = Relocatable::from((address.segment_index, address.offset - 1));
+        let b_addr = Relocatable::from((address.segment_index, address.offset - 2));
+        let num_a = memory.get(&a_addr);
+        let num_b = memory.get(&b_addr);
         if let (Ok(Some(MaybeRelocatable::Int(num_a))), Ok(Some(MaybeRelocatable::Int(num_b)))) = (
             num_a.as_ref().map(|x| x.as_ref().map(|x| x.as_ref())),
             num_b.as_ref().map(|x| x.as_ref().map(|x| x.as_ref())),
         ) {
-            self.verified_addresses.borrow_mut().push(*address);
-
-            //Convert MaybeRelocatable to FieldElement
-            let a_string = num_a.to_str_radix(10);
-            let b_string = num_b.to_str_radix(10);
-            let (y, x) = match (
-                FieldElement::from_dec_str(&a_string),
-                FieldElement::from_dec_str(&b_string),
-            ) {
-                (Ok(field_element_a), Ok(field_element_b)) => (field_element_a, field_element_b),
-                _ => return Err(RunnerError::FailedStringConversion),
-            };
-            //Compute pedersen Hash
-            let fe_result = pedersen_hash(&x, &y);
-            //Convert result from FieldElement to MaybeRelocatable
-            let r_byte_slice = fe_result.to_bytes_be();
-            let result = Felt::from_bytes_be(&r_byte_slice);
+            for (addr, num) in [(b_addr, num_b), (a_addr, num_a)] {
+                if num.to_biguint() >= self.hash_limit {
+                    return Err(RunnerError::PedersenInputOverHashLimit(
+                        addr.into(),
+                        num.clone(),
+                        self.hash_limit.clone(),
+                    ));
+                }
+            }
+            let result = pedersen_hash(num_b, num_a)?;
             return Ok(Some(MaybeRelocatable::from(result)));
         }
         Ok(None)
     }
 
     pub fn get_allocated_memory_units(&self, vm: &VirtualMachine) -> Result<usize, MemoryError> {
-        let value = safe_div_usize(vm.current_step, self.ratio as usize)
-            .map_err(|_| MemoryError::ErrorCalculatingMemoryUnits)?;
+        let value =
+            get_allocated_instances(Self::NAME, vm.current_step, self.ratio, self.ratio_den)?;
         Ok(self.cells_per_instance as usize * value)
     }
 
-    pub fn get_memory_segment_addresses(&self) -> (&'static str, (isize, Option<usize>)) {
-        ("pedersen", (self.base, self.stop_ptr))
+    pub fn air_private_input(&self, memory: &Memory) -> Vec<PrivateInput> {
+        get_instances_inputs::<2>(memory, self.base, self.cells_per_instance)
+            .into_iter()
+            .map(|(index, [x, y])| PrivateInput::Pair(PrivateInputPair { index, x, y }))
+            .collect()
+    }
+
+    pub fn get_memory_segment_addresses(&self) -> Vec<SegmentAddresses> {
+        vec![SegmentAddresses::new(Self::NAME, self.base, self.stop_ptr)]
     }
 
     pub fn get_used_cells(&self, vm: &VirtualMachine) -> Result<usize, MemoryError> {
         let base = self.base();
+        let index = base
+            .try_into()
+            .map_err(|_| MemoryError::AddressInTemporarySegment(base))?;
         vm.segments
-            .get_segment_used_size(
-                base.try_into()
-                    .map_err(|_| MemoryError::AddressInTemporarySegment(base))?,
-            )
-            .ok_or(MemoryError::MissingSegmentUsedSizes)
+            .get_segment_used_size(index)
+            .ok_or(MemoryError::MissingSegmentUsedSizes(index))
+    }
+
+    pub fn get_used_cells_mid_run(&self, vm: &VirtualMachine) -> Result<usize, MemoryError> {
+        let base = self.base();
+        let index = base
+            .try_into()
+            .map_err(|_| MemoryError::AddressInTemporarySegment(base))?;
+        vm.segments
+            .get_segment_used_size_mid_run(index, &vm.memory)
+            .ok_or(MemoryError::UnallocatedSegment(index, vm.memory.data.len()))
     }
 
     pub fn get_used_cells_and_allocated_size(
         &self,
         vm: &VirtualMachine,
     ) -> Result<(usize, usize), MemoryError> {
-        let ratio = self.ratio as usize;
         let cells_per_instance = self.cells_per_instance;
-        let min_step = ratio * self.instances_per_component as usize;
+        let min_step = div_ceil(
+            self.ratio as usize * self.instances_per_component as usize,
+            self.ratio_den as usize,
+        );
         if vm.current_step < min_step {
-            Err(MemoryError::InsufficientAllocatedCells)
+            Err(MemoryError::MinStepNotReached {
+                builtin: Self::NAME,
+                min_step,
+            })
         } else {
             let used = self.get_used_cells(vm)?;
             let size = cells_per_instance as usize
-                * safe_div_usize(vm.current_step, ratio)
-                    .map_err(|_| MemoryError::InsufficientAllocatedCells)?;
+                * get_allocated_instances(Self::NAME, vm.current_step, self.ratio, self.ratio_den)?;
             if used > size {
-                return Err(MemoryError::InsufficientAllocatedCells);
+                return Err(MemoryError::InsufficientAllocatedCells {
+                    builtin: Self::NAME,
+                    used,
+                    allocated: size,
+                });
             }
             Ok((used, size))
         }
     }
 
     pub fn get_used_instances(&self, vm: &VirtualMachine) -> Result<usize, MemoryError> {
-        let used_cells = self.get_used_cells(vm)?;
+        let used_cells = self.get_used_cells_mid_run(vm)?;
         Ok(div_ceil(used_cells, self.cells_per_instance as usize))
     }
 
     pub fn final_stack(
-        &self,
+        &mut self,
         vm: &VirtualMachine,
         pointer: Relocatable,
-    ) -> Result<(Relocatable, usize), RunnerError> {
-        if self._included {
-            if let Ok(stop_pointer) =
-                vm.get_relocatable(&(pointer.sub_usize(1)).map_err(|_| RunnerError::FinalStack)?)
-            {
-                if self.base() != stop_pointer.segment_index {
-                    return Err(RunnerError::InvalidStopPointer("pedersen".to_string()));
-                }
-
-                let stop_ptr = stop_pointer.offset;
+    ) -> Result<Relocatable, RunnerError> {
+        if self.included {
+            if let Ok(stop_pointer) = vm.get_relocatable(
+                &(pointer.sub_usize(1)).map_err(|_| RunnerError::FinalStack(Self::NAME))?,
+            ) {
                 let num_instances = self
                     .get_used_instances(vm)
-                    .map_err(|_| RunnerError::FinalStack)?;
+                    .map_err(|_| RunnerError::FinalStack(Self::NAME))?;
                 let used_cells = num_instances * self.cells_per_instance as usize;
-                if stop_ptr != used_cells {
-                    return Err(RunnerError::InvalidStopPointer("pedersen".to_string()));
+                let expected = Relocatable::from((self.base(), used_cells));
+                if stop_pointer != expected {
+                    return Err(RunnerError::InvalidStopPointer {
+                        builtin: Self::NAME,
+                        expected,
+                        found: stop_pointer,
+                    });
                 }
-                Ok((
-                    pointer.sub_usize(1).map_err(|_| RunnerError::FinalStack)?,
-                    stop_ptr,
-                ))
+                self.stop_ptr = Some(stop_pointer.offset);
+                Ok(pointer
+                    .sub_usize(1)
+                    .map_err(|_| RunnerError::FinalStack(Self::NAME))?)
             } else {
-                Err(RunnerError::FinalStack)
+                Err(RunnerError::FinalStack(Self::NAME))
             }
         } else {
-            let stop_ptr = self.base() as usize;
-            Ok((pointer, stop_ptr))
+            self.stop_ptr = Some(0);
+            Ok(pointer)
         }
     }
 }
 
+fn mark_verified(verified_addresses: &mut Vec<bool>, offset: usize) {
+    if offset >= verified_addresses.len() {
+        verified_addresses.resize(offset + 1, false);
+    }
+    verified_addresses[offset] = true;
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::hint_processor::builtin_hint_processor::builtin_hint_processor_definition::BuiltinHintProcessor;
     use crate::types::program::Program;
     use crate::utils::test_utils::*;
-    use crate::vm::runners::cairo_runner::CairoRunner;
+    use crate::vm::runners::cairo_runner::{CairoRunner, RunResources};
     use crate::vm::{
         errors::memory_errors::MemoryError, runners::builtin_runner::BuiltinRunner,
         vm_core::VirtualMachine,
     };
-    use felt::felt_str;
+    use felt::{felt_str, Felt, NewFelt};
 
     #[test]
     fn get_used_instances() {
-        let builtin = HashBuiltinRunner::new(10, true);
+        let builtin = HashBuiltinRunner::new(&PedersenInstanceDef::new(10, 1), true);
 
         let mut vm = vm!();
 
@@ -232,7 +335,7 @@ mod tests {
 
     #[test]
     fn final_stack() {
-        let builtin = HashBuiltinRunner::new(10, true);
+        let mut builtin = HashBuiltinRunner::new(&PedersenInstanceDef::new(10, 1), true);
 
         let mut vm = vm!();
 
@@ -249,13 +352,14 @@ mod tests {
 
         assert_eq!(
             builtin.final_stack(&vm, pointer).unwrap(),
-            (Relocatable::from((2, 1)), 0)
+            Relocatable::from((2, 1))
         );
+        assert_eq!(builtin.stop_ptr, Some(0));
     }
 
     #[test]
     fn final_stack_error_stop_pointer() {
-        let builtin = HashBuiltinRunner::new(10, true);
+        let mut builtin = HashBuiltinRunner::new(&PedersenInstanceDef::new(10, 1), true);
 
         let mut vm = vm!();
 
@@ -272,13 +376,17 @@ mod tests {
 
         assert_eq!(
             builtin.final_stack(&vm, pointer),
-            Err(RunnerError::InvalidStopPointer("pedersen".to_string()))
+            Err(RunnerError::InvalidStopPointer {
+                builtin: "pedersen",
+                expected: Relocatable::from((0, 999)),
+                found: Relocatable::from((0, 0))
+            })
         );
     }
 
     #[test]
     fn final_stack_error_when_not_included() {
-        let builtin = HashBuiltinRunner::new(10, false);
+        let mut builtin = HashBuiltinRunner::new(&PedersenInstanceDef::new(10, 1), false);
 
         let mut vm = vm!();
 
@@ -295,13 +403,14 @@ mod tests {
 
         assert_eq!(
             builtin.final_stack(&vm, pointer).unwrap(),
-            (Relocatable::from((2, 2)), 0)
+            Relocatable::from((2, 2))
         );
+        assert_eq!(builtin.stop_ptr, Some(0));
     }
 
     #[test]
     fn final_stack_error_non_relocatable() {
-        let builtin = HashBuiltinRunner::new(10, true);
+        let mut builtin = HashBuiltinRunner::new(&PedersenInstanceDef::new(10, 1), true);
 
         let mut vm = vm!();
 
@@ -318,41 +427,20 @@ mod tests {
 
         assert_eq!(
             builtin.final_stack(&vm, pointer),
-            Err(RunnerError::FinalStack)
+            Err(RunnerError::FinalStack("pedersen"))
         );
     }
 
     #[test]
     fn get_used_cells_and_allocated_size_test() {
-        let builtin: BuiltinRunner = HashBuiltinRunner::new(10, true).into();
+        let builtin: BuiltinRunner =
+            HashBuiltinRunner::new(&PedersenInstanceDef::new(10, 1), true).into();
 
         let mut vm = vm!();
 
         vm.segments.segment_used_sizes = Some(vec![0]);
 
-        let program = program!(
-            builtins = vec![String::from("pedersen")],
-            data = vec_data!(
-                (4612671182993129469_i64),
-                (5189976364521848832_i64),
-                (18446744073709551615_i128),
-                (5199546496550207487_i64),
-                (4612389712311386111_i64),
-                (5198983563776393216_i64),
-                (2),
-                (2345108766317314046_i64),
-                (5191102247248822272_i64),
-                (5189976364521848832_i64),
-                (7),
-                (1226245742482522112_i64),
-                ((
-                    "3618502788666131213697322783095070105623107215331596699973092056135872020470",
-                    10
-                )),
-                (2345108766317314046_i64)
-            ),
-            main = Some(8),
-        );
+        let program = Program::from_fixture("check_range");
 
         let mut cairo_runner = cairo_runner!(program);
 
@@ -361,7 +449,12 @@ mod tests {
         let address = cairo_runner.initialize(&mut vm).unwrap();
 
         cairo_runner
-            .run_until_pc(address, &mut vm, &mut hint_processor)
+            .run_until_pc(
+                address,
+                &mut RunResources::default(),
+                &mut vm,
+                &mut hint_processor,
+            )
             .unwrap();
 
         assert_eq!(builtin.get_used_cells_and_allocated_size(&vm), Ok((0, 3)));
@@ -369,33 +462,11 @@ mod tests {
 
     #[test]
     fn get_allocated_memory_units() {
-        let builtin = HashBuiltinRunner::new(10, true);
+        let builtin = HashBuiltinRunner::new(&PedersenInstanceDef::new(10, 1), true);
 
         let mut vm = vm!();
 
-        let program = program!(
-            builtins = vec![String::from("pedersen")],
-            data = vec_data!(
-                (4612671182993129469_i64),
-                (5189976364521848832_i64),
-                (18446744073709551615_i128),
-                (5199546496550207487_i64),
-                (4612389712311386111_i64),
-                (5198983563776393216_i64),
-                (2),
-                (2345108766317314046_i64),
-                (5191102247248822272_i64),
-                (5189976364521848832_i64),
-                (7),
-                (1226245742482522112_i64),
-                ((
-                    "3618502788666131213697322783095070105623107215331596699973092056135872020470",
-                    10
-                )),
-                (2345108766317314046_i64)
-            ),
-            main = Some(8),
-        );
+        let program = Program::from_fixture("check_range");
 
         let mut cairo_runner = cairo_runner!(program);
 
@@ -404,7 +475,12 @@ mod tests {
         let address = cairo_runner.initialize(&mut vm).unwrap();
 
         cairo_runner
-            .run_until_pc(address, &mut vm, &mut hint_processor)
+            .run_until_pc(
+                address,
+                &mut RunResources::default(),
+                &mut vm,
+                &mut hint_processor,
+            )
             .unwrap();
 
         assert_eq!(builtin.get_allocated_memory_units(&vm), Ok(3));
@@ -413,7 +489,7 @@ mod tests {
     #[test]
     fn deduce_memory_cell_pedersen_for_preset_memory_valid() {
         let memory = memory![((0, 3), 32), ((0, 4), 72), ((0, 5), 0)];
-        let builtin = HashBuiltinRunner::new(8, true);
+        let builtin = HashBuiltinRunner::new(&PedersenInstanceDef::default(), true);
 
         let result = builtin.deduce_memory_cell(&Relocatable::from((0, 5)), &memory);
         assert_eq!(
@@ -422,8 +498,9 @@ mod tests {
                 "3270867057177188607814717243084834301278723532952411121381966378910183338911"
             ))))
         );
+        assert!(builtin.is_verified(5));
         assert_eq!(
-            builtin.verified_addresses.into_inner(),
+            builtin.get_additional_data(),
             vec![Relocatable::from((0, 5))]
         );
     }
@@ -431,7 +508,7 @@ mod tests {
     #[test]
     fn deduce_memory_cell_pedersen_for_preset_memory_incorrect_offset() {
         let memory = memory![((0, 4), 32), ((0, 5), 72), ((0, 6), 0)];
-        let builtin = HashBuiltinRunner::new(8, true);
+        let builtin = HashBuiltinRunner::new(&PedersenInstanceDef::default(), true);
         let result = builtin.deduce_memory_cell(&Relocatable::from((0, 6)), &memory);
         assert_eq!(result, Ok(None));
     }
@@ -439,44 +516,113 @@ mod tests {
     #[test]
     fn deduce_memory_cell_pedersen_for_preset_memory_no_values_to_hash() {
         let memory = memory![((0, 4), 72), ((0, 5), 0)];
-        let builtin = HashBuiltinRunner::new(8, true);
+        let builtin = HashBuiltinRunner::new(&PedersenInstanceDef::default(), true);
         let result = builtin.deduce_memory_cell(&Relocatable::from((0, 5)), &memory);
         assert_eq!(result, Ok(None));
     }
 
+    #[test]
+    fn deduce_memory_cell_pedersen_for_preset_memory_relocatable_input() {
+        let memory = memory![((0, 3), (1, 0)), ((0, 4), 72)];
+        let builtin = HashBuiltinRunner::new(&PedersenInstanceDef::default(), true);
+        let result = builtin.deduce_memory_cell(&Relocatable::from((0, 5)), &memory);
+        assert_eq!(result, Ok(None));
+    }
+
+    #[test]
+    fn deduce_memory_cell_pedersen_input_over_hash_limit() {
+        let instance_def = PedersenInstanceDef {
+            hash_limit: num_bigint::BigInt::from(100),
+            ..PedersenInstanceDef::default()
+        };
+        let builtin = HashBuiltinRunner::new(&instance_def, true);
+        let memory = memory![((0, 3), 32), ((0, 4), 99)];
+        assert!(matches!(
+            builtin.deduce_memory_cell(&Relocatable::from((0, 5)), &memory),
+            Ok(Some(_))
+        ));
+        let builtin = HashBuiltinRunner::new(&instance_def, true);
+        let memory = memory![((0, 3), 32), ((0, 4), 100)];
+        assert_eq!(
+            builtin.deduce_memory_cell(&Relocatable::from((0, 5)), &memory),
+            Err(RunnerError::PedersenInputOverHashLimit(
+                MaybeRelocatable::from((0, 4)),
+                Felt::new(100),
+                BigUint::from(100_u32)
+            ))
+        );
+    }
+
+    #[test]
+    fn deduce_memory_cell_many_verified_instances() {
+        // Looking up the verified cells doesn't depend on how many of them there are, so
+        // going over a hundred thousand already deduced instances stays fast
+        const N_INSTANCES: usize = 100_000;
+        let mut builtin = HashBuiltinRunner::new(&PedersenInstanceDef::default(), true);
+        let output_cells = (0..N_INSTANCES)
+            .map(|index| Relocatable::from((0, index * CELLS_PER_HASH as usize + 2)))
+            .collect::<Vec<_>>();
+        builtin.extend_additional_data(&output_cells).unwrap();
+
+        let memory = Memory::new();
+        for address in &output_cells {
+            assert_eq!(builtin.deduce_memory_cell(address, &memory), Ok(None));
+        }
+        assert!(!builtin.is_verified(N_INSTANCES * CELLS_PER_HASH as usize + 2));
+        assert_eq!(builtin.get_additional_data(), output_cells);
+    }
+
+    #[test]
+    fn extend_additional_data_other_segment() {
+        let mut builtin = HashBuiltinRunner::new(&PedersenInstanceDef::default(), true);
+        assert_eq!(
+            builtin.extend_additional_data(&[Relocatable::from((1, 2))]),
+            Err(RunnerError::InvalidAdditionalData("pedersen"))
+        );
+        assert!(!builtin.is_verified(2));
+    }
+
     #[test]
     fn deduce_memory_cell_pedersen_for_preset_memory_already_computed() {
         let memory = memory![((0, 3), 32), ((0, 4), 72), ((0, 5), 0)];
-        let mut builtin = HashBuiltinRunner::new(8, true);
-        builtin.verified_addresses = RefCell::new(vec![Relocatable::from((0, 5))]);
+        let mut builtin = HashBuiltinRunner::new(&PedersenInstanceDef::default(), true);
+        builtin
+            .extend_additional_data(&[Relocatable::from((0, 5))])
+            .unwrap();
         let result = builtin.deduce_memory_cell(&Relocatable::from((0, 5)), &memory);
         assert_eq!(result, Ok(None));
     }
 
     #[test]
     fn get_memory_segment_addresses() {
-        let builtin = HashBuiltinRunner::new(256, true);
+        let builtin = HashBuiltinRunner::new(&PedersenInstanceDef::new(256, 1), true);
 
         assert_eq!(
             builtin.get_memory_segment_addresses(),
-            ("pedersen", (0, None)),
+            vec![SegmentAddresses::new("pedersen", 0, None)],
         );
     }
 
     #[test]
     fn get_memory_accesses_missing_segment_used_sizes() {
-        let builtin = BuiltinRunner::Hash(HashBuiltinRunner::new(256, true));
+        let builtin = BuiltinRunner::Hash(HashBuiltinRunner::new(
+            &PedersenInstanceDef::new(256, 1),
+            true,
+        ));
         let vm = vm!();
 
         assert_eq!(
             builtin.get_memory_accesses(&vm),
-            Err(MemoryError::MissingSegmentUsedSizes),
+            Err(MemoryError::MissingSegmentUsedSizes(0)),
         );
     }
 
     #[test]
     fn get_memory_accesses_empty() {
-        let builtin = BuiltinRunner::Hash(HashBuiltinRunner::new(256, true));
+        let builtin = BuiltinRunner::Hash(HashBuiltinRunner::new(
+            &PedersenInstanceDef::new(256, 1),
+            true,
+        ));
         let mut vm = vm!();
 
         vm.segments.segment_used_sizes = Some(vec![0]);
@@ -485,10 +631,14 @@ mod tests {
 
     #[test]
     fn get_memory_accesses() {
-        let builtin = BuiltinRunner::Hash(HashBuiltinRunner::new(256, true));
+        let builtin = BuiltinRunner::Hash(HashBuiltinRunner::new(
+            &PedersenInstanceDef::new(256, 1),
+            true,
+        ));
         let mut vm = vm!();
 
         vm.segments.segment_used_sizes = Some(vec![4]);
+        vm.memory = memory![((0, 0), 1), ((0, 1), 2), ((0, 2), 3), ((0, 3), 4)];
         assert_eq!(
             builtin.get_memory_accesses(&vm),
             Ok(vec![
@@ -500,20 +650,42 @@ mod tests {
         );
     }
 
+    #[test]
+    fn get_memory_accesses_skips_unwritten_cells() {
+        let builtin = BuiltinRunner::Hash(HashBuiltinRunner::new(
+            &PedersenInstanceDef::new(256, 1),
+            true,
+        ));
+        let mut vm = vm!();
+
+        vm.segments.segment_used_sizes = Some(vec![4]);
+        vm.memory = memory![((0, 0), 1), ((0, 3), 4)];
+        assert_eq!(
+            builtin.get_memory_accesses(&vm),
+            Ok(vec![(builtin.base(), 0).into(), (builtin.base(), 3).into()]),
+        );
+    }
+
     #[test]
     fn get_used_cells_missing_segment_used_sizes() {
-        let builtin = BuiltinRunner::Hash(HashBuiltinRunner::new(256, true));
+        let builtin = BuiltinRunner::Hash(HashBuiltinRunner::new(
+            &PedersenInstanceDef::new(256, 1),
+            true,
+        ));
         let vm = vm!();
 
         assert_eq!(
             builtin.get_used_cells(&vm),
-            Err(MemoryError::MissingSegmentUsedSizes)
+            Err(MemoryError::MissingSegmentUsedSizes(0))
         );
     }
 
     #[test]
     fn get_used_cells_empty() {
-        let builtin = BuiltinRunner::Hash(HashBuiltinRunner::new(256, true));
+        let builtin = BuiltinRunner::Hash(HashBuiltinRunner::new(
+            &PedersenInstanceDef::new(256, 1),
+            true,
+        ));
         let mut vm = vm!();
 
         vm.segments.segment_used_sizes = Some(vec![0]);
@@ -522,7 +694,10 @@ mod tests {
 
     #[test]
     fn get_used_cells() {
-        let builtin = BuiltinRunner::Hash(HashBuiltinRunner::new(256, true));
+        let builtin = BuiltinRunner::Hash(HashBuiltinRunner::new(
+            &PedersenInstanceDef::new(256, 1),
+            true,
+        ));
         let mut vm = vm!();
 
         vm.segments.segment_used_sizes = Some(vec![4]);
@@ -7,38 +7,48 @@ use crate::types::instance_definitions::keccak_instance_def::KeccakInstanceDef;
 use crate::types::relocatable::{MaybeRelocatable, Relocatable};
 use crate::vm::errors::memory_errors::MemoryError;
 use crate::vm::errors::runner_errors::RunnerError;
+use crate::vm::runners::air_private_input::{PrivateInput, PrivateInputKeccakState};
+use crate::vm::runners::builtin_runner::{
+    get_allocated_instances, get_instances_inputs, SegmentAddresses,
+};
 use crate::vm::vm_core::VirtualMachine;
-use crate::vm::vm_memory::memory::Memory;
+use crate::vm::vm_memory::memory::{Memory, ValidationRule};
 use crate::vm::vm_memory::memory_segments::MemorySegmentManager;
 use felt::Felt;
 use num_integer::div_ceil;
-use num_traits::One;
+use num_traits::{One, ToPrimitive};
 
 const KECCAK_ARRAY_LEN: usize = 25;
 
 #[derive(Debug, Clone)]
 pub struct KeccakBuiltinRunner {
     ratio: u32,
+    pub(crate) ratio_den: u32,
     pub base: isize,
+    pub(crate) initialized: bool,
     pub(crate) cells_per_instance: u32,
     pub(crate) n_input_cells: u32,
     verified_addresses: Vec<Relocatable>,
     pub(crate) stop_ptr: Option<usize>,
-    _included: bool,
+    pub(crate) included: bool,
     state_rep: Vec<u32>,
     instances_per_component: u32,
 }
 
 impl KeccakBuiltinRunner {
+    pub const NAME: &str = "keccak";
+
     pub(crate) fn new(instance_def: &KeccakInstanceDef, included: bool) -> Self {
         KeccakBuiltinRunner {
             base: 0,
+            initialized: false,
             ratio: instance_def._ratio,
+            ratio_den: instance_def.ratio_den,
             n_input_cells: instance_def._state_rep.len() as u32,
             cells_per_instance: instance_def._cells_per_builtin(),
             stop_ptr: None,
             verified_addresses: Vec::new(),
-            _included: included,
+            included,
             instances_per_component: instance_def._instance_per_component,
             state_rep: instance_def._state_rep.clone(),
         }
@@ -48,12 +58,17 @@ impl KeccakBuiltinRunner {
         &mut self,
         segments: &mut MemorySegmentManager,
         memory: &mut Memory,
-    ) {
-        self.base = segments.add(memory).segment_index
+    ) -> Result<(), RunnerError> {
+        if self.initialized {
+            return Err(RunnerError::BuiltinAlreadyInitialized(Self::NAME));
+        }
+        self.base = segments.add(memory).segment_index;
+        self.initialized = true;
+        Ok(())
     }
 
     pub fn initial_stack(&self) -> Vec<MaybeRelocatable> {
-        if self._included {
+        if self.included {
             vec![MaybeRelocatable::from((self.base, 0))]
         } else {
             vec![]
@@ -64,11 +79,66 @@ impl KeccakBuiltinRunner {
         self.base
     }
 
+    pub fn is_initialized(&self) -> bool {
+        self.initialized
+    }
+
     pub fn ratio(&self) -> u32 {
         self.ratio
     }
 
-    pub fn add_validation_rule(&self, _memory: &mut Memory) -> Result<(), RunnerError> {
+    pub fn ratio_den(&self) -> u32 {
+        self.ratio_den
+    }
+
+    ///Checks that each input cell written to the keccak segment fits in the bits of its state
+    ///representation. The output cells are deduced, so they aren't checked.
+    // Moves the runner and its verified addresses to the segment its temporary segment was
+    // relocated to
+    pub(crate) fn relocate_base(&mut self, new_base: isize) {
+        for address in self.verified_addresses.iter_mut() {
+            if address.segment_index == self.base {
+                address.segment_index = new_base;
+            }
+        }
+        self.base = new_base;
+    }
+
+    pub fn add_validation_rule(&self, memory: &mut Memory) -> Result<(), RunnerError> {
+        let cells_per_instance = self.cells_per_instance as usize;
+        let state_rep = self.state_rep.clone();
+        let rule = ValidationRule(Box::new(
+            move |memory: &Memory,
+                  address: &MaybeRelocatable|
+                  -> Result<Vec<MaybeRelocatable>, MemoryError> {
+                let addr: Relocatable = address.try_into()?;
+                let bits = match state_rep.get(addr.offset % cells_per_instance) {
+                    Some(bits) => *bits,
+                    None => return Ok(Vec::new()),
+                };
+                let value = match memory.get(address)? {
+                    Some(value) => value.into_owned(),
+                    None => return Ok(Vec::new()),
+                };
+                match value {
+                    MaybeRelocatable::Int(value) if value < (Felt::one() << bits) => {
+                        Ok(vec![address.to_owned()])
+                    }
+                    MaybeRelocatable::Int(value) => Err(MemoryError::KeccakInputOutOfBounds {
+                        addr,
+                        value,
+                        bound: Felt::one() << bits,
+                    }),
+                    MaybeRelocatable::RelocatableValue(_) => Err(MemoryError::FoundNonInt),
+                }
+            },
+        ));
+        match self.base.to_usize() {
+            Some(segment_index) => memory.add_validation_rule(segment_index, rule),
+            None => memory
+                .add_temp_validation_rule(self.base, rule)
+                .map_err(RunnerError::MemoryValidationError)?,
+        }
         Ok(())
     }
 
@@ -145,80 +215,121 @@ impl KeccakBuiltinRunner {
     }
 
     pub fn get_allocated_memory_units(&self, vm: &VirtualMachine) -> Result<usize, MemoryError> {
-        let value = safe_div_usize(vm.current_step, self.ratio as usize)
-            .map_err(|_| MemoryError::ErrorCalculatingMemoryUnits)?;
+        let value =
+            get_allocated_instances(Self::NAME, vm.current_step, self.ratio, self.ratio_den)?;
         Ok(self.cells_per_instance as usize * value)
     }
 
-    pub fn get_memory_segment_addresses(&self) -> (&'static str, (isize, Option<usize>)) {
-        ("keccak", (self.base, self.stop_ptr))
+    pub fn air_private_input(&self, memory: &Memory) -> Vec<PrivateInput> {
+        get_instances_inputs::<8>(memory, self.base, self.cells_per_instance)
+            .into_iter()
+            .map(
+                |(index, [input_s0, input_s1, input_s2, input_s3, input_s4, input_s5, input_s6, input_s7])| {
+                    PrivateInput::KeccakState(PrivateInputKeccakState {
+                        index,
+                        input_s0,
+                        input_s1,
+                        input_s2,
+                        input_s3,
+                        input_s4,
+                        input_s5,
+                        input_s6,
+                        input_s7,
+                    })
+                },
+            )
+            .collect()
+    }
+
+    pub fn get_memory_segment_addresses(&self) -> Vec<SegmentAddresses> {
+        vec![SegmentAddresses::new(Self::NAME, self.base, self.stop_ptr)]
     }
 
     pub fn get_used_cells(&self, vm: &VirtualMachine) -> Result<usize, MemoryError> {
         let base = self.base();
+        let index = base
+            .try_into()
+            .map_err(|_| MemoryError::AddressInTemporarySegment(base))?;
         vm.segments
-            .get_segment_used_size(
-                base.try_into()
-                    .map_err(|_| MemoryError::AddressInTemporarySegment(base))?,
-            )
-            .ok_or(MemoryError::MissingSegmentUsedSizes)
+            .get_segment_used_size(index)
+            .ok_or(MemoryError::MissingSegmentUsedSizes(index))
+    }
+
+    pub fn get_used_cells_mid_run(&self, vm: &VirtualMachine) -> Result<usize, MemoryError> {
+        let base = self.base();
+        let index = base
+            .try_into()
+            .map_err(|_| MemoryError::AddressInTemporarySegment(base))?;
+        vm.segments
+            .get_segment_used_size_mid_run(index, &vm.memory)
+            .ok_or(MemoryError::UnallocatedSegment(index, vm.memory.data.len()))
     }
 
     pub fn get_used_cells_and_allocated_size(
         &self,
         vm: &VirtualMachine,
     ) -> Result<(usize, usize), MemoryError> {
-        let ratio = self.ratio as usize;
-
         let cells_per_instance = self.cells_per_instance;
-        let min_step = ratio * self.instances_per_component as usize;
+        let min_step = div_ceil(
+            self.ratio as usize * self.instances_per_component as usize,
+            self.ratio_den as usize,
+        );
         if vm.current_step < min_step {
-            Err(MemoryError::InsufficientAllocatedCells)
+            Err(MemoryError::MinStepNotReached {
+                builtin: Self::NAME,
+                min_step,
+            })
         } else {
             let used = self.get_used_cells(vm)?;
             let size = cells_per_instance as usize
-                * safe_div_usize(vm.current_step, ratio)
-                    .map_err(|_| MemoryError::InsufficientAllocatedCells)?;
+                * get_allocated_instances(Self::NAME, vm.current_step, self.ratio, self.ratio_den)?;
+            if used > size {
+                return Err(MemoryError::InsufficientAllocatedCells {
+                    builtin: Self::NAME,
+                    used,
+                    allocated: size,
+                });
+            }
             Ok((used, size))
         }
     }
 
     pub fn get_used_instances(&self, vm: &VirtualMachine) -> Result<usize, MemoryError> {
-        let used_cells = self.get_used_cells(vm)?;
+        let used_cells = self.get_used_cells_mid_run(vm)?;
         Ok(div_ceil(used_cells, self.cells_per_instance as usize))
     }
 
     pub fn final_stack(
-        &self,
+        &mut self,
         vm: &VirtualMachine,
         pointer: Relocatable,
-    ) -> Result<(Relocatable, usize), RunnerError> {
-        if self._included {
-            if let Ok(stop_pointer) =
-                vm.get_relocatable(&(pointer.sub_usize(1)).map_err(|_| RunnerError::FinalStack)?)
-            {
-                if self.base() != stop_pointer.segment_index {
-                    return Err(RunnerError::InvalidStopPointer("keccak".to_string()));
-                }
-                let stop_ptr = stop_pointer.offset;
+    ) -> Result<Relocatable, RunnerError> {
+        if self.included {
+            if let Ok(stop_pointer) = vm.get_relocatable(
+                &(pointer.sub_usize(1)).map_err(|_| RunnerError::FinalStack(Self::NAME))?,
+            ) {
                 let num_instances = self
                     .get_used_instances(vm)
-                    .map_err(|_| RunnerError::FinalStack)?;
+                    .map_err(|_| RunnerError::FinalStack(Self::NAME))?;
                 let used_cells = num_instances * self.cells_per_instance as usize;
-                if stop_ptr != used_cells {
-                    return Err(RunnerError::InvalidStopPointer("keccak".to_string()));
+                let expected = Relocatable::from((self.base(), used_cells));
+                if stop_pointer != expected {
+                    return Err(RunnerError::InvalidStopPointer {
+                        builtin: Self::NAME,
+                        expected,
+                        found: stop_pointer,
+                    });
                 }
-
-                Ok((
-                    pointer.sub_usize(1).map_err(|_| RunnerError::FinalStack)?,
-                    stop_ptr,
-                ))
+                self.stop_ptr = Some(stop_pointer.offset);
+                Ok(pointer
+                    .sub_usize(1)
+                    .map_err(|_| RunnerError::FinalStack(Self::NAME))?)
             } else {
-                Err(RunnerError::FinalStack)
+                Err(RunnerError::FinalStack(Self::NAME))
             }
         } else {
-            let stop_ptr = self.base() as usize;
-            Ok((pointer, stop_ptr))
+            self.stop_ptr = Some(0);
+            Ok(pointer)
         }
     }
 
@@ -227,13 +338,13 @@ impl KeccakBuiltinRunner {
         vm: &VirtualMachine,
     ) -> Result<Vec<Relocatable>, MemoryError> {
         let base = self.base();
+        let index = base
+            .try_into()
+            .map_err(|_| MemoryError::AddressInTemporarySegment(base))?;
         let segment_size = vm
             .segments
-            .get_segment_size(
-                base.try_into()
-                    .map_err(|_| MemoryError::AddressInTemporarySegment(base))?,
-            )
-            .ok_or(MemoryError::MissingSegmentUsedSizes)?;
+            .get_segment_size(index)
+            .ok_or(MemoryError::MissingSegmentUsedSizes(index))?;
 
         Ok((0..segment_size).map(|i| (base, i).into()).collect())
     }
@@ -259,7 +370,7 @@ mod tests {
     use crate::hint_processor::builtin_hint_processor::builtin_hint_processor_definition::BuiltinHintProcessor;
     use crate::types::program::Program;
     use crate::utils::test_utils::*;
-    use crate::vm::runners::cairo_runner::CairoRunner;
+    use crate::vm::runners::cairo_runner::{CairoRunner, RunResources};
     use crate::vm::{
         errors::{memory_errors::MemoryError, runner_errors::RunnerError},
         runners::builtin_runner::BuiltinRunner,
@@ -288,7 +399,7 @@ mod tests {
 
     #[test]
     fn final_stack() {
-        let builtin = KeccakBuiltinRunner::new(&KeccakInstanceDef::new(10), true);
+        let mut builtin = KeccakBuiltinRunner::new(&KeccakInstanceDef::new(10), true);
 
         let mut vm = vm!();
 
@@ -305,13 +416,14 @@ mod tests {
 
         assert_eq!(
             builtin.final_stack(&vm, pointer).unwrap(),
-            (Relocatable::from((2, 1)), 0)
+            Relocatable::from((2, 1))
         );
+        assert_eq!(builtin.stop_ptr, Some(0));
     }
 
     #[test]
     fn final_stack_error_stop_pointer() {
-        let builtin = KeccakBuiltinRunner::new(&KeccakInstanceDef::new(10), true);
+        let mut builtin = KeccakBuiltinRunner::new(&KeccakInstanceDef::new(10), true);
 
         let mut vm = vm!();
 
@@ -328,13 +440,17 @@ mod tests {
 
         assert_eq!(
             builtin.final_stack(&vm, pointer),
-            Err(RunnerError::InvalidStopPointer("keccak".to_string()))
+            Err(RunnerError::InvalidStopPointer {
+                builtin: "keccak",
+                expected: Relocatable::from((0, 1008)),
+                found: Relocatable::from((0, 0))
+            })
         );
     }
 
     #[test]
     fn final_stack_error_when_not_included() {
-        let builtin = KeccakBuiltinRunner::new(&KeccakInstanceDef::new(10), false);
+        let mut builtin = KeccakBuiltinRunner::new(&KeccakInstanceDef::new(10), false);
 
         let mut vm = vm!();
 
@@ -351,13 +467,14 @@ mod tests {
 
         assert_eq!(
             builtin.final_stack(&vm, pointer).unwrap(),
-            (Relocatable::from((2, 2)), 0)
+            Relocatable::from((2, 2))
         );
+        assert_eq!(builtin.stop_ptr, Some(0));
     }
 
     #[test]
     fn final_stack_error_non_relocatable() {
-        let builtin = KeccakBuiltinRunner::new(&KeccakInstanceDef::new(10), true);
+        let mut builtin = KeccakBuiltinRunner::new(&KeccakInstanceDef::new(10), true);
 
         let mut vm = vm!();
 
@@ -374,7 +491,26 @@ mod tests {
 
         assert_eq!(
             builtin.final_stack(&vm, pointer),
-            Err(RunnerError::FinalStack)
+            Err(RunnerError::FinalStack("keccak"))
+        );
+    }
+
+    #[test]
+    fn get_used_cells_and_allocated_size_insufficient_allocated_cells() {
+        let builtin: BuiltinRunner =
+            KeccakBuiltinRunner::new(&KeccakInstanceDef::new(10), true).into();
+        let mut vm = vm!();
+        // 16 instances of 16 cells are allocated for 160 steps
+        vm.current_step = 160;
+        vm.segments.segment_used_sizes = Some(vec![257]);
+
+        assert_eq!(
+            builtin.get_used_cells_and_allocated_size(&vm),
+            Err(MemoryError::InsufficientAllocatedCells {
+                builtin: "keccak",
+                used: 257,
+                allocated: 256,
+            })
         );
     }
 
@@ -396,7 +532,12 @@ mod tests {
         let address = cairo_runner.initialize(&mut vm).unwrap();
 
         cairo_runner
-            .run_until_pc(address, &mut vm, &mut hint_processor)
+            .run_until_pc(
+                address,
+                &mut RunResources::default(),
+                &mut vm,
+                &mut hint_processor,
+            )
             .unwrap();
 
         assert_eq!(
@@ -412,29 +553,7 @@ mod tests {
 
         let mut vm = vm!();
 
-        let program = program!(
-            builtins = vec![String::from("keccak")],
-            data = vec_data!(
-                (4612671182993129469_i64),
-                (5189976364521848832_i64),
-                (18446744073709551615_i128),
-                (5199546496550207487_i64),
-                (4612389712311386111_i64),
-                (5198983563776393216_i64),
-                (2),
-                (2345108766317314046_i64),
-                (5191102247248822272_i64),
-                (5189976364521848832_i64),
-                (7),
-                (1226245742482522112_i64),
-                ((
-                    "3618502788666131213697322783095070105623107215331596699973092056135872020470",
-                    10
-                )),
-                (2345108766317314046_i64)
-            ),
-            main = Some(8),
-        );
+        let program = Program::from_fixture("check_range");
 
         let mut cairo_runner = cairo_runner!(program, "recursive");
 
@@ -443,7 +562,12 @@ mod tests {
         let address = cairo_runner.initialize(&mut vm).unwrap();
 
         cairo_runner
-            .run_until_pc(address, &mut vm, &mut hint_processor)
+            .run_until_pc(
+                address,
+                &mut RunResources::default(),
+                &mut vm,
+                &mut hint_processor,
+            )
             .unwrap();
 
         assert_eq!(builtin.get_allocated_memory_units(&vm), Ok(16));
@@ -455,7 +579,7 @@ mod tests {
 
         assert_eq!(
             builtin.get_memory_segment_addresses(),
-            ("keccak", (0, None))
+            vec![SegmentAddresses::new("keccak", 0, None)]
         );
     }
 
@@ -466,7 +590,7 @@ mod tests {
 
         assert_eq!(
             builtin.get_memory_accesses(&vm),
-            Err(MemoryError::MissingSegmentUsedSizes),
+            Err(MemoryError::MissingSegmentUsedSizes(0)),
         );
     }
 
@@ -504,7 +628,7 @@ mod tests {
 
         assert_eq!(
             builtin.get_used_cells(&vm),
-            Err(MemoryError::MissingSegmentUsedSizes)
+            Err(MemoryError::MissingSegmentUsedSizes(0))
         );
     }
 
@@ -605,4 +729,60 @@ mod tests {
         let result = builtin.deduce_memory_cell(&Relocatable::from((0, 2)), &memory);
         assert_eq!(result, Ok(None));
     }
+
+    #[test]
+    fn validation_rule_rejects_input_cell_at_bound() {
+        let mut builtin = KeccakBuiltinRunner::new(&KeccakInstanceDef::default(), true);
+        let mut segments = MemorySegmentManager::new();
+        let mut memory = Memory::new();
+        builtin
+            .initialize_segments(&mut segments, &mut memory)
+            .unwrap();
+        builtin.add_validation_rule(&mut memory).unwrap();
+
+        let bound = Felt::one() << 200_u32;
+        assert_eq!(
+            memory.insert(&Relocatable::from((0, 1)), &(&bound - 1_u32)),
+            Ok(())
+        );
+        // The input cells of the second instance are checked as well
+        assert_eq!(
+            memory.insert(&Relocatable::from((0, 17)), &bound),
+            Err(MemoryError::KeccakInputOutOfBounds {
+                addr: Relocatable::from((0, 17)),
+                value: bound.clone(),
+                bound,
+            })
+        );
+    }
+
+    #[test]
+    fn validation_rule_exempts_output_cells() {
+        let mut builtin = KeccakBuiltinRunner::new(&KeccakInstanceDef::default(), true);
+        let mut segments = MemorySegmentManager::new();
+        let mut memory = Memory::new();
+        builtin
+            .initialize_segments(&mut segments, &mut memory)
+            .unwrap();
+        builtin.add_validation_rule(&mut memory).unwrap();
+
+        for (offset, value) in [43, 199, 0, 0, 0, 0, 0, 1].into_iter().enumerate() {
+            memory
+                .insert(&Relocatable::from((0, offset)), &Felt::new(value))
+                .unwrap();
+        }
+        // The deduced output passes the rule
+        let output_addr = Relocatable::from((0, 8));
+        let output = builtin
+            .deduce_memory_cell(&output_addr, &memory)
+            .unwrap()
+            .unwrap();
+        assert_eq!(memory.insert(&output_addr, &output), Ok(()));
+        // and no output cell is checked against the bound of the input cells
+        assert_eq!(
+            memory.insert(&Relocatable::from((0, 15)), &(Felt::one() << 200_u32)),
+            Ok(())
+        );
+        assert_eq!(memory.validate_existing_memory(), Ok(()));
+    }
 }
@@ -1,16 +1,23 @@
+use crate::math_utils::safe_div_usize;
 use crate::types::relocatable::{MaybeRelocatable, Relocatable};
 use crate::vm::errors::memory_errors::{self, MemoryError};
 use crate::vm::errors::runner_errors::RunnerError;
 use crate::vm::errors::vm_errors::VirtualMachineError;
+use crate::vm::runners::air_private_input::PrivateInput;
 use crate::vm::vm_core::VirtualMachine;
 use crate::vm::vm_memory::memory::Memory;
 use crate::vm::vm_memory::memory_segments::MemorySegmentManager;
+use felt::Felt;
+#[cfg(feature = "snapshot")]
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 
 mod bitwise;
 mod ec_op;
 mod hash;
 mod keccak;
 mod output;
+mod poseidon;
 mod range_check;
 mod signature;
 
@@ -18,11 +25,87 @@ pub use self::keccak::KeccakBuiltinRunner;
 pub use bitwise::BitwiseBuiltinRunner;
 pub use ec_op::EcOpBuiltinRunner;
 pub use hash::HashBuiltinRunner;
-use num_integer::div_floor;
-pub use output::OutputBuiltinRunner;
+use num_integer::{div_ceil, div_floor};
+pub use output::{
+    OutputBuiltinAdditionalData, OutputBuiltinRunner, OutputBuiltinState, PublicMemoryPage,
+};
+pub use poseidon::PoseidonBuiltinRunner;
 pub use range_check::RangeCheckBuiltinRunner;
 pub use signature::SignatureBuiltinRunner;
 
+/// The cells of a builtin instance, along with the values the builtin deduces for them.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BuiltinInstance {
+    pub index: usize,
+    /// The cells of the instance, None for the ones missing from memory
+    pub cells: Vec<Option<MaybeRelocatable>>,
+    /// The value deduced by the builtin for each cell. None for the input cells, and for the
+    /// output cells which can't be deduced from the inputs
+    pub deductions: Vec<Option<MaybeRelocatable>>,
+}
+
+impl BuiltinInstance {
+    /// Returns true if all the cells of the instance are in memory.
+    pub fn is_complete(&self) -> bool {
+        self.cells.iter().all(Option::is_some)
+    }
+}
+
+/// A memory segment owned by a builtin, as reported in the public input.
+/// A builtin may own several segments, each with its own name.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SegmentAddresses {
+    pub name: String,
+    pub base: isize,
+    pub stop_ptr: Option<usize>,
+}
+
+impl SegmentAddresses {
+    pub fn new(name: &str, base: isize, stop_ptr: Option<usize>) -> Self {
+        SegmentAddresses {
+            name: name.to_string(),
+            base,
+            stop_ptr,
+        }
+    }
+}
+
+/// The layout of the instances of a builtin, for tools which need it without matching on
+/// each kind of builtin.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BuiltinInstanceInfo {
+    pub name: &'static str,
+    pub cells_per_instance: u32,
+    pub n_input_cells: u32,
+    /// None for the output builtin, which has no ratio
+    pub ratio: Option<u32>,
+}
+
+/// The data a builtin gathers during the run which isn't stored in memory, exported along with
+/// the builtin's segment in a Cairo PIE or the prover's private input.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "snapshot", derive(Serialize, Deserialize))]
+pub enum BuiltinRunnerAdditionalData {
+    /// The output cells of the hash builtin which were already deduced
+    Hash(Vec<Relocatable>),
+    Output(OutputBuiltinAdditionalData),
+    /// The (r, s) pair of each signature, by the address of its public key
+    Signature(HashMap<Relocatable, (Felt, Felt)>),
+    /// The builtin has no additional data
+    Empty,
+}
+
+/// The state of a builtin in the middle of a run, taken with `BuiltinRunner::snapshot`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "snapshot", derive(Serialize, Deserialize))]
+pub struct BuiltinRunnerSnapshot {
+    pub name: String,
+    pub initialized: bool,
+    pub base: isize,
+    pub stop_ptr: Option<usize>,
+    pub additional_data: BuiltinRunnerAdditionalData,
+}
+
 /* NB: this enum is no accident: we may need (and cairo-rs-py *does* need)
  * structs containing this to be `Send`. The only two ways to achieve that
  * are either storing a `dyn Trait` inside an `Arc<Mutex<&dyn Trait>>` or
@@ -40,60 +123,56 @@ pub enum BuiltinRunner {
     RangeCheck(RangeCheckBuiltinRunner),
     Keccak(KeccakBuiltinRunner),
     Signature(SignatureBuiltinRunner),
+    Poseidon(PoseidonBuiltinRunner),
+}
+
+// Matches on every variant of the BuiltinRunner, binding the inner runner to `$runner` and
+// evaluating `$body` with it, so that adding a builtin only requires adding a line here.
+// The output builtin, which has no instances, may be given its own arm.
+macro_rules! impl_builtin_dispatch {
+    ($self:expr, $runner:ident => $body:expr) => {
+        impl_builtin_dispatch!($self, $runner => $body, Output($runner) => $body)
+    };
+    ($self:expr, $runner:ident => $body:expr, Output($output:pat) => $output_body:expr) => {
+        match $self {
+            BuiltinRunner::Bitwise($runner) => $body,
+            BuiltinRunner::EcOp($runner) => $body,
+            BuiltinRunner::Hash($runner) => $body,
+            BuiltinRunner::Output($output) => $output_body,
+            BuiltinRunner::RangeCheck($runner) => $body,
+            BuiltinRunner::Keccak($runner) => $body,
+            BuiltinRunner::Signature($runner) => $body,
+            BuiltinRunner::Poseidon($runner) => $body,
+        }
+    };
 }
 
 impl BuiltinRunner {
     ///Creates the necessary segments for the builtin in the MemorySegmentManager and stores the first address on the builtin's base
+    ///Fails if the builtin was already initialized, as its previous segment may already be in use
     pub fn initialize_segments(
         &mut self,
         segments: &mut MemorySegmentManager,
         memory: &mut Memory,
-    ) {
-        match *self {
-            BuiltinRunner::Bitwise(ref mut bitwise) => {
-                bitwise.initialize_segments(segments, memory)
-            }
-            BuiltinRunner::EcOp(ref mut ec) => ec.initialize_segments(segments, memory),
-            BuiltinRunner::Hash(ref mut hash) => hash.initialize_segments(segments, memory),
-            BuiltinRunner::Output(ref mut output) => output.initialize_segments(segments, memory),
-            BuiltinRunner::RangeCheck(ref mut range_check) => {
-                range_check.initialize_segments(segments, memory)
-            }
-            BuiltinRunner::Keccak(ref mut keccak) => keccak.initialize_segments(segments, memory),
-            BuiltinRunner::Signature(ref mut signature) => {
-                signature.initialize_segments(segments, memory)
-            }
-        }
+    ) -> Result<(), RunnerError> {
+        impl_builtin_dispatch!(self, runner => runner.initialize_segments(segments, memory))
     }
 
     pub fn initial_stack(&self) -> Vec<MaybeRelocatable> {
-        match *self {
-            BuiltinRunner::Bitwise(ref bitwise) => bitwise.initial_stack(),
-            BuiltinRunner::EcOp(ref ec) => ec.initial_stack(),
-            BuiltinRunner::Hash(ref hash) => hash.initial_stack(),
-            BuiltinRunner::Output(ref output) => output.initial_stack(),
-            BuiltinRunner::RangeCheck(ref range_check) => range_check.initial_stack(),
-            BuiltinRunner::Keccak(ref keccak) => keccak.initial_stack(),
-            BuiltinRunner::Signature(ref signature) => signature.initial_stack(),
-        }
+        impl_builtin_dispatch!(self, runner => runner.initial_stack())
     }
 
+    ///Reads the stop pointer of the builtin from the cell before `stack_pointer`, checks that it
+    ///points right after the cells used by the builtin and stores it. Returns the pointer to the
+    ///stop pointer, where the builtin before it in the program has its own.
+    ///A builtin which isn't included in the program has nothing on the stack, so its stop
+    ///pointer is its base and `stack_pointer` is returned as is.
     pub fn final_stack(
-        &self,
+        &mut self,
         vm: &VirtualMachine,
         stack_pointer: Relocatable,
-    ) -> Result<(Relocatable, usize), RunnerError> {
-        match *self {
-            BuiltinRunner::Bitwise(ref bitwise) => bitwise.final_stack(vm, stack_pointer),
-            BuiltinRunner::EcOp(ref ec) => ec.final_stack(vm, stack_pointer),
-            BuiltinRunner::Hash(ref hash) => hash.final_stack(vm, stack_pointer),
-            BuiltinRunner::Output(ref output) => output.final_stack(vm, stack_pointer),
-            BuiltinRunner::RangeCheck(ref range_check) => {
-                range_check.final_stack(vm, stack_pointer)
-            }
-            BuiltinRunner::Keccak(ref keccak) => keccak.final_stack(vm, stack_pointer),
-            BuiltinRunner::Signature(ref signature) => signature.final_stack(vm, stack_pointer),
-        }
+    ) -> Result<Relocatable, RunnerError> {
+        impl_builtin_dispatch!(self, runner => runner.final_stack(vm, stack_pointer))
     }
 
     ///Returns the builtin's allocated memory units
@@ -101,53 +180,47 @@ impl BuiltinRunner {
         &self,
         vm: &VirtualMachine,
     ) -> Result<usize, memory_errors::MemoryError> {
-        match *self {
-            BuiltinRunner::Bitwise(ref bitwise) => bitwise.get_allocated_memory_units(vm),
-            BuiltinRunner::EcOp(ref ec) => ec.get_allocated_memory_units(vm),
-            BuiltinRunner::Hash(ref hash) => hash.get_allocated_memory_units(vm),
-            BuiltinRunner::Output(ref output) => output.get_allocated_memory_units(vm),
-            BuiltinRunner::RangeCheck(ref range_check) => {
-                range_check.get_allocated_memory_units(vm)
-            }
-            BuiltinRunner::Keccak(ref keccak) => keccak.get_allocated_memory_units(vm),
-            BuiltinRunner::Signature(ref signature) => signature.get_allocated_memory_units(vm),
-        }
+        impl_builtin_dispatch!(self, runner => runner.get_allocated_memory_units(vm))
     }
 
     ///Returns the builtin's base
+    ///Before `initialize_segments` is called the base is 0, which is also the index of the
+    ///program segment: use `is_initialized` to tell both cases apart
     pub fn base(&self) -> isize {
-        match *self {
-            BuiltinRunner::Bitwise(ref bitwise) => bitwise.base(),
-            BuiltinRunner::EcOp(ref ec) => ec.base(),
-            BuiltinRunner::Hash(ref hash) => hash.base(),
-            BuiltinRunner::Output(ref output) => output.base(),
-            BuiltinRunner::RangeCheck(ref range_check) => range_check.base(),
-            BuiltinRunner::Keccak(ref keccak) => keccak.base(),
-            BuiltinRunner::Signature(ref signature) => signature.base(),
-        }
+        impl_builtin_dispatch!(self, runner => runner.base())
+    }
+
+    ///Returns true once `initialize_segments` created the builtin's segment
+    pub fn is_initialized(&self) -> bool {
+        impl_builtin_dispatch!(self, runner => runner.is_initialized())
+    }
+
+    ///Returns whether the program uses the builtin. The builtins of the layout which the program
+    ///doesn't use are only instantiated in proof mode, and have no pointer on the stack.
+    pub fn included(&self) -> bool {
+        impl_builtin_dispatch!(self, runner => runner.included)
     }
 
     pub fn ratio(&self) -> Option<u32> {
-        match self {
-            BuiltinRunner::Bitwise(bitwise) => Some(bitwise.ratio()),
-            BuiltinRunner::EcOp(ec) => Some(ec.ratio()),
-            BuiltinRunner::Hash(hash) => Some(hash.ratio()),
-            BuiltinRunner::Output(_) => None,
-            BuiltinRunner::RangeCheck(range_check) => Some(range_check.ratio()),
-            BuiltinRunner::Keccak(keccak) => Some(keccak.ratio()),
-            BuiltinRunner::Signature(ref signature) => Some(signature.ratio()),
-        }
+        impl_builtin_dispatch!(self, runner => Some(runner.ratio()), Output(_) => None)
+    }
+
+    /// Returns the denominator of the ratio, the builtin having `ratio_den` instances every
+    /// `ratio` steps.
+    pub fn ratio_den(&self) -> Option<u32> {
+        impl_builtin_dispatch!(self, runner => Some(runner.ratio_den()), Output(_) => None)
     }
 
     pub fn add_validation_rule(&self, memory: &mut Memory) -> Result<(), RunnerError> {
-        match *self {
-            BuiltinRunner::Bitwise(ref bitwise) => bitwise.add_validation_rule(memory),
-            BuiltinRunner::EcOp(ref ec) => ec.add_validation_rule(memory),
-            BuiltinRunner::Hash(ref hash) => hash.add_validation_rule(memory),
-            BuiltinRunner::Output(ref output) => output.add_validation_rule(memory),
-            BuiltinRunner::RangeCheck(ref range_check) => range_check.add_validation_rule(memory),
-            BuiltinRunner::Keccak(ref keccak) => keccak.add_validation_rule(memory),
-            BuiltinRunner::Signature(ref signature) => signature.add_validation_rule(memory),
+        impl_builtin_dispatch!(self, runner => runner.add_validation_rule(memory))
+    }
+
+    /// Moves a builtin located in a temporary segment to the segment it was relocated to, along
+    /// with the addresses cached by its deductions.
+    pub(crate) fn relocate_base(&mut self, new_base: isize) {
+        match self {
+            BuiltinRunner::Keccak(keccak) => keccak.relocate_base(new_base),
+            _ => impl_builtin_dispatch!(self, runner => runner.base = new_base),
         }
     }
 
@@ -155,74 +228,68 @@ impl BuiltinRunner {
         &self,
         address: &Relocatable,
         memory: &Memory,
+    ) -> Result<Option<MaybeRelocatable>, RunnerError> {
+        impl_builtin_dispatch!(self, runner => runner.deduce_memory_cell(address, memory))
+    }
+
+    // Like deduce_memory_cell, but also deduces the cells already deduced once, which the
+    // pedersen builtin otherwise skips as their values are expected to be in memory
+    pub(crate) fn deduce_memory_cell_uncached(
+        &self,
+        address: &Relocatable,
+        memory: &Memory,
     ) -> Result<Option<MaybeRelocatable>, RunnerError> {
         match *self {
-            BuiltinRunner::Bitwise(ref bitwise) => bitwise.deduce_memory_cell(address, memory),
-            BuiltinRunner::EcOp(ref ec) => ec.deduce_memory_cell(address, memory),
-            BuiltinRunner::Hash(ref hash) => hash.deduce_memory_cell(address, memory),
-            BuiltinRunner::Output(ref output) => output.deduce_memory_cell(address, memory),
-            BuiltinRunner::RangeCheck(ref range_check) => {
-                range_check.deduce_memory_cell(address, memory)
-            }
-            BuiltinRunner::Keccak(ref keccak) => keccak.deduce_memory_cell(address, memory),
-            BuiltinRunner::Signature(ref signature) => {
-                signature.deduce_memory_cell(address, memory)
-            }
+            BuiltinRunner::Hash(ref hash) => hash.hash_cell(address, memory),
+            _ => self.deduce_memory_cell(address, memory),
         }
     }
 
+    /// Returns the addresses of the written cells of the builtin segment, which must have its
+    /// effective size computed.
     pub fn get_memory_accesses(
         &self,
         vm: &VirtualMachine,
     ) -> Result<Vec<Relocatable>, MemoryError> {
         let base = self.base();
+        let index = base
+            .try_into()
+            .map_err(|_| MemoryError::AddressInTemporarySegment(base))?;
         let segment_size = vm
             .segments
-            .get_segment_size(
-                base.try_into()
-                    .map_err(|_| MemoryError::AddressInTemporarySegment(base))?,
-            )
-            .ok_or(MemoryError::MissingSegmentUsedSizes)?;
-
-        Ok((0..segment_size).map(|i| (base, i).into()).collect())
+            .get_segment_size(index)
+            .ok_or(MemoryError::MissingSegmentUsedSizes(index))?;
+
+        // Only the written cells were accessed, the holes left by unfinished instances weren't
+        let segment = vm.memory.data.get(index);
+        Ok((0..segment_size)
+            .filter(|offset| {
+                segment
+                    .and_then(|segment| segment.get(*offset))
+                    .map_or(false, Option::is_some)
+            })
+            .map(|offset| (base, offset).into())
+            .collect())
     }
 
-    pub fn get_memory_segment_addresses(&self) -> (&'static str, (isize, Option<usize>)) {
-        match self {
-            BuiltinRunner::Bitwise(ref bitwise) => bitwise.get_memory_segment_addresses(),
-            BuiltinRunner::EcOp(ref ec) => ec.get_memory_segment_addresses(),
-            BuiltinRunner::Hash(ref hash) => hash.get_memory_segment_addresses(),
-            BuiltinRunner::Output(ref output) => output.get_memory_segment_addresses(),
-            BuiltinRunner::RangeCheck(ref range_check) => {
-                range_check.get_memory_segment_addresses()
-            }
-            BuiltinRunner::Keccak(ref keccak) => keccak.get_memory_segment_addresses(),
-            BuiltinRunner::Signature(ref signature) => signature.get_memory_segment_addresses(),
-        }
+    /// Returns the segments owned by the builtin, starting with the one at its base.
+    pub fn get_memory_segment_addresses(&self) -> Vec<SegmentAddresses> {
+        impl_builtin_dispatch!(self, runner => runner.get_memory_segment_addresses())
     }
 
     pub fn get_used_cells(&self, vm: &VirtualMachine) -> Result<usize, MemoryError> {
-        match self {
-            BuiltinRunner::Bitwise(ref bitwise) => bitwise.get_used_cells(vm),
-            BuiltinRunner::EcOp(ref ec) => ec.get_used_cells(vm),
-            BuiltinRunner::Hash(ref hash) => hash.get_used_cells(vm),
-            BuiltinRunner::Output(ref output) => output.get_used_cells(vm),
-            BuiltinRunner::RangeCheck(ref range_check) => range_check.get_used_cells(vm),
-            BuiltinRunner::Keccak(ref keccak) => keccak.get_used_cells(vm),
-            BuiltinRunner::Signature(ref signature) => signature.get_used_cells(vm),
-        }
+        impl_builtin_dispatch!(self, runner => runner.get_used_cells(vm))
+    }
+
+    /// Returns the number of cells used by the builtin so far. Unlike `get_used_cells`, this
+    /// doesn't need the segment sizes to be computed, so it can be called during the run.
+    pub fn get_used_cells_mid_run(&self, vm: &VirtualMachine) -> Result<usize, MemoryError> {
+        impl_builtin_dispatch!(self, runner => runner.get_used_cells_mid_run(vm))
     }
 
     pub fn get_used_instances(&self, vm: &VirtualMachine) -> Result<usize, MemoryError> {
-        match self {
-            BuiltinRunner::Bitwise(ref bitwise) => bitwise.get_used_instances(vm),
-            BuiltinRunner::EcOp(ref ec) => ec.get_used_instances(vm),
-            BuiltinRunner::Hash(ref hash) => hash.get_used_instances(vm),
-            BuiltinRunner::Output(ref output) => output.get_used_instances(vm),
-            BuiltinRunner::RangeCheck(ref range_check) => range_check.get_used_instances(vm),
-            BuiltinRunner::Keccak(ref keccak) => keccak.get_used_instances(vm),
-            BuiltinRunner::Signature(ref signature) => signature.get_used_instances(vm),
-        }
+        let used_cells = self.get_used_cells_mid_run(vm)?;
+        Ok(div_ceil(used_cells, self.cells_per_instance() as usize))
     }
 
     pub fn get_range_check_usage(&self, memory: &Memory) -> Option<(usize, usize)> {
@@ -258,49 +325,35 @@ impl BuiltinRunner {
     }
 
     pub fn run_security_checks(&self, vm: &mut VirtualMachine) -> Result<(), VirtualMachineError> {
-        if let BuiltinRunner::Output(_) = self {
+        // The program has no pointer to a builtin it doesn't use, so its segment is empty
+        if !self.included() || matches!(self, BuiltinRunner::Output(_)) {
             return Ok(());
         }
-
-        let (cells_per_instance, n_input_cells) = match self {
-            BuiltinRunner::Bitwise(x) => (x.cells_per_instance, x.n_input_cells),
-            BuiltinRunner::EcOp(x) => (x.cells_per_instance, x.n_input_cells),
-            BuiltinRunner::Hash(x) => (x.cells_per_instance, x.n_input_cells),
-            BuiltinRunner::RangeCheck(x) => (x.cells_per_instance, x.n_input_cells),
-            BuiltinRunner::Output(_) => unreachable!(),
-            BuiltinRunner::Keccak(x) => (x.cells_per_instance, x.n_input_cells),
-            BuiltinRunner::Signature(ref x) => (x.cells_per_instance, x.n_input_cells),
+        let cells_per_instance = self.cells_per_instance();
+        let n_input_cells = self.n_input_cells();
+        // The errors have always referred to the pedersen builtin as "hash"
+        let name = match self {
+            BuiltinRunner::Hash(_) => "hash",
+            _ => self.name(),
         };
 
         let base = self.base();
+        let index: usize = base
+            .try_into()
+            .map_err(|_| MemoryError::AddressInTemporarySegment(base))?;
         let offsets = vm
             .memory
             .data
-            .get(
-                TryInto::<usize>::try_into(base)
-                    .map_err(|_| MemoryError::AddressInTemporarySegment(base))?,
-            )
-            .ok_or(MemoryError::NumOutOfBounds)?
+            .get(index)
+            .ok_or(MemoryError::UnallocatedSegment(index, vm.memory.data.len()))?
             .iter()
             .enumerate()
-            .filter_map(|(offset, value)| match value {
-                Some(MaybeRelocatable::RelocatableValue(_)) => Some(offset),
-                _ => None,
-            })
+            .filter_map(|(offset, value)| value.as_ref().map(|_| offset))
             .collect::<Vec<_>>();
 
         let n = div_floor(offsets.len(), cells_per_instance as usize);
         if n > div_floor(offsets.len(), n_input_cells as usize) {
-            return Err(MemoryError::MissingMemoryCells(match self {
-                BuiltinRunner::Bitwise(_) => "bitwise",
-                BuiltinRunner::EcOp(_) => "ec_op",
-                BuiltinRunner::Hash(_) => "hash",
-                BuiltinRunner::Output(_) => "output",
-                BuiltinRunner::RangeCheck(_) => "range_check",
-                BuiltinRunner::Keccak(_) => "keccak",
-                BuiltinRunner::Signature(_) => "ecdsa",
-            })
-            .into());
+            return Err(MemoryError::MissingMemoryCells(name).into());
         }
 
         // Since both offsets and this iterator are ordered, a simple pointer is
@@ -311,6 +364,8 @@ impl BuiltinRunner {
             let offset = cells_per_instance as usize * i;
             for j in 0..n_input_cells as usize {
                 let offset = offset + j;
+                // Skip the output cells of the previous instances
+                while offsets_iter.next_if(|present| *present < offset).is_some() {}
                 match offsets_iter.next_if_eq(&offset) {
                     Some(_) => {}
                     None => {
@@ -320,20 +375,9 @@ impl BuiltinRunner {
             }
         }
         if !missing_offsets.is_empty() {
-            return Err(MemoryError::MissingMemoryCellsWithOffsets(
-                match self {
-                    BuiltinRunner::Bitwise(_) => "bitwise",
-                    BuiltinRunner::EcOp(_) => "ec_op",
-                    BuiltinRunner::Hash(_) => "hash",
-                    BuiltinRunner::Output(_) => "output",
-                    BuiltinRunner::RangeCheck(_) => "range_check",
-                    BuiltinRunner::Keccak(_) => "keccak",
-                    BuiltinRunner::Signature(_) => "ecdsa",
-                },
-                missing_offsets,
-            )
-            .into());
+            return Err(MemoryError::MissingMemoryCellsWithOffsets(name, missing_offsets).into());
         }
+        self.run_additional_security_checks(vm)?;
 
         let mut should_validate_auto_deductions = false;
         for i in 0..n {
@@ -345,44 +389,342 @@ impl BuiltinRunner {
             }
         }
         if should_validate_auto_deductions {
-            vm.verify_auto_deductions()?;
+            vm.verify_builtin_auto_deductions(self.name(), self)?;
         }
 
         Ok(())
     }
 
+    /// Runs the security checks specific to the builtin, once the input cells of its instances
+    /// are known to be present.
+    fn run_additional_security_checks(&self, vm: &VirtualMachine) -> Result<(), RunnerError> {
+        match self {
+            BuiltinRunner::EcOp(ec_op) => ec_op.run_additional_security_checks(vm),
+            _ => Ok(()),
+        }
+    }
+
     pub fn get_used_cells_and_allocated_size(
         &self,
         vm: &VirtualMachine,
     ) -> Result<(usize, usize), MemoryError> {
+        impl_builtin_dispatch!(self, runner => runner.get_used_cells_and_allocated_size(vm))
+    }
+
+    /// Returns the name of the builtin, as used in the program's builtins and the layouts.
+    pub fn name(&self) -> &'static str {
         match self {
-            BuiltinRunner::Bitwise(ref bitwise) => bitwise.get_used_cells_and_allocated_size(vm),
-            BuiltinRunner::EcOp(ref ec) => ec.get_used_cells_and_allocated_size(vm),
-            BuiltinRunner::Hash(ref hash) => hash.get_used_cells_and_allocated_size(vm),
-            BuiltinRunner::Output(ref output) => output.get_used_cells_and_allocated_size(vm),
-            BuiltinRunner::RangeCheck(ref range_check) => {
-                range_check.get_used_cells_and_allocated_size(vm)
+            BuiltinRunner::Bitwise(_) => BitwiseBuiltinRunner::NAME,
+            BuiltinRunner::EcOp(_) => EcOpBuiltinRunner::NAME,
+            BuiltinRunner::Hash(_) => HashBuiltinRunner::NAME,
+            BuiltinRunner::Output(_) => OutputBuiltinRunner::NAME,
+            BuiltinRunner::RangeCheck(range_check) => range_check.name,
+            BuiltinRunner::Keccak(_) => KeccakBuiltinRunner::NAME,
+            BuiltinRunner::Signature(_) => SignatureBuiltinRunner::NAME,
+            BuiltinRunner::Poseidon(_) => PoseidonBuiltinRunner::NAME,
+        }
+    }
+
+    /// Returns the number of cells of each instance of the builtin. Each cell of the output
+    /// builtin is an instance of its own.
+    pub fn cells_per_instance(&self) -> u32 {
+        impl_builtin_dispatch!(self, runner => runner.cells_per_instance, Output(_) => 1)
+    }
+
+    /// Returns the number of cells of each instance which are written by the program, the
+    /// remaining ones being deduced by the builtin.
+    pub fn n_input_cells(&self) -> u32 {
+        impl_builtin_dispatch!(self, runner => runner.n_input_cells, Output(_) => 1)
+    }
+
+    /// Returns the name of the builtin along with the layout of its instances.
+    pub fn instance_info(&self) -> BuiltinInstanceInfo {
+        BuiltinInstanceInfo {
+            name: self.name(),
+            cells_per_instance: self.cells_per_instance(),
+            n_input_cells: self.n_input_cells(),
+            ratio: self.ratio(),
+        }
+    }
+
+    /// Sets the stop pointer of the builtin. The pointer must lie in the
+    /// builtin's own segment.
+    pub fn set_stop_ptr(&mut self, stop_ptr: Relocatable) -> Result<(), RunnerError> {
+        if stop_ptr.segment_index != self.base() {
+            return Err(RunnerError::StopPointerSegmentMismatch(
+                self.name(),
+                stop_ptr,
+                self.base(),
+            ));
+        }
+        let stop_ptr = Some(stop_ptr.offset);
+        impl_builtin_dispatch!(self, runner => runner.stop_ptr = stop_ptr);
+        Ok(())
+    }
+
+    /// Returns a snapshot of the builtin's state. Only supported by the output builtin.
+    pub fn get_state(&self) -> Result<OutputBuiltinState, RunnerError> {
+        match self {
+            BuiltinRunner::Output(ref output) => Ok(output.get_state()),
+            _ => Err(RunnerError::StateNotSupported(self.name())),
+        }
+    }
+
+    /// Restores a state previously taken with `get_state`. Only supported by the output builtin.
+    pub fn set_state(&mut self, state: OutputBuiltinState) -> Result<(), RunnerError> {
+        match self {
+            BuiltinRunner::Output(ref mut output) => {
+                output.set_state(state);
+                Ok(())
             }
-            BuiltinRunner::Keccak(ref keccak) => keccak.get_used_cells_and_allocated_size(vm),
-            BuiltinRunner::Signature(ref signature) => {
-                signature.get_used_cells_and_allocated_size(vm)
+            _ => Err(RunnerError::StateNotSupported(self.name())),
+        }
+    }
+
+    /// Returns a snapshot of the builtin's state: its base, its stop pointer and the data it
+    /// gathered so far. Along with a copy of the memory, it allows resuming the run from this
+    /// point, e.g. to revert a transaction. Unlike a clone of the builtin, which shares its
+    /// signatures with the original ecdsa builtin, the snapshot is independent of the builtin.
+    pub fn snapshot(&self) -> BuiltinRunnerSnapshot {
+        BuiltinRunnerSnapshot {
+            name: self.name().to_string(),
+            initialized: self.is_initialized(),
+            base: self.base(),
+            stop_ptr: impl_builtin_dispatch!(self, runner => runner.stop_ptr),
+            additional_data: self.get_additional_data(),
+        }
+    }
+
+    /// Restores a snapshot taken from a builtin of the same kind, discarding the data gathered
+    /// since it was taken. The cached outputs of the ec_op and poseidon builtins are dropped,
+    /// as the memory they were computed from may not be the restored one.
+    pub fn restore(&mut self, snapshot: &BuiltinRunnerSnapshot) -> Result<(), RunnerError> {
+        if snapshot.name != self.name() {
+            return Err(RunnerError::InvalidSnapshot(self.name()));
+        }
+        match self {
+            BuiltinRunner::EcOp(ec_op) => ec_op.clear_cache(),
+            BuiltinRunner::Hash(hash) => hash.clear_verified_addresses(),
+            BuiltinRunner::Output(output) => output.clear_additional_data(),
+            BuiltinRunner::Poseidon(poseidon) => poseidon.clear_cache(),
+            BuiltinRunner::Signature(signature) => signature.clear_signatures(),
+            BuiltinRunner::Bitwise(_) | BuiltinRunner::RangeCheck(_) | BuiltinRunner::Keccak(_) => {
             }
         }
+        impl_builtin_dispatch!(self, runner => {
+            runner.initialized = snapshot.initialized;
+            runner.base = snapshot.base;
+            runner.stop_ptr = snapshot.stop_ptr;
+        });
+        self.extend_additional_data(&snapshot.additional_data)
     }
 
-    pub fn set_stop_ptr(&mut self, stop_ptr: usize) {
+    /// Returns the data gathered by the builtin during the run which isn't stored in memory.
+    pub fn get_additional_data(&self) -> BuiltinRunnerAdditionalData {
         match self {
-            BuiltinRunner::Bitwise(ref mut bitwise) => bitwise.stop_ptr = Some(stop_ptr),
-            BuiltinRunner::EcOp(ref mut ec) => ec.stop_ptr = Some(stop_ptr),
-            BuiltinRunner::Hash(ref mut hash) => hash.stop_ptr = Some(stop_ptr),
-            BuiltinRunner::Output(ref mut output) => output.stop_ptr = Some(stop_ptr),
-            BuiltinRunner::RangeCheck(ref mut range_check) => range_check.stop_ptr = Some(stop_ptr),
-            BuiltinRunner::Keccak(ref mut keccak) => keccak.stop_ptr = Some(stop_ptr),
-            BuiltinRunner::Signature(ref mut signature) => signature.stop_ptr = Some(stop_ptr),
+            BuiltinRunner::Hash(ref hash) => {
+                BuiltinRunnerAdditionalData::Hash(hash.get_additional_data())
+            }
+            BuiltinRunner::Output(ref output) => {
+                BuiltinRunnerAdditionalData::Output(output.get_additional_data())
+            }
+            BuiltinRunner::Signature(ref signature) => {
+                BuiltinRunnerAdditionalData::Signature(signature.get_additional_data())
+            }
+            _ => BuiltinRunnerAdditionalData::Empty,
+        }
+    }
+
+    /// Adds the data returned by `get_additional_data` to the builtin, as when loading it from
+    /// a Cairo PIE. Fails if the data belongs to another kind of builtin.
+    pub fn extend_additional_data(
+        &mut self,
+        data: &BuiltinRunnerAdditionalData,
+    ) -> Result<(), RunnerError> {
+        let name = self.name();
+        match (self, data) {
+            (BuiltinRunner::Hash(ref mut hash), BuiltinRunnerAdditionalData::Hash(addresses)) => {
+                hash.extend_additional_data(addresses)?;
+            }
+            (BuiltinRunner::Output(ref mut output), BuiltinRunnerAdditionalData::Output(data)) => {
+                output.extend_additional_data(data);
+            }
+            (
+                BuiltinRunner::Signature(ref mut signature),
+                BuiltinRunnerAdditionalData::Signature(signatures),
+            ) => signature.extend_additional_data(signatures)?,
+            (
+                BuiltinRunner::Hash(_) | BuiltinRunner::Output(_) | BuiltinRunner::Signature(_),
+                _,
+            )
+            | (_, BuiltinRunnerAdditionalData::Hash(_))
+            | (_, BuiltinRunnerAdditionalData::Output(_))
+            | (_, BuiltinRunnerAdditionalData::Signature(_)) => {
+                return Err(RunnerError::InvalidAdditionalData(name))
+            }
+            (_, BuiltinRunnerAdditionalData::Empty) => {}
+        }
+        Ok(())
+    }
+
+    /// Returns the private input of the AIR for each instance of the builtin, skipping the
+    /// instances whose input cells aren't all set to integers. The output builtin has no
+    /// instances, so its cells are only part of the public input.
+    pub fn air_private_input(&self, memory: &Memory) -> Vec<PrivateInput> {
+        impl_builtin_dispatch!(self, runner => runner.air_private_input(memory))
+    }
+
+    /// Returns the instances of the builtin, as found in its segment so far. The last instance
+    /// may be incomplete. Each cell of the output builtin is an instance of its own.
+    pub fn get_instances(&self, memory: &Memory) -> Vec<BuiltinInstance> {
+        let cells_per_instance = self.cells_per_instance() as usize;
+        let base = self.base();
+        let segment = match usize::try_from(base) {
+            Ok(index) => memory.data.get(index),
+            Err(_) => memory.temp_data.get(-(base + 1) as usize),
+        };
+        let segment = match segment {
+            Some(segment) => segment,
+            None => return Vec::new(),
+        };
+
+        segment
+            .chunks(cells_per_instance)
+            .enumerate()
+            .map(|(index, cells)| {
+                let mut cells = cells.to_vec();
+                cells.resize(cells_per_instance, None);
+                let deductions = (0..cells_per_instance)
+                    .map(|offset| {
+                        let address =
+                            Relocatable::from((base, index * cells_per_instance + offset));
+                        // A deduction failing on invalid inputs leaves the cell undeduced
+                        self.deduce_memory_cell_uncached(&address, memory)
+                            .ok()
+                            .flatten()
+                    })
+                    .collect();
+                BuiltinInstance {
+                    index,
+                    cells,
+                    deductions,
+                }
+            })
+            .collect()
+    }
+
+    /// Describes an instance of the builtin on a single line, e.g. `0: hash(x, y) = z` for the
+    /// pedersen builtin. See `CairoRunner::dump_builtin` for the notation of the cells.
+    pub(crate) fn describe_instance(&self, instance: &BuiltinInstance) -> String {
+        let cell = |offset: usize| describe_instance_cell(instance, offset);
+        let description = match self {
+            BuiltinRunner::Hash(_) => format!("hash({}, {}) = {}", cell(0), cell(1), cell(2)),
+            BuiltinRunner::Bitwise(_) => format!(
+                "x = {}, y = {}, x & y = {}, x ^ y = {}, x | y = {}",
+                cell(0),
+                cell(1),
+                cell(2),
+                cell(3),
+                cell(4)
+            ),
+            BuiltinRunner::EcOp(_) => format!(
+                "p = ({}, {}), q = ({}, {}), m = {}, r = ({}, {})",
+                cell(0),
+                cell(1),
+                cell(2),
+                cell(3),
+                cell(4),
+                cell(5),
+                cell(6)
+            ),
+            BuiltinRunner::Poseidon(_) => format!(
+                "poseidon({}, {}, {}) = ({}, {}, {})",
+                cell(0),
+                cell(1),
+                cell(2),
+                cell(3),
+                cell(4),
+                cell(5)
+            ),
+            BuiltinRunner::RangeCheck(range_check) => match &instance.cells[0] {
+                Some(MaybeRelocatable::Int(value)) => {
+                    let limbs = range_check
+                        .limbs(value)
+                        .iter()
+                        .map(Felt::to_string)
+                        .collect::<Vec<_>>();
+                    format!("{} = [{}]", cell(0), limbs.join(", "))
+                }
+                _ => cell(0),
+            },
+            _ => (0..instance.cells.len())
+                .map(cell)
+                .collect::<Vec<_>>()
+                .join(", "),
+        };
+        match instance.is_complete() {
+            true => format!("{}: {}", instance.index, description),
+            false => format!("{}: {} (incomplete)", instance.index, description),
         }
     }
 }
 
+// Written cells are shown as is, and marked with a `*` when they hold the value deduced by the
+// builtin. Missing cells are shown as `_`.
+fn describe_instance_cell(instance: &BuiltinInstance, offset: usize) -> String {
+    match (&instance.cells[offset], &instance.deductions[offset]) {
+        (Some(value), Some(deduced)) if value == deduced => format!("{value}*"),
+        (Some(value), Some(deduced)) => format!("{value} (deduced {deduced})"),
+        (Some(value), None) => value.to_string(),
+        (None, Some(deduced)) => format!("_ (deduced {deduced})"),
+        (None, None) => String::from("_"),
+    }
+}
+
+// Returns the number of instances allocated to a builtin after `current_step` steps, the builtin
+// having `ratio_den` instances every `ratio` steps
+pub(crate) fn get_allocated_instances(
+    name: &'static str,
+    current_step: usize,
+    ratio: u32,
+    ratio_den: u32,
+) -> Result<usize, MemoryError> {
+    safe_div_usize(current_step * ratio_den as usize, ratio as usize)
+        .map_err(|_| MemoryError::ErrorCalculatingMemoryUnits(name))
+}
+
+// Returns the N input cells of each instance of the builtin segment at `base`, along with the
+// index of the instance, skipping the instances whose input cells aren't all set to integers
+pub(crate) fn get_instances_inputs<const N: usize>(
+    memory: &Memory,
+    base: isize,
+    cells_per_instance: u32,
+) -> Vec<(usize, [Felt; N])> {
+    let segment = match usize::try_from(base)
+        .ok()
+        .and_then(|index| memory.data.get(index))
+    {
+        Some(segment) => segment,
+        None => return Vec::new(),
+    };
+
+    segment
+        .chunks(cells_per_instance as usize)
+        .enumerate()
+        .filter_map(|(index, cells)| {
+            let inputs = cells
+                .get(..N)?
+                .iter()
+                .map(|cell| match cell {
+                    Some(MaybeRelocatable::Int(value)) => Some(value.clone()),
+                    _ => None,
+                })
+                .collect::<Option<Vec<_>>>()?;
+            Some((index, inputs.try_into().ok()?))
+        })
+        .collect()
+}
+
 impl From<KeccakBuiltinRunner> for BuiltinRunner {
     fn from(runner: KeccakBuiltinRunner) -> Self {
         BuiltinRunner::Keccak(runner)
@@ -425,14 +767,32 @@ impl From<SignatureBuiltinRunner> for BuiltinRunner {
     }
 }
 
+impl From<PoseidonBuiltinRunner> for BuiltinRunner {
+    fn from(runner: PoseidonBuiltinRunner) -> Self {
+        BuiltinRunner::Poseidon(runner)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::hint_processor::builtin_hint_processor::builtin_hint_processor_definition::BuiltinHintProcessor;
+    use crate::any_box;
+    use crate::hint_processor::builtin_hint_processor::builtin_hint_processor_definition::{
+        BuiltinHintProcessor, HintFunc, HintProcessorData,
+    };
+    use crate::hint_processor::hint_processor_definition::{HintProcessor, HintReference};
+    use crate::serde::deserialize_program::ApTracking;
+    use crate::types::exec_scope::ExecutionScopes;
     use crate::types::instance_definitions::ecdsa_instance_def::EcdsaInstanceDef;
     use crate::types::instance_definitions::keccak_instance_def::KeccakInstanceDef;
+    use crate::types::instance_definitions::pedersen_instance_def::PedersenInstanceDef;
+    use crate::types::instance_definitions::poseidon_instance_def::PoseidonInstanceDef;
     use crate::types::program::Program;
-    use crate::vm::runners::cairo_runner::CairoRunner;
+    use crate::vm::errors::hint_errors::HintError;
+    use crate::vm::errors::vm_errors::AutoDeductionMismatch;
+    use crate::vm::runners::air_private_input::{PrivateInputEcOp, PrivateInputPair};
+    use crate::vm::runners::cairo_runner::{CairoRunner, RunResources};
+    use crate::vm::vm_memory::memory::Memory;
     use crate::{
         types::instance_definitions::{
             bitwise_instance_def::BitwiseInstanceDef, ec_op_instance_def::EcOpInstanceDef,
@@ -440,6 +800,34 @@ mod tests {
         utils::test_utils::*,
         vm::vm_core::VirtualMachine,
     };
+    use felt::NewFelt;
+    use num_traits::Zero;
+    use std::any::Any;
+    use std::collections::HashMap;
+    use std::rc::Rc;
+
+    #[test]
+    fn initialize_segments_twice() {
+        let mut builtin: BuiltinRunner =
+            BitwiseBuiltinRunner::new(&BitwiseInstanceDef::default(), true).into();
+        let mut segments = MemorySegmentManager::new();
+        let mut memory = Memory::new();
+        segments.add(&mut memory);
+        assert!(!builtin.is_initialized());
+        assert_eq!(builtin.base(), 0);
+
+        builtin
+            .initialize_segments(&mut segments, &mut memory)
+            .unwrap();
+        assert!(builtin.is_initialized());
+        assert_eq!(builtin.base(), 1);
+        assert_eq!(
+            builtin.initialize_segments(&mut segments, &mut memory),
+            Err(RunnerError::BuiltinAlreadyInitialized("bitwise"))
+        );
+        assert_eq!(builtin.base(), 1);
+        assert_eq!(segments.num_segments, 2);
+    }
 
     #[test]
     fn get_memory_accesses_missing_segment_used_sizes() {
@@ -449,7 +837,7 @@ mod tests {
 
         assert_eq!(
             builtin.get_memory_accesses(&vm),
-            Err(MemoryError::MissingSegmentUsedSizes),
+            Err(MemoryError::MissingSegmentUsedSizes(0)),
         );
     }
 
@@ -470,6 +858,7 @@ mod tests {
         let mut vm = vm!();
 
         vm.segments.segment_used_sizes = Some(vec![4]);
+        vm.memory = memory![((0, 0), 1), ((0, 1), 2), ((0, 2), 3), ((0, 3), 4)];
         assert_eq!(
             builtin.get_memory_accesses(&vm),
             Ok(vec![
@@ -481,6 +870,34 @@ mod tests {
         );
     }
 
+    #[test]
+    fn get_memory_accesses_skips_unwritten_cells() {
+        let builtin: BuiltinRunner =
+            BitwiseBuiltinRunner::new(&BitwiseInstanceDef::default(), true).into();
+        let mut vm = vm!();
+
+        // The first instance is complete, the second one has its x & y and x | y cells missing,
+        // and the segment is allocated up to the end of the second instance
+        vm.segments.segment_used_sizes = Some(vec![10]);
+        vm.memory = memory![
+            ((0, 0), 12),
+            ((0, 1), 10),
+            ((0, 2), 8),
+            ((0, 3), 6),
+            ((0, 4), 14),
+            ((0, 5), 12),
+            ((0, 6), 10),
+            ((0, 8), 6)
+        ];
+        assert_eq!(
+            builtin.get_memory_accesses(&vm),
+            Ok([0, 1, 2, 3, 4, 5, 6, 8]
+                .into_iter()
+                .map(|offset| (0, offset).into())
+                .collect()),
+        );
+    }
+
     #[test]
     fn get_allocated_memory_units_bitwise_with_items() {
         let builtin = BuiltinRunner::Bitwise(BitwiseBuiltinRunner::new(
@@ -490,29 +907,7 @@ mod tests {
 
         let mut vm = vm!();
 
-        let program = program!(
-            builtins = vec![String::from("bitwise")],
-            data = vec_data!(
-                (4612671182993129469_i64),
-                (5189976364521848832_i64),
-                (18446744073709551615_i128),
-                (5199546496550207487_i64),
-                (4612389712311386111_i64),
-                (5198983563776393216_i64),
-                (2),
-                (2345108766317314046_i64),
-                (5191102247248822272_i64),
-                (5189976364521848832_i64),
-                (7),
-                (1226245742482522112_i64),
-                ((
-                    "3618502788666131213697322783095070105623107215331596699973092056135872020470",
-                    10
-                )),
-                (2345108766317314046_i64)
-            ),
-            main = Some(8),
-        );
+        let program = Program::from_fixture("check_range");
 
         let mut cairo_runner = cairo_runner!(program);
 
@@ -521,7 +916,12 @@ mod tests {
         let address = cairo_runner.initialize(&mut vm).unwrap();
 
         cairo_runner
-            .run_until_pc(address, &mut vm, &mut hint_processor)
+            .run_until_pc(
+                address,
+                &mut RunResources::default(),
+                &mut vm,
+                &mut hint_processor,
+            )
             .unwrap();
 
         assert_eq!(builtin.get_allocated_memory_units(&vm), Ok(5));
@@ -533,29 +933,7 @@ mod tests {
 
         let mut vm = vm!();
 
-        let program = program!(
-            builtins = vec![String::from("ec_op")],
-            data = vec_data!(
-                (4612671182993129469_i64),
-                (5189976364521848832_i64),
-                (18446744073709551615_i128),
-                (5199546496550207487_i64),
-                (4612389712311386111_i64),
-                (5198983563776393216_i64),
-                (2),
-                (2345108766317314046_i64),
-                (5191102247248822272_i64),
-                (5189976364521848832_i64),
-                (7),
-                (1226245742482522112_i64),
-                ((
-                    "3618502788666131213697322783095070105623107215331596699973092056135872020470",
-                    10
-                )),
-                (2345108766317314046_i64)
-            ),
-            main = Some(8),
-        );
+        let program = Program::from_fixture("check_range");
 
         let mut cairo_runner = cairo_runner!(program);
 
@@ -564,7 +942,12 @@ mod tests {
         let address = cairo_runner.initialize(&mut vm).unwrap();
 
         cairo_runner
-            .run_until_pc(address, &mut vm, &mut hint_processor)
+            .run_until_pc(
+                address,
+                &mut RunResources::default(),
+                &mut vm,
+                &mut hint_processor,
+            )
             .unwrap();
 
         assert_eq!(builtin.get_allocated_memory_units(&vm), Ok(7));
@@ -572,33 +955,14 @@ mod tests {
 
     #[test]
     fn get_allocated_memory_units_hash_with_items() {
-        let builtin = BuiltinRunner::Hash(HashBuiltinRunner::new(10, true));
+        let builtin = BuiltinRunner::Hash(HashBuiltinRunner::new(
+            &PedersenInstanceDef::new(10, 1),
+            true,
+        ));
 
         let mut vm = vm!();
 
-        let program = program!(
-            builtins = vec![String::from("pedersen")],
-            data = vec_data!(
-                (4612671182993129469_i64),
-                (5189976364521848832_i64),
-                (18446744073709551615_i128),
-                (5199546496550207487_i64),
-                (4612389712311386111_i64),
-                (5198983563776393216_i64),
-                (2),
-                (2345108766317314046_i64),
-                (5191102247248822272_i64),
-                (5189976364521848832_i64),
-                (7),
-                (1226245742482522112_i64),
-                ((
-                    "3618502788666131213697322783095070105623107215331596699973092056135872020470",
-                    10
-                )),
-                (2345108766317314046_i64)
-            ),
-            main = Some(8),
-        );
+        let program = Program::from_fixture("check_range");
 
         let mut cairo_runner = cairo_runner!(program);
 
@@ -607,7 +971,12 @@ mod tests {
         let address = cairo_runner.initialize(&mut vm).unwrap();
 
         cairo_runner
-            .run_until_pc(address, &mut vm, &mut hint_processor)
+            .run_until_pc(
+                address,
+                &mut RunResources::default(),
+                &mut vm,
+                &mut hint_processor,
+            )
             .unwrap();
 
         assert_eq!(builtin.get_allocated_memory_units(&vm), Ok(3));
@@ -619,29 +988,7 @@ mod tests {
 
         let mut vm = vm!();
 
-        let program = program!(
-            builtins = vec![String::from("range_check")],
-            data = vec_data!(
-                (4612671182993129469_i64),
-                (5189976364521848832_i64),
-                (18446744073709551615_i128),
-                (5199546496550207487_i64),
-                (4612389712311386111_i64),
-                (5198983563776393216_i64),
-                (2),
-                (2345108766317314046_i64),
-                (5191102247248822272_i64),
-                (5189976364521848832_i64),
-                (7),
-                (1226245742482522112_i64),
-                ((
-                    "3618502788666131213697322783095070105623107215331596699973092056135872020470",
-                    10
-                )),
-                (2345108766317314046_i64)
-            ),
-            main = Some(8),
-        );
+        let program = Program::from_fixture("check_range");
 
         let mut cairo_runner = cairo_runner!(program);
 
@@ -650,7 +997,12 @@ mod tests {
         let address = cairo_runner.initialize(&mut vm).unwrap();
 
         cairo_runner
-            .run_until_pc(address, &mut vm, &mut hint_processor)
+            .run_until_pc(
+                address,
+                &mut RunResources::default(),
+                &mut vm,
+                &mut hint_processor,
+            )
             .unwrap();
 
         assert_eq!(builtin.get_allocated_memory_units(&vm), Ok(1));
@@ -663,29 +1015,7 @@ mod tests {
 
         let mut vm = vm!();
 
-        let program = program!(
-            builtins = vec![String::from("keccak")],
-            data = vec_data!(
-                (4612671182993129469_i64),
-                (5189976364521848832_i64),
-                (18446744073709551615_i128),
-                (5199546496550207487_i64),
-                (4612389712311386111_i64),
-                (5198983563776393216_i64),
-                (2),
-                (2345108766317314046_i64),
-                (5191102247248822272_i64),
-                (5189976364521848832_i64),
-                (7),
-                (1226245742482522112_i64),
-                ((
-                    "3618502788666131213697322783095070105623107215331596699973092056135872020470",
-                    10
-                )),
-                (2345108766317314046_i64)
-            ),
-            main = Some(8),
-        );
+        let program = Program::from_fixture("check_range");
 
         let mut cairo_runner = cairo_runner!(program, "recursive");
 
@@ -694,7 +1024,12 @@ mod tests {
         let address = cairo_runner.initialize(&mut vm).unwrap();
 
         cairo_runner
-            .run_until_pc(address, &mut vm, &mut hint_processor)
+            .run_until_pc(
+                address,
+                &mut RunResources::default(),
+                &mut vm,
+                &mut hint_processor,
+            )
             .unwrap();
 
         assert_eq!(builtin.get_allocated_memory_units(&vm), Ok(16));
@@ -718,7 +1053,10 @@ mod tests {
 
     #[test]
     fn get_allocated_memory_units_hash() {
-        let builtin = BuiltinRunner::Hash(HashBuiltinRunner::new(1, true));
+        let builtin = BuiltinRunner::Hash(HashBuiltinRunner::new(
+            &PedersenInstanceDef::new(1, 1),
+            true,
+        ));
         let vm = vm!();
         assert_eq!(builtin.get_allocated_memory_units(&vm), Ok(0));
     }
@@ -733,6 +1071,59 @@ mod tests {
         assert_eq!(builtin.get_allocated_memory_units(&vm), Ok(0));
     }
 
+    #[test]
+    fn get_allocated_memory_units_fractional_ratio() {
+        // Two instances every step
+        let instance_def = BitwiseInstanceDef {
+            ratio: 1,
+            ratio_den: 2,
+            total_n_bits: 251,
+        };
+        let builtin = BuiltinRunner::Bitwise(BitwiseBuiltinRunner::new(&instance_def, true));
+        assert_eq!(builtin.ratio(), Some(1));
+        assert_eq!(builtin.ratio_den(), Some(2));
+
+        let mut vm = vm!();
+        vm.current_step = 10;
+        assert_eq!(builtin.get_allocated_memory_units(&vm), Ok(100));
+    }
+
+    #[test]
+    fn get_allocated_memory_units_fractional_ratio_not_divisible() {
+        // Two instances every three steps
+        let mut builtin = HashBuiltinRunner::new(&PedersenInstanceDef::new(3, 1), true);
+        builtin.ratio_den = 2;
+        let builtin = BuiltinRunner::Hash(builtin);
+
+        let mut vm = vm!();
+        vm.current_step = 6;
+        assert_eq!(builtin.get_allocated_memory_units(&vm), Ok(12));
+        vm.current_step = 5;
+        assert_eq!(
+            builtin.get_allocated_memory_units(&vm),
+            Err(MemoryError::ErrorCalculatingMemoryUnits("pedersen"))
+        );
+    }
+
+    #[test]
+    fn get_used_cells_and_allocated_size_fractional_ratio_min_step() {
+        // Four instances every three steps, with 16 instances per component
+        let instance_def = KeccakInstanceDef {
+            ratio_den: 4,
+            ..KeccakInstanceDef::new(3)
+        };
+        let builtin = BuiltinRunner::Keccak(KeccakBuiltinRunner::new(&instance_def, true));
+        let mut vm = vm!();
+        vm.current_step = 11;
+        assert_eq!(
+            builtin.get_used_cells_and_allocated_size(&vm),
+            Err(MemoryError::MinStepNotReached {
+                builtin: "keccak",
+                min_step: 12
+            })
+        );
+    }
+
     #[test]
     fn get_allocated_memory_units_ec_op() {
         let builtin =
@@ -755,7 +1146,7 @@ mod tests {
     fn get_range_check_usage_range_check() {
         let builtin = BuiltinRunner::RangeCheck(RangeCheckBuiltinRunner::new(8, 8, true));
         let memory = memory![((0, 0), 1), ((0, 1), 2), ((0, 2), 3), ((0, 3), 4)];
-        assert_eq!(builtin.get_range_check_usage(&memory), Some((1, 4)));
+        assert_eq!(builtin.get_range_check_usage(&memory), Some((0, 4)));
     }
 
     #[test]
@@ -767,7 +1158,10 @@ mod tests {
 
     #[test]
     fn get_range_check_usage_hash() {
-        let builtin = BuiltinRunner::Hash(HashBuiltinRunner::new(256, true));
+        let builtin = BuiltinRunner::Hash(HashBuiltinRunner::new(
+            &PedersenInstanceDef::new(256, 1),
+            true,
+        ));
         let memory = memory![((0, 0), 1), ((0, 1), 2), ((0, 2), 3), ((0, 3), 4)];
         assert_eq!(builtin.get_range_check_usage(&memory), None);
     }
@@ -825,7 +1219,10 @@ mod tests {
 
     #[test]
     fn get_used_diluted_check_units_hash() {
-        let builtin = BuiltinRunner::Hash(HashBuiltinRunner::new(16, true));
+        let builtin = BuiltinRunner::Hash(HashBuiltinRunner::new(
+            &PedersenInstanceDef::new(16, 1),
+            true,
+        ));
         assert_eq!(builtin.get_used_diluted_check_units(270, 7), 0);
     }
 
@@ -847,29 +1244,91 @@ mod tests {
             BitwiseBuiltinRunner::new(&BitwiseInstanceDef::default(), true).into();
         assert_eq!(
             bitwise_builtin.get_memory_segment_addresses(),
-            ("bitwise", (0, None)),
+            vec![SegmentAddresses::new("bitwise", 0, None)],
         );
         let ec_op_builtin: BuiltinRunner =
             EcOpBuiltinRunner::new(&EcOpInstanceDef::default(), true).into();
         assert_eq!(
             ec_op_builtin.get_memory_segment_addresses(),
-            ("ec_op", (0, None)),
+            vec![SegmentAddresses::new("ec_op", 0, None)],
         );
-        let hash_builtin: BuiltinRunner = HashBuiltinRunner::new(8, true).into();
+        let hash_builtin: BuiltinRunner =
+            HashBuiltinRunner::new(&PedersenInstanceDef::default(), true).into();
         assert_eq!(
             hash_builtin.get_memory_segment_addresses(),
-            ("pedersen", (0, None)),
+            vec![SegmentAddresses::new("pedersen", 0, None)],
         );
         let output_builtin: BuiltinRunner = OutputBuiltinRunner::new(true).into();
         assert_eq!(
             output_builtin.get_memory_segment_addresses(),
-            ("output", (0, None)),
+            vec![SegmentAddresses::new("output", 0, None)],
         );
         let range_check_builtin: BuiltinRunner =
             BuiltinRunner::RangeCheck(RangeCheckBuiltinRunner::new(8, 8, true));
         assert_eq!(
             range_check_builtin.get_memory_segment_addresses(),
-            ("range_check", (0, None)),
+            vec![SegmentAddresses::new("range_check", 0, None)],
+        );
+    }
+
+    #[test]
+    fn builtin_names_and_instance_layout() {
+        let builtins: [(BuiltinRunner, &str, u32, u32); 5] = [
+            (
+                BitwiseBuiltinRunner::new(&BitwiseInstanceDef::default(), true).into(),
+                "bitwise",
+                5,
+                2,
+            ),
+            (
+                EcOpBuiltinRunner::new(&EcOpInstanceDef::default(), true).into(),
+                "ec_op",
+                7,
+                5,
+            ),
+            (
+                HashBuiltinRunner::new(&PedersenInstanceDef::default(), true).into(),
+                "pedersen",
+                3,
+                2,
+            ),
+            (OutputBuiltinRunner::new(true).into(), "output", 1, 1),
+            (
+                RangeCheckBuiltinRunner::new_range_check96(8, true).into(),
+                "range_check96",
+                1,
+                1,
+            ),
+        ];
+        for (builtin, name, cells_per_instance, n_input_cells) in builtins {
+            assert_eq!(builtin.name(), name);
+            assert_eq!(builtin.cells_per_instance(), cells_per_instance);
+            assert_eq!(builtin.n_input_cells(), n_input_cells);
+        }
+    }
+
+    #[test]
+    fn instance_info() {
+        let builtin: BuiltinRunner =
+            HashBuiltinRunner::new(&PedersenInstanceDef::default(), true).into();
+        assert_eq!(
+            builtin.instance_info(),
+            BuiltinInstanceInfo {
+                name: "pedersen",
+                cells_per_instance: 3,
+                n_input_cells: 2,
+                ratio: Some(8),
+            }
+        );
+        let builtin: BuiltinRunner = OutputBuiltinRunner::new(true).into();
+        assert_eq!(
+            builtin.instance_info(),
+            BuiltinInstanceInfo {
+                name: "output",
+                cells_per_instance: 1,
+                n_input_cells: 1,
+                ratio: None,
+            }
         );
     }
 
@@ -891,10 +1350,22 @@ mod tests {
 
         assert_eq!(
             builtin.run_security_checks(&mut vm),
-            Err(MemoryError::NumOutOfBounds.into()),
+            Err(MemoryError::UnallocatedSegment(0, 0).into()),
         );
     }
 
+    #[test]
+    fn run_security_checks_not_included() {
+        let builtin = BuiltinRunner::Bitwise(BitwiseBuiltinRunner::new(
+            &BitwiseInstanceDef::default(),
+            false,
+        ));
+        let mut vm = vm!();
+
+        assert!(!builtin.included());
+        assert_eq!(builtin.run_security_checks(&mut vm), Ok(()));
+    }
+
     #[test]
     fn run_security_checks_temporary_segment() {
         let builtin = BuiltinRunner::Bitwise({
@@ -974,7 +1445,8 @@ mod tests {
 
     #[test]
     fn run_security_checks_hash_missing_memory_cells_with_offsets() {
-        let builtin: BuiltinRunner = HashBuiltinRunner::new(8, true).into();
+        let builtin: BuiltinRunner =
+            HashBuiltinRunner::new(&PedersenInstanceDef::default(), true).into();
         let mut vm = vm!();
 
         vm.memory.data = vec![vec![
@@ -994,7 +1466,7 @@ mod tests {
 
     #[test]
     fn run_security_checks_hash_missing_memory_cells() {
-        let mut hash_builtin = HashBuiltinRunner::new(8, true);
+        let mut hash_builtin = HashBuiltinRunner::new(&PedersenInstanceDef::default(), true);
 
         hash_builtin.cells_per_instance = 2;
         hash_builtin.n_input_cells = 3;
@@ -1087,6 +1559,107 @@ mod tests {
         assert_eq!(builtin.run_security_checks(&mut vm), Ok(()));
     }
 
+    // Writes three pedersen instances whose results are all zero
+    fn write_corrupted_pedersen_results(
+        vm: &mut VirtualMachine,
+        _exec_scopes: &mut ExecutionScopes,
+        _ids_data: &HashMap<String, HintReference>,
+        _ap_tracking: &ApTracking,
+        _constants: &HashMap<String, Felt>,
+    ) -> Result<(), HintError> {
+        for i in 0..3 {
+            vm.insert_value(&(0, 3 * i).into(), Felt::new(i + 1))?;
+            vm.insert_value(&(0, 3 * i + 1).into(), Felt::new(i + 2))?;
+            vm.insert_value(&(0, 3 * i + 2).into(), Felt::zero())?;
+        }
+        Ok(())
+    }
+
+    fn run_corrupting_pedersen_hint(vm: &mut VirtualMachine) {
+        let hint_code = "corrupt_pedersen_results()";
+        let mut hint_processor = BuiltinHintProcessor::new_empty();
+        hint_processor.add_hint(
+            String::from(hint_code),
+            Rc::new(HintFunc(Box::new(write_corrupted_pedersen_results))),
+        );
+        let hint_data = HintProcessorData::new_default(String::from(hint_code), HashMap::new());
+        assert_eq!(
+            hint_processor.execute_hint(
+                vm,
+                exec_scopes_ref!(),
+                &any_box!(hint_data),
+                &HashMap::new(),
+                &mut RunResources::default()
+            ),
+            Ok(())
+        );
+    }
+
+    // The results the pedersen builtin deduces for the instances written by
+    // write_corrupted_pedersen_results
+    fn expected_pedersen_results(vm: &VirtualMachine) -> Vec<MaybeRelocatable> {
+        let builtin = HashBuiltinRunner::new(&PedersenInstanceDef::default(), true);
+        (0..3)
+            .map(|i| {
+                builtin
+                    .deduce_memory_cell(&(0, 3 * i + 2).into(), &vm.memory)
+                    .unwrap()
+                    .unwrap()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn run_security_checks_hash_inconsistent_auto_deduction() {
+        let builtin: BuiltinRunner =
+            HashBuiltinRunner::new(&PedersenInstanceDef::default(), true).into();
+        let mut vm = vm!();
+        vm.segments.add(&mut vm.memory);
+        run_corrupting_pedersen_hint(&mut vm);
+        let expected = expected_pedersen_results(&vm);
+
+        assert_eq!(
+            builtin.run_security_checks(&mut vm),
+            Err(VirtualMachineError::InconsistentAutoDeduction(
+                String::from("pedersen"),
+                expected[0].clone(),
+                Some(mayberelocatable!(0)),
+            ))
+        );
+    }
+
+    #[test]
+    fn run_security_checks_hash_collect_all_inconsistent_auto_deductions() {
+        let builtin: BuiltinRunner =
+            HashBuiltinRunner::new(&PedersenInstanceDef::default(), true).into();
+        let mut vm = vm!();
+        vm.segments.add(&mut vm.memory);
+        vm.set_collect_all_errors(true);
+        run_corrupting_pedersen_hint(&mut vm);
+        let expected = expected_pedersen_results(&vm);
+
+        let mismatches = expected
+            .into_iter()
+            .enumerate()
+            .map(|(i, expected)| AutoDeductionMismatch {
+                builtin: String::from("pedersen"),
+                address: (0, 3 * i + 2).into(),
+                expected,
+                actual: mayberelocatable!(0),
+            })
+            .collect::<Vec<_>>();
+        let error = builtin.run_security_checks(&mut vm).unwrap_err();
+        let message = error.to_string();
+        assert_eq!(
+            error,
+            VirtualMachineError::InconsistentAutoDeductions(mismatches.clone(), 3)
+        );
+        assert!(message.starts_with("Found 3 inconsistent auto-deductions:"));
+        for mismatch in mismatches {
+            assert!(message.contains(&mismatch.to_string()));
+        }
+    }
+
     #[test]
     fn run_security_ec_op_missing_memory_cells_with_offsets() {
         let builtin: BuiltinRunner =
@@ -1170,7 +1743,8 @@ mod tests {
     /// builtin is a HashBuiltinRunner.
     #[test]
     fn get_used_perm_range_check_units_hash() {
-        let builtin_runner: BuiltinRunner = HashBuiltinRunner::new(8, true).into();
+        let builtin_runner: BuiltinRunner =
+            HashBuiltinRunner::new(&PedersenInstanceDef::default(), true).into();
         let mut vm = vm!();
 
         vm.current_step = 8;
@@ -1210,7 +1784,8 @@ mod tests {
         let ec_op_builtin: BuiltinRunner =
             EcOpBuiltinRunner::new(&EcOpInstanceDef::default(), true).into();
         assert_eq!(ec_op_builtin.ratio(), (Some(256)),);
-        let hash_builtin: BuiltinRunner = HashBuiltinRunner::new(8, true).into();
+        let hash_builtin: BuiltinRunner =
+            HashBuiltinRunner::new(&PedersenInstanceDef::default(), true).into();
         assert_eq!(hash_builtin.ratio(), (Some(8)),);
         let output_builtin: BuiltinRunner = OutputBuiltinRunner::new(true).into();
         assert_eq!(output_builtin.ratio(), None,);
@@ -1247,7 +1822,8 @@ mod tests {
         let mut vm = vm!();
         vm.segments.segment_used_sizes = Some(vec![4]);
 
-        let hash_builtin: BuiltinRunner = HashBuiltinRunner::new(8, true).into();
+        let hash_builtin: BuiltinRunner =
+            HashBuiltinRunner::new(&PedersenInstanceDef::default(), true).into();
         assert_eq!(hash_builtin.get_used_instances(&vm), Ok(2));
     }
 
@@ -1277,7 +1853,10 @@ mod tests {
                 false,
             )),
             BuiltinRunner::EcOp(EcOpBuiltinRunner::new(&EcOpInstanceDef::default(), false)),
-            BuiltinRunner::Hash(HashBuiltinRunner::new(1, false)),
+            BuiltinRunner::Hash(HashBuiltinRunner::new(
+                &PedersenInstanceDef::new(1, 1),
+                false,
+            )),
             BuiltinRunner::Output(OutputBuiltinRunner::new(false)),
             BuiltinRunner::RangeCheck(RangeCheckBuiltinRunner::new(8, 8, false)),
             BuiltinRunner::Keccak(KeccakBuiltinRunner::new(
@@ -1288,11 +1867,16 @@ mod tests {
                 &EcdsaInstanceDef::default(),
                 false,
             )),
+            BuiltinRunner::Poseidon(PoseidonBuiltinRunner::new(
+                &PoseidonInstanceDef::default(),
+                false,
+            )),
         ];
         let vm = vm!();
 
-        for br in builtins {
-            assert_eq!(br.final_stack(&vm, vm.get_ap()), Ok((vm.get_ap(), 0)));
+        for mut br in builtins {
+            assert_eq!(br.final_stack(&vm, vm.get_ap()), Ok(vm.get_ap()));
+            assert_eq!(br.get_memory_segment_addresses()[0].stop_ptr, Some(0));
         }
     }
 
@@ -1304,7 +1888,10 @@ mod tests {
                 false,
             )),
             BuiltinRunner::EcOp(EcOpBuiltinRunner::new(&EcOpInstanceDef::default(), false)),
-            BuiltinRunner::Hash(HashBuiltinRunner::new(1, false)),
+            BuiltinRunner::Hash(HashBuiltinRunner::new(
+                &PedersenInstanceDef::new(1, 1),
+                false,
+            )),
             BuiltinRunner::Output(OutputBuiltinRunner::new(false)),
             BuiltinRunner::RangeCheck(RangeCheckBuiltinRunner::new(8, 8, false)),
             BuiltinRunner::Keccak(KeccakBuiltinRunner::new(
@@ -1320,9 +1907,320 @@ mod tests {
         let ptr = 3;
 
         for mut br in builtins {
-            br.set_stop_ptr(ptr);
-            let (_, (_, stop_ptr)) = br.get_memory_segment_addresses();
-            assert_eq!(stop_ptr, Some(ptr));
+            br.set_stop_ptr(Relocatable::from((0, ptr))).unwrap();
+            assert_eq!(br.get_memory_segment_addresses()[0].stop_ptr, Some(ptr));
         }
     }
+
+    #[test]
+    fn runners_set_stop_ptr_wrong_segment() {
+        let mut builtin = BuiltinRunner::Output(OutputBuiltinRunner::new(true));
+        assert_eq!(
+            builtin.set_stop_ptr(Relocatable::from((1, 3))),
+            Err(RunnerError::StopPointerSegmentMismatch(
+                "output",
+                Relocatable::from((1, 3)),
+                0
+            ))
+        );
+        assert_eq!(builtin.get_memory_segment_addresses()[0].stop_ptr, None);
+    }
+
+    #[test]
+    fn get_and_set_state_output() {
+        let mut builtin = BuiltinRunner::Output(OutputBuiltinRunner::new(true));
+        let mut state = builtin.get_state().unwrap();
+        assert_eq!(state.base, 0);
+        state.base = 5;
+        builtin.set_state(state).unwrap();
+        assert_eq!(builtin.base(), 5);
+    }
+
+    #[test]
+    fn get_and_set_state_not_supported() {
+        let mut builtin = BuiltinRunner::RangeCheck(RangeCheckBuiltinRunner::new(8, 8, true));
+        assert_eq!(
+            builtin.get_state(),
+            Err(RunnerError::StateNotSupported("range_check"))
+        );
+        let state = OutputBuiltinRunner::new(true).get_state();
+        assert_eq!(
+            builtin.set_state(state),
+            Err(RunnerError::StateNotSupported("range_check"))
+        );
+    }
+
+    #[test]
+    fn air_private_input_hash() {
+        let builtin: BuiltinRunner =
+            HashBuiltinRunner::new(&PedersenInstanceDef::default(), true).into();
+        // The first instance is complete, the second one lacks its result, and the third one
+        // lacks an input
+        let memory = memory![
+            ((0, 0), 1),
+            ((0, 1), 2),
+            ((0, 2), 3),
+            ((0, 3), 4),
+            ((0, 4), 5),
+            ((0, 6), 6)
+        ];
+        assert_eq!(
+            builtin.air_private_input(&memory),
+            vec![
+                PrivateInput::Pair(PrivateInputPair {
+                    index: 0,
+                    x: Felt::new(1),
+                    y: Felt::new(2)
+                }),
+                PrivateInput::Pair(PrivateInputPair {
+                    index: 1,
+                    x: Felt::new(4),
+                    y: Felt::new(5)
+                }),
+            ]
+        );
+    }
+
+    #[test]
+    fn air_private_input_output() {
+        let builtin: BuiltinRunner = OutputBuiltinRunner::new(true).into();
+        let memory = memory![((0, 0), 1), ((0, 1), 2)];
+        assert!(builtin.air_private_input(&memory).is_empty());
+    }
+
+    #[test]
+    fn air_private_input_ec_op() {
+        let builtin: BuiltinRunner =
+            EcOpBuiltinRunner::new(&EcOpInstanceDef::default(), true).into();
+        let memory = memory![
+            ((0, 0), 1),
+            ((0, 1), 2),
+            ((0, 2), 3),
+            ((0, 3), 4),
+            ((0, 4), 5),
+            ((0, 5), 6)
+        ];
+        // The inputs are stored as p_x, p_y, q_x, q_y, m
+        assert_eq!(
+            builtin.air_private_input(&memory),
+            vec![PrivateInput::EcOp(PrivateInputEcOp {
+                index: 0,
+                p_x: Felt::new(1),
+                p_y: Felt::new(2),
+                m: Felt::new(5),
+                q_x: Felt::new(3),
+                q_y: Felt::new(4),
+            })]
+        );
+    }
+
+    #[test]
+    fn get_instances_range_check() {
+        let builtin: BuiltinRunner = RangeCheckBuiltinRunner::new(8, 8, true).into();
+        let memory = memory![((0, 0), 0x10001), ((0, 2), 3)];
+        let instances = builtin.get_instances(&memory);
+        assert_eq!(
+            instances,
+            vec![
+                BuiltinInstance {
+                    index: 0,
+                    cells: vec![Some(mayberelocatable!(0x10001))],
+                    deductions: vec![None],
+                },
+                BuiltinInstance {
+                    index: 1,
+                    cells: vec![None],
+                    deductions: vec![None],
+                },
+                BuiltinInstance {
+                    index: 2,
+                    cells: vec![Some(mayberelocatable!(3))],
+                    deductions: vec![None],
+                },
+            ]
+        );
+        assert_eq!(
+            builtin.describe_instance(&instances[0]),
+            "0: 65537 = [1, 1, 0, 0, 0, 0, 0, 0]"
+        );
+        assert_eq!(
+            builtin.describe_instance(&instances[1]),
+            "1: _ (incomplete)"
+        );
+    }
+
+    #[test]
+    fn hash_additional_data_round_trip() {
+        let memory = memory![((0, 3), 32), ((0, 4), 72), ((0, 5), 0)];
+        let builtin: BuiltinRunner =
+            HashBuiltinRunner::new(&PedersenInstanceDef::default(), true).into();
+        assert!(builtin
+            .deduce_memory_cell(&Relocatable::from((0, 5)), &memory)
+            .unwrap()
+            .is_some());
+        let data = builtin.get_additional_data();
+        assert_eq!(
+            data,
+            BuiltinRunnerAdditionalData::Hash(vec![Relocatable::from((0, 5))])
+        );
+
+        let mut restored: BuiltinRunner =
+            HashBuiltinRunner::new(&PedersenInstanceDef::default(), true).into();
+        restored.extend_additional_data(&data).unwrap();
+        // The address was already verified, so it isn't deduced again
+        assert_eq!(
+            restored.deduce_memory_cell(&Relocatable::from((0, 5)), &memory),
+            Ok(None)
+        );
+        assert_eq!(restored.get_additional_data(), data);
+    }
+
+    #[test]
+    fn signature_additional_data_round_trip() {
+        let mut signature_builtin = SignatureBuiltinRunner::new(&EcdsaInstanceDef::default(), true);
+        let signature = (
+            felt::felt_str!(
+                "1839793652349538280924927302501143912227271479439798783640887258675143576352"
+            ),
+            felt::felt_str!(
+                "1819432147005223164874083361865404672584671743718628757598322238853218813979"
+            ),
+        );
+        signature_builtin
+            .add_signature(Relocatable::from((0, 0)), &signature)
+            .unwrap();
+        let data = BuiltinRunner::from(signature_builtin).get_additional_data();
+        assert_eq!(
+            data,
+            BuiltinRunnerAdditionalData::Signature(HashMap::from([(
+                Relocatable::from((0, 0)),
+                signature
+            )]))
+        );
+
+        let mut restored: BuiltinRunner =
+            SignatureBuiltinRunner::new(&EcdsaInstanceDef::default(), true).into();
+        restored.extend_additional_data(&data).unwrap();
+        let mut segments = MemorySegmentManager::new();
+        let mut memory = memory![
+            (
+                (0, 0),
+                (
+                    "874739451078007766457464989774322083649278607533249481151382481072868806602",
+                    10
+                )
+            ),
+            ((0, 1), 2)
+        ];
+        restored
+            .initialize_segments(&mut segments, &mut memory)
+            .unwrap();
+        restored.add_validation_rule(&mut memory).unwrap();
+        assert_eq!(memory.validate_existing_memory(), Ok(()));
+    }
+
+    #[test]
+    fn output_additional_data_round_trip() {
+        let mut output_builtin = OutputBuiltinRunner::new(true);
+        output_builtin
+            .pages
+            .insert(1, PublicMemoryPage { start: 0, size: 3 });
+        output_builtin
+            .attributes
+            .insert(String::from("gps_fact_topology"), vec![1, 0]);
+        let data = BuiltinRunner::from(output_builtin.clone()).get_additional_data();
+
+        let mut restored: BuiltinRunner = OutputBuiltinRunner::new(true).into();
+        restored.extend_additional_data(&data).unwrap();
+        assert_eq!(restored.get_state().unwrap(), output_builtin.get_state());
+    }
+
+    #[test]
+    fn additional_data_empty_for_other_builtins() {
+        let mut builtin: BuiltinRunner =
+            BitwiseBuiltinRunner::new(&BitwiseInstanceDef::default(), true).into();
+        assert_eq!(
+            builtin.get_additional_data(),
+            BuiltinRunnerAdditionalData::Empty
+        );
+        assert_eq!(
+            builtin.extend_additional_data(&BuiltinRunnerAdditionalData::Empty),
+            Ok(())
+        );
+        assert_eq!(
+            builtin.extend_additional_data(&BuiltinRunnerAdditionalData::Hash(Vec::new())),
+            Err(RunnerError::InvalidAdditionalData("bitwise"))
+        );
+    }
+
+    #[test]
+    fn snapshot_and_restore_hash_builtin() {
+        let mut builtin: BuiltinRunner =
+            HashBuiltinRunner::new(&PedersenInstanceDef::default(), true).into();
+        builtin
+            .extend_additional_data(&BuiltinRunnerAdditionalData::Hash(vec![(0, 2).into()]))
+            .unwrap();
+        let snapshot = builtin.snapshot();
+        assert_eq!(
+            snapshot,
+            BuiltinRunnerSnapshot {
+                name: String::from("pedersen"),
+                initialized: false,
+                base: 0,
+                stop_ptr: None,
+                additional_data: BuiltinRunnerAdditionalData::Hash(vec![(0, 2).into()]),
+            }
+        );
+
+        // The run goes on, then is reverted
+        builtin
+            .extend_additional_data(&BuiltinRunnerAdditionalData::Hash(vec![(0, 5).into()]))
+            .unwrap();
+        builtin.set_stop_ptr((0, 6).into()).unwrap();
+        builtin.restore(&snapshot).unwrap();
+        assert_eq!(builtin.snapshot(), snapshot);
+    }
+
+    #[test]
+    fn snapshot_and_restore_signature_builtin() {
+        let mut builtin: BuiltinRunner =
+            SignatureBuiltinRunner::new(&EcdsaInstanceDef::default(), true).into();
+        let snapshot = builtin.snapshot();
+        // Unlike the snapshot, a clone shares its signatures with the original builtin
+        let clone = builtin.clone();
+        let signatures = BuiltinRunnerAdditionalData::Signature(HashMap::from([(
+            Relocatable::from((0, 0)),
+            (Felt::new(1), Felt::new(2)),
+        )]));
+        builtin.extend_additional_data(&signatures).unwrap();
+        assert_eq!(clone.get_additional_data(), signatures);
+        assert_eq!(
+            snapshot.additional_data,
+            BuiltinRunnerAdditionalData::Signature(HashMap::new())
+        );
+
+        builtin.restore(&snapshot).unwrap();
+        assert_eq!(builtin.snapshot(), snapshot);
+    }
+
+    #[test]
+    fn restore_snapshot_of_another_builtin() {
+        let hash: BuiltinRunner =
+            HashBuiltinRunner::new(&PedersenInstanceDef::default(), true).into();
+        let mut range_check: BuiltinRunner = RangeCheckBuiltinRunner::new(8, 8, true).into();
+        assert_eq!(
+            range_check.restore(&hash.snapshot()),
+            Err(RunnerError::InvalidSnapshot("range_check"))
+        );
+    }
+
+    #[test]
+    fn extend_additional_data_mismatch() {
+        let mut builtin: BuiltinRunner =
+            HashBuiltinRunner::new(&PedersenInstanceDef::default(), true).into();
+        assert_eq!(
+            builtin.extend_additional_data(&BuiltinRunnerAdditionalData::Empty),
+            Err(RunnerError::InvalidAdditionalData("pedersen"))
+        );
+    }
 }
@@ -1,36 +1,162 @@
 use crate::types::relocatable::{MaybeRelocatable, Relocatable};
 use crate::vm::errors::memory_errors::MemoryError;
 use crate::vm::errors::runner_errors::RunnerError;
+use crate::vm::runners::air_private_input::PrivateInput;
+use crate::vm::runners::builtin_runner::SegmentAddresses;
 use crate::vm::vm_core::VirtualMachine;
 use crate::vm::vm_memory::memory::Memory;
 use crate::vm::vm_memory::memory_segments::MemorySegmentManager;
+#[cfg(feature = "snapshot")]
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// A contiguous range of the output segment, identified by its page id.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "snapshot", derive(Serialize, Deserialize))]
+pub struct PublicMemoryPage {
+    pub start: usize,
+    pub size: usize,
+}
+
+/// Snapshot of the mutable state of an [`OutputBuiltinRunner`].
+///
+/// Hints that need to redirect the output (e.g. to a temporary segment) take
+/// the current state with `get_state`, swap in a new base through `set_state`
+/// and restore the original state once they are done.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OutputBuiltinState {
+    pub base: isize,
+    pub pages: HashMap<usize, PublicMemoryPage>,
+    pub attributes: HashMap<String, Vec<usize>>,
+    pub stop_ptr: Option<usize>,
+}
+
+/// The pages and attributes of the output builtin, which aren't part of its memory but are
+/// exported along with it in a Cairo PIE.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "snapshot", derive(Serialize, Deserialize))]
+pub struct OutputBuiltinAdditionalData {
+    pub pages: HashMap<usize, PublicMemoryPage>,
+    pub attributes: HashMap<String, Vec<usize>>,
+}
 
 #[derive(Debug, Clone)]
 pub struct OutputBuiltinRunner {
-    base: isize,
+    pub(crate) base: isize,
+    pub(crate) initialized: bool,
+    pub(crate) pages: HashMap<usize, PublicMemoryPage>,
+    pub(crate) attributes: HashMap<String, Vec<usize>>,
     pub(crate) stop_ptr: Option<usize>,
-    pub(crate) _included: bool,
+    pub(crate) included: bool,
 }
 
 impl OutputBuiltinRunner {
+    pub const NAME: &str = "output";
+
     pub fn new(included: bool) -> OutputBuiltinRunner {
         OutputBuiltinRunner {
             base: 0,
+            initialized: false,
+            pages: HashMap::new(),
+            attributes: HashMap::new(),
             stop_ptr: None,
-            _included: included,
+            included,
+        }
+    }
+
+    pub fn get_state(&self) -> OutputBuiltinState {
+        OutputBuiltinState {
+            base: self.base,
+            pages: self.pages.clone(),
+            attributes: self.attributes.clone(),
+            stop_ptr: self.stop_ptr,
+        }
+    }
+
+    pub fn set_state(&mut self, state: OutputBuiltinState) {
+        self.base = state.base;
+        self.pages = state.pages;
+        self.attributes = state.attributes;
+        self.stop_ptr = state.stop_ptr;
+    }
+
+    /// Adds a page of `page_size` cells starting at `page_start`, which must be in the output
+    /// segment. A page added with an existing id replaces the previous one.
+    pub fn add_page(
+        &mut self,
+        page_id: usize,
+        page_start: Relocatable,
+        page_size: usize,
+    ) -> Result<(), RunnerError> {
+        if page_start.segment_index != self.base {
+            return Err(RunnerError::PageNotOnSegment(page_start, self.base));
+        }
+        self.pages.insert(
+            page_id,
+            PublicMemoryPage {
+                start: page_start.offset,
+                size: page_size,
+            },
+        );
+        Ok(())
+    }
+
+    pub fn add_attribute(&mut self, name: String, value: Vec<usize>) {
+        self.attributes.insert(name, value);
+    }
+
+    /// Returns the offset and page id of each cell of the output segment, the cells which
+    /// don't belong to any page being in page 0.
+    pub fn get_public_memory(
+        &self,
+        vm: &VirtualMachine,
+    ) -> Result<Vec<(usize, usize)>, RunnerError> {
+        let size = self.get_used_cells(vm)?;
+        let mut public_memory: Vec<(usize, usize)> = (0..size).map(|offset| (offset, 0)).collect();
+        for (page_id, page) in self.pages.iter() {
+            let page_cells = public_memory
+                .get_mut(page.start..page.start + page.size)
+                .ok_or(RunnerError::PageOutOfBounds(*page_id, size))?;
+            for (_, cell_page_id) in page_cells {
+                *cell_page_id = *page_id;
+            }
+        }
+        Ok(public_memory)
+    }
+
+    pub fn get_additional_data(&self) -> OutputBuiltinAdditionalData {
+        OutputBuiltinAdditionalData {
+            pages: self.pages.clone(),
+            attributes: self.attributes.clone(),
         }
     }
 
+    pub(crate) fn clear_additional_data(&mut self) {
+        self.pages.clear();
+        self.attributes.clear();
+    }
+
+    pub fn extend_additional_data(&mut self, data: &OutputBuiltinAdditionalData) {
+        self.pages
+            .extend(data.pages.iter().map(|(id, page)| (*id, page.clone())));
+        self.attributes.extend(data.attributes.clone());
+    }
+
     pub fn initialize_segments(
         &mut self,
         segments: &mut MemorySegmentManager,
         memory: &mut Memory,
-    ) {
-        self.base = segments.add(memory).segment_index
+    ) -> Result<(), RunnerError> {
+        if self.initialized {
+            return Err(RunnerError::BuiltinAlreadyInitialized(Self::NAME));
+        }
+        self.base = segments.add(memory).segment_index;
+        self.initialized = true;
+        Ok(())
     }
 
     pub fn initial_stack(&self) -> Vec<MaybeRelocatable> {
-        if self._included {
+        if self.included {
             vec![MaybeRelocatable::from((self.base, 0))]
         } else {
             vec![]
@@ -41,6 +167,10 @@ impl OutputBuiltinRunner {
         self.base
     }
 
+    pub fn is_initialized(&self) -> bool {
+        self.initialized
+    }
+
     pub fn add_validation_rule(&self, _memory: &mut Memory) -> Result<(), RunnerError> {
         Ok(())
     }
@@ -57,18 +187,32 @@ impl OutputBuiltinRunner {
         Ok(0)
     }
 
-    pub fn get_memory_segment_addresses(&self) -> (&'static str, (isize, Option<usize>)) {
-        ("output", (self.base, self.stop_ptr))
+    pub fn air_private_input(&self, _memory: &Memory) -> Vec<PrivateInput> {
+        Vec::new()
+    }
+
+    pub fn get_memory_segment_addresses(&self) -> Vec<SegmentAddresses> {
+        vec![SegmentAddresses::new(Self::NAME, self.base, self.stop_ptr)]
     }
 
     pub fn get_used_cells(&self, vm: &VirtualMachine) -> Result<usize, MemoryError> {
         let base = self.base();
+        let index = base
+            .try_into()
+            .map_err(|_| MemoryError::AddressInTemporarySegment(base))?;
+        vm.segments
+            .get_segment_used_size(index)
+            .ok_or(MemoryError::MissingSegmentUsedSizes(index))
+    }
+
+    pub fn get_used_cells_mid_run(&self, vm: &VirtualMachine) -> Result<usize, MemoryError> {
+        let base = self.base();
+        let index = base
+            .try_into()
+            .map_err(|_| MemoryError::AddressInTemporarySegment(base))?;
         vm.segments
-            .get_segment_used_size(
-                base.try_into()
-                    .map_err(|_| MemoryError::AddressInTemporarySegment(base))?,
-            )
-            .ok_or(MemoryError::MissingSegmentUsedSizes)
+            .get_segment_used_size_mid_run(index, &vm.memory)
+            .ok_or(MemoryError::UnallocatedSegment(index, vm.memory.data.len()))
     }
 
     pub fn get_used_cells_and_allocated_size(
@@ -80,39 +224,39 @@ impl OutputBuiltinRunner {
     }
 
     pub fn get_used_instances(&self, vm: &VirtualMachine) -> Result<usize, MemoryError> {
-        self.get_used_cells(vm)
+        self.get_used_cells_mid_run(vm)
     }
 
     pub fn final_stack(
-        &self,
+        &mut self,
         vm: &VirtualMachine,
         pointer: Relocatable,
-    ) -> Result<(Relocatable, usize), RunnerError> {
-        if self._included {
-            if let Ok(stop_pointer) =
-                vm.get_relocatable(&(pointer.sub_usize(1)).map_err(|_| RunnerError::FinalStack)?)
-            {
-                if self.base() != stop_pointer.segment_index {
-                    return Err(RunnerError::InvalidStopPointer("range_check".to_string()));
-                }
-                let stop_ptr = stop_pointer.offset;
+    ) -> Result<Relocatable, RunnerError> {
+        if self.included {
+            if let Ok(stop_pointer) = vm.get_relocatable(
+                &(pointer.sub_usize(1)).map_err(|_| RunnerError::FinalStack(Self::NAME))?,
+            ) {
                 let used = self
-                    .get_used_cells(vm)
-                    .map_err(|_| RunnerError::FinalStack)?;
-                if stop_ptr != used {
-                    return Err(RunnerError::InvalidStopPointer("output".to_string()));
+                    .get_used_cells_mid_run(vm)
+                    .map_err(|_| RunnerError::FinalStack(Self::NAME))?;
+                let expected = Relocatable::from((self.base(), used));
+                if stop_pointer != expected {
+                    return Err(RunnerError::InvalidStopPointer {
+                        builtin: Self::NAME,
+                        expected,
+                        found: stop_pointer,
+                    });
                 }
-
-                Ok((
-                    pointer.sub_usize(1).map_err(|_| RunnerError::FinalStack)?,
-                    stop_ptr,
-                ))
+                self.stop_ptr = Some(stop_pointer.offset);
+                Ok(pointer
+                    .sub_usize(1)
+                    .map_err(|_| RunnerError::FinalStack(Self::NAME))?)
             } else {
-                Err(RunnerError::FinalStack)
+                Err(RunnerError::FinalStack(Self::NAME))
             }
         } else {
-            let stop_ptr = self.base() as usize;
-            Ok((pointer, stop_ptr))
+            self.stop_ptr = Some(0);
+            Ok(pointer)
         }
     }
 }
@@ -154,7 +298,7 @@ mod tests {
 
     #[test]
     fn final_stack() {
-        let builtin = OutputBuiltinRunner::new(true);
+        let mut builtin = OutputBuiltinRunner::new(true);
 
         let mut vm = vm!();
 
@@ -171,13 +315,14 @@ mod tests {
 
         assert_eq!(
             builtin.final_stack(&vm, pointer).unwrap(),
-            (Relocatable::from((2, 1)), 0)
+            Relocatable::from((2, 1))
         );
+        assert_eq!(builtin.stop_ptr, Some(0));
     }
 
     #[test]
     fn final_stack_error_stop_pointer() {
-        let builtin = OutputBuiltinRunner::new(true);
+        let mut builtin = OutputBuiltinRunner::new(true);
 
         let mut vm = vm!();
 
@@ -194,13 +339,103 @@ mod tests {
 
         assert_eq!(
             builtin.final_stack(&vm, pointer),
-            Err(RunnerError::InvalidStopPointer("output".to_string()))
+            Err(RunnerError::InvalidStopPointer {
+                builtin: "output",
+                expected: Relocatable::from((0, 999)),
+                found: Relocatable::from((0, 0))
+            })
+        );
+    }
+
+    #[test]
+    fn get_state_initial() {
+        let builtin = OutputBuiltinRunner::new(true);
+        assert_eq!(
+            builtin.get_state(),
+            OutputBuiltinState {
+                base: 0,
+                pages: HashMap::new(),
+                attributes: HashMap::new(),
+                stop_ptr: None,
+            }
+        );
+    }
+
+    #[test]
+    fn set_state_and_restore() {
+        let mut builtin = OutputBuiltinRunner::new(true);
+        let original = builtin.get_state();
+
+        let new_state = OutputBuiltinState {
+            base: 4,
+            pages: HashMap::from([(1, PublicMemoryPage { start: 0, size: 2 })]),
+            attributes: HashMap::from([("gps_fact_topology".to_string(), vec![2, 1, 0, 2])]),
+            stop_ptr: Some(2),
+        };
+        builtin.set_state(new_state.clone());
+        assert_eq!(builtin.base(), 4);
+        assert_eq!(builtin.get_state(), new_state);
+        assert_eq!(
+            builtin.get_memory_segment_addresses(),
+            vec![SegmentAddresses::new("output", 4, Some(2))]
+        );
+
+        builtin.set_state(original.clone());
+        assert_eq!(builtin.get_state(), original);
+    }
+
+    #[test]
+    fn add_page_and_attribute() {
+        let mut builtin = OutputBuiltinRunner::new(true);
+        builtin.add_page(1, Relocatable::from((0, 2)), 3).unwrap();
+        builtin.add_attribute("gps_fact_topology".to_string(), vec![2, 1, 0, 2]);
+        assert_eq!(
+            builtin.get_additional_data(),
+            OutputBuiltinAdditionalData {
+                pages: HashMap::from([(1, PublicMemoryPage { start: 2, size: 3 })]),
+                attributes: HashMap::from([("gps_fact_topology".to_string(), vec![2, 1, 0, 2])]),
+            }
+        );
+    }
+
+    #[test]
+    fn add_page_outside_output_segment() {
+        let mut builtin = OutputBuiltinRunner::new(true);
+        assert_eq!(
+            builtin.add_page(1, Relocatable::from((1, 0)), 3),
+            Err(RunnerError::PageNotOnSegment(Relocatable::from((1, 0)), 0))
+        );
+        assert!(builtin.pages.is_empty());
+    }
+
+    #[test]
+    fn get_public_memory() {
+        let mut builtin = OutputBuiltinRunner::new(true);
+        builtin.add_page(1, Relocatable::from((0, 1)), 2).unwrap();
+        builtin.add_page(2, Relocatable::from((0, 4)), 1).unwrap();
+        let mut vm = vm!();
+        vm.segments.segment_used_sizes = Some(vec![6]);
+        assert_eq!(
+            builtin.get_public_memory(&vm),
+            Ok(vec![(0, 0), (1, 1), (2, 1), (3, 0), (4, 2), (5, 0)])
+        );
+    }
+
+    #[test]
+    fn get_public_memory_page_out_of_bounds() {
+        let mut builtin = OutputBuiltinRunner::new(true);
+        builtin.add_page(1, Relocatable::from((0, 2)), 3).unwrap();
+        let mut vm = vm!();
+        vm.segments.segment_used_sizes = Some(vec![4]);
+        assert_eq!(
+            builtin.get_public_memory(&vm),
+            Err(RunnerError::PageOutOfBounds(1, 4))
         );
     }
 
     #[test]
     fn final_stack_error_when_not_included() {
-        let builtin = OutputBuiltinRunner::new(false);
+        let mut builtin = OutputBuiltinRunner::new(false);
 
         let mut vm = vm!();
 
@@ -217,13 +452,14 @@ mod tests {
 
         assert_eq!(
             builtin.final_stack(&vm, pointer).unwrap(),
-            (Relocatable::from((2, 2)), 0)
+            Relocatable::from((2, 2))
         );
+        assert_eq!(builtin.stop_ptr, Some(0));
     }
 
     #[test]
     fn final_stack_error_non_relocatable() {
-        let builtin = OutputBuiltinRunner::new(true);
+        let mut builtin = OutputBuiltinRunner::new(true);
 
         let mut vm = vm!();
 
@@ -240,7 +476,7 @@ mod tests {
 
         assert_eq!(
             builtin.final_stack(&vm, pointer),
-            Err(RunnerError::FinalStack)
+            Err(RunnerError::FinalStack("output"))
         );
     }
 
@@ -272,7 +508,9 @@ mod tests {
         let mut builtin = OutputBuiltinRunner::new(true);
         let mut segments = MemorySegmentManager::new();
         let mut memory = Memory::new();
-        builtin.initialize_segments(&mut segments, &mut memory);
+        builtin
+            .initialize_segments(&mut segments, &mut memory)
+            .unwrap();
         assert_eq!(builtin.base, 0);
     }
 
@@ -294,7 +532,7 @@ mod tests {
 
         assert_eq!(
             builtin.get_memory_segment_addresses(),
-            ("output", (0, None)),
+            vec![SegmentAddresses::new("output", 0, None)],
         );
     }
 
@@ -305,7 +543,7 @@ mod tests {
 
         assert_eq!(
             builtin.get_memory_accesses(&vm),
-            Err(MemoryError::MissingSegmentUsedSizes),
+            Err(MemoryError::MissingSegmentUsedSizes(0)),
         );
     }
 
@@ -324,6 +562,7 @@ mod tests {
         let mut vm = vm!();
 
         vm.segments.segment_used_sizes = Some(vec![4]);
+        vm.memory = memory![((0, 0), 1), ((0, 1), 2), ((0, 2), 3), ((0, 3), 4)];
         assert_eq!(
             builtin.get_memory_accesses(&vm),
             Ok(vec![
@@ -342,7 +581,7 @@ mod tests {
 
         assert_eq!(
             builtin.get_used_cells(&vm),
-            Err(MemoryError::MissingSegmentUsedSizes)
+            Err(MemoryError::MissingSegmentUsedSizes(0))
         );
     }
 
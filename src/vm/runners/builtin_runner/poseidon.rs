@@ -0,0 +1,397 @@
+use std::{cell::RefCell, collections::HashMap};
+
+use crate::{
+    math_utils::poseidon_permute,
+    types::{
+        instance_definitions::poseidon_instance_def::{
+            PoseidonInstanceDef, CELLS_PER_POSEIDON, INPUT_CELLS_PER_POSEIDON,
+        },
+        relocatable::{MaybeRelocatable, Relocatable},
+    },
+    vm::{
+        errors::{memory_errors::MemoryError, runner_errors::RunnerError},
+        runners::{
+            air_private_input::{PrivateInput, PrivateInputPoseidonState},
+            builtin_runner::{get_allocated_instances, get_instances_inputs, SegmentAddresses},
+        },
+        vm_core::VirtualMachine,
+        vm_memory::{memory::Memory, memory_segments::MemorySegmentManager},
+    },
+};
+use felt::Felt;
+use num_integer::div_ceil;
+
+#[derive(Debug, Clone)]
+pub struct PoseidonBuiltinRunner {
+    ratio: u32,
+    pub(crate) ratio_den: u32,
+    pub base: isize,
+    pub(crate) initialized: bool,
+    pub(crate) cells_per_instance: u32,
+    pub(crate) n_input_cells: u32,
+    pub(crate) stop_ptr: Option<usize>,
+    pub(crate) included: bool,
+    instances_per_component: u32,
+    // The output cells of the instances already permuted, by offset in the segment, so that
+    // deducing the other outputs of an instance doesn't compute the permutation again
+    // Therefore need interior mutability
+    cache: RefCell<HashMap<usize, Felt>>,
+}
+
+impl PoseidonBuiltinRunner {
+    pub const NAME: &str = "poseidon";
+
+    pub(crate) fn new(instance_def: &PoseidonInstanceDef, included: bool) -> Self {
+        PoseidonBuiltinRunner {
+            base: 0,
+            initialized: false,
+            ratio: instance_def.ratio,
+            ratio_den: instance_def.ratio_den,
+            cells_per_instance: CELLS_PER_POSEIDON,
+            n_input_cells: INPUT_CELLS_PER_POSEIDON,
+            stop_ptr: None,
+            included,
+            instances_per_component: 1,
+            cache: RefCell::new(HashMap::new()),
+        }
+    }
+
+    pub fn initialize_segments(
+        &mut self,
+        segments: &mut MemorySegmentManager,
+        memory: &mut Memory,
+    ) -> Result<(), RunnerError> {
+        if self.initialized {
+            return Err(RunnerError::BuiltinAlreadyInitialized(Self::NAME));
+        }
+        self.base = segments.add(memory).segment_index;
+        self.initialized = true;
+        Ok(())
+    }
+
+    pub fn initial_stack(&self) -> Vec<MaybeRelocatable> {
+        if self.included {
+            vec![MaybeRelocatable::from((self.base, 0))]
+        } else {
+            vec![]
+        }
+    }
+
+    pub fn base(&self) -> isize {
+        self.base
+    }
+
+    pub fn is_initialized(&self) -> bool {
+        self.initialized
+    }
+
+    pub(crate) fn clear_cache(&mut self) {
+        self.cache.get_mut().clear();
+    }
+
+    pub fn ratio(&self) -> u32 {
+        self.ratio
+    }
+
+    pub fn ratio_den(&self) -> u32 {
+        self.ratio_den
+    }
+
+    pub fn add_validation_rule(&self, _memory: &mut Memory) -> Result<(), RunnerError> {
+        Ok(())
+    }
+
+    pub fn deduce_memory_cell(
+        &self,
+        address: &Relocatable,
+        memory: &Memory,
+    ) -> Result<Option<MaybeRelocatable>, RunnerError> {
+        let index = address.offset % self.cells_per_instance as usize;
+        if index < self.n_input_cells as usize {
+            return Ok(None);
+        }
+        if let Some(value) = self.cache.borrow().get(&address.offset) {
+            return Ok(Some(value.into()));
+        }
+
+        let first_input_offset = address.offset - index;
+        let mut state = [Felt::default(), Felt::default(), Felt::default()];
+        for (i, input) in state.iter_mut().enumerate() {
+            let input_addr = Relocatable::from((address.segment_index, first_input_offset + i));
+            match memory.get(&input_addr) {
+                Ok(Some(value)) => match value.as_ref() {
+                    MaybeRelocatable::Int(value) => *input = value.clone(),
+                    _ => return Err(RunnerError::FoundNonInt),
+                },
+                _ => return Ok(None),
+            }
+        }
+
+        let first_output_offset = first_input_offset + self.n_input_cells as usize;
+        let mut cache = self.cache.borrow_mut();
+        for (i, output) in poseidon_permute(&state)?.into_iter().enumerate() {
+            cache.insert(first_output_offset + i, output);
+        }
+        Ok(cache.get(&address.offset).map(MaybeRelocatable::from))
+    }
+
+    pub fn get_allocated_memory_units(&self, vm: &VirtualMachine) -> Result<usize, MemoryError> {
+        let value =
+            get_allocated_instances(Self::NAME, vm.current_step, self.ratio, self.ratio_den)?;
+        Ok(self.cells_per_instance as usize * value)
+    }
+
+    pub fn air_private_input(&self, memory: &Memory) -> Vec<PrivateInput> {
+        get_instances_inputs::<3>(memory, self.base, self.cells_per_instance)
+            .into_iter()
+            .map(|(index, [input_s0, input_s1, input_s2])| {
+                PrivateInput::PoseidonState(PrivateInputPoseidonState {
+                    index,
+                    input_s0,
+                    input_s1,
+                    input_s2,
+                })
+            })
+            .collect()
+    }
+
+    pub fn get_memory_segment_addresses(&self) -> Vec<SegmentAddresses> {
+        vec![SegmentAddresses::new(Self::NAME, self.base, self.stop_ptr)]
+    }
+
+    pub fn get_used_cells(&self, vm: &VirtualMachine) -> Result<usize, MemoryError> {
+        let base = self.base();
+        let index = base
+            .try_into()
+            .map_err(|_| MemoryError::AddressInTemporarySegment(base))?;
+        vm.segments
+            .get_segment_used_size(index)
+            .ok_or(MemoryError::MissingSegmentUsedSizes(index))
+    }
+
+    pub fn get_used_cells_mid_run(&self, vm: &VirtualMachine) -> Result<usize, MemoryError> {
+        let base = self.base();
+        let index = base
+            .try_into()
+            .map_err(|_| MemoryError::AddressInTemporarySegment(base))?;
+        vm.segments
+            .get_segment_used_size_mid_run(index, &vm.memory)
+            .ok_or(MemoryError::UnallocatedSegment(index, vm.memory.data.len()))
+    }
+
+    pub fn get_used_cells_and_allocated_size(
+        &self,
+        vm: &VirtualMachine,
+    ) -> Result<(usize, usize), MemoryError> {
+        let cells_per_instance = self.cells_per_instance;
+        let min_step = div_ceil(
+            self.ratio as usize * self.instances_per_component as usize,
+            self.ratio_den as usize,
+        );
+        if vm.current_step < min_step {
+            Err(MemoryError::MinStepNotReached {
+                builtin: Self::NAME,
+                min_step,
+            })
+        } else {
+            let used = self.get_used_cells(vm)?;
+            let size = cells_per_instance as usize
+                * get_allocated_instances(Self::NAME, vm.current_step, self.ratio, self.ratio_den)?;
+            if used > size {
+                return Err(MemoryError::InsufficientAllocatedCells {
+                    builtin: Self::NAME,
+                    used,
+                    allocated: size,
+                });
+            }
+            Ok((used, size))
+        }
+    }
+
+    pub fn get_used_instances(&self, vm: &VirtualMachine) -> Result<usize, MemoryError> {
+        let used_cells = self.get_used_cells_mid_run(vm)?;
+        Ok(div_ceil(used_cells, self.cells_per_instance as usize))
+    }
+
+    pub fn final_stack(
+        &mut self,
+        vm: &VirtualMachine,
+        pointer: Relocatable,
+    ) -> Result<Relocatable, RunnerError> {
+        if self.included {
+            if let Ok(stop_pointer) = vm.get_relocatable(
+                &(pointer.sub_usize(1)).map_err(|_| RunnerError::FinalStack(Self::NAME))?,
+            ) {
+                let num_instances = self
+                    .get_used_instances(vm)
+                    .map_err(|_| RunnerError::FinalStack(Self::NAME))?;
+                let used_cells = num_instances * self.cells_per_instance as usize;
+                let expected = Relocatable::from((self.base(), used_cells));
+                if stop_pointer != expected {
+                    return Err(RunnerError::InvalidStopPointer {
+                        builtin: Self::NAME,
+                        expected,
+                        found: stop_pointer,
+                    });
+                }
+                self.stop_ptr = Some(stop_pointer.offset);
+                Ok(pointer
+                    .sub_usize(1)
+                    .map_err(|_| RunnerError::FinalStack(Self::NAME))?)
+            } else {
+                Err(RunnerError::FinalStack(Self::NAME))
+            }
+        } else {
+            self.stop_ptr = Some(0);
+            Ok(pointer)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        hint_processor::builtin_hint_processor::builtin_hint_processor_definition::BuiltinHintProcessor,
+        types::program::Program,
+        utils::test_utils::*,
+        vm::runners::cairo_runner::{CairoRunner, RunResources},
+    };
+    use felt::felt_str;
+
+    fn hash_inputs() -> Memory {
+        memory![
+            (
+                (0, 0),
+                (
+                    "20140578132262368862000200556100848058304535795501751794007564626976013246",
+                    10
+                )
+            ),
+            (
+                (0, 1),
+                (
+                    "56341294147480832855401895867265471615027720793558289370730438999346154474",
+                    10
+                )
+            ),
+            ((0, 2), 2)
+        ]
+    }
+
+    #[test]
+    fn deduce_memory_cell_poseidon_hash() {
+        let memory = hash_inputs();
+        let builtin = PoseidonBuiltinRunner::new(&PoseidonInstanceDef::default(), true);
+        assert_eq!(
+            builtin.deduce_memory_cell(&(0, 3).into(), &memory),
+            Ok(Some(MaybeRelocatable::from(felt_str!(
+                "75540825a6ecc5dc7d7c2f5f868164182742227f1367d66c43ee51ec7937a81",
+                16
+            ))))
+        );
+    }
+
+    #[test]
+    fn deduce_memory_cell_caches_the_instance_outputs() {
+        let memory = hash_inputs();
+        let builtin = PoseidonBuiltinRunner::new(&PoseidonInstanceDef::default(), true);
+        let outputs = (3..6)
+            .map(|offset| builtin.deduce_memory_cell(&(0, offset).into(), &memory))
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(builtin.cache.borrow().len(), 3);
+
+        // Deducing again, even once the inputs are gone, reads the cached outputs
+        let empty_memory = memory![((0, 0), 0)];
+        for (offset, output) in (3..6).zip(outputs) {
+            assert!(output.is_some());
+            assert_eq!(
+                builtin.deduce_memory_cell(&(0, offset).into(), &empty_memory),
+                Ok(output)
+            );
+        }
+    }
+
+    #[test]
+    fn deduce_memory_cell_input_cell() {
+        let memory = hash_inputs();
+        let builtin = PoseidonBuiltinRunner::new(&PoseidonInstanceDef::default(), true);
+        assert_eq!(
+            builtin.deduce_memory_cell(&(0, 2).into(), &memory),
+            Ok(None)
+        );
+    }
+
+    #[test]
+    fn deduce_memory_cell_missing_input() {
+        let memory = memory![((0, 0), 1), ((0, 2), 2)];
+        let builtin = PoseidonBuiltinRunner::new(&PoseidonInstanceDef::default(), true);
+        assert_eq!(
+            builtin.deduce_memory_cell(&(0, 4).into(), &memory),
+            Ok(None)
+        );
+        assert!(builtin.cache.borrow().is_empty());
+    }
+
+    #[test]
+    fn deduce_memory_cell_relocatable_input() {
+        let memory = memory![((0, 0), 1), ((0, 1), (1, 0)), ((0, 2), 2)];
+        let builtin = PoseidonBuiltinRunner::new(&PoseidonInstanceDef::default(), true);
+        assert_eq!(
+            builtin.deduce_memory_cell(&(0, 5).into(), &memory),
+            Err(RunnerError::FoundNonInt)
+        );
+    }
+
+    #[test]
+    fn get_used_instances() {
+        let builtin = PoseidonBuiltinRunner::new(&PoseidonInstanceDef::default(), true);
+        let mut vm = vm!();
+        vm.memory = memory![((0, 0), 1), ((0, 1), 2), ((0, 2), 3), ((0, 6), 4)];
+        assert_eq!(builtin.get_used_instances(&vm), Ok(2));
+    }
+
+    #[test]
+    fn run_poseidon_hash_program() {
+        let program = Program::from_fixture("poseidon_hash");
+        let mut cairo_runner = cairo_runner!(program);
+        let mut vm = vm!();
+        let mut hint_processor = BuiltinHintProcessor::new_empty();
+        let end = cairo_runner.initialize(&mut vm).unwrap();
+        cairo_runner
+            .run_until_pc(
+                end,
+                &mut RunResources::default(),
+                &mut vm,
+                &mut hint_processor,
+            )
+            .unwrap();
+        cairo_runner
+            .end_run(false, false, &mut vm, &mut hint_processor)
+            .unwrap();
+
+        let (name, builtin) = &vm.builtin_runners[0];
+        assert_eq!(name, "poseidon");
+        let mut builtin = builtin.clone();
+        assert_eq!(builtin.get_used_instances(&vm), Ok(1));
+        // main returns the poseidon pointer past the only instance
+        let pointer = vm.get_ap();
+        assert_eq!(
+            builtin.final_stack(&vm, pointer),
+            Ok(pointer.sub_usize(1).unwrap())
+        );
+        assert_eq!(builtin.get_memory_segment_addresses()[0].stop_ptr, Some(6));
+        check_memory!(
+            vm.memory,
+            (
+                (2, 3),
+                (
+                    "3316817166671974741821520388381754819442969945697309900446133225793489435265",
+                    10
+                )
+            )
+        );
+        assert_eq!(builtin.run_security_checks(&mut vm), Ok(()));
+    }
+}
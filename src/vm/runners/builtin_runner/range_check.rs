@@ -1,11 +1,16 @@
 use crate::{
-    math_utils::safe_div_usize,
     types::{
-        instance_definitions::range_check_instance_def::CELLS_PER_RANGE_CHECK,
+        instance_definitions::range_check_instance_def::{
+            CELLS_PER_RANGE_CHECK, N_PARTS_RANGE_CHECK96,
+        },
         relocatable::{MaybeRelocatable, Relocatable},
     },
     vm::{
         errors::{memory_errors::MemoryError, runner_errors::RunnerError},
+        runners::{
+            air_private_input::{PrivateInput, PrivateInputValue},
+            builtin_runner::{get_allocated_instances, get_instances_inputs, SegmentAddresses},
+        },
         vm_core::VirtualMachine,
         vm_memory::{
             memory::{Memory, ValidationRule},
@@ -13,29 +18,37 @@ use crate::{
         },
     },
 };
-use felt::{Felt, NewFelt};
-use num_integer::Integer;
+use felt::{Felt, FeltOps, NewFelt};
+use num_bigint::BigUint;
+use num_integer::{div_ceil, Integer};
 use num_traits::{One, ToPrimitive, Zero};
 use std::{
     cmp::{max, min},
+    collections::BTreeMap,
     ops::Shl,
 };
 
 #[derive(Debug, Clone)]
 pub struct RangeCheckBuiltinRunner {
+    pub(crate) name: &'static str,
     ratio: u32,
-    base: isize,
+    pub(crate) ratio_den: u32,
+    pub(crate) base: isize,
+    pub(crate) initialized: bool,
     pub(crate) stop_ptr: Option<usize>,
     pub(crate) cells_per_instance: u32,
     pub(crate) n_input_cells: u32,
     inner_rc_bound: usize,
     pub _bound: Option<Felt>,
-    pub(crate) _included: bool,
+    pub(crate) included: bool,
     n_parts: u32,
     instances_per_component: u32,
 }
 
 impl RangeCheckBuiltinRunner {
+    pub const NAME: &str = "range_check";
+    pub const RANGE_CHECK96_NAME: &str = "range_check96";
+
     pub fn new(ratio: u32, n_parts: u32, included: bool) -> RangeCheckBuiltinRunner {
         let inner_rc_bound = 1_usize << 16;
 
@@ -47,29 +60,45 @@ impl RangeCheckBuiltinRunner {
         };
 
         RangeCheckBuiltinRunner {
+            name: Self::NAME,
             ratio,
+            ratio_den: 1,
             base: 0,
+            initialized: false,
             stop_ptr: None,
             cells_per_instance: CELLS_PER_RANGE_CHECK,
             n_input_cells: CELLS_PER_RANGE_CHECK,
             inner_rc_bound,
             _bound,
-            _included: included,
+            included,
             n_parts,
             instances_per_component: 1,
         }
     }
 
+    /// Creates a range_check96 builtin, which checks that its cells are below 2^96.
+    pub fn new_range_check96(ratio: u32, included: bool) -> RangeCheckBuiltinRunner {
+        RangeCheckBuiltinRunner {
+            name: Self::RANGE_CHECK96_NAME,
+            ..RangeCheckBuiltinRunner::new(ratio, N_PARTS_RANGE_CHECK96, included)
+        }
+    }
+
     pub fn initialize_segments(
         &mut self,
         segments: &mut MemorySegmentManager,
         memory: &mut Memory,
-    ) {
-        self.base = segments.add(memory).segment_index
+    ) -> Result<(), RunnerError> {
+        if self.initialized {
+            return Err(RunnerError::BuiltinAlreadyInitialized(self.name));
+        }
+        self.base = segments.add(memory).segment_index;
+        self.initialized = true;
+        Ok(())
     }
 
     pub fn initial_stack(&self) -> Vec<MaybeRelocatable> {
-        if self._included {
+        if self.included {
             vec![MaybeRelocatable::from((self.base, 0))]
         } else {
             vec![]
@@ -80,36 +109,49 @@ impl RangeCheckBuiltinRunner {
         self.base
     }
 
+    pub fn is_initialized(&self) -> bool {
+        self.initialized
+    }
+
     pub fn ratio(&self) -> u32 {
         self.ratio
     }
 
+    pub fn ratio_den(&self) -> u32 {
+        self.ratio_den
+    }
+
     pub fn add_validation_rule(&self, memory: &mut Memory) -> Result<(), RunnerError> {
+        // Without a bound, the range checked values span the whole field
+        let bound = self._bound.clone();
         let rule: ValidationRule = ValidationRule(Box::new(
-            |memory: &Memory,
-             address: &MaybeRelocatable|
-             -> Result<Vec<MaybeRelocatable>, MemoryError> {
+            move |memory: &Memory,
+                  address: &MaybeRelocatable|
+                  -> Result<Vec<MaybeRelocatable>, MemoryError> {
                 if let MaybeRelocatable::Int(ref num) = memory
                     .get(address)?
                     .ok_or(MemoryError::FoundNonInt)?
                     .into_owned()
                 {
-                    if &Felt::zero() <= num && num < &Felt::one().shl(128_usize) {
+                    if !matches!(&bound, Some(bound) if num >= bound) {
                         Ok(vec![address.to_owned()])
                     } else {
-                        Err(MemoryError::NumOutOfBounds)
+                        Err(MemoryError::NumOutOfBounds {
+                            addr: address.try_into()?,
+                            value: num.clone(),
+                        })
                     }
                 } else {
                     Err(MemoryError::FoundNonInt)
                 }
             },
         ));
-        memory.add_validation_rule(
-            self.base
-                .to_usize()
-                .ok_or(RunnerError::RunnerInTemporarySegment(self.base))?,
-            rule,
-        );
+        match self.base.to_usize() {
+            Some(segment_index) => memory.add_validation_rule(segment_index, rule),
+            None => memory
+                .add_temp_validation_rule(self.base, rule)
+                .map_err(RunnerError::MemoryValidationError)?,
+        }
         Ok(())
     }
 
@@ -122,108 +164,163 @@ impl RangeCheckBuiltinRunner {
     }
 
     pub fn get_allocated_memory_units(&self, vm: &VirtualMachine) -> Result<usize, MemoryError> {
-        let value = safe_div_usize(vm.current_step, self.ratio as usize)
-            .map_err(|_| MemoryError::ErrorCalculatingMemoryUnits)?;
+        let value =
+            get_allocated_instances(self.name, vm.current_step, self.ratio, self.ratio_den)?;
         Ok(self.cells_per_instance as usize * value)
     }
 
-    pub fn get_memory_segment_addresses(&self) -> (&'static str, (isize, Option<usize>)) {
-        ("range_check", (self.base, self.stop_ptr))
+    pub fn air_private_input(&self, memory: &Memory) -> Vec<PrivateInput> {
+        get_instances_inputs::<1>(memory, self.base, self.cells_per_instance)
+            .into_iter()
+            .map(|(index, [value])| PrivateInput::Value(PrivateInputValue { index, value }))
+            .collect()
+    }
+
+    pub fn get_memory_segment_addresses(&self) -> Vec<SegmentAddresses> {
+        vec![SegmentAddresses::new(self.name, self.base, self.stop_ptr)]
     }
 
     pub fn get_used_cells(&self, vm: &VirtualMachine) -> Result<usize, MemoryError> {
         let base = self.base();
+        let index = base
+            .try_into()
+            .map_err(|_| MemoryError::AddressInTemporarySegment(base))?;
         vm.segments
-            .get_segment_used_size(
-                base.try_into()
-                    .map_err(|_| MemoryError::AddressInTemporarySegment(base))?,
-            )
-            .ok_or(MemoryError::MissingSegmentUsedSizes)
+            .get_segment_used_size(index)
+            .ok_or(MemoryError::MissingSegmentUsedSizes(index))
+    }
+
+    pub fn get_used_cells_mid_run(&self, vm: &VirtualMachine) -> Result<usize, MemoryError> {
+        let base = self.base();
+        let index = base
+            .try_into()
+            .map_err(|_| MemoryError::AddressInTemporarySegment(base))?;
+        vm.segments
+            .get_segment_used_size_mid_run(index, &vm.memory)
+            .ok_or(MemoryError::UnallocatedSegment(index, vm.memory.data.len()))
     }
 
     pub fn get_used_cells_and_allocated_size(
         &self,
         vm: &VirtualMachine,
     ) -> Result<(usize, usize), MemoryError> {
-        let ratio = self.ratio as usize;
         let cells_per_instance = self.cells_per_instance;
-        let min_step = ratio * self.instances_per_component as usize;
+        let min_step = div_ceil(
+            self.ratio as usize * self.instances_per_component as usize,
+            self.ratio_den as usize,
+        );
         if vm.current_step < min_step {
-            Err(MemoryError::InsufficientAllocatedCells)
+            Err(MemoryError::MinStepNotReached {
+                builtin: self.name,
+                min_step,
+            })
         } else {
             let used = self.get_used_cells(vm)?;
             let size = cells_per_instance as usize
-                * safe_div_usize(vm.current_step, ratio)
-                    .map_err(|_| MemoryError::InsufficientAllocatedCells)?;
+                * get_allocated_instances(self.name, vm.current_step, self.ratio, self.ratio_den)?;
             if used > size {
-                return Err(MemoryError::InsufficientAllocatedCells);
+                return Err(MemoryError::InsufficientAllocatedCells {
+                    builtin: self.name,
+                    used,
+                    allocated: size,
+                });
             }
             Ok((used, size))
         }
     }
 
+    /// Splits the value into the n_parts limbs checked by the builtin, starting from the least
+    /// significant one.
+    pub(crate) fn limbs(&self, value: &Felt) -> Vec<Felt> {
+        let inner_rc_bound = BigUint::from(self.inner_rc_bound);
+        let mut value = value.to_biguint();
+        (0..self.n_parts)
+            .map(|_| {
+                let limb = &value % &inner_rc_bound;
+                value /= &inner_rc_bound;
+                Felt::from(limb)
+            })
+            .collect()
+    }
+
     pub fn get_range_check_usage(&self, memory: &Memory) -> Option<(usize, usize)> {
         let mut rc_bounds: Option<(usize, usize)> = None;
         let range_check_segment = memory.data.get(self.base as usize)?;
-        let inner_rc_bound = Felt::new(self.inner_rc_bound);
+        let part_bits = self.inner_rc_bound.trailing_zeros();
+        let part_mask = self.inner_rc_bound as u64 - 1;
         for value in range_check_segment {
-            //Split val into n_parts parts.
-            for _ in 0..self.n_parts {
-                let part_val = value
-                    .as_ref()?
-                    .get_int_ref()
-                    .ok()?
-                    .mod_floor(&inner_rc_bound)
-                    .to_usize()?;
+            let digits: Vec<u64> = value
+                .as_ref()?
+                .get_int_ref()
+                .ok()?
+                .iter_u64_digits()
+                .collect();
+            //Split val into n_parts parts, the bound being a power of two that divides 2^64.
+            for i in 0..self.n_parts {
+                let offset = i * part_bits;
+                let digit = digits.get((offset / 64) as usize).copied().unwrap_or(0);
+                let part_val = ((digit >> (offset % 64)) & part_mask) as usize;
                 rc_bounds = Some(match rc_bounds {
                     None => (part_val, part_val),
-                    Some((rc_min, rc_max)) => {
-                        let rc_min = min(rc_min, part_val);
-                        let rc_max = max(rc_max, part_val);
-
-                        (rc_min, rc_max)
-                    }
+                    Some((rc_min, rc_max)) => (min(rc_min, part_val), max(rc_max, part_val)),
                 });
             }
         }
         rc_bounds
     }
 
+    ///Counts the occurrences of each 16 bit limb of the range checked values, each value being
+    ///split into `n_parts` limbs. Returns None if the segment has a hole or a relocatable value.
+    pub fn get_usage_histogram(&self, memory: &Memory) -> Option<BTreeMap<usize, usize>> {
+        let range_check_segment = memory.data.get(usize::try_from(self.base).ok()?)?;
+        let inner_rc_bound = BigUint::from(self.inner_rc_bound);
+        let mut histogram = BTreeMap::new();
+        for value in range_check_segment {
+            let mut value = value.as_ref()?.get_int_ref().ok()?.to_biguint();
+            for _ in 0..self.n_parts {
+                let (rest, limb) = value.div_rem(&inner_rc_bound);
+                *histogram.entry(limb.to_usize()?).or_insert(0) += 1;
+                value = rest;
+            }
+        }
+        Some(histogram)
+    }
+
     pub fn get_used_instances(&self, vm: &VirtualMachine) -> Result<usize, MemoryError> {
-        self.get_used_cells(vm)
+        self.get_used_cells_mid_run(vm)
     }
 
     pub fn final_stack(
-        &self,
+        &mut self,
         vm: &VirtualMachine,
         pointer: Relocatable,
-    ) -> Result<(Relocatable, usize), RunnerError> {
-        if self._included {
-            if let Ok(stop_pointer) =
-                vm.get_relocatable(&(pointer.sub_usize(1)).map_err(|_| RunnerError::FinalStack)?)
-            {
-                if self.base() != stop_pointer.segment_index {
-                    return Err(RunnerError::InvalidStopPointer("range_check".to_string()));
-                }
-                let stop_ptr = stop_pointer.offset;
+    ) -> Result<Relocatable, RunnerError> {
+        if self.included {
+            if let Ok(stop_pointer) = vm.get_relocatable(
+                &(pointer.sub_usize(1)).map_err(|_| RunnerError::FinalStack(self.name))?,
+            ) {
                 let num_instances = self
                     .get_used_instances(vm)
-                    .map_err(|_| RunnerError::FinalStack)?;
+                    .map_err(|_| RunnerError::FinalStack(self.name))?;
                 let used_cells = num_instances * self.cells_per_instance as usize;
-                if stop_ptr != used_cells {
-                    return Err(RunnerError::InvalidStopPointer("range_check".to_string()));
+                let expected = Relocatable::from((self.base(), used_cells));
+                if stop_pointer != expected {
+                    return Err(RunnerError::InvalidStopPointer {
+                        builtin: self.name,
+                        expected,
+                        found: stop_pointer,
+                    });
                 }
-
-                Ok((
-                    pointer.sub_usize(1).map_err(|_| RunnerError::FinalStack)?,
-                    stop_ptr,
-                ))
+                self.stop_ptr = Some(stop_pointer.offset);
+                Ok(pointer
+                    .sub_usize(1)
+                    .map_err(|_| RunnerError::FinalStack(self.name))?)
             } else {
-                Err(RunnerError::FinalStack)
+                Err(RunnerError::FinalStack(self.name))
             }
         } else {
-            let stop_ptr = self.base() as usize;
-            Ok((pointer, stop_ptr))
+            self.stop_ptr = Some(0);
+            Ok(pointer)
         }
     }
 
@@ -245,11 +342,83 @@ mod tests {
         types::program::Program,
         utils::test_utils::*,
         vm::{
-            runners::{builtin_runner::BuiltinRunner, cairo_runner::CairoRunner},
+            runners::{
+                builtin_runner::BuiltinRunner,
+                cairo_runner::{CairoRunner, RunResources},
+            },
             vm_core::VirtualMachine,
         },
     };
 
+    // Writes the value in the first cell of the builtin's segment and validates it
+    fn validate_range_check_cell(
+        builtin: &RangeCheckBuiltinRunner,
+        value: Felt,
+    ) -> Result<(), MemoryError> {
+        let mut builtin = builtin.clone();
+        let mut segments = MemorySegmentManager::new();
+        let mut memory = Memory::new();
+        builtin
+            .initialize_segments(&mut segments, &mut memory)
+            .unwrap();
+        memory
+            .insert(
+                &MaybeRelocatable::from((0, 0)),
+                &MaybeRelocatable::from(value),
+            )
+            .unwrap();
+        builtin.add_validation_rule(&mut memory).unwrap();
+        memory.validate_existing_memory()
+    }
+
+    #[test]
+    fn validation_rule_range_check96_bound() {
+        let builtin = RangeCheckBuiltinRunner::new_range_check96(8, true);
+        let bound = Felt::one() << 96_u32;
+        assert_eq!(
+            validate_range_check_cell(&builtin, bound.clone() - 1_u32),
+            Ok(())
+        );
+        assert_eq!(
+            validate_range_check_cell(&builtin, bound.clone()),
+            Err(MemoryError::NumOutOfBounds {
+                addr: Relocatable::from((0, 0)),
+                value: bound,
+            })
+        );
+    }
+
+    #[test]
+    fn validation_rule_range_check_bound() {
+        let builtin = RangeCheckBuiltinRunner::new(8, 8, true);
+        let bound = Felt::one() << 128_u32;
+        assert_eq!(
+            validate_range_check_cell(&builtin, Felt::one() << 96_u32),
+            Ok(())
+        );
+        assert_eq!(
+            validate_range_check_cell(&builtin, bound.clone() - 1_u32),
+            Ok(())
+        );
+        assert_eq!(
+            validate_range_check_cell(&builtin, bound.clone()),
+            Err(MemoryError::NumOutOfBounds {
+                addr: Relocatable::from((0, 0)),
+                value: bound,
+            })
+        );
+    }
+
+    #[test]
+    fn get_memory_segment_addresses_range_check96() {
+        let builtin: BuiltinRunner = RangeCheckBuiltinRunner::new_range_check96(8, true).into();
+        assert_eq!(builtin.name(), "range_check96");
+        assert_eq!(
+            builtin.get_memory_segment_addresses(),
+            vec![SegmentAddresses::new("range_check96", 0, None)],
+        );
+    }
+
     #[test]
     fn get_used_instances() {
         let builtin = RangeCheckBuiltinRunner::new(10, 12, true);
@@ -270,7 +439,7 @@ mod tests {
 
     #[test]
     fn final_stack() {
-        let builtin = RangeCheckBuiltinRunner::new(10, 12, true);
+        let mut builtin = RangeCheckBuiltinRunner::new(10, 12, true);
 
         let mut vm = vm!();
 
@@ -287,13 +456,14 @@ mod tests {
 
         assert_eq!(
             builtin.final_stack(&vm, pointer).unwrap(),
-            (Relocatable::from((2, 1)), 0)
+            Relocatable::from((2, 1))
         );
+        assert_eq!(builtin.stop_ptr, Some(0));
     }
 
     #[test]
     fn final_stack_error_stop_pointer() {
-        let builtin = RangeCheckBuiltinRunner::new(10, 12, true);
+        let mut builtin = RangeCheckBuiltinRunner::new(10, 12, true);
 
         let mut vm = vm!();
 
@@ -310,13 +480,17 @@ mod tests {
 
         assert_eq!(
             builtin.final_stack(&vm, pointer),
-            Err(RunnerError::InvalidStopPointer("range_check".to_string()))
+            Err(RunnerError::InvalidStopPointer {
+                builtin: "range_check",
+                expected: Relocatable::from((0, 999)),
+                found: Relocatable::from((0, 0))
+            })
         );
     }
 
     #[test]
     fn final_stack_error_when_not_included() {
-        let builtin = RangeCheckBuiltinRunner::new(10, 12, false);
+        let mut builtin = RangeCheckBuiltinRunner::new(10, 12, false);
 
         let mut vm = vm!();
 
@@ -333,13 +507,14 @@ mod tests {
 
         assert_eq!(
             builtin.final_stack(&vm, pointer).unwrap(),
-            (Relocatable::from((2, 2)), 0)
+            Relocatable::from((2, 2))
         );
+        assert_eq!(builtin.stop_ptr, Some(0));
     }
 
     #[test]
     fn final_stack_error_non_relocatable() {
-        let builtin = RangeCheckBuiltinRunner::new(10, 12, true);
+        let mut builtin = RangeCheckBuiltinRunner::new(10, 12, true);
 
         let mut vm = vm!();
 
@@ -356,10 +531,43 @@ mod tests {
 
         assert_eq!(
             builtin.final_stack(&vm, pointer),
-            Err(RunnerError::FinalStack)
+            Err(RunnerError::FinalStack("range_check"))
         );
     }
 
+    #[test]
+    fn final_stack_after_run_until_pc() {
+        let program = Program::from_fixture("check_range");
+
+        let mut cairo_runner = cairo_runner!(program);
+
+        let mut vm = vm!();
+
+        let mut hint_processor = BuiltinHintProcessor::new_empty();
+
+        let address = cairo_runner.initialize(&mut vm).unwrap();
+
+        cairo_runner
+            .run_until_pc(
+                address,
+                &mut RunResources::default(),
+                &mut vm,
+                &mut hint_processor,
+            )
+            .unwrap();
+
+        // The segment sizes haven't been computed, so the used cells are read from memory
+        assert_eq!(vm.segments.segment_used_sizes, None);
+        let mut builtin = vm.get_range_check_builtin().unwrap().clone();
+        let pointer = vm.get_ap();
+
+        assert_eq!(
+            builtin.final_stack(&vm, pointer),
+            Ok(pointer.sub_usize(1).unwrap())
+        );
+        assert_eq!(builtin.stop_ptr, Some(2));
+    }
+
     #[test]
     fn get_used_cells_and_allocated_size_test() {
         let builtin: BuiltinRunner = RangeCheckBuiltinRunner::new(10, 12, true).into();
@@ -368,29 +576,7 @@ mod tests {
 
         vm.segments.segment_used_sizes = Some(vec![0]);
 
-        let program = program!(
-            builtins = vec![String::from("pedersen")],
-            data = vec_data!(
-                (4612671182993129469_i64),
-                (5189976364521848832_i64),
-                (18446744073709551615_i128),
-                (5199546496550207487_i64),
-                (4612389712311386111_i64),
-                (5198983563776393216_i64),
-                (2),
-                (2345108766317314046_i64),
-                (5191102247248822272_i64),
-                (5189976364521848832_i64),
-                (7),
-                (1226245742482522112_i64),
-                ((
-                    "3618502788666131213697322783095070105623107215331596699973092056135872020470",
-                    10
-                )),
-                (2345108766317314046_i64)
-            ),
-            main = Some(8),
-        );
+        let program = Program::from_fixture("check_range");
 
         let mut cairo_runner = cairo_runner!(program);
 
@@ -399,41 +585,46 @@ mod tests {
         let address = cairo_runner.initialize(&mut vm).unwrap();
 
         cairo_runner
-            .run_until_pc(address, &mut vm, &mut hint_processor)
+            .run_until_pc(
+                address,
+                &mut RunResources::default(),
+                &mut vm,
+                &mut hint_processor,
+            )
             .unwrap();
 
         assert_eq!(builtin.get_used_cells_and_allocated_size(&vm), Ok((0, 1)));
     }
 
+    #[test]
+    fn get_used_cells_and_allocated_size_insufficient_allocated_cells() {
+        let builtin: BuiltinRunner = RangeCheckBuiltinRunner::new(8, 8, true).into();
+        let mut vm = vm!();
+        vm.current_step = 8;
+        vm.segments.segment_used_sizes = Some(vec![2]);
+
+        let error = builtin.get_used_cells_and_allocated_size(&vm).unwrap_err();
+        assert_eq!(
+            error,
+            MemoryError::InsufficientAllocatedCells {
+                builtin: "range_check",
+                used: 2,
+                allocated: 1,
+            }
+        );
+        assert_eq!(
+            error.to_string(),
+            "The range_check builtin uses 2 cells, but only 1 are allocated"
+        );
+    }
+
     #[test]
     fn get_allocated_memory_units() {
         let builtin = RangeCheckBuiltinRunner::new(10, 12, true);
 
         let mut vm = vm!();
 
-        let program = program!(
-            builtins = vec![String::from("pedersen")],
-            data = vec_data!(
-                (4612671182993129469_i64),
-                (5189976364521848832_i64),
-                (18446744073709551615_i128),
-                (5199546496550207487_i64),
-                (4612389712311386111_i64),
-                (5198983563776393216_i64),
-                (2),
-                (2345108766317314046_i64),
-                (5191102247248822272_i64),
-                (5189976364521848832_i64),
-                (7),
-                (1226245742482522112_i64),
-                ((
-                    "3618502788666131213697322783095070105623107215331596699973092056135872020470",
-                    10
-                )),
-                (2345108766317314046_i64)
-            ),
-            main = Some(8),
-        );
+        let program = Program::from_fixture("check_range");
 
         let mut cairo_runner = cairo_runner!(program);
 
@@ -442,7 +633,12 @@ mod tests {
         let address = cairo_runner.initialize(&mut vm).unwrap();
 
         cairo_runner
-            .run_until_pc(address, &mut vm, &mut hint_processor)
+            .run_until_pc(
+                address,
+                &mut RunResources::default(),
+                &mut vm,
+                &mut hint_processor,
+            )
             .unwrap();
 
         assert_eq!(builtin.get_allocated_memory_units(&vm), Ok(1));
@@ -453,7 +649,9 @@ mod tests {
         let mut builtin = RangeCheckBuiltinRunner::new(8, 8, true);
         let mut segments = MemorySegmentManager::new();
         let mut memory = Memory::new();
-        builtin.initialize_segments(&mut segments, &mut memory);
+        builtin
+            .initialize_segments(&mut segments, &mut memory)
+            .unwrap();
         assert_eq!(builtin.base, 0);
     }
 
@@ -475,7 +673,7 @@ mod tests {
 
         assert_eq!(
             builtin.get_memory_segment_addresses(),
-            ("range_check", (0, None)),
+            vec![SegmentAddresses::new("range_check", 0, None)],
         );
     }
 
@@ -486,7 +684,7 @@ mod tests {
 
         assert_eq!(
             builtin.get_memory_accesses(&vm),
-            Err(MemoryError::MissingSegmentUsedSizes),
+            Err(MemoryError::MissingSegmentUsedSizes(0)),
         );
     }
 
@@ -505,6 +703,7 @@ mod tests {
         let mut vm = vm!();
 
         vm.segments.segment_used_sizes = Some(vec![4]);
+        vm.memory = memory![((0, 0), 1), ((0, 1), 2), ((0, 2), 3), ((0, 3), 4)];
         assert_eq!(
             builtin.get_memory_accesses(&vm),
             Ok(vec![
@@ -523,7 +722,7 @@ mod tests {
 
         assert_eq!(
             builtin.get_used_cells(&vm),
-            Err(MemoryError::MissingSegmentUsedSizes)
+            Err(MemoryError::MissingSegmentUsedSizes(0))
         );
     }
 
@@ -545,11 +744,53 @@ mod tests {
         assert_eq!(builtin.get_used_cells(&vm), Ok(4));
     }
 
+    #[test]
+    fn get_used_cells_mid_run() {
+        let builtin = BuiltinRunner::RangeCheck(RangeCheckBuiltinRunner::new(256, 8, true));
+        let mut vm = vm!();
+
+        vm.memory = memory![((0, 0), 1), ((0, 1), 2), ((0, 2), 3)];
+        assert_eq!(builtin.get_used_cells_mid_run(&vm), Ok(3));
+    }
+
+    #[test]
+    fn get_used_cells_mid_run_after_computing_sizes() {
+        let builtin = BuiltinRunner::RangeCheck(RangeCheckBuiltinRunner::new(256, 8, true));
+        let mut vm = vm!();
+
+        vm.memory = memory![((0, 0), 1), ((0, 1), 2), ((0, 2), 3)];
+        vm.segments.segment_used_sizes = Some(vec![4]);
+        assert_eq!(builtin.get_used_cells_mid_run(&vm), Ok(4));
+    }
+
+    #[test]
+    fn get_used_cells_mid_run_unallocated_segment() {
+        let builtin = BuiltinRunner::RangeCheck(RangeCheckBuiltinRunner::new(256, 8, true));
+        let vm = vm!();
+
+        assert_eq!(
+            builtin.get_used_cells_mid_run(&vm),
+            Err(MemoryError::UnallocatedSegment(0, 0))
+        );
+    }
+
+    #[test]
+    fn get_used_cells_mid_run_temporary_segment() {
+        let mut builtin = RangeCheckBuiltinRunner::new(256, 8, true);
+        builtin.base = -1;
+        let vm = vm!();
+
+        assert_eq!(
+            builtin.get_used_cells_mid_run(&vm),
+            Err(MemoryError::AddressInTemporarySegment(-1))
+        );
+    }
+
     #[test]
     fn get_range_check_usage_succesful_a() {
         let builtin = RangeCheckBuiltinRunner::new(8, 8, true);
         let memory = memory![((0, 0), 1), ((0, 1), 2), ((0, 2), 3), ((0, 3), 4)];
-        assert_eq!(builtin.get_range_check_usage(&memory), Some((1, 4)));
+        assert_eq!(builtin.get_range_check_usage(&memory), Some((0, 4)));
     }
 
     #[test]
@@ -561,7 +802,7 @@ mod tests {
             ((0, 2), 31349610736_i64),
             ((0, 3), 413468326585859_i64)
         ];
-        assert_eq!(builtin.get_range_check_usage(&memory), Some((6384, 62821)));
+        assert_eq!(builtin.get_range_check_usage(&memory), Some((0, 62821)));
     }
 
     #[test]
@@ -575,7 +816,17 @@ mod tests {
             ((0, 4), 75346043276073460326_i128),
             ((0, 5), 87234598724867609478353436890268_i128)
         ];
-        assert_eq!(builtin.get_range_check_usage(&memory), Some((10480, 42341)));
+        assert_eq!(builtin.get_range_check_usage(&memory), Some((0, 61576)));
+    }
+
+    #[test]
+    fn get_range_check_usage_values_over_one_part() {
+        let builtin = RangeCheckBuiltinRunner::new(8, 2, true);
+        let memory = memory![((0, 0), 0x10000)];
+        assert_eq!(builtin.get_range_check_usage(&memory), Some((0, 1)));
+        // 0x100030002 is split into the parts 2 and 3, its third part being out of the range
+        let memory = memory![((0, 0), 0x1_0003_0002_i64)];
+        assert_eq!(builtin.get_range_check_usage(&memory), Some((2, 3)));
     }
 
     #[test]
@@ -585,6 +836,20 @@ mod tests {
         assert_eq!(builtin.get_range_check_usage(&memory), None);
     }
 
+    #[test]
+    fn get_usage_histogram() {
+        let builtin = RangeCheckBuiltinRunner::new(8, 2, true);
+        // 0x10002 and 0x20002, split into the limbs 2, 1 and 2, 2
+        let mut memory = memory![((0, 0), 0x10002), ((0, 1), 0x20002)];
+        assert_eq!(
+            builtin.get_usage_histogram(&memory),
+            Some(BTreeMap::from([(1, 1), (2, 3)]))
+        );
+        memory.data[0].push(None);
+        memory.data[0].push(Some(MaybeRelocatable::from(3)));
+        assert_eq!(builtin.get_usage_histogram(&memory), None);
+    }
+
     /// Test that the method get_used_perm_range_check_units works as intended.
     #[test]
     fn get_used_perm_range_check_units() {
@@ -595,4 +860,14 @@ mod tests {
         vm.segments.segment_used_sizes = Some(vec![1]);
         assert_eq!(builtin_runner.get_used_perm_range_check_units(&vm), Ok(8));
     }
+
+    #[test]
+    fn limbs_least_significant_first() {
+        let builtin = RangeCheckBuiltinRunner::new(8, 8, true);
+        let value = Felt::new(0x0003_0002_0001_u64);
+        assert_eq!(
+            builtin.limbs(&value),
+            [1, 2, 3, 0, 0, 0, 0, 0].map(Felt::new).to_vec()
+        );
+    }
 }
@@ -1,11 +1,15 @@
 use crate::{
-    math_utils::safe_div_usize,
+    math_utils::div_mod,
     types::{
         instance_definitions::ecdsa_instance_def::EcdsaInstanceDef,
         relocatable::{MaybeRelocatable, Relocatable},
     },
     vm::{
         errors::{memory_errors::MemoryError, runner_errors::RunnerError},
+        runners::{
+            air_private_input::{PrivateInput, PrivateInputSignature, SignatureInput},
+            builtin_runner::{get_allocated_instances, get_instances_inputs, SegmentAddresses},
+        },
         vm_core::VirtualMachine,
         vm_memory::{
             memory::{Memory, ValidationRule},
@@ -14,16 +18,29 @@ use crate::{
     },
 };
 use felt::{Felt, FeltOps};
+use lazy_static::lazy_static;
+use num_bigint::{BigInt, Sign};
 use num_integer::{div_ceil, Integer};
-use num_traits::ToPrimitive;
+use num_traits::{Num, One, ToPrimitive};
 use starknet_crypto::{verify, FieldElement, Signature};
 use std::{any::Any, cell::RefCell, collections::HashMap, rc::Rc};
 
+lazy_static! {
+    // The order of the STARK curve, modulo which the prover expects the inverse of `s`
+    static ref EC_ORDER: BigInt = BigInt::from_str_radix(
+        "800000000000010ffffffffffffffffb781126dcae7b2321e66a241adc64d2f",
+        16
+    )
+    .unwrap();
+}
+
 #[derive(Debug, Clone)]
 pub struct SignatureBuiltinRunner {
-    included: bool,
+    pub(crate) included: bool,
     ratio: u32,
-    base: isize,
+    pub(crate) ratio_den: u32,
+    pub(crate) base: isize,
+    pub(crate) initialized: bool,
     pub(crate) cells_per_instance: u32,
     pub(crate) n_input_cells: u32,
     _total_n_bits: u32,
@@ -33,11 +50,15 @@ pub struct SignatureBuiltinRunner {
 }
 
 impl SignatureBuiltinRunner {
+    pub const NAME: &str = "ecdsa";
+
     pub(crate) fn new(instance_def: &EcdsaInstanceDef, included: bool) -> Self {
         SignatureBuiltinRunner {
             base: 0,
+            initialized: false,
             included,
             ratio: instance_def.ratio,
+            ratio_den: instance_def.ratio_den,
             cells_per_instance: 2,
             n_input_cells: 2,
             _total_n_bits: 251,
@@ -73,6 +94,38 @@ impl SignatureBuiltinRunner {
 
         Ok(())
     }
+
+    /// Returns the (r, s) pair of each signature added to the builtin, by the address of its
+    /// public key.
+    pub fn get_additional_data(&self) -> HashMap<Relocatable, (Felt, Felt)> {
+        self.signatures
+            .borrow()
+            .iter()
+            .map(|(address, signature)| {
+                (
+                    *address,
+                    (
+                        Felt::from_bytes_be(&signature.r.to_bytes_be()),
+                        Felt::from_bytes_be(&signature.s.to_bytes_be()),
+                    ),
+                )
+            })
+            .collect()
+    }
+
+    pub fn extend_additional_data(
+        &mut self,
+        signatures: &HashMap<Relocatable, (Felt, Felt)>,
+    ) -> Result<(), MemoryError> {
+        for (address, signature) in signatures {
+            self.add_signature(*address, signature)?;
+        }
+        Ok(())
+    }
+
+    pub(crate) fn clear_signatures(&mut self) {
+        self.signatures.borrow_mut().clear();
+    }
 }
 
 impl SignatureBuiltinRunner {
@@ -80,8 +133,13 @@ impl SignatureBuiltinRunner {
         &mut self,
         segments: &mut MemorySegmentManager,
         memory: &mut Memory,
-    ) {
-        self.base = segments.add(memory).segment_index
+    ) -> Result<(), RunnerError> {
+        if self.initialized {
+            return Err(RunnerError::BuiltinAlreadyInitialized(Self::NAME));
+        }
+        self.base = segments.add(memory).segment_index;
+        self.initialized = true;
+        Ok(())
     }
 
     pub fn initial_stack(&self) -> Vec<MaybeRelocatable> {
@@ -95,6 +153,10 @@ impl SignatureBuiltinRunner {
     pub fn base(&self) -> isize {
         self.base
     }
+
+    pub fn is_initialized(&self) -> bool {
+        self.initialized
+    }
     pub fn add_validation_rule(&self, memory: &mut Memory) -> Result<(), RunnerError> {
         let cells_per_instance = self.cells_per_instance;
         let signatures = Rc::clone(&self.signatures);
@@ -110,11 +172,10 @@ impl SignatureBuiltinRunner {
                 let address_offset = address.offset.mod_floor(&(cells_per_instance as usize));
                 let mem_addr_sum = memory.get(&(address + 1_i32));
                 let mem_addr_less = if address.offset > 0 {
-                    memory.get(
-                        &address
-                            .sub_usize(1)
-                            .map_err(|_| MemoryError::NumOutOfBounds)?,
-                    )
+                    memory.get(&Relocatable::from((
+                        address.segment_index,
+                        address.offset - 1,
+                    )))
                 } else {
                     Ok(None)
                 };
@@ -158,12 +219,12 @@ impl SignatureBuiltinRunner {
                 }
             },
         ));
-        memory.add_validation_rule(
-            self.base
-                .to_usize()
-                .ok_or(RunnerError::RunnerInTemporarySegment(self.base))?,
-            rule,
-        );
+        match self.base.to_usize() {
+            Some(segment_index) => memory.add_validation_rule(segment_index, rule),
+            None => memory
+                .add_temp_validation_rule(self.base, rule)
+                .map_err(RunnerError::MemoryValidationError)?,
+        }
         Ok(())
     }
 
@@ -183,83 +244,127 @@ impl SignatureBuiltinRunner {
         self.ratio
     }
 
+    pub fn ratio_den(&self) -> u32 {
+        self.ratio_den
+    }
+
     pub fn get_allocated_memory_units(&self, vm: &VirtualMachine) -> Result<usize, MemoryError> {
-        let value = safe_div_usize(vm.current_step, self.ratio as usize)
-            .map_err(|_| MemoryError::ErrorCalculatingMemoryUnits)?;
+        let value =
+            get_allocated_instances(Self::NAME, vm.current_step, self.ratio, self.ratio_den)?;
         Ok(self.cells_per_instance as usize * value)
     }
 
-    pub fn get_memory_segment_addresses(&self) -> (&'static str, (isize, Option<usize>)) {
-        ("ecdsa", (self.base, self.stop_ptr))
+    pub fn air_private_input(&self, memory: &Memory) -> Vec<PrivateInput> {
+        let signatures = self.signatures.borrow();
+        get_instances_inputs::<2>(memory, self.base, self.cells_per_instance)
+            .into_iter()
+            .filter_map(|(index, [pubkey, msg])| {
+                let pubkey_addr =
+                    Relocatable::from((self.base, index * self.cells_per_instance as usize));
+                let signature = signatures.get(&pubkey_addr)?;
+                let s = BigInt::from_bytes_be(Sign::Plus, &signature.s.to_bytes_be());
+                Some(PrivateInput::Signature(PrivateInputSignature {
+                    index,
+                    pubkey,
+                    msg,
+                    signature_input: SignatureInput {
+                        r: Felt::from_bytes_be(&signature.r.to_bytes_be()),
+                        w: Felt::from(div_mod(&BigInt::one(), &s, &EC_ORDER)),
+                    },
+                }))
+            })
+            .collect()
+    }
+
+    pub fn get_memory_segment_addresses(&self) -> Vec<SegmentAddresses> {
+        vec![SegmentAddresses::new(Self::NAME, self.base, self.stop_ptr)]
     }
 
     pub fn get_used_cells(&self, vm: &VirtualMachine) -> Result<usize, MemoryError> {
         let base = self.base();
+        let index = base
+            .try_into()
+            .map_err(|_| MemoryError::AddressInTemporarySegment(base))?;
+        vm.segments
+            .get_segment_used_size(index)
+            .ok_or(MemoryError::MissingSegmentUsedSizes(index))
+    }
+
+    pub fn get_used_cells_mid_run(&self, vm: &VirtualMachine) -> Result<usize, MemoryError> {
+        let base = self.base();
+        let index = base
+            .try_into()
+            .map_err(|_| MemoryError::AddressInTemporarySegment(base))?;
         vm.segments
-            .get_segment_used_size(
-                base.try_into()
-                    .map_err(|_| MemoryError::AddressInTemporarySegment(base))?,
-            )
-            .ok_or(MemoryError::MissingSegmentUsedSizes)
+            .get_segment_used_size_mid_run(index, &vm.memory)
+            .ok_or(MemoryError::UnallocatedSegment(index, vm.memory.data.len()))
     }
 
     pub fn get_used_cells_and_allocated_size(
         &self,
         vm: &VirtualMachine,
     ) -> Result<(usize, usize), MemoryError> {
-        let ratio = self.ratio as usize;
         let cells_per_instance = self.cells_per_instance;
-        let min_step = ratio * self.instances_per_component as usize;
+        let min_step = div_ceil(
+            self.ratio as usize * self.instances_per_component as usize,
+            self.ratio_den as usize,
+        );
         if vm.current_step < min_step {
-            Err(MemoryError::InsufficientAllocatedCells)
+            Err(MemoryError::MinStepNotReached {
+                builtin: Self::NAME,
+                min_step,
+            })
         } else {
             let used = self.get_used_cells(vm)?;
             let size = cells_per_instance as usize
-                * safe_div_usize(vm.current_step, ratio)
-                    .map_err(|_| MemoryError::InsufficientAllocatedCells)?;
+                * get_allocated_instances(Self::NAME, vm.current_step, self.ratio, self.ratio_den)?;
             if used > size {
-                return Err(MemoryError::InsufficientAllocatedCells);
+                return Err(MemoryError::InsufficientAllocatedCells {
+                    builtin: Self::NAME,
+                    used,
+                    allocated: size,
+                });
             }
             Ok((used, size))
         }
     }
 
     pub fn get_used_instances(&self, vm: &VirtualMachine) -> Result<usize, MemoryError> {
-        let used_cells = self.get_used_cells(vm)?;
+        let used_cells = self.get_used_cells_mid_run(vm)?;
         Ok(div_ceil(used_cells, self.cells_per_instance as usize))
     }
 
     pub fn final_stack(
-        &self,
+        &mut self,
         vm: &VirtualMachine,
         pointer: Relocatable,
-    ) -> Result<(Relocatable, usize), RunnerError> {
+    ) -> Result<Relocatable, RunnerError> {
         if self.included {
-            if let Ok(stop_pointer) =
-                vm.get_relocatable(&(pointer.sub_usize(1)).map_err(|_| RunnerError::FinalStack)?)
-            {
-                if self.base() != stop_pointer.segment_index {
-                    return Err(RunnerError::InvalidStopPointer("ecdsa".to_string()));
-                }
-                let stop_ptr = stop_pointer.offset;
+            if let Ok(stop_pointer) = vm.get_relocatable(
+                &(pointer.sub_usize(1)).map_err(|_| RunnerError::FinalStack(Self::NAME))?,
+            ) {
                 let num_instances = self
                     .get_used_instances(vm)
-                    .map_err(|_| RunnerError::FinalStack)?;
+                    .map_err(|_| RunnerError::FinalStack(Self::NAME))?;
                 let used_cells = num_instances * self.cells_per_instance as usize;
-                if stop_ptr != used_cells {
-                    return Err(RunnerError::InvalidStopPointer("ecdsa".to_string()));
+                let expected = Relocatable::from((self.base(), used_cells));
+                if stop_pointer != expected {
+                    return Err(RunnerError::InvalidStopPointer {
+                        builtin: Self::NAME,
+                        expected,
+                        found: stop_pointer,
+                    });
                 }
-
-                Ok((
-                    pointer.sub_usize(1).map_err(|_| RunnerError::FinalStack)?,
-                    stop_ptr,
-                ))
+                self.stop_ptr = Some(stop_pointer.offset);
+                Ok(pointer
+                    .sub_usize(1)
+                    .map_err(|_| RunnerError::FinalStack(Self::NAME))?)
             } else {
-                Err(RunnerError::FinalStack)
+                Err(RunnerError::FinalStack(Self::NAME))
             }
         } else {
-            let stop_ptr = self.base() as usize;
-            Ok((pointer, stop_ptr))
+            self.stop_ptr = Some(0);
+            Ok(pointer)
         }
     }
 }
@@ -277,16 +382,44 @@ mod tests {
             vm_memory::{memory::Memory, memory_segments::MemorySegmentManager},
         },
     };
+    use felt::NewFelt;
 
     #[test]
     fn initialize_segments_for_ecdsa() {
         let mut builtin = SignatureBuiltinRunner::new(&EcdsaInstanceDef::default(), true);
         let mut segments = MemorySegmentManager::new();
         let mut memory = Memory::new();
-        builtin.initialize_segments(&mut segments, &mut memory);
+        builtin
+            .initialize_segments(&mut segments, &mut memory)
+            .unwrap();
         assert_eq!(builtin.base, 0);
     }
 
+    #[test]
+    fn air_private_input() {
+        let mut builtin = SignatureBuiltinRunner::new(&EcdsaInstanceDef::default(), true);
+        let (r, s) = (Felt::new(3), Felt::new(2));
+        builtin
+            .add_signature(Relocatable::from((0, 2)), &(r.clone(), s))
+            .unwrap();
+        // The first instance has no signature, so it has no private input
+        let memory = memory![((0, 0), 1), ((0, 1), 2), ((0, 2), 5), ((0, 3), 6)];
+        let w = Felt::from(div_mod(&BigInt::one(), &BigInt::from(2), &EC_ORDER));
+        assert_eq!(
+            (BigInt::from(w.to_biguint()) * 2_u32).mod_floor(&EC_ORDER),
+            BigInt::one()
+        );
+        assert_eq!(
+            builtin.air_private_input(&memory),
+            vec![PrivateInput::Signature(PrivateInputSignature {
+                index: 1,
+                pubkey: Felt::new(5),
+                msg: Felt::new(6),
+                signature_input: SignatureInput { r, w },
+            })]
+        );
+    }
+
     #[test]
     fn get_used_instances() {
         let builtin = SignatureBuiltinRunner::new(&EcdsaInstanceDef::default(), true);
@@ -307,7 +440,7 @@ mod tests {
 
     #[test]
     fn final_stack() {
-        let builtin = SignatureBuiltinRunner::new(&EcdsaInstanceDef::default(), true);
+        let mut builtin = SignatureBuiltinRunner::new(&EcdsaInstanceDef::default(), true);
 
         let mut vm = vm!();
 
@@ -324,13 +457,14 @@ mod tests {
 
         assert_eq!(
             builtin.final_stack(&vm, pointer).unwrap(),
-            (Relocatable::from((2, 1)), 0)
+            Relocatable::from((2, 1))
         );
+        assert_eq!(builtin.stop_ptr, Some(0));
     }
 
     #[test]
     fn final_stack_error_stop_pointer() {
-        let builtin = SignatureBuiltinRunner::new(&EcdsaInstanceDef::default(), true);
+        let mut builtin = SignatureBuiltinRunner::new(&EcdsaInstanceDef::default(), true);
 
         let mut vm = vm!();
 
@@ -347,13 +481,17 @@ mod tests {
 
         assert_eq!(
             builtin.final_stack(&vm, pointer),
-            Err(RunnerError::InvalidStopPointer("ecdsa".to_string()))
+            Err(RunnerError::InvalidStopPointer {
+                builtin: "ecdsa",
+                expected: Relocatable::from((0, 1000)),
+                found: Relocatable::from((0, 0))
+            })
         );
     }
 
     #[test]
     fn final_stack_error_non_relocatable() {
-        let builtin = SignatureBuiltinRunner::new(&EcdsaInstanceDef::default(), true);
+        let mut builtin = SignatureBuiltinRunner::new(&EcdsaInstanceDef::default(), true);
 
         let mut vm = vm!();
 
@@ -370,7 +508,7 @@ mod tests {
 
         assert_eq!(
             builtin.final_stack(&vm, pointer),
-            Err(RunnerError::FinalStack)
+            Err(RunnerError::FinalStack("ecdsa"))
         );
     }
 
@@ -378,7 +516,10 @@ mod tests {
     fn get_memory_segment_addresses() {
         let builtin = SignatureBuiltinRunner::new(&EcdsaInstanceDef::default(), true);
 
-        assert_eq!(builtin.get_memory_segment_addresses(), ("ecdsa", (0, None)));
+        assert_eq!(
+            builtin.get_memory_segment_addresses(),
+            vec![SegmentAddresses::new("ecdsa", 0, None)]
+        );
     }
 
     #[test]
@@ -391,7 +532,7 @@ mod tests {
 
         assert_eq!(
             builtin.get_memory_accesses(&vm),
-            Err(MemoryError::MissingSegmentUsedSizes),
+            Err(MemoryError::MissingSegmentUsedSizes(0)),
         );
     }
 
@@ -416,6 +557,7 @@ mod tests {
         let mut vm = vm!();
 
         vm.segments.segment_used_sizes = Some(vec![4]);
+        vm.memory = memory![((0, 0), 1), ((0, 1), 2), ((0, 2), 3), ((0, 3), 4)];
         assert_eq!(
             builtin.get_memory_accesses(&vm),
             Ok(vec![
@@ -427,6 +569,22 @@ mod tests {
         );
     }
 
+    #[test]
+    fn get_memory_accesses_skips_unwritten_cells() {
+        let builtin = BuiltinRunner::Signature(SignatureBuiltinRunner::new(
+            &EcdsaInstanceDef::default(),
+            true,
+        ));
+        let mut vm = vm!();
+
+        vm.segments.segment_used_sizes = Some(vec![4]);
+        vm.memory = memory![((0, 0), 1), ((0, 3), 4)];
+        assert_eq!(
+            builtin.get_memory_accesses(&vm),
+            Ok(vec![(builtin.base(), 0).into(), (builtin.base(), 3).into()]),
+        );
+    }
+
     #[test]
     fn get_used_cells_missing_segment_used_sizes() {
         let builtin = BuiltinRunner::Signature(SignatureBuiltinRunner::new(
@@ -437,7 +595,7 @@ mod tests {
 
         assert_eq!(
             builtin.get_used_cells(&vm),
-            Err(MemoryError::MissingSegmentUsedSizes)
+            Err(MemoryError::MissingSegmentUsedSizes(0))
         );
     }
 
@@ -0,0 +1,265 @@
+use super::{
+    builtin_runner::BuiltinRunnerAdditionalData,
+    cairo_runner::{ExecutionResources, SegmentInfo},
+};
+use crate::types::relocatable::{MaybeRelocatable, Relocatable};
+use felt::{Felt, FeltOps};
+use serde::{Serialize, Serializer};
+use serde_json::{json, Number, Value};
+use std::{
+    collections::BTreeMap,
+    fs::File,
+    io::{self, Seek, Write},
+    path::Path,
+    str::FromStr,
+};
+use zip::{write::FileOptions, CompressionMethod, ZipWriter};
+
+/// The version of the Cairo PIE format written to `version.json`.
+pub const CAIRO_PIE_VERSION: &str = "1.0";
+
+// The relocatable values of the memory file use cairo-lang's RelocatableValue bit layout:
+// the highest bit is set, and the segment index is stored above the 47 bits of the offset.
+const OFFSET_BITS: u32 = 47;
+const ADDR_SIZE_IN_BYTES: usize = 8;
+const FIELD_SIZE_IN_BYTES: usize = 32;
+
+/// The program of a Cairo PIE, stripped of everything but what is needed to run it again.
+#[derive(Serialize, Clone, Debug, PartialEq, Eq)]
+pub struct StrippedProgram {
+    #[serde(serialize_with = "serialize_felts_hex")]
+    pub data: Vec<Felt>,
+    pub builtins: Vec<String>,
+    pub main: usize,
+    pub prime: String,
+}
+
+/// The contents of `metadata.json`: the program and the index and size of each segment.
+#[derive(Serialize, Clone, Debug, PartialEq, Eq)]
+pub struct CairoPieMetadata {
+    pub program: StrippedProgram,
+    pub program_segment: SegmentInfo,
+    pub execution_segment: SegmentInfo,
+    pub ret_fp_segment: SegmentInfo,
+    pub ret_pc_segment: SegmentInfo,
+    pub builtin_segments: BTreeMap<String, SegmentInfo>,
+    /// The segments added during the run which aren't any of the above
+    pub extra_segments: Vec<SegmentInfo>,
+}
+
+/// A position independent execution of a program, as returned by `CairoRunner::get_cairo_pie`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CairoPie {
+    pub metadata: CairoPieMetadata,
+    /// The unrelocated memory of the run, sorted by address
+    pub memory: Vec<(Relocatable, MaybeRelocatable)>,
+    pub additional_data: BTreeMap<String, BuiltinRunnerAdditionalData>,
+    pub execution_resources: ExecutionResources,
+}
+
+impl CairoPie {
+    /// Writes the PIE to a zip file, in the layout cairo-lang reads it from.
+    pub fn write_zip(&self, path: &Path) -> io::Result<()> {
+        self.write_zip_to(File::create(path)?)
+    }
+
+    /// Writes the PIE as a zip archive to any seekable writer.
+    pub fn write_zip_to<W: Write + Seek>(&self, writer: W) -> io::Result<()> {
+        let options = FileOptions::default().compression_method(CompressionMethod::Deflated);
+        let mut zip = ZipWriter::new(writer);
+
+        zip.start_file("metadata.json", options)?;
+        serde_json::to_writer(&mut zip, &self.metadata)?;
+
+        zip.start_file("memory.bin", options)?;
+        self.write_memory(&mut zip)?;
+
+        zip.start_file("additional_data.json", options)?;
+        let additional_data = self
+            .additional_data
+            .iter()
+            .map(|(name, data)| Ok((name.clone(), additional_data_to_json(data)?)))
+            .collect::<serde_json::Result<BTreeMap<_, _>>>()?;
+        serde_json::to_writer(&mut zip, &additional_data)?;
+
+        zip.start_file("execution_resources.json", options)?;
+        serde_json::to_writer(&mut zip, &self.execution_resources)?;
+
+        zip.start_file("version.json", options)?;
+        serde_json::to_writer(&mut zip, &json!({ "cairo_pie": CAIRO_PIE_VERSION }))?;
+
+        zip.finish()?;
+        Ok(())
+    }
+
+    /// Writes the contents of `memory.bin`: an 8 bytes address followed by a 32 bytes value for
+    /// each memory cell, both in little endian.
+    pub fn write_memory(&self, writer: &mut impl Write) -> io::Result<()> {
+        for (address, value) in &self.memory {
+            write_relocatable(writer, address, ADDR_SIZE_IN_BYTES)?;
+            match value {
+                MaybeRelocatable::RelocatableValue(value) => {
+                    write_relocatable(writer, value, FIELD_SIZE_IN_BYTES)?
+                }
+                MaybeRelocatable::Int(value) => {
+                    let mut value_bytes = value.to_signed_bytes_le();
+                    value_bytes.resize(FIELD_SIZE_IN_BYTES, 0);
+                    writer.write_all(&value_bytes)?
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+fn write_relocatable(
+    writer: &mut impl Write,
+    value: &Relocatable,
+    n_bytes: usize,
+) -> io::Result<()> {
+    let mut bytes = vec![0; n_bytes];
+    let packed = ((value.segment_index as u64) << OFFSET_BITS) | value.offset as u64;
+    bytes[..8].copy_from_slice(&packed.to_le_bytes());
+    bytes[n_bytes - 1] |= 0x80;
+    writer.write_all(&bytes)
+}
+
+fn serialize_felts_hex<S: Serializer>(values: &[Felt], serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.collect_seq(
+        values
+            .iter()
+            .map(|value| format!("0x{}", value.to_str_radix(16))),
+    )
+}
+
+// Addresses are written as [segment_index, offset] pairs, the shape cairo-lang gives them
+fn address_to_json(address: &Relocatable) -> Value {
+    json!([address.segment_index, address.offset])
+}
+
+// Matches the get_additional_data of the corresponding cairo-lang builtin runners
+fn additional_data_to_json(data: &BuiltinRunnerAdditionalData) -> serde_json::Result<Value> {
+    Ok(match data {
+        BuiltinRunnerAdditionalData::Hash(addresses) => {
+            let mut addresses = addresses.clone();
+            addresses.sort_by_key(|address| (address.segment_index, address.offset));
+            Value::Array(addresses.iter().map(address_to_json).collect())
+        }
+        BuiltinRunnerAdditionalData::Output(output) => {
+            let pages = output
+                .pages
+                .iter()
+                .map(|(id, page)| (id.to_string(), json!([page.start, page.size])))
+                .collect::<BTreeMap<_, _>>();
+            let attributes = output.attributes.iter().collect::<BTreeMap<_, _>>();
+            json!({ "pages": pages, "attributes": attributes })
+        }
+        BuiltinRunnerAdditionalData::Signature(signatures) => {
+            let mut signatures = signatures.iter().collect::<Vec<_>>();
+            signatures.sort_by_key(|(address, _)| (address.segment_index, address.offset));
+            let mut entries = Vec::with_capacity(signatures.len());
+            for (address, (r, s)) in signatures {
+                entries.push(json!([
+                    address_to_json(address),
+                    [felt_to_json(r)?, felt_to_json(s)?]
+                ]));
+            }
+            Value::Array(entries)
+        }
+        BuiltinRunnerAdditionalData::Empty => json!({}),
+    })
+}
+
+// Felts are written as json integers, which don't lose precision as serde_json is built with
+// the arbitrary_precision feature
+fn felt_to_json(value: &Felt) -> serde_json::Result<Value> {
+    Number::from_str(&value.to_str_radix(10)).map(Value::Number)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vm::runners::builtin_runner::{OutputBuiltinAdditionalData, PublicMemoryPage};
+    use felt::NewFelt;
+    use std::collections::HashMap;
+
+    #[test]
+    fn write_memory_packs_segmented_addresses() {
+        let pie = CairoPie {
+            memory: vec![
+                (
+                    Relocatable::from((0, 0)),
+                    MaybeRelocatable::from(Felt::new(5)),
+                ),
+                (Relocatable::from((2, 3)), MaybeRelocatable::from((1, 4))),
+            ],
+            ..test_pie()
+        };
+        let mut buffer = Vec::new();
+        pie.write_memory(&mut buffer).unwrap();
+
+        let mut expected = Vec::new();
+        // (0, 0) -> 5
+        expected.extend_from_slice(&(1u64 << 63).to_le_bytes());
+        expected.push(5);
+        expected.extend_from_slice(&[0; 31]);
+        // (2, 3) -> (1, 4)
+        expected.extend_from_slice(&((1u64 << 63) | (2 << 47) | 3).to_le_bytes());
+        expected.extend_from_slice(&((1u64 << 47) | 4).to_le_bytes());
+        expected.extend_from_slice(&[0; 23]);
+        expected.push(0x80);
+        assert_eq!(buffer, expected);
+    }
+
+    #[test]
+    fn additional_data_to_json_output() {
+        let data = BuiltinRunnerAdditionalData::Output(OutputBuiltinAdditionalData {
+            pages: HashMap::from([(1, PublicMemoryPage { start: 0, size: 3 })]),
+            attributes: HashMap::from([("gps_fact_topology".to_string(), vec![2, 1])]),
+        });
+        assert_eq!(
+            additional_data_to_json(&data).unwrap(),
+            json!({ "pages": { "1": [0, 3] }, "attributes": { "gps_fact_topology": [2, 1] } })
+        );
+    }
+
+    #[test]
+    fn additional_data_to_json_hash() {
+        let data = BuiltinRunnerAdditionalData::Hash(vec![
+            Relocatable::from((2, 5)),
+            Relocatable::from((2, 2)),
+        ]);
+        assert_eq!(
+            additional_data_to_json(&data).unwrap(),
+            json!([[2, 2], [2, 5]])
+        );
+    }
+
+    fn test_pie() -> CairoPie {
+        let segment = |index| SegmentInfo { index, size: 0 };
+        CairoPie {
+            metadata: CairoPieMetadata {
+                program: StrippedProgram {
+                    data: Vec::new(),
+                    builtins: Vec::new(),
+                    main: 0,
+                    prime: String::new(),
+                },
+                program_segment: segment(0),
+                execution_segment: segment(1),
+                ret_fp_segment: segment(2),
+                ret_pc_segment: segment(3),
+                builtin_segments: BTreeMap::new(),
+                extra_segments: Vec::new(),
+            },
+            memory: Vec::new(),
+            additional_data: BTreeMap::new(),
+            execution_resources: ExecutionResources {
+                n_steps: 0,
+                n_memory_holes: 0,
+                builtin_instance_counter: BTreeMap::new(),
+                n_dropped_temporary_segments: 0,
+            },
+        }
+    }
+}
@@ -1,51 +1,155 @@
 use crate::{
-    hint_processor::hint_processor_definition::{HintProcessor, HintReference},
+    hint_processor::{
+        builtin_hint_processor::hint_utils::PROGRAM_INPUT,
+        hint_processor_definition::{HintProcessor, HintReference},
+    },
     math_utils::safe_div_usize,
-    serde::deserialize_program::OffsetValue,
+    serde::deserialize_program::{get_entrypoint_pc, OffsetValue},
     types::{
         errors::program_errors::ProgramError,
         exec_scope::ExecutionScopes,
         instance_definitions::{
-            bitwise_instance_def::BitwiseInstanceDef, ec_op_instance_def::EcOpInstanceDef,
-            ecdsa_instance_def::EcdsaInstanceDef,
+            bitwise_instance_def::BitwiseInstanceDef,
+            diluted_pool_instance_def::DilutedPoolInstanceDef, ec_op_instance_def::EcOpInstanceDef,
+            ecdsa_instance_def::EcdsaInstanceDef, keccak_instance_def::KeccakInstanceDef,
+            pedersen_instance_def::PedersenInstanceDef, poseidon_instance_def::PoseidonInstanceDef,
         },
         instruction::Register,
         layout::CairoLayout,
         program::Program,
         relocatable::{relocate_address, relocate_value, MaybeRelocatable, Relocatable},
     },
-    utils::is_subsequence,
+    utils::{as_int, is_subsequence, CAIRO_PRIME},
     vm::{
         errors::{
-            memory_errors::MemoryError, runner_errors::RunnerError, trace_errors::TraceError,
-            vm_errors::VirtualMachineError,
+            cairo_run_errors::CairoRunError, memory_errors::MemoryError,
+            runner_errors::RunnerError, trace_errors::TraceError, vm_errors::VirtualMachineError,
         },
         security::verify_secure_runner,
         trace::get_perm_range_check_limits,
-        vm_memory::{memory::RelocateValue, memory_segments::gen_typed_args},
+        vm_memory::memory::RelocateValue,
         {
+            runners::air_private_input::AirPrivateInput,
             runners::builtin_runner::{
                 BitwiseBuiltinRunner, BuiltinRunner, EcOpBuiltinRunner, HashBuiltinRunner,
-                OutputBuiltinRunner, RangeCheckBuiltinRunner, SignatureBuiltinRunner,
+                OutputBuiltinRunner, PoseidonBuiltinRunner, RangeCheckBuiltinRunner,
+                SegmentAddresses, SignatureBuiltinRunner,
             },
+            runners::cairo_pie::{CairoPie, CairoPieMetadata, StrippedProgram},
             trace::trace_entry::{relocate_trace_register, RelocatedTraceEntry},
             vm_core::VirtualMachine,
         },
     },
 };
 use felt::{Felt, FeltOps};
+use num_bigint::BigUint;
 use num_integer::div_rem;
 use num_traits::Zero;
+use serde::Serialize;
 use std::{
     any::Any,
-    collections::{HashMap, HashSet},
+    collections::{BTreeMap, BTreeSet, HashMap},
     io,
+    ops::{Add, Mul, Sub},
 };
 
 use super::builtin_runner::KeccakBuiltinRunner;
 
+// Returns the base and the used size of the output builtin's segment, if there is one.
+fn output_segment(vm: &mut VirtualMachine) -> Result<Option<(Relocatable, usize)>, RunnerError> {
+    let base = match vm.builtin_runners.iter().find(|(name, _)| name == "output") {
+        Some((_, builtin)) => builtin.base(),
+        None => return Ok(None),
+    };
+    let segment_index: usize = base
+        .try_into()
+        .map_err(|_| RunnerError::RunnerInTemporarySegment(base))?;
+    let segment_used_sizes = vm.segments.compute_effective_sizes(&vm.memory);
+    Ok(Some((
+        Relocatable::from((base, 0)),
+        segment_used_sizes[segment_index],
+    )))
+}
+
+// Returns the program prime when it isn't the Cairo prime, for the vm to do the arithmetic of
+// the instructions modulo it instead. Felt always works modulo the Cairo prime, so it can't hold
+// the elements of a larger field, and programs compiled for one aren't supported.
+fn parse_program_prime(prime: &str) -> Result<Option<BigUint>, RunnerError> {
+    let cairo_prime = CAIRO_PRIME.magnitude();
+    match prime
+        .strip_prefix("0x")
+        .and_then(|hex| BigUint::parse_bytes(hex.as_bytes(), 16))
+    {
+        Some(value) if &value == cairo_prime => Ok(None),
+        Some(value) if value > BigUint::from(2_u32) && &value < cairo_prime => Ok(Some(value)),
+        _ => Err(RunnerError::UnsupportedPrime(prime.to_string())),
+    }
+}
+
+// Hints, div_mod and the builtins' deductions and validation rules work modulo the Cairo prime, so
+// a program running over another prime can only use the output builtin.
+fn check_custom_prime_support(program: &Program) -> Result<(), RunnerError> {
+    if !program.hints.is_empty() {
+        return Err(RunnerError::UnsupportedWithCustomPrime("hints".to_string()));
+    }
+    match program.builtins.iter().find(|name| *name != "output") {
+        Some(name) => Err(RunnerError::UnsupportedWithCustomPrime(format!(
+            "the {name} builtin"
+        ))),
+        None => Ok(()),
+    }
+}
+
+/// An argument of a function called with `CairoRunner::run_from_entrypoint`.
+/// Arrays and composed arguments are written to a new segment, and passed as a pointer to it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum CairoArg {
+    Single(MaybeRelocatable),
+    Array(Vec<MaybeRelocatable>),
+    Composed(Vec<CairoArg>),
+}
+
+impl From<MaybeRelocatable> for CairoArg {
+    fn from(value: MaybeRelocatable) -> Self {
+        CairoArg::Single(value)
+    }
+}
+
+impl From<Vec<MaybeRelocatable>> for CairoArg {
+    fn from(values: Vec<MaybeRelocatable>) -> Self {
+        CairoArg::Array(values)
+    }
+}
+
+/// The steps a run may execute, shared with the runs that its hints start so that they count
+/// against the same budget. `n_steps` is the number of steps left, or None for unbounded runs.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct RunResources {
+    pub n_steps: Option<usize>,
+}
+
+impl RunResources {
+    pub fn new(n_steps: usize) -> Self {
+        RunResources {
+            n_steps: Some(n_steps),
+        }
+    }
+
+    pub fn consumed(&self) -> bool {
+        self.n_steps == Some(0)
+    }
+
+    pub fn consume_step(&mut self) {
+        if let Some(n_steps) = self.n_steps.as_mut() {
+            *n_steps = n_steps.saturating_sub(1);
+        }
+    }
+}
+
 pub struct CairoRunner {
     pub(crate) program: Program,
+    // The program prime, when it isn't the Cairo prime
+    prime: Option<BigUint>,
     layout: CairoLayout,
     final_pc: Option<Relocatable>,
     pub(crate) program_base: Option<Relocatable>,
@@ -61,6 +165,15 @@ pub struct CairoRunner {
     pub relocated_memory: Vec<Option<Felt>>,
     pub relocated_trace: Option<Vec<RelocatedTraceEntry>>,
     pub exec_scopes: ExecutionScopes,
+    hint_data: HashMap<Relocatable, Vec<Box<dyn Any>>>,
+    program_hints_loaded: bool,
+    dropped_temporary_segments: usize,
+    pub(crate) max_segment_sizes: HashMap<usize, usize>,
+    child_programs: Vec<LoadedProgram>,
+    // The child programs whose hints will be compiled on the next run, once a hint processor
+    // is at hand
+    pending_child_hints: Vec<(Program, Relocatable)>,
+    program_input: Option<serde_json::Value>,
 }
 
 impl CairoRunner {
@@ -76,11 +189,20 @@ impl CairoRunner {
             "perpetual_with_bitwise" => CairoLayout::perpetual_with_bitwise_instance(),
             "bitwise" => CairoLayout::bitwise_instance(),
             "recursive" => CairoLayout::recursive_instance(),
+            "starknet" => CairoLayout::starknet_instance(),
+            "starknet_with_keccak" => CairoLayout::starknet_with_keccak_instance(),
+            "all_cairo" => CairoLayout::all_cairo_instance(),
+            "all_solidity" => CairoLayout::all_solidity_instance(),
             "all" => CairoLayout::all_instance(),
             name => return Err(RunnerError::InvalidLayoutName(name.to_string())),
         };
+        let prime = parse_program_prime(&program.prime)?;
+        if prime.is_some() {
+            check_custom_prime_support(program)?;
+        }
         Ok(CairoRunner {
             program: program.clone(),
+            prime,
             layout: cairo_layout,
             final_pc: None,
             program_base: None,
@@ -96,12 +218,19 @@ impl CairoRunner {
             relocated_trace: None,
             exec_scopes: ExecutionScopes::new(),
             execution_public_memory: if proof_mode { Some(Vec::new()) } else { None },
+            hint_data: HashMap::new(),
+            program_hints_loaded: false,
+            dropped_temporary_segments: 0,
+            max_segment_sizes: HashMap::new(),
+            child_programs: Vec::new(),
+            pending_child_hints: Vec::new(),
+            program_input: None,
         })
     }
 
     pub fn initialize(&mut self, vm: &mut VirtualMachine) -> Result<Relocatable, RunnerError> {
         self.initialize_builtins(vm)?;
-        self.initialize_segments(vm, None);
+        self.initialize_segments(vm, None, None)?;
         let end = self.initialize_main_entrypoint(vm)?;
         self.initialize_vm(vm)?;
         Ok(end)
@@ -109,13 +238,15 @@ impl CairoRunner {
 
     pub fn initialize_builtins(&self, vm: &mut VirtualMachine) -> Result<(), RunnerError> {
         let builtin_ordered_list = vec![
-            String::from("output"),
-            String::from("pedersen"),
-            String::from("range_check"),
-            String::from("ecdsa"),
-            String::from("bitwise"),
-            String::from("ec_op"),
-            String::from("keccak"),
+            String::from(OutputBuiltinRunner::NAME),
+            String::from(HashBuiltinRunner::NAME),
+            String::from(RangeCheckBuiltinRunner::NAME),
+            String::from(SignatureBuiltinRunner::NAME),
+            String::from(BitwiseBuiltinRunner::NAME),
+            String::from(EcOpBuiltinRunner::NAME),
+            String::from(KeccakBuiltinRunner::NAME),
+            String::from(PoseidonBuiltinRunner::NAME),
+            String::from(RangeCheckBuiltinRunner::RANGE_CHECK96_NAME),
         ];
         if !is_subsequence(&self.program.builtins, &builtin_ordered_list) {
             return Err(RunnerError::DisorderedBuiltins);
@@ -123,93 +254,143 @@ impl CairoRunner {
         let mut builtin_runners = Vec::<(String, BuiltinRunner)>::new();
 
         if self.layout.builtins._output {
-            let included = self.program.builtins.contains(&"output".to_string());
+            let included = self
+                .program
+                .builtins
+                .contains(&OutputBuiltinRunner::NAME.to_string());
             if included || self.proof_mode {
                 builtin_runners.push((
-                    "output".to_string(),
+                    OutputBuiltinRunner::NAME.to_string(),
                     OutputBuiltinRunner::new(included).into(),
                 ));
             }
         }
 
         if let Some(instance_def) = self.layout.builtins.pedersen.as_ref() {
-            let included = self.program.builtins.contains(&"pedersen".to_string());
+            let included = self
+                .program
+                .builtins
+                .contains(&HashBuiltinRunner::NAME.to_string());
             if included || self.proof_mode {
                 builtin_runners.push((
-                    "pedersen".to_string(),
-                    HashBuiltinRunner::new(instance_def.ratio, included).into(),
+                    HashBuiltinRunner::NAME.to_string(),
+                    HashBuiltinRunner::new(instance_def, included).into(),
                 ));
             }
         }
 
         if let Some(instance_def) = self.layout.builtins.range_check.as_ref() {
-            let included = self.program.builtins.contains(&"range_check".to_string());
+            let included = self
+                .program
+                .builtins
+                .contains(&RangeCheckBuiltinRunner::NAME.to_string());
             if included || self.proof_mode {
-                builtin_runners.push((
-                    "range_check".to_string(),
-                    RangeCheckBuiltinRunner::new(
-                        instance_def.ratio,
-                        instance_def.n_parts,
-                        included,
-                    )
-                    .into(),
-                ));
+                let mut builtin = RangeCheckBuiltinRunner::new(
+                    instance_def.ratio,
+                    instance_def.n_parts,
+                    included,
+                );
+                builtin.ratio_den = instance_def.ratio_den;
+                builtin_runners.push((RangeCheckBuiltinRunner::NAME.to_string(), builtin.into()));
             }
         }
 
         if let Some(instance_def) = self.layout.builtins._ecdsa.as_ref() {
-            let included = self.program.builtins.contains(&"ecdsa".to_string());
+            let included = self
+                .program
+                .builtins
+                .contains(&SignatureBuiltinRunner::NAME.to_string());
             if included || self.proof_mode {
                 builtin_runners.push((
-                    "ecdsa".to_string(),
+                    SignatureBuiltinRunner::NAME.to_string(),
                     SignatureBuiltinRunner::new(instance_def, included).into(),
                 ));
             }
         }
 
         if let Some(instance_def) = self.layout.builtins.bitwise.as_ref() {
-            let included = self.program.builtins.contains(&"bitwise".to_string());
+            let included = self
+                .program
+                .builtins
+                .contains(&BitwiseBuiltinRunner::NAME.to_string());
             if included || self.proof_mode {
                 builtin_runners.push((
-                    "bitwise".to_string(),
+                    BitwiseBuiltinRunner::NAME.to_string(),
                     BitwiseBuiltinRunner::new(instance_def, included).into(),
                 ));
             }
         }
 
         if let Some(instance_def) = self.layout.builtins.ec_op.as_ref() {
-            let included = self.program.builtins.contains(&"ec_op".to_string());
+            let included = self
+                .program
+                .builtins
+                .contains(&EcOpBuiltinRunner::NAME.to_string());
             if included || self.proof_mode {
                 builtin_runners.push((
-                    "ec_op".to_string(),
+                    EcOpBuiltinRunner::NAME.to_string(),
                     EcOpBuiltinRunner::new(instance_def, included).into(),
                 ));
             }
         }
 
         if let Some(instance_def) = self.layout.builtins.keccak.as_ref() {
-            let included = self.program.builtins.contains(&"keccak".to_string());
+            let included = self
+                .program
+                .builtins
+                .contains(&KeccakBuiltinRunner::NAME.to_string());
             if included || self.proof_mode {
                 builtin_runners.push((
-                    "keccak".to_string(),
+                    KeccakBuiltinRunner::NAME.to_string(),
                     KeccakBuiltinRunner::new(instance_def, included).into(),
                 ));
             }
         }
 
-        let inserted_builtins = builtin_runners
-            .iter()
-            .map(|x| &x.0)
-            .collect::<HashSet<&String>>();
-        let program_builtins: HashSet<&String> =
-            self.program.builtins.iter().collect::<HashSet<&String>>();
+        if let Some(instance_def) = self.layout.builtins.poseidon.as_ref() {
+            let included = self
+                .program
+                .builtins
+                .contains(&PoseidonBuiltinRunner::NAME.to_string());
+            if included || self.proof_mode {
+                builtin_runners.push((
+                    PoseidonBuiltinRunner::NAME.to_string(),
+                    PoseidonBuiltinRunner::new(instance_def, included).into(),
+                ));
+            }
+        }
+
+        if let Some(instance_def) = self.layout.builtins.range_check96.as_ref() {
+            let included = self
+                .program
+                .builtins
+                .contains(&RangeCheckBuiltinRunner::RANGE_CHECK96_NAME.to_string());
+            if included || self.proof_mode {
+                let mut builtin =
+                    RangeCheckBuiltinRunner::new_range_check96(instance_def.ratio, included);
+                builtin.ratio_den = instance_def.ratio_den;
+                builtin_runners.push((
+                    RangeCheckBuiltinRunner::RANGE_CHECK96_NAME.to_string(),
+                    builtin.into(),
+                ));
+            }
+        }
+
         // Get the builtins that belong to the program but weren't inserted (those who dont belong to the instance)
-        if !program_builtins.is_subset(&inserted_builtins) {
+        let missing_builtins = self
+            .program
+            .builtins
+            .iter()
+            .filter(|name| {
+                !builtin_runners
+                    .iter()
+                    .any(|(inserted, _)| inserted == *name)
+            })
+            .cloned()
+            .collect::<BTreeSet<String>>();
+        if !missing_builtins.is_empty() {
             return Err(RunnerError::NoBuiltinForInstance(
-                program_builtins
-                    .difference(&inserted_builtins)
-                    .map(|x| (**x).clone())
-                    .collect(),
+                missing_builtins,
                 self.layout._name.clone(),
             ));
         }
@@ -222,42 +403,52 @@ impl CairoRunner {
     // Values extracted from here: https://github.com/starkware-libs/cairo-lang/blob/4fb83010ab77aa7ead0c9df4b0c05e030bc70b87/src/starkware/cairo/common/cairo_function_runner.py#L28
     fn initialize_all_builtins(&self, vm: &mut VirtualMachine) -> Result<(), RunnerError> {
         let starknet_preset_builtins = vec![
-            String::from("pedersen"),
-            String::from("range_check"),
-            String::from("output"),
-            String::from("ecdsa"),
-            String::from("bitwise"),
-            String::from("ec_op"),
-            String::from("keccak"),
+            String::from(HashBuiltinRunner::NAME),
+            String::from(RangeCheckBuiltinRunner::NAME),
+            String::from(OutputBuiltinRunner::NAME),
+            String::from(SignatureBuiltinRunner::NAME),
+            String::from(BitwiseBuiltinRunner::NAME),
+            String::from(EcOpBuiltinRunner::NAME),
+            String::from(KeccakBuiltinRunner::NAME),
+            String::from(PoseidonBuiltinRunner::NAME),
         ];
 
         fn initialize_builtin(name: &str, vm: &mut VirtualMachine) {
             match name {
-                "pedersen" => vm
-                    .builtin_runners
-                    .push((name.to_string(), HashBuiltinRunner::new(32, true).into())),
-                "range_check" => vm.builtin_runners.push((
+                HashBuiltinRunner::NAME => vm.builtin_runners.push((
+                    name.to_string(),
+                    HashBuiltinRunner::new(&PedersenInstanceDef::new(32, 1), true).into(),
+                )),
+                RangeCheckBuiltinRunner::NAME => vm.builtin_runners.push((
                     name.to_string(),
                     RangeCheckBuiltinRunner::new(1, 8, true).into(),
                 )),
-                "output" => vm
+                OutputBuiltinRunner::NAME => vm
                     .builtin_runners
                     .push((name.to_string(), OutputBuiltinRunner::new(true).into())),
-                "ecdsa" => vm.builtin_runners.push((
+                SignatureBuiltinRunner::NAME => vm.builtin_runners.push((
                     name.to_string(),
                     SignatureBuiltinRunner::new(&EcdsaInstanceDef::new(1), true).into(),
                 )),
-                "bitwise" => vm.builtin_runners.push((
+                BitwiseBuiltinRunner::NAME => vm.builtin_runners.push((
                     name.to_string(),
                     BitwiseBuiltinRunner::new(&BitwiseInstanceDef::new(1), true).into(),
                 )),
-                "ec_op" => vm.builtin_runners.push((
+                EcOpBuiltinRunner::NAME => vm.builtin_runners.push((
                     name.to_string(),
                     EcOpBuiltinRunner::new(&EcOpInstanceDef::new(1), true).into(),
                 )),
-                "keccak" => vm.builtin_runners.push((
+                KeccakBuiltinRunner::NAME => vm.builtin_runners.push((
                     name.to_string(),
-                    EcOpBuiltinRunner::new(&EcOpInstanceDef::new(1), true).into(),
+                    KeccakBuiltinRunner::new(&KeccakInstanceDef::new(1), true).into(),
+                )),
+                PoseidonBuiltinRunner::NAME => vm.builtin_runners.push((
+                    name.to_string(),
+                    PoseidonBuiltinRunner::new(&PoseidonInstanceDef::new(1), true).into(),
+                )),
+                RangeCheckBuiltinRunner::RANGE_CHECK96_NAME => vm.builtin_runners.push((
+                    name.to_string(),
+                    RangeCheckBuiltinRunner::new_range_check96(1, true).into(),
                 )),
                 _ => {}
             }
@@ -275,19 +466,56 @@ impl CairoRunner {
     }
 
     ///Creates the necessary segments for the program, execution, and each builtin on the MemorySegmentManager and stores the first adress of each of this new segments as each owner's base
+    ///The program and execution segments can be placed in segments added beforehand, e.g. to
+    ///keep them at fixed indices when other segments are created before the runner's. Such a
+    ///base must refer to an empty segment that was already added.
+    ///Returns the base of each segment.
     pub fn initialize_segments(
         &mut self,
         vm: &mut VirtualMachine,
         program_base: Option<Relocatable>,
-    ) {
-        self.program_base = match program_base {
-            Some(base) => Some(base),
-            None => Some(vm.segments.add(&mut vm.memory)),
+        execution_base: Option<Relocatable>,
+    ) -> Result<SegmentLayout, RunnerError> {
+        for base in program_base.iter().chain(execution_base.iter()) {
+            if !is_empty_added_segment(vm, base) {
+                return Err(RunnerError::InvalidSegmentBase(*base));
+            }
+        }
+        if let (Some(program_base), Some(execution_base)) = (program_base, execution_base) {
+            if program_base.segment_index == execution_base.segment_index {
+                return Err(RunnerError::InvalidSegmentBase(execution_base));
+            }
+        }
+        // Fail before adding any segment if the builtins were already initialized
+        if let Some((_, builtin)) = vm
+            .builtin_runners
+            .iter()
+            .find(|(_, builtin)| builtin.is_initialized())
+        {
+            return Err(RunnerError::BuiltinAlreadyInitialized(builtin.name()));
+        }
+
+        let program = match program_base {
+            Some(base) => base,
+            None => vm.segments.add(&mut vm.memory),
         };
-        self.execution_base = Some(vm.segments.add(&mut vm.memory));
-        for (_key, builtin_runner) in vm.builtin_runners.iter_mut() {
-            builtin_runner.initialize_segments(&mut vm.segments, &mut vm.memory);
+        let execution = match execution_base {
+            Some(base) => base,
+            None => vm.segments.add(&mut vm.memory),
+        };
+        self.program_base = Some(program);
+        self.execution_base = Some(execution);
+        let mut builtins = Vec::with_capacity(vm.builtin_runners.len());
+        for (name, builtin_runner) in vm.builtin_runners.iter_mut() {
+            builtin_runner.initialize_segments(&mut vm.segments, &mut vm.memory)?;
+            builtins.push((name.clone(), Relocatable::from((builtin_runner.base(), 0))));
         }
+        vm.index_builtin_segments();
+        Ok(SegmentLayout {
+            program,
+            execution,
+            builtins,
+        })
     }
 
     fn initialize_state(
@@ -408,9 +636,15 @@ impl CairoRunner {
         vm.run_context.pc = *self.initial_pc.as_ref().ok_or(RunnerError::NoPC)?;
         vm.run_context.ap = self.initial_ap.as_ref().ok_or(RunnerError::NoAP)?.offset;
         vm.run_context.fp = self.initial_fp.as_ref().ok_or(RunnerError::NoFP)?.offset;
-        vm._program_base = Some(MaybeRelocatable::from(
-            self.program_base.as_ref().ok_or(RunnerError::NoProgBase)?,
-        ));
+        let program_base = self.program_base.ok_or(RunnerError::NoProgBase)?;
+        vm._program_base = Some(MaybeRelocatable::from(program_base));
+        vm.prime = self.prime.clone();
+        vm.label_constants = self
+            .program
+            .label_constants
+            .iter()
+            .map(|(name, pc)| (name.clone(), program_base + *pc))
+            .collect();
         for (_, builtin) in vm.builtin_runners.iter() {
             builtin.add_validation_rule(&mut vm.memory)?;
         }
@@ -422,7 +656,7 @@ impl CairoRunner {
             .unwrap_or(&Relocatable::from((0, 0)))
             .segment_index;
 
-        let initial_accessed_addresses = (0..self.program.data.len())
+        let initial_accessed_addresses = (0..self.get_program_segment_size())
             .map(|offset| Relocatable::from((prog_segment_index, offset)))
             .collect();
 
@@ -437,56 +671,144 @@ impl CairoRunner {
         self.initial_fp
     }
 
+    /// Returns the pc the run is at, e.g. to checkpoint its position between calls to
+    /// `run_until_pc`.
+    pub fn get_pc(&self, vm: &VirtualMachine) -> Relocatable {
+        *vm.get_pc()
+    }
+
+    /// Returns the ap the run is at.
+    pub fn get_ap(&self, vm: &VirtualMachine) -> Relocatable {
+        vm.get_ap()
+    }
+
+    /// Returns the fp the run is at.
+    pub fn get_fp(&self, vm: &VirtualMachine) -> Relocatable {
+        vm.get_fp()
+    }
+
     pub fn get_reference_list(&self) -> HashMap<usize, HintReference> {
-        let mut references = HashMap::<usize, HintReference>::new();
-
-        for (i, reference) in self.program.reference_manager.references.iter().enumerate() {
-            references.insert(
-                i,
-                HintReference {
-                    offset1: reference.value_address.offset1.clone(),
-                    offset2: reference.value_address.offset2.clone(),
-                    dereference: reference.value_address.dereference,
-                    // only store `ap` tracking data if the reference is referred to it
-                    ap_tracking_data: match (
-                        &reference.value_address.offset1,
-                        &reference.value_address.offset2,
-                    ) {
-                        (OffsetValue::Reference(Register::AP, _, _), _)
-                        | (_, OffsetValue::Reference(Register::AP, _, _)) => {
-                            Some(reference.ap_tracking_data.clone())
-                        }
-                        _ => None,
-                    },
-                    cairo_type: Some(reference.value_address.value_type.clone()),
-                },
-            );
-        }
-        references
+        program_reference_list(&self.program)
     }
 
-    /// Gets the data used by the HintProcessor to execute each hint
+    /// Gets the data used by the HintProcessor to execute each hint, keyed by the address of
+    /// the hint's pc in the program segment.
     pub fn get_hint_data_dictionary(
         &self,
         references: &HashMap<usize, HintReference>,
         hint_executor: &mut dyn HintProcessor,
-    ) -> Result<HashMap<usize, Vec<Box<dyn Any>>>, VirtualMachineError> {
-        let mut hint_data_dictionary = HashMap::<usize, Vec<Box<dyn Any>>>::new();
-        for (hint_index, hints) in self.program.hints.iter() {
-            for hint in hints {
-                let hint_data = hint_executor.compile_hint(
-                    &hint.code,
-                    &hint.flow_tracking_data.ap_tracking,
-                    &hint.flow_tracking_data.reference_ids,
-                    references,
-                );
-                hint_data_dictionary.entry(*hint_index).or_default().push(
-                    hint_data
-                        .map_err(|_| VirtualMachineError::CompileHintFail(hint.code.clone()))?,
-                );
-            }
+    ) -> Result<HashMap<Relocatable, Vec<Box<dyn Any>>>, VirtualMachineError> {
+        let program_base = self.program_base.ok_or(RunnerError::NoProgBase)?;
+        compile_program_hints(&self.program, program_base, references, hint_executor)
+    }
+
+    /// Compiles the hints of a program loaded at `base`, e.g. a program run by a bootloader,
+    /// so that they are executed when the pc reaches them.
+    pub fn load_program_hints(
+        &mut self,
+        program: &Program,
+        base: Relocatable,
+        hint_executor: &mut dyn HintProcessor,
+    ) -> Result<(), VirtualMachineError> {
+        let references = program_reference_list(program);
+        let hint_data = compile_program_hints(program, base, &references, hint_executor)?;
+        self.hint_data.extend(hint_data);
+        Ok(())
+    }
+
+    /// Compiles the hints of the runner's own program the first time it's run, and the ones of
+    /// the child programs loaded since the last run.
+    fn load_own_program_hints(
+        &mut self,
+        hint_executor: &mut dyn HintProcessor,
+    ) -> Result<(), VirtualMachineError> {
+        if !self.program_hints_loaded {
+            let references = self.get_reference_list();
+            let hint_data = self.get_hint_data_dictionary(&references, hint_executor)?;
+            self.hint_data.extend(hint_data);
+            self.program_hints_loaded = true;
+        }
+        for (program, base) in std::mem::take(&mut self.pending_child_hints) {
+            self.load_program_hints(&program, base, hint_executor)?;
+        }
+        Ok(())
+    }
+
+    /// Loads another program in the program segment, e.g. the task of a bootloader, so that
+    /// it can be called by the runner's program or run through `run_from_entrypoint`.
+    /// The program is loaded at `base`, or right after the programs already loaded if None.
+    /// Its hints are compiled on the next run, and are given the constants of the runner's
+    /// program. The child programs are program memory for the security checks and the
+    /// finalization of the program segment.
+    /// Must be called after `initialize_segments`.
+    pub fn load_child_program(
+        &mut self,
+        program: &Program,
+        base: Option<Relocatable>,
+        vm: &mut VirtualMachine,
+    ) -> Result<LoadedProgram, RunnerError> {
+        let program_base = self.program_base.ok_or(RunnerError::NoProgBase)?;
+        let base = base.unwrap_or(program_base + self.get_program_segment_size());
+        if base.segment_index != program_base.segment_index || base.offset < program_base.offset {
+            return Err(RunnerError::ChildProgramOutsideProgramSegment(base));
+        }
+        let end = base.offset + program.data.len();
+        let overlaps = |start: usize, size: usize| start < end && base.offset < start + size;
+        if overlaps(program_base.offset, self.program.data.len())
+            || self
+                .child_programs
+                .iter()
+                .any(|child| overlaps(child.base.offset, child.size))
+        {
+            return Err(RunnerError::ChildProgramOverlap(base));
+        }
+        vm.segments
+            .load_data(&mut vm.memory, &base.into(), &program.data)
+            .map_err(|_| RunnerError::ChildProgramOverlap(base))?;
+        if let Some(accessed_addresses) = vm.accessed_addresses.as_mut() {
+            accessed_addresses.extend((0..program.data.len()).map(|offset| base + offset));
         }
-        Ok(hint_data_dictionary)
+
+        let entrypoints = program
+            .identifiers
+            .iter()
+            .filter(|(_, identifier)| {
+                matches!(
+                    identifier.type_.as_deref(),
+                    Some("function") | Some("label")
+                )
+            })
+            .filter_map(|(name, identifier)| {
+                Some((
+                    name.clone(),
+                    base.offset - program_base.offset + identifier.pc?,
+                ))
+            })
+            .collect();
+        let loaded_program = LoadedProgram {
+            base,
+            size: program.data.len(),
+            builtins: program.builtins.clone(),
+            entrypoints,
+        };
+        self.pending_child_hints.push((program.clone(), base));
+        self.child_programs.push(loaded_program.clone());
+        Ok(loaded_program)
+    }
+
+    /// Returns the child programs loaded by `load_child_program`.
+    pub fn get_child_programs(&self) -> &[LoadedProgram] {
+        &self.child_programs
+    }
+
+    // Returns the size of the program segment, which holds the runner's program followed by
+    // the child programs, relative to the program base.
+    pub(crate) fn get_program_segment_size(&self) -> usize {
+        let program_base_offset = self.program_base.map_or(0, |base| base.offset);
+        self.child_programs
+            .iter()
+            .map(|child| child.base.offset - program_base_offset + child.size)
+            .fold(self.program.data.len(), usize::max)
     }
 
     pub fn get_constants(&self) -> &HashMap<String, Felt> {
@@ -497,101 +819,133 @@ impl CairoRunner {
         &self.program.builtins
     }
 
+    /// Runs until the pc reaches `address`, consuming a step of `run_resources` for each step.
+    /// Fails with `UnfinishedExecution` if the resources are exhausted before that.
+    /// Can be called again with other addresses to resume the run, until `end_run` is called.
+    /// The hints are compiled on the first run and kept by the runner for the next ones.
     pub fn run_until_pc(
         &mut self,
         address: Relocatable,
+        run_resources: &mut RunResources,
         vm: &mut VirtualMachine,
         hint_processor: &mut dyn HintProcessor,
     ) -> Result<(), VirtualMachineError> {
-        let references = self.get_reference_list();
-        let hint_data_dictionary = self.get_hint_data_dictionary(&references, hint_processor)?;
+        #[cfg(feature = "tracing")]
+        let _span = tracing::info_span!("run_until_pc", end = %address).entered();
+        if self.run_ended {
+            return Err(RunnerError::RunAlreadyFinished.into());
+        }
+        self.load_own_program_hints(hint_processor)?;
+        let mut n_steps = 0;
         while vm.run_context.pc != address {
+            if run_resources.consumed() {
+                return Err(VirtualMachineError::UnfinishedExecution(n_steps));
+            }
             vm.step(
                 hint_processor,
                 &mut self.exec_scopes,
-                &hint_data_dictionary,
+                &self.hint_data,
                 &self.program.constants,
+                run_resources,
             )?;
+            run_resources.consume_step();
+            n_steps += 1;
         }
         Ok(())
     }
 
-    /// Execute an exact number of steps on the program from the actual position.
+    /// Execute at most a number of steps on the program from the actual position, stopping early
+    /// if the final pc is reached. Returns whether the final pc was reached.
+    /// The runner can be resumed afterwards with any of the run methods, until `end_run` is called.
     pub fn run_for_steps(
         &mut self,
         steps: usize,
         vm: &mut VirtualMachine,
         hint_processor: &mut dyn HintProcessor,
-    ) -> Result<(), VirtualMachineError> {
-        let references = self.get_reference_list();
-        let hint_data_dictionary = self.get_hint_data_dictionary(&references, hint_processor)?;
+    ) -> Result<bool, VirtualMachineError> {
+        if self.run_ended {
+            return Err(RunnerError::RunAlreadyFinished.into());
+        }
+        self.load_own_program_hints(hint_processor)?;
 
-        for remaining_steps in (1..=steps).rev() {
+        // The steps are already bounded, so the hints run with unbounded resources
+        let mut run_resources = RunResources::default();
+        for _ in 0..steps {
             if self.final_pc.as_ref() == Some(&vm.run_context.pc) {
-                return Err(VirtualMachineError::EndOfProgram(remaining_steps));
+                return Ok(true);
             }
 
             vm.step(
                 hint_processor,
                 &mut self.exec_scopes,
-                &hint_data_dictionary,
+                &self.hint_data,
                 &self.program.constants,
+                &mut run_resources,
             )?;
         }
 
-        Ok(())
+        Ok(self.final_pc.as_ref() == Some(&vm.run_context.pc))
     }
 
     /// Execute steps until a number of steps since the start of the program is reached.
+    /// Does nothing if that number was already reached.
+    /// Fails with `EndOfProgram` and the number of steps left if the final pc is reached first.
     pub fn run_until_steps(
         &mut self,
         steps: usize,
         vm: &mut VirtualMachine,
         hint_processor: &mut dyn HintProcessor,
     ) -> Result<(), VirtualMachineError> {
-        self.run_for_steps(steps.saturating_sub(vm.current_step), vm, hint_processor)
+        self.run_for_steps(steps.saturating_sub(vm.current_step), vm, hint_processor)?;
+        if vm.current_step < steps {
+            return Err(VirtualMachineError::EndOfProgram(steps - vm.current_step));
+        }
+        Ok(())
     }
 
     /// Execute steps until the step counter reaches a power of two.
+    /// Only available in proof mode, where the program ends in an infinite loop that pads the trace.
     pub fn run_until_next_power_of_2(
         &mut self,
         vm: &mut VirtualMachine,
         hint_processor: &mut dyn HintProcessor,
     ) -> Result<(), VirtualMachineError> {
+        if !self.proof_mode {
+            return Err(RunnerError::PaddingNoProofMode.into());
+        }
         self.run_until_steps(vm.current_step.next_power_of_two(), vm, hint_processor)
     }
 
+    /// Returns the lowest and highest values checked by the permutation range check: the
+    /// biased offsets of every executed instruction and the range check usage of the builtins.
+    /// Returns `None` when the trace isn't enabled, as the executed instructions are unknown.
     pub fn get_perm_range_check_limits(
         &self,
         vm: &VirtualMachine,
     ) -> Result<Option<(isize, isize)>, VirtualMachineError> {
-        let limits = get_perm_range_check_limits(
-            vm.trace.as_ref().ok_or(VirtualMachineError::TracerError(
-                TraceError::TraceNotEnabled,
-            ))?,
-            &vm.memory,
-        )?;
-
-        match limits {
-            Some((mut rc_min, mut rc_max)) => {
-                for (_, runner) in &vm.builtin_runners {
-                    let (runner_min, runner_max) = match runner.get_range_check_usage(&vm.memory) {
-                        Some(x) => x,
-                        None => continue,
-                    };
-
-                    rc_min = rc_min.min(runner_min as isize);
-                    rc_max = rc_max.max(runner_max as isize);
-                }
+        let trace = match vm.trace.as_ref() {
+            Some(trace) => trace,
+            None => return Ok(None),
+        };
+        let limits = get_perm_range_check_limits(trace, &vm.memory)?;
 
-                Ok(Some((rc_min, rc_max)))
-            }
-            None => Ok(None),
-        }
+        Ok(vm
+            .builtin_runners
+            .iter()
+            .filter_map(|(_, runner)| runner.get_range_check_usage(&vm.memory))
+            .fold(limits, |limits, (runner_min, runner_max)| {
+                let (runner_min, runner_max) = (runner_min as isize, runner_max as isize);
+                Some(limits.map_or((runner_min, runner_max), |(rc_min, rc_max)| {
+                    (rc_min.min(runner_min), rc_max.max(runner_max))
+                }))
+            }))
     }
 
     /// Checks that there are enough trace cells to fill the entire range check
     /// range.
+    /// The layout allocates `rc_units` range check units per step, out of which the
+    /// builtins and the instructions (3 per step, one for each offset) are served. Enough
+    /// units must remain to fill every value between the lowest and highest checked ones.
     pub fn check_range_check_usage(&self, vm: &VirtualMachine) -> Result<(), VirtualMachineError> {
         let (rc_min, rc_max) = match self.get_perm_range_check_limits(vm)? {
             Some(x) => x,
@@ -603,54 +957,77 @@ impl CairoRunner {
             rc_units_used_by_builtins += builtin_runner.get_used_perm_range_check_units(vm)?;
         }
 
-        let unused_rc_units =
-            (self.layout.rc_units as usize - 3) * vm.current_step - rc_units_used_by_builtins;
-        if unused_rc_units < (rc_max - rc_min) as usize {
-            return Err(MemoryError::InsufficientAllocatedCells.into());
+        let rc_units_used = rc_units_used_by_builtins + 3 * vm.current_step;
+        let rc_units_reserved = (rc_max - rc_min) as usize;
+        let rc_units = self.layout.rc_units as usize * vm.current_step;
+        if rc_units_used + rc_units_reserved > rc_units {
+            return Err(MemoryError::InsufficientRangeCheckUnits(
+                rc_units_used,
+                rc_units.saturating_sub(rc_units_reserved),
+            )
+            .into());
         }
 
         Ok(())
     }
 
-    /// Count the number of holes present in the segments.
+    /// Marks the program and the public memory of the execution segment, its initial stack, as
+    /// accessed. They are part of the public memory in proof mode, so they mustn't be counted as
+    /// memory holes even when the run doesn't read them, e.g. the dummy return fp and pc.
+    /// Does nothing outside of proof mode.
+    pub fn mark_initial_segments_as_accessed(
+        &self,
+        vm: &mut VirtualMachine,
+    ) -> Result<(), RunnerError> {
+        if !self.proof_mode {
+            return Ok(());
+        }
+        let program_base = self.program_base.ok_or(RunnerError::NoProgBase)?;
+        let execution_base = self.execution_base.ok_or(RunnerError::NoExecBase)?;
+        if let Some(accessed_addresses) = vm.accessed_addresses.as_mut() {
+            accessed_addresses
+                .extend((0..self.get_program_segment_size()).map(|offset| program_base + offset));
+            accessed_addresses.extend(
+                self.execution_public_memory
+                    .iter()
+                    .flatten()
+                    .map(|offset| execution_base + *offset),
+            );
+        }
+        Ok(())
+    }
+
+    /// Count the number of holes present in the segments: the cells below the size of a segment
+    /// which were never accessed. The builtin segments are left out, as the holes in them are
+    /// accounted for by the builtins' cells.
     pub fn get_memory_holes(&self, vm: &VirtualMachine) -> Result<usize, MemoryError> {
         let program_addresses =
-            (0..self.program.data.len()).map(|offset| Relocatable::from((0, offset)));
+            (0..self.get_program_segment_size()).map(|offset| Relocatable::from((0, offset)));
 
+        let builtin_segments = vm
+            .builtin_runners
+            .iter()
+            .map(|(_, runner)| runner.base())
+            .collect::<BTreeSet<_>>();
         let accessed_addresses = vm
             .accessed_addresses
             .as_ref()
             .ok_or(MemoryError::MissingAccessedAddresses)?
             .iter()
-            .map(|addr| vm.memory.relocate_value(*addr));
-
-        let builtin_addresses = vm
-            .builtin_runners
-            .iter()
-            .map(|(_, runner)| runner.base())
-            .collect::<Vec<_>>()
-            .into_iter()
-            .flat_map(|base| {
-                let size = vm.segments.get_segment_size(base as usize).unwrap();
-                (0..size).map(move |offset| Relocatable::from((base, offset)))
-            });
+            .map(|addr| vm.memory.relocate_value(*addr))
+            .filter(|addr| !builtin_segments.contains(&addr.segment_index));
 
-        let addresses = program_addresses
-            .chain(accessed_addresses)
-            .chain(builtin_addresses);
-        vm.segments.get_memory_holes(addresses)
+        vm.segments
+            .get_memory_holes(program_addresses.chain(accessed_addresses))
     }
 
-    /// Check if there are enough trace cells to fill the entire diluted checks.
-    pub fn check_diluted_check_usage(
+    /// Sums the diluted units used by the builtins over the whole run. Fails if they use more
+    /// units than the diluted pool provides for the current step.
+    pub(crate) fn get_used_diluted_check_units_sum(
         &self,
         vm: &VirtualMachine,
-    ) -> Result<(), VirtualMachineError> {
-        let diluted_pool_instance = match &self.layout.diluted_pool_instance_def {
-            Some(x) => x,
-            None => return Ok(()),
-        };
-
+        diluted_pool_instance: &DilutedPoolInstanceDef,
+    ) -> Result<usize, VirtualMachineError> {
         let mut used_units_by_builtins = 0;
         for (_, builtin_runner) in &vm.builtin_runners {
             let used_units = builtin_runner.get_used_diluted_check_units(
@@ -659,23 +1036,57 @@ impl CairoRunner {
             );
 
             let multiplier = safe_div_usize(
-                vm.current_step,
+                vm.current_step * builtin_runner.ratio_den().unwrap_or(1) as usize,
                 builtin_runner.ratio().unwrap_or(1) as usize,
             )?;
             used_units_by_builtins += used_units * multiplier;
         }
 
+        let diluted_units = diluted_pool_instance.units_per_step as usize * vm.current_step;
+        if used_units_by_builtins > diluted_units {
+            return Err(MemoryError::DilutedUnitsExceeded {
+                used: used_units_by_builtins,
+                available: diluted_units,
+            }
+            .into());
+        }
+        Ok(used_units_by_builtins)
+    }
+
+    /// Check if there are enough trace cells to fill the entire diluted checks.
+    /// The layout allocates `units_per_step` diluted units per step. Once the builtins are
+    /// served, the unused units must cover every one of the `2 ^ n_bits` diluted values.
+    /// Layouts without a diluted pool skip the check.
+    pub fn check_diluted_check_usage(
+        &self,
+        vm: &VirtualMachine,
+    ) -> Result<(), VirtualMachineError> {
+        let diluted_pool_instance = match &self.layout.diluted_pool_instance_def {
+            Some(x) => x,
+            None => return Ok(()),
+        };
+
+        let used_units_by_builtins =
+            self.get_used_diluted_check_units_sum(vm, diluted_pool_instance)?;
         let diluted_units = diluted_pool_instance.units_per_step as usize * vm.current_step;
         let unused_diluted_units = diluted_units - used_units_by_builtins;
 
         let diluted_usage_upper_bound = 1usize << diluted_pool_instance.n_bits;
         if unused_diluted_units < diluted_usage_upper_bound {
-            return Err(MemoryError::InsufficientAllocatedCells.into());
+            return Err(MemoryError::InsufficientDilutedUnits {
+                unused: unused_diluted_units,
+                required: diluted_usage_upper_bound,
+            }
+            .into());
         }
 
         Ok(())
     }
 
+    /// Ends the run: relocates the temporary segments, computes the effective sizes of the
+    /// segments and, in proof mode, pads the trace unless `disable_trace_padding` is set.
+    /// The memory is frozen afterwards, and the run can neither be resumed nor ended again,
+    /// even if ending it failed.
     pub fn end_run(
         &mut self,
         disable_trace_padding: bool,
@@ -686,9 +1097,43 @@ impl CairoRunner {
         if self.run_ended {
             return Err(RunnerError::RunAlreadyFinished.into());
         }
+        let result = self.finish_run(
+            disable_trace_padding,
+            disable_finalize_all,
+            vm,
+            hint_processor,
+        );
+        self.run_ended = true;
+        let n_segments = vm.memory.data.len() as isize;
+        let n_temporary_segments = vm.memory.temp_data.len() as isize;
+        for segment_index in -n_temporary_segments..n_segments {
+            vm.memory.freeze_segment(segment_index);
+        }
+        result
+    }
 
+    fn finish_run(
+        &mut self,
+        disable_trace_padding: bool,
+        disable_finalize_all: bool,
+        vm: &mut VirtualMachine,
+        hint_processor: &mut dyn HintProcessor,
+    ) -> Result<(), VirtualMachineError> {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::info_span!("end_run").entered();
+
+        self.dropped_temporary_segments = vm.memory.drop_unrelocated_temporary_segments()?;
+        #[cfg(feature = "tracing")]
+        tracing::debug!(
+            count = self.dropped_temporary_segments,
+            "temporary segments dropped"
+        );
+        vm.relocate_temporary_builtin_segments()?;
         vm.memory.relocate_memory()?;
         vm.end_run(&self.exec_scopes)?;
+        #[cfg(feature = "tracing")]
+        tracing::info!(steps = vm.current_step, "vm run ended");
+        self.mark_initial_segments_as_accessed(vm)?;
 
         if disable_finalize_all {
             return Ok(());
@@ -696,13 +1141,19 @@ impl CairoRunner {
 
         vm.segments.compute_effective_sizes(&vm.memory);
         if self.proof_mode && !disable_trace_padding {
+            #[cfg(feature = "tracing")]
+            tracing::debug!(steps = vm.current_step, "padding the trace");
             self.run_until_next_power_of_2(vm, hint_processor)?;
             loop {
                 match self.check_used_cells(vm) {
                     Ok(_) => break,
                     Err(e) => match e {
                         VirtualMachineError::MemoryError(
-                            MemoryError::InsufficientAllocatedCells,
+                            MemoryError::InsufficientAllocatedCells { .. }
+                            | MemoryError::MinStepNotReached { .. }
+                            | MemoryError::InsufficientDilutedUnits { .. }
+                            | MemoryError::InsufficientMemoryUnits { .. }
+                            | MemoryError::InsufficientRangeCheckUnits(..),
                         ) => {}
                         e => return Err(e),
                     },
@@ -711,9 +1162,13 @@ impl CairoRunner {
                 self.run_for_steps(1, vm, hint_processor)?;
                 self.run_until_next_power_of_2(vm, hint_processor)?;
             }
+        } else if self.proof_mode {
+            // Without padding the trace can't grow, so a run that exceeds the layout's
+            // range check or diluted pool capacity can't be proven.
+            self.check_range_check_usage(vm)?;
+            self.check_diluted_check_usage(vm)?;
         }
 
-        self.run_ended = true;
         Ok(())
     }
 
@@ -732,19 +1187,17 @@ impl CairoRunner {
         self.relocated_memory.push(None);
         for (index, segment) in vm.memory.data.iter().enumerate() {
             for (seg_offset, element) in segment.iter().enumerate() {
-                match element {
-                    Some(elem) => {
-                        let relocated_addr = relocate_address(
-                            Relocatable::from((index as isize, seg_offset)),
-                            relocation_table,
-                        )?;
-                        let value = relocate_value(elem.clone(), relocation_table)?;
-                        if self.relocated_memory.len() <= relocated_addr {
-                            self.relocated_memory.resize(relocated_addr + 1, None);
-                        }
-                        self.relocated_memory[relocated_addr] = Some(value);
-                    }
-                    None => self.relocated_memory.push(None),
+                // Segments may be relocated out of order, so holes are filled by address too
+                let relocated_addr = relocate_address(
+                    Relocatable::from((index as isize, seg_offset)),
+                    relocation_table,
+                )?;
+                if self.relocated_memory.len() <= relocated_addr {
+                    self.relocated_memory.resize(relocated_addr + 1, None);
+                }
+                if let Some(elem) = element {
+                    let value = relocate_value(elem.clone(), relocation_table)?;
+                    self.relocated_memory[relocated_addr] = Some(value);
                 }
             }
         }
@@ -774,55 +1227,108 @@ impl CairoRunner {
         Ok(())
     }
 
-    pub fn relocate(&mut self, vm: &mut VirtualMachine) -> Result<(), TraceError> {
+    /// Relocates the memory and the trace, applying the relocation rules of the temporary segments.
+    /// Fails if a value points to a temporary segment without a relocation rule.
+    /// `overrides` maps segment indexes to the first
+    /// relocated address they must be placed at, see
+    /// `MemorySegmentManager::relocate_segments_with_offsets`.
+    pub fn relocate(
+        &mut self,
+        vm: &mut VirtualMachine,
+        overrides: Option<&HashMap<usize, usize>>,
+    ) -> Result<(), TraceError> {
+        self.relocate_and_get_table(vm, overrides).map(|_| ())
+    }
+
+    /// Relocates the memory and the trace, returning the relocation table used to do so.
+    fn relocate_and_get_table(
+        &mut self,
+        vm: &mut VirtualMachine,
+        overrides: Option<&HashMap<usize, usize>>,
+    ) -> Result<Vec<usize>, TraceError> {
+        // Temporary segments are moved to their destination first, which end_run already did
+        // unless it wasn't called
+        vm.memory.relocate_memory()?;
         vm.segments.compute_effective_sizes(&vm.memory);
-        // relocate_segments can fail if compute_effective_sizes is not called before.
-        // The expect should be unreachable.
-        let relocation_table = vm
-            .segments
-            .relocate_segments()
-            .expect("compute_effective_sizes called but relocate_memory still returned error");
+        let relocation_table = match overrides {
+            Some(overrides) => vm.segments.relocate_segments_with_offsets(overrides)?,
+            None => vm.segments.relocate_segments()?,
+        };
         if let Err(memory_error) = self.relocate_memory(vm, &relocation_table) {
             return Err(TraceError::MemoryError(memory_error));
         }
         if vm.trace.is_some() {
             self.relocate_trace(vm, &relocation_table)?;
         }
-        Ok(())
+        Ok(relocation_table)
+    }
+
+    /// Returns the trace relocated by `relocate`, whose registers are relocated addresses.
+    pub fn get_relocated_trace(&self) -> Result<&Vec<RelocatedTraceEntry>, TraceError> {
+        self.relocated_trace
+            .as_ref()
+            .ok_or(TraceError::TraceNotRelocated)
     }
 
     pub fn get_builtin_segments_info(
         &self,
         vm: &VirtualMachine,
-    ) -> Result<HashMap<&'static str, SegmentInfo>, RunnerError> {
-        let mut builtin_segments = HashMap::new();
-
-        for (_, builtin) in &vm.builtin_runners {
-            let (name, segment_address) = builtin.get_memory_segment_addresses();
-            if builtin_segments.contains_key(&name) {
-                return Err(RunnerError::BuiltinSegmentNameCollision(name));
-            }
-
-            let index = segment_address.0;
-            let size = segment_address.1.ok_or(RunnerError::BaseNotFinished)?;
-
-            builtin_segments.insert(name, SegmentInfo { index, size });
-        }
+    ) -> Result<HashMap<String, SegmentInfo>, RunnerError> {
+        collect_segments_info(
+            vm.builtin_runners
+                .iter()
+                .flat_map(|(_, builtin)| builtin.get_memory_segment_addresses()),
+        )
+    }
 
-        Ok(builtin_segments)
+    /// Returns the used and allocated instances of each builtin, to tune the ratios of a layout.
+    /// The segment sizes must have been computed, see `MemorySegmentManager::compute_effective_sizes`.
+    pub fn get_builtin_usage_report(
+        &self,
+        vm: &VirtualMachine,
+    ) -> Result<Vec<BuiltinUsage>, MemoryError> {
+        vm.builtin_runners
+            .iter()
+            .map(|(name, builtin)| {
+                let used_instances = builtin.get_used_instances(vm)?;
+                let allocated_instances = builtin.ratio().and_then(|ratio| {
+                    let ratio_den = builtin.ratio_den().unwrap_or(1) as usize;
+                    (vm.current_step * ratio_den).checked_div(ratio as usize)
+                });
+                let utilization = allocated_instances
+                    .filter(|allocated_instances| *allocated_instances != 0)
+                    .map(|allocated_instances| {
+                        100.0 * used_instances as f64 / allocated_instances as f64
+                    });
+                Ok(BuiltinUsage {
+                    name: name.clone(),
+                    used_instances,
+                    allocated_instances,
+                    utilization,
+                })
+            })
+            .collect()
     }
 
     pub fn get_execution_resources(
         &self,
         vm: &VirtualMachine,
     ) -> Result<ExecutionResources, TraceError> {
+        Ok(self.execution_resources(vm)?)
+    }
+
+    fn execution_resources(&self, vm: &VirtualMachine) -> Result<ExecutionResources, MemoryError> {
         let n_steps = match self.original_steps {
             Some(x) => x,
-            None => vm.trace.as_ref().map(|x| x.len()).unwrap_or(0),
+            None => vm
+                .trace
+                .as_ref()
+                .map(|x| x.len())
+                .unwrap_or(vm.current_step),
         };
         let n_memory_holes = self.get_memory_holes(vm)?;
 
-        let mut builtin_instance_counter = HashMap::new();
+        let mut builtin_instance_counter = BTreeMap::new();
         for (builtin_name, builtin_runner) in &vm.builtin_runners {
             builtin_instance_counter.insert(
                 builtin_name.to_string(),
@@ -834,51 +1340,314 @@ impl CairoRunner {
             n_steps,
             n_memory_holes,
             builtin_instance_counter,
+            n_dropped_temporary_segments: self.dropped_temporary_segments,
         })
     }
 
-    pub fn get_output(&mut self, vm: &mut VirtualMachine) -> Result<String, RunnerError> {
-        let mut output = Vec::<u8>::new();
-        self.write_output(vm, &mut output)?;
-        let output = String::from_utf8(output).map_err(|_| RunnerError::FailedStringConversion)?;
-        Ok(output)
+    /// Returns the public input of the AIR: the range check limits, the number of steps, the
+    /// relocated addresses of each segment and the public memory.
+    /// Must be called after `finalize_segments`, which sets the public memory.
+    pub fn get_air_public_input(
+        &self,
+        vm: &VirtualMachine,
+    ) -> Result<PublicInput, VirtualMachineError> {
+        let relocation_table = vm.segments.relocate_segments()?;
+        let rc_limits = self
+            .get_perm_range_check_limits(vm)?
+            .ok_or(RunnerError::NoRangeCheckLimits)?;
+        self.build_air_public_input(vm, &relocation_table, rc_limits)
     }
 
-    /// Writes the values hosted in the output builtin's segment.
-    /// Does nothing if the output builtin is not present in the program.
-    pub fn write_output(
-        &mut self,
-        vm: &mut VirtualMachine,
-        stdout: &mut dyn io::Write,
-    ) -> Result<(), RunnerError> {
-        let builtin = vm
-            .builtin_runners
-            .iter_mut()
-            .find_map(|(k, v)| match k.as_str() {
-                "output" => Some(v),
-                _ => None,
-            });
-        let builtin = match builtin {
-            Some(x) => x,
-            _ => return Ok(()),
-        };
+    fn build_air_public_input(
+        &self,
+        vm: &VirtualMachine,
+        relocation_table: &Vec<usize>,
+        (rc_min, rc_max): (isize, isize),
+    ) -> Result<PublicInput, VirtualMachineError> {
+        let segment_addresses = |begin: Relocatable, stop: Relocatable| {
+            Ok::<_, MemoryError>(MemorySegmentAddresses {
+                begin_addr: relocate_address(begin, relocation_table)?,
+                stop_ptr: relocate_address(stop, relocation_table)?,
+            })
+        };
+
+        let mut memory_segments = BTreeMap::new();
+        let program_base = self.program_base.ok_or(RunnerError::NoProgBase)?;
+        memory_segments.insert(
+            "program".to_string(),
+            segment_addresses(program_base, *vm.get_pc())?,
+        );
+        let execution_base = self.execution_base.ok_or(RunnerError::NoExecBase)?;
+        memory_segments.insert(
+            "execution".to_string(),
+            segment_addresses(execution_base, vm.get_ap())?,
+        );
+        for (_, builtin) in &vm.builtin_runners {
+            for segment in builtin.get_memory_segment_addresses() {
+                let begin = Relocatable::from((segment.base, 0));
+                let index = segment
+                    .base
+                    .try_into()
+                    .map_err(|_| MemoryError::AddressInTemporarySegment(segment.base))?;
+                let used_size = vm
+                    .segments
+                    .get_segment_used_size(index)
+                    .ok_or(MemoryError::MissingSegmentUsedSizes(index))?;
+                memory_segments.insert(segment.name, segment_addresses(begin, begin + used_size)?);
+            }
+        }
+
+        let mut public_memory = Vec::new();
+        let mut segments: Vec<_> = vm.segments.public_memory_offsets.iter().collect();
+        segments.sort_by_key(|(segment_index, _)| **segment_index);
+        for (segment_index, offsets) in segments {
+            for (offset, page) in offsets {
+                let address = Relocatable::from((*segment_index as isize, *offset));
+                let value = vm
+                    .memory
+                    .get(&address)?
+                    .ok_or_else(|| RunnerError::MemoryGet(address.into()))?;
+                public_memory.push(PublicMemoryEntry {
+                    address: relocate_address(address, relocation_table)?,
+                    value: relocate_value(value.into_owned(), relocation_table)?,
+                    page: *page,
+                });
+            }
+        }
+
+        Ok(PublicInput {
+            layout: self.layout._name.clone(),
+            rc_min,
+            rc_max,
+            n_steps: vm.current_step,
+            memory_segments,
+            public_memory,
+        })
+    }
+
+    /// Returns the private input of the AIR: the inputs of each builtin instance, keyed by the
+    /// builtin's name. The output builtin has no private input.
+    pub fn get_air_private_input(&self, vm: &VirtualMachine) -> AirPrivateInput {
+        AirPrivateInput {
+            builtins: vm
+                .builtin_runners
+                .iter()
+                .filter(|(_, builtin)| !matches!(builtin, BuiltinRunner::Output(_)))
+                .map(|(name, builtin)| (name.clone(), builtin.air_private_input(&vm.memory)))
+                .collect(),
+        }
+    }
+
+    /// Returns the Cairo PIE of a finished run, which can be written with `CairoPie::write_zip`.
+    /// Must be called after `read_return_values`, which sets the sizes of the builtin segments.
+    pub fn get_cairo_pie(&self, vm: &VirtualMachine) -> Result<CairoPie, RunnerError> {
+        let program_base = self.program_base.ok_or(RunnerError::NoProgBase)?;
+        let execution_base = self.execution_base.ok_or(RunnerError::NoExecBase)?;
+        let initial_fp = self.initial_fp.ok_or(RunnerError::NoFP)?;
+        let segment_info = |segment_index: isize| -> Result<SegmentInfo, MemoryError> {
+            let index = segment_index as usize;
+            Ok(SegmentInfo {
+                index: segment_index,
+                size: vm
+                    .segments
+                    .get_segment_size(index)
+                    .ok_or(MemoryError::MissingSegmentUsedSizes(index))?,
+            })
+        };
+
+        // The return fp and pc pushed before the arguments of main are the bases of their own
+        // segments
+        let return_pointer = |offset: usize| {
+            initial_fp
+                .sub_usize(offset)
+                .and_then(|address| vm.memory.get_relocatable(&address))
+                .map_err(|_| RunnerError::NoReturnPointers)
+        };
+        let ret_fp = return_pointer(2)?;
+        let ret_pc = return_pointer(1)?;
+        let builtin_segments: BTreeMap<String, SegmentInfo> =
+            self.get_builtin_segments_info(vm)?.into_iter().collect();
+        let program_segment = segment_info(program_base.segment_index)?;
+        let execution_segment = segment_info(execution_base.segment_index)?;
+        let ret_fp_segment = segment_info(ret_fp.segment_index)?;
+        let ret_pc_segment = segment_info(ret_pc.segment_index)?;
+
+        let known_segments: BTreeSet<isize> = builtin_segments
+            .values()
+            .chain([
+                &program_segment,
+                &execution_segment,
+                &ret_fp_segment,
+                &ret_pc_segment,
+            ])
+            .map(|segment| segment.index)
+            .collect();
+        let extra_segments = (0..vm.segments.num_segments as isize)
+            .filter(|index| !known_segments.contains(index))
+            .map(segment_info)
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut memory = Vec::new();
+        for (segment_index, segment) in vm.memory.data.iter().enumerate() {
+            for (offset, value) in segment.iter().enumerate() {
+                if let Some(value) = value {
+                    if let MaybeRelocatable::RelocatableValue(relocatable) = value {
+                        if relocatable.segment_index < 0 {
+                            return Err(MemoryError::TemporarySegmentInRelocation(
+                                relocatable.segment_index,
+                            )
+                            .into());
+                        }
+                    }
+                    memory.push((
+                        Relocatable::from((segment_index as isize, offset)),
+                        value.clone(),
+                    ));
+                }
+            }
+        }
+
+        Ok(CairoPie {
+            metadata: CairoPieMetadata {
+                program: StrippedProgram {
+                    data: self
+                        .program
+                        .data
+                        .iter()
+                        .map(|value| value.get_int_ref().cloned())
+                        .collect::<Result<Vec<_>, _>>()
+                        .map_err(|_| RunnerError::FoundNonInt)?,
+                    builtins: self.program.builtins.clone(),
+                    main: self.program.main.ok_or(RunnerError::MissingMain)?,
+                    prime: self.program.prime.clone(),
+                },
+                program_segment,
+                execution_segment,
+                ret_fp_segment,
+                ret_pc_segment,
+                builtin_segments,
+                extra_segments,
+            },
+            memory,
+            additional_data: vm
+                .builtin_runners
+                .iter()
+                .map(|(name, builtin)| (name.clone(), builtin.get_additional_data()))
+                .collect(),
+            execution_resources: self.execution_resources(vm)?,
+        })
+    }
+
+    /// Ends a proof mode run and builds everything the prover consumes: ends the run, reads
+    /// the return values, finalizes the segments and relocates, sharing the relocation table
+    /// and the range check limits between the artifacts. Each artifact
+    /// is equal to what the corresponding getter returns.
+    /// The relocated memory and trace are moved out of the runner into the artifacts, which
+    /// own all their data, so the VM can be dropped afterwards.
+    pub fn finalize_for_proving(
+        &mut self,
+        vm: &mut VirtualMachine,
+        hint_processor: &mut dyn HintProcessor,
+    ) -> Result<RunArtifacts, CairoRunError> {
+        self.end_run(false, false, vm, hint_processor)?;
+        vm.verify_auto_deductions()?;
+        self.read_return_values(vm)?;
+        self.finalize_segments(vm)?;
+
+        let rc_limits = self
+            .get_perm_range_check_limits(vm)?
+            .ok_or(RunnerError::NoRangeCheckLimits)?;
+        let relocation_table = self.relocate_and_get_table(vm, None)?;
+        let public_input = self.build_air_public_input(vm, &relocation_table, rc_limits)?;
+        let private_input = self.get_air_private_input(vm);
+        let execution_resources = self.get_execution_resources(vm)?;
+
+        Ok(RunArtifacts {
+            relocated_memory: std::mem::take(&mut self.relocated_memory),
+            relocated_trace: self
+                .relocated_trace
+                .take()
+                .ok_or(TraceError::TraceNotEnabled)?,
+            public_input,
+            private_input,
+            execution_resources,
+        })
+    }
+
+    pub fn get_output(&mut self, vm: &mut VirtualMachine) -> Result<String, RunnerError> {
+        let mut output = Vec::<u8>::new();
+        self.write_output(vm, &mut output)?;
+        let output = String::from_utf8(output).map_err(|_| RunnerError::FailedStringConversion)?;
+        Ok(output)
+    }
+
+    /// Writes the values hosted in the output builtin's segment, one per line. Integers are
+    /// written in decimal, relocatable values as `segment:offset`, and missing cells as
+    /// `<missing>`.
+    /// Does nothing if the output builtin is not present in the program.
+    pub fn write_output(
+        &mut self,
+        vm: &mut VirtualMachine,
+        stdout: &mut dyn io::Write,
+    ) -> Result<(), RunnerError> {
+        let (base, size) = match output_segment(vm)? {
+            Some(segment) => segment,
+            None => return Ok(()),
+        };
 
-        let segment_used_sizes = vm.segments.compute_effective_sizes(&vm.memory);
-        let base = builtin.base();
+        for value in vm.memory.get_range(&base.into(), size)? {
+            match value.as_deref() {
+                Some(MaybeRelocatable::Int(num)) => {
+                    writeln!(stdout, "{}", as_int(&num.to_biguint().into(), &CAIRO_PRIME))
+                }
+                Some(MaybeRelocatable::RelocatableValue(relocatable)) => {
+                    writeln!(stdout, "{relocatable}")
+                }
+                None => writeln!(stdout, "<missing>"),
+            }
+            .map_err(|_| RunnerError::WriteFail)?;
+        }
 
-        let segment_index: usize = base
-            .try_into()
-            .map_err(|_| RunnerError::RunnerInTemporarySegment(base))?;
+        Ok(())
+    }
 
-        for i in 0..segment_used_sizes[segment_index] {
-            let value = vm
-                .memory
-                .get_integer(&(base, i).into())
-                .map_err(|_| RunnerError::MemoryGet((base, i).into()))?
-                .to_bigint();
-            writeln!(stdout, "{}", value).map_err(|_| RunnerError::WriteFail)?;
+    /// Returns the values hosted in the output builtin's segment, failing if one of them is
+    /// missing.
+    /// Returns no value if the output builtin is not present in the program.
+    pub fn get_output_values(
+        &self,
+        vm: &mut VirtualMachine,
+    ) -> Result<Vec<MaybeRelocatable>, RunnerError> {
+        match output_segment(vm)? {
+            Some((base, size)) => Ok(vm.memory.get_continuous_range(&base.into(), size)?),
+            None => Ok(Vec::new()),
         }
+    }
 
+    /// Writes the instances of the builtin with the given name in a human-readable form, one per
+    /// line after a header with their number, e.g. `0: hash(x, y) = z` for the pedersen builtin.
+    /// The cells holding the value deduced by the builtin are marked with a `*`, and the ones
+    /// holding another value are followed by the deduced one. Missing cells are shown as `_`,
+    /// and the instances missing cells are flagged as incomplete.
+    pub fn dump_builtin(
+        &self,
+        vm: &VirtualMachine,
+        name: &str,
+        writer: &mut impl io::Write,
+    ) -> Result<(), RunnerError> {
+        let builtin = vm
+            .builtin_runners
+            .iter()
+            .find_map(|(builtin_name, builtin)| (builtin_name == name).then_some(builtin))
+            .ok_or_else(|| RunnerError::MissingBuiltin(name.to_string()))?;
+
+        let instances = builtin.get_instances(&vm.memory);
+        writeln!(writer, "{name}: {} instance(s)", instances.len())
+            .map_err(|_| RunnerError::WriteFail)?;
+        for instance in instances.iter() {
+            writeln!(writer, "{}", builtin.describe_instance(instance))
+                .map_err(|_| RunnerError::WriteFail)?;
+        }
         Ok(())
     }
 
@@ -894,7 +1663,7 @@ impl CairoRunner {
         if !self.run_ended {
             return Err(RunnerError::FinalizeNoEndRun);
         }
-        let size = self.program.data.len();
+        let size = self.get_program_segment_size();
         let mut public_memory = Vec::with_capacity(size);
         for i in 0..size {
             public_memory.push((i, 0_usize))
@@ -906,7 +1675,7 @@ impl CairoRunner {
                 .ok_or(RunnerError::NoProgBase)?
                 .segment_index as usize,
             Some(&public_memory),
-        );
+        )?;
         let mut public_memory = Vec::with_capacity(size);
         let exec_base = self
             .execution_base
@@ -921,50 +1690,40 @@ impl CairoRunner {
             public_memory.push((elem + exec_base.offset, 0))
         }
         vm.segments
-            .finalize(None, exec_base.segment_index as usize, Some(&public_memory));
+            .finalize(None, exec_base.segment_index as usize, Some(&public_memory))?;
         for (_, builtin_runner) in vm.builtin_runners.iter() {
             let (_, size) = builtin_runner
                 .get_used_cells_and_allocated_size(vm)
                 .map_err(RunnerError::FinalizeSegements)?;
             vm.segments
-                .finalize(Some(size), builtin_runner.base() as usize, None)
+                .finalize(Some(size), builtin_runner.base() as usize, None)?;
         }
         self.segments_finalized = true;
         Ok(())
     }
 
-    #[allow(clippy::too_many_arguments)]
+    /// Runs the function at `entrypoint` with the given arguments, until it returns.
+    /// Its return values can then be read with `vm.get_return_values`.
     pub fn run_from_entrypoint(
         &mut self,
         entrypoint: usize,
-        args: Vec<&dyn Any>,
-        typed_args: bool,
+        args: &[&CairoArg],
         verify_secure: bool,
-        _apply_modulo_to_args: bool,
+        run_resources: &mut RunResources,
         vm: &mut VirtualMachine,
         hint_processor: &mut dyn HintProcessor,
     ) -> Result<(), VirtualMachineError> {
-        let stack = if typed_args {
-            if args.len() != 1 {
-                return Err(VirtualMachineError::InvalidArgCount(1, args.len()));
-            }
-
-            gen_typed_args(args)?
-        } else {
-            let mut stack = Vec::new();
-            for arg in args {
-                stack.push(vm.segments.gen_arg(arg, &mut vm.memory)?);
-            }
-
-            stack
-        };
+        let stack = args
+            .iter()
+            .map(|arg| vm.segments.gen_cairo_arg(arg, &mut vm.memory))
+            .collect::<Result<Vec<_>, _>>()?;
 
         let return_fp = vm.segments.add(&mut vm.memory);
         let end = self.initialize_function_entrypoint(vm, entrypoint, stack, return_fp.into())?;
 
         self.initialize_vm(vm)?;
 
-        self.run_until_pc(end, vm, hint_processor)?;
+        self.run_until_pc(end, run_resources, vm, hint_processor)?;
         self.end_run(true, false, vm, hint_processor)?;
 
         if verify_secure {
@@ -1025,17 +1784,63 @@ impl CairoRunner {
             - (public_memory_units + instruction_memory_units + builtins_memory_units);
         let memory_address_holes = self.get_memory_holes(vm)?;
         if unused_memory_units < memory_address_holes as u32 {
-            Err(MemoryError::InsufficientAllocatedCells)?
+            Err(MemoryError::InsufficientMemoryUnits {
+                unused: unused_memory_units,
+                holes: memory_address_holes,
+            })?
         }
         Ok(())
     }
 
+    /// Replaces the execution scopes the hints run with, e.g. to provide a dict manager holding
+    /// dictionaries seeded before the run. Must be called before running.
+    /// Limits the size of a segment which isn't finalized, checked by `verify_secure_runner`.
+    /// The size of a finalized segment takes precedence over this limit.
+    pub fn set_max_segment_size(&mut self, segment_index: usize, max_size: usize) {
+        self.max_segment_sizes.insert(segment_index, max_size);
+    }
+
+    pub fn set_initial_exec_scopes(&mut self, exec_scopes: ExecutionScopes) {
+        self.exec_scopes = exec_scopes;
+        if let Some(program_input) = &self.program_input {
+            self.exec_scopes
+                .insert_value(PROGRAM_INPUT, program_input.clone());
+        }
+    }
+
+    /// Sets the input of the program, the untrusted data its hints read as `program_input`.
+    /// It is kept in the main execution scope, where hints get it with
+    /// `hint_utils::get_program_input` and its typed variants.
+    pub fn set_program_input(&mut self, program_input: serde_json::Value) {
+        self.exec_scopes
+            .insert_value(PROGRAM_INPUT, program_input.clone());
+        self.program_input = Some(program_input);
+    }
+
+    pub fn get_program_input(&self) -> Option<&serde_json::Value> {
+        self.program_input.as_ref()
+    }
+
+    /// Prepares the runner to call the functions of the program with `run_from_entrypoint`, the
+    /// way Starknet executes contracts: every builtin the Starknet OS provides is initialized and
+    /// included, whether the program declares it or not, and the program is loaded into its
+    /// segment. Unlike `initialize`, the program doesn't need a `main` function, as no
+    /// entrypoint is set up until `run_from_entrypoint` is called.
+    /// `run_from_entrypoint` then sets up the entrypoint's frame on the execution segment and
+    /// runs the function, ending the run once it returns.
     pub fn initialize_function_runner(
         &mut self,
         vm: &mut VirtualMachine,
     ) -> Result<(), RunnerError> {
         self.initialize_all_builtins(vm)?;
-        self.initialize_segments(vm, self.program_base);
+        let layout = self.initialize_segments(vm, self.program_base, None)?;
+        vm.segments
+            .load_data(&mut vm.memory, &layout.program.into(), &self.program.data)
+            .map_err(RunnerError::MemoryInitializationError)?;
+        vm._program_base = Some(layout.program.into());
+        for (_, builtin) in vm.builtin_runners.iter() {
+            builtin.add_validation_rule(&mut vm.memory)?;
+        }
         Ok(())
     }
 
@@ -1043,36 +1848,48 @@ impl CairoRunner {
     /// is specified.
     pub fn set_entrypoint(&mut self, new_entrypoint: Option<&str>) -> Result<(), ProgramError> {
         let new_entrypoint = new_entrypoint.unwrap_or("main");
-        self.program.main = Some(
-            self.program
-                .identifiers
-                .get(&format!("__main__.{new_entrypoint}"))
-                .and_then(|x| x.pc)
-                .ok_or_else(|| ProgramError::EntrypointNotFound(new_entrypoint.to_string()))?,
-        );
+        self.program.main = Some(get_entrypoint_pc(
+            &self.program.identifiers,
+            new_entrypoint,
+        )?);
 
         Ok(())
     }
 
-    pub fn read_return_values(&mut self, vm: &VirtualMachine) -> Result<(), RunnerError> {
+    /// Reads the stop pointers of the program's builtins, which main returns in the reverse
+    /// order of its builtins, and adds the return values before them to the public memory.
+    pub fn read_return_values(&mut self, vm: &mut VirtualMachine) -> Result<(), RunnerError> {
         if !self.run_ended {
             return Err(RunnerError::FinalizeNoEndRun);
         }
-        let mut pointer = vm.get_ap();
-        for builtin_name in self.program.builtins.iter().rev() {
-            let builtin_runner = vm
-                .builtin_runners
+        // The builtins are taken out of the vm while they read their stop pointers from its
+        // memory
+        let mut builtin_runners = std::mem::take(&mut vm.builtin_runners);
+        let initial_fp = self.initial_fp;
+        let final_stack =
+            self.program
+                .builtins
                 .iter()
-                .find(|(name, _builtin)| builtin_name == name);
-
-            match builtin_runner {
-                None => return Err(RunnerError::MissingBuiltin(builtin_name.to_string())),
-                Some((_, builtin)) => {
-                    let (new_pointer, _) = builtin.final_stack(vm, pointer)?;
-                    pointer = new_pointer;
-                }
-            }
-        }
+                .rev()
+                .try_fold(vm.get_ap(), |pointer, builtin_name| {
+                    match builtin_runners
+                        .iter_mut()
+                        .find(|(name, _builtin)| builtin_name == name)
+                    {
+                        None => Err(RunnerError::MissingBuiltin(builtin_name.to_string())),
+                        // The return values start at the initial fp, a stop pointer below it
+                        // would be read from the arguments of main
+                        Some((_, builtin))
+                            if builtin.included()
+                                && initial_fp.map_or(false, |fp| pointer <= fp) =>
+                        {
+                            Err(RunnerError::NoStopPointer(builtin.name()))
+                        }
+                        Some((_, builtin)) => builtin.final_stack(vm, pointer),
+                    }
+                });
+        vm.builtin_runners = builtin_runners;
+        let pointer = final_stack?;
         if self.segments_finalized {
             return Err(RunnerError::FailedAddingReturnValues);
         }
@@ -1092,48 +1909,280 @@ impl CairoRunner {
 
     /// Add (or replace if already present) a custom hash builtin. Returns a Relocatable
     /// with the new builtin base as the segment index.
-    pub fn add_additional_hash_builtin(&self, vm: &mut VirtualMachine) -> Relocatable {
+    pub fn add_additional_hash_builtin(
+        &self,
+        vm: &mut VirtualMachine,
+    ) -> Result<Relocatable, RunnerError> {
         // Remove the custom hash runner if it was already present.
         vm.builtin_runners
             .retain(|(name, _)| name != "hash_builtin");
 
         // Create, initialize and insert the new custom hash runner.
-        let mut builtin: BuiltinRunner = HashBuiltinRunner::new(32, true).into();
-        builtin.initialize_segments(&mut vm.segments, &mut vm.memory);
+        let mut builtin: BuiltinRunner =
+            HashBuiltinRunner::new(&PedersenInstanceDef::new(32, 1), true).into();
+        builtin.initialize_segments(&mut vm.segments, &mut vm.memory)?;
         let segment_index = builtin.base();
         vm.builtin_runners
             .push(("hash_builtin".to_string(), builtin));
+        vm.index_builtin_segments();
 
-        Relocatable {
+        Ok(Relocatable {
             segment_index,
             offset: 0,
+        })
+    }
+}
+
+/// Builds the references of a program, which are used to compile its hints.
+fn program_reference_list(program: &Program) -> HashMap<usize, HintReference> {
+    let mut references = HashMap::<usize, HintReference>::new();
+
+    for (i, reference) in program.reference_manager.references.iter().enumerate() {
+        references.insert(
+            i,
+            HintReference {
+                offset1: reference.value_address.offset1.clone(),
+                offset2: reference.value_address.offset2.clone(),
+                dereference: reference.value_address.dereference,
+                // only store `ap` tracking data if the reference is referred to it
+                ap_tracking_data: match (
+                    &reference.value_address.offset1,
+                    &reference.value_address.offset2,
+                ) {
+                    (OffsetValue::Reference(Register::AP, _, _), _)
+                    | (_, OffsetValue::Reference(Register::AP, _, _)) => {
+                        Some(reference.ap_tracking_data.clone())
+                    }
+                    _ => None,
+                },
+                cairo_type: Some(reference.value_address.value_type.clone()),
+            },
+        );
+    }
+    references
+}
+
+/// Compiles the hints of a program loaded at `program_base`, keying them by `program_base + pc`.
+fn compile_program_hints(
+    program: &Program,
+    program_base: Relocatable,
+    references: &HashMap<usize, HintReference>,
+    hint_executor: &mut dyn HintProcessor,
+) -> Result<HashMap<Relocatable, Vec<Box<dyn Any>>>, VirtualMachineError> {
+    let mut hint_data_dictionary = HashMap::<Relocatable, Vec<Box<dyn Any>>>::new();
+    // Compile the hints in pc order, so that the same hint is reported on every run if
+    // several of them fail to compile
+    let mut program_hints = program.hints.iter().collect::<Vec<_>>();
+    program_hints.sort_unstable_by_key(|(hint_index, _)| **hint_index);
+    for (hint_index, hints) in program_hints {
+        for hint in hints {
+            let hint_data = hint_executor.compile_hint(
+                &hint.code,
+                &hint.flow_tracking_data.ap_tracking,
+                &hint.flow_tracking_data.reference_ids,
+                references,
+            );
+            hint_data_dictionary
+                .entry(program_base + *hint_index)
+                .or_default()
+                .push(
+                    hint_data
+                        .map_err(|_| VirtualMachineError::CompileHintFail(hint.code.clone()))?,
+                );
+        }
+    }
+    Ok(hint_data_dictionary)
+}
+
+// Maps the name of each segment to its index and size, failing if two segments share a name
+fn collect_segments_info(
+    segments: impl IntoIterator<Item = SegmentAddresses>,
+) -> Result<HashMap<String, SegmentInfo>, RunnerError> {
+    let mut segments_info = HashMap::new();
+    for segment in segments {
+        if segments_info.contains_key(&segment.name) {
+            return Err(RunnerError::BuiltinSegmentNameCollision(segment.name));
         }
+        let size = segment.stop_ptr.ok_or(RunnerError::BaseNotFinished)?;
+        segments_info.insert(
+            segment.name,
+            SegmentInfo {
+                index: segment.base,
+                size,
+            },
+        );
     }
+    Ok(segments_info)
 }
 
+// Whether the base is in a segment that was added, but holds no value yet
+fn is_empty_added_segment(vm: &VirtualMachine, base: &Relocatable) -> bool {
+    match usize::try_from(base.segment_index) {
+        Ok(index) if index < vm.segments.num_segments => !matches!(
+            vm.memory.data.get(index),
+            Some(segment) if segment.iter().any(Option::is_some)
+        ),
+        _ => false,
+    }
+}
+
+/// A program loaded by `CairoRunner::load_child_program`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct LoadedProgram {
+    pub base: Relocatable,
+    pub size: usize,
+    pub builtins: Vec<String>,
+    /// The pc of each function and label of the program, by full name, relative to the
+    /// runner's program base, as taken by `CairoRunner::run_from_entrypoint`
+    pub entrypoints: HashMap<String, usize>,
+}
+
+///The bases of the segments created by `CairoRunner::initialize_segments`.
 #[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SegmentLayout {
+    pub program: Relocatable,
+    pub execution: Relocatable,
+    pub builtins: Vec<(String, Relocatable)>,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
 pub struct SegmentInfo {
     pub index: isize,
     pub size: usize,
 }
 
-#[derive(Clone, Debug, Eq, PartialEq)]
+/// The usage of a builtin, as reported by `CairoRunner::get_builtin_usage_report`.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct BuiltinUsage {
+    pub name: String,
+    pub used_instances: usize,
+    /// The instances the layout allocates for the steps run so far. None for the output builtin,
+    /// which has no ratio.
+    pub allocated_instances: Option<usize>,
+    /// The used instances, as a percentage of the allocated ones.
+    pub utilization: Option<f64>,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
 pub struct ExecutionResources {
     pub n_steps: usize,
     pub n_memory_holes: usize,
-    pub builtin_instance_counter: HashMap<String, usize>,
+    pub builtin_instance_counter: BTreeMap<String, usize>,
+    // The temporary segments left without a relocation rule, dropped at the end of the run
+    #[serde(skip)]
+    pub n_dropped_temporary_segments: usize,
+}
+
+/// Resources can be added up or subtracted to account for several runs, such as the entrypoints
+/// called by a transaction. A builtin missing from one side is counted as 0 instances.
+impl Add<&ExecutionResources> for &ExecutionResources {
+    type Output = ExecutionResources;
+
+    fn add(self, rhs: &ExecutionResources) -> ExecutionResources {
+        let mut resources = self.clone();
+        resources.n_steps += rhs.n_steps;
+        resources.n_memory_holes += rhs.n_memory_holes;
+        resources.n_dropped_temporary_segments += rhs.n_dropped_temporary_segments;
+        for (builtin_name, instances) in &rhs.builtin_instance_counter {
+            *resources
+                .builtin_instance_counter
+                .entry(builtin_name.clone())
+                .or_insert(0) += instances;
+        }
+        resources
+    }
+}
+
+/// Subtraction saturates at 0.
+impl Sub<&ExecutionResources> for &ExecutionResources {
+    type Output = ExecutionResources;
+
+    fn sub(self, rhs: &ExecutionResources) -> ExecutionResources {
+        let mut resources = self.clone();
+        resources.n_steps = resources.n_steps.saturating_sub(rhs.n_steps);
+        resources.n_memory_holes = resources.n_memory_holes.saturating_sub(rhs.n_memory_holes);
+        resources.n_dropped_temporary_segments = resources
+            .n_dropped_temporary_segments
+            .saturating_sub(rhs.n_dropped_temporary_segments);
+        for (builtin_name, instances) in &rhs.builtin_instance_counter {
+            let counter = resources
+                .builtin_instance_counter
+                .entry(builtin_name.clone())
+                .or_insert(0);
+            *counter = counter.saturating_sub(*instances);
+        }
+        resources
+    }
+}
+
+impl Mul<usize> for &ExecutionResources {
+    type Output = ExecutionResources;
+
+    fn mul(self, rhs: usize) -> ExecutionResources {
+        ExecutionResources {
+            n_steps: self.n_steps * rhs,
+            n_memory_holes: self.n_memory_holes * rhs,
+            builtin_instance_counter: self
+                .builtin_instance_counter
+                .iter()
+                .map(|(builtin_name, instances)| (builtin_name.clone(), instances * rhs))
+                .collect(),
+            n_dropped_temporary_segments: self.n_dropped_temporary_segments * rhs,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct MemorySegmentAddresses {
+    pub begin_addr: usize,
+    pub stop_ptr: usize,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PublicMemoryEntry {
+    pub address: usize,
+    pub value: Felt,
+    pub page: usize,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PublicInput {
+    pub layout: String,
+    pub rc_min: isize,
+    pub rc_max: isize,
+    pub n_steps: usize,
+    pub memory_segments: BTreeMap<String, MemorySegmentAddresses>,
+    pub public_memory: Vec<PublicMemoryEntry>,
+}
+
+/// Everything the prover consumes, as returned by `CairoRunner::finalize_for_proving`.
+#[derive(Debug, Eq, PartialEq)]
+pub struct RunArtifacts {
+    pub relocated_memory: Vec<Option<Felt>>,
+    pub relocated_trace: Vec<RelocatedTraceEntry>,
+    pub public_input: PublicInput,
+    pub private_input: AirPrivateInput,
+    pub execution_resources: ExecutionResources,
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::{
-        hint_processor::builtin_hint_processor::builtin_hint_processor_definition::BuiltinHintProcessor,
+        hint_processor::{
+            builtin_hint_processor::builtin_hint_processor_definition::BuiltinHintProcessor,
+            cairo_1_hint_processor::hint_processor::Cairo1HintProcessor,
+        },
         relocatable,
-        serde::deserialize_program::{Identifier, ReferenceManager},
+        serde::deserialize_program::{ApTracking, FlowTrackingData, HintParams, Identifier},
         types::instance_definitions::bitwise_instance_def::BitwiseInstanceDef,
         utils::test_utils::*,
-        vm::{trace::trace_entry::TraceEntry, vm_memory::memory::Memory},
+        vm::{
+            errors::hint_errors::HintError,
+            runners::air_private_input::{PrivateInput, PrivateInputValue},
+            trace::trace_entry::TraceEntry,
+            vm_memory::memory::Memory,
+        },
     };
     use felt::{felt_str, NewFelt};
     use num_traits::One;
@@ -1163,7 +2212,9 @@ mod tests {
         vm.accessed_addresses = Some(vec![(1, 0).into(), (1, 3).into()]);
         vm.builtin_runners = vec![{
             let mut builtin_runner: BuiltinRunner = OutputBuiltinRunner::new(true).into();
-            builtin_runner.initialize_segments(&mut vm.segments, &mut vm.memory);
+            builtin_runner
+                .initialize_segments(&mut vm.segments, &mut vm.memory)
+                .unwrap();
 
             ("output".to_string(), builtin_runner)
         }];
@@ -1171,7 +2222,10 @@ mod tests {
         assert_eq!(
             cairo_runner.check_memory_usage(&vm),
             Err(VirtualMachineError::MemoryError(
-                MemoryError::InsufficientAllocatedCells
+                MemoryError::InsufficientMemoryUnits {
+                    unused: 0,
+                    holes: 10
+                }
             ))
         );
     }
@@ -1185,6 +2239,78 @@ mod tests {
         assert!(cairo_runner.initialize_builtins(&mut vm).is_err());
     }
 
+    #[test]
+    fn initialize_builtins_range_check96() {
+        let program = program!["range_check", "range_check96"];
+        let cairo_runner = cairo_runner!(program, "all");
+        let mut vm = vm!();
+        cairo_runner.initialize_builtins(&mut vm).unwrap();
+
+        let names = vm
+            .builtin_runners
+            .iter()
+            .map(|(name, builtin)| (name.as_str(), builtin.name()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            names,
+            [
+                ("range_check", "range_check"),
+                ("range_check96", "range_check96")
+            ]
+        );
+        assert_eq!(vm.get_range_check_builtin().unwrap().name, "range_check");
+    }
+
+    #[test]
+    fn initialize_builtins_range_check96_not_in_layout() {
+        let program = program!["range_check96"];
+        let cairo_runner = cairo_runner!(program, "small");
+        let mut vm = vm!();
+        assert_eq!(
+            cairo_runner.initialize_builtins(&mut vm),
+            Err(RunnerError::NoBuiltinForInstance(
+                BTreeSet::from([String::from("range_check96")]),
+                String::from("small")
+            ))
+        );
+    }
+
+    #[test]
+    fn initialize_builtins_proof_mode_instantiates_layout_builtins() {
+        let program = program!["range_check"];
+        let mut cairo_runner = cairo_runner!(program, "small", true);
+        let mut vm = vm!();
+        cairo_runner.initialize_builtins(&mut vm).unwrap();
+        cairo_runner
+            .initialize_segments(&mut vm, None, None)
+            .unwrap();
+
+        let builtins = vm
+            .builtin_runners
+            .iter()
+            .map(|(name, builtin)| (name.as_str(), builtin.included()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            builtins,
+            [
+                ("output", false),
+                ("pedersen", false),
+                ("range_check", true),
+                ("ecdsa", false)
+            ]
+        );
+        // Only the builtins of the program are given to main
+        let initial_stack = vm
+            .builtin_runners
+            .iter()
+            .flat_map(|(_, builtin)| builtin.initial_stack())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            initial_stack,
+            vec![MaybeRelocatable::from((vm.builtin_runners[2].1.base(), 0))]
+        );
+    }
+
     #[test]
     fn create_cairo_runner_with_ordered_but_missing_builtins() {
         //This test works with basic Program definition, will later be updated to use Program::new() when fully defined
@@ -1205,7 +2331,9 @@ mod tests {
         });
         vm.segments.num_segments = 6;
         cairo_runner.initialize_builtins(&mut vm).unwrap();
-        cairo_runner.initialize_segments(&mut vm, program_base);
+        cairo_runner
+            .initialize_segments(&mut vm, program_base, None)
+            .unwrap();
         assert_eq!(
             cairo_runner.program_base,
             Some(Relocatable {
@@ -1233,7 +2361,9 @@ mod tests {
         let mut cairo_runner = cairo_runner!(program);
         let mut vm = vm!();
         cairo_runner.initialize_builtins(&mut vm).unwrap();
-        cairo_runner.initialize_segments(&mut vm, None);
+        cairo_runner
+            .initialize_segments(&mut vm, None, None)
+            .unwrap();
         assert_eq!(
             cairo_runner.program_base,
             Some(Relocatable {
@@ -1255,48 +2385,135 @@ mod tests {
     }
 
     #[test]
-    fn initialize_state_empty_data_and_stack() {
-        //This test works with basic Program definition, will later be updated to use Program::new() when fully defined
+    fn initialize_segments_twice() {
         let program = program!["output"];
         let mut cairo_runner = cairo_runner!(program);
         let mut vm = vm!();
-        cairo_runner.program_base = Some(relocatable!(1, 0));
-        cairo_runner.execution_base = Some(relocatable!(2, 0));
-        let stack = Vec::new();
         cairo_runner.initialize_builtins(&mut vm).unwrap();
-        cairo_runner.initialize_state(&mut vm, 1, stack).unwrap();
+        cairo_runner
+            .initialize_segments(&mut vm, None, None)
+            .unwrap();
         assert_eq!(
-            cairo_runner.initial_pc,
-            Some(Relocatable {
-                segment_index: 1,
-                offset: 1
-            })
+            cairo_runner.initialize_segments(&mut vm, None, None),
+            Err(RunnerError::BuiltinAlreadyInitialized("output"))
         );
+        // No segment was leaked, and the builtin keeps its base
+        assert_eq!(vm.segments.num_segments, 3);
+        assert_eq!(vm.builtin_runners[0].1.base(), 2);
     }
 
     #[test]
-    fn initialize_state_some_data_empty_stack() {
-        //This test works with basic Program definition, will later be updated to use Program::new() when fully defined
+    fn initialize_segments_with_preallocated_bases() {
         let program = program!(
-            builtins = vec![String::from("output")],
-            data = vec_data!((4), (6)),
+            builtins = vec![String::from("output"), String::from("pedersen")],
+            data = vec![mayberelocatable!(5)],
+            main = Some(0),
         );
         let mut cairo_runner = cairo_runner!(program);
         let mut vm = vm!();
-        for _ in 0..2 {
-            vm.segments.add(&mut vm.memory);
-        }
-        cairo_runner.program_base = Some(Relocatable {
-            segment_index: 1,
-            offset: 0,
-        });
-        cairo_runner.execution_base = Some(relocatable!(2, 0));
-        let stack = Vec::new();
-        cairo_runner.initialize_state(&mut vm, 1, stack).unwrap();
-        check_memory!(vm.memory, ((1, 0), 4), ((1, 1), 6));
-    }
-
-    #[test]
+        // A segment created by the embedder, followed by the two segments reserved for the runner
+        let calldata = vm.segments.add(&mut vm.memory);
+        vm.insert_value(&calldata, 7).unwrap();
+        let execution_base = vm.segments.add(&mut vm.memory);
+        let program_base = vm.segments.add(&mut vm.memory);
+        cairo_runner.initialize_builtins(&mut vm).unwrap();
+        assert_eq!(
+            cairo_runner.initialize_segments(&mut vm, Some(program_base), Some(execution_base)),
+            Ok(SegmentLayout {
+                program: Relocatable::from((2, 0)),
+                execution: Relocatable::from((1, 0)),
+                builtins: vec![
+                    (String::from("output"), Relocatable::from((3, 0))),
+                    (String::from("pedersen"), Relocatable::from((4, 0))),
+                ],
+            })
+        );
+        assert_eq!(cairo_runner.program_base, Some(program_base));
+        assert_eq!(cairo_runner.execution_base, Some(execution_base));
+        assert_eq!(vm.segments.num_segments, 5);
+        // The program is loaded in its segment, leaving the embedder's segment untouched
+        cairo_runner.initialize_main_entrypoint(&mut vm).unwrap();
+        check_memory![vm.memory, ((0, 0), 7), ((2, 0), 5)];
+        assert_eq!(vm.memory.data[0].len(), 1);
+    }
+
+    #[test]
+    fn initialize_segments_invalid_bases() {
+        let program = program!();
+        let mut cairo_runner = cairo_runner!(program);
+        let mut vm = vm!();
+        let used_segment = vm.segments.add(&mut vm.memory);
+        vm.insert_value(&used_segment, 7).unwrap();
+        let empty_segment = vm.segments.add(&mut vm.memory);
+        for (program_base, execution_base, invalid_base) in [
+            // Not added yet
+            (Some((2, 0)), None, (2, 0)),
+            (None, Some((-1, 0)), (-1, 0)),
+            // Not empty
+            (Some((0, 0)), None, (0, 0)),
+            // Used for both segments
+            (Some((1, 0)), Some((1, 0)), (1, 0)),
+        ] {
+            assert_eq!(
+                cairo_runner.initialize_segments(
+                    &mut vm,
+                    program_base.map(Relocatable::from),
+                    execution_base.map(Relocatable::from)
+                ),
+                Err(RunnerError::InvalidSegmentBase(Relocatable::from(
+                    invalid_base
+                )))
+            );
+        }
+        assert_eq!(vm.segments.num_segments, 2);
+        assert!(cairo_runner
+            .initialize_segments(&mut vm, Some(empty_segment), None)
+            .is_ok());
+    }
+
+    #[test]
+    fn initialize_state_empty_data_and_stack() {
+        //This test works with basic Program definition, will later be updated to use Program::new() when fully defined
+        let program = program!["output"];
+        let mut cairo_runner = cairo_runner!(program);
+        let mut vm = vm!();
+        cairo_runner.program_base = Some(relocatable!(1, 0));
+        cairo_runner.execution_base = Some(relocatable!(2, 0));
+        let stack = Vec::new();
+        cairo_runner.initialize_builtins(&mut vm).unwrap();
+        cairo_runner.initialize_state(&mut vm, 1, stack).unwrap();
+        assert_eq!(
+            cairo_runner.initial_pc,
+            Some(Relocatable {
+                segment_index: 1,
+                offset: 1
+            })
+        );
+    }
+
+    #[test]
+    fn initialize_state_some_data_empty_stack() {
+        //This test works with basic Program definition, will later be updated to use Program::new() when fully defined
+        let program = program!(
+            builtins = vec![String::from("output")],
+            data = vec_data!((4), (6)),
+        );
+        let mut cairo_runner = cairo_runner!(program);
+        let mut vm = vm!();
+        for _ in 0..2 {
+            vm.segments.add(&mut vm.memory);
+        }
+        cairo_runner.program_base = Some(Relocatable {
+            segment_index: 1,
+            offset: 0,
+        });
+        cairo_runner.execution_base = Some(relocatable!(2, 0));
+        let stack = Vec::new();
+        cairo_runner.initialize_state(&mut vm, 1, stack).unwrap();
+        check_memory!(vm.memory, ((1, 0), 4), ((1, 1), 6));
+    }
+
+    #[test]
     fn initialize_state_empty_data_some_stack() {
         //This test works with basic Program definition, will later be updated to use Program::new() when fully defined
         let program = program!["output"];
@@ -1490,7 +2707,9 @@ mod tests {
         cairo_runner.initial_ap = Some(relocatable!(1, 2));
         cairo_runner.initial_fp = Some(relocatable!(1, 2));
         cairo_runner.initialize_builtins(&mut vm).unwrap();
-        cairo_runner.initialize_segments(&mut vm, None);
+        cairo_runner
+            .initialize_segments(&mut vm, None, None)
+            .unwrap();
         vm.memory = memory![((2, 0), 23), ((2, 1), 233)];
         assert_eq!(vm.builtin_runners[0].0, String::from("range_check"));
         assert_eq!(vm.builtin_runners[0].1.base(), 2);
@@ -1516,7 +2735,9 @@ mod tests {
         cairo_runner.initial_ap = Some(relocatable!(1, 2));
         cairo_runner.initial_fp = Some(relocatable!(1, 2));
         cairo_runner.initialize_builtins(&mut vm).unwrap();
-        cairo_runner.initialize_segments(&mut vm, None);
+        cairo_runner
+            .initialize_segments(&mut vm, None, None)
+            .unwrap();
         vm.memory = memory![((2, 1), 23), ((2, 4), (-1))];
 
         assert_eq!(
@@ -1528,41 +2749,13 @@ mod tests {
     //Integration tests for initialization phase
 
     #[test]
-    /* Program used:
-    func myfunc(a: felt) -> (r: felt):
-        let b = a * 2
-        return(b)
-    end
-
-    func main():
-        let a = 1
-        let b = myfunc(a)
-        return()
-    end
-
-    main = 3
-    data = [5207990763031199744, 2, 2345108766317314046, 5189976364521848832, 1, 1226245742482522112, 3618502788666131213697322783095070105623107215331596699973092056135872020476, 2345108766317314046]
-    */
     fn initialization_phase_no_builtins() {
-        let program = program!(
-            data = vec_data!(
-                (5207990763031199744_u64),
-                (2),
-                (2345108766317314046_u64),
-                (5189976364521848832_u64),
-                (1),
-                (1226245742482522112_u64),
-                ((
-                    "3618502788666131213697322783095070105623107215331596699973092056135872020476",
-                    10
-                )),
-                (2345108766317314046_i64)
-            ),
-            main = Some(3),
-        );
+        let program = Program::from_fixture("function_call");
         let mut cairo_runner = cairo_runner!(program);
         let mut vm = vm!();
-        cairo_runner.initialize_segments(&mut vm, None);
+        cairo_runner
+            .initialize_segments(&mut vm, None, None)
+            .unwrap();
         cairo_runner.initialize_main_entrypoint(&mut vm).unwrap();
         cairo_runner.initialize_vm(&mut vm).unwrap();
 
@@ -1598,45 +2791,15 @@ mod tests {
     }
 
     #[test]
-    /*Program used:
-    %builtins output
-
-    from starkware.cairo.common.serialize import serialize_word
-
-    func main{output_ptr: felt*}():
-        let a = 1
-        serialize_word(a)
-        return()
-    end
-
-    main = 4
-    data = [4612671182993129469, 5198983563776393216, 1, 2345108766317314046, 5191102247248822272, 5189976364521848832, 1, 1226245742482522112, 3618502788666131213697322783095070105623107215331596699973092056135872020474, 2345108766317314046]
-    */
     fn initialization_phase_output_builtin() {
-        let program = program!(
-            builtins = vec![String::from("output")],
-            data = vec_data!(
-                (4612671182993129469_u64),
-                (5198983563776393216_u64),
-                (1),
-                (2345108766317314046_u64),
-                (5191102247248822272_u64),
-                (5189976364521848832_u64),
-                (1),
-                (1226245742482522112_u64),
-                ((
-                    "3618502788666131213697322783095070105623107215331596699973092056135872020474",
-                    10
-                )),
-                (2345108766317314046_u64)
-            ),
-            main = Some(4),
-        );
+        let program = Program::from_fixture("serialize_word");
         let mut cairo_runner = cairo_runner!(program);
         let mut vm = vm!();
 
         cairo_runner.initialize_builtins(&mut vm).unwrap();
-        cairo_runner.initialize_segments(&mut vm, None);
+        cairo_runner
+            .initialize_segments(&mut vm, None, None)
+            .unwrap();
         cairo_runner.initialize_main_entrypoint(&mut vm).unwrap();
         cairo_runner.initialize_vm(&mut vm).unwrap();
 
@@ -1667,7 +2830,17 @@ mod tests {
                     10
                 )
             ),
-            ((0, 9), 2345108766317314046_u64),
+            ((0, 9), 5189976364521848832_u64),
+            ((0, 10), 17),
+            ((0, 11), 1226245742482522112_u64),
+            (
+                (0, 12),
+                (
+                    "3618502788666131213697322783095070105623107215331596699973092056135872020470",
+                    10
+                )
+            ),
+            ((0, 13), 2345108766317314046_u64),
             ((1, 0), (2, 0)),
             ((1, 1), (3, 0)),
             ((1, 2), (4, 0))
@@ -1675,56 +2848,15 @@ mod tests {
     }
 
     #[test]
-    /*Program used:
-    %builtins range_check
-
-    func check_range{range_check_ptr}(num):
-
-        # Check that 0 <= num < 2**64.
-        [range_check_ptr] = num
-        assert [range_check_ptr + 1] = 2 ** 64 - 1 - num
-        let range_check_ptr = range_check_ptr + 2
-        return()
-    end
-
-    func main{range_check_ptr}():
-        check_range(7)
-        return()
-    end
-
-    main = 8
-    data = [4612671182993129469, 5189976364521848832, 18446744073709551615, 5199546496550207487, 4612389712311386111, 5198983563776393216, 2, 2345108766317314046, 5191102247248822272, 5189976364521848832, 7, 1226245742482522112, 3618502788666131213697322783095070105623107215331596699973092056135872020470, 2345108766317314046]
-    */
     fn initialization_phase_range_check_builtin() {
-        let program = program!(
-            builtins = vec![String::from("range_check")],
-            data = vec_data!(
-                (4612671182993129469_u64),
-                (5189976364521848832_u64),
-                (18446744073709551615_u128),
-                (5199546496550207487_u64),
-                (4612389712311386111_u64),
-                (5198983563776393216_u64),
-                (2),
-                (2345108766317314046_u64),
-                (5191102247248822272_u64),
-                (5189976364521848832_u64),
-                (7),
-                (1226245742482522112_u64),
-                ((
-                    "3618502788666131213697322783095070105623107215331596699973092056135872020474",
-                    10
-                )),
-                (2345108766317314046_u64)
-            ),
-            main = Some(8),
-        );
-
+        let program = Program::from_fixture("check_range");
         let mut cairo_runner = cairo_runner!(program);
         let mut vm = vm!();
 
         cairo_runner.initialize_builtins(&mut vm).unwrap();
-        cairo_runner.initialize_segments(&mut vm, None);
+        cairo_runner
+            .initialize_segments(&mut vm, None, None)
+            .unwrap();
         cairo_runner.initialize_main_entrypoint(&mut vm).unwrap();
         cairo_runner.initialize_vm(&mut vm).unwrap();
 
@@ -1755,7 +2887,7 @@ mod tests {
             (
                 (0, 12),
                 (
-                    "3618502788666131213697322783095070105623107215331596699973092056135872020474",
+                    "3618502788666131213697322783095070105623107215331596699973092056135872020470",
                     10
                 )
             ),
@@ -1769,49 +2901,26 @@ mod tests {
     //Integration tests for initialization + execution phase
 
     #[test]
-    /*Program used:
-    func myfunc(a: felt) -> (r: felt):
-        let b = a * 2
-        return(b)
-    end
-
-    func main():
-        let a = 1
-        let b = myfunc(a)
-        return()
-    end
-
-    main = 3
-    data = [5207990763031199744, 2, 2345108766317314046, 5189976364521848832, 1, 1226245742482522112, 3618502788666131213697322783095070105623107215331596699973092056135872020476, 2345108766317314046]
-    */
     fn initialize_and_run_function_call() {
         //Initialization Phase
-        let program = program!(
-            data = vec_data!(
-                (5207990763031199744_i64),
-                (2),
-                (2345108766317314046_i64),
-                (5189976364521848832_i64),
-                (1),
-                (1226245742482522112_i64),
-                ((
-                    "3618502788666131213697322783095070105623107215331596699973092056135872020476",
-                    10
-                )),
-                (2345108766317314046_i64)
-            ),
-            main = Some(3),
-        );
+        let program = Program::from_fixture("function_call");
         let mut hint_processor = BuiltinHintProcessor::new_empty();
         let mut cairo_runner = cairo_runner!(program);
         let mut vm = vm!(true);
-        cairo_runner.initialize_segments(&mut vm, None);
+        cairo_runner
+            .initialize_segments(&mut vm, None, None)
+            .unwrap();
         let end = cairo_runner.initialize_main_entrypoint(&mut vm).unwrap();
         assert_eq!(end, Relocatable::from((3, 0)));
         cairo_runner.initialize_vm(&mut vm).unwrap();
         //Execution Phase
         assert_eq!(
-            cairo_runner.run_until_pc(end, &mut vm, &mut hint_processor),
+            cairo_runner.run_until_pc(
+                end,
+                &mut RunResources::default(),
+                &mut vm,
+                &mut hint_processor
+            ),
             Ok(())
         );
         //Check final values against Python VM
@@ -1838,61 +2947,26 @@ mod tests {
     }
 
     #[test]
-    /*Program used:
-    %builtins range_check
-
-    func check_range{range_check_ptr}(num):
-
-        # Check that 0 <= num < 2**64.
-        [range_check_ptr] = num
-        assert [range_check_ptr + 1] = 2 ** 64 - 1 - num
-        let range_check_ptr = range_check_ptr + 2
-        return()
-    end
-
-    func main{range_check_ptr}():
-        check_range(7)
-        return()
-    end
-
-    main = 8
-    data = [4612671182993129469, 5189976364521848832, 18446744073709551615, 5199546496550207487, 4612389712311386111, 5198983563776393216, 2, 2345108766317314046, 5191102247248822272, 5189976364521848832, 7, 1226245742482522112, 3618502788666131213697322783095070105623107215331596699973092056135872020470, 2345108766317314046]
-    */
     fn initialize_and_run_range_check_builtin() {
         //Initialization Phase
-        let program = program!(
-            builtins = vec![String::from("range_check")],
-            data = vec_data!(
-                (4612671182993129469_i64),
-                (5189976364521848832_i64),
-                (18446744073709551615_i128),
-                (5199546496550207487_i64),
-                (4612389712311386111_i64),
-                (5198983563776393216_i64),
-                (2),
-                (2345108766317314046_i64),
-                (5191102247248822272_i64),
-                (5189976364521848832_i64),
-                (7),
-                (1226245742482522112_i64),
-                ((
-                    "3618502788666131213697322783095070105623107215331596699973092056135872020470",
-                    10
-                )),
-                (2345108766317314046_i64)
-            ),
-            main = Some(8),
-        );
+        let program = Program::from_fixture("check_range");
         let mut hint_processor = BuiltinHintProcessor::new_empty();
         let mut cairo_runner = cairo_runner!(program);
         let mut vm = vm!(true);
         cairo_runner.initialize_builtins(&mut vm).unwrap();
-        cairo_runner.initialize_segments(&mut vm, None);
+        cairo_runner
+            .initialize_segments(&mut vm, None, None)
+            .unwrap();
         let end = cairo_runner.initialize_main_entrypoint(&mut vm).unwrap();
         cairo_runner.initialize_vm(&mut vm).unwrap();
         //Execution Phase
         assert_eq!(
-            cairo_runner.run_until_pc(end, &mut vm, &mut hint_processor),
+            cairo_runner.run_until_pc(
+                end,
+                &mut RunResources::default(),
+                &mut vm,
+                &mut hint_processor
+            ),
             Ok(())
         );
         //Check final values against Python VM
@@ -1930,75 +3004,26 @@ mod tests {
     }
 
     #[test]
-    /*Program used:
-    %builtins output
-
-    from starkware.cairo.common.serialize import serialize_word
-
-    func main{output_ptr: felt*}():
-        let a = 1
-        serialize_word(a)
-        let b = 17 * a
-        serialize_word(b)
-        return()
-    end
-
-    main = 4
-    data = [
-    4612671182993129469,
-    5198983563776393216,
-    1,
-    2345108766317314046,
-    5191102247248822272,
-    5189976364521848832,
-    1,
-    1226245742482522112,
-    3618502788666131213697322783095070105623107215331596699973092056135872020474,
-    5189976364521848832,
-    17,
-    1226245742482522112,
-    3618502788666131213697322783095070105623107215331596699973092056135872020470,
-    2345108766317314046
-    ]
-    */
     fn initialize_and_run_output_builtin() {
         //Initialization Phase
-        let program = program!(
-            builtins = vec![String::from("output")],
-            data = vec_data!(
-                (4612671182993129469_i64),
-                (5198983563776393216_i64),
-                (1),
-                (2345108766317314046_i64),
-                (5191102247248822272_i64),
-                (5189976364521848832_i64),
-                (1),
-                (1226245742482522112_i64),
-                ((
-                    "3618502788666131213697322783095070105623107215331596699973092056135872020474",
-                    10
-                )),
-                (5189976364521848832_i64),
-                (17),
-                (1226245742482522112_i64),
-                ((
-                    "3618502788666131213697322783095070105623107215331596699973092056135872020470",
-                    10
-                )),
-                (2345108766317314046_i64)
-            ),
-            main = Some(4),
-        );
+        let program = Program::from_fixture("serialize_word");
         let mut hint_processor = BuiltinHintProcessor::new_empty();
         let mut cairo_runner = cairo_runner!(program);
         let mut vm = vm!(true);
         cairo_runner.initialize_builtins(&mut vm).unwrap();
-        cairo_runner.initialize_segments(&mut vm, None);
+        cairo_runner
+            .initialize_segments(&mut vm, None, None)
+            .unwrap();
         let end = cairo_runner.initialize_main_entrypoint(&mut vm).unwrap();
         cairo_runner.initialize_vm(&mut vm).unwrap();
         //Execution Phase
         assert_eq!(
-            cairo_runner.run_until_pc(end, &mut vm, &mut hint_processor),
+            cairo_runner.run_until_pc(
+                end,
+                &mut RunResources::default(),
+                &mut vm,
+                &mut hint_processor
+            ),
             Ok(())
         );
         //Check final values against Python VM
@@ -2038,101 +3063,81 @@ mod tests {
     }
 
     #[test]
-    /*Program used:
-    %builtins output range_check
-
-    from starkware.cairo.common.serialize import serialize_word
-
-    func check_range{range_check_ptr}(num) -> (num : felt):
+    fn resume_run_from_builtin_and_memory_snapshots() {
+        let program = Program::from_fixture("serialize_checked_range");
+        let mut hint_processor = BuiltinHintProcessor::new_empty();
+        let mut cairo_runner = cairo_runner!(program);
+        let mut vm = vm!();
+        let end = cairo_runner.initialize(&mut vm).unwrap();
+        cairo_runner
+            .run_for_steps(9, &mut vm, &mut hint_processor)
+            .unwrap();
 
-        # Check that 0 <= num < 2**64.
-        [range_check_ptr] = num
-        assert [range_check_ptr + 1] = 2 ** 64 - 1 - num
-        let range_check_ptr = range_check_ptr + 2
-        return(num)
-    end
+        // Resume the run halfway on a copy, built from the snapshots of the builtins and a
+        // copy of the memory and registers
+        let mut copy_runner = cairo_runner!(program);
+        let mut copy_vm = vm!();
+        copy_runner.initialize(&mut copy_vm).unwrap();
+        for ((_, builtin), (_, copy_builtin)) in vm
+            .builtin_runners
+            .iter()
+            .zip(copy_vm.builtin_runners.iter_mut())
+        {
+            copy_builtin.restore(&builtin.snapshot()).unwrap();
+        }
+        copy_vm.memory.data = vm.memory.data.clone();
+        copy_vm.memory.validated_addresses = vm.memory.validated_addresses.clone();
+        copy_vm.run_context.pc = vm.run_context.pc;
+        copy_vm.run_context.ap = vm.run_context.ap;
+        copy_vm.run_context.fp = vm.run_context.fp;
+        copy_vm.current_step = vm.current_step;
 
-    func main{output_ptr: felt*, range_check_ptr: felt}():
-        let num: felt = check_range(7)
-        serialize_word(num)
-        return()
-    end
+        cairo_runner
+            .run_until_pc(
+                end,
+                &mut RunResources::default(),
+                &mut vm,
+                &mut hint_processor,
+            )
+            .unwrap();
+        copy_runner
+            .run_until_pc(
+                end,
+                &mut RunResources::default(),
+                &mut copy_vm,
+                &mut hint_processor,
+            )
+            .unwrap();
+        assert_eq!(copy_vm.current_step, vm.current_step);
+        assert_eq!(copy_vm.memory.data, vm.memory.data);
+        assert_eq!(
+            copy_runner.get_output(&mut copy_vm),
+            cairo_runner.get_output(&mut vm)
+        );
+        assert_eq!(cairo_runner.get_output(&mut vm), Ok(String::from("7\n")));
+    }
 
-    main = 13
-    data = [
-    4612671182993129469,
-    5198983563776393216,
-    1,
-    2345108766317314046,
-    4612671182993129469,
-    5189976364521848832,
-    18446744073709551615,
-    5199546496550207487,
-    4612389712311386111,
-    5198983563776393216,
-    2,
-    5191102247248822272,
-    2345108766317314046,
-    5191102247248822272,
-    5189976364521848832,
-    7,
-    1226245742482522112,
-    3618502788666131213697322783095070105623107215331596699973092056135872020469,
-    5191102242953854976,
-    5193354051357474816,
-    1226245742482522112,
-    3618502788666131213697322783095070105623107215331596699973092056135872020461,
-    5193354029882638336,
-    2345108766317314046]
-    */
+    #[test]
     fn initialize_and_run_output_range_check_builtin() {
         //Initialization Phase
-        let program = program!(
-            builtins = vec![String::from("output"), String::from("range_check")],
-            data = vec_data!(
-                (4612671182993129469_i64),
-                (5198983563776393216_i64),
-                (1),
-                (2345108766317314046_i64),
-                (4612671182993129469_i64),
-                (5189976364521848832_i64),
-                (18446744073709551615_i128),
-                (5199546496550207487_i64),
-                (4612389712311386111_i64),
-                (5198983563776393216_i64),
-                (2),
-                (5191102247248822272_i64),
-                (2345108766317314046_i64),
-                (5191102247248822272_i64),
-                (5189976364521848832_i64),
-                (7),
-                (1226245742482522112_i64),
-                ((
-                    "3618502788666131213697322783095070105623107215331596699973092056135872020469",
-                    10
-                )),
-                (5191102242953854976_i64),
-                (5193354051357474816_i64),
-                (1226245742482522112_i64),
-                ((
-                    "3618502788666131213697322783095070105623107215331596699973092056135872020461",
-                    10
-                )),
-                (5193354029882638336_i64),
-                (2345108766317314046_i64)
-            ),
-            main = Some(13),
-        );
+        let program = Program::from_fixture("serialize_checked_range");
         let mut hint_processor = BuiltinHintProcessor::new_empty();
         let mut cairo_runner = cairo_runner!(program);
         let mut vm = vm!(true);
         cairo_runner.initialize_builtins(&mut vm).unwrap();
-        cairo_runner.initialize_segments(&mut vm, None);
+        cairo_runner
+            .initialize_segments(&mut vm, None, None)
+            .unwrap();
         let end = cairo_runner.initialize_main_entrypoint(&mut vm).unwrap();
         cairo_runner.initialize_vm(&mut vm).unwrap();
         //Execution Phase
         assert_eq!(
-            cairo_runner.run_until_pc(end, &mut vm, &mut hint_processor),
+            cairo_runner.run_until_pc(
+                end,
+                &mut RunResources::default(),
+                &mut vm,
+                &mut hint_processor
+            ),
             Ok(())
         );
         //Check final values against Python VM
@@ -2284,84 +3289,24 @@ mod tests {
     }
 
     #[test]
-    /* Program used:
-    %builtins output
-
-    from starkware.cairo.common.serialize import serialize_word
-
-    func main{output_ptr: felt*}():
-        let a = 1
-        serialize_word(a)
-        let b = 17 * a
-        serialize_word(b)
-        return()
-    end
-    Relocated Memory:
-        1     4612671182993129469
-        2     5198983563776393216
-        3     1
-        4     2345108766317314046
-        5     5191102247248822272
-        6     5189976364521848832
-        7     1
-        8     1226245742482522112
-        9     -7
-        10    5189976364521848832
-        11    17
-        12    1226245742482522112
-        13    -11
-        14    2345108766317314046
-        15    27
-        16    29
-        17    29
-        18    27
-        19    1
-        20    18
-        21    10
-        22    28
-        23    17
-        24    18
-        25    14
-        26    29
-        27    1
-        28    17
-     */
     fn initialize_run_and_relocate_output_builtin() {
-        let program = program!(
-            builtins = vec![String::from("output")],
-            data = vec_data!(
-                (4612671182993129469_i64),
-                (5198983563776393216_i64),
-                (1),
-                (2345108766317314046_i64),
-                (5191102247248822272_i64),
-                (5189976364521848832_i64),
-                (1),
-                (1226245742482522112_i64),
-                ((
-                    "3618502788666131213697322783095070105623107215331596699973092056135872020474",
-                    10
-                )),
-                (5189976364521848832_i64),
-                (17),
-                (1226245742482522112_i64),
-                ((
-                    "3618502788666131213697322783095070105623107215331596699973092056135872020470",
-                    10
-                )),
-                (2345108766317314046_i64)
-            ),
-            main = Some(4),
-        );
+        let program = Program::from_fixture("serialize_word");
         let mut hint_processor = BuiltinHintProcessor::new_empty();
         let mut cairo_runner = cairo_runner!(program);
         let mut vm = vm!(true);
         cairo_runner.initialize_builtins(&mut vm).unwrap();
-        cairo_runner.initialize_segments(&mut vm, None);
+        cairo_runner
+            .initialize_segments(&mut vm, None, None)
+            .unwrap();
         let end = cairo_runner.initialize_main_entrypoint(&mut vm).unwrap();
         cairo_runner.initialize_vm(&mut vm).unwrap();
         assert_eq!(
-            cairo_runner.run_until_pc(end, &mut vm, &mut hint_processor),
+            cairo_runner.run_until_pc(
+                end,
+                &mut RunResources::default(),
+                &mut vm,
+                &mut hint_processor
+            ),
             Ok(())
         );
         vm.segments.compute_effective_sizes(&vm.memory);
@@ -2439,64 +3384,267 @@ mod tests {
     }
 
     #[test]
-    /* Program used:
-    %builtins output
-
-    from starkware.cairo.common.serialize import serialize_word
-
-    func main{output_ptr: felt*}():
-        let a = 1
-        serialize_word(a)
-        let b = 17 * a
-        serialize_word(b)
-        return()
-    end
+    fn new_runner_with_unsupported_prime() {
+        let prime = "0x7fffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffed";
+        let program = program!(prime = prime.to_string(),);
+        assert_eq!(
+            CairoRunner::new(&program, "plain", false).err(),
+            Some(RunnerError::UnsupportedPrime(prime.to_string()))
+        );
+    }
 
-    Relocated Trace:
-    [TraceEntry(pc=5, ap=18, fp=18),
-     TraceEntry(pc=6, ap=19, fp=18),
-     TraceEntry(pc=8, ap=20, fp=18),
-     TraceEntry(pc=1, ap=22, fp=22),
-     TraceEntry(pc=2, ap=22, fp=22),
-     TraceEntry(pc=4, ap=23, fp=22),
-     TraceEntry(pc=10, ap=23, fp=18),
-    */
-    fn relocate_trace_output_builtin() {
+    #[test]
+    fn new_runner_with_cairo_prime_in_other_notation() {
         let program = program!(
-            builtins = vec![String::from("output")],
-            data = vec_data!(
-                (4612671182993129469_i64),
-                (5198983563776393216_i64),
-                (1),
-                (2345108766317314046_i64),
-                (5191102247248822272_i64),
-                (5189976364521848832_i64),
-                (1),
-                (1226245742482522112_i64),
-                ((
-                    "3618502788666131213697322783095070105623107215331596699973092056135872020474",
-                    10
-                )),
-                (5189976364521848832_i64),
-                (17),
-                (1226245742482522112_i64),
-                ((
-                    "3618502788666131213697322783095070105623107215331596699973092056135872020470",
-                    10
-                )),
-                (2345108766317314046_i64)
-            ),
-            main = Some(4),
+            prime =
+                "0x0800000000000011000000000000000000000000000000000000000000000001".to_string(),
+        );
+        assert!(CairoRunner::new(&program, "plain", false).is_ok());
+    }
+
+    /// Runs a program assembled by hand for the field of `prime`, and returns the values it
+    /// wrote to the execution segment:
+    ///     [ap] = 2 ** 100, ap++
+    ///     [ap] = [ap - 1] * [ap - 1], ap++
+    ///     [ap - 1] = [ap] * 3, ap++
+    ///     [ap] = 3, ap++
+    ///     loop:
+    ///     [ap] = [ap - 1] + (-1), ap++
+    ///     jmp loop if [ap - 1] != 0
+    ///     jmp rel 0
+    /// The negative immediates are encoded as `prime - 1` and `prime - 2`.
+    fn run_field_arithmetic(prime: &BigUint) -> Vec<Felt> {
+        let data = vec![
+            Felt::new(0x480680017fff8000_u64),
+            Felt::from(BigUint::from(2_u32).pow(100)),
+            Felt::new(0x48507fff7fff8000_u64),
+            Felt::new(0x4844800180007fff_u64),
+            Felt::new(3),
+            Felt::new(0x480680017fff8000_u64),
+            Felt::new(3),
+            Felt::new(0x482480017fff8000_u64),
+            Felt::from(prime - 1_u32),
+            Felt::new(0x020680017fff7fff_u64),
+            Felt::from(prime - 2_u32),
+            Felt::new(0x010780017fff7fff_u64),
+            Felt::zero(),
+        ];
+        let program = program!(
+            prime = format!("{prime:#x}"),
+            data = data.into_iter().map(MaybeRelocatable::from).collect(),
+            main = Some(0),
+        );
+        let mut cairo_runner = cairo_runner!(program);
+        let mut vm = vm!();
+        let mut hint_processor = BuiltinHintProcessor::new_empty();
+        cairo_runner.initialize(&mut vm).unwrap();
+        cairo_runner
+            .run_until_pc(
+                Relocatable::from((0, 11)),
+                &mut RunResources::default(),
+                &mut vm,
+                &mut hint_processor,
+            )
+            .unwrap();
+        (2..vm.run_context.ap)
+            .map(|offset| {
+                vm.memory
+                    .get_integer(&(1, offset).into())
+                    .unwrap()
+                    .into_owned()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn run_with_cairo_prime_and_smaller_prime() {
+        let cairo_prime = CAIRO_PRIME.magnitude().clone();
+        // The Mersenne prime 2**127 - 1
+        let mersenne_prime = (BigUint::from(1_u32) << 127_u32) - 1_u32;
+
+        for prime in [&cairo_prime, &mersenne_prime] {
+            let values = run_field_arithmetic(prime);
+            assert_eq!(values.len(), 7);
+            // The square wraps around the field, and the division is the inverse of the
+            // multiplication by 3 in it
+            let square = BigUint::from(2_u32).pow(200) % prime;
+            assert_eq!(values[1].to_biguint(), square);
+            assert_eq!((values[2].to_biguint() * 3_u32) % prime, square);
+            // The counter is decremented by adding prime - 1, and the loop jumps back by
+            // prime - 2
+            assert_eq!(
+                values[3..],
+                [Felt::new(3), Felt::new(2), Felt::one(), Felt::zero()]
+            );
+        }
+
+        // 2**200 only wraps around the smaller prime: 2**127 = 1 modulo it, so 2**200 = 2**73
+        let values = run_field_arithmetic(&mersenne_prime);
+        assert_eq!(values[1], Felt::from(BigUint::from(2_u32).pow(73)));
+        let values = run_field_arithmetic(&cairo_prime);
+        assert_eq!(values[1], Felt::from(BigUint::from(2_u32).pow(200)));
+    }
+
+    #[test]
+    fn custom_prime_program_with_hints_or_builtins() {
+        let prime = "0x7fffffffffffffffffffffffffffffff";
+        let mut program = program!(
+            prime = prime.to_string(),
+            builtins = vec!["output".to_string()],
+        );
+        assert!(CairoRunner::new(&program, "all", false).is_ok());
+
+        program.builtins.push("range_check".to_string());
+        assert_eq!(
+            CairoRunner::new(&program, "all", false).err(),
+            Some(RunnerError::UnsupportedWithCustomPrime(
+                "the range_check builtin".to_string()
+            ))
+        );
+
+        program.builtins.pop();
+        program.hints.insert(
+            0,
+            vec![HintParams {
+                code: "memory[ap] = 1".to_string(),
+                accessible_scopes: Vec::new(),
+                flow_tracking_data: FlowTrackingData {
+                    ap_tracking: ApTracking::new(),
+                    reference_ids: HashMap::new(),
+                },
+            }],
+        );
+        assert_eq!(
+            CairoRunner::new(&program, "all", false).err(),
+            Some(RunnerError::UnsupportedWithCustomPrime("hints".to_string()))
+        );
+    }
+
+    #[test]
+    fn relocate_applies_relocation_rules() {
+        let program = program!();
+        let mut cairo_runner = cairo_runner!(program);
+        let mut vm = vm!();
+        vm.segments.add(&mut vm.memory);
+        vm.segments.add(&mut vm.memory);
+        let temporary_base = vm.segments.add_temporary_segment(&mut vm.memory);
+        for (address, value) in [
+            ((0, 0), MaybeRelocatable::from((-1, 1))),
+            ((1, 0), mayberelocatable!(3)),
+            ((-1, 0), mayberelocatable!(5)),
+            ((-1, 1), mayberelocatable!(7)),
+        ] {
+            vm.memory
+                .insert(&MaybeRelocatable::from(address), &value)
+                .unwrap();
+        }
+        vm.memory
+            .add_relocation_rule(temporary_base, (1, 1).into())
+            .unwrap();
+
+        assert_eq!(cairo_runner.relocate(&mut vm, None), Ok(()));
+        // The temporary segment was moved right after the cell of segment 1, at address 3
+        assert_eq!(
+            cairo_runner.relocated_memory,
+            vec![
+                None,
+                Some(Felt::new(4)),
+                Some(Felt::new(3)),
+                Some(Felt::new(5)),
+                Some(Felt::new(7))
+            ]
+        );
+    }
+
+    #[test]
+    fn relocate_pointer_to_unrelocated_temporary_segment() {
+        let program = program!();
+        let mut cairo_runner = cairo_runner!(program);
+        let mut vm = vm!();
+        vm.segments.add(&mut vm.memory);
+        vm.segments.add_temporary_segment(&mut vm.memory);
+        vm.memory
+            .insert(
+                &MaybeRelocatable::from((0, 0)),
+                &MaybeRelocatable::from((-1, 0)),
+            )
+            .unwrap();
+
+        assert_eq!(
+            cairo_runner.relocate(&mut vm, None),
+            Err(TraceError::MemoryError(
+                MemoryError::TemporarySegmentInRelocation(-1)
+            ))
         );
+    }
+
+    #[test]
+    fn relocate_with_output_segment_override() {
+        let program = Program::from_fixture("serialize_word");
+        let mut hint_processor = BuiltinHintProcessor::new_empty();
+        let mut cairo_runner = cairo_runner!(program);
+        let mut vm = vm!(true);
+        let end = cairo_runner.initialize(&mut vm).unwrap();
+        cairo_runner
+            .run_until_pc(
+                end,
+                &mut RunResources::default(),
+                &mut vm,
+                &mut hint_processor,
+            )
+            .unwrap();
+        let output_index = vm.builtin_runners[0].1.base() as usize;
+        let overrides = HashMap::from([(output_index, 10_000)]);
+        cairo_runner.relocate(&mut vm, Some(&overrides)).unwrap();
+
+        let relocation_table = vm
+            .segments
+            .relocate_segments_with_offsets(&overrides)
+            .unwrap();
+        assert_eq!(relocation_table[output_index], 10_000);
+        assert_eq!(
+            cairo_runner.relocated_memory[10_000],
+            vm.memory
+                .get_integer(&(output_index as isize, 0).into())
+                .ok()
+                .map(|value| value.into_owned())
+        );
+        // Every cell holds its own value, so no two cells were relocated to the same address
+        for (index, segment) in vm.memory.data.iter().enumerate() {
+            for (offset, value) in segment.iter().enumerate() {
+                if let Some(value) = value {
+                    assert_eq!(
+                        cairo_runner.relocated_memory[relocation_table[index] + offset],
+                        Some(relocate_value(value.clone(), &relocation_table).unwrap())
+                    );
+                }
+            }
+        }
+        assert_eq!(
+            cairo_runner.relocated_memory.len(),
+            10_000 + vm.segments.get_segment_used_size(output_index).unwrap()
+        );
+    }
+
+    #[test]
+    fn relocate_trace_output_builtin() {
+        let program = Program::from_fixture("serialize_word");
         let mut hint_processor = BuiltinHintProcessor::new_empty();
         let mut cairo_runner = cairo_runner!(program);
         let mut vm = vm!(true);
         cairo_runner.initialize_builtins(&mut vm).unwrap();
-        cairo_runner.initialize_segments(&mut vm, None);
+        cairo_runner
+            .initialize_segments(&mut vm, None, None)
+            .unwrap();
         let end = cairo_runner.initialize_main_entrypoint(&mut vm).unwrap();
         cairo_runner.initialize_vm(&mut vm).unwrap();
         assert_eq!(
-            cairo_runner.run_until_pc(end, &mut vm, &mut hint_processor),
+            cairo_runner.run_until_pc(
+                end,
+                &mut RunResources::default(),
+                &mut vm,
+                &mut hint_processor
+            ),
             Ok(())
         );
         vm.segments.compute_effective_sizes(&vm.memory);
@@ -2611,7 +3759,9 @@ mod tests {
         let mut cairo_runner = cairo_runner!(program);
         let mut vm = vm!();
         cairo_runner.initialize_builtins(&mut vm).unwrap();
-        cairo_runner.initialize_segments(&mut vm, None);
+        cairo_runner
+            .initialize_segments(&mut vm, None, None)
+            .unwrap();
         assert_eq!(vm.builtin_runners[0].0, String::from("output"));
         assert_eq!(vm.builtin_runners[0].1.base(), 2);
 
@@ -2623,54 +3773,26 @@ mod tests {
     }
 
     #[test]
-    /*Program used:
-    %builtins output
-
-    from starkware.cairo.common.serialize import serialize_word
-
-    func main{output_ptr: felt*}():
-        let a = 1
-        serialize_word(a)
-        return()
-    end */
     fn write_output_from_program() {
         //Initialization Phase
-        let program = program!(
-            builtins = vec![String::from("output")],
-            data = vec_data!(
-                (4612671182993129469_i64),
-                (5198983563776393216_i64),
-                (1),
-                (2345108766317314046_i64),
-                (5191102247248822272_i64),
-                (5189976364521848832_i64),
-                (1),
-                (1226245742482522112_i64),
-                ((
-                    "3618502788666131213697322783095070105623107215331596699973092056135872020474",
-                    10
-                )),
-                (5189976364521848832_i64),
-                (17),
-                (1226245742482522112_i64),
-                ((
-                    "3618502788666131213697322783095070105623107215331596699973092056135872020470",
-                    10
-                )),
-                (2345108766317314046_i64)
-            ),
-            main = Some(4),
-        );
+        let program = Program::from_fixture("serialize_word");
         let mut cairo_runner = cairo_runner!(program);
         let mut vm = vm!();
         cairo_runner.initialize_builtins(&mut vm).unwrap();
-        cairo_runner.initialize_segments(&mut vm, None);
+        cairo_runner
+            .initialize_segments(&mut vm, None, None)
+            .unwrap();
         let end = cairo_runner.initialize_main_entrypoint(&mut vm).unwrap();
         cairo_runner.initialize_vm(&mut vm).unwrap();
         //Execution Phase
         let mut hint_processor = BuiltinHintProcessor::new_empty();
         assert_eq!(
-            cairo_runner.run_until_pc(end, &mut vm, &mut hint_processor),
+            cairo_runner.run_until_pc(
+                end,
+                &mut RunResources::default(),
+                &mut vm,
+                &mut hint_processor
+            ),
             Ok(())
         );
 
@@ -2685,7 +3807,9 @@ mod tests {
         let mut cairo_runner = cairo_runner!(program);
         let mut vm = vm!();
         cairo_runner.initialize_builtins(&mut vm).unwrap();
-        cairo_runner.initialize_segments(&mut vm, None);
+        cairo_runner
+            .initialize_segments(&mut vm, None, None)
+            .unwrap();
         assert_eq!(vm.builtin_runners[0].0, String::from("output"));
         assert_eq!(vm.builtin_runners[0].1.base(), 2);
         vm.memory = memory![(
@@ -2701,36 +3825,69 @@ mod tests {
         assert_eq!(String::from_utf8(stdout), Ok(String::from("-1\n")));
     }
 
+    #[test]
+    fn write_output_relocatable_and_missing_values() {
+        let program = program!["output"];
+        let mut cairo_runner = cairo_runner!(program);
+        let mut vm = vm!();
+        cairo_runner.initialize_builtins(&mut vm).unwrap();
+        cairo_runner
+            .initialize_segments(&mut vm, None, None)
+            .unwrap();
+
+        vm.memory = memory![((2, 0), 1), ((2, 1), (1, 3)), ((2, 3), 4)];
+        vm.segments.segment_used_sizes = Some(vec![0, 0, 4]);
+        let mut stdout = Vec::<u8>::new();
+        cairo_runner.write_output(&mut vm, &mut stdout).unwrap();
+        assert_eq!(
+            String::from_utf8(stdout),
+            Ok(String::from("1\n1:3\n<missing>\n4\n"))
+        );
+        assert_eq!(
+            cairo_runner.get_output_values(&mut vm),
+            Err(RunnerError::MemoryError(MemoryError::GetRangeMemoryGap))
+        );
+    }
+
+    #[test]
+    fn get_output_values_from_program() {
+        let program = Program::from_fixture("serialize_word");
+        let mut cairo_runner = cairo_runner!(program);
+        let mut vm = vm!();
+        let end = cairo_runner.initialize(&mut vm).unwrap();
+        let mut hint_processor = BuiltinHintProcessor::new_empty();
+        cairo_runner
+            .run_until_pc(
+                end,
+                &mut RunResources::default(),
+                &mut vm,
+                &mut hint_processor,
+            )
+            .unwrap();
+
+        assert_eq!(
+            cairo_runner.get_output_values(&mut vm),
+            Ok(vec![mayberelocatable!(1), mayberelocatable!(17)])
+        );
+    }
+
+    #[test]
+    fn get_output_values_without_output_builtin() {
+        let program = Program::from_fixture("check_range");
+        let mut cairo_runner = cairo_runner!(program);
+        let mut vm = vm!();
+        cairo_runner.initialize(&mut vm).unwrap();
+
+        assert_eq!(cairo_runner.get_output_values(&mut vm), Ok(Vec::new()));
+    }
+
     /// Test that `write_output()` works when the `output` builtin is not the first one.
     #[test]
     fn write_output_unordered_builtins() {
         //Initialization Phase
-        let program = program!(
-            builtins = vec!["output".to_string(), "bitwise".to_string()],
-            data = vec_data!(
-                (4612671182993129469_i64),
-                (5198983563776393216_i64),
-                (1),
-                (2345108766317314046_i64),
-                (5191102247248822272_i64),
-                (5189976364521848832_i64),
-                (1),
-                (1226245742482522112_i64),
-                ((
-                    "3618502788666131213697322783095070105623107215331596699973092056135872020474",
-                    10
-                )),
-                (5189976364521848832_i64),
-                (17),
-                (1226245742482522112_i64),
-                ((
-                    "3618502788666131213697322783095070105623107215331596699973092056135872020470",
-                    10
-                )),
-                (2345108766317314046_i64)
-            ),
-            main = Some(4),
-        );
+        let mut program = Program::from_fixture("serialize_word");
+        // Add a second builtin to swap the output builtin with
+        program.builtins.push(String::from("bitwise"));
 
         let mut cairo_runner = cairo_runner!(program);
         let mut vm = vm!();
@@ -2742,7 +3899,9 @@ mod tests {
         // Swap the first and second builtins (first should be `output`).
         vm.builtin_runners.swap(0, 1);
 
-        cairo_runner.initialize_segments(&mut vm, None);
+        cairo_runner
+            .initialize_segments(&mut vm, None, None)
+            .unwrap();
 
         let end = cairo_runner
             .initialize_main_entrypoint(&mut vm)
@@ -2753,7 +3912,12 @@ mod tests {
 
         let mut hint_processor = BuiltinHintProcessor::new_empty();
         assert_eq!(
-            cairo_runner.run_until_pc(end, &mut vm, &mut hint_processor),
+            cairo_runner.run_until_pc(
+                end,
+                &mut RunResources::default(),
+                &mut vm,
+                &mut hint_processor
+            ),
             Ok(())
         );
 
@@ -2778,56 +3942,17 @@ mod tests {
     }
 
     #[test]
-    /*Program used:
-    %builtins range_check
-
-    func check_range{range_check_ptr}(num):
-        # Check that 0 <= num < 2**64.
-        [range_check_ptr] = num
-        assert [range_check_ptr + 1] = 2 ** 64 - 1 - num
-        let range_check_ptr = range_check_ptr + 2
-        return()
-    end
-
-    func main{range_check_ptr}():
-        check_range(7)
-        return()
-    end
-
-    main = 8
-    data = [4612671182993129469, 5189976364521848832, 18446744073709551615, 5199546496550207487, 4612389712311386111, 5198983563776393216, 2, 2345108766317314046, 5191102247248822272, 5189976364521848832, 7, 1226245742482522112, 3618502788666131213697322783095070105623107215331596699973092056135872020470, 2345108766317314046]
-    */
     fn run_for_steps() {
-        let program = program!(
-            builtins = vec![String::from("range_check")],
-            data = vec_data!(
-                (4612671182993129469_i64),
-                (5189976364521848832_i64),
-                (18446744073709551615_i128),
-                (5199546496550207487_i64),
-                (4612389712311386111_i64),
-                (5198983563776393216_i64),
-                (2),
-                (2345108766317314046_i64),
-                (5191102247248822272_i64),
-                (5189976364521848832_i64),
-                (7),
-                (1226245742482522112_i64),
-                ((
-                    "3618502788666131213697322783095070105623107215331596699973092056135872020470",
-                    10
-                )),
-                (2345108766317314046_i64)
-            ),
-            main = Some(8),
-        );
+        let program = Program::from_fixture("check_range");
 
         let mut hint_processor = BuiltinHintProcessor::new_empty();
         let mut cairo_runner = cairo_runner!(&program);
 
         let mut vm = vm!(true);
         cairo_runner.initialize_builtins(&mut vm).unwrap();
-        cairo_runner.initialize_segments(&mut vm, None);
+        cairo_runner
+            .initialize_segments(&mut vm, None, None)
+            .unwrap();
 
         cairo_runner.initialize_main_entrypoint(&mut vm).unwrap();
         cairo_runner.initialize_vm(&mut vm).unwrap();
@@ -2835,65 +3960,27 @@ mod tests {
         // Full takes 10 steps.
         assert_eq!(
             cairo_runner.run_for_steps(8, &mut vm, &mut hint_processor),
-            Ok(())
+            Ok(false)
         );
         assert_eq!(
             cairo_runner.run_for_steps(8, &mut vm, &mut hint_processor),
-            Err(VirtualMachineError::EndOfProgram(8 - 2))
+            Ok(true)
         );
+        assert_eq!(vm.current_step, 10);
     }
 
     #[test]
-    /*Program used:
-    %builtins range_check
-
-    func check_range{range_check_ptr}(num):
-        # Check that 0 <= num < 2**64.
-        [range_check_ptr] = num
-        assert [range_check_ptr + 1] = 2 ** 64 - 1 - num
-        let range_check_ptr = range_check_ptr + 2
-        return()
-    end
-
-    func main{range_check_ptr}():
-        check_range(7)
-        return()
-    end
-
-    main = 8
-    data = [4612671182993129469, 5189976364521848832, 18446744073709551615, 5199546496550207487, 4612389712311386111, 5198983563776393216, 2, 2345108766317314046, 5191102247248822272, 5189976364521848832, 7, 1226245742482522112, 3618502788666131213697322783095070105623107215331596699973092056135872020470, 2345108766317314046]
-    */
     fn run_until_steps() {
-        let program = program!(
-            builtins = vec![String::from("range_check")],
-            data = vec_data!(
-                (4612671182993129469_i64),
-                (5189976364521848832_i64),
-                (18446744073709551615_i128),
-                (5199546496550207487_i64),
-                (4612389712311386111_i64),
-                (5198983563776393216_i64),
-                (2),
-                (2345108766317314046_i64),
-                (5191102247248822272_i64),
-                (5189976364521848832_i64),
-                (7),
-                (1226245742482522112_i64),
-                ((
-                    "3618502788666131213697322783095070105623107215331596699973092056135872020470",
-                    10
-                )),
-                (2345108766317314046_i64)
-            ),
-            main = Some(8),
-        );
+        let program = Program::from_fixture("check_range");
 
         let mut hint_processor = BuiltinHintProcessor::new_empty();
         let mut cairo_runner = cairo_runner!(&program);
 
         let mut vm = vm!(true);
         cairo_runner.initialize_builtins(&mut vm).unwrap();
-        cairo_runner.initialize_segments(&mut vm, None);
+        cairo_runner
+            .initialize_segments(&mut vm, None, None)
+            .unwrap();
 
         cairo_runner.initialize_main_entrypoint(&mut vm).unwrap();
         cairo_runner.initialize_vm(&mut vm).unwrap();
@@ -2914,112 +4001,198 @@ mod tests {
     }
 
     #[test]
-    /*Program used:
-    %builtins range_check
-
-    func check_range{range_check_ptr}(num):
-        # Check that 0 <= num < 2**64.
-        [range_check_ptr] = num
-        assert [range_check_ptr + 1] = 2 ** 64 - 1 - num
-        let range_check_ptr = range_check_ptr + 2
-        return()
-    end
-
-    func main{range_check_ptr}():
-        check_range(7)
-        return()
-    end
+    fn run_until_pc_out_of_run_resources() {
+        let program = Program::from_fixture("countdown");
+        let mut hint_processor = BuiltinHintProcessor::new_empty();
+        let mut cairo_runner = cairo_runner!(program);
+        let mut vm = vm!();
+        let end = cairo_runner.initialize(&mut vm).unwrap();
 
-    main = 8
-    data = [4612671182993129469, 5189976364521848832, 18446744073709551615, 5199546496550207487, 4612389712311386111, 5198983563776393216, 2, 2345108766317314046, 5191102247248822272, 5189976364521848832, 7, 1226245742482522112, 3618502788666131213697322783095070105623107215331596699973092056135872020470, 2345108766317314046]
-    */
-    /// Verify that run_until_next_power_2() executes steps until the current
-    /// step reaches a power of two, or an error occurs.
-    fn run_until_next_power_of_2() {
-        let program = program!(
-            builtins = vec![String::from("range_check")],
-            data = vec_data!(
-                (4612671182993129469_i64),
-                (5189976364521848832_i64),
-                (18446744073709551615_i128),
-                (5199546496550207487_i64),
-                (4612389712311386111_i64),
-                (5198983563776393216_i64),
-                (2),
-                (2345108766317314046_i64),
-                (5191102247248822272_i64),
-                (5189976364521848832_i64),
-                (7),
-                (1226245742482522112_i64),
-                ((
-                    "3618502788666131213697322783095070105623107215331596699973092056135872020470",
-                    10
-                )),
-                (2345108766317314046_i64)
-            ),
-            main = Some(8),
+        let mut run_resources = RunResources::new(100);
+        assert_eq!(
+            cairo_runner.run_until_pc(end, &mut run_resources, &mut vm, &mut hint_processor),
+            Err(VirtualMachineError::UnfinishedExecution(100))
         );
+        assert!(run_resources.consumed());
+        assert_eq!(vm.current_step, 100);
+    }
 
+    #[test]
+    fn run_until_pc_within_run_resources() {
+        let program = Program::from_fixture("countdown");
         let mut hint_processor = BuiltinHintProcessor::new_empty();
-        let mut cairo_runner = cairo_runner!(&program);
-
-        let mut vm = vm!(true);
-        cairo_runner.initialize_builtins(&mut vm).unwrap();
-        cairo_runner.initialize_segments(&mut vm, None);
-
-        cairo_runner.initialize_main_entrypoint(&mut vm).unwrap();
-        cairo_runner.initialize_vm(&mut vm).unwrap();
+        let mut cairo_runner = cairo_runner!(program);
+        let mut vm = vm!();
+        let end = cairo_runner.initialize(&mut vm).unwrap();
 
-        // Full takes 10 steps.
-        assert_eq!(
-            cairo_runner.run_for_steps(1, &mut vm, &mut hint_processor),
-            Ok(()),
-        );
+        let mut run_resources = RunResources::new(1001);
         assert_eq!(
-            cairo_runner.run_until_next_power_of_2(&mut vm, &mut hint_processor),
+            cairo_runner.run_until_pc(end, &mut run_resources, &mut vm, &mut hint_processor),
             Ok(())
         );
-        assert_eq!(vm.current_step, 1);
+        assert_eq!(run_resources, RunResources { n_steps: Some(1) });
+        assert_eq!(vm.current_step, 1000);
+    }
+
+    #[test]
+    fn run_until_pc_with_hint_consuming_run_resources() {
+        // Runs the builtin hints, after consuming 3 steps as a hint running code would
+        struct StepConsumingHintProcessor(BuiltinHintProcessor);
+
+        impl HintProcessor for StepConsumingHintProcessor {
+            fn execute_hint(
+                &mut self,
+                vm: &mut VirtualMachine,
+                exec_scopes: &mut ExecutionScopes,
+                hint_data: &Box<dyn Any>,
+                constants: &HashMap<String, Felt>,
+                run_resources: &mut RunResources,
+            ) -> Result<(), HintError> {
+                for _ in 0..3 {
+                    run_resources.consume_step();
+                }
+                self.0
+                    .execute_hint(vm, exec_scopes, hint_data, constants, run_resources)
+            }
+        }
 
+        let program = Program::from_fixture("memory_holes");
+        let mut hint_processor = StepConsumingHintProcessor(BuiltinHintProcessor::new_empty());
+        let mut cairo_runner = cairo_runner!(program);
+        let mut vm = vm!();
+        let end = cairo_runner.initialize(&mut vm).unwrap();
+
+        // The program takes 5 steps, and its hint 3 more
+        let mut run_resources = RunResources::new(5);
         assert_eq!(
-            cairo_runner.run_for_steps(1, &mut vm, &mut hint_processor),
-            Ok(()),
-        );
-        assert_eq!(
-            cairo_runner.run_until_next_power_of_2(&mut vm, &mut hint_processor),
-            Ok(())
+            cairo_runner.run_until_pc(end, &mut run_resources, &mut vm, &mut hint_processor),
+            Err(VirtualMachineError::UnfinishedExecution(2))
         );
         assert_eq!(vm.current_step, 2);
+    }
 
-        assert_eq!(
-            cairo_runner.run_for_steps(1, &mut vm, &mut hint_processor),
-            Ok(()),
+    #[test]
+    fn run_resources_default_is_unbounded() {
+        let mut run_resources = RunResources::default();
+        run_resources.consume_step();
+        assert_eq!(run_resources.n_steps, None);
+        assert!(!run_resources.consumed());
+    }
+
+    #[test]
+    fn run_program_loaded_at_non_zero_offset() {
+        let program = Program::from_fixture("check_range");
+        // A single `ap += 1` instruction, with a hint that allocates a segment at [ap]
+        let child_program = program!(
+            data = vec![mayberelocatable!(0x40780017fff7fff), mayberelocatable!(1),],
+            hints = HashMap::from([(
+                0,
+                vec![HintParams {
+                    code: String::from("memory[ap] = segments.add()"),
+                    accessible_scopes: Vec::new(),
+                    flow_tracking_data: FlowTrackingData {
+                        ap_tracking: ApTracking::new(),
+                        reference_ids: HashMap::new(),
+                    },
+                }],
+            )]),
         );
+
+        let mut hint_processor = BuiltinHintProcessor::new_empty();
+        let mut cairo_runner = cairo_runner!(&program);
+        let mut vm = vm!();
+        cairo_runner.initialize(&mut vm).unwrap();
+
+        let child_base = vm.add_memory_segment() + 100_usize;
+        vm.load_data(&child_base.into(), &child_program.data)
+            .unwrap();
+        cairo_runner
+            .load_program_hints(&child_program, child_base, &mut hint_processor)
+            .unwrap();
+
+        let ap = vm.get_ap();
+        let next_segment = vm.segments.num_segments;
+        vm.run_context.pc = child_base;
         assert_eq!(
-            cairo_runner.run_until_next_power_of_2(&mut vm, &mut hint_processor),
+            cairo_runner.run_until_pc(
+                child_base + 2_usize,
+                &mut RunResources::default(),
+                &mut vm,
+                &mut hint_processor
+            ),
             Ok(())
         );
-        assert_eq!(vm.current_step, 4);
+        assert_eq!(
+            vm.get_relocatable(&ap),
+            Ok(Relocatable::from((next_segment as isize, 0)))
+        );
+        assert_eq!(vm.get_ap(), ap + 1_usize);
+    }
+
+    #[test]
+    /// Verify that run_until_next_power_2() executes steps until the current
+    /// step reaches a power of two, running the __end__ loop once main returned.
+    fn run_until_next_power_of_2() {
+        let program = Program::from_fixture("check_range_proof_mode");
+
+        let mut hint_processor = BuiltinHintProcessor::new_empty();
+        let mut cairo_runner = cairo_runner!(program, "small", true);
+        let mut vm = vm!(true);
+        let end = cairo_runner.initialize(&mut vm).unwrap();
+
+        for expected_step in [1, 2, 4, 8] {
+            assert_eq!(
+                cairo_runner.run_for_steps(1, &mut vm, &mut hint_processor),
+                Ok(false),
+            );
+            assert_eq!(
+                cairo_runner.run_until_next_power_of_2(&mut vm, &mut hint_processor),
+                Ok(())
+            );
+            assert_eq!(vm.current_step, expected_step);
+        }
 
+        // Full takes 12 steps.
         assert_eq!(
-            cairo_runner.run_for_steps(1, &mut vm, &mut hint_processor),
-            Ok(()),
+            cairo_runner.run_until_pc(
+                end,
+                &mut RunResources::default(),
+                &mut vm,
+                &mut hint_processor
+            ),
+            Ok(())
         );
+        assert_eq!(vm.current_step, 12);
         assert_eq!(
             cairo_runner.run_until_next_power_of_2(&mut vm, &mut hint_processor),
             Ok(())
         );
-        assert_eq!(vm.current_step, 8);
+        assert_eq!(vm.current_step, 16);
+        let trace = vm.trace.as_ref().unwrap();
+        assert_eq!(trace.len(), 16);
+        assert!(trace[12..].iter().all(|entry| entry.pc == end));
+    }
+
+    #[test]
+    fn run_until_next_power_of_2_without_proof_mode() {
+        let program = Program::from_fixture("check_range");
+
+        let mut hint_processor = BuiltinHintProcessor::new_empty();
+        let mut cairo_runner = cairo_runner!(&program);
+        let mut vm = vm!(true);
+        cairo_runner.initialize(&mut vm).unwrap();
 
         assert_eq!(
-            cairo_runner.run_for_steps(1, &mut vm, &mut hint_processor),
-            Ok(()),
+            cairo_runner.run_for_steps(3, &mut vm, &mut hint_processor),
+            Ok(false)
         );
         assert_eq!(
             cairo_runner.run_until_next_power_of_2(&mut vm, &mut hint_processor),
-            Err(VirtualMachineError::EndOfProgram(6)),
+            Err(VirtualMachineError::RunnerError(
+                RunnerError::PaddingNoProofMode
+            ))
         );
-        assert_eq!(vm.current_step, 10);
+        assert_eq!(vm.current_step, 3);
     }
 
     #[test]
@@ -3057,7 +4230,7 @@ mod tests {
         vm.builtin_runners = Vec::new();
         assert_eq!(
             cairo_runner.get_memory_holes(&vm),
-            Err(MemoryError::MissingSegmentUsedSizes),
+            Err(MemoryError::EffectiveSizesNotCalled),
         );
     }
 
@@ -3095,7 +4268,9 @@ mod tests {
 
         vm.builtin_runners = vec![{
             let mut builtin_runner: BuiltinRunner = OutputBuiltinRunner::new(true).into();
-            builtin_runner.initialize_segments(&mut vm.segments, &mut vm.memory);
+            builtin_runner
+                .initialize_segments(&mut vm.segments, &mut vm.memory)
+                .unwrap();
 
             ("output".to_string(), builtin_runner)
         }];
@@ -3113,7 +4288,9 @@ mod tests {
         vm.accessed_addresses = Some(vec![(1, 0).into(), (1, 2).into()]);
         vm.builtin_runners = vec![{
             let mut builtin_runner: BuiltinRunner = OutputBuiltinRunner::new(true).into();
-            builtin_runner.initialize_segments(&mut vm.segments, &mut vm.memory);
+            builtin_runner
+                .initialize_segments(&mut vm.segments, &mut vm.memory)
+                .unwrap();
 
             ("output".to_string(), builtin_runner)
         }];
@@ -3121,6 +4298,60 @@ mod tests {
         assert_eq!(cairo_runner.get_memory_holes(&vm), Ok(2));
     }
 
+    #[test]
+    fn get_memory_holes_skips_builtin_segments() {
+        let program = program!();
+
+        let cairo_runner = cairo_runner!(program);
+        let mut vm = vm!();
+
+        // Only one of the 4 cells of the output segment was accessed
+        vm.accessed_addresses = Some(vec![(0, 1).into(), (1, 0).into()]);
+        vm.builtin_runners = vec![{
+            let mut builtin_runner: BuiltinRunner = OutputBuiltinRunner::new(true).into();
+            builtin_runner
+                .initialize_segments(&mut vm.segments, &mut vm.memory)
+                .unwrap();
+
+            ("output".to_string(), builtin_runner)
+        }];
+        vm.segments.segment_used_sizes = Some(vec![4, 1]);
+        assert_eq!(cairo_runner.get_memory_holes(&vm), Ok(0));
+    }
+
+    #[test]
+    fn get_memory_holes_segment_added_by_hint() {
+        let program = Program::from_fixture("memory_holes");
+        let mut cairo_runner = cairo_runner!(program);
+        let mut vm = vm!();
+        let mut hint_processor = BuiltinHintProcessor::new_empty();
+
+        let end = cairo_runner.initialize(&mut vm).unwrap();
+        cairo_runner
+            .run_until_pc(
+                end,
+                &mut RunResources::default(),
+                &mut vm,
+                &mut hint_processor,
+            )
+            .unwrap();
+        cairo_runner
+            .end_run(false, false, &mut vm, &mut hint_processor)
+            .unwrap();
+
+        // The segment added by the hint comes after the program, execution, return fp and
+        // return pc segments. Its cell 1 was never written nor accessed.
+        assert_eq!(vm.segments.get_segment_used_size(4), Some(3));
+        assert_eq!(cairo_runner.get_memory_holes(&vm), Ok(1));
+        assert_eq!(
+            cairo_runner
+                .get_execution_resources(&vm)
+                .unwrap()
+                .n_memory_holes,
+            1
+        );
+    }
+
     /// Test that check_diluted_check_usage() works without a diluted pool
     /// instance.
     #[test]
@@ -3160,7 +4391,11 @@ mod tests {
         vm.builtin_runners = vec![];
         assert_eq!(
             cairo_runner.check_diluted_check_usage(&vm),
-            Err(MemoryError::InsufficientAllocatedCells.into()),
+            Err(MemoryError::InsufficientDilutedUnits {
+                unused: 1600,
+                required: 65536
+            }
+            .into()),
         );
     }
 
@@ -3182,62 +4417,514 @@ mod tests {
     }
 
     #[test]
-    fn end_run_run_already_finished() {
-        let program = program!();
-
+    fn get_used_diluted_check_units_sum() {
+        let program = program!();
+
+        let cairo_runner = cairo_runner!(program);
+        let mut vm = vm!();
+
+        vm.current_step = 512;
+        vm.builtin_runners = vec![(
+            "bitwise".to_string(),
+            BitwiseBuiltinRunner::new(&BitwiseInstanceDef::default(), true).into(),
+        )];
+        // Each bitwise instance uses 68 diluted units, and there are 2 of them in 512 steps
+        assert_eq!(
+            cairo_runner
+                .get_used_diluted_check_units_sum(&vm, &DilutedPoolInstanceDef::new(16, 4, 16)),
+            Ok(136)
+        );
+    }
+
+    #[test]
+    fn get_used_diluted_check_units_sum_pool_exceeded() {
+        let program = program!();
+
+        let cairo_runner = cairo_runner!(program);
+        let mut vm = vm!();
+
+        vm.current_step = 512;
+        vm.builtin_runners = vec![(
+            "bitwise".to_string(),
+            BitwiseBuiltinRunner::new(&BitwiseInstanceDef::default(), true).into(),
+        )];
+        assert_eq!(
+            cairo_runner
+                .get_used_diluted_check_units_sum(&vm, &DilutedPoolInstanceDef::new(0, 4, 16)),
+            Err(MemoryError::DilutedUnitsExceeded {
+                used: 136,
+                available: 0
+            }
+            .into())
+        );
+    }
+
+    #[test]
+    fn end_run_run_already_finished() {
+        let program = program!();
+
+        let mut hint_processor = BuiltinHintProcessor::new_empty();
+        let mut cairo_runner = cairo_runner!(program);
+        let mut vm = vm!();
+
+        cairo_runner.run_ended = true;
+        assert_eq!(
+            cairo_runner.end_run(true, false, &mut vm, &mut hint_processor),
+            Err(RunnerError::RunAlreadyFinished.into()),
+        );
+    }
+
+    #[test]
+    fn run_after_end_run() {
+        let program = Program::from_fixture("check_range");
+
+        let mut hint_processor = BuiltinHintProcessor::new_empty();
+        let mut cairo_runner = cairo_runner!(&program);
+        let mut vm = vm!();
+        let end = cairo_runner.initialize(&mut vm).unwrap();
+        cairo_runner
+            .run_until_pc(
+                end,
+                &mut RunResources::default(),
+                &mut vm,
+                &mut hint_processor,
+            )
+            .unwrap();
+        cairo_runner
+            .end_run(false, false, &mut vm, &mut hint_processor)
+            .unwrap();
+
+        assert_eq!(
+            cairo_runner.run_until_pc(
+                end,
+                &mut RunResources::default(),
+                &mut vm,
+                &mut hint_processor
+            ),
+            Err(RunnerError::RunAlreadyFinished.into()),
+        );
+        assert_eq!(
+            cairo_runner.run_for_steps(1, &mut vm, &mut hint_processor),
+            Err(RunnerError::RunAlreadyFinished.into()),
+        );
+        assert_eq!(
+            cairo_runner.end_run(false, false, &mut vm, &mut hint_processor),
+            Err(RunnerError::RunAlreadyFinished.into()),
+        );
+    }
+
+    #[test]
+    fn end_run() {
+        let program = program!();
+
+        let mut hint_processor = BuiltinHintProcessor::new_empty();
+        let mut cairo_runner = cairo_runner!(program);
+        let mut vm = vm!();
+
+        vm.accessed_addresses = Some(Vec::new());
+        assert_eq!(
+            cairo_runner.end_run(true, false, &mut vm, &mut hint_processor),
+            Ok(()),
+        );
+
+        cairo_runner.run_ended = false;
+        cairo_runner.relocated_memory.clear();
+        assert_eq!(
+            cairo_runner.end_run(true, true, &mut vm, &mut hint_processor),
+            Ok(()),
+        );
+        assert!(cairo_runner.run_ended);
+    }
+
+    #[test]
+    fn end_run_without_finalizing_twice() {
+        let program = Program::from_fixture("function_call");
+
+        let mut hint_processor = BuiltinHintProcessor::new_empty();
+        let mut cairo_runner = cairo_runner!(program);
+        let mut vm = vm!();
+        cairo_runner.initialize(&mut vm).unwrap();
+
+        assert_eq!(
+            cairo_runner.end_run(false, true, &mut vm, &mut hint_processor),
+            Ok(()),
+        );
+        assert_eq!(
+            cairo_runner.end_run(false, true, &mut vm, &mut hint_processor),
+            Err(RunnerError::RunAlreadyFinished.into()),
+        );
+        assert_eq!(
+            cairo_runner.run_for_steps(1, &mut vm, &mut hint_processor),
+            Err(RunnerError::RunAlreadyFinished.into()),
+        );
+    }
+
+    #[test]
+    fn end_run_freezes_memory() {
+        let program = Program::from_fixture("function_call");
+
+        let mut hint_processor = BuiltinHintProcessor::new_empty();
+        let mut cairo_runner = cairo_runner!(program);
+        let mut vm = vm!();
+        cairo_runner.initialize(&mut vm).unwrap();
+
+        assert_eq!(
+            cairo_runner.end_run(false, true, &mut vm, &mut hint_processor),
+            Ok(()),
+        );
+        assert_eq!(
+            vm.insert_value(&relocatable!(1, 5), Felt::new(7)),
+            Err(MemoryError::FrozenSegment(1).into()),
+        );
+        assert_eq!(
+            vm.insert_value(&relocatable!(0, 0), Felt::new(7)),
+            Err(MemoryError::FrozenSegment(0).into()),
+        );
+    }
+
+    #[test]
+    fn end_run_drops_unrelocated_temporary_segments() {
+        let program = program!();
+
+        let mut hint_processor = BuiltinHintProcessor::new_empty();
+        let mut cairo_runner = cairo_runner!(program);
+        let mut vm = vm!();
+
+        vm.accessed_addresses = Some(Vec::new());
+        add_segments!(vm, 1);
+        let scratch = vm.segments.add_temporary_segment(&mut vm.memory);
+        let aborted = vm.segments.add_temporary_segment(&mut vm.memory);
+        vm.insert_value(&scratch, aborted).unwrap();
+        vm.insert_value(&(0, 0).into(), 5).unwrap();
+        assert_eq!(
+            cairo_runner.end_run(true, false, &mut vm, &mut hint_processor),
+            Ok(()),
+        );
+        assert!(vm.memory.temp_data.is_empty());
+        assert_eq!(
+            cairo_runner
+                .get_execution_resources(&vm)
+                .unwrap()
+                .n_dropped_temporary_segments,
+            2
+        );
+    }
+
+    #[test]
+    fn end_run_referenced_unrelocated_temporary_segment() {
+        let program = program!();
+
+        let mut hint_processor = BuiltinHintProcessor::new_empty();
+        let mut cairo_runner = cairo_runner!(program);
+        let mut vm = vm!();
+
+        add_segments!(vm, 1);
+        let scratch = vm.segments.add_temporary_segment(&mut vm.memory);
+        vm.insert_value(&(0, 2).into(), scratch).unwrap();
+        assert_eq!(
+            cairo_runner.end_run(true, false, &mut vm, &mut hint_processor),
+            Err(MemoryError::UnrelocatedTemporarySegment(-1, vec![(0, 2).into()]).into()),
+        );
+    }
+
+    #[test]
+    fn end_run_proof_mode_insufficient_allocated_cells() {
+        let program = Program::from_file(
+            Path::new("cairo_programs/proof_programs/fibonacci.json"),
+            Some("main"),
+        )
+        .expect("Call to `Program::from_file()` failed.");
+
+        let mut hint_processor = BuiltinHintProcessor::new_empty();
+        let mut cairo_runner = cairo_runner!(program, "all", true);
+        let mut vm = vm!(true);
+
+        let end = cairo_runner.initialize(&mut vm).unwrap();
+        cairo_runner
+            .run_until_pc(
+                end,
+                &mut RunResources::default(),
+                &mut vm,
+                &mut hint_processor,
+            )
+            .expect("Call to `CairoRunner::run_until_pc()` failed.");
+        assert_eq!(
+            cairo_runner.end_run(false, false, &mut vm, &mut hint_processor),
+            Ok(()),
+        );
+    }
+
+    /// Runs the proof mode version of check_range until the __end__ loop.
+    fn run_check_range_proof_mode() -> (CairoRunner, VirtualMachine) {
+        let program = Program::from_fixture("check_range_proof_mode");
+        let mut cairo_runner = cairo_runner!(program, "small", true);
+        let mut vm = vm!(true);
+        let mut hint_processor = BuiltinHintProcessor::new_empty();
+
+        let end = cairo_runner.initialize(&mut vm).unwrap();
+        cairo_runner
+            .run_until_pc(
+                end,
+                &mut RunResources::default(),
+                &mut vm,
+                &mut hint_processor,
+            )
+            .unwrap();
+        (cairo_runner, vm)
+    }
+
+    #[test]
+    fn finalize_for_proving() {
+        let (mut cairo_runner, mut vm) = run_check_range_proof_mode();
+        let mut hint_processor = BuiltinHintProcessor::new_empty();
+        let artifacts = cairo_runner
+            .finalize_for_proving(&mut vm, &mut hint_processor)
+            .unwrap();
+
+        let public_input = &artifacts.public_input;
+        assert_eq!(public_input.layout, "small");
+        assert!(public_input.n_steps.is_power_of_two());
+        assert_eq!(artifacts.relocated_trace.len(), public_input.n_steps);
+        let range_check = &public_input.memory_segments["range_check"];
+        assert_eq!(range_check.stop_ptr - range_check.begin_addr, 2);
+        // The program, and the initial stack and return values of the execution segment
+        assert_eq!(
+            public_input.public_memory.len(),
+            cairo_runner.program.data.len() + 4
+        );
+        for entry in &public_input.public_memory {
+            assert_eq!(
+                artifacts.relocated_memory[entry.address].as_ref(),
+                Some(&entry.value)
+            );
+        }
+        assert_eq!(
+            artifacts.private_input.builtins["range_check"],
+            vec![
+                PrivateInput::Value(PrivateInputValue {
+                    index: 0,
+                    value: Felt::new(7),
+                }),
+                PrivateInput::Value(PrivateInputValue {
+                    index: 1,
+                    value: Felt::new(u64::MAX - 7),
+                }),
+            ]
+        );
+        assert_eq!(
+            artifacts.execution_resources.builtin_instance_counter["range_check"],
+            2
+        );
+
+        // The relocated memory and trace were moved into the artifacts
+        assert!(cairo_runner.relocated_memory.is_empty());
+        assert_eq!(cairo_runner.relocated_trace, None);
+    }
+
+    #[test]
+    fn mark_initial_segments_as_accessed_proof_mode() {
+        let (cairo_runner, mut vm) = run_check_range_proof_mode();
+        vm.segments.compute_effective_sizes(&vm.memory);
+        // The initial stack holds the dummy return fp and pc, and the range check base. The
+        // run never reads the dummy return fp, as the __end__ loop never returns
+        let dummy_return_fp = Relocatable::from((1, 0));
+        let accessed_addresses = vm.accessed_addresses.as_ref().unwrap();
+        assert!(!accessed_addresses.contains(&dummy_return_fp));
+        assert_eq!(cairo_runner.get_memory_holes(&vm), Ok(1));
+
+        cairo_runner
+            .mark_initial_segments_as_accessed(&mut vm)
+            .unwrap();
+        let accessed_addresses = vm.accessed_addresses.as_ref().unwrap();
+        assert!((0..3).all(|offset| accessed_addresses.contains(&Relocatable::from((1, offset)))));
+        assert!((0..cairo_runner.get_program_segment_size())
+            .all(|offset| accessed_addresses.contains(&Relocatable::from((0, offset)))));
+        assert_eq!(cairo_runner.get_memory_holes(&vm), Ok(0));
+    }
+
+    #[test]
+    fn end_run_proof_mode_leaves_no_memory_holes() {
+        let (mut cairo_runner, mut vm) = run_check_range_proof_mode();
+        let mut hint_processor = BuiltinHintProcessor::new_empty();
+        // The trace is padded, as the 12 steps of the run don't fill the range check instances
+        // of the small layout
+        cairo_runner
+            .end_run(false, false, &mut vm, &mut hint_processor)
+            .unwrap();
+        assert!(vm
+            .accessed_addresses
+            .as_ref()
+            .unwrap()
+            .contains(&Relocatable::from((1, 0))));
+        assert_eq!(cairo_runner.get_memory_holes(&vm), Ok(0));
+    }
+
+    #[test]
+    fn mark_initial_segments_as_accessed_without_proof_mode() {
+        let program = Program::from_fixture("check_range");
+        let mut cairo_runner = cairo_runner!(program);
+        let mut vm = vm!();
+        cairo_runner.initialize(&mut vm).unwrap();
+        let accessed_addresses = vm.accessed_addresses.clone();
+
+        assert_eq!(
+            cairo_runner.mark_initial_segments_as_accessed(&mut vm),
+            Ok(())
+        );
+        assert_eq!(vm.accessed_addresses, accessed_addresses);
+    }
+
+    #[test]
+    fn read_return_values_sets_stop_pointers() {
+        let (mut cairo_runner, mut vm) = run_check_range_proof_mode();
+        let mut hint_processor = BuiltinHintProcessor::new_empty();
+        cairo_runner
+            .end_run(false, false, &mut vm, &mut hint_processor)
+            .unwrap();
+        cairo_runner.read_return_values(&mut vm).unwrap();
+
+        let stop_ptrs = vm
+            .builtin_runners
+            .iter()
+            .map(|(name, builtin)| {
+                let stop_ptr = builtin.get_memory_segment_addresses()[0].stop_ptr;
+                (name.as_str(), stop_ptr)
+            })
+            .collect::<Vec<_>>();
+        // Only the builtins of the program are on the stack, the other ones are added by the
+        // proof mode
+        assert_eq!(
+            stop_ptrs,
+            [
+                ("output", None),
+                ("pedersen", None),
+                ("range_check", Some(2)),
+                ("ecdsa", None)
+            ]
+        );
+    }
+
+    // Overwrites the stop pointer of the range_check builtin, the only one returned by the
+    // program, at [ap - 1]
+    fn replace_range_check_stop_pointer(vm: &mut VirtualMachine, stop_pointer: Relocatable) {
+        let ap = vm.get_ap();
+        vm.memory.data[ap.segment_index as usize][ap.offset - 1] = Some(stop_pointer.into());
+    }
+
+    #[test]
+    fn read_return_values_stop_pointer_off_by_one() {
+        let (mut cairo_runner, mut vm) = run_check_range_proof_mode();
+        let mut hint_processor = BuiltinHintProcessor::new_empty();
+        cairo_runner
+            .end_run(false, false, &mut vm, &mut hint_processor)
+            .unwrap();
+        let base = vm.builtin_runners[2].1.base();
+        replace_range_check_stop_pointer(&mut vm, (base, 1).into());
+
+        assert_eq!(
+            cairo_runner.read_return_values(&mut vm),
+            Err(RunnerError::InvalidStopPointer {
+                builtin: "range_check",
+                expected: (base, 2).into(),
+                found: (base, 1).into(),
+            })
+        );
+    }
+
+    #[test]
+    fn read_return_values_stop_pointer_in_another_segment() {
+        let (mut cairo_runner, mut vm) = run_check_range_proof_mode();
         let mut hint_processor = BuiltinHintProcessor::new_empty();
-        let mut cairo_runner = cairo_runner!(program);
-        let mut vm = vm!();
+        cairo_runner
+            .end_run(false, false, &mut vm, &mut hint_processor)
+            .unwrap();
+        let base = vm.builtin_runners[2].1.base();
+        replace_range_check_stop_pointer(&mut vm, (1, 2).into());
 
-        cairo_runner.run_ended = true;
         assert_eq!(
-            cairo_runner.end_run(true, false, &mut vm, &mut hint_processor),
-            Err(RunnerError::RunAlreadyFinished.into()),
+            cairo_runner.read_return_values(&mut vm),
+            Err(RunnerError::InvalidStopPointer {
+                builtin: "range_check",
+                expected: (base, 2).into(),
+                found: (1, 2).into(),
+            })
         );
     }
 
     #[test]
-    fn end_run() {
-        let program = program!();
-
+    fn read_return_values_truncated_stack() {
+        let (mut cairo_runner, mut vm) = run_check_range_proof_mode();
         let mut hint_processor = BuiltinHintProcessor::new_empty();
-        let mut cairo_runner = cairo_runner!(program);
-        let mut vm = vm!();
+        cairo_runner
+            .end_run(false, false, &mut vm, &mut hint_processor)
+            .unwrap();
+        // As if main returned no value at all
+        vm.run_context.ap = cairo_runner.initial_fp.unwrap().offset;
 
-        vm.accessed_addresses = Some(Vec::new());
         assert_eq!(
-            cairo_runner.end_run(true, false, &mut vm, &mut hint_processor),
-            Ok(()),
+            cairo_runner.read_return_values(&mut vm),
+            Err(RunnerError::NoStopPointer("range_check"))
         );
+        assert_eq!(vm.builtin_runners.len(), 4);
+    }
 
-        cairo_runner.run_ended = false;
-        cairo_runner.relocated_memory.clear();
+    #[test]
+    fn finalize_for_proving_matches_getters() {
+        let (mut cairo_runner, mut vm) = run_check_range_proof_mode();
+        let mut hint_processor = BuiltinHintProcessor::new_empty();
+        let artifacts = cairo_runner
+            .finalize_for_proving(&mut vm, &mut hint_processor)
+            .unwrap();
+
+        let (mut cairo_runner, mut vm) = run_check_range_proof_mode();
+        cairo_runner
+            .end_run(false, false, &mut vm, &mut hint_processor)
+            .unwrap();
+        vm.verify_auto_deductions().unwrap();
+        cairo_runner.read_return_values(&mut vm).unwrap();
+        cairo_runner.finalize_segments(&mut vm).unwrap();
         assert_eq!(
-            cairo_runner.end_run(true, true, &mut vm, &mut hint_processor),
-            Ok(()),
+            cairo_runner.get_air_public_input(&vm),
+            Ok(artifacts.public_input)
+        );
+        assert_eq!(
+            cairo_runner.get_air_private_input(&vm),
+            artifacts.private_input
+        );
+        assert_eq!(
+            cairo_runner.get_execution_resources(&vm),
+            Ok(artifacts.execution_resources)
+        );
+        cairo_runner.relocate(&mut vm, None).unwrap();
+        assert_eq!(cairo_runner.relocated_memory, artifacts.relocated_memory);
+        assert_eq!(
+            cairo_runner.relocated_trace,
+            Some(artifacts.relocated_trace)
         );
-        assert!(!cairo_runner.run_ended);
     }
 
     #[test]
-    fn end_run_proof_mode_insufficient_allocated_cells() {
-        let program = Program::from_file(
-            Path::new("cairo_programs/proof_programs/fibonacci.json"),
-            Some("main"),
-        )
-        .expect("Call to `Program::from_file()` failed.");
-
-        let mut hint_processor = BuiltinHintProcessor::new_empty();
-        let mut cairo_runner = cairo_runner!(program, "all", true);
+    fn finalize_for_proving_not_proof_mode() {
+        let program = Program::from_fixture("check_range");
+        let mut cairo_runner = cairo_runner!(program);
         let mut vm = vm!(true);
+        let mut hint_processor = BuiltinHintProcessor::new_empty();
 
         let end = cairo_runner.initialize(&mut vm).unwrap();
         cairo_runner
-            .run_until_pc(end, &mut vm, &mut hint_processor)
-            .expect("Call to `CairoRunner::run_until_pc()` failed.");
+            .run_until_pc(
+                end,
+                &mut RunResources::default(),
+                &mut vm,
+                &mut hint_processor,
+            )
+            .unwrap();
         assert_eq!(
-            cairo_runner.end_run(false, false, &mut vm, &mut hint_processor),
-            Ok(()),
+            cairo_runner
+                .finalize_for_proving(&mut vm, &mut hint_processor)
+                .unwrap_err()
+                .to_string(),
+            RunnerError::NoExecPublicMemory.to_string()
         );
     }
 
@@ -3271,6 +4958,43 @@ mod tests {
         );
     }
 
+    #[test]
+    fn get_builtin_segments_info_multiple_segments_per_builtin() {
+        let segments = vec![
+            SegmentAddresses::new("output", 2, Some(3)),
+            SegmentAddresses::new("segment_arena", 3, Some(6)),
+            SegmentAddresses::new("segment_arena_info", 4, Some(2)),
+        ];
+        assert_eq!(
+            collect_segments_info(segments),
+            Ok(HashMap::from([
+                ("output".to_string(), SegmentInfo { index: 2, size: 3 }),
+                (
+                    "segment_arena".to_string(),
+                    SegmentInfo { index: 3, size: 6 }
+                ),
+                (
+                    "segment_arena_info".to_string(),
+                    SegmentInfo { index: 4, size: 2 }
+                ),
+            ]))
+        );
+    }
+
+    #[test]
+    fn get_builtin_segments_info_name_collision() {
+        let segments = vec![
+            SegmentAddresses::new("keccak", 2, Some(3)),
+            SegmentAddresses::new("keccak", 3, Some(6)),
+        ];
+        assert_eq!(
+            collect_segments_info(segments),
+            Err(RunnerError::BuiltinSegmentNameCollision(
+                "keccak".to_string()
+            ))
+        );
+    }
+
     #[test]
     fn get_execution_resources_trace_not_enabled() {
         let program = program!();
@@ -3278,13 +5002,15 @@ mod tests {
         let cairo_runner = cairo_runner!(program);
         let mut vm = vm!();
 
+        vm.current_step = 7;
         vm.segments.segment_used_sizes = Some(vec![4]);
         assert_eq!(
             cairo_runner.get_execution_resources(&vm),
             Ok(ExecutionResources {
-                n_steps: 0,
+                n_steps: 7,
                 n_memory_holes: 0,
-                builtin_instance_counter: HashMap::new(),
+                builtin_instance_counter: BTreeMap::new(),
+                n_dropped_temporary_segments: 0,
             }),
         );
     }
@@ -3303,7 +5029,8 @@ mod tests {
             Ok(ExecutionResources {
                 n_steps: 10,
                 n_memory_holes: 0,
-                builtin_instance_counter: HashMap::new(),
+                builtin_instance_counter: BTreeMap::new(),
+                n_dropped_temporary_segments: 0,
             }),
         );
     }
@@ -3319,7 +5046,9 @@ mod tests {
         vm.segments.segment_used_sizes = Some(vec![4]);
         vm.builtin_runners = vec![{
             let mut builtin = OutputBuiltinRunner::new(true);
-            builtin.initialize_segments(&mut vm.segments, &mut vm.memory);
+            builtin
+                .initialize_segments(&mut vm.segments, &mut vm.memory)
+                .unwrap();
 
             ("output".to_string(), BuiltinRunner::Output(builtin))
         }];
@@ -3328,57 +5057,59 @@ mod tests {
             Ok(ExecutionResources {
                 n_steps: 10,
                 n_memory_holes: 0,
-                builtin_instance_counter: HashMap::from([("output".to_string(), 4)]),
+                builtin_instance_counter: BTreeMap::from([("output".to_string(), 4)]),
+                n_dropped_temporary_segments: 0,
             }),
         );
     }
 
-    /// Test that the call to .run_from_entrypoint() with args.count() != 1 when
-    /// typed_args is true fails.
+    fn execution_resources(
+        n_steps: usize,
+        n_memory_holes: usize,
+        builtin_instance_counter: &[(&str, usize)],
+    ) -> ExecutionResources {
+        ExecutionResources {
+            n_steps,
+            n_memory_holes,
+            builtin_instance_counter: builtin_instance_counter
+                .iter()
+                .map(|(name, instances)| (name.to_string(), *instances))
+                .collect(),
+            n_dropped_temporary_segments: 0,
+        }
+    }
+
     #[test]
-    fn run_from_entrypoint_typed_args_invalid_arg_count() {
-        let program =
-            Program::from_file(Path::new("cairo_programs/not_main.json"), Some("main")).unwrap();
-        let mut cairo_runner = cairo_runner!(program);
-        let mut vm = vm!();
-        let mut hint_processor = BuiltinHintProcessor::new_empty();
+    fn execution_resources_add() {
+        let lhs = execution_resources(10, 2, &[("output", 1), ("pedersen", 3)]);
+        let rhs = execution_resources(5, 1, &[("pedersen", 2), ("range_check", 0)]);
+        assert_eq!(
+            &lhs + &rhs,
+            execution_resources(15, 3, &[("output", 1), ("pedersen", 5), ("range_check", 0)])
+        );
+    }
 
-        let entrypoint = program
-            .identifiers
-            .get("__main__.not_main")
-            .unwrap()
-            .pc
-            .unwrap();
+    #[test]
+    fn execution_resources_sub() {
+        let lhs = execution_resources(10, 2, &[("output", 1), ("pedersen", 3)]);
+        let rhs = execution_resources(4, 3, &[("pedersen", 2), ("range_check", 1)]);
         assert_eq!(
-            cairo_runner.run_from_entrypoint(
-                entrypoint,
-                vec![],
-                true,
-                true,
-                true,
-                &mut vm,
-                &mut hint_processor,
-            ),
-            Err(VirtualMachineError::InvalidArgCount(1, 0)),
+            &lhs - &rhs,
+            execution_resources(6, 0, &[("output", 1), ("pedersen", 1), ("range_check", 0)])
         );
+    }
+
+    #[test]
+    fn execution_resources_mul() {
+        let resources = execution_resources(10, 2, &[("output", 0), ("pedersen", 3)]);
         assert_eq!(
-            cairo_runner.run_from_entrypoint(
-                entrypoint,
-                vec![&mayberelocatable!(0), &mayberelocatable!(1)],
-                true,
-                true,
-                true,
-                &mut vm,
-                &mut hint_processor,
-            ),
-            Err(VirtualMachineError::InvalidArgCount(1, 2)),
+            &resources * 3,
+            execution_resources(30, 6, &[("output", 0), ("pedersen", 9)])
         );
     }
 
-    /// Test that the call to .run_from_entrypoint() with args.count() == 1 when
-    /// typed_args is true succeeds.
     #[test]
-    fn run_from_entrypoint_typed_args() {
+    fn run_from_entrypoint_no_args() {
         let program =
             Program::from_file(Path::new("cairo_programs/not_main.json"), Some("main")).unwrap();
         let mut cairo_runner = cairo_runner!(program);
@@ -3394,52 +5125,80 @@ mod tests {
 
         vm.accessed_addresses = Some(Vec::new());
         cairo_runner.initialize_builtins(&mut vm).unwrap();
-        cairo_runner.initialize_segments(&mut vm, None);
+        cairo_runner
+            .initialize_segments(&mut vm, None, None)
+            .unwrap();
         assert_eq!(
             cairo_runner.run_from_entrypoint(
                 entrypoint,
-                vec![&mayberelocatable!(0)],
-                true,
-                true,
+                &[],
                 true,
+                &mut RunResources::default(),
                 &mut vm,
-                &mut hint_processor,
+                &mut hint_processor
             ),
             Ok(()),
         );
     }
 
-    /// Test that the call to .run_from_entrypoint() when typed_args is false
-    /// succeeds.
-    #[test]
-    fn run_from_entrypoint_untyped_args() {
-        let program =
-            Program::from_file(Path::new("cairo_programs/not_main.json"), Some("main")).unwrap();
+    // Runs the function of the entrypoint_args fixture called `name` with `args`, and returns its
+    // return value
+    fn run_entrypoint_args_function(name: &str, args: &[&CairoArg]) -> MaybeRelocatable {
+        let program = Program::from_fixture("entrypoint_args");
         let mut cairo_runner = cairo_runner!(program);
         let mut vm = vm!();
         let mut hint_processor = BuiltinHintProcessor::new_empty();
 
         let entrypoint = program
             .identifiers
-            .get("__main__.not_main")
+            .get(&format!("__main__.{name}"))
             .unwrap()
             .pc
             .unwrap();
 
         vm.accessed_addresses = Some(Vec::new());
         cairo_runner.initialize_builtins(&mut vm).unwrap();
-        cairo_runner.initialize_segments(&mut vm, None);
-        assert_eq!(
-            cairo_runner.run_from_entrypoint(
+        cairo_runner
+            .initialize_segments(&mut vm, None, None)
+            .unwrap();
+        cairo_runner
+            .run_from_entrypoint(
                 entrypoint,
-                vec![],
-                false,
-                true,
+                args,
                 true,
+                &mut RunResources::default(),
                 &mut vm,
                 &mut hint_processor,
+            )
+            .unwrap();
+        vm.get_return_values(1).unwrap().remove(0)
+    }
+
+    #[test]
+    fn run_from_entrypoint_single_args() {
+        assert_eq!(
+            run_entrypoint_args_function(
+                "sum",
+                &[
+                    &CairoArg::from(mayberelocatable!(3)),
+                    &CairoArg::from(mayberelocatable!(4))
+                ]
             ),
-            Ok(()),
+            mayberelocatable!(7)
+        );
+    }
+
+    #[test]
+    fn run_from_entrypoint_array_arg() {
+        assert_eq!(
+            run_entrypoint_args_function(
+                "mul_first",
+                &[
+                    &CairoArg::from(vec![mayberelocatable!(5), mayberelocatable!(6)]),
+                    &CairoArg::from(mayberelocatable!(3))
+                ]
+            ),
+            mayberelocatable!(15)
         );
     }
 
@@ -3540,6 +5299,29 @@ mod tests {
         assert_eq!(vm.segments.public_memory_offsets.get(&1), Some(&vec![]));
     }
 
+    #[test]
+    fn finalize_segments_program_segment_below_used_size() {
+        let mut program = program!();
+        program.data = vec_data![(1), (2), (3), (4)];
+        let mut cairo_runner = cairo_runner!(program, "plain", true);
+        cairo_runner.program_base = Some(Relocatable::from((0, 0)));
+        cairo_runner.execution_base = Some(Relocatable::from((1, 0)));
+        cairo_runner.run_ended = true;
+        let mut vm = vm!();
+        vm.segments.segment_used_sizes = Some(vec![6, 0]);
+        assert_eq!(
+            cairo_runner.finalize_segments(&mut vm),
+            Err(RunnerError::MemoryError(
+                MemoryError::FinalizedSizeBelowUsedSize {
+                    segment_index: 0,
+                    size: 4,
+                    used_size: 6
+                }
+            ))
+        );
+        assert!(!cairo_runner.segments_finalized);
+    }
+
     #[test]
     fn finalize_segments_run_ended_not_emptyproof_mode_with_execution_public_memory() {
         let mut program = program!();
@@ -3577,7 +5359,7 @@ mod tests {
         );
     }
 
-    /// Test that ensures get_perm_range_check_limits() returns an error when
+    /// Test that ensures get_perm_range_check_limits() returns None when
     /// trace is not enabled.
     #[test]
     fn get_perm_range_check_limits_trace_not_enabled() {
@@ -3586,10 +5368,7 @@ mod tests {
         let cairo_runner = cairo_runner!(program);
         let vm = vm!();
 
-        assert_eq!(
-            cairo_runner.get_perm_range_check_limits(&vm),
-            Err(TraceError::TraceNotEnabled.into()),
-        );
+        assert_eq!(cairo_runner.get_perm_range_check_limits(&vm), Ok(None));
     }
 
     /// Test that ensures get_perm_range_check_limits() returns None when the
@@ -3639,7 +5418,7 @@ mod tests {
 
         assert_eq!(
             cairo_runner.get_perm_range_check_limits(&vm),
-            Ok(Some((-31440, 16383))),
+            Ok(Some((1328, 49151))),
         );
     }
 
@@ -3665,7 +5444,29 @@ mod tests {
 
         assert_eq!(
             cairo_runner.get_perm_range_check_limits(&vm),
-            Ok(Some((-31440, 1328))),
+            Ok(Some((0, 33023))),
+        );
+    }
+
+    /// Test that get_perm_range_check_limits() takes the builtins into account
+    /// when no instruction was executed.
+    #[test]
+    fn get_perm_range_check_limits_only_builtins() {
+        let program = program!();
+
+        let cairo_runner = cairo_runner!(program);
+        let mut vm = vm!();
+
+        vm.trace = Some(vec![]);
+        vm.memory.data = vec![vec![mayberelocatable!(0x80FF_8000_0530u64).into()]];
+        vm.builtin_runners = vec![(
+            "range_check".to_string(),
+            RangeCheckBuiltinRunner::new(12, 5, true).into(),
+        )];
+
+        assert_eq!(
+            cairo_runner.get_perm_range_check_limits(&vm),
+            Ok(Some((0, 33023))),
         );
     }
 
@@ -3722,8 +5523,162 @@ mod tests {
         }]);
 
         assert_eq!(
-            cairo_runner.check_range_check_usage(&vm),
-            Err(MemoryError::InsufficientAllocatedCells.into()),
+            cairo_runner.check_range_check_usage(&vm),
+            Err(MemoryError::MinStepNotReached {
+                builtin: "range_check",
+                min_step: 8
+            }
+            .into()),
+        );
+    }
+
+    #[test]
+    fn check_range_check_usage_small_layout() {
+        let program = Program::from_fixture("range_check_heavy");
+        let mut hint_processor = BuiltinHintProcessor::new_empty();
+        let mut cairo_runner = cairo_runner!(program, "small");
+        let mut vm = vm!(true);
+        let end = cairo_runner.initialize(&mut vm).unwrap();
+        cairo_runner
+            .run_until_pc(
+                end,
+                &mut RunResources::default(),
+                &mut vm,
+                &mut hint_processor,
+            )
+            .unwrap();
+        vm.segments.compute_effective_sizes(&vm.memory);
+        assert_eq!(cairo_runner.check_range_check_usage(&vm), Ok(()));
+
+        // 40 steps: 5 range checked cells of 8 units each, plus 3 units per step,
+        // against 4 units per step minus the 7 reserved to fill the range.
+        cairo_runner.layout.rc_units = 4;
+        assert_eq!(
+            cairo_runner.check_range_check_usage(&vm),
+            Err(MemoryError::InsufficientRangeCheckUnits(160, 153).into()),
+        );
+    }
+
+    #[test]
+    fn range_check_heavy_usage() {
+        let program = Program::from_fixture("range_check_heavy");
+        let mut hint_processor = BuiltinHintProcessor::new_empty();
+        let mut cairo_runner = cairo_runner!(program, "small");
+        let mut vm = vm!(true);
+        let end = cairo_runner.initialize(&mut vm).unwrap();
+        cairo_runner
+            .run_until_pc(
+                end,
+                &mut RunResources::default(),
+                &mut vm,
+                &mut hint_processor,
+            )
+            .unwrap();
+        vm.segments.compute_effective_sizes(&vm.memory);
+
+        // 5 range checked cells in 40 steps, against one instance every 8 steps
+        let report = cairo_runner.get_builtin_usage_report(&vm).unwrap();
+        assert_eq!(
+            report,
+            vec![BuiltinUsage {
+                name: String::from("range_check"),
+                used_instances: 5,
+                allocated_instances: Some(5),
+                utilization: Some(100.0),
+            }]
+        );
+        assert_eq!(
+            serde_json::to_value(&report).unwrap(),
+            serde_json::json!([{
+                "name": "range_check",
+                "used_instances": 5,
+                "allocated_instances": 5,
+                "utilization": 100.0,
+            }])
+        );
+
+        let range_check = vm.get_range_check_builtin().unwrap();
+        let histogram = range_check.get_usage_histogram(&vm.memory).unwrap();
+        let used_cells = range_check.get_used_cells(&vm).unwrap();
+        assert_eq!(histogram.values().sum::<usize>(), used_cells * 8);
+    }
+
+    #[test]
+    fn end_run_proof_mode_no_padding_insufficient_range_check_units() {
+        let program = Program::from_fixture("range_check_heavy");
+        let mut hint_processor = BuiltinHintProcessor::new_empty();
+        let mut cairo_runner = cairo_runner!(program, "small");
+        let mut vm = vm!(true);
+        let end = cairo_runner.initialize(&mut vm).unwrap();
+        cairo_runner
+            .run_until_pc(
+                end,
+                &mut RunResources::default(),
+                &mut vm,
+                &mut hint_processor,
+            )
+            .unwrap();
+
+        cairo_runner.proof_mode = true;
+        cairo_runner.layout.rc_units = 4;
+        assert_eq!(
+            cairo_runner.end_run(true, false, &mut vm, &mut hint_processor),
+            Err(MemoryError::InsufficientRangeCheckUnits(160, 153).into()),
+        );
+    }
+
+    #[test]
+    fn end_run_proof_mode_no_padding_without_diluted_pool() {
+        let program = Program::from_fixture("range_check_heavy");
+        let mut hint_processor = BuiltinHintProcessor::new_empty();
+        let mut cairo_runner = cairo_runner!(program, "small");
+        let mut vm = vm!(true);
+        let end = cairo_runner.initialize(&mut vm).unwrap();
+        cairo_runner
+            .run_until_pc(
+                end,
+                &mut RunResources::default(),
+                &mut vm,
+                &mut hint_processor,
+            )
+            .unwrap();
+
+        cairo_runner.proof_mode = true;
+        // The small layout has no diluted pool, so the check is skipped
+        assert_eq!(cairo_runner.layout.diluted_pool_instance_def, None);
+        assert_eq!(
+            cairo_runner.end_run(true, false, &mut vm, &mut hint_processor),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn end_run_proof_mode_no_padding_insufficient_diluted_units() {
+        let program = Program::from_fixture("range_check_heavy");
+        let mut hint_processor = BuiltinHintProcessor::new_empty();
+        let mut cairo_runner = cairo_runner!(program, "small");
+        let mut vm = vm!(true);
+        let end = cairo_runner.initialize(&mut vm).unwrap();
+        cairo_runner
+            .run_until_pc(
+                end,
+                &mut RunResources::default(),
+                &mut vm,
+                &mut hint_processor,
+            )
+            .unwrap();
+
+        cairo_runner.proof_mode = true;
+        // 40 steps of 16 units each can't cover the 2 ^ 16 diluted values
+        cairo_runner.layout.diluted_pool_instance_def =
+            Some(DilutedPoolInstanceDef::new(16, 4, 16));
+        assert_eq!(
+            cairo_runner.end_run(true, false, &mut vm, &mut hint_processor),
+            Err(MemoryError::InsufficientDilutedUnits {
+                unused: 640,
+                required: 65536
+            }
+            .into()),
         );
     }
 
@@ -3786,7 +5741,10 @@ mod tests {
         assert_eq!(
             cairo_runner.check_used_cells(&vm),
             Err(VirtualMachineError::MemoryError(
-                MemoryError::InsufficientAllocatedCells
+                MemoryError::MinStepNotReached {
+                    builtin: "range_check",
+                    min_step: 8
+                }
             ))
         );
     }
@@ -3801,7 +5759,9 @@ mod tests {
         vm.accessed_addresses = Some(vec![(1, 0).into(), (1, 3).into()]);
         vm.builtin_runners = vec![{
             let mut builtin_runner: BuiltinRunner = OutputBuiltinRunner::new(true).into();
-            builtin_runner.initialize_segments(&mut vm.segments, &mut vm.memory);
+            builtin_runner
+                .initialize_segments(&mut vm.segments, &mut vm.memory)
+                .unwrap();
 
             ("output".to_string(), builtin_runner)
         }];
@@ -3811,7 +5771,10 @@ mod tests {
         assert_eq!(
             cairo_runner.check_used_cells(&vm),
             Err(VirtualMachineError::MemoryError(
-                MemoryError::InsufficientAllocatedCells
+                MemoryError::InsufficientMemoryUnits {
+                    unused: 0,
+                    holes: 10
+                }
             ))
         );
     }
@@ -3827,7 +5790,10 @@ mod tests {
         assert_eq!(
             cairo_runner.check_used_cells(&vm),
             Err(VirtualMachineError::MemoryError(
-                MemoryError::InsufficientAllocatedCells
+                MemoryError::InsufficientDilutedUnits {
+                    unused: 0,
+                    required: 65536
+                }
             ))
         );
     }
@@ -3896,6 +5862,7 @@ mod tests {
         assert_eq!(builtin_runners[4].0, "bitwise");
         assert_eq!(builtin_runners[5].0, "ec_op");
         assert_eq!(builtin_runners[6].0, "keccak");
+        assert_eq!(builtin_runners[7].0, "poseidon");
 
         assert_eq!(
             cairo_runner.program_base,
@@ -3911,7 +5878,42 @@ mod tests {
                 offset: 0,
             })
         );
-        assert_eq!(vm.segments.num_segments, 9);
+        assert_eq!(vm.segments.num_segments, 10);
+    }
+
+    #[test]
+    fn initialize_function_runner_then_run_from_entrypoint() {
+        let mut program = Program::from_fixture("entrypoint_args");
+        program.main = None;
+        let entrypoint = program.identifiers.get("__main__.sum").unwrap().pc.unwrap();
+        let mut cairo_runner = cairo_runner!(program);
+        let mut vm = vm!();
+        let mut hint_processor = BuiltinHintProcessor::new_empty();
+
+        cairo_runner.initialize_function_runner(&mut vm).unwrap();
+        assert!(vm
+            .builtin_runners
+            .iter()
+            .all(|(_, builtin)| builtin.included()));
+        // The program is loaded before any entrypoint is set up
+        assert_eq!(vm.memory.data[0].len(), program.data.len());
+
+        cairo_runner
+            .run_from_entrypoint(
+                entrypoint,
+                &[
+                    &CairoArg::from(mayberelocatable!(3)),
+                    &CairoArg::from(mayberelocatable!(4)),
+                ],
+                true,
+                &mut RunResources::default(),
+                &mut vm,
+                &mut hint_processor,
+            )
+            .unwrap();
+        assert_eq!(vm.get_return_values(1).unwrap(), vec![mayberelocatable!(7)]);
+        // run_from_entrypoint added the segments of the return fp and pc
+        assert_eq!(vm.segments.num_segments, 12);
     }
 
     #[test]
@@ -3922,7 +5924,7 @@ mod tests {
         assert_eq!(
             cairo_runner.initialize_builtins(&mut vm),
             Err(RunnerError::NoBuiltinForInstance(
-                HashSet::from([String::from("output")]),
+                BTreeSet::from([String::from("output")]),
                 String::from("plain")
             ))
         );
@@ -3936,7 +5938,7 @@ mod tests {
         assert_eq!(
             cairo_runner.initialize_builtins(&mut vm),
             Err(RunnerError::NoBuiltinForInstance(
-                HashSet::from([String::from("output"), String::from("pedersen")]),
+                BTreeSet::from([String::from("output"), String::from("pedersen")]),
                 String::from("plain")
             ))
         );
@@ -3950,18 +5952,168 @@ mod tests {
         assert_eq!(
             cairo_runner.initialize_builtins(&mut vm),
             Err(RunnerError::NoBuiltinForInstance(
-                HashSet::from([String::from("bitwise")]),
+                BTreeSet::from([String::from("bitwise")]),
                 String::from("small")
             ))
         );
     }
+
+    #[test]
+    fn new_runner_resolves_layouts_by_name() {
+        let program = program!();
+        for name in [
+            "plain",
+            "small",
+            "dex",
+            "perpetual_with_bitwise",
+            "bitwise",
+            "recursive",
+            "starknet",
+            "starknet_with_keccak",
+            "all_cairo",
+            "all_solidity",
+            "all",
+        ] {
+            let cairo_runner = CairoRunner::new(&program, name, false).unwrap();
+            assert_eq!(cairo_runner.layout._name, name);
+        }
+    }
+
+    #[test]
+    fn new_runner_with_invalid_layout_name() {
+        let program = program!();
+        assert_eq!(
+            CairoRunner::new(&program, "starkware", false).err(),
+            Some(RunnerError::InvalidLayoutName(String::from("starkware")))
+        );
+    }
+
+    #[test]
+    fn initialize_builtins_all_cairo_layout() {
+        let program = program![
+            "output",
+            "pedersen",
+            "range_check",
+            "ecdsa",
+            "bitwise",
+            "ec_op",
+            "keccak",
+            "poseidon"
+        ];
+        let cairo_runner = cairo_runner!(program, "all_cairo");
+        let mut vm = vm!();
+        cairo_runner.initialize_builtins(&mut vm).unwrap();
+
+        let names = vm
+            .builtin_runners
+            .iter()
+            .map(|(name, _)| name.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            names,
+            [
+                "output",
+                "pedersen",
+                "range_check",
+                "ecdsa",
+                "bitwise",
+                "ec_op",
+                "keccak",
+                "poseidon"
+            ]
+        );
+    }
+
+    #[test]
+    fn initialize_builtins_keccak_not_in_starknet_layout() {
+        let program = program!["output", "keccak", "poseidon"];
+        let mut vm = vm!();
+        let cairo_runner = cairo_runner!(program, "starknet");
+        assert_eq!(
+            cairo_runner.initialize_builtins(&mut vm),
+            Err(RunnerError::NoBuiltinForInstance(
+                BTreeSet::from([String::from("keccak")]),
+                String::from("starknet")
+            ))
+        );
+        let cairo_runner = cairo_runner!(program, "starknet_with_keccak");
+        assert_eq!(cairo_runner.initialize_builtins(&mut vm), Ok(()));
+    }
+
+    #[test]
+    fn initialize_builtins_poseidon_not_in_all_solidity_layout() {
+        let program = program!["output", "poseidon"];
+        let mut vm = vm!();
+        let cairo_runner = cairo_runner!(program, "all_solidity");
+        assert_eq!(
+            cairo_runner.initialize_builtins(&mut vm),
+            Err(RunnerError::NoBuiltinForInstance(
+                BTreeSet::from([String::from("poseidon")]),
+                String::from("all_solidity")
+            ))
+        );
+    }
+
+    #[test]
+    fn initialize_builtins_missing_builtins_error_is_deterministic() {
+        let program = program!["output", "pedersen", "range_check", "ecdsa", "bitwise"];
+        let errors: HashSet<String> = (0..50)
+            .map(|_| {
+                let mut vm = vm!();
+                let cairo_runner = cairo_runner!(program, "plain");
+                cairo_runner
+                    .initialize_builtins(&mut vm)
+                    .unwrap_err()
+                    .to_string()
+            })
+            .collect();
+        assert_eq!(
+            errors,
+            HashSet::from([String::from(
+                r#"Builtin(s) {"bitwise", "ecdsa", "output", "pedersen", "range_check"} not present in layout plain"#
+            )])
+        );
+    }
+
+    #[test]
+    fn get_hint_data_dictionary_reports_first_failing_hint() {
+        let hint_params = |code: &str| HintParams {
+            code: code.to_string(),
+            accessible_scopes: Vec::new(),
+            flow_tracking_data: FlowTrackingData {
+                ap_tracking: ApTracking::new(),
+                reference_ids: HashMap::new(),
+            },
+        };
+        let program = program!(
+            hints = (0..20)
+                .map(|pc| (pc, vec![hint_params(&format!("unknown_hint_{pc}"))]))
+                .collect(),
+        );
+        // Unlike the builtin hint processor, the Cairo 1 one fails to compile unknown hints
+        let mut hint_processor = Cairo1HintProcessor::new(&[]);
+        for _ in 0..50 {
+            let mut cairo_runner = cairo_runner!(program);
+            cairo_runner.program_base = Some(Relocatable::from((0, 0)));
+            assert_eq!(
+                cairo_runner
+                    .get_hint_data_dictionary(&HashMap::new(), &mut hint_processor)
+                    .err()
+                    .map(|error| error.to_string()),
+                Some(
+                    VirtualMachineError::CompileHintFail(String::from("unknown_hint_0"))
+                        .to_string()
+                )
+            );
+        }
+    }
     #[test]
     fn initialize_main_entrypoint_proof_mode_empty_program() {
         let program = program!(start = Some(0), end = Some(0), main = Some(8),);
         let mut runner = cairo_runner!(program);
         runner.proof_mode = true;
         let mut vm = vm!();
-        runner.initialize_segments(&mut vm, None);
+        runner.initialize_segments(&mut vm, None, None).unwrap();
         assert_eq!(runner.execution_base, Some(Relocatable::from((1, 0))));
         assert_eq!(runner.program_base, Some(Relocatable::from((0, 0))));
         assert_eq!(
@@ -3985,7 +6137,7 @@ mod tests {
         runner.proof_mode = true;
         let mut vm = vm!();
         runner.initialize_builtins(&mut vm).unwrap();
-        runner.initialize_segments(&mut vm, None);
+        runner.initialize_segments(&mut vm, None, None).unwrap();
         assert_eq!(runner.execution_base, Some(Relocatable::from((1, 0))));
         assert_eq!(runner.program_base, Some(Relocatable::from((0, 0))));
         assert_eq!(
@@ -3997,6 +6149,83 @@ mod tests {
         assert_eq!(runner.execution_public_memory, Some(vec![0, 1, 2, 3]));
     }
 
+    /// The initial memory of check_range in proof mode with the small layout, as laid out by
+    /// cairo-lang: the builtins of the layout get their segments after the program and
+    /// execution ones, whether the program uses them or not, and the execution segment starts
+    /// with the dummy fp and pc followed by the base of range_check, the only program builtin.
+    #[test]
+    fn initialize_proof_mode_initial_memory() {
+        let program = Program::from_fixture("check_range_proof_mode");
+        let mut cairo_runner = cairo_runner!(program, "small", true);
+        let mut vm = vm!(true);
+
+        let end = cairo_runner.initialize(&mut vm).unwrap();
+        assert_eq!(end, Relocatable::from((0, 4)));
+        assert_eq!(
+            vm.builtin_runners
+                .iter()
+                .map(|(name, builtin)| (name.as_str(), builtin.base()))
+                .collect::<Vec<_>>(),
+            vec![
+                ("output", 2),
+                ("pedersen", 3),
+                ("range_check", 4),
+                ("ecdsa", 5)
+            ]
+        );
+        assert_eq!(
+            vm.memory.data[1],
+            vec![
+                Some(MaybeRelocatable::from((1, 2))),
+                Some(MaybeRelocatable::from(0)),
+                Some(MaybeRelocatable::from((4, 0))),
+            ]
+        );
+        assert_eq!(cairo_runner.execution_public_memory, Some(vec![0, 1, 2]));
+        assert_eq!(vm.run_context.pc, Relocatable::from((0, 0)));
+        assert_eq!(vm.run_context.ap, 2);
+        assert_eq!(vm.run_context.fp, 2);
+    }
+
+    /// The run of check_range in proof mode is padded to a power of two steps by looping on
+    /// __end__, and its relocated memory is the one cairo-lang produces: the program at 1, the
+    /// execution segment at 21, and the two range checked cells at 33, after the empty output
+    /// and pedersen segments.
+    #[test]
+    fn proof_mode_run_relocated_memory() {
+        let (mut cairo_runner, mut vm) = run_check_range_proof_mode();
+        let mut hint_processor = BuiltinHintProcessor::new_empty();
+        assert_eq!(vm.current_step, 12);
+        cairo_runner
+            .end_run(false, false, &mut vm, &mut hint_processor)
+            .unwrap();
+        assert!(vm.current_step.is_power_of_two());
+        cairo_runner.relocate(&mut vm, None).unwrap();
+
+        let relocated_trace = cairo_runner.relocated_trace.as_ref().unwrap();
+        assert_eq!(relocated_trace.len(), vm.current_step);
+        // After main returns, the padding steps all run the __end__ loop
+        assert!(relocated_trace[12..].iter().all(|entry| entry.pc == 5));
+
+        let program_data = cairo_runner
+            .program
+            .data
+            .iter()
+            .map(|value| Some(value.get_int_ref().unwrap().clone()));
+        let execution = [23, 0, 33, 23, 5, 33, 7, 26, 20, u64::MAX, u64::MAX - 7, 35]
+            .into_iter()
+            .map(|value| Some(Felt::new(value)));
+        let range_check = [7, u64::MAX - 7]
+            .into_iter()
+            .map(|value| Some(Felt::new(value)));
+        let expected_memory = std::iter::once(None)
+            .chain(program_data)
+            .chain(execution)
+            .chain(range_check)
+            .collect::<Vec<_>>();
+        assert_eq!(cairo_runner.relocated_memory, expected_memory);
+    }
+
     #[test]
     fn can_get_the_runner_program_builtins() {
         let program = program!(
@@ -4108,10 +6337,10 @@ mod tests {
         cairo_runner.execution_base = Some(Relocatable::from((1, 0)));
         cairo_runner.run_ended = true;
         cairo_runner.segments_finalized = false;
-        let vm = vm!();
+        let mut vm = vm!();
         //Check values written by first call to segments.finalize()
 
-        assert_eq!(cairo_runner.read_return_values(&vm), Ok(()));
+        assert_eq!(cairo_runner.read_return_values(&mut vm), Ok(()));
         assert_eq!(
             cairo_runner
                 .execution_public_memory
@@ -4129,9 +6358,9 @@ mod tests {
         cairo_runner.program_base = Some(Relocatable::from((0, 0)));
         cairo_runner.execution_base = Some(Relocatable::from((1, 0)));
         cairo_runner.run_ended = false;
-        let vm = vm!();
+        let mut vm = vm!();
         assert_eq!(
-            cairo_runner.read_return_values(&vm),
+            cairo_runner.read_return_values(&mut vm),
             Err(RunnerError::FinalizeNoEndRun)
         );
     }
@@ -4146,9 +6375,9 @@ mod tests {
         cairo_runner.execution_base = Some(Relocatable::from((1, 0)));
         cairo_runner.run_ended = true;
         cairo_runner.segments_finalized = true;
-        let vm = vm!();
+        let mut vm = vm!();
         assert_eq!(
-            cairo_runner.read_return_values(&vm),
+            cairo_runner.read_return_values(&mut vm),
             Err(RunnerError::FailedAddingReturnValues)
         );
     }
@@ -4161,7 +6390,7 @@ mod tests {
         let mut vm = vm!();
 
         let num_builtins = vm.builtin_runners.len();
-        cairo_runner.add_additional_hash_builtin(&mut vm);
+        cairo_runner.add_additional_hash_builtin(&mut vm).unwrap();
         assert_eq!(vm.builtin_runners.len(), num_builtins + 1);
 
         let (key, value) = vm
@@ -4173,7 +6402,7 @@ mod tests {
             BuiltinRunner::Hash(builtin) => {
                 assert_eq!(builtin.base(), 0);
                 assert_eq!(builtin.ratio(), 32);
-                assert!(builtin._included);
+                assert!(builtin.included);
             }
             _ => unreachable!(),
         }
@@ -4188,8 +6417,8 @@ mod tests {
         let mut vm = vm!();
 
         let num_builtins = vm.builtin_runners.len();
-        cairo_runner.add_additional_hash_builtin(&mut vm);
-        cairo_runner.add_additional_hash_builtin(&mut vm);
+        cairo_runner.add_additional_hash_builtin(&mut vm).unwrap();
+        cairo_runner.add_additional_hash_builtin(&mut vm).unwrap();
         assert_eq!(vm.builtin_runners.len(), num_builtins + 1);
 
         let (key, value) = vm
@@ -4201,7 +6430,7 @@ mod tests {
             BuiltinRunner::Hash(builtin) => {
                 assert_eq!(builtin.base(), 1);
                 assert_eq!(builtin.ratio(), 32);
-                assert!(builtin._included);
+                assert!(builtin.included);
             }
             _ => unreachable!(),
         }
@@ -4225,14 +6454,18 @@ mod tests {
 
         vm.accessed_addresses = Some(Vec::new());
         cairo_runner.initialize_builtins(&mut vm).unwrap();
-        cairo_runner.initialize_segments(&mut vm, None);
+        cairo_runner
+            .initialize_segments(&mut vm, None, None)
+            .unwrap();
         assert_eq!(
             cairo_runner.run_from_entrypoint(
                 main_entrypoint,
-                vec![&mayberelocatable!(2), &MaybeRelocatable::from((2, 0))], //range_check_ptr
-                false,
-                true,
+                &[
+                    &mayberelocatable!(2).into(),
+                    &MaybeRelocatable::from((2, 0)).into() //range_check_ptr
+                ],
                 true,
+                &mut RunResources::default(),
                 &mut vm,
                 &mut hint_processor,
             ),
@@ -4245,7 +6478,9 @@ mod tests {
 
         new_vm.accessed_addresses = Some(Vec::new());
         new_cairo_runner.initialize_builtins(&mut new_vm).unwrap();
-        new_cairo_runner.initialize_segments(&mut new_vm, None);
+        new_cairo_runner
+            .initialize_segments(&mut new_vm, None, None)
+            .unwrap();
 
         let fib_entrypoint = program
             .identifiers
@@ -4257,14 +6492,313 @@ mod tests {
         assert_eq!(
             new_cairo_runner.run_from_entrypoint(
                 fib_entrypoint,
-                vec![&mayberelocatable!(2), &MaybeRelocatable::from((2, 0))],
-                false,
-                true,
+                &[
+                    &mayberelocatable!(2).into(),
+                    &MaybeRelocatable::from((2, 0)).into()
+                ],
                 true,
+                &mut RunResources::default(),
                 &mut new_vm,
                 &mut hint_processor,
             ),
             Ok(()),
         );
     }
+
+    #[test]
+    fn dump_pedersen_and_bitwise_builtins() {
+        let program = program!(
+            builtins = vec![String::from("pedersen"), String::from("bitwise")],
+            main = Some(0),
+        );
+        let mut cairo_runner = cairo_runner!(program);
+        let mut vm = vm!();
+        cairo_runner.initialize(&mut vm).unwrap();
+        let pedersen_base = vm.builtin_runners[0].1.base();
+        let bitwise_base = vm.builtin_runners[1].1.base();
+
+        // The outputs are written as the vm would after deducing them, except for the xor of the
+        // bitwise instance, which holds a wrong value
+        vm.insert_value(&(pedersen_base, 0).into(), Felt::new(32))
+            .unwrap();
+        vm.insert_value(&(pedersen_base, 1).into(), Felt::new(72))
+            .unwrap();
+        let hash = vm.builtin_runners[0]
+            .1
+            .deduce_memory_cell(&(pedersen_base, 2).into(), &vm.memory)
+            .unwrap()
+            .unwrap();
+        vm.insert_value(&(pedersen_base, 2).into(), hash).unwrap();
+        vm.insert_value(&(pedersen_base, 3).into(), Felt::new(5))
+            .unwrap();
+        // Written into the memory directly, as the vm checks the values written into deducible
+        // cells
+        for (offset, value) in [(0, 12), (1, 10), (2, 8), (3, 7)] {
+            vm.memory
+                .insert_value(&(bitwise_base, offset).into(), Felt::new(value))
+                .unwrap();
+        }
+
+        let mut dump = Vec::new();
+        cairo_runner
+            .dump_builtin(&vm, "pedersen", &mut dump)
+            .unwrap();
+        cairo_runner
+            .dump_builtin(&vm, "bitwise", &mut dump)
+            .unwrap();
+        assert_eq!(
+            String::from_utf8(dump).unwrap(),
+            "pedersen: 2 instance(s)\n\
+             0: hash(32, 72) = 3270867057177188607814717243084834301278723532952411121381966378910183338911*\n\
+             1: hash(5, _) = _ (incomplete)\n\
+             bitwise: 1 instance(s)\n\
+             0: x = 12, y = 10, x & y = 8*, x ^ y = 7 (deduced 6), x | y = _ (deduced 14) (incomplete)\n"
+        );
+        assert_eq!(
+            cairo_runner.dump_builtin(&vm, "ec_op", &mut Vec::new()),
+            Err(RunnerError::MissingBuiltin(String::from("ec_op")))
+        );
+    }
+
+    #[test]
+    fn end_run_relocates_builtin_in_temporary_segment() {
+        let program = program!(builtins = vec![String::from("pedersen")], main = Some(0),);
+        let mut cairo_runner = cairo_runner!(program);
+        let mut vm = vm!();
+        cairo_runner.initialize(&mut vm).unwrap();
+
+        // Run the pedersen instance in a temporary segment, as a loader would
+        let temp_base = vm.add_temporary_segment();
+        vm.builtin_runners[0]
+            .1
+            .relocate_base(temp_base.segment_index);
+        vm.insert_value(&(-1, 0).into(), Felt::new(32)).unwrap();
+        vm.insert_value(&(-1, 1).into(), Felt::new(72)).unwrap();
+        let hash = vm.builtin_runners[0]
+            .1
+            .deduce_memory_cell(&(-1, 2).into(), &vm.memory)
+            .unwrap()
+            .unwrap();
+        vm.insert_value(&(-1, 2).into(), hash.clone()).unwrap();
+        vm.builtin_runners[0]
+            .1
+            .set_stop_ptr((-1, 3).into())
+            .unwrap();
+
+        let dst = vm.add_memory_segment();
+        vm.add_relocation_rule(temp_base, dst).unwrap();
+        let mut hint_processor = BuiltinHintProcessor::new_empty();
+        cairo_runner
+            .end_run(true, false, &mut vm, &mut hint_processor)
+            .unwrap();
+
+        assert_eq!(vm.builtin_runners[0].1.base(), dst.segment_index);
+        assert_eq!(
+            vm.memory
+                .get(&MaybeRelocatable::from((dst.segment_index, 2)))
+                .unwrap()
+                .unwrap()
+                .as_ref(),
+            &hash
+        );
+        assert_eq!(verify_secure_runner(&cairo_runner, true, &mut vm), Ok(()));
+    }
+
+    #[test]
+    fn end_run_builtin_in_temporary_segment_relocated_with_offset() {
+        let program = program!(builtins = vec![String::from("pedersen")], main = Some(0),);
+        let mut cairo_runner = cairo_runner!(program);
+        let mut vm = vm!();
+        cairo_runner.initialize(&mut vm).unwrap();
+
+        let temp_base = vm.add_temporary_segment();
+        vm.builtin_runners[0]
+            .1
+            .relocate_base(temp_base.segment_index);
+        vm.add_relocation_rule(temp_base, (1, 3).into()).unwrap();
+        let mut hint_processor = BuiltinHintProcessor::new_empty();
+        assert_eq!(
+            cairo_runner.end_run(true, false, &mut vm, &mut hint_processor),
+            Err(VirtualMachineError::MemoryError(
+                MemoryError::BuiltinSegmentRelocatedWithOffset(-1, (1, 3).into())
+            ))
+        );
+    }
+
+    #[test]
+    fn load_child_program_after_program() {
+        let program = program!(
+            data = vec_data!((5207990763031199744_i64), (2), (2345108766317314046_i64)),
+            main = Some(0),
+        );
+        let child = program!(
+            data = vec_data!((2345108766317314046_i64)),
+            identifiers = HashMap::from([(
+                String::from("__main__.main"),
+                Identifier {
+                    pc: Some(0),
+                    type_: Some(String::from("function")),
+                    value: None,
+                    full_name: None,
+                    members: None,
+                },
+            )]),
+        );
+        let mut cairo_runner = cairo_runner!(program);
+        let mut vm = vm!();
+        cairo_runner.initialize(&mut vm).unwrap();
+
+        let loaded = cairo_runner
+            .load_child_program(&child, None, &mut vm)
+            .unwrap();
+        assert_eq!(loaded.base, (0, 3).into());
+        assert_eq!(
+            loaded.entrypoints,
+            HashMap::from([(String::from("__main__.main"), 3)])
+        );
+        assert_eq!(cairo_runner.get_child_programs(), &[loaded]);
+        assert_eq!(cairo_runner.get_program_segment_size(), 4);
+        check_memory!(vm.memory, ((0, 3), 2345108766317314046_i64));
+    }
+
+    #[test]
+    fn load_child_program_overlapping_program() {
+        let program = program!(
+            data = vec_data!((5207990763031199744_i64), (2), (2345108766317314046_i64)),
+            main = Some(0),
+        );
+        let child = program!(data = vec_data!((2345108766317314046_i64)),);
+        let mut cairo_runner = cairo_runner!(program);
+        let mut vm = vm!();
+        cairo_runner.initialize(&mut vm).unwrap();
+
+        assert_eq!(
+            cairo_runner.load_child_program(&child, Some((0, 2).into()), &mut vm),
+            Err(RunnerError::ChildProgramOverlap((0, 2).into()))
+        );
+        cairo_runner
+            .load_child_program(&child, Some((0, 5).into()), &mut vm)
+            .unwrap();
+        assert_eq!(
+            cairo_runner.load_child_program(&child, Some((0, 5).into()), &mut vm),
+            Err(RunnerError::ChildProgramOverlap((0, 5).into()))
+        );
+        assert_eq!(cairo_runner.get_program_segment_size(), 6);
+    }
+
+    #[test]
+    fn load_child_program_outside_program_segment() {
+        let program = program!(main = Some(0),);
+        let mut cairo_runner = cairo_runner!(program);
+        let mut vm = vm!();
+        cairo_runner.initialize(&mut vm).unwrap();
+
+        assert_eq!(
+            cairo_runner.load_child_program(&program, Some((1, 0).into()), &mut vm),
+            Err(RunnerError::ChildProgramOutsideProgramSegment(
+                (1, 0).into()
+            ))
+        );
+    }
+
+    #[cfg(feature = "tracing")]
+    #[test]
+    fn run_emits_tracing_events() {
+        use std::sync::{Arc, Mutex};
+        use tracing::{
+            field::{Field, Visit},
+            span, Event, Metadata, Subscriber,
+        };
+
+        // Records the name of each entered span and the message of each event
+        #[derive(Default)]
+        struct Collector(Arc<Mutex<Vec<String>>>, Mutex<Vec<&'static str>>);
+
+        struct Message(Option<String>);
+
+        impl Visit for Message {
+            fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+                if field.name() == "message" {
+                    self.0 = Some(format!("{value:?}"));
+                }
+            }
+        }
+
+        impl Subscriber for Collector {
+            fn enabled(&self, _: &Metadata<'_>) -> bool {
+                true
+            }
+            fn new_span(&self, span: &span::Attributes<'_>) -> span::Id {
+                let mut names = self.1.lock().unwrap();
+                names.push(span.metadata().name());
+                span::Id::from_u64(names.len() as u64)
+            }
+            fn record(&self, _: &span::Id, _: &span::Record<'_>) {}
+            fn record_follows_from(&self, _: &span::Id, _: &span::Id) {}
+            fn event(&self, event: &Event<'_>) {
+                let mut message = Message(None);
+                event.record(&mut message);
+                self.0.lock().unwrap().extend(message.0);
+            }
+            fn enter(&self, span: &span::Id) {
+                let name = self.1.lock().unwrap()[span.into_u64() as usize - 1];
+                self.0.lock().unwrap().push(format!("enter {name}"));
+            }
+            fn exit(&self, _: &span::Id) {}
+        }
+
+        // [ap] = segments.add(); ap += 1; ret
+        let program = program!(
+            data = vec![
+                mayberelocatable!(0x40780017fff7fff),
+                mayberelocatable!(1),
+                mayberelocatable!(0x208b7fff7fff7ffe),
+            ],
+            hints = HashMap::from([(
+                0,
+                vec![HintParams {
+                    code: String::from("memory[ap] = segments.add()"),
+                    accessible_scopes: Vec::new(),
+                    flow_tracking_data: FlowTrackingData {
+                        ap_tracking: ApTracking::new(),
+                        reference_ids: HashMap::new(),
+                    },
+                }],
+            )]),
+            main = Some(0),
+        );
+        let mut hint_processor = BuiltinHintProcessor::new_empty();
+        let mut cairo_runner = cairo_runner!(program);
+        let mut vm = vm!();
+        let end = cairo_runner.initialize(&mut vm).unwrap();
+
+        let collector = Collector::default();
+        let events = collector.0.clone();
+        tracing::subscriber::with_default(collector, || {
+            cairo_runner
+                .run_until_pc(
+                    end,
+                    &mut RunResources::default(),
+                    &mut vm,
+                    &mut hint_processor,
+                )
+                .unwrap();
+            cairo_runner
+                .end_run(false, false, &mut vm, &mut hint_processor)
+                .unwrap();
+        });
+        assert_eq!(
+            *events.lock().unwrap(),
+            vec![
+                "enter run_until_pc",
+                "step",
+                "segment added",
+                "hint executed",
+                "step",
+                "enter end_run",
+                "temporary segments dropped",
+                "vm run ended",
+            ]
+        );
+    }
 }
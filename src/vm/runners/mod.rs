@@ -1,2 +1,4 @@
+pub mod air_private_input;
 pub mod builtin_runner;
+pub mod cairo_pie;
 pub mod cairo_runner;
@@ -16,7 +16,9 @@ use std::{collections::HashMap, mem::swap};
 ///   - All accesses to the builtin segments must be within the range defined by
 ///     the builtins themselves.
 ///   - There mustn't be accesses to the program segment outside the program
-///     data range.
+///     data range, which includes the child programs loaded by the runner.
+///   - No segment may be written beyond its finalized size, or the maximum
+///     size set through `CairoRunner::set_max_segment_size`.
 ///
 /// Note: Each builtin is responsible for checking its own segments' data.
 pub fn verify_secure_runner(
@@ -51,19 +53,30 @@ pub fn verify_secure_runner(
                     .map(|val| (Relocatable::from((idx as _, off)), val))
             })
         });
+    let program_segment_size = runner.get_program_segment_size();
     for (addr, value) in memory_iter {
         // Check builtin segment bounds.
         if let Some((_, seg_info)) = builtin_segment_by_index.get(&addr.segment_index) {
             if addr.offset >= seg_info.size {
-                return Err(RunnerError::FailedMemoryGet(MemoryError::NumOutOfBounds).into());
+                return Err(
+                    RunnerError::FailedMemoryGet(MemoryError::AddressOutOfSegmentBounds {
+                        addr,
+                        size: seg_info.size,
+                    })
+                    .into(),
+                );
             }
         }
 
         // Check program segment bounds.
-        if addr.segment_index == program_base.segment_index
-            && addr.offset >= runner.program.data.len()
-        {
-            return Err(RunnerError::FailedMemoryGet(MemoryError::NumOutOfBounds).into());
+        if addr.segment_index == program_base.segment_index && addr.offset >= program_segment_size {
+            return Err(
+                RunnerError::FailedMemoryGet(MemoryError::AddressOutOfSegmentBounds {
+                    addr,
+                    size: program_segment_size,
+                })
+                .into(),
+            );
         }
 
         // Check value validity (when relocatable, that the segment exists and
@@ -80,6 +93,30 @@ pub fn verify_secure_runner(
         }
     }
 
+    // Check the segment sizes, naming the pc which wrote the furthest cell.
+    for (index, segment) in vm.memory.data.iter().enumerate() {
+        let max_size = match vm
+            .segments
+            .segment_sizes
+            .get(&index)
+            .or_else(|| runner.max_segment_sizes.get(&index))
+        {
+            Some(max_size) => *max_size,
+            None => continue,
+        };
+        if segment.len() > max_size {
+            return Err(
+                RunnerError::FailedMemoryGet(MemoryError::SegmentSizeExceeded {
+                    segment_index: index,
+                    offset: segment.len() - 1,
+                    size: max_size,
+                    pc: vm.memory.get_max_offset_writer(index as isize),
+                })
+                .into(),
+            );
+        }
+    }
+
     // This swap is needed to avoid double mutable borrows.
     let mut tmp = Vec::new();
     swap(&mut tmp, &mut vm.builtin_runners);
@@ -94,9 +131,23 @@ pub fn verify_secure_runner(
 #[cfg(test)]
 mod test {
     use super::*;
-    use crate::{relocatable, types::program::Program, utils::test_utils::*};
+    use crate::vm::runners::cairo_runner::RunResources;
+    use crate::{
+        hint_processor::{
+            builtin_hint_processor::builtin_hint_processor_definition::{
+                BuiltinHintProcessor, HintFunc,
+            },
+            hint_processor_definition::HintReference,
+        },
+        relocatable,
+        serde::deserialize_program::{ApTracking, FlowTrackingData, HintParams},
+        types::{exec_scope::ExecutionScopes, program::Program, relocatable::MaybeRelocatable},
+        utils::test_utils::*,
+        vm::errors::hint_errors::HintError,
+    };
     use felt::Felt;
-    use num_traits::Zero;
+    use num_traits::{One, Zero};
+    use std::rc::Rc;
 
     #[test]
     fn verify_secure_runner_without_program_base() {
@@ -137,7 +188,13 @@ mod test {
 
         assert_eq!(
             verify_secure_runner(&runner, true, &mut vm),
-            Err(RunnerError::FailedMemoryGet(MemoryError::NumOutOfBounds).into())
+            Err(
+                RunnerError::FailedMemoryGet(MemoryError::AddressOutOfSegmentBounds {
+                    addr: Relocatable::from((0, 0)),
+                    size: 0,
+                })
+                .into()
+            )
         );
     }
 
@@ -168,4 +225,97 @@ mod test {
 
         assert_eq!(verify_secure_runner(&runner, true, &mut vm), Ok(()));
     }
+
+    // Writes far beyond the end of the execution segment, as an off-by-large pointer would
+    fn write_far_from_ap(
+        vm: &mut VirtualMachine,
+        _exec_scopes: &mut ExecutionScopes,
+        _ids_data: &HashMap<String, HintReference>,
+        _ap_tracking: &ApTracking,
+        _constants: &HashMap<String, Felt>,
+    ) -> Result<(), HintError> {
+        vm.insert_value(&(vm.get_ap() + 10_000_000), Felt::one())
+            .map_err(HintError::Internal)
+    }
+
+    #[test]
+    fn verify_secure_runner_segment_size_exceeded_by_hint() {
+        // ret, with a hint writing to ap + 10_000_000
+        let program = program!(
+            data = vec![mayberelocatable!(0x208b7fff7fff7ffe)],
+            hints = HashMap::from([(
+                0,
+                vec![HintParams {
+                    code: String::from("memory[ap + 10_000_000] = 1"),
+                    accessible_scopes: Vec::new(),
+                    flow_tracking_data: FlowTrackingData {
+                        ap_tracking: ApTracking::new(),
+                        reference_ids: HashMap::new(),
+                    },
+                }],
+            )]),
+            main = Some(0),
+        );
+        let mut hint_processor = BuiltinHintProcessor::new_empty();
+        hint_processor.add_hint(
+            String::from("memory[ap + 10_000_000] = 1"),
+            Rc::new(HintFunc(Box::new(write_far_from_ap))),
+        );
+        let mut runner = cairo_runner!(program);
+        let mut vm = vm!();
+        let end = runner.initialize(&mut vm).unwrap();
+        runner
+            .run_until_pc(
+                end,
+                &mut RunResources::default(),
+                &mut vm,
+                &mut hint_processor,
+            )
+            .unwrap();
+        runner
+            .end_run(false, false, &mut vm, &mut hint_processor)
+            .unwrap();
+        runner.set_max_segment_size(1, 1000);
+
+        assert_eq!(
+            verify_secure_runner(&runner, true, &mut vm),
+            Err(
+                RunnerError::FailedMemoryGet(MemoryError::SegmentSizeExceeded {
+                    segment_index: 1,
+                    offset: 10_000_002,
+                    size: 1000,
+                    pc: Some(relocatable!(0, 0)),
+                })
+                .into()
+            )
+        );
+    }
+
+    #[test]
+    fn verify_secure_runner_finalized_segment_size_exceeded() {
+        let program = program!(main = Some(0),);
+        let mut runner = cairo_runner!(program);
+        let mut vm = vm!();
+        runner.initialize(&mut vm).unwrap();
+        vm.insert_value(&relocatable!(1, 4), Felt::one()).unwrap();
+        vm.segments.compute_effective_sizes(&vm.memory);
+        vm.segments.segment_sizes.insert(1, 3);
+        // The finalized size takes precedence over the maximum size
+        runner.set_max_segment_size(1, 1000);
+
+        let error = verify_secure_runner(&runner, true, &mut vm).unwrap_err();
+        assert_eq!(
+            error,
+            RunnerError::FailedMemoryGet(MemoryError::SegmentSizeExceeded {
+                segment_index: 1,
+                offset: 4,
+                size: 3,
+                pc: None,
+            })
+            .into()
+        );
+        assert!(error.to_string().contains(
+            "Segment 1 is limited to 3 cells, but offset 4 was written outside of a step"
+        ));
+    }
 }
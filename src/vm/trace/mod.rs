@@ -9,6 +9,8 @@ use std::borrow::Cow;
 
 pub mod trace_entry;
 
+const OFFSET_BIAS: isize = 1 << 15;
+
 /// Return the minimum and maximum values in the perm_range_check component.
 pub fn get_perm_range_check_limits(
     trace: &[TraceEntry],
@@ -35,9 +37,11 @@ pub fn get_perm_range_check_limits(
                 .transpose()?;
 
             let decoded_instruction = decode_instruction(instruction, immediate.as_ref())?;
-            let off0 = decoded_instruction.off0;
-            let off1 = decoded_instruction.off1;
-            let off2 = decoded_instruction.off2;
+            // Offsets are range checked in their biased (16-bit unsigned) representation,
+            // the same one the range check builtin values are split into
+            let off0 = decoded_instruction.off0 + OFFSET_BIAS;
+            let off1 = decoded_instruction.off1 + OFFSET_BIAS;
+            let off2 = decoded_instruction.off2 + OFFSET_BIAS;
 
             let min_value = off0.min(off1).min(off2);
             let max_value = off0.max(off1).max(off2);
@@ -77,7 +81,7 @@ mod test {
         let memory = memory![((0, 0), 0xFFFF_8000_0000_u64)];
         assert_eq!(
             get_perm_range_check_limits(trace, &memory),
-            Ok(Some((-32768, 32767))),
+            Ok(Some((0, 65535))),
         );
     }
 
@@ -110,7 +114,7 @@ mod test {
 
         assert_eq!(
             get_perm_range_check_limits(trace, &memory),
-            Ok(Some((-31440, 16383))),
+            Ok(Some((1328, 49151))),
         );
     }
 }
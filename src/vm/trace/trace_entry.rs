@@ -1,16 +1,35 @@
 use crate::vm::errors::trace_errors::TraceError;
-use crate::{types::relocatable::Relocatable, vm::errors::memory_errors::MemoryError};
+use crate::{
+    types::{
+        instruction::Instruction,
+        relocatable::{MaybeRelocatable, Relocatable},
+    },
+    vm::errors::memory_errors::MemoryError,
+};
 use serde::{Deserialize, Serialize};
 
 ///A trace entry for every instruction that was executed.
 ///Holds the register values before the instruction was executed.
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct TraceEntry {
     pub pc: Relocatable,
     pub ap: Relocatable,
     pub fp: Relocatable,
 }
 
+///A trace entry along with the instruction it executed and the values of its operands, see
+///`VirtualMachine::iter_trace_with_instructions`.
+#[derive(Debug, PartialEq, Eq)]
+pub struct ExecutedStep {
+    pub registers: TraceEntry,
+    pub instruction: Instruction,
+    pub dst: MaybeRelocatable,
+    pub op0: MaybeRelocatable,
+    pub op1: MaybeRelocatable,
+    ///None when the instruction's res is unconstrained
+    pub res: Option<MaybeRelocatable>,
+}
+
 #[derive(Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub struct RelocatedTraceEntry {
     pub ap: usize,
@@ -0,0 +1,459 @@
+use crate::{
+    types::{
+        instruction::{ApUpdate, FpUpdate, Instruction, Op1Addr, Opcode, PcUpdate, Register, Res},
+        program::Program,
+        relocatable::{MaybeRelocatable, Relocatable},
+    },
+    vm::{
+        decoding::decoder::decode_instruction, errors::verification_errors::VerificationError,
+        runners::cairo_runner::CairoRunner, trace::trace_entry::RelocatedTraceEntry,
+        vm_core::VirtualMachine,
+    },
+};
+use felt::{Felt, NewFelt};
+use num_traits::{ToPrimitive, Zero};
+
+// The relocated address of the program segment
+const PROGRAM_BASE: usize = 1;
+
+/// Checks that a relocated memory and trace are a valid execution of `program`, without running
+/// it again. As the memory is given, the hints aren't needed.
+///
+/// Checks include:
+///   - The program data is loaded at the start of the memory, and every pc in the trace points
+///     to an instruction of the program.
+///   - Every instruction holds: its operands are in the memory, its assertions are satisfied,
+///     and the registers of the next trace entry are the ones it computes.
+///   - The builtin segments contain valid instances. Their bounds are read from the pointers
+///     `main` receives and returns, so they are only checked when the run ended by returning
+///     from `main` (i.e. not in proof mode).
+pub fn verify_run(
+    program: &Program,
+    memory: &[Option<Felt>],
+    trace: &[RelocatedTraceEntry],
+) -> Result<(), VerificationError> {
+    let (first, last) = match (trace.first(), trace.last()) {
+        (Some(first), Some(last)) => (first, last),
+        _ => return Err(VerificationError::EmptyTrace),
+    };
+    verify_program_data(program, memory)?;
+
+    let mut last_opcode = Opcode::NOp;
+    for (step, entry) in trace.iter().enumerate() {
+        last_opcode = verify_step(program, memory, step, entry, trace.get(step + 1))?.opcode;
+    }
+
+    if last_opcode == Opcode::Ret && !program.builtins.is_empty() {
+        verify_builtin_segments(program, memory, first.fp, last.ap)?;
+    }
+    Ok(())
+}
+
+fn get_cell(memory: &[Option<Felt>], addr: usize) -> Option<&Felt> {
+    memory.get(addr).and_then(Option::as_ref)
+}
+
+fn verify_program_data(
+    program: &Program,
+    memory: &[Option<Felt>],
+) -> Result<(), VerificationError> {
+    for (offset, value) in program.data.iter().enumerate() {
+        let addr = PROGRAM_BASE + offset;
+        // Relocatable values depend on the segment layout of the run, so they can't be compared
+        if let MaybeRelocatable::Int(value) = value {
+            if get_cell(memory, addr) != Some(value) {
+                return Err(VerificationError::ProgramMismatch(addr));
+            }
+        }
+    }
+    Ok(())
+}
+
+// Verifies the instruction executed at the given step, returning it.
+fn verify_step(
+    program: &Program,
+    memory: &[Option<Felt>],
+    step: usize,
+    entry: &RelocatedTraceEntry,
+    next_entry: Option<&RelocatedTraceEntry>,
+) -> Result<Instruction, VerificationError> {
+    let RelocatedTraceEntry { ap, fp, pc } = *entry;
+    if !(PROGRAM_BASE..PROGRAM_BASE + program.data.len()).contains(&pc) {
+        return Err(VerificationError::PcOutOfProgram { step, pc });
+    }
+    let instruction = get_cell(memory, pc)
+        .and_then(|encoded_instruction| encoded_instruction.to_i64())
+        .and_then(|encoded_instruction| {
+            decode_instruction(encoded_instruction, get_cell(memory, pc + 1)).ok()
+        })
+        .ok_or(VerificationError::InvalidInstruction { step, pc })?;
+
+    let register = |register: &Register| match register {
+        Register::AP => ap,
+        Register::FP => fp,
+    };
+    let operand_addr = |operand: &'static str, base: usize, offset: isize| {
+        base.checked_add_signed(offset)
+            .ok_or(VerificationError::InvalidOperandAddress { step, operand })
+    };
+    let operand = |operand: &'static str, addr: usize| {
+        get_cell(memory, addr).ok_or(VerificationError::MissingOperand {
+            step,
+            operand,
+            addr,
+        })
+    };
+
+    let dst_addr = operand_addr("dst", register(&instruction.dst_register), instruction.off0)?;
+    let dst = operand("dst", dst_addr)?;
+    let op0_addr = operand_addr("op0", register(&instruction.op0_register), instruction.off1)?;
+    let op0 = operand("op0", op0_addr)?;
+    let op1_base = match instruction.op1_addr {
+        Op1Addr::Imm => pc,
+        Op1Addr::AP => ap,
+        Op1Addr::FP => fp,
+        Op1Addr::Op0 => op0
+            .to_usize()
+            .ok_or(VerificationError::InvalidOperandAddress {
+                step,
+                operand: "op1",
+            })?,
+    };
+    let op1_addr = operand_addr("op1", op1_base, instruction.off2)?;
+    let op1 = operand("op1", op1_addr)?;
+
+    let res = match instruction.res {
+        Res::Op1 => Some(op1.clone()),
+        Res::Add => Some(op0 + op1),
+        Res::Mul => Some(op0 * op1),
+        Res::Unconstrained => None,
+    };
+    let required_res = || {
+        res.clone()
+            .ok_or(VerificationError::InvalidInstruction { step, pc })
+    };
+
+    match instruction.opcode {
+        Opcode::AssertEq => {
+            let res = required_res()?;
+            if dst != &res {
+                return Err(VerificationError::AssertEqFailed {
+                    step,
+                    dst: dst.clone(),
+                    res,
+                });
+            }
+        }
+        Opcode::Call => {
+            if dst != &Felt::new(fp) {
+                return Err(VerificationError::CallFpNotSaved {
+                    step,
+                    fp,
+                    dst: dst.clone(),
+                });
+            }
+            let return_pc = pc + instruction.size();
+            if op0 != &Felt::new(return_pc) {
+                return Err(VerificationError::CallReturnPcNotSaved {
+                    step,
+                    return_pc,
+                    op0: op0.clone(),
+                });
+            }
+        }
+        Opcode::Ret | Opcode::NOp => (),
+    }
+
+    // The registers after the last step aren't part of the trace
+    let next_entry = match next_entry {
+        Some(next_entry) => next_entry,
+        None => return Ok(instruction),
+    };
+    let next_pc = match instruction.pc_update {
+        PcUpdate::Regular => Felt::new(pc + instruction.size()),
+        PcUpdate::Jump => required_res()?,
+        PcUpdate::JumpRel => required_res()? + pc,
+        PcUpdate::Jnz if dst.is_zero() => Felt::new(pc + instruction.size()),
+        PcUpdate::Jnz => op1 + pc,
+    };
+    let next_ap = match instruction.ap_update {
+        ApUpdate::Regular => Felt::new(ap),
+        ApUpdate::Add => required_res()? + ap,
+        ApUpdate::Add1 => Felt::new(ap + 1),
+        ApUpdate::Add2 => Felt::new(ap + 2),
+    };
+    let next_fp = match instruction.fp_update {
+        FpUpdate::Regular => Felt::new(fp),
+        FpUpdate::APPlus2 => Felt::new(ap + 2),
+        FpUpdate::Dst => dst.clone(),
+    };
+    for (register, expected, found) in [
+        ("pc", next_pc, next_entry.pc),
+        ("ap", next_ap, next_entry.ap),
+        ("fp", next_fp, next_entry.fp),
+    ] {
+        if expected != Felt::new(found) {
+            return Err(VerificationError::RegisterMismatch {
+                step,
+                register,
+                expected,
+                found,
+            });
+        }
+    }
+    Ok(instruction)
+}
+
+// Checks the instances of each builtin segment, by copying the segment into a virtual machine
+// and running the same checks as at the end of a run.
+// Main receives the builtin bases right below the return fp and pc, and returns the stop
+// pointers as its last return values.
+fn verify_builtin_segments(
+    program: &Program,
+    memory: &[Option<Felt>],
+    initial_fp: usize,
+    final_ap: usize,
+) -> Result<(), VerificationError> {
+    let runner = CairoRunner::new(program, "all", false)?;
+    let mut vm = VirtualMachine::new(false);
+    runner.initialize_builtins(&mut vm)?;
+    let n_builtins = vm.builtin_runners.len();
+
+    for (index, (name, builtin)) in vm.builtin_runners.iter_mut().enumerate() {
+        builtin.initialize_segments(&mut vm.segments, &mut vm.memory)?;
+        let invalid_pointers = || VerificationError::InvalidBuiltinPointers(name.clone());
+        let pointer = |addr: Option<usize>| {
+            addr.and_then(|addr| get_cell(memory, addr))
+                .and_then(Felt::to_usize)
+                .ok_or_else(invalid_pointers)
+        };
+        let base = pointer((initial_fp + index).checked_sub(2 + n_builtins))?;
+        let stop_ptr = pointer((final_ap + index).checked_sub(n_builtins))?;
+        let cells = memory.get(base..stop_ptr).ok_or_else(invalid_pointers)?;
+
+        for (offset, value) in cells.iter().enumerate() {
+            if let Some(value) = value {
+                vm.memory
+                    .insert(&Relocatable::from((builtin.base(), offset)), value)
+                    .map_err(|error| {
+                        VerificationError::InvalidBuiltinSegment(name.clone(), error.into())
+                    })?;
+            }
+        }
+        // The signatures aren't part of the memory, so the signature instances can't be checked
+        if name != "ecdsa" {
+            builtin.add_validation_rule(&mut vm.memory)?;
+            vm.memory.validate_existing_memory().map_err(|error| {
+                VerificationError::InvalidBuiltinSegment(name.clone(), error.into())
+            })?;
+        }
+    }
+
+    for (name, builtin) in vm.builtin_runners.iter() {
+        vm.verify_builtin_auto_deductions(name, builtin)
+            .map_err(|error| VerificationError::InvalidBuiltinSegment(name.clone(), error))?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vm::runners::cairo_runner::RunResources;
+    use crate::{
+        hint_processor::builtin_hint_processor::builtin_hint_processor_definition::BuiltinHintProcessor,
+        utils::test_utils::*,
+        vm::errors::{memory_errors::MemoryError, vm_errors::VirtualMachineError},
+    };
+
+    // fibonacci.cairo, computing the 10th Fibonacci number with a recursive fib
+    fn fibonacci_program() -> Program {
+        program!(
+            data = vec![
+                // main:
+                mayberelocatable!(0x480680017fff8000), // [ap] = 1; ap++
+                mayberelocatable!(1),
+                mayberelocatable!(0x480680017fff8000), // [ap] = 1; ap++
+                mayberelocatable!(1),
+                mayberelocatable!(0x480680017fff8000), // [ap] = 10; ap++
+                mayberelocatable!(10),
+                mayberelocatable!(0x1104800180018000), // call rel 5
+                mayberelocatable!(5),
+                mayberelocatable!(0x400680017fff7fff), // [ap + (-1)] = 144
+                mayberelocatable!(144),
+                mayberelocatable!(0x208b7fff7fff7ffe), // ret
+                // fib:
+                mayberelocatable!(0x20780017fff7ffd), // jmp rel 5 if [fp + (-3)] != 0
+                mayberelocatable!(5),
+                mayberelocatable!(0x480a7ffc7fff8000), // [ap] = [fp + (-4)]; ap++
+                mayberelocatable!(0x480a7ffc7fff8000), // [ap] = [fp + (-4)]; ap++
+                mayberelocatable!(0x208b7fff7fff7ffe), // ret
+                mayberelocatable!(0x482a7ffc7ffb8000), // [ap] = [fp + (-5)] + [fp + (-4)]; ap++
+                mayberelocatable!(0x480a7ffc7fff8000), // [ap] = [fp + (-4)]; ap++
+                mayberelocatable!(0x48127ffe7fff8000), // [ap] = [ap + (-2)]; ap++
+                mayberelocatable!(0x482680017ffd8000), // [ap] = [fp + (-3)] + (-1); ap++
+                mayberelocatable!(-1),
+                mayberelocatable!(0x1104800180018000), // call rel -10
+                mayberelocatable!(-10),
+                mayberelocatable!(0x208b7fff7fff7ffe), // ret
+            ],
+            main = Some(0),
+        )
+    }
+
+    fn run(program: &Program, proof_mode: bool) -> (Vec<Option<Felt>>, Vec<RelocatedTraceEntry>) {
+        let mut cairo_runner = cairo_runner!(program, "all", proof_mode);
+        let mut vm = vm!(true);
+        let end = cairo_runner.initialize(&mut vm).unwrap();
+        let mut hint_processor = BuiltinHintProcessor::new_empty();
+        cairo_runner
+            .run_until_pc(
+                end,
+                &mut RunResources::default(),
+                &mut vm,
+                &mut hint_processor,
+            )
+            .unwrap();
+        cairo_runner
+            .end_run(false, false, &mut vm, &mut hint_processor)
+            .unwrap();
+        if proof_mode {
+            cairo_runner.read_return_values(&mut vm).unwrap();
+            cairo_runner.finalize_segments(&mut vm).unwrap();
+        }
+        cairo_runner.relocate(&mut vm, None).unwrap();
+        (
+            cairo_runner.relocated_memory,
+            cairo_runner.relocated_trace.unwrap(),
+        )
+    }
+
+    #[test]
+    fn verify_fibonacci_run() {
+        let program = fibonacci_program();
+        let (memory, trace) = run(&program, false);
+        assert_eq!(verify_run(&program, &memory, &trace), Ok(()));
+    }
+
+    #[test]
+    fn verify_fibonacci_run_with_flipped_memory_cell() {
+        let program = fibonacci_program();
+        let (mut memory, trace) = run(&program, false);
+        // The first fib frame pushes y = 1 + 1 at its ap
+        let addr = trace
+            .iter()
+            .find(|entry| entry.pc == PROGRAM_BASE + 16)
+            .unwrap()
+            .ap;
+        assert_eq!(memory[addr], Some(Felt::new(2)));
+        memory[addr] = Some(Felt::new(3));
+        let step = trace
+            .iter()
+            .position(|entry| entry.pc == PROGRAM_BASE + 16)
+            .unwrap();
+        assert_eq!(
+            verify_run(&program, &memory, &trace),
+            Err(VerificationError::AssertEqFailed {
+                step,
+                dst: Felt::new(3),
+                res: Felt::new(2),
+            })
+        );
+    }
+
+    #[test]
+    fn verify_fibonacci_run_with_flipped_trace_register() {
+        let program = fibonacci_program();
+        let (memory, mut trace) = run(&program, false);
+        trace[4].ap += 1;
+        let error = verify_run(&program, &memory, &trace).unwrap_err();
+        assert_eq!(
+            error,
+            VerificationError::RegisterMismatch {
+                step: 3,
+                register: "ap",
+                expected: Felt::new(trace[4].ap - 1),
+                found: trace[4].ap,
+            }
+        );
+        assert_eq!(
+            error.to_string(),
+            format!(
+                "Step 3: ap should be {} after the instruction, but the trace has {}",
+                trace[4].ap - 1,
+                trace[4].ap
+            )
+        );
+    }
+
+    #[test]
+    fn verify_run_with_modified_program() {
+        let program = fibonacci_program();
+        let (mut memory, trace) = run(&program, false);
+        memory[PROGRAM_BASE + 9] = Some(Felt::new(143));
+        assert_eq!(
+            verify_run(&program, &memory, &trace),
+            Err(VerificationError::ProgramMismatch(PROGRAM_BASE + 9))
+        );
+    }
+
+    #[test]
+    fn verify_run_with_pc_out_of_program() {
+        let program = fibonacci_program();
+        let (memory, mut trace) = run(&program, false);
+        trace[0].pc = PROGRAM_BASE + program.data.len();
+        assert_eq!(
+            verify_run(&program, &memory, &trace),
+            Err(VerificationError::PcOutOfProgram {
+                step: 0,
+                pc: trace[0].pc
+            })
+        );
+    }
+
+    #[test]
+    fn verify_empty_trace() {
+        let program = fibonacci_program();
+        let (memory, _) = run(&program, false);
+        assert_eq!(
+            verify_run(&program, &memory, &[]),
+            Err(VerificationError::EmptyTrace)
+        );
+    }
+
+    #[test]
+    fn verify_run_with_builtins() {
+        let program = Program::from_fixture("check_range");
+        let (memory, trace) = run(&program, false);
+        assert_eq!(verify_run(&program, &memory, &trace), Ok(()));
+    }
+
+    #[test]
+    fn verify_run_with_invalid_range_check_instance() {
+        let program = Program::from_fixture("check_range");
+        let (mut memory, trace) = run(&program, false);
+        // Main receives the range check base and returns its stop pointer
+        let pointer = |addr: usize| memory[addr].as_ref().unwrap().to_usize().unwrap();
+        let (initial_fp, final_ap) = (trace[0].fp, trace.last().unwrap().ap);
+        let (base, stop_ptr) = (pointer(initial_fp - 3), pointer(final_ap - 1));
+        let value = Felt::new(u128::MAX) + Felt::new(1);
+        memory[stop_ptr - 1] = Some(value.clone());
+        assert_eq!(
+            verify_builtin_segments(&program, &memory, initial_fp, final_ap),
+            Err(VerificationError::InvalidBuiltinSegment(
+                String::from("range_check"),
+                VirtualMachineError::MemoryError(MemoryError::NumOutOfBounds {
+                    addr: Relocatable::from((0, stop_ptr - 1 - base)),
+                    value,
+                })
+            ))
+        );
+    }
+
+    #[test]
+    fn verify_proof_mode_run() {
+        let program = Program::from_fixture("check_range_proof_mode");
+        let (memory, trace) = run(&program, true);
+        assert_eq!(verify_run(&program, &memory, &trace), Ok(()));
+    }
+}
@@ -12,21 +12,30 @@ use crate::{
         context::run_context::RunContext,
         decoding::decoder::decode_instruction,
         errors::{
-            exec_scope_errors::ExecScopeError, memory_errors::MemoryError,
-            vm_errors::VirtualMachineError,
+            exec_scope_errors::ExecScopeError,
+            memory_errors::MemoryError,
+            trace_errors::TraceError,
+            vm_errors::{AutoDeductionMismatch, VirtualMachineError},
         },
-        runners::builtin_runner::{BuiltinRunner, RangeCheckBuiltinRunner, SignatureBuiltinRunner},
-        trace::trace_entry::TraceEntry,
+        runners::{
+            builtin_runner::{
+                BuiltinRunner, OutputBuiltinRunner, RangeCheckBuiltinRunner, SignatureBuiltinRunner,
+            },
+            cairo_runner::RunResources,
+        },
+        trace::trace_entry::{ExecutedStep, TraceEntry},
         vm_memory::{memory::Memory, memory_segments::MemorySegmentManager},
     },
 };
-use felt::Felt;
+use felt::{Felt, FeltOps};
+use num_bigint::{BigInt, BigUint};
 use num_traits::{ToPrimitive, Zero};
 use std::{any::Any, borrow::Cow, collections::HashMap};
 
 use super::vm_memory::memory_segments::gen_typed_args;
 
-const MAX_TRACEBACK_ENTRIES: u32 = 20;
+pub const MAX_TRACEBACK_ENTRIES: usize = 20;
+pub const MAX_REPORTED_AUTO_DEDUCTION_MISMATCHES: usize = 20;
 
 #[derive(PartialEq, Eq, Debug)]
 pub struct Operands {
@@ -76,17 +85,77 @@ pub struct HintData {
     pub ap_tracking_data: ApTracking,
 }
 
+/// A frame of the call stack, as found by walking the fp chain.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TracebackEntry {
+    /// The fp of the caller, read from `[fp - 2]`.
+    pub fp: Relocatable,
+    /// The pc the callee returns to, read from `[fp - 1]`.
+    pub return_pc: Relocatable,
+    /// The pc of the call instruction which precedes `return_pc`.
+    pub call_pc: Relocatable,
+}
+
+// Maps each segment index to the position in builtin_runners of the builtin owning it, so that
+// deducing a memory cell doesn't have to ask every builtin whether the address belongs to it.
+// The output builtin is left out, as it never deduces anything.
+struct BuiltinSegmentLookup {
+    // The number of builtin runners when the lookup was built, to detect added runners
+    n_builtins: usize,
+    runner_by_segment: Vec<Option<usize>>,
+}
+
+// Returns the element of the field of the given prime that an integer stands for. Memory may hold
+// integers at or above the prime, as Felt works modulo the larger Cairo prime.
+fn reduce(num: &Felt, prime: &BigUint) -> BigUint {
+    num.to_biguint() % prime
+}
+
+// Returns the value of a field element of the given prime as a signed integer, in the range
+// (-prime / 2, prime / 2].
+fn signed_value(num: &Felt, prime: &BigUint) -> BigInt {
+    let value = reduce(num, prime);
+    if value > prime >> 1_u32 {
+        BigInt::from(value) - BigInt::from(prime.clone())
+    } else {
+        BigInt::from(value)
+    }
+}
+
 pub struct VirtualMachine {
     pub(crate) run_context: RunContext,
     pub(crate) builtin_runners: Vec<(String, BuiltinRunner)>,
+    builtin_segment_lookup: Option<BuiltinSegmentLookup>,
     pub(crate) segments: MemorySegmentManager,
     pub(crate) _program_base: Option<MaybeRelocatable>,
+    // The constants of the program whose value is a label, resolved against the program base
+    pub(crate) label_constants: HashMap<String, Relocatable>,
     pub(crate) memory: Memory,
     pub(crate) accessed_addresses: Option<Vec<Relocatable>>,
     pub(crate) trace: Option<Vec<TraceEntry>>,
     pub(crate) current_step: usize,
+    last_assert_operands: Option<(MaybeRelocatable, MaybeRelocatable, MaybeRelocatable)>,
     skip_instruction_execution: bool,
     run_finished: bool,
+    collect_all_errors: bool,
+    // The prime of the program's field when it isn't the Cairo prime. Felt always works modulo
+    // the Cairo prime, so the instructions' arithmetic is then done on BigUint modulo this one
+    pub(crate) prime: Option<BigUint>,
+}
+
+// Turns the mismatches found while verifying auto deductions into an error listing the first
+// MAX_REPORTED_AUTO_DEDUCTION_MISMATCHES of them, if there are any.
+fn auto_deduction_mismatches_to_result(
+    mut mismatches: Vec<AutoDeductionMismatch>,
+) -> Result<(), VirtualMachineError> {
+    if mismatches.is_empty() {
+        return Ok(());
+    }
+    let total = mismatches.len();
+    mismatches.truncate(MAX_REPORTED_AUTO_DEDUCTION_MISMATCHES);
+    Err(VirtualMachineError::InconsistentAutoDeductions(
+        mismatches, total,
+    ))
 }
 
 impl HintData {
@@ -120,30 +189,36 @@ impl VirtualMachine {
         VirtualMachine {
             run_context,
             builtin_runners: Vec::new(),
+            builtin_segment_lookup: None,
             _program_base: None,
+            label_constants: HashMap::new(),
             memory: Memory::new(),
             // We had to change this from None to this Some because when calling run_from_entrypoint from cairo-rs-py
             // we could not change this value and faced an Error. This is the behaviour that the original VM implements also.
             accessed_addresses: Some(Vec::new()),
             trace,
             current_step: 0,
+            last_assert_operands: None,
             skip_instruction_execution: false,
             segments: MemorySegmentManager::new(),
             run_finished: false,
+            collect_all_errors: false,
+            prime: None,
         }
     }
 
     ///Returns the encoded instruction (the value at pc) and the immediate value (the value at pc + 1, if it exists in the memory).
     fn get_instruction_encoding(
         &self,
+        pc: &Relocatable,
     ) -> Result<(Cow<Felt>, Option<Cow<MaybeRelocatable>>), VirtualMachineError> {
-        let encoding_ref = match self.memory.get(&self.run_context.pc) {
+        let encoding_ref = match self.memory.get(pc) {
             Ok(Some(Cow::Owned(MaybeRelocatable::Int(encoding)))) => Cow::Owned(encoding),
             Ok(Some(Cow::Borrowed(MaybeRelocatable::Int(encoding)))) => Cow::Borrowed(encoding),
             _ => return Err(VirtualMachineError::InvalidInstructionEncoding),
         };
 
-        let imm_addr = &self.run_context.pc + 1_i32;
+        let imm_addr = pc + 1_i32;
 
         if let Ok(optional_imm) = self.memory.get(&imm_addr) {
             Ok((encoding_ref, optional_imm))
@@ -178,7 +253,7 @@ impl VirtualMachine {
     ) -> Result<(), VirtualMachineError> {
         let new_ap: Relocatable = match instruction.ap_update {
             ApUpdate::Add => match operands.res.clone() {
-                Some(res) => self.run_context.get_ap().add_maybe(&res)?,
+                Some(res) => self.add_to_relocatable(&self.run_context.get_ap(), &res)?,
                 None => return Err(VirtualMachineError::UnconstrainedResAdd),
             },
             ApUpdate::Add1 => self.run_context.get_ap() + 1_i32,
@@ -202,15 +277,17 @@ impl VirtualMachine {
             },
             PcUpdate::JumpRel => match operands.res.clone() {
                 Some(res) => match res {
-                    MaybeRelocatable::Int(num_res) => self.run_context.pc.add_int(&num_res)?,
+                    MaybeRelocatable::Int(_) => {
+                        self.add_to_relocatable(&self.run_context.pc, &res)?
+                    }
 
                     _ => return Err(VirtualMachineError::PureValue),
                 },
                 None => return Err(VirtualMachineError::UnconstrainedResJumpRel),
             },
-            PcUpdate::Jnz => match VirtualMachine::is_zero(&operands.dst)? {
+            PcUpdate::Jnz => match self.is_zero_value(&operands.dst)? {
                 true => self.run_context.pc + instruction.size(),
-                false => (self.run_context.pc.add_maybe(&operands.op1))?,
+                false => self.add_to_relocatable(&self.run_context.pc, &operands.op1)?,
             },
         };
         self.run_context.pc = new_pc;
@@ -260,7 +337,10 @@ impl VirtualMachine {
                 match instruction.res {
                     Res::Add => {
                         if let (Some(dst_addr), Some(op1_addr)) = (dst, op1) {
-                            return Ok((Some(dst_addr.sub(op1_addr)?), Some(dst_addr.clone())));
+                            return Ok((
+                                Some(self.sub_values(dst_addr, op1_addr)?),
+                                Some(dst_addr.clone()),
+                            ));
                         }
                     }
                     Res::Mul => {
@@ -270,10 +350,9 @@ impl VirtualMachine {
                                 MaybeRelocatable::Int(ref num_op1_ref),
                             ) = (dst_addr, op1_addr)
                             {
-                                let num_op1 = Clone::clone(num_op1_ref);
-                                if num_op1 != Felt::zero() {
+                                if let Some(op0) = self.div_felts(num_dst, num_op1_ref) {
                                     return Ok((
-                                        Some(MaybeRelocatable::Int(num_dst / num_op1)),
+                                        Some(MaybeRelocatable::Int(op0)),
                                         Some(dst_addr.clone()),
                                     ));
                                 }
@@ -306,7 +385,10 @@ impl VirtualMachine {
                 }
                 Res::Add => {
                     if let (Some(dst_addr), Some(op0_addr)) = (dst, op0) {
-                        return Ok((Some(dst_addr.sub(&op0_addr)?), Some(dst_addr.clone())));
+                        return Ok((
+                            Some(self.sub_values(dst_addr, &op0_addr)?),
+                            Some(dst_addr.clone()),
+                        ));
                     }
                 }
                 Res::Mul => {
@@ -314,9 +396,9 @@ impl VirtualMachine {
                         if let (MaybeRelocatable::Int(num_dst), MaybeRelocatable::Int(num_op0)) =
                             (dst_addr, op0_addr)
                         {
-                            if num_op0 != Felt::zero() {
+                            if let Some(op1) = self.div_felts(num_dst, &num_op0) {
                                 return Ok((
-                                    Some(MaybeRelocatable::Int(num_dst / num_op0)),
+                                    Some(MaybeRelocatable::Int(op1)),
                                     Some(dst_addr.clone()),
                                 ));
                             }
@@ -333,15 +415,76 @@ impl VirtualMachine {
         &self,
         address: &Relocatable,
     ) -> Result<Option<MaybeRelocatable>, VirtualMachineError> {
-        for (_, builtin) in self.builtin_runners.iter() {
-            if builtin.base() == address.segment_index {
-                match builtin.deduce_memory_cell(address, &self.memory) {
-                    Ok(maybe_reloc) => return Ok(maybe_reloc),
-                    Err(error) => return Err(VirtualMachineError::RunnerError(error)),
-                };
+        match self.get_deducing_builtin(address.segment_index) {
+            Some(builtin) => {
+                let value = builtin.deduce_memory_cell(address, &self.memory)?;
+                #[cfg(feature = "tracing")]
+                if let Some(value) = &value {
+                    tracing::trace!(
+                        builtin = builtin.name(),
+                        %address,
+                        %value,
+                        "builtin deduction"
+                    );
+                }
+                Ok(value)
+            }
+            None => Ok(None),
+        }
+    }
+
+    // Returns the builtin which can deduce the cells of the given segment, if any.
+    // Uses the segment lookup when it is up to date, and asks every builtin otherwise (or for
+    // temporary segments, which aren't indexed).
+    fn get_deducing_builtin(&self, segment_index: isize) -> Option<&BuiltinRunner> {
+        match &self.builtin_segment_lookup {
+            Some(lookup)
+                if segment_index >= 0 && lookup.n_builtins == self.builtin_runners.len() =>
+            {
+                lookup
+                    .runner_by_segment
+                    .get(segment_index as usize)
+                    .copied()
+                    .flatten()
+                    .map(|index| &self.builtin_runners[index].1)
+                    .filter(|builtin| builtin.base() == segment_index)
+            }
+            _ => self
+                .builtin_runners
+                .iter()
+                .map(|(_, builtin)| builtin)
+                .find(|builtin| {
+                    builtin.base() == segment_index && !matches!(builtin, BuiltinRunner::Output(_))
+                }),
+        }
+    }
+
+    /// Indexes the builtin runners by the segment they own, so that deducing a memory cell
+    /// doesn't have to go through every builtin.
+    /// Should be called once the builtins' segments are initialized. Mutable access to the
+    /// builtin runners through `get_builtin_runners_as_mut` drops the index, as it may change
+    /// their bases.
+    pub(crate) fn index_builtin_segments(&mut self) {
+        let mut runner_by_segment = Vec::new();
+        for (index, (_, builtin)) in self.builtin_runners.iter().enumerate() {
+            if matches!(builtin, BuiltinRunner::Output(_)) {
+                continue;
             }
+            // Builtins in temporary segments aren't indexed
+            let segment_index = match usize::try_from(builtin.base()) {
+                Ok(segment_index) => segment_index,
+                Err(_) => continue,
+            };
+            if runner_by_segment.len() <= segment_index {
+                runner_by_segment.resize(segment_index + 1, None);
+            }
+            // Keep the first builtin found for each segment
+            runner_by_segment[segment_index].get_or_insert(index);
         }
-        Ok(None)
+        self.builtin_segment_lookup = Some(BuiltinSegmentLookup {
+            n_builtins: self.builtin_runners.len(),
+            runner_by_segment,
+        });
     }
 
     ///Computes the value of res if possible
@@ -353,11 +496,13 @@ impl VirtualMachine {
     ) -> Result<Option<MaybeRelocatable>, VirtualMachineError> {
         match instruction.res {
             Res::Op1 => Ok(Some(op1.clone())),
-            Res::Add => Ok(Some(op0.add(op1)?)),
+            Res::Add => Ok(Some(self.add_values(op0, op1)?)),
             Res::Mul => {
                 if let (MaybeRelocatable::Int(num_op0), MaybeRelocatable::Int(num_op1)) = (op0, op1)
                 {
-                    return Ok(Some(MaybeRelocatable::Int(num_op0 * num_op1)));
+                    return Ok(Some(MaybeRelocatable::Int(
+                        self.mul_felts(num_op0, num_op1),
+                    )));
                 }
                 Err(VirtualMachineError::PureValue)
             }
@@ -365,6 +510,122 @@ impl VirtualMachine {
         }
     }
 
+    // Adds two values as an instruction does, modulo the program prime.
+    fn add_values(
+        &self,
+        a: &MaybeRelocatable,
+        b: &MaybeRelocatable,
+    ) -> Result<MaybeRelocatable, VirtualMachineError> {
+        let prime = match &self.prime {
+            Some(prime) => prime,
+            None => return a.add(b),
+        };
+        match (a, b) {
+            (MaybeRelocatable::Int(num_a), MaybeRelocatable::Int(num_b)) => {
+                Ok(MaybeRelocatable::Int(Felt::from(
+                    (reduce(num_a, prime) + reduce(num_b, prime)) % prime,
+                )))
+            }
+            (MaybeRelocatable::RelocatableValue(rel), num @ MaybeRelocatable::Int(_))
+            | (num @ MaybeRelocatable::Int(_), MaybeRelocatable::RelocatableValue(rel)) => {
+                Ok(self.add_to_relocatable(rel, num)?.into())
+            }
+            _ => a.add(b),
+        }
+    }
+
+    // Subtracts two values as an instruction does, modulo the program prime.
+    fn sub_values(
+        &self,
+        a: &MaybeRelocatable,
+        b: &MaybeRelocatable,
+    ) -> Result<MaybeRelocatable, VirtualMachineError> {
+        let prime = match &self.prime {
+            Some(prime) => prime,
+            None => return a.sub(b),
+        };
+        match (a, b) {
+            (MaybeRelocatable::Int(num_a), MaybeRelocatable::Int(num_b)) => {
+                Ok(MaybeRelocatable::Int(Felt::from(
+                    (reduce(num_a, prime) + prime - reduce(num_b, prime)) % prime,
+                )))
+            }
+            (MaybeRelocatable::RelocatableValue(rel), MaybeRelocatable::Int(num)) => {
+                let offset = BigInt::from(rel.offset) - signed_value(num, prime);
+                let offset = offset
+                    .to_usize()
+                    .ok_or_else(|| VirtualMachineError::OffsetExceeded(Felt::from(offset)))?;
+                Ok(MaybeRelocatable::from((rel.segment_index, offset)))
+            }
+            _ => a.sub(b),
+        }
+    }
+
+    // Multiplies two integers as an instruction does, modulo the program prime.
+    fn mul_felts(&self, a: &Felt, b: &Felt) -> Felt {
+        match &self.prime {
+            Some(prime) => Felt::from((reduce(a, prime) * reduce(b, prime)) % prime),
+            None => a * b,
+        }
+    }
+
+    // Divides two integers in the field of the program prime, or returns None if the divisor is
+    // zero in it.
+    fn div_felts(&self, a: &Felt, b: &Felt) -> Option<Felt> {
+        match &self.prime {
+            Some(prime) => {
+                let b = reduce(b, prime);
+                if b.is_zero() {
+                    return None;
+                }
+                // The inverse of b is b^(p - 2), as b^(p - 1) = 1 by Fermat's little theorem
+                let b_inverse = b.modpow(&(prime - 2_u32), prime);
+                Some(Felt::from((reduce(a, prime) * b_inverse) % prime))
+            }
+            None => (!b.is_zero()).then(|| a / b),
+        }
+    }
+
+    // Tells whether two values are equal, comparing integers as elements of the field of the
+    // program prime.
+    fn values_eq(&self, a: &MaybeRelocatable, b: &MaybeRelocatable) -> bool {
+        match (&self.prime, a, b) {
+            (Some(prime), MaybeRelocatable::Int(num_a), MaybeRelocatable::Int(num_b)) => {
+                reduce(num_a, prime) == reduce(num_b, prime)
+            }
+            _ => a == b,
+        }
+    }
+
+    // Tells whether a value is zero in the field of the program prime.
+    fn is_zero_value(&self, value: &MaybeRelocatable) -> Result<bool, VirtualMachineError> {
+        match (&self.prime, value) {
+            (Some(prime), MaybeRelocatable::Int(num)) => Ok(reduce(num, prime).is_zero()),
+            _ => VirtualMachine::is_zero(value),
+        }
+    }
+
+    // Moves an address by an integer, which is negative when it's in the upper half of the field
+    // of the program prime, as an instruction updating a register does.
+    fn add_to_relocatable(
+        &self,
+        rel: &Relocatable,
+        value: &MaybeRelocatable,
+    ) -> Result<Relocatable, VirtualMachineError> {
+        let prime = match &self.prime {
+            Some(prime) => prime,
+            None => return rel.add_maybe(value),
+        };
+        let num = value
+            .get_int_ref()
+            .map_err(|_| VirtualMachineError::RelocatableAdd)?;
+        let offset = BigInt::from(rel.offset) + signed_value(num, prime);
+        let offset = offset
+            .to_usize()
+            .ok_or_else(|| VirtualMachineError::OffsetExceeded(Felt::from(offset)))?;
+        Ok(Relocatable::from((rel.segment_index, offset)))
+    }
+
     fn deduce_dst(
         &self,
         instruction: &Instruction,
@@ -392,7 +653,7 @@ impl VirtualMachine {
                 match &operands.res {
                     None => return Err(VirtualMachineError::UnconstrainedResAssertEq),
                     Some(res) => {
-                        if res != &operands.dst {
+                        if !self.values_eq(res, &operands.dst) {
                             return Err(VirtualMachineError::DiffAssertValues(
                                 operands.dst.clone(),
                                 res.clone(),
@@ -431,18 +692,15 @@ impl VirtualMachine {
     ) -> Result<(), VirtualMachineError> {
         if deduced_operands.was_op0_deducted() {
             self.memory
-                .insert(&operands_addresses.op0_addr, &operands.op0)
-                .map_err(VirtualMachineError::MemoryError)?;
+                .insert(&operands_addresses.op0_addr, &operands.op0)?;
         }
         if deduced_operands.was_op1_deducted() {
             self.memory
-                .insert(&operands_addresses.op1_addr, &operands.op1)
-                .map_err(VirtualMachineError::MemoryError)?;
+                .insert(&operands_addresses.op1_addr, &operands.op1)?;
         }
         if deduced_operands.was_dest_deducted() {
             self.memory
-                .insert(&operands_addresses.dst_addr, &operands.dst)
-                .map_err(VirtualMachineError::MemoryError)?;
+                .insert(&operands_addresses.dst_addr, &operands.dst)?;
         }
 
         Ok(())
@@ -452,7 +710,12 @@ impl VirtualMachine {
         let (operands, operands_addresses, deduced_operands) =
             self.compute_operands(&instruction)?;
         self.insert_deduced_operands(deduced_operands, &operands, &operands_addresses)?;
-        self.opcode_assertions(&instruction, &operands)?;
+        if let Err(error) = self.opcode_assertions(&instruction, &operands) {
+            if let VirtualMachineError::DiffAssertValues(_, _) = error {
+                self.last_assert_operands = Some((operands.dst, operands.op0, operands.op1));
+            }
+            return Err(error);
+        }
 
         if let Some(ref mut trace) = &mut self.trace {
             trace.push(TraceEntry {
@@ -473,8 +736,13 @@ impl VirtualMachine {
         Ok(())
     }
 
-    fn decode_current_instruction(&self) -> Result<Instruction, VirtualMachineError> {
-        let (instruction_ref, imm) = self.get_instruction_encoding()?;
+    /// Decodes the instruction pointed to by the current pc, without executing it.
+    pub fn decode_current_instruction(&self) -> Result<Instruction, VirtualMachineError> {
+        self.decode_instruction_at(&self.run_context.pc)
+    }
+
+    fn decode_instruction_at(&self, pc: &Relocatable) -> Result<Instruction, VirtualMachineError> {
+        let (instruction_ref, imm) = self.get_instruction_encoding(pc)?;
         match instruction_ref.to_i64() {
             Some(instruction) => {
                 if let Some(MaybeRelocatable::Int(imm_ref)) = imm.as_ref().map(|x| x.as_ref()) {
@@ -492,14 +760,22 @@ impl VirtualMachine {
         &mut self,
         hint_executor: &mut dyn HintProcessor,
         exec_scopes: &mut ExecutionScopes,
-        hint_data_dictionary: &HashMap<usize, Vec<Box<dyn Any>>>,
+        hint_data_dictionary: &HashMap<Relocatable, Vec<Box<dyn Any>>>,
         constants: &HashMap<String, Felt>,
+        run_resources: &mut RunResources,
     ) -> Result<(), VirtualMachineError> {
-        if let Some(hint_list) = hint_data_dictionary.get(&self.run_context.pc.offset) {
+        if let Some(hint_list) = hint_data_dictionary.get(&self.run_context.pc) {
             for (hint_index, hint_data) in hint_list.iter().enumerate() {
-                hint_executor
-                    .execute_hint(self, exec_scopes, hint_data, constants)
-                    .map_err(|err| VirtualMachineError::Hint(hint_index, Box::new(err)))?
+                let result = hint_executor.execute_hint(
+                    self,
+                    exec_scopes,
+                    hint_data,
+                    constants,
+                    run_resources,
+                );
+                #[cfg(feature = "tracing")]
+                trace_hint(&self.run_context.pc, hint_index, hint_data, &result);
+                result.map_err(|err| VirtualMachineError::Hint(hint_index, Box::new(err)))?
             }
         }
         Ok(())
@@ -516,11 +792,24 @@ impl VirtualMachine {
         &mut self,
         hint_executor: &mut dyn HintProcessor,
         exec_scopes: &mut ExecutionScopes,
-        hint_data_dictionary: &HashMap<usize, Vec<Box<dyn Any>>>,
+        hint_data_dictionary: &HashMap<Relocatable, Vec<Box<dyn Any>>>,
         constants: &HashMap<String, Felt>,
+        run_resources: &mut RunResources,
     ) -> Result<(), VirtualMachineError> {
-        self.step_hint(hint_executor, exec_scopes, hint_data_dictionary, constants)?;
-        self.step_instruction()
+        #[cfg(feature = "tracing")]
+        tracing::trace!(pc = %self.run_context.pc, "step");
+        self.memory.writer_pc = Some(self.run_context.pc);
+        let result = self
+            .step_hint(
+                hint_executor,
+                exec_scopes,
+                hint_data_dictionary,
+                constants,
+                run_resources,
+            )
+            .and_then(|()| self.step_instruction());
+        self.memory.writer_pc = None;
+        result
     }
 
     fn compute_op0_deductions(
@@ -592,27 +881,15 @@ impl VirtualMachine {
     ) -> Result<(Operands, OperandsAddresses, DeducedOperands), VirtualMachineError> {
         //Get operands from memory
         let dst_addr = self.run_context.compute_dst_addr(instruction)?;
-        let dst_op = self
-            .memory
-            .get(&dst_addr)
-            .map_err(VirtualMachineError::MemoryError)?
-            .map(Cow::into_owned);
+        let dst_op = self.memory.get(&dst_addr)?.map(Cow::into_owned);
 
         let op0_addr = self.run_context.compute_op0_addr(instruction)?;
-        let op0_op = self
-            .memory
-            .get(&op0_addr)
-            .map_err(VirtualMachineError::MemoryError)?
-            .map(Cow::into_owned);
+        let op0_op = self.memory.get(&op0_addr)?.map(Cow::into_owned);
 
         let op1_addr = self
             .run_context
             .compute_op1_addr(instruction, op0_op.as_ref())?;
-        let op1_op = self
-            .memory
-            .get(&op1_addr)
-            .map_err(VirtualMachineError::MemoryError)?
-            .map(Cow::into_owned);
+        let op1_op = self.memory.get(&op1_addr)?.map(Cow::into_owned);
 
         let mut res: Option<MaybeRelocatable> = None;
 
@@ -662,28 +939,99 @@ impl VirtualMachine {
     }
 
     ///Makes sure that all assigned memory cells are consistent with their auto deduction rules.
-    pub fn verify_auto_deductions(&mut self) -> Result<(), VirtualMachineError> {
-        for (name, builtin) in self.builtin_runners.iter_mut() {
-            let index: usize = builtin
-                .base()
-                .try_into()
-                .map_err(|_| MemoryError::AddressInTemporarySegment(builtin.base()))?;
-            for (offset, value) in self.memory.data[index].iter().enumerate() {
-                if let Some(deduced_memory_cell) = builtin
-                    .deduce_memory_cell(&Relocatable::from((index as isize, offset)), &self.memory)
-                    .map_err(VirtualMachineError::RunnerError)?
-                {
-                    if Some(&deduced_memory_cell) != value.as_ref() && value.is_some() {
-                        return Err(VirtualMachineError::InconsistentAutoDeduction(
-                            name.to_owned(),
-                            deduced_memory_cell,
-                            value.to_owned(),
-                        ));
+    pub fn verify_auto_deductions(&self) -> Result<(), VirtualMachineError> {
+        for (name, builtin) in self.builtin_runners.iter() {
+            if let Some(mismatch) = self
+                .get_auto_deduction_mismatches(name, builtin, true)?
+                .pop()
+            {
+                return Err(mismatch.into_error());
+            }
+        }
+        Ok(())
+    }
+
+    /// Like `verify_auto_deductions`, but checks every builtin cell instead of stopping at the
+    /// first inconsistent one, and reports up to `MAX_REPORTED_AUTO_DEDUCTION_MISMATCHES` of
+    /// them in a single `InconsistentAutoDeductions` error.
+    pub fn verify_auto_deductions_collect_errors(&self) -> Result<(), VirtualMachineError> {
+        let mut mismatches = Vec::new();
+        for (name, builtin) in self.builtin_runners.iter() {
+            mismatches.extend(self.get_auto_deduction_mismatches(name, builtin, false)?);
+        }
+        auto_deduction_mismatches_to_result(mismatches)
+    }
+
+    /// Verifies the auto deductions of a single builtin's segment, either stopping at the first
+    /// inconsistency or collecting all of them, depending on `collect_all_errors`.
+    pub(crate) fn verify_builtin_auto_deductions(
+        &self,
+        name: &str,
+        builtin: &BuiltinRunner,
+    ) -> Result<(), VirtualMachineError> {
+        let mismatches =
+            self.get_auto_deduction_mismatches(name, builtin, !self.collect_all_errors)?;
+        if self.collect_all_errors {
+            return auto_deduction_mismatches_to_result(mismatches);
+        }
+        match mismatches.into_iter().next() {
+            Some(mismatch) => Err(mismatch.into_error()),
+            None => Ok(()),
+        }
+    }
+
+    // Returns the cells of the builtin's segment whose values differ from the ones deduced by the
+    // builtin, in address order. If stop_at_first is set, at most one mismatch is returned.
+    fn get_auto_deduction_mismatches(
+        &self,
+        name: &str,
+        builtin: &BuiltinRunner,
+        stop_at_first: bool,
+    ) -> Result<Vec<AutoDeductionMismatch>, VirtualMachineError> {
+        let mut mismatches = Vec::new();
+        // The output builtin has no deduction rules
+        if let BuiltinRunner::Output(_) = builtin {
+            return Ok(mismatches);
+        }
+        let base = builtin.base();
+        let segment = match usize::try_from(base) {
+            Ok(index) => self.memory.data.get(index),
+            Err(_) => self.memory.temp_data.get(-(base + 1) as usize),
+        };
+        let segment = match segment {
+            Some(segment) => segment,
+            None => return Ok(mismatches),
+        };
+        for (offset, value) in segment.iter().enumerate() {
+            let value = match value {
+                Some(value) => value,
+                None => continue,
+            };
+            let address = Relocatable::from((base, offset));
+            if let Some(deduced_memory_cell) = builtin
+                .deduce_memory_cell(&address, &self.memory)
+                .map_err(VirtualMachineError::RunnerError)?
+            {
+                if &deduced_memory_cell != value {
+                    mismatches.push(AutoDeductionMismatch {
+                        builtin: name.to_string(),
+                        address,
+                        expected: deduced_memory_cell,
+                        actual: value.clone(),
+                    });
+                    if stop_at_first {
+                        break;
                     }
                 }
             }
         }
-        Ok(())
+        Ok(mismatches)
+    }
+
+    /// When set, security checks report every inconsistent auto-deduction found in a builtin
+    /// segment instead of failing on the first one.
+    pub fn set_collect_all_errors(&mut self, collect_all_errors: bool) {
+        self.collect_all_errors = collect_all_errors;
     }
 
     pub fn end_run(&mut self, exec_scopes: &ExecutionScopes) -> Result<(), VirtualMachineError> {
@@ -710,67 +1058,104 @@ impl VirtualMachine {
         Ok(())
     }
 
-    // Returns the values (fp, pc) corresponding to each call instruction in the traceback.
-    // Returns the most recent call last.
-    pub(crate) fn get_traceback_entries(&self) -> Vec<(Relocatable, Relocatable)> {
-        let mut entries = Vec::<(Relocatable, Relocatable)>::new();
+    /// Iterates over the executed steps, resolving the instruction and operands of each trace
+    /// entry, e.g. to compute the coverage of a program or to check its instructions.
+    /// The operands are read from the memory as it is when iterating. As memory cells are written
+    /// once, after the run it holds the values the instructions were executed with.
+    /// Yields a single TraceNotEnabled error when the trace is disabled.
+    pub fn iter_trace_with_instructions(
+        &self,
+    ) -> impl Iterator<Item = Result<ExecutedStep, TraceError>> + '_ {
+        let trace = self.trace.as_deref();
+        let not_enabled = trace.is_none().then_some(Err(TraceError::TraceNotEnabled));
+        not_enabled.into_iter().chain(
+            trace
+                .unwrap_or_default()
+                .iter()
+                .map(|entry| self.resolve_trace_entry(entry)),
+        )
+    }
+
+    fn resolve_trace_entry(&self, entry: &TraceEntry) -> Result<ExecutedStep, TraceError> {
+        let instruction = self
+            .decode_instruction_at(&entry.pc)
+            .map_err(|_| TraceError::InvalidInstruction(entry.pc))?;
+        let run_context = RunContext {
+            pc: entry.pc,
+            ap: entry.ap.offset,
+            fp: entry.fp.offset,
+        };
+        let operand = |name, addr: Result<Relocatable, VirtualMachineError>| {
+            addr.ok()
+                .and_then(|addr| self.memory.get(&addr).ok().flatten())
+                .map(Cow::into_owned)
+                .ok_or(TraceError::UnresolvedOperand(name, entry.pc))
+        };
+        let dst = operand("dst", run_context.compute_dst_addr(&instruction))?;
+        let op0 = operand("op0", run_context.compute_op0_addr(&instruction))?;
+        let op1 = operand(
+            "op1",
+            run_context.compute_op1_addr(&instruction, Some(&op0)),
+        )?;
+        let res = self
+            .compute_res(&instruction, &op0, &op1)
+            .map_err(|_| TraceError::UnresolvedOperand("res", entry.pc))?;
+        Ok(ExecutedStep {
+            registers: entry.clone(),
+            instruction,
+            dst,
+            op0,
+            op1,
+            res,
+        })
+    }
+
+    /// Walks the fp chain from the current fp, returning an entry for each call in the traceback,
+    /// with the most recent call last.
+    /// The walk stops at the first frame whose return pc or caller fp is missing or isn't a
+    /// relocatable value, or whose return pc doesn't follow a call instruction (as is the case for
+    /// the entrypoint's frame), and after `max_depth` entries, so that corrupted frames can't loop.
+    pub fn get_traceback_entries(&self, max_depth: usize) -> Vec<TracebackEntry> {
+        let mut entries = Vec::<TracebackEntry>::new();
         let mut fp = Relocatable::from((1, self.run_context.fp));
-        // Fetch the fp and pc traceback entries
-        for _ in 0..MAX_TRACEBACK_ENTRIES {
-            // Get return pc
-            let ret_pc = match fp
-                .sub_usize(1)
-                .ok()
-                .map(|ref r| self.memory.get_relocatable(r))
-            {
-                Some(Ok(opt_pc)) => opt_pc,
+        while entries.len() < max_depth {
+            let (return_pc, caller_fp) = match (
+                fp.sub_usize(1)
+                    .and_then(|ref r| self.memory.get_relocatable(r)),
+                fp.sub_usize(2)
+                    .and_then(|ref r| self.memory.get_relocatable(r)),
+            ) {
+                (Ok(return_pc), Ok(caller_fp)) if caller_fp != fp => (return_pc, caller_fp),
                 _ => break,
             };
-            // Get fp traceback
-            match fp
-                .sub_usize(2)
-                .ok()
-                .map(|ref r| self.memory.get_relocatable(r))
-            {
-                Some(Ok(opt_fp)) if opt_fp != fp => fp = opt_fp,
-                _ => break,
-            }
-            // Try to check if the call instruction is (instruction0, instruction1) or just
-            // instruction1 (with no immediate).
-            let call_pc = match ret_pc
-                .sub_usize(1)
-                .ok()
-                .map(|ref r| self.memory.get_integer(r))
-            {
-                Some(Ok(instruction1)) => {
-                    match is_call_instruction(&instruction1, None) {
-                        true => ret_pc.sub_usize(1).unwrap(), // This unwrap wont fail as it is checked before
-                        false => {
-                            match ret_pc
-                                .sub_usize(2)
-                                .ok()
-                                .map(|ref r| self.memory.get_integer(r))
-                            {
-                                Some(Ok(instruction0)) => {
-                                    match is_call_instruction(&instruction0, Some(&instruction1)) {
-                                        true => ret_pc.sub_usize(2).unwrap(), // This unwrap wont fail as it is checked before
-                                        false => break,
-                                    }
-                                }
-                                _ => break,
-                            }
-                        }
-                    }
-                }
-                _ => break,
+            let call_pc = match self.get_call_pc(&return_pc) {
+                Some(call_pc) => call_pc,
+                None => break,
             };
-            // Append traceback entries
-            entries.push((fp, call_pc))
+            fp = caller_fp;
+            entries.push(TracebackEntry {
+                fp,
+                return_pc,
+                call_pc,
+            })
         }
         entries.reverse();
         entries
     }
 
+    // Returns the pc of the call instruction preceding return_pc, which can be either
+    // (instruction0, instruction1) or just instruction1 (with no immediate).
+    fn get_call_pc(&self, return_pc: &Relocatable) -> Option<Relocatable> {
+        let instruction1_pc = return_pc.sub_usize(1).ok()?;
+        let instruction1 = self.memory.get_integer(&instruction1_pc).ok()?;
+        if is_call_instruction(&instruction1, None) {
+            return Some(instruction1_pc);
+        }
+        let instruction0_pc = return_pc.sub_usize(2).ok()?;
+        let instruction0 = self.memory.get_integer(&instruction0_pc).ok()?;
+        is_call_instruction(&instruction0, Some(&instruction1)).then_some(instruction0_pc)
+    }
+
     ///Adds a new segment and to the VirtualMachine.memory returns its starting location as a RelocatableValue.
     pub fn add_memory_segment(&mut self) -> Relocatable {
         self.segments.add(&mut self.memory)
@@ -788,6 +1173,20 @@ impl VirtualMachine {
         self.run_context.get_pc()
     }
 
+    /// Returns the number of steps executed since the start of the run.
+    pub fn get_current_step(&self) -> usize {
+        self.current_step
+    }
+
+    ///Returns the (dst, op0, op1) operands of the last ASSERT_EQ instruction that failed, if any.
+    pub fn get_last_assert_operands(
+        &self,
+    ) -> Option<(&MaybeRelocatable, &MaybeRelocatable, &MaybeRelocatable)> {
+        self.last_assert_operands
+            .as_ref()
+            .map(|(dst, op0, op1)| (dst, op0, op1))
+    }
+
     ///Gets the integer value corresponding to the Relocatable address
     pub fn get_integer(&self, key: &Relocatable) -> Result<Cow<Felt>, VirtualMachineError> {
         self.memory.get_integer(key)
@@ -818,16 +1217,51 @@ impl VirtualMachine {
         &self.builtin_runners
     }
 
+    /// Moves the builtins located in temporary segments to the segments these are relocated to.
+    /// Must be called before relocating the memory, as it relies on its relocation rules.
+    /// Builtins whose temporary segment has no relocation rule are left untouched.
+    pub(crate) fn relocate_temporary_builtin_segments(&mut self) -> Result<(), MemoryError> {
+        for (_, builtin) in self.builtin_runners.iter_mut() {
+            let base = builtin.base();
+            if base >= 0 {
+                continue;
+            }
+            let new_base = match self.memory.relocation_rules.get(&(-(base + 1) as usize)) {
+                Some(new_base) if new_base.offset == 0 => new_base.segment_index,
+                Some(new_base) => {
+                    return Err(MemoryError::BuiltinSegmentRelocatedWithOffset(
+                        base, *new_base,
+                    ))
+                }
+                None => continue,
+            };
+            builtin.relocate_base(new_base);
+            self.builtin_segment_lookup = None;
+        }
+        Ok(())
+    }
+
     pub fn get_builtin_runners_as_mut(&mut self) -> &mut Vec<(String, BuiltinRunner)> {
+        self.builtin_segment_lookup = None;
         &mut self.builtin_runners
     }
 
     ///Inserts a value into a memory address given by a Relocatable value
+    ///A value written into a builtin cell which the builtin can already deduce from its inputs
+    ///must be the deduced one, otherwise it fails with `MemoryError::InconsistentMemory`.
     pub fn insert_value<T: Into<MaybeRelocatable>>(
         &mut self,
         key: &Relocatable,
         val: T,
     ) -> Result<(), VirtualMachineError> {
+        let val = val.into();
+        if let Some(builtin) = self.get_deducing_builtin(key.segment_index) {
+            if let Some(deduced) = builtin.deduce_memory_cell_uncached(key, &self.memory)? {
+                if deduced != val {
+                    return Err(MemoryError::InconsistentMemory(key.into(), deduced, val).into());
+                }
+            }
+        }
         self.memory.insert_value(key, val)
     }
 
@@ -852,11 +1286,10 @@ impl VirtualMachine {
 
     ///Gets `n_ret` return values from memory
     pub fn get_return_values(&self, n_ret: usize) -> Result<Vec<MaybeRelocatable>, MemoryError> {
-        let addr = &self
-            .run_context
-            .get_ap()
+        let ap = self.run_context.get_ap();
+        let addr = &ap
             .sub_usize(n_ret)
-            .map_err(|_| MemoryError::NumOutOfBounds)?;
+            .map_err(|_| MemoryError::ReturnValuesOutOfBounds { ap, n_ret })?;
         self.memory.get_continuous_range(&addr.into(), n_ret)
     }
 
@@ -878,6 +1311,46 @@ impl VirtualMachine {
         self.memory.get_continuous_range(addr, size)
     }
 
+    ///Like `get_continuous_range`, but the missing cells of a builtin segment are deduced by the
+    ///builtin before reporting a gap, e.g. the results of the last instances of a builtin, which
+    ///the program never read. The memory is left untouched, see `materialize_range`.
+    pub fn get_range_with_deduction(
+        &self,
+        addr: &Relocatable,
+        size: usize,
+    ) -> Result<Vec<MaybeRelocatable>, VirtualMachineError> {
+        let mut values = Vec::with_capacity(size);
+        for i in 0..size {
+            let cell_addr = addr + i;
+            let value = match self.memory.get(&cell_addr)? {
+                Some(value) => value.into_owned(),
+                None => self
+                    .get_deducing_builtin(cell_addr.segment_index)
+                    .map(|builtin| builtin.deduce_memory_cell_uncached(&cell_addr, &self.memory))
+                    .transpose()?
+                    .flatten()
+                    .ok_or(MemoryError::GetRangeMemoryGap)?,
+            };
+            values.push(value);
+        }
+        Ok(values)
+    }
+
+    ///Like `get_range_with_deduction`, but also inserts the deduced cells into the memory.
+    pub fn materialize_range(
+        &mut self,
+        addr: &Relocatable,
+        size: usize,
+    ) -> Result<Vec<MaybeRelocatable>, VirtualMachineError> {
+        let values = self.get_range_with_deduction(addr, size)?;
+        for (i, value) in values.iter().enumerate() {
+            if self.memory.get(&(addr + i))?.is_none() {
+                self.memory.insert(&(addr + i), value)?;
+            }
+        }
+        Ok(values)
+    }
+
     ///Gets n integer values from memory starting from addr (n being size),
     pub fn get_integer_range(
         &self,
@@ -889,7 +1362,7 @@ impl VirtualMachine {
 
     pub fn get_range_check_builtin(&self) -> Result<&RangeCheckBuiltinRunner, VirtualMachineError> {
         for (name, builtin) in &self.builtin_runners {
-            if name == &String::from("range_check") {
+            if name == RangeCheckBuiltinRunner::NAME {
                 if let BuiltinRunner::RangeCheck(range_check_builtin) = builtin {
                     return Ok(range_check_builtin);
                 };
@@ -901,8 +1374,8 @@ impl VirtualMachine {
     pub fn get_signature_builtin(
         &mut self,
     ) -> Result<&mut SignatureBuiltinRunner, VirtualMachineError> {
-        for (name, builtin) in self.get_builtin_runners_as_mut() {
-            if name == &String::from("ecdsa") {
+        for (name, builtin) in self.builtin_runners.iter_mut() {
+            if name == SignatureBuiltinRunner::NAME {
                 if let BuiltinRunner::Signature(signature_builtin) = builtin {
                     return Ok(signature_builtin);
                 };
@@ -911,6 +1384,20 @@ impl VirtualMachine {
 
         Err(VirtualMachineError::NoSignatureBuiltin)
     }
+
+    /// Gives mutable access to the output builtin, so hints can swap its state
+    /// (e.g. redirect the output to a temporary segment and restore it afterwards).
+    pub fn get_output_builtin(&mut self) -> Result<&mut OutputBuiltinRunner, VirtualMachineError> {
+        for (name, builtin) in self.builtin_runners.iter_mut() {
+            if name == OutputBuiltinRunner::NAME {
+                if let BuiltinRunner::Output(output_builtin) = builtin {
+                    return Ok(output_builtin);
+                };
+            }
+        }
+
+        Err(VirtualMachineError::NoOutputBuiltin)
+    }
     pub fn disable_trace(&mut self) {
         self.trace = None
     }
@@ -970,6 +1457,29 @@ impl VirtualMachine {
     }
 }
 
+// Reports an executed hint. The code is identified by its hash, as hints can span many lines,
+// and only known for the hints of the BuiltinHintProcessor.
+#[cfg(feature = "tracing")]
+fn trace_hint(
+    pc: &impl std::fmt::Display,
+    hint_index: usize,
+    hint_data: &Box<dyn Any>,
+    result: &Result<(), crate::vm::errors::hint_errors::HintError>,
+) {
+    use crate::hint_processor::builtin_hint_processor::builtin_hint_processor_definition::HintProcessorData;
+    use std::hash::{Hash, Hasher};
+
+    let code_hash = hint_data.downcast_ref::<HintProcessorData>().map(|data| {
+        let mut hasher = std::collections::hash_map::DefaultHasher::new();
+        data.code.hash(&mut hasher);
+        format!("{:016x}", hasher.finish())
+    });
+    match result {
+        Ok(()) => tracing::debug!(%pc, hint_index, code_hash, "hint executed"),
+        Err(error) => tracing::warn!(%pc, hint_index, code_hash, %error, "hint failed"),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -982,6 +1492,7 @@ mod tests {
         types::{
             instance_definitions::{
                 bitwise_instance_def::BitwiseInstanceDef, ec_op_instance_def::EcOpInstanceDef,
+                pedersen_instance_def::PedersenInstanceDef,
             },
             instruction::{Op1Addr, Register},
             program::Program,
@@ -989,15 +1500,18 @@ mod tests {
         },
         utils::test_utils::*,
         vm::{
-            errors::memory_errors::MemoryError,
+            errors::{memory_errors::MemoryError, runner_errors::RunnerError},
             runners::{
-                builtin_runner::{BitwiseBuiltinRunner, EcOpBuiltinRunner, HashBuiltinRunner},
+                builtin_runner::{
+                    BitwiseBuiltinRunner, EcOpBuiltinRunner, HashBuiltinRunner, SegmentAddresses,
+                },
                 cairo_runner::CairoRunner,
             },
         },
     };
 
     use felt::{felt_str, NewFelt};
+    use num_traits::One;
     use std::{collections::HashSet, path::Path};
 
     #[test]
@@ -1005,7 +1519,7 @@ mod tests {
         let mut vm = vm!();
         vm.memory = memory![((0, 0), 5)];
         assert_eq!((Felt::new(5), None), {
-            let value = vm.get_instruction_encoding().unwrap();
+            let value = vm.get_instruction_encoding(&vm.run_context.pc).unwrap();
             (value.0.into_owned(), value.1)
         });
     }
@@ -1017,7 +1531,7 @@ mod tests {
         vm.memory = memory![((0, 0), 5), ((0, 1), 6)];
 
         let (num, imm) = vm
-            .get_instruction_encoding()
+            .get_instruction_encoding(&vm.run_context.pc)
             .expect("Unexpected error on get_instruction_encoding");
         assert_eq!(num.as_ref(), &Felt::new(5));
         assert_eq!(
@@ -1030,7 +1544,7 @@ mod tests {
     fn get_instruction_encoding_unsuccesful() {
         let vm = vm!();
         assert_eq!(
-            vm.get_instruction_encoding(),
+            vm.get_instruction_encoding(&vm.run_context.pc),
             Err(VirtualMachineError::InvalidInstructionEncoding)
         );
     }
@@ -2444,7 +2958,8 @@ mod tests {
                 &mut hint_processor,
                 exec_scopes_ref!(),
                 &HashMap::new(),
-                &HashMap::new()
+                &HashMap::new(),
+                &mut RunResources::default()
             ),
             Ok(())
         );
@@ -2683,7 +3198,8 @@ mod tests {
                 &mut hint_processor,
                 exec_scopes_ref!(),
                 &HashMap::new(),
-                &HashMap::new()
+                &HashMap::new(),
+                &mut RunResources::default()
             ),
             Ok(())
         );
@@ -2763,7 +3279,8 @@ mod tests {
                     &mut hint_processor,
                     exec_scopes_ref!(),
                     &HashMap::new(),
-                    &HashMap::new()
+                    &HashMap::new(),
+                    &mut RunResources::default()
                 ),
                 Ok(())
             );
@@ -2859,7 +3376,8 @@ mod tests {
                 &mut hint_processor,
                 exec_scopes_ref!(),
                 &HashMap::new(),
-                &HashMap::new()
+                &HashMap::new(),
+                &mut RunResources::default()
             ),
             Ok(())
         );
@@ -2880,7 +3398,8 @@ mod tests {
                 &mut hint_processor,
                 exec_scopes_ref!(),
                 &HashMap::new(),
-                &HashMap::new()
+                &HashMap::new(),
+                &mut RunResources::default()
             ),
             Ok(())
         );
@@ -2902,7 +3421,8 @@ mod tests {
                 &mut hint_processor,
                 exec_scopes_ref!(),
                 &HashMap::new(),
-                &HashMap::new()
+                &HashMap::new(),
+                &mut RunResources::default()
             ),
             Ok(())
         );
@@ -2928,7 +3448,7 @@ mod tests {
     #[test]
     fn deduce_memory_cell_pedersen_builtin_valid() {
         let mut vm = vm!();
-        let builtin = HashBuiltinRunner::new(8, true);
+        let builtin = HashBuiltinRunner::new(&PedersenInstanceDef::default(), true);
         vm.builtin_runners
             .push((String::from("pedersen"), builtin.into()));
         vm.memory = memory![((0, 3), 32), ((0, 4), 72), ((0, 5), 0)];
@@ -2940,6 +3460,196 @@ mod tests {
         );
     }
 
+    #[test]
+    fn deduce_memory_cells_while_iterating_builtins() {
+        let mut vm = vm!();
+        let builtin = HashBuiltinRunner::new(&PedersenInstanceDef::default(), true);
+        vm.builtin_runners
+            .push((String::from("pedersen"), builtin.into()));
+        vm.memory = memory![((0, 0), 32), ((0, 1), 72), ((0, 3), 32), ((0, 4), 72)];
+        let vm = vm;
+
+        // Deducing only needs shared access to the builtins, so the VM can be inspected while
+        // they are being iterated
+        let deductions = vm
+            .get_builtin_runners()
+            .iter()
+            .flat_map(|(_, builtin)| {
+                [2, 5].map(|offset| builtin.deduce_memory_cell(&(0, offset).into(), &vm.memory))
+            })
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        let hash = MaybeRelocatable::from(felt::felt_str!(
+            "3270867057177188607814717243084834301278723532952411121381966378910183338911"
+        ));
+        assert_eq!(deductions, vec![Some(hash.clone()), Some(hash)]);
+        assert_eq!(vm.verify_auto_deductions(), Ok(()));
+    }
+
+    #[test]
+    fn get_range_with_deduction_pedersen_last_result_never_read() {
+        let mut vm = vm!();
+        let builtin = HashBuiltinRunner::new(&PedersenInstanceDef::default(), true);
+        vm.builtin_runners
+            .push((String::from("pedersen"), builtin.into()));
+        let hash = MaybeRelocatable::from(felt::felt_str!(
+            "3270867057177188607814717243084834301278723532952411121381966378910183338911"
+        ));
+        // The program read the result of the first instance, but not the one of the second
+        vm.memory = memory![
+            ((0, 0), 32),
+            ((0, 1), 72),
+            ((0, 2), 0),
+            ((0, 3), 32),
+            ((0, 4), 72)
+        ];
+        vm.memory.data[0][2] = Some(hash.clone());
+        let expected = vec![
+            MaybeRelocatable::from(32),
+            MaybeRelocatable::from(72),
+            hash.clone(),
+            MaybeRelocatable::from(32),
+            MaybeRelocatable::from(72),
+            hash,
+        ];
+        assert_eq!(
+            vm.get_continuous_range(&MaybeRelocatable::from((0, 0)), 6),
+            Err(MemoryError::GetRangeMemoryGap)
+        );
+        // Deducing the result doesn't insert it, nor prevents deducing it again
+        for _ in 0..2 {
+            assert_eq!(
+                vm.get_range_with_deduction(&Relocatable::from((0, 0)), 6),
+                Ok(expected.clone())
+            );
+            assert_eq!(vm.memory.get(&Relocatable::from((0, 5))), Ok(None));
+        }
+
+        assert_eq!(
+            vm.materialize_range(&Relocatable::from((0, 0)), 6),
+            Ok(expected.clone())
+        );
+        assert_eq!(
+            vm.get_continuous_range(&MaybeRelocatable::from((0, 0)), 6),
+            Ok(expected)
+        );
+    }
+
+    #[test]
+    fn get_range_with_deduction_gap() {
+        let mut vm = vm!();
+        let builtin = HashBuiltinRunner::new(&PedersenInstanceDef::default(), true);
+        vm.builtin_runners
+            .push((String::from("pedersen"), builtin.into()));
+        // The result of the instance can't be deduced without its second input
+        vm.memory = memory![((0, 0), 32), ((1, 0), 1)];
+        assert_eq!(
+            vm.get_range_with_deduction(&Relocatable::from((0, 0)), 3),
+            Err(VirtualMachineError::MemoryError(
+                MemoryError::GetRangeMemoryGap
+            ))
+        );
+        // Cells outside of builtin segments aren't deduced
+        assert_eq!(
+            vm.get_range_with_deduction(&Relocatable::from((1, 0)), 2),
+            Err(VirtualMachineError::MemoryError(
+                MemoryError::GetRangeMemoryGap
+            ))
+        );
+    }
+
+    #[test]
+    fn deduce_memory_cell_pedersen_builtin_indexed() {
+        let mut vm = vm!();
+        let output_builtin = OutputBuiltinRunner::new(true);
+        let mut pedersen_builtin = HashBuiltinRunner::new(&PedersenInstanceDef::default(), true);
+        pedersen_builtin.base = 3;
+        vm.builtin_runners = vec![
+            (String::from("output"), output_builtin.into()),
+            (String::from("pedersen"), pedersen_builtin.into()),
+        ];
+        vm.index_builtin_segments();
+        vm.memory = memory![((3, 0), 32), ((3, 1), 72), ((3, 2), 0)];
+        assert_eq!(
+            vm.deduce_memory_cell(&Relocatable::from((3, 2))),
+            Ok(Some(MaybeRelocatable::from(felt::felt_str!(
+                "3270867057177188607814717243084834301278723532952411121381966378910183338911"
+            ))))
+        );
+        assert_eq!(vm.deduce_memory_cell(&Relocatable::from((1, 2))), Ok(None));
+        // The output builtin (at its default base) is left out of the index
+        assert_eq!(vm.deduce_memory_cell(&Relocatable::from((0, 0))), Ok(None));
+    }
+
+    #[test]
+    fn deduce_memory_cell_builtin_moved_after_indexing() {
+        let mut vm = vm!();
+        let mut pedersen_builtin = HashBuiltinRunner::new(&PedersenInstanceDef::default(), true);
+        pedersen_builtin.base = 3;
+        vm.builtin_runners = vec![(String::from("pedersen"), pedersen_builtin.into())];
+        vm.index_builtin_segments();
+        if let BuiltinRunner::Hash(builtin) = &mut vm.get_builtin_runners_as_mut()[0].1 {
+            builtin.base = 4;
+        }
+        vm.memory = memory![((4, 0), 32), ((4, 1), 72), ((4, 2), 0)];
+        assert_eq!(vm.deduce_memory_cell(&Relocatable::from((3, 2))), Ok(None));
+        assert!(matches!(
+            vm.deduce_memory_cell(&Relocatable::from((4, 2))),
+            Ok(Some(_))
+        ));
+    }
+
+    #[test]
+    fn verify_auto_deductions_bitwise_inconsistent_and() {
+        let builtin = BitwiseBuiltinRunner::new(&BitwiseInstanceDef::default(), true);
+        let mut vm = vm!();
+        vm.builtin_runners
+            .push((String::from("bitwise"), builtin.into()));
+        vm.memory = memory![((0, 0), 12), ((0, 1), 10), ((0, 2), 0)];
+        assert_eq!(
+            vm.verify_auto_deductions(),
+            Err(VirtualMachineError::InconsistentAutoDeduction(
+                String::from("bitwise"),
+                mayberelocatable!(8),
+                Some(mayberelocatable!(0))
+            ))
+        );
+    }
+
+    #[test]
+    fn verify_auto_deductions_collect_errors_caps_reported_mismatches() {
+        let builtin = BitwiseBuiltinRunner::new(&BitwiseInstanceDef::default(), true);
+        let mut vm = vm!();
+        vm.builtin_runners
+            .push((String::from("bitwise"), builtin.into()));
+        vm.segments.add(&mut vm.memory);
+        // 25 instances whose x & y cell holds 0 instead of 8
+        for i in 0..25 {
+            vm.insert_value(&(0, 5 * i).into(), Felt::new(12)).unwrap();
+            vm.insert_value(&(0, 5 * i + 1).into(), Felt::new(10))
+                .unwrap();
+            vm.memory
+                .insert_value(&(0, 5 * i + 2).into(), Felt::zero())
+                .unwrap();
+        }
+        let mismatches = (0..MAX_REPORTED_AUTO_DEDUCTION_MISMATCHES)
+            .map(|i| AutoDeductionMismatch {
+                builtin: String::from("bitwise"),
+                address: (0, 5 * i + 2).into(),
+                expected: mayberelocatable!(8),
+                actual: mayberelocatable!(0),
+            })
+            .collect::<Vec<_>>();
+        let error = vm.verify_auto_deductions_collect_errors().unwrap_err();
+        assert!(error
+            .to_string()
+            .ends_with("bitwise builtin at 0:97: expected 8, got 0\n    ... and 5 more"));
+        assert_eq!(
+            error,
+            VirtualMachineError::InconsistentAutoDeductions(mismatches, 25)
+        );
+    }
+
     #[test]
     /* Program used:
     %builtins output pedersen
@@ -2979,7 +3689,7 @@ mod tests {
             fp_update: FpUpdate::Regular,
             opcode: Opcode::AssertEq,
         };
-        let mut builtin = HashBuiltinRunner::new(8, true);
+        let mut builtin = HashBuiltinRunner::new(&PedersenInstanceDef::default(), true);
         builtin.base = 3;
         let mut vm = vm!();
         vm.accessed_addresses = Some(Vec::new());
@@ -3028,6 +3738,40 @@ mod tests {
         assert_eq!(operands_mem_address, expected_operands_mem_addresses);
     }
 
+    #[test]
+    fn insert_value_into_deducible_pedersen_output() {
+        let mut builtin = HashBuiltinRunner::new(&PedersenInstanceDef::default(), true);
+        builtin.base = 3;
+        let mut vm = vm!();
+        vm.builtin_runners
+            .push((String::from("pedersen"), builtin.into()));
+        vm.memory = memory![((3, 0), 32), ((3, 1), 72), ((3, 3), 32)];
+        let hash = MaybeRelocatable::from(felt_str!(
+            "3270867057177188607814717243084834301278723532952411121381966378910183338911"
+        ));
+
+        // A hint writing a value other than the one deduced from the inputs fails right away,
+        // before any instruction reads the cell
+        assert_eq!(
+            vm.insert_value(&(3, 2).into(), Felt::new(7)),
+            Err(VirtualMachineError::MemoryError(
+                MemoryError::InconsistentMemory(
+                    MaybeRelocatable::from((3, 2)),
+                    hash.clone(),
+                    MaybeRelocatable::from(Felt::new(7))
+                )
+            ))
+        );
+        assert_eq!(vm.memory.get(&Relocatable::from((3, 2))), Ok(None));
+        assert_eq!(vm.insert_value(&(3, 2).into(), hash.clone()), Ok(()));
+        assert_eq!(
+            vm.memory.get(&Relocatable::from((3, 2))),
+            Ok(Some(Cow::Owned(hash)))
+        );
+        // Without all of its inputs, the output can't be deduced yet and isn't checked
+        assert_eq!(vm.insert_value(&(3, 5).into(), Felt::new(7)), Ok(()));
+    }
+
     #[test]
     fn deduce_memory_cell_bitwise_builtin_valid_and() {
         let mut vm = vm!();
@@ -3109,6 +3853,48 @@ mod tests {
         assert_eq!(operands_mem_address, expected_operands_mem_addresses);
     }
 
+    #[test]
+    fn step_bitwise_input_over_the_limit() {
+        let mut builtin = BitwiseBuiltinRunner::new(&BitwiseInstanceDef::default(), true);
+        builtin.base = 2;
+        let mut vm = vm!();
+        vm.builtin_runners
+            .push((String::from("bitwise"), builtin.into()));
+        run_context!(vm, 0, 9, 8);
+
+        //[ap] = [[fp - 5] + 2]; ap++, with the instance's x input over 2^251
+        vm.memory = memory![
+            ((0, 0), 4900620092757999616_u64),
+            (
+                (2, 0),
+                (
+                    "3618502788666131106986593281521497120414687020801267626233049500247285301248",
+                    10
+                )
+            ),
+            ((2, 1), 10),
+            ((1, 3), (2, 0))
+        ];
+
+        let mut hint_processor = BuiltinHintProcessor::new_empty();
+        assert_eq!(
+            vm.step(
+                &mut hint_processor,
+                exec_scopes_ref!(),
+                &HashMap::new(),
+                &HashMap::new(),
+                &mut RunResources::default()
+            ),
+            Err(VirtualMachineError::RunnerError(
+                RunnerError::IntegerBiggerThanPowerOfTwo(
+                    MaybeRelocatable::from((2, 0)),
+                    251,
+                    Felt::one() << 251_usize
+                )
+            ))
+        );
+    }
+
     #[test]
     fn deduce_memory_cell_ec_op_builtin_valid() {
         let mut vm = vm!();
@@ -3337,7 +4123,7 @@ mod tests {
     end
      */
     fn verify_auto_deductions_pedersen() {
-        let mut builtin = HashBuiltinRunner::new(8, true);
+        let mut builtin = HashBuiltinRunner::new(&PedersenInstanceDef::default(), true);
         builtin.base = 3;
         let mut vm = vm!();
         vm.builtin_runners
@@ -3364,10 +4150,13 @@ mod tests {
     fn get_return_values_fails_when_ap_is_0() {
         let mut vm = vm!();
         vm.memory = memory![((1, 0), 1), ((1, 1), 2), ((1, 2), 3), ((1, 3), 4)];
-        assert!(matches!(
+        assert_eq!(
             vm.get_return_values(3),
-            Err(MemoryError::NumOutOfBounds)
-        ));
+            Err(MemoryError::ReturnValuesOutOfBounds {
+                ap: Relocatable::from((1, 0)),
+                n_ret: 3,
+            })
+        );
     }
 
     /*
@@ -3395,7 +4184,7 @@ mod tests {
     fn test_step_for_preset_memory_with_alloc_hint() {
         let mut vm = vm!(true);
         let hint_data_dictionary = HashMap::from([(
-            0_usize,
+            Relocatable::from((0, 0)),
             vec![any_box!(HintProcessorData::new_default(
                 "memory[ap] = segments.add()".to_string(),
                 HashMap::new(),
@@ -3440,7 +4229,8 @@ mod tests {
                     &mut hint_processor,
                     exec_scopes_ref!(),
                     &hint_data_dictionary,
-                    &HashMap::new()
+                    &HashMap::new(),
+                    &mut RunResources::default()
                 ),
                 Ok(())
             );
@@ -3475,7 +4265,7 @@ mod tests {
     #[test]
     fn test_get_builtin_runners() {
         let mut vm = vm!();
-        let hash_builtin = HashBuiltinRunner::new(8, true);
+        let hash_builtin = HashBuiltinRunner::new(&PedersenInstanceDef::default(), true);
         let bitwise_builtin = BitwiseBuiltinRunner::new(&BitwiseInstanceDef::default(), true);
         vm.builtin_runners
             .push((String::from("pedersen"), hash_builtin.into()));
@@ -3488,6 +4278,163 @@ mod tests {
         assert_eq!(builtins[1].0, "bitwise");
     }
 
+    #[test]
+    fn get_output_builtin_missing() {
+        let mut vm = vm!();
+        assert_eq!(
+            vm.get_output_builtin().unwrap_err(),
+            VirtualMachineError::NoOutputBuiltin
+        );
+    }
+
+    #[test]
+    fn swap_output_builtin_to_temp_segment_and_restore() {
+        let mut vm = vm!();
+        let mut output_builtin = OutputBuiltinRunner::new(true);
+        output_builtin
+            .initialize_segments(&mut vm.segments, &mut vm.memory)
+            .unwrap();
+        vm.builtin_runners
+            .push((String::from("output"), output_builtin.into()));
+
+        let original_state = vm.get_output_builtin().unwrap().get_state();
+        let temp_segment = vm.add_temporary_segment();
+        assert_eq!(temp_segment, Relocatable::from((-1, 0)));
+
+        let mut temp_state = original_state.clone();
+        temp_state.base = temp_segment.segment_index;
+        vm.get_output_builtin().unwrap().set_state(temp_state);
+        let base = vm.get_output_builtin().unwrap().base();
+        assert_eq!(base, -1);
+        vm.insert_value(&Relocatable::from((base, 0)), Felt::new(7))
+            .unwrap();
+        vm.insert_value(&Relocatable::from((base, 1)), Felt::new(8))
+            .unwrap();
+
+        vm.get_output_builtin()
+            .unwrap()
+            .set_state(original_state.clone());
+        let base = vm.get_output_builtin().unwrap().base();
+        vm.insert_value(&Relocatable::from((base, 0)), Felt::new(1))
+            .unwrap();
+        vm.get_builtin_runners_as_mut()[0]
+            .1
+            .set_stop_ptr(Relocatable::from((base, 1)))
+            .unwrap();
+
+        // The output written while swapped lives in the temporary segment, apart from the
+        // output segment
+        check_memory!(vm.memory, ((0, 0), 1), ((-1, 0), 7), ((-1, 1), 8));
+        assert_eq!(vm.memory.get(&MaybeRelocatable::from((0, 1))), Ok(None));
+        assert_eq!(vm.memory.data.len(), 1);
+        assert_eq!(vm.memory.temp_data.len(), 1);
+        assert_eq!(
+            vm.get_builtin_runners()[0].1.get_memory_segment_addresses(),
+            vec![SegmentAddresses::new(
+                "output",
+                original_state.base,
+                Some(1)
+            )]
+        );
+        assert_eq!(
+            vm.get_builtin_runners_as_mut()[0]
+                .1
+                .set_stop_ptr(Relocatable::from((temp_segment.segment_index, 2))),
+            Err(RunnerError::StopPointerSegmentMismatch(
+                "output",
+                Relocatable::from((-1, 2)),
+                0
+            ))
+        );
+
+        // Until it's relocated into a real segment
+        let task_output = vm.segments.add(&mut vm.memory);
+        vm.add_relocation_rule(temp_segment, task_output).unwrap();
+        vm.memory.relocate_memory().unwrap();
+        check_memory!(vm.memory, ((0, 0), 1), ((1, 0), 7), ((1, 1), 8));
+        assert!(vm.memory.temp_data.is_empty());
+    }
+
+    #[test]
+    fn iter_trace_with_instructions() {
+        let mut vm = vm!(true);
+        run_context!(vm, 3, 2, 2);
+        // The function call program of test_step_for_preset_memory_function_call
+        vm.memory =
+            memory![
+            ((0, 0), 5207990763031199744_i64),
+            ((0, 1), 2),
+            ((0, 2), 2345108766317314046_i64),
+            ((0, 3), 5189976364521848832_i64),
+            ((0, 4), 1),
+            ((0, 5), 1226245742482522112_i64),
+            (
+                (0, 6),
+                ("3618502788666131213697322783095070105623107215331596699973092056135872020476",10)
+            ),
+            ((0, 7), 2345108766317314046_i64),
+            ((1, 0), (2, 0)),
+            ((1, 1), (3, 0))
+        ];
+        let mut hint_processor = BuiltinHintProcessor::new_empty();
+        while vm.run_context.pc != Relocatable::from((3, 0)) {
+            vm.step(
+                &mut hint_processor,
+                exec_scopes_ref!(),
+                &HashMap::new(),
+                &HashMap::new(),
+                &mut RunResources::default(),
+            )
+            .unwrap();
+        }
+
+        let steps: Vec<ExecutedStep> = vm
+            .iter_trace_with_instructions()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(
+            steps
+                .iter()
+                .map(|step| (step.registers.pc, &step.instruction.opcode))
+                .collect::<Vec<_>>(),
+            vec![
+                (Relocatable::from((0, 3)), &Opcode::AssertEq),
+                (Relocatable::from((0, 5)), &Opcode::Call),
+                (Relocatable::from((0, 0)), &Opcode::AssertEq),
+                (Relocatable::from((0, 2)), &Opcode::Ret),
+                (Relocatable::from((0, 7)), &Opcode::Ret),
+            ]
+        );
+        // [ap] = [fp - 3] * 2; ap++
+        assert_eq!(
+            (&steps[2].dst, &steps[2].op0, &steps[2].op1, &steps[2].res),
+            (
+                &mayberelocatable!(2),
+                &mayberelocatable!(1),
+                &mayberelocatable!(2),
+                &Some(mayberelocatable!(2))
+            )
+        );
+        assert_eq!(
+            steps[4].registers,
+            TraceEntry {
+                pc: (0, 7).into(),
+                ap: (1, 6).into(),
+                fp: (1, 2).into(),
+            }
+        );
+        assert_eq!(steps[4].dst, mayberelocatable!(2, 0));
+    }
+
+    #[test]
+    fn iter_trace_with_instructions_trace_disabled() {
+        let vm = vm!();
+        assert_eq!(
+            vm.iter_trace_with_instructions().collect::<Vec<_>>(),
+            vec![Err(TraceError::TraceNotEnabled)]
+        );
+    }
+
     #[test]
     fn disable_trace() {
         let mut vm = VirtualMachine::new(true);
@@ -3881,14 +4828,25 @@ mod tests {
 
         let end = cairo_runner.initialize(&mut vm).unwrap();
         assert!(cairo_runner
-            .run_until_pc(end, &mut vm, &mut hint_processor)
+            .run_until_pc(
+                end,
+                &mut RunResources::default(),
+                &mut vm,
+                &mut hint_processor
+            )
             .is_err());
         let expected_traceback = vec![
             (Relocatable::from((1, 3)), Relocatable::from((0, 97))),
             (Relocatable::from((1, 14)), Relocatable::from((0, 30))),
             (Relocatable::from((1, 26)), Relocatable::from((0, 60))),
         ];
-        assert_eq!(vm.get_traceback_entries(), expected_traceback);
+        assert_eq!(
+            vm.get_traceback_entries(MAX_TRACEBACK_ENTRIES)
+                .into_iter()
+                .map(|entry| (entry.fp, entry.call_pc))
+                .collect::<Vec<_>>(),
+            expected_traceback
+        );
     }
 
     #[test]
@@ -3905,9 +4863,144 @@ mod tests {
 
         let end = cairo_runner.initialize(&mut vm).unwrap();
         assert!(cairo_runner
-            .run_until_pc(end, &mut vm, &mut hint_processor)
+            .run_until_pc(
+                end,
+                &mut RunResources::default(),
+                &mut vm,
+                &mut hint_processor
+            )
             .is_err());
         let expected_traceback = vec![(Relocatable::from((1, 2)), Relocatable::from((0, 34)))];
-        assert_eq!(vm.get_traceback_entries(), expected_traceback);
+        assert_eq!(
+            vm.get_traceback_entries(MAX_TRACEBACK_ENTRIES)
+                .into_iter()
+                .map(|entry| (entry.fp, entry.call_pc))
+                .collect::<Vec<_>>(),
+            expected_traceback
+        );
+    }
+
+    // Builds the frames of a three-deep call chain, each call being `call rel 2` at
+    // pcs 0, 2 and 4, with the innermost frame at fp = (1, 8)
+    fn vm_with_call_chain() -> VirtualMachine {
+        let mut vm = vm!();
+        let call_instruction = 1226245742482522112_i64;
+        vm.memory = memory![
+            ((0, 0), call_instruction),
+            ((0, 1), 2),
+            ((0, 2), call_instruction),
+            ((0, 3), 2),
+            ((0, 4), call_instruction),
+            ((0, 5), 2),
+            ((1, 2), (1, 2)),
+            ((1, 3), (0, 2)),
+            ((1, 4), (1, 4)),
+            ((1, 5), (0, 4)),
+            ((1, 6), (1, 6)),
+            ((1, 7), (0, 6))
+        ];
+        vm.run_context.fp = 8;
+        vm
+    }
+
+    #[test]
+    fn get_traceback_entries_call_chain() {
+        let vm = vm_with_call_chain();
+        assert_eq!(
+            vm.get_traceback_entries(MAX_TRACEBACK_ENTRIES),
+            vec![
+                TracebackEntry {
+                    fp: relocatable!(1, 2),
+                    return_pc: relocatable!(0, 2),
+                    call_pc: relocatable!(0, 0),
+                },
+                TracebackEntry {
+                    fp: relocatable!(1, 4),
+                    return_pc: relocatable!(0, 4),
+                    call_pc: relocatable!(0, 2),
+                },
+                TracebackEntry {
+                    fp: relocatable!(1, 6),
+                    return_pc: relocatable!(0, 6),
+                    call_pc: relocatable!(0, 4),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn get_traceback_entries_max_depth() {
+        let vm = vm_with_call_chain();
+        let entries = vm.get_traceback_entries(2);
+        assert_eq!(
+            entries
+                .iter()
+                .map(|entry| entry.call_pc)
+                .collect::<Vec<_>>(),
+            vec![relocatable!(0, 2), relocatable!(0, 4)]
+        );
+    }
+
+    #[test]
+    fn get_traceback_entries_corrupted_fp() {
+        let mut vm = vm_with_call_chain();
+        vm.memory.data[1][4] = Some(MaybeRelocatable::from(Felt::new(7)));
+        assert_eq!(
+            vm.get_traceback_entries(MAX_TRACEBACK_ENTRIES),
+            vec![TracebackEntry {
+                fp: relocatable!(1, 6),
+                return_pc: relocatable!(0, 6),
+                call_pc: relocatable!(0, 4),
+            }]
+        );
+    }
+
+    // A vm running a program over the field of 17
+    fn vm_with_prime_17() -> VirtualMachine {
+        let mut vm = vm!();
+        vm.prime = Some(BigUint::from(17_u32));
+        vm
+    }
+
+    #[test]
+    fn arithmetic_with_operands_at_or_above_the_program_prime() {
+        let vm = vm_with_prime_17();
+        let int = |value: i64| MaybeRelocatable::from(Felt::new(value));
+        // 40 = 6 and 30 = 13 modulo 17
+        assert_eq!(vm.add_values(&int(40), &int(30)), Ok(int(2)));
+        assert_eq!(vm.sub_values(&int(3), &int(40)), Ok(int(14)));
+        assert_eq!(vm.sub_values(&int(17), &int(34)), Ok(int(0)));
+        assert_eq!(vm.mul_felts(&Felt::new(40), &Felt::new(30)), Felt::new(10));
+        // 35 = 1 modulo 17
+        assert_eq!(
+            vm.div_felts(&Felt::new(20), &Felt::new(35)),
+            Some(Felt::new(3))
+        );
+        assert_eq!(vm.div_felts(&Felt::new(20), &Felt::new(34)), None);
+        assert!(vm.values_eq(&int(5), &int(22)));
+        assert_eq!(vm.is_zero_value(&int(51)), Ok(true));
+    }
+
+    #[test]
+    fn relocatable_arithmetic_with_operands_at_or_above_the_program_prime() {
+        let vm = vm_with_prime_17();
+        let rel = relocatable!(1, 5);
+        // 35 = 1 and 33 = 16 = -1 modulo 17
+        assert_eq!(
+            vm.add_to_relocatable(&rel, &Felt::new(35).into()),
+            Ok(relocatable!(1, 6))
+        );
+        assert_eq!(
+            vm.add_to_relocatable(&rel, &Felt::new(33).into()),
+            Ok(relocatable!(1, 4))
+        );
+        assert_eq!(
+            vm.add_values(&rel.into(), &Felt::new(33).into()),
+            Ok(relocatable!(1, 4).into())
+        );
+        assert_eq!(
+            vm.sub_values(&rel.into(), &Felt::new(33).into()),
+            Ok(relocatable!(1, 6).into())
+        );
     }
 }
@@ -6,10 +6,15 @@ use crate::{
 use felt::Felt;
 use std::{
     borrow::Cow,
-    collections::{HashMap, HashSet},
+    collections::{BTreeMap, HashMap, HashSet},
     mem::swap,
 };
 
+/// A validation rule is called with the memory and the address of a newly inserted cell of the
+/// segment it was registered for.
+/// It should return an error if the value violates the segment's invariant, and otherwise the list
+/// of addresses that can be considered validated, which may include more than the given address
+/// when the validation covers several cells at once (e.g. a whole ecdsa instance).
 pub struct ValidationRule(
     #[allow(clippy::type_complexity)]
     pub  Box<dyn Fn(&Memory, &MaybeRelocatable) -> Result<Vec<MaybeRelocatable>, MemoryError>>,
@@ -23,6 +28,16 @@ pub struct Memory {
     pub(crate) relocation_rules: HashMap<usize, Relocatable>,
     pub validated_addresses: HashSet<MaybeRelocatable>,
     validation_rules: HashMap<usize, ValidationRule>,
+    // The validation rules of the temporary segments, keyed by their (negative) segment index
+    temp_validation_rules: HashMap<isize, ValidationRule>,
+    frozen_segments: HashSet<isize>,
+    // The addresses of the cells written since start_recording_writes, if recording
+    write_log: Option<Vec<Relocatable>>,
+    // The pc of the step being run, if any
+    pub(crate) writer_pc: Option<Relocatable>,
+    // The pc of the step which wrote the furthest cell of each segment, if it was written during
+    // a step
+    max_offset_writers: HashMap<isize, Relocatable>,
 }
 
 impl Memory {
@@ -33,12 +48,32 @@ impl Memory {
             relocation_rules: HashMap::new(),
             validated_addresses: HashSet::<MaybeRelocatable>::new(),
             validation_rules: HashMap::new(),
+            temp_validation_rules: HashMap::new(),
+            frozen_segments: HashSet::new(),
+            write_log: None,
+            writer_pc: None,
+            max_offset_writers: HashMap::new(),
         }
     }
     ///Inserts an MaybeRelocatable value into an address given by a MaybeRelocatable::Relocatable
     /// Will panic if the segment index given by the address corresponds to a non-allocated segment
     /// If the address isnt contiguous with previously inserted data, memory gaps will be represented by inserting None values
+    #[cfg_attr(not(feature = "tracing"), allow(clippy::let_and_return))]
     pub fn insert<'a, K: 'a, V: 'a>(&mut self, key: &'a K, val: &'a V) -> Result<(), MemoryError>
+    where
+        Relocatable: TryFrom<&'a K>,
+        MaybeRelocatable: From<&'a K>,
+        MaybeRelocatable: From<&'a V>,
+    {
+        let result = self.insert_cell(key, val);
+        #[cfg(feature = "tracing")]
+        if let Err(error) = &result {
+            tracing::warn!(address = %MaybeRelocatable::from(key), %error, "memory insert failed");
+        }
+        result
+    }
+
+    fn insert_cell<'a, K: 'a, V: 'a>(&mut self, key: &'a K, val: &'a V) -> Result<(), MemoryError>
     where
         Relocatable: TryFrom<&'a K>,
         MaybeRelocatable: From<&'a K>,
@@ -47,6 +82,9 @@ impl Memory {
         let relocatable: Relocatable = key
             .try_into()
             .map_err(|_| MemoryError::AddressNotRelocatable)?;
+        if self.frozen_segments.contains(&relocatable.segment_index) {
+            return Err(MemoryError::FrozenSegment(relocatable.segment_index));
+        }
         let val = MaybeRelocatable::from(val);
         let (value_index, value_offset) = from_relocatable_to_indexes(&relocatable);
 
@@ -65,11 +103,22 @@ impl Memory {
         //Forgoing this check would allow data to be inserted in a different index
         if segment.len() <= value_offset {
             segment.resize(value_offset + 1, None);
+            match self.writer_pc {
+                Some(pc) => self
+                    .max_offset_writers
+                    .insert(relocatable.segment_index, pc),
+                None => self.max_offset_writers.remove(&relocatable.segment_index),
+            };
         }
         // At this point there's *something* in there
 
         match segment[value_offset] {
-            None => segment[value_offset] = Some(val),
+            None => {
+                segment[value_offset] = Some(val);
+                if let Some(write_log) = self.write_log.as_mut() {
+                    write_log.push(relocatable);
+                }
+            }
             Some(ref current_value) => {
                 if current_value != &val {
                     //Existing memory cannot be changed
@@ -148,12 +197,6 @@ impl Memory {
                 },
             ));
         for (addr, value) in data_iter {
-            // After the following check, addr.segment_index cannot be negative, therefore it is
-            // safe to cast to `usize`.
-            if addr.segment_index.is_negative() {
-                continue;
-            }
-
             let value = match value {
                 Some(x) => x,
                 None => continue,
@@ -163,6 +206,12 @@ impl Memory {
                 .relocate_value(&MaybeRelocatable::RelocatableValue(addr))
                 .into_owned()
                 .try_into()?;
+            // The cells of the temporary segments without a relocation rule are discarded. After
+            // the following check, new_addr.segment_index cannot be negative, therefore it is
+            // safe to cast to `usize`.
+            if new_addr.segment_index.is_negative() {
+                continue;
+            }
             let new_value = self.relocate_value(&value).into_owned();
 
             if new_addr.segment_index as usize >= self.data.len() {
@@ -178,10 +227,78 @@ impl Memory {
             segment_data[new_addr.offset] = Some(new_value);
         }
 
+        // The cells validated in the temporary segments now live in the relocated ones, which
+        // the temporary segments' rules don't apply to
+        self.validated_addresses = self
+            .validated_addresses
+            .iter()
+            .map(|addr| self.relocate_value(addr).into_owned())
+            .collect();
+        self.temp_validation_rules.clear();
         self.relocation_rules.clear();
         Ok(())
     }
 
+    /// Drops the temporary segments that have no relocation rule, as they won't be part of the
+    /// relocated memory, and returns how many were dropped. Must be called once the run has
+    /// ended, before relocating the memory.
+    /// Fails if a cell of the non-temporary memory, or of a temporary segment that will be
+    /// relocated, points to one of these segments, as the pointer couldn't be relocated.
+    pub(crate) fn drop_unrelocated_temporary_segments(&mut self) -> Result<usize, MemoryError> {
+        let has_rule = |segment_index: isize| {
+            self.relocation_rules
+                .contains_key(&(-(segment_index + 1) as usize))
+        };
+        let cells =
+            self.data
+                .iter()
+                .enumerate()
+                .map(|(index, segment)| (index as isize, segment))
+                .chain(
+                    self.temp_data
+                        .iter()
+                        .enumerate()
+                        .map(|(index, segment)| (-(index as isize) - 1, segment))
+                        .filter(|(segment_index, _)| has_rule(*segment_index)),
+                )
+                .flat_map(|(segment_index, segment)| {
+                    segment.iter().enumerate().map(move |(offset, cell)| {
+                        (Relocatable::from((segment_index, offset)), cell)
+                    })
+                });
+
+        let mut references = BTreeMap::<isize, Vec<Relocatable>>::new();
+        for (addr, cell) in cells {
+            if let Some(MaybeRelocatable::RelocatableValue(value)) = cell {
+                if value.segment_index < 0 && !has_rule(value.segment_index) {
+                    references
+                        .entry(value.segment_index)
+                        .or_default()
+                        .push(addr);
+                }
+            }
+        }
+        // Report the segment closest to -1, as the first one to be added
+        if let Some((segment_index, referenced_by)) = references.into_iter().next_back() {
+            return Err(MemoryError::UnrelocatedTemporarySegment(
+                segment_index,
+                referenced_by,
+            ));
+        }
+
+        let mut dropped = 0;
+        for (index, segment) in self.temp_data.iter_mut().enumerate() {
+            if !self.relocation_rules.contains_key(&index) {
+                *segment = Vec::new();
+                dropped += 1;
+            }
+        }
+        if self.relocation_rules.is_empty() {
+            self.temp_data.clear();
+        }
+        Ok(dropped)
+    }
+
     /// Add a new relocation rule.
     ///
     /// Will return an error if any of the following conditions are not met:
@@ -217,7 +334,7 @@ impl Memory {
     //If the value is an MaybeRelocatable::Int(Bigint) return &Bigint
     //else raises Err
     pub fn get_integer(&self, key: &Relocatable) -> Result<Cow<Felt>, VirtualMachineError> {
-        match self.get(key).map_err(VirtualMachineError::MemoryError)? {
+        match self.get(key)? {
             Some(Cow::Borrowed(MaybeRelocatable::Int(int))) => Ok(Cow::Borrowed(int)),
             Some(Cow::Owned(MaybeRelocatable::Int(int))) => Ok(Cow::Owned(int)),
             _ => Err(VirtualMachineError::ExpectedInteger(
@@ -227,7 +344,7 @@ impl Memory {
     }
 
     pub fn get_relocatable(&self, key: &Relocatable) -> Result<Relocatable, VirtualMachineError> {
-        match self.get(key).map_err(VirtualMachineError::MemoryError)? {
+        match self.get(key)? {
             Some(Cow::Borrowed(MaybeRelocatable::RelocatableValue(rel))) => Ok(*rel),
             Some(Cow::Owned(MaybeRelocatable::RelocatableValue(rel))) => Ok(rel),
             _ => Err(VirtualMachineError::ExpectedRelocatable(
@@ -245,18 +362,64 @@ impl Memory {
             .map_err(VirtualMachineError::MemoryError)
     }
 
+    ///Starts recording the addresses of the cells written from now on
+    pub(crate) fn start_recording_writes(&mut self) {
+        self.write_log = Some(Vec::new());
+    }
+
+    ///Stops recording writes, returning the addresses of the cells written since
+    ///start_recording_writes was called, in insertion order
+    pub(crate) fn stop_recording_writes(&mut self) -> Vec<Relocatable> {
+        self.write_log.take().unwrap_or_default()
+    }
+
+    ///Registers a validation rule for the given segment, replacing the previous one, if any.
+    ///The rule will be applied to every cell inserted into the segment afterwards, and to the
+    ///existing cells when calling validate_existing_memory
     pub fn add_validation_rule(&mut self, segment_index: usize, rule: ValidationRule) {
         self.validation_rules.insert(segment_index, rule);
     }
 
+    ///Like add_validation_rule, for a temporary segment. The rule is dropped when the memory is
+    ///relocated, while the addresses it validated are relocated along with the segment.
+    pub fn add_temp_validation_rule(
+        &mut self,
+        segment_index: isize,
+        rule: ValidationRule,
+    ) -> Result<(), MemoryError> {
+        if segment_index >= 0 {
+            return Err(MemoryError::AddressNotInTemporarySegment(segment_index));
+        }
+        self.temp_validation_rules.insert(segment_index, rule);
+        Ok(())
+    }
+
+    ///Returns the pc of the instruction or hint which wrote the furthest cell of the segment.
+    ///Returns None if the segment is empty, or if that cell was written outside of a step.
+    pub fn get_max_offset_writer(&self, segment_index: isize) -> Option<Relocatable> {
+        self.max_offset_writers.get(&segment_index).copied()
+    }
+
+    ///Freezes the given segment (either a normal or a temporary one)
+    ///Any subsequent insertion into it will fail
+    pub fn freeze_segment(&mut self, segment_index: isize) {
+        self.frozen_segments.insert(segment_index);
+    }
+
     fn validate_memory_cell(&mut self, address: &MaybeRelocatable) -> Result<(), MemoryError> {
         if let &MaybeRelocatable::RelocatableValue(ref rel_addr) = address {
             if !self.validated_addresses.contains(address) {
-                for (index, validation_rule) in self.validation_rules.iter() {
-                    if rel_addr.segment_index == *index as isize {
-                        self.validated_addresses
-                            .extend(validation_rule.0(self, address)?);
+                let validation_rule = match usize::try_from(rel_addr.segment_index) {
+                    Ok(index) => self.validation_rules.get(&index),
+                    Err(_) => self.temp_validation_rules.get(&rel_addr.segment_index),
+                };
+                if let Some(validation_rule) = validation_rule {
+                    let validated_addresses = validation_rule.0(self, address);
+                    #[cfg(feature = "tracing")]
+                    if let Err(error) = &validated_addresses {
+                        tracing::warn!(%address, %error, "memory validation failed");
                     }
+                    self.validated_addresses.extend(validated_addresses?);
                 }
             }
             Ok(())
@@ -272,6 +435,11 @@ impl Memory {
                 self.validate_memory_cell(&MaybeRelocatable::from((i as isize, j)))?;
             }
         }
+        for i in 0..self.temp_data.len() {
+            for j in 0..self.temp_data[i].len() {
+                self.validate_memory_cell(&MaybeRelocatable::from((-(i as isize) - 1, j)))?;
+            }
+        }
         Ok(())
     }
 
@@ -376,6 +544,7 @@ mod memory_tests {
         },
     };
     use felt::{felt_str, NewFelt};
+    use num_integer::Integer;
 
     use crate::vm::errors::memory_errors::MemoryError;
 
@@ -578,7 +747,7 @@ mod memory_tests {
         let mut builtin = RangeCheckBuiltinRunner::new(8, 8, true);
         let mut segments = MemorySegmentManager::new();
         let mut memory = Memory::new();
-        builtin.initialize_segments(&mut segments, &mut memory);
+        builtin.initialize_segments(&mut segments, &mut memory).unwrap();
         assert_eq!(builtin.add_validation_rule(&mut memory), Ok(()));
         for _ in 0..3 {
             segments.add(&mut memory);
@@ -602,7 +771,7 @@ mod memory_tests {
         let mut segments = MemorySegmentManager::new();
         let mut memory = Memory::new();
         segments.add(&mut memory);
-        builtin.initialize_segments(&mut segments, &mut memory);
+        builtin.initialize_segments(&mut segments, &mut memory).unwrap();
         memory
             .insert(
                 &MaybeRelocatable::from((1, 0)),
@@ -611,10 +780,65 @@ mod memory_tests {
             .unwrap();
         assert_eq!(builtin.add_validation_rule(&mut memory), Ok(()));
         let error = memory.validate_existing_memory();
-        assert_eq!(error, Err(MemoryError::NumOutOfBounds));
+        assert_eq!(
+            error,
+            Err(MemoryError::NumOutOfBounds {
+                addr: Relocatable::from((1, 0)),
+                value: Felt::new(-10),
+            })
+        );
         assert_eq!(
             error.unwrap_err().to_string(),
-            "Range-check validation failed, number is out of valid range"
+            format!(
+                "Range-check validation failed, number {} at 1:0 is out of valid range",
+                Felt::new(-10)
+            )
+        );
+    }
+
+    #[test]
+    fn range_check_in_temporary_segment() {
+        let mut builtin = RangeCheckBuiltinRunner::new(8, 8, true);
+        let mut segments = MemorySegmentManager::new();
+        let mut memory = Memory::new();
+        segments.add(&mut memory);
+        builtin.base = segments.add_temporary_segment(&mut memory).segment_index;
+        assert_eq!(builtin.add_validation_rule(&mut memory), Ok(()));
+        memory
+            .insert(
+                &MaybeRelocatable::from((-1, 0)),
+                &MaybeRelocatable::from(Felt::new(10)),
+            )
+            .unwrap();
+        assert_eq!(
+            memory.insert(
+                &MaybeRelocatable::from((-1, 1)),
+                &MaybeRelocatable::from(Felt::new(-10)),
+            ),
+            Err(MemoryError::NumOutOfBounds {
+                addr: Relocatable::from((-1, 1)),
+                value: Felt::new(-10),
+            })
+        );
+
+        memory
+            .add_relocation_rule((-1, 0).into(), (0, 0).into())
+            .unwrap();
+        memory.relocate_memory().unwrap();
+        assert!(memory
+            .validated_addresses
+            .contains(&MaybeRelocatable::from((0, 0))));
+        assert!(!memory
+            .validated_addresses
+            .contains(&MaybeRelocatable::from((-1, 0))));
+    }
+
+    #[test]
+    fn add_temp_validation_rule_to_real_segment() {
+        let mut memory = Memory::new();
+        assert_eq!(
+            memory.add_temp_validation_rule(1, ValidationRule(Box::new(|_, _| Ok(Vec::new())))),
+            Err(MemoryError::AddressNotInTemporarySegment(1))
         );
     }
 
@@ -639,7 +863,7 @@ mod memory_tests {
             )
         ];
         segments.add(&mut memory);
-        builtin.initialize_segments(&mut segments, &mut memory);
+        builtin.initialize_segments(&mut segments, &mut memory).unwrap();
         builtin.add_validation_rule(&mut memory).unwrap();
         let error = memory.validate_existing_memory();
         assert_eq!(error, Err(MemoryError::SignatureNotFound));
@@ -673,7 +897,7 @@ mod memory_tests {
             ((1, 1), 2)
         ];
 
-        builtin.initialize_segments(&mut segments, &mut memory);
+        builtin.initialize_segments(&mut segments, &mut memory).unwrap();
 
         builtin.add_validation_rule(&mut memory).unwrap();
 
@@ -688,7 +912,7 @@ mod memory_tests {
         let mut segments = MemorySegmentManager::new();
         let mut memory = memory![((1, 7), (1, 4))];
         segments.add(&mut memory);
-        builtin.initialize_segments(&mut segments, &mut memory);
+        builtin.initialize_segments(&mut segments, &mut memory).unwrap();
         assert_eq!(builtin.add_validation_rule(&mut memory), Ok(()));
         dbg!(builtin._bound);
         dbg!(&memory.data);
@@ -706,7 +930,7 @@ mod memory_tests {
         let mut segments = MemorySegmentManager::new();
         let mut memory = Memory::new();
         segments.add(&mut memory);
-        builtin.initialize_segments(&mut segments, &mut memory);
+        builtin.initialize_segments(&mut segments, &mut memory).unwrap();
         memory
             .insert(
                 &MaybeRelocatable::from((0, 0)),
@@ -991,8 +1215,174 @@ mod memory_tests {
                     mayberelocatable!(5).into(),
                     mayberelocatable!(2, 3).into(),
                 ],
+                vec![
+                    None,
+                    mayberelocatable!(7).into(),
+                    mayberelocatable!(8).into(),
+                    mayberelocatable!(9).into(),
+                ],
             ],
         );
         assert!(memory.temp_data.is_empty());
     }
+
+    #[test]
+    fn drop_unrelocated_temporary_segments() {
+        // (-2) is only referenced by (-3), which is dropped too, and (-1) is relocated
+        let mut memory = memory![((0, 0), 1), ((0, 1), (-1, 1))];
+        memory.temp_data = vec![
+            vec![mayberelocatable!(7).into(), mayberelocatable!(8).into()],
+            vec![mayberelocatable!(9).into()],
+            vec![mayberelocatable!(-2, 0).into()],
+        ];
+        memory
+            .add_relocation_rule((-1, 0).into(), (1, 0).into())
+            .unwrap();
+
+        assert_eq!(memory.drop_unrelocated_temporary_segments(), Ok(2));
+        assert_eq!(
+            memory.temp_data,
+            vec![
+                vec![mayberelocatable!(7).into(), mayberelocatable!(8).into()],
+                Vec::new(),
+                Vec::new(),
+            ]
+        );
+        assert_eq!(memory.relocate_memory(), Ok(()));
+        assert!(memory.temp_data.is_empty());
+    }
+
+    #[test]
+    fn drop_unrelocated_temporary_segments_without_rules() {
+        let mut memory = memory![((0, 0), 1)];
+        memory.temp_data = vec![vec![mayberelocatable!(-2, 0).into()], Vec::new()];
+
+        assert_eq!(memory.drop_unrelocated_temporary_segments(), Ok(2));
+        assert!(memory.temp_data.is_empty());
+    }
+
+    #[test]
+    fn drop_unrelocated_temporary_segments_referenced() {
+        // (-2) is referenced by the non-temporary memory and by (-1), which will be relocated
+        let mut memory = memory![((0, 0), (-2, 0)), ((0, 1), (-2, 1)), ((0, 2), (-1, 0))];
+        memory.temp_data = vec![
+            vec![mayberelocatable!(7).into(), mayberelocatable!(-2, 0).into()],
+            vec![mayberelocatable!(9).into()],
+        ];
+        memory
+            .add_relocation_rule((-1, 0).into(), (1, 0).into())
+            .unwrap();
+
+        assert_eq!(
+            memory.drop_unrelocated_temporary_segments(),
+            Err(MemoryError::UnrelocatedTemporarySegment(
+                -2,
+                vec![(0, 0).into(), (0, 1).into(), (-1, 1).into()]
+            ))
+        );
+    }
+
+    fn reject_odd_numbers() -> ValidationRule {
+        ValidationRule(Box::new(
+            |memory: &Memory,
+             address: &MaybeRelocatable|
+             -> Result<Vec<MaybeRelocatable>, MemoryError> {
+                let address = address
+                    .get_relocatable()
+                    .map_err(|_| MemoryError::AddressNotRelocatable)?;
+                let value = memory
+                    .get_integer(&address)
+                    .map_err(|_| MemoryError::FoundNonInt)?;
+                if value.is_odd() {
+                    return Err(MemoryError::InvalidMemoryValue(
+                        address,
+                        MaybeRelocatable::from(value.into_owned()),
+                    ));
+                }
+                Ok(vec![address.into()])
+            },
+        ))
+    }
+
+    #[test]
+    fn custom_validation_rule_accepts_valid_value() {
+        let mut memory = Memory::new();
+        memory.data.push(Vec::new());
+        memory.add_validation_rule(0, reject_odd_numbers());
+        assert_eq!(
+            memory.insert(&mayberelocatable!(0, 0), &mayberelocatable!(4)),
+            Ok(())
+        );
+        assert!(memory
+            .validated_addresses
+            .contains(&MaybeRelocatable::from((0, 0))));
+    }
+
+    #[test]
+    fn custom_validation_rule_rejects_invalid_value() {
+        let mut memory = Memory::new();
+        memory.data.push(Vec::new());
+        memory.data.push(Vec::new());
+        memory.add_validation_rule(0, reject_odd_numbers());
+        assert_eq!(
+            memory.insert(&mayberelocatable!(0, 0), &mayberelocatable!(3)),
+            Err(MemoryError::InvalidMemoryValue(
+                (0, 0).into(),
+                mayberelocatable!(3)
+            ))
+        );
+        // Other segments are not affected by the rule
+        assert_eq!(
+            memory.insert(&mayberelocatable!(1, 0), &mayberelocatable!(3)),
+            Ok(())
+        );
+        assert!(memory.validated_addresses.is_empty());
+    }
+
+    #[test]
+    fn custom_validation_rule_on_existing_memory() {
+        let mut memory = memory![((0, 0), 2), ((0, 1), 5)];
+        memory.add_validation_rule(0, reject_odd_numbers());
+        assert_eq!(
+            memory.validate_existing_memory(),
+            Err(MemoryError::InvalidMemoryValue(
+                (0, 1).into(),
+                mayberelocatable!(5)
+            ))
+        );
+    }
+
+    #[test]
+    fn freeze_segment_rejects_inserts() {
+        let mut memory = memory![((0, 0), 1), ((1, 0), 2)];
+        memory.freeze_segment(0);
+        assert_eq!(
+            memory.insert(&mayberelocatable!(0, 1), &mayberelocatable!(3)),
+            Err(MemoryError::FrozenSegment(0))
+        );
+        assert_eq!(
+            memory.insert(&mayberelocatable!(0, 0), &mayberelocatable!(1)),
+            Err(MemoryError::FrozenSegment(0))
+        );
+        // Reads and inserts into other segments still work
+        assert_eq!(
+            memory.get(&mayberelocatable!(0, 0)),
+            Ok(Some(Cow::Borrowed(&mayberelocatable!(1))))
+        );
+        assert_eq!(
+            memory.insert(&mayberelocatable!(1, 1), &mayberelocatable!(3)),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn freeze_temporary_segment() {
+        let mut memory = Memory::new();
+        memory.temp_data.push(Vec::new());
+        memory.freeze_segment(-1);
+        assert_eq!(
+            memory.insert(&mayberelocatable!(-1, 0), &mayberelocatable!(3)),
+            Err(MemoryError::FrozenSegment(-1))
+        );
+    }
 }
@@ -3,7 +3,7 @@ use crate::{
     utils::from_relocatable_to_indexes,
     vm::{
         errors::memory_errors::MemoryError, errors::vm_errors::VirtualMachineError,
-        vm_memory::memory::Memory,
+        runners::cairo_runner::CairoArg, vm_memory::memory::Memory,
     },
 };
 
@@ -30,6 +30,8 @@ impl MemorySegmentManager {
         let segment_index = self.num_segments;
         self.num_segments += 1;
         memory.data.push(Vec::new());
+        #[cfg(feature = "tracing")]
+        tracing::debug!(segment_index = segment_index as i64, "segment added");
         Relocatable {
             segment_index: segment_index as isize,
             offset: 0,
@@ -41,6 +43,11 @@ impl MemorySegmentManager {
     pub fn add_temporary_segment(&mut self, memory: &mut Memory) -> Relocatable {
         self.num_temp_segments += 1;
         memory.temp_data.push(Vec::new());
+        #[cfg(feature = "tracing")]
+        tracing::debug!(
+            segment_index = -(self.num_temp_segments as i64),
+            "temporary segment added"
+        );
         Relocatable {
             segment_index: -(self.num_temp_segments as isize),
             offset: 0,
@@ -82,6 +89,14 @@ impl MemorySegmentManager {
         self.segment_used_sizes.as_ref()?.get(index).copied()
     }
 
+    ///Returns the used size of a segment, which can be called before the sizes are computed.
+    ///Falls back to the size of the segment in memory, which grows as cells are inserted.
+    ///Returns None if the segment doesn't exist.
+    pub fn get_segment_used_size_mid_run(&self, index: usize, memory: &Memory) -> Option<usize> {
+        self.get_segment_used_size(index)
+            .or_else(|| memory.data.get(index).map(Vec::len))
+    }
+
     pub fn get_segment_size(&self, index: usize) -> Option<usize> {
         self.segment_sizes
             .get(&index)
@@ -91,22 +106,54 @@ impl MemorySegmentManager {
 
     ///Returns a vector that contains the first relocated address of each memory segment
     pub fn relocate_segments(&self) -> Result<Vec<usize>, MemoryError> {
-        let first_addr = 1;
-        let mut relocation_table = vec![first_addr];
-        match &self.segment_used_sizes {
-            Some(segment_used_sizes) => {
-                for (i, _size) in segment_used_sizes.iter().enumerate() {
-                    let segment_size = self
-                        .get_segment_size(i)
-                        .ok_or(MemoryError::SegmentNotFinalized(i))?;
+        self.relocate_segments_with_offsets(&HashMap::new())
+    }
 
-                    relocation_table.push(relocation_table[i] + segment_size);
+    ///Returns a vector that contains the first relocated address of each memory segment,
+    ///placing the segments found in `overrides` at the given first address.
+    ///The other segments are placed one after the other starting from address 1, skipping the
+    ///overridden ones. Fails if two non-empty segments end up overlapping.
+    pub fn relocate_segments_with_offsets(
+        &self,
+        overrides: &HashMap<usize, usize>,
+    ) -> Result<Vec<usize>, MemoryError> {
+        let num_segments = self
+            .segment_used_sizes
+            .as_ref()
+            .ok_or(MemoryError::EffectiveSizesNotCalled)?
+            .len();
+        if let Some(index) = overrides.keys().find(|index| **index >= num_segments) {
+            return Err(MemoryError::UnallocatedSegment(*index, num_segments));
+        }
+        let mut relocation_table = Vec::with_capacity(num_segments);
+        // (first address, end address, segment index) of each non-empty segment
+        let mut ranges = Vec::with_capacity(num_segments);
+        let mut next_addr = 1;
+        for index in 0..num_segments {
+            let segment_size = self
+                .get_segment_size(index)
+                .ok_or(MemoryError::SegmentNotFinalized(index))?;
+            let first_addr = match overrides.get(&index) {
+                Some(0) => return Err(MemoryError::ZeroRelocationAddress(index)),
+                Some(addr) => *addr,
+                None => {
+                    let first_addr = next_addr;
+                    next_addr += segment_size;
+                    first_addr
                 }
+            };
+            relocation_table.push(first_addr);
+            if segment_size != 0 {
+                ranges.push((first_addr, first_addr + segment_size, index));
+            }
+        }
+        // Once sorted by first address, any overlap shows up between neighbouring segments
+        ranges.sort_unstable();
+        for pair in ranges.windows(2) {
+            if pair[1].0 < pair[0].1 {
+                return Err(MemoryError::SegmentRelocationOverlap(pair[0].2, pair[1].2));
             }
-            None => return Err(MemoryError::EffectiveSizesNotCalled),
         }
-        //The last value corresponds to the total amount of elements across all segments, which isnt needed for relocation.
-        relocation_table.pop();
         Ok(relocation_table)
     }
 
@@ -130,6 +177,32 @@ impl MemorySegmentManager {
         }
     }
 
+    ///Returns the value passed to a function for the given argument, writing the arrays and the
+    ///composed arguments into new segments.
+    pub fn gen_cairo_arg(
+        &mut self,
+        arg: &CairoArg,
+        memory: &mut Memory,
+    ) -> Result<MaybeRelocatable, MemoryError> {
+        match arg {
+            CairoArg::Single(value) => Ok(value.clone()),
+            CairoArg::Array(values) => {
+                let base = self.add(memory);
+                self.load_data(memory, &base.into(), values)?;
+                Ok(base.into())
+            }
+            CairoArg::Composed(args) => {
+                let values = args
+                    .iter()
+                    .map(|arg| self.gen_cairo_arg(arg, memory))
+                    .collect::<Result<Vec<_>, _>>()?;
+                let base = self.add(memory);
+                self.load_data(memory, &base.into(), &values)?;
+                Ok(base.into())
+            }
+        }
+    }
+
     pub fn write_arg(
         &mut self,
         memory: &mut Memory,
@@ -178,7 +251,7 @@ impl MemorySegmentManager {
         let segment_used_sizes = self
             .segment_used_sizes
             .as_ref()
-            .ok_or(MemoryError::MissingSegmentUsedSizes)?;
+            .ok_or(MemoryError::EffectiveSizesNotCalled)?;
 
         let mut accessed_offsets_sets = HashMap::new();
         for addr in accessed_addresses {
@@ -197,7 +270,10 @@ impl MemorySegmentManager {
                 }
             };
             if offset > *segment_size {
-                return Err(MemoryError::NumOutOfBounds);
+                return Err(MemoryError::AddressOutOfSegmentBounds {
+                    addr,
+                    size: *segment_size,
+                });
             }
 
             offset_set.insert(offset);
@@ -212,21 +288,55 @@ impl MemorySegmentManager {
 
     // Writes the following information for the given segment:
     // * size - The size of the segment (to be used in relocate_segments).
-    // * public_memory - A list of offsets for memory cells that will be considered as public
-    // memory.
-    pub(crate) fn finalize(
+    // * public_memory - A list of (offset, page) pairs for memory cells that will be considered
+    // as public memory.
+    // Fails if the size is smaller than the used size of the segment, when it is known.
+    pub fn finalize(
         &mut self,
         size: Option<usize>,
         segment_index: usize,
         public_memory: Option<&Vec<(usize, usize)>>,
-    ) {
+    ) -> Result<(), MemoryError> {
         if let Some(size) = size {
+            if let Some(used_size) = self
+                .get_segment_used_size(segment_index)
+                .filter(|used_size| *used_size > size)
+            {
+                return Err(MemoryError::FinalizedSizeBelowUsedSize {
+                    segment_index,
+                    size,
+                    used_size,
+                });
+            }
             self.segment_sizes.insert(segment_index, size);
         }
         if let Some(public_memory) = public_memory {
             self.public_memory_offsets
                 .insert(segment_index, public_memory.clone());
         }
+        Ok(())
+    }
+
+    ///Returns the relocated address and the page of each public memory cell, ordered by segment.
+    ///`relocation_table` holds the first relocated address of each segment.
+    pub fn get_public_memory_addresses(
+        &self,
+        relocation_table: &[usize],
+    ) -> Result<Vec<(usize, usize)>, MemoryError> {
+        let mut addresses = Vec::new();
+        for segment_index in 0..self.num_segments {
+            if let Some(offsets) = self.public_memory_offsets.get(&segment_index) {
+                let segment_start = relocation_table.get(segment_index).ok_or(
+                    MemoryError::UnallocatedSegment(segment_index, relocation_table.len()),
+                )?;
+                addresses.extend(
+                    offsets
+                        .iter()
+                        .map(|(offset, page)| (segment_start + offset, *page)),
+                );
+            }
+        }
+        Ok(addresses)
     }
 }
 
@@ -457,6 +567,23 @@ mod tests {
         assert_eq!(None, segments.get_segment_used_size(2));
     }
 
+    #[test]
+    fn get_segment_used_size_mid_run_before_computing_used() {
+        let segments = MemorySegmentManager::new();
+        let memory = memory![((0, 2), 1), ((1, 1), 1)];
+        assert_eq!(Some(3), segments.get_segment_used_size_mid_run(0, &memory));
+        assert_eq!(Some(2), segments.get_segment_used_size_mid_run(1, &memory));
+        assert_eq!(None, segments.get_segment_used_size_mid_run(2, &memory));
+    }
+
+    #[test]
+    fn get_segment_used_size_mid_run_after_computing_used() {
+        let mut segments = MemorySegmentManager::new();
+        segments.segment_used_sizes = Some(vec![5]);
+        let memory = memory![((0, 2), 1)];
+        assert_eq!(Some(5), segments.get_segment_used_size_mid_run(0, &memory));
+    }
+
     #[test]
     fn relocate_segments_one_segment() {
         let mut segments = MemorySegmentManager::new();
@@ -481,6 +608,67 @@ mod tests {
         )
     }
 
+    #[test]
+    fn relocate_segments_with_offsets_pinned_segment() {
+        let mut segments = MemorySegmentManager::new();
+        segments.segment_used_sizes = Some(vec![3, 3, 56, 78, 8]);
+        assert_eq!(
+            segments.relocate_segments_with_offsets(&HashMap::from([(2, 10_000)])),
+            Ok(vec![1, 4, 10_000, 7, 85])
+        )
+    }
+
+    #[test]
+    fn relocate_segments_with_offsets_no_overrides() {
+        let mut segments = MemorySegmentManager::new();
+        segments.segment_used_sizes = Some(vec![3, 3, 56, 78, 8]);
+        assert_eq!(
+            segments.relocate_segments_with_offsets(&HashMap::new()),
+            segments.relocate_segments()
+        )
+    }
+
+    #[test]
+    fn relocate_segments_with_offsets_overlap() {
+        let mut segments = MemorySegmentManager::new();
+        segments.segment_used_sizes = Some(vec![3, 3, 56, 78, 8]);
+        // Segment 3 is naturally placed at 7..85
+        assert_eq!(
+            segments.relocate_segments_with_offsets(&HashMap::from([(2, 80)])),
+            Err(MemoryError::SegmentRelocationOverlap(3, 2))
+        )
+    }
+
+    #[test]
+    fn relocate_segments_with_offsets_empty_segment_does_not_overlap() {
+        let mut segments = MemorySegmentManager::new();
+        segments.segment_used_sizes = Some(vec![3, 0]);
+        assert_eq!(
+            segments.relocate_segments_with_offsets(&HashMap::from([(1, 2)])),
+            Ok(vec![1, 2])
+        )
+    }
+
+    #[test]
+    fn relocate_segments_with_offsets_zero_address() {
+        let mut segments = MemorySegmentManager::new();
+        segments.segment_used_sizes = Some(vec![3, 3]);
+        assert_eq!(
+            segments.relocate_segments_with_offsets(&HashMap::from([(1, 0)])),
+            Err(MemoryError::ZeroRelocationAddress(1))
+        )
+    }
+
+    #[test]
+    fn relocate_segments_with_offsets_missing_segment() {
+        let mut segments = MemorySegmentManager::new();
+        segments.segment_used_sizes = Some(vec![3, 3]);
+        assert_eq!(
+            segments.relocate_segments_with_offsets(&HashMap::from([(2, 100)])),
+            Err(MemoryError::UnallocatedSegment(2, 2))
+        )
+    }
+
     #[test]
     fn write_arg_with_apply_modulo() {
         let mut big_num = num_bigint::BigInt::from_str_radix(&felt::PRIME_STR[2..], 16)
@@ -596,7 +784,7 @@ mod tests {
 
         assert_eq!(
             memory_segment_manager.get_memory_holes(accessed_addresses.into_iter()),
-            Err(MemoryError::MissingSegmentUsedSizes),
+            Err(MemoryError::EffectiveSizesNotCalled),
         );
     }
 
@@ -620,7 +808,10 @@ mod tests {
         let accessed_addresses = vec![(0, 0).into(), (0, 1).into(), (0, 2).into(), (0, 3).into()];
         assert_eq!(
             memory_segment_manager.get_memory_holes(accessed_addresses.into_iter()),
-            Err(MemoryError::NumOutOfBounds),
+            Err(MemoryError::AddressOutOfSegmentBounds {
+                addr: Relocatable::from((0, 3)),
+                size: 2,
+            }),
         );
     }
 
@@ -808,6 +999,67 @@ mod tests {
         );
     }
 
+    #[test]
+    fn gen_cairo_arg_single() {
+        let mut segments = MemorySegmentManager::new();
+        let mut memory = Memory::new();
+
+        assert_eq!(
+            segments.gen_cairo_arg(&mayberelocatable!(1234).into(), &mut memory),
+            Ok(mayberelocatable!(1234)),
+        );
+        assert_eq!(segments.num_segments, 0);
+    }
+
+    #[test]
+    fn gen_cairo_arg_array() {
+        let mut segments = MemorySegmentManager::new();
+        let mut memory = Memory::new();
+
+        assert_eq!(
+            segments.gen_cairo_arg(
+                &vec![mayberelocatable!(1), mayberelocatable!(0, 0)].into(),
+                &mut memory
+            ),
+            Ok(mayberelocatable!(0, 0)),
+        );
+        assert_eq!(
+            memory.data,
+            vec![vec![Some(mayberelocatable!(1)), Some(mayberelocatable!(0, 0))]]
+        );
+    }
+
+    /// Test that the call to .gen_cairo_arg() with a composed argument writes
+    /// its inner arrays first, then the pointers to them.
+    #[test]
+    fn gen_cairo_arg_composed() {
+        let mut segments = MemorySegmentManager::new();
+        let mut memory = Memory::new();
+
+        let arg = CairoArg::Composed(vec![
+            mayberelocatable!(7).into(),
+            vec![mayberelocatable!(1), mayberelocatable!(2)].into(),
+            CairoArg::Composed(vec![vec![mayberelocatable!(3)].into()]),
+        ]);
+        assert_eq!(
+            segments.gen_cairo_arg(&arg, &mut memory),
+            Ok(mayberelocatable!(3, 0)),
+        );
+        assert_eq!(
+            memory.data,
+            vec![
+                vec![Some(mayberelocatable!(1)), Some(mayberelocatable!(2))],
+                vec![Some(mayberelocatable!(3))],
+                vec![Some(mayberelocatable!(1, 0))],
+                vec![
+                    Some(mayberelocatable!(7)),
+                    Some(mayberelocatable!(0, 0)),
+                    Some(mayberelocatable!(2, 0))
+                ],
+            ]
+        );
+    }
+
     /// Test that the call to .gen_typed_args() with an empty vector returns an
     /// empty vector.
     #[test]
@@ -848,14 +1100,14 @@ mod tests {
     #[test]
     fn finalize_no_size_nor_memory_no_change() {
         let mut segments = MemorySegmentManager::new();
-        segments.finalize(None, 0, None);
+        segments.finalize(None, 0, None).unwrap();
         assert_eq!(segments, MemorySegmentManager::new());
     }
 
     #[test]
     fn finalize_no_memory() {
         let mut segments = MemorySegmentManager::new();
-        segments.finalize(Some(42), 0, None);
+        segments.finalize(Some(42), 0, None).unwrap();
         assert!(segments.public_memory_offsets.is_empty());
         assert_eq!(segments.segment_sizes, HashMap::from([(0, 42)]));
     }
@@ -863,7 +1115,7 @@ mod tests {
     #[test]
     fn finalize_no_size() {
         let mut segments = MemorySegmentManager::new();
-        segments.finalize(None, 0, Some(&vec![(1_usize, 2_usize)]));
+        segments.finalize(None, 0, Some(&vec![(1_usize, 2_usize)])).unwrap();
         assert_eq!(
             segments.public_memory_offsets,
             HashMap::from([(0_usize, vec![(1_usize, 2_usize)])])
@@ -874,11 +1126,51 @@ mod tests {
     #[test]
     fn finalize_all_args() {
         let mut segments = MemorySegmentManager::new();
-        segments.finalize(Some(42), 0, Some(&vec![(1_usize, 2_usize)]));
+        segments.finalize(Some(42), 0, Some(&vec![(1_usize, 2_usize)])).unwrap();
         assert_eq!(
             segments.public_memory_offsets,
             HashMap::from([(0_usize, vec![(1_usize, 2_usize)])])
         );
         assert_eq!(segments.segment_sizes, HashMap::from([(0, 42)]));
     }
+
+    #[test]
+    fn finalize_below_used_size() {
+        let mut segments = MemorySegmentManager::new();
+        segments.segment_used_sizes = Some(vec![3, 7]);
+        assert_eq!(segments.finalize(Some(7), 1, None), Ok(()));
+        assert_eq!(
+            segments.finalize(Some(2), 0, Some(&vec![(0, 0)])),
+            Err(MemoryError::FinalizedSizeBelowUsedSize {
+                segment_index: 0,
+                size: 2,
+                used_size: 3
+            })
+        );
+        assert_eq!(segments.segment_sizes, HashMap::from([(1, 7)]));
+        assert!(segments.public_memory_offsets.is_empty());
+    }
+
+    #[test]
+    fn get_public_memory_addresses() {
+        let mut segments = MemorySegmentManager::new();
+        let mut memory = Memory::new();
+        for _ in 0..3 {
+            segments.add(&mut memory);
+        }
+        segments
+            .finalize(None, 2, Some(&vec![(0, 1), (2, 1)]))
+            .unwrap();
+        segments
+            .finalize(Some(3), 0, Some(&vec![(0, 0), (1, 0), (2, 0)]))
+            .unwrap();
+        assert_eq!(
+            segments.get_public_memory_addresses(&[1, 4, 9]),
+            Ok(vec![(1, 0), (2, 0), (3, 0), (9, 1), (11, 1)])
+        );
+        assert_eq!(
+            segments.get_public_memory_addresses(&[1, 4]),
+            Err(MemoryError::UnallocatedSegment(2, 2))
+        );
+    }
 }
@@ -2,7 +2,8 @@ use cairo_vm::{
     hint_processor::builtin_hint_processor::builtin_hint_processor_definition::BuiltinHintProcessor,
     types::program::Program,
     vm::{
-        runners::cairo_runner::CairoRunner, trace::trace_entry::RelocatedTraceEntry,
+        runners::cairo_runner::{CairoRunner, RunResources},
+        trace::trace_entry::RelocatedTraceEntry,
         vm_core::VirtualMachine,
     },
 };
@@ -19,11 +20,16 @@ fn bitwise_integration_test() {
     let mut vm = VirtualMachine::new(true);
     let end = cairo_runner.initialize(&mut vm).unwrap();
     assert!(
-        cairo_runner.run_until_pc(end, &mut vm, &mut hint_processor) == Ok(()),
+        cairo_runner.run_until_pc(
+            end,
+            &mut RunResources::default(),
+            &mut vm,
+            &mut hint_processor
+        ) == Ok(()),
         "Execution failed"
     );
     assert!(
-        cairo_runner.relocate(&mut vm,) == Ok(()),
+        cairo_runner.relocate(&mut vm, None) == Ok(()),
         "Execution failed"
     );
 
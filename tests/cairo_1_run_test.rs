@@ -0,0 +1,109 @@
+use cairo_vm::{
+    hint_processor::cairo_1_hint_processor::{
+        casm_program::CasmProgram, hint_processor::Cairo1HintProcessor,
+    },
+    types::relocatable::MaybeRelocatable,
+    vm::{
+        runners::cairo_runner::{CairoArg, CairoRunner, RunResources},
+        vm_core::VirtualMachine,
+    },
+};
+use felt::{Felt, NewFelt};
+use std::path::Path;
+
+const INITIAL_GAS: u64 = 1_000_000;
+
+/// Runs the function at pc 0 of a CASM program with the range check builtin.
+/// The function receives a range check pointer and the available gas, followed by the arguments
+/// returned by `setup_args`, which is called once the runner's segments are initialized.
+fn run_casm_program(
+    path: &str,
+    setup_args: impl FnOnce(&mut VirtualMachine) -> Vec<MaybeRelocatable>,
+) -> VirtualMachine {
+    let casm_program = CasmProgram::from_file(Path::new(path)).unwrap();
+    let program = casm_program
+        .to_program(vec![String::from("range_check")])
+        .unwrap();
+    let mut hint_processor = Cairo1HintProcessor::new(&casm_program.hints);
+    let mut cairo_runner = CairoRunner::new(&program, "all", false).unwrap();
+    let mut vm = VirtualMachine::new(false);
+
+    cairo_runner.initialize_builtins(&mut vm).unwrap();
+    cairo_runner
+        .initialize_segments(&mut vm, None, None)
+        .unwrap();
+
+    let mut args = vec![
+        MaybeRelocatable::from((2, 0)),
+        MaybeRelocatable::from(Felt::new(INITIAL_GAS)),
+    ];
+    args.extend(setup_args(&mut vm));
+    let args: Vec<CairoArg> = args.into_iter().map(CairoArg::from).collect();
+
+    cairo_runner
+        .run_from_entrypoint(
+            0,
+            &args.iter().collect::<Vec<_>>(),
+            true,
+            &mut RunResources::default(),
+            &mut vm,
+            &mut hint_processor,
+        )
+        .unwrap();
+    vm
+}
+
+/// Returns the value `n` cells before the final ap, where the function's return values end.
+fn return_value(vm: &VirtualMachine, n: usize) -> MaybeRelocatable {
+    vm.get_maybe(&vm.get_ap().sub_usize(n).unwrap())
+        .unwrap()
+        .unwrap()
+}
+
+#[test]
+fn cairo_1_fibonacci() {
+    let vm = run_casm_program("cairo_programs/cairo-1-programs/fibonacci.casm", |_| {
+        vec![MaybeRelocatable::from(Felt::new(10))]
+    });
+
+    // Returns (range_check_ptr, gas, fib(10))
+    assert_eq!(return_value(&vm, 1), MaybeRelocatable::from(Felt::new(89)));
+    // Each gas withdrawal uses a range check
+    assert_eq!(return_value(&vm, 3), MaybeRelocatable::from((2, 10)));
+}
+
+#[test]
+fn cairo_1_dict() {
+    let mut segment_arena_ptr = None;
+    let vm = run_casm_program("cairo_programs/cairo-1-programs/dict.casm", |vm| {
+        // The segment arena holds [dict_infos_ptr, n_dicts, n_destructed] triplets, and the
+        // function receives a pointer to the end of the last one
+        let dict_infos = vm.add_memory_segment();
+        let segment_arena = vm.add_memory_segment();
+        let segment_arena_end = vm
+            .load_data(
+                &segment_arena.into(),
+                &vec![dict_infos.into(), Felt::new(0).into(), Felt::new(0).into()],
+            )
+            .unwrap();
+        segment_arena_ptr = Some(segment_arena_end.clone());
+        vec![segment_arena_end]
+    });
+
+    // Returns (range_check_ptr, gas, segment_arena_ptr, dict[7] + dict[3])
+    assert_eq!(return_value(&vm, 1), MaybeRelocatable::from(Felt::new(18)));
+    // Creating and squashing the dict each append a triplet to the segment arena
+    assert_eq!(
+        return_value(&vm, 2),
+        segment_arena_ptr.unwrap().add_usize(6)
+    );
+    let segment_arena_end = return_value(&vm, 2).get_relocatable().unwrap();
+    // n_dicts and n_destructed
+    let dict_counters: Vec<Felt> = vm
+        .get_integer_range(&segment_arena_end.sub_usize(2).unwrap(), 2)
+        .unwrap()
+        .into_iter()
+        .map(|value| value.into_owned())
+        .collect();
+    assert_eq!(dict_counters, vec![Felt::new(1), Felt::new(1)]);
+}
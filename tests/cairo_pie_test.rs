@@ -0,0 +1,144 @@
+use std::{collections::HashMap, fs::File, io::Read, path::Path, rc::Rc};
+
+use cairo_vm::{
+    hint_processor::{
+        builtin_hint_processor::builtin_hint_processor_definition::{
+            BuiltinHintProcessor, HintFunc,
+        },
+        hint_processor_definition::HintReference,
+    },
+    serde::deserialize_program::ApTracking,
+    types::{exec_scope::ExecutionScopes, program::Program},
+    vm::{
+        errors::hint_errors::HintError,
+        runners::{
+            cairo_pie::CairoPie,
+            cairo_runner::{CairoRunner, RunResources, SegmentInfo},
+        },
+        vm_core::VirtualMachine,
+    },
+};
+use felt::Felt;
+use serde_json::{json, Value};
+
+// The program segment holds the 24 words of the program, and the execution segment the return
+// fp and pc, the 3 arguments and the frame of main, 6 cells for each of the 10 recursive calls
+// to fib and the 2 values returned by the last one
+const PROGRAM_SIZE: usize = 24;
+const EXECUTION_SIZE: usize = 69;
+
+fn fibonacci_pie() -> CairoPie {
+    let program = Program::from_file(
+        Path::new("cairo_programs/manually_compiled/fib_child.json"),
+        Some("main"),
+    )
+    .expect("Failed to deserialize program");
+    let mut hint_processor = BuiltinHintProcessor::new_empty();
+    hint_processor.add_hint(
+        String::from("count_fib_step"),
+        Rc::new(HintFunc(Box::new(
+            |_vm: &mut VirtualMachine,
+             _exec_scopes: &mut ExecutionScopes,
+             _ids_data: &HashMap<String, HintReference>,
+             _ap_tracking: &ApTracking,
+             _constants: &HashMap<String, Felt>|
+             -> Result<(), HintError> { Ok(()) },
+        ))),
+    );
+    let mut cairo_runner = CairoRunner::new(&program, "all", false).unwrap();
+    let mut vm = VirtualMachine::new(false);
+    let end = cairo_runner.initialize(&mut vm).unwrap();
+    cairo_runner
+        .run_until_pc(
+            end,
+            &mut RunResources::default(),
+            &mut vm,
+            &mut hint_processor,
+        )
+        .unwrap();
+    cairo_runner
+        .end_run(false, false, &mut vm, &mut hint_processor)
+        .unwrap();
+    cairo_runner.get_cairo_pie(&vm).unwrap()
+}
+
+#[test]
+fn fibonacci_cairo_pie() {
+    let cairo_pie = fibonacci_pie();
+
+    let metadata = &cairo_pie.metadata;
+    assert_eq!(metadata.program.main, 0);
+    assert_eq!(metadata.program.data.len(), PROGRAM_SIZE);
+    assert!(metadata.program.builtins.is_empty());
+    assert_eq!(
+        metadata.program_segment,
+        SegmentInfo {
+            index: 0,
+            size: PROGRAM_SIZE
+        }
+    );
+    assert_eq!(
+        metadata.execution_segment,
+        SegmentInfo {
+            index: 1,
+            size: EXECUTION_SIZE
+        }
+    );
+    assert_eq!(metadata.ret_fp_segment, SegmentInfo { index: 2, size: 0 });
+    assert_eq!(metadata.ret_pc_segment, SegmentInfo { index: 3, size: 0 });
+    assert!(metadata.builtin_segments.is_empty());
+    assert!(metadata.extra_segments.is_empty());
+
+    assert_eq!(cairo_pie.memory.len(), PROGRAM_SIZE + EXECUTION_SIZE);
+    assert_eq!(cairo_pie.execution_resources.n_steps, 80);
+    assert_eq!(cairo_pie.execution_resources.n_memory_holes, 0);
+}
+
+#[test]
+fn fibonacci_cairo_pie_zip() {
+    let cairo_pie = fibonacci_pie();
+    let path = std::env::temp_dir().join("fibonacci_cairo_rs_pie.zip");
+    cairo_pie.write_zip(&path).unwrap();
+
+    let mut zip = zip::ZipArchive::new(File::open(&path).unwrap()).unwrap();
+    let mut read_file = |name: &str| {
+        let mut contents = Vec::new();
+        zip.by_name(name)
+            .unwrap()
+            .read_to_end(&mut contents)
+            .unwrap();
+        contents
+    };
+
+    let metadata: Value = serde_json::from_slice(&read_file("metadata.json")).unwrap();
+    assert_eq!(
+        metadata["program_segment"],
+        json!({ "index": 0, "size": PROGRAM_SIZE })
+    );
+    assert_eq!(
+        metadata["execution_segment"],
+        json!({ "index": 1, "size": EXECUTION_SIZE })
+    );
+    assert_eq!(metadata["ret_fp_segment"], json!({ "index": 2, "size": 0 }));
+    assert_eq!(metadata["ret_pc_segment"], json!({ "index": 3, "size": 0 }));
+    assert_eq!(metadata["builtin_segments"], json!({}));
+    assert_eq!(metadata["program"]["data"][0], json!("0x480680017fff8000"));
+
+    // Each cell takes an 8 bytes address and a 32 bytes value
+    let memory = read_file("memory.bin");
+    assert_eq!(memory.len(), (PROGRAM_SIZE + EXECUTION_SIZE) * 40);
+    // The first cell is the first word of the program, at (0, 0)
+    assert_eq!(memory[..8], (1u64 << 63).to_le_bytes());
+
+    let execution_resources: Value =
+        serde_json::from_slice(&read_file("execution_resources.json")).unwrap();
+    assert_eq!(
+        execution_resources,
+        json!({ "n_steps": 80, "n_memory_holes": 0, "builtin_instance_counter": {} })
+    );
+    let additional_data: Value =
+        serde_json::from_slice(&read_file("additional_data.json")).unwrap();
+    assert_eq!(additional_data, json!({}));
+    let version: Value = serde_json::from_slice(&read_file("version.json")).unwrap();
+    assert_eq!(version, json!({ "cairo_pie": "1.0" }));
+}
@@ -1,784 +1,290 @@
-use cairo_vm::cairo_run;
+use cairo_vm::cairo_run::{self, CairoRunConfig};
 use cairo_vm::hint_processor::builtin_hint_processor::builtin_hint_processor_definition::BuiltinHintProcessor;
-use std::path::Path;
+use cairo_vm::vm::errors::cairo_run_errors::CairoRunError;
+use cairo_vm::vm::runners::cairo_runner::CairoRunner;
+use cairo_vm::vm::vm_core::VirtualMachine;
+
+fn run_program(path: &str, layout: &str) -> Result<(CairoRunner, VirtualMachine), CairoRunError> {
+    let program_content = std::fs::read(path).unwrap();
+    let mut hint_executor = BuiltinHintProcessor::new_empty();
+    let cairo_run_config = CairoRunConfig {
+        layout,
+        ..Default::default()
+    };
+    cairo_run::cairo_run(&program_content, &cairo_run_config, &mut hint_executor)
+}
 
 #[test]
 fn cairo_run_test() {
-    let mut hint_executor = BuiltinHintProcessor::new_empty();
-    cairo_run::cairo_run(
-        Path::new("cairo_programs/fibonacci.json"),
-        "main",
-        false,
-        false,
-        "small",
-        false,
-        &mut hint_executor,
-    )
-    .expect("Couldn't run program");
+    run_program("cairo_programs/fibonacci.json", "small").expect("Couldn't run program");
 }
 
 #[test]
 fn cairo_run_array_sum() {
-    let mut hint_executor = BuiltinHintProcessor::new_empty();
-    cairo_run::cairo_run(
-        Path::new("cairo_programs/array_sum.json"),
-        "main",
-        false,
-        false,
-        "small",
-        false,
-        &mut hint_executor,
-    )
-    .expect("Couldn't run program");
+    run_program("cairo_programs/array_sum.json", "small").expect("Couldn't run program");
 }
 
 #[test]
 fn cairo_run_big_struct() {
-    let mut hint_executor = BuiltinHintProcessor::new_empty();
-    cairo_run::cairo_run(
-        Path::new("cairo_programs/big_struct.json"),
-        "main",
-        false,
-        false,
-        "small",
-        false,
-        &mut hint_executor,
-    )
-    .expect("Couldn't run program");
+    run_program("cairo_programs/big_struct.json", "small").expect("Couldn't run program");
 }
 
 #[test]
 fn cairo_run_call_function_assign_param_by_name() {
-    let mut hint_executor = BuiltinHintProcessor::new_empty();
-    cairo_run::cairo_run(
-        Path::new("cairo_programs/call_function_assign_param_by_name.json"),
-        "main",
-        false,
-        false,
+    run_program(
+        "cairo_programs/call_function_assign_param_by_name.json",
         "small",
-        false,
-        &mut hint_executor,
     )
     .expect("Couldn't run program");
 }
 
 #[test]
 fn cairo_run_function_return() {
-    let mut hint_executor = BuiltinHintProcessor::new_empty();
-    cairo_run::cairo_run(
-        Path::new("cairo_programs/function_return.json"),
-        "main",
-        false,
-        false,
-        "small",
-        false,
-        &mut hint_executor,
-    )
-    .expect("Couldn't run program");
+    run_program("cairo_programs/function_return.json", "small").expect("Couldn't run program");
 }
 
 #[test]
 fn cairo_run_function_return_if_print() {
-    let mut hint_executor = BuiltinHintProcessor::new_empty();
-    cairo_run::cairo_run(
-        Path::new("cairo_programs/function_return_if_print.json"),
-        "main",
-        false,
-        false,
-        "small",
-        false,
-        &mut hint_executor,
-    )
-    .expect("Couldn't run program");
+    run_program("cairo_programs/function_return_if_print.json", "small")
+        .expect("Couldn't run program");
 }
 
 #[test]
 fn cairo_run_function_return_to_variable() {
-    let mut hint_executor = BuiltinHintProcessor::new_empty();
-    cairo_run::cairo_run(
-        Path::new("cairo_programs/function_return_to_variable.json"),
-        "main",
-        false,
-        false,
-        "small",
-        false,
-        &mut hint_executor,
-    )
-    .expect("Couldn't run program");
+    run_program("cairo_programs/function_return_to_variable.json", "small")
+        .expect("Couldn't run program");
 }
 
 #[test]
 fn cairo_run_if_and_prime() {
-    let mut hint_executor = BuiltinHintProcessor::new_empty();
-    cairo_run::cairo_run(
-        Path::new("cairo_programs/if_and_prime.json"),
-        "main",
-        false,
-        false,
-        "small",
-        false,
-        &mut hint_executor,
-    )
-    .expect("Couldn't run program");
+    run_program("cairo_programs/if_and_prime.json", "small").expect("Couldn't run program");
 }
 
 #[test]
 fn cairo_run_if_in_function() {
-    let mut hint_executor = BuiltinHintProcessor::new_empty();
-    cairo_run::cairo_run(
-        Path::new("cairo_programs/if_in_function.json"),
-        "main",
-        false,
-        false,
-        "small",
-        false,
-        &mut hint_executor,
-    )
-    .expect("Couldn't run program");
+    run_program("cairo_programs/if_in_function.json", "small").expect("Couldn't run program");
 }
 
 #[test]
 fn cairo_run_if_list() {
-    let mut hint_executor = BuiltinHintProcessor::new_empty();
-    cairo_run::cairo_run(
-        Path::new("cairo_programs/if_list.json"),
-        "main",
-        false,
-        false,
-        "small",
-        false,
-        &mut hint_executor,
-    )
-    .expect("Couldn't run program");
+    run_program("cairo_programs/if_list.json", "small").expect("Couldn't run program");
 }
 
 #[test]
 fn cairo_run_jmp() {
-    let mut hint_executor = BuiltinHintProcessor::new_empty();
-    cairo_run::cairo_run(
-        Path::new("cairo_programs/jmp.json"),
-        "main",
-        false,
-        false,
-        "small",
-        false,
-        &mut hint_executor,
-    )
-    .expect("Couldn't run program");
+    run_program("cairo_programs/jmp.json", "small").expect("Couldn't run program");
 }
 
 #[test]
 fn cairo_run_jmp_if_condition() {
-    let mut hint_executor = BuiltinHintProcessor::new_empty();
-    cairo_run::cairo_run(
-        Path::new("cairo_programs/jmp_if_condition.json"),
-        "main",
-        false,
-        false,
-        "small",
-        false,
-        &mut hint_executor,
-    )
-    .expect("Couldn't run program");
+    run_program("cairo_programs/jmp_if_condition.json", "small").expect("Couldn't run program");
 }
 
 #[test]
 fn cairo_run_pointers() {
-    let mut hint_executor = BuiltinHintProcessor::new_empty();
-    cairo_run::cairo_run(
-        Path::new("cairo_programs/pointers.json"),
-        "main",
-        false,
-        false,
-        "small",
-        false,
-        &mut hint_executor,
-    )
-    .expect("Couldn't run program");
+    run_program("cairo_programs/pointers.json", "small").expect("Couldn't run program");
 }
 
 #[test]
 fn cairo_run_print() {
-    let mut hint_executor = BuiltinHintProcessor::new_empty();
-    cairo_run::cairo_run(
-        Path::new("cairo_programs/print.json"),
-        "main",
-        false,
-        false,
-        "small",
-        false,
-        &mut hint_executor,
-    )
-    .expect("Couldn't run program");
+    run_program("cairo_programs/print.json", "small").expect("Couldn't run program");
 }
 
 #[test]
 fn cairo_run_return() {
-    let mut hint_executor = BuiltinHintProcessor::new_empty();
-    cairo_run::cairo_run(
-        Path::new("cairo_programs/return.json"),
-        "main",
-        false,
-        false,
-        "small",
-        false,
-        &mut hint_executor,
-    )
-    .expect("Couldn't run program");
+    run_program("cairo_programs/return.json", "small").expect("Couldn't run program");
 }
 
 #[test]
 fn cairo_run_reversed_register_instructions() {
-    let mut hint_executor = BuiltinHintProcessor::new_empty();
-    cairo_run::cairo_run(
-        Path::new("cairo_programs/reversed_register_instructions.json"),
-        "main",
-        false,
-        false,
+    run_program(
+        "cairo_programs/reversed_register_instructions.json",
         "small",
-        false,
-        &mut hint_executor,
     )
     .expect("Couldn't run program");
 }
 
 #[test]
 fn cairo_run_simple_print() {
-    let mut hint_executor = BuiltinHintProcessor::new_empty();
-    cairo_run::cairo_run(
-        Path::new("cairo_programs/simple_print.json"),
-        "main",
-        false,
-        false,
-        "small",
-        false,
-        &mut hint_executor,
-    )
-    .expect("Couldn't run program");
+    run_program("cairo_programs/simple_print.json", "small").expect("Couldn't run program");
 }
 
 #[test]
 fn cairo_run_test_addition_if() {
-    let mut hint_executor = BuiltinHintProcessor::new_empty();
-    cairo_run::cairo_run(
-        Path::new("cairo_programs/test_addition_if.json"),
-        "main",
-        false,
-        false,
-        "small",
-        false,
-        &mut hint_executor,
-    )
-    .expect("Couldn't run program");
+    run_program("cairo_programs/test_addition_if.json", "small").expect("Couldn't run program");
 }
 
 #[test]
 fn cairo_run_test_reverse_if() {
-    let mut hint_executor = BuiltinHintProcessor::new_empty();
-    cairo_run::cairo_run(
-        Path::new("cairo_programs/test_reverse_if.json"),
-        "main",
-        false,
-        false,
-        "small",
-        false,
-        &mut hint_executor,
-    )
-    .expect("Couldn't run program");
+    run_program("cairo_programs/test_reverse_if.json", "small").expect("Couldn't run program");
 }
 
 #[test]
 fn cairo_run_test_subtraction_if() {
-    let mut hint_executor = BuiltinHintProcessor::new_empty();
-    cairo_run::cairo_run(
-        Path::new("cairo_programs/test_subtraction_if.json"),
-        "main",
-        false,
-        false,
-        "small",
-        false,
-        &mut hint_executor,
-    )
-    .expect("Couldn't run program");
+    run_program("cairo_programs/test_subtraction_if.json", "small").expect("Couldn't run program");
 }
 
 #[test]
 fn cairo_run_use_imported_module() {
-    let mut hint_executor = BuiltinHintProcessor::new_empty();
-    cairo_run::cairo_run(
-        Path::new("cairo_programs/use_imported_module.json"),
-        "main",
-        false,
-        false,
-        "all",
-        false,
-        &mut hint_executor,
-    )
-    .expect("Couldn't run program");
+    run_program("cairo_programs/use_imported_module.json", "all").expect("Couldn't run program");
 }
 
 #[test]
 fn cairo_run_bitwise_output() {
-    let mut hint_executor = BuiltinHintProcessor::new_empty();
-    cairo_run::cairo_run(
-        Path::new("cairo_programs/bitwise_output.json"),
-        "main",
-        false,
-        false,
-        "all",
-        false,
-        &mut hint_executor,
-    )
-    .expect("Couldn't run program");
+    run_program("cairo_programs/bitwise_output.json", "all").expect("Couldn't run program");
 }
 
 #[test]
 fn cairo_run_bitwise_recursion() {
-    let mut hint_executor = BuiltinHintProcessor::new_empty();
-    cairo_run::cairo_run(
-        Path::new("cairo_programs/bitwise_recursion.json"),
-        "main",
-        false,
-        false,
-        "all",
-        false,
-        &mut hint_executor,
-    )
-    .expect("Couldn't run program");
+    run_program("cairo_programs/bitwise_recursion.json", "all").expect("Couldn't run program");
 }
 
 #[test]
 fn cairo_run_integration() {
-    let mut hint_executor = BuiltinHintProcessor::new_empty();
-    cairo_run::cairo_run(
-        Path::new("cairo_programs/integration.json"),
-        "main",
-        false,
-        false,
-        "all",
-        false,
-        &mut hint_executor,
-    )
-    .expect("Couldn't run program");
+    run_program("cairo_programs/integration.json", "all").expect("Couldn't run program");
 }
 
 #[test]
 fn cairo_run_integration_with_alloc_locals() {
-    let mut hint_executor = BuiltinHintProcessor::new_empty();
-    cairo_run::cairo_run(
-        Path::new("cairo_programs/integration_with_alloc_locals.json"),
-        "main",
-        false,
-        false,
-        "all",
-        false,
-        &mut hint_executor,
-    )
-    .expect("Couldn't run program");
+    run_program("cairo_programs/integration_with_alloc_locals.json", "all")
+        .expect("Couldn't run program");
 }
 
 #[test]
 fn cairo_run_compare_arrays() {
-    let mut hint_executor = BuiltinHintProcessor::new_empty();
-    cairo_run::cairo_run(
-        Path::new("cairo_programs/compare_arrays.json"),
-        "main",
-        false,
-        false,
-        "all",
-        false,
-        &mut hint_executor,
-    )
-    .expect("Couldn't run program");
+    run_program("cairo_programs/compare_arrays.json", "all").expect("Couldn't run program");
 }
 
 #[test]
 fn cairo_run_compare_greater_array() {
-    let mut hint_executor = BuiltinHintProcessor::new_empty();
-    cairo_run::cairo_run(
-        Path::new("cairo_programs/compare_greater_array.json"),
-        "main",
-        false,
-        false,
-        "all",
-        false,
-        &mut hint_executor,
-    )
-    .expect("Couldn't run program");
+    run_program("cairo_programs/compare_greater_array.json", "all").expect("Couldn't run program");
 }
 
 #[test]
 fn cairo_run_compare_lesser_array() {
-    let mut hint_executor = BuiltinHintProcessor::new_empty();
-    cairo_run::cairo_run(
-        Path::new("cairo_programs/compare_lesser_array.json"),
-        "main",
-        false,
-        false,
-        "all",
-        false,
-        &mut hint_executor,
-    )
-    .expect("Couldn't run program");
+    run_program("cairo_programs/compare_lesser_array.json", "all").expect("Couldn't run program");
 }
 
 #[test]
 fn cairo_run_assert_le_felt_hint() {
-    let mut hint_executor = BuiltinHintProcessor::new_empty();
-    cairo_run::cairo_run(
-        Path::new("cairo_programs/assert_le_felt_hint.json"),
-        "main",
-        false,
-        false,
-        "all",
-        false,
-        &mut hint_executor,
-    )
-    .expect("Couldn't run program");
+    run_program("cairo_programs/assert_le_felt_hint.json", "all").expect("Couldn't run program");
 }
 
 #[test]
 fn cairo_run_assert_250_bit_element_array() {
-    let mut hint_executor = BuiltinHintProcessor::new_empty();
-    cairo_run::cairo_run(
-        Path::new("cairo_programs/assert_250_bit_element_array.json"),
-        "main",
-        false,
-        false,
-        "all",
-        false,
-        &mut hint_executor,
-    )
-    .expect("Couldn't run program");
+    run_program("cairo_programs/assert_250_bit_element_array.json", "all")
+        .expect("Couldn't run program");
 }
 
 #[test]
 fn cairo_abs_value() {
-    let mut hint_executor = BuiltinHintProcessor::new_empty();
-    cairo_run::cairo_run(
-        Path::new("cairo_programs/abs_value_array.json"),
-        "main",
-        false,
-        false,
-        "all",
-        false,
-        &mut hint_executor,
-    )
-    .expect("Couldn't run program");
+    run_program("cairo_programs/abs_value_array.json", "all").expect("Couldn't run program");
 }
 
 #[test]
 fn cairo_run_compare_different_arrays() {
-    let mut hint_executor = BuiltinHintProcessor::new_empty();
-    cairo_run::cairo_run(
-        Path::new("cairo_programs/compare_different_arrays.json"),
-        "main",
-        false,
-        false,
-        "all",
-        false,
-        &mut hint_executor,
-    )
-    .expect("Couldn't run program");
+    run_program("cairo_programs/compare_different_arrays.json", "all")
+        .expect("Couldn't run program");
 }
 
 #[test]
 fn cairo_run_assert_nn() {
-    let mut hint_executor = BuiltinHintProcessor::new_empty();
-    cairo_run::cairo_run(
-        Path::new("cairo_programs/assert_nn.json"),
-        "main",
-        false,
-        false,
-        "all",
-        false,
-        &mut hint_executor,
-    )
-    .expect("Couldn't run program");
+    run_program("cairo_programs/assert_nn.json", "all").expect("Couldn't run program");
 }
 
 #[test]
 fn cairo_run_sqrt() {
-    let mut hint_executor = BuiltinHintProcessor::new_empty();
-    cairo_run::cairo_run(
-        Path::new("cairo_programs/sqrt.json"),
-        "main",
-        false,
-        false,
-        "all",
-        false,
-        &mut hint_executor,
-    )
-    .expect("Couldn't run program");
+    run_program("cairo_programs/sqrt.json", "all").expect("Couldn't run program");
 }
 
 #[test]
 fn cairo_run_assert_not_zero() {
-    let mut hint_executor = BuiltinHintProcessor::new_empty();
-    cairo_run::cairo_run(
-        Path::new("cairo_programs/assert_not_zero.json"),
-        "main",
-        false,
-        false,
-        "all",
-        false,
-        &mut hint_executor,
-    )
-    .expect("Couldn't run program");
+    run_program("cairo_programs/assert_not_zero.json", "all").expect("Couldn't run program");
 }
 
 #[test]
 fn cairo_run_split_int() {
-    let mut hint_executor = BuiltinHintProcessor::new_empty();
-    cairo_run::cairo_run(
-        Path::new("cairo_programs/split_int.json"),
-        "main",
-        false,
-        false,
-        "all",
-        false,
-        &mut hint_executor,
-    )
-    .expect("Couldn't run program");
+    run_program("cairo_programs/split_int.json", "all").expect("Couldn't run program");
 }
 
 #[test]
 fn cairo_run_split_int_big() {
-    let mut hint_executor = BuiltinHintProcessor::new_empty();
-    cairo_run::cairo_run(
-        Path::new("cairo_programs/split_int_big.json"),
-        "main",
-        false,
-        false,
-        "all",
-        false,
-        &mut hint_executor,
-    )
-    .expect("Couldn't run program");
+    run_program("cairo_programs/split_int_big.json", "all").expect("Couldn't run program");
 }
 
 #[test]
 fn cairo_run_split_felt() {
-    let mut hint_executor = BuiltinHintProcessor::new_empty();
-    cairo_run::cairo_run(
-        Path::new("cairo_programs/split_felt.json"),
-        "main",
-        false,
-        false,
-        "all",
-        false,
-        &mut hint_executor,
-    )
-    .expect("Couldn't run program");
+    run_program("cairo_programs/split_felt.json", "all").expect("Couldn't run program");
 }
 
 #[test]
 fn cairo_run_math_cmp() {
-    let mut hint_executor = BuiltinHintProcessor::new_empty();
-    cairo_run::cairo_run(
-        Path::new("cairo_programs/math_cmp.json"),
-        "main",
-        false,
-        false,
-        "all",
-        false,
-        &mut hint_executor,
-    )
-    .expect("Couldn't run program");
+    run_program("cairo_programs/math_cmp.json", "all").expect("Couldn't run program");
 }
 
 #[test]
 fn cairo_run_unsigned_div_rem() {
-    let mut hint_executor = BuiltinHintProcessor::new_empty();
-    cairo_run::cairo_run(
-        Path::new("cairo_programs/unsigned_div_rem.json"),
-        "main",
-        false,
-        false,
-        "all",
-        false,
-        &mut hint_executor,
-    )
-    .expect("Couldn't run program");
+    run_program("cairo_programs/unsigned_div_rem.json", "all").expect("Couldn't run program");
 }
 
 #[test]
 fn cairo_run_signed_div_rem() {
-    let mut hint_executor = BuiltinHintProcessor::new_empty();
-    cairo_run::cairo_run(
-        Path::new("cairo_programs/signed_div_rem.json"),
-        "main",
-        false,
-        false,
-        "all",
-        false,
-        &mut hint_executor,
-    )
-    .expect("Couldn't run program");
+    run_program("cairo_programs/signed_div_rem.json", "all").expect("Couldn't run program");
 }
 
 #[test]
 fn cairo_run_assert_lt_felt() {
-    let mut hint_executor = BuiltinHintProcessor::new_empty();
-    cairo_run::cairo_run(
-        Path::new("cairo_programs/assert_lt_felt.json"),
-        "main",
-        false,
-        false,
-        "all",
-        false,
-        &mut hint_executor,
-    )
-    .expect("Couldn't run program");
+    run_program("cairo_programs/assert_lt_felt.json", "all").expect("Couldn't run program");
 }
 
 #[test]
 fn cairo_run_memcpy() {
-    let mut hint_executor = BuiltinHintProcessor::new_empty();
-    cairo_run::cairo_run(
-        Path::new("cairo_programs/memcpy_test.json"),
-        "main",
-        false,
-        false,
-        "all",
-        false,
-        &mut hint_executor,
-    )
-    .expect("Couldn't run program");
+    run_program("cairo_programs/memcpy_test.json", "all").expect("Couldn't run program");
 }
 
 #[test]
 fn cairo_run_memset() {
-    let mut hint_executor = BuiltinHintProcessor::new_empty();
-    cairo_run::cairo_run(
-        Path::new("cairo_programs/memset.json"),
-        "main",
-        false,
-        false,
-        "all",
-        false,
-        &mut hint_executor,
-    )
-    .expect("Couldn't run program");
+    run_program("cairo_programs/memset.json", "all").expect("Couldn't run program");
 }
 
 #[test]
 fn cairo_run_pow() {
-    let mut hint_executor = BuiltinHintProcessor::new_empty();
-    cairo_run::cairo_run(
-        Path::new("cairo_programs/pow.json"),
-        "main",
-        false,
-        false,
-        "all",
-        false,
-        &mut hint_executor,
-    )
-    .expect("Couldn't run program");
+    run_program("cairo_programs/pow.json", "all").expect("Couldn't run program");
 }
 
 #[test]
 fn cairo_run_dict() {
-    let mut hint_executor = BuiltinHintProcessor::new_empty();
-    cairo_run::cairo_run(
-        Path::new("cairo_programs/dict.json"),
-        "main",
-        false,
-        false,
-        "all",
-        false,
-        &mut hint_executor,
-    )
-    .expect("Couldn't run program");
+    run_program("cairo_programs/dict.json", "all").expect("Couldn't run program");
 }
 
 #[test]
 fn cairo_run_dict_update() {
-    let mut hint_executor = BuiltinHintProcessor::new_empty();
-    cairo_run::cairo_run(
-        Path::new("cairo_programs/dict_update.json"),
-        "main",
-        false,
-        false,
-        "all",
-        false,
-        &mut hint_executor,
-    )
-    .expect("Couldn't run program");
+    run_program("cairo_programs/dict_update.json", "all").expect("Couldn't run program");
 }
 
 #[test]
 fn cairo_run_uint256() {
-    let mut hint_executor = BuiltinHintProcessor::new_empty();
-    cairo_run::cairo_run(
-        Path::new("cairo_programs/uint256.json"),
-        "main",
-        false,
-        false,
-        "all",
-        false,
-        &mut hint_executor,
-    )
-    .expect("Couldn't run program");
+    run_program("cairo_programs/uint256.json", "all").expect("Couldn't run program");
 }
 
 #[test]
 fn cairo_run_find_element() {
-    let mut hint_executor = BuiltinHintProcessor::new_empty();
-    cairo_run::cairo_run(
-        Path::new("cairo_programs/find_element.json"),
-        "main",
-        false,
-        false,
-        "all",
-        false,
-        &mut hint_executor,
-    )
-    .expect("Couldn't run program");
+    run_program("cairo_programs/find_element.json", "all").expect("Couldn't run program");
 }
 
 #[test]
 fn cairo_run_search_sorted_lower() {
-    let mut hint_executor = BuiltinHintProcessor::new_empty();
-    cairo_run::cairo_run(
-        Path::new("cairo_programs/search_sorted_lower.json"),
-        "main",
-        false,
-        false,
-        "all",
-        false,
-        &mut hint_executor,
-    )
-    .expect("Couldn't run program");
+    run_program("cairo_programs/search_sorted_lower.json", "all").expect("Couldn't run program");
 }
 
 #[test]
 fn cairo_run_usort() {
-    let mut hint_executor = BuiltinHintProcessor::new_empty();
-    cairo_run::cairo_run(
-        Path::new("cairo_programs/usort.json"),
-        "main",
-        false,
-        false,
-        "all",
-        false,
-        &mut hint_executor,
-    )
-    .expect("Couldn't run program");
+    run_program("cairo_programs/usort.json", "all").expect("Couldn't run program");
 }
 
 #[test]
 fn cairo_run_usort_bad() {
-    let mut hint_executor = BuiltinHintProcessor::new_empty();
-    let err = cairo_run::cairo_run(
-        Path::new("cairo_programs/bad_programs/bad_usort.json"),
-        "main",
-        false,
-        false,
-        "all",
-        false,
-        &mut hint_executor,
-    );
+    let err = run_program("cairo_programs/bad_programs/bad_usort.json", "all");
     assert!(err.is_err());
     assert!(err
         .err()
@@ -789,28 +295,9 @@ fn cairo_run_usort_bad() {
 
 #[test]
 fn cairo_run_dict_write_bad() {
-    let mut hint_executor = BuiltinHintProcessor::new_empty();
-    assert!(cairo_run::cairo_run(
-        Path::new("cairo_programs/bad_programs/bad_dict_new.json"),
-        "main",
-        false,
-        false,
-        "all",
-        false,
-        &mut hint_executor,
-    )
-    .is_err());
+    assert!(run_program("cairo_programs/bad_programs/bad_dict_new.json", "all").is_err());
 
-    let err = cairo_run::cairo_run(
-        Path::new("cairo_programs/bad_programs/bad_dict_new.json"),
-        "main",
-        false,
-        false,
-        "all",
-        false,
-        &mut hint_executor,
-    )
-    .err();
+    let err = run_program("cairo_programs/bad_programs/bad_dict_new.json", "all").err();
     assert!(err
         .unwrap()
         .to_string()
@@ -819,27 +306,8 @@ fn cairo_run_dict_write_bad() {
 
 #[test]
 fn cairo_run_dict_update_bad() {
-    let mut hint_executor = BuiltinHintProcessor::new_empty();
-    assert!(cairo_run::cairo_run(
-        Path::new("cairo_programs/bad_programs/bad_dict_update.json"),
-        "main",
-        false,
-        false,
-        "all",
-        false,
-        &mut hint_executor,
-    )
-    .is_err());
-    let err = cairo_run::cairo_run(
-        Path::new("cairo_programs/bad_programs/bad_dict_update.json"),
-        "main",
-        false,
-        false,
-        "all",
-        false,
-        &mut hint_executor,
-    )
-    .err();
+    assert!(run_program("cairo_programs/bad_programs/bad_dict_update.json", "all").is_err());
+    let err = run_program("cairo_programs/bad_programs/bad_dict_update.json", "all").err();
     assert!(err.unwrap().to_string().contains(
         "Dict Error: Got the wrong value for dict_update, expected value: 3, got: 5 for key: 2"
     ));
@@ -847,422 +315,157 @@ fn cairo_run_dict_update_bad() {
 
 #[test]
 fn cairo_run_squash_dict() {
-    let mut hint_executor = BuiltinHintProcessor::new_empty();
-    cairo_run::cairo_run(
-        Path::new("cairo_programs/squash_dict.json"),
-        "main",
-        false,
-        false,
-        "all",
-        false,
-        &mut hint_executor,
-    )
-    .expect("Couldn't run program");
+    run_program("cairo_programs/squash_dict.json", "all").expect("Couldn't run program");
 }
 
 #[test]
 fn cairo_run_dict_squash() {
-    let mut hint_executor = BuiltinHintProcessor::new_empty();
-    cairo_run::cairo_run(
-        Path::new("cairo_programs/dict_squash.json"),
-        "main",
-        false,
-        false,
-        "all",
-        false,
-        &mut hint_executor,
-    )
-    .expect("Couldn't run program");
+    run_program("cairo_programs/dict_squash.json", "all").expect("Couldn't run program");
 }
 
 #[test]
 fn cairo_run_set_add() {
-    let mut hint_executor = BuiltinHintProcessor::new_empty();
-    cairo_run::cairo_run(
-        Path::new("cairo_programs/set_add.json"),
-        "main",
-        false,
-        false,
-        "all",
-        false,
-        &mut hint_executor,
-    )
-    .expect("Couldn't run program");
+    run_program("cairo_programs/set_add.json", "all").expect("Couldn't run program");
 }
 
 #[test]
 fn cairo_run_secp() {
-    let mut hint_executor = BuiltinHintProcessor::new_empty();
-    cairo_run::cairo_run(
-        Path::new("cairo_programs/secp.json"),
-        "main",
-        false,
-        false,
-        "all",
-        false,
-        &mut hint_executor,
-    )
-    .expect("Couldn't run program");
+    run_program("cairo_programs/secp.json", "all").expect("Couldn't run program");
 }
 
 #[test]
 fn cairo_run_signature() {
-    let mut hint_executor = BuiltinHintProcessor::new_empty();
-    cairo_run::cairo_run(
-        Path::new("cairo_programs/signature.json"),
-        "main",
-        false,
-        false,
-        "all",
-        false,
-        &mut hint_executor,
-    )
-    .expect("Couldn't run program");
+    run_program("cairo_programs/signature.json", "all").expect("Couldn't run program");
 }
 
 #[test]
 fn cairo_run_secp_ec() {
-    let mut hint_executor = BuiltinHintProcessor::new_empty();
-    cairo_run::cairo_run(
-        Path::new("cairo_programs/secp_ec.json"),
-        "main",
-        false,
-        false,
-        "all",
-        false,
-        &mut hint_executor,
-    )
-    .expect("Couldn't run program");
+    run_program("cairo_programs/secp_ec.json", "all").expect("Couldn't run program");
 }
 
 #[test]
 fn cairo_run_blake2s_hello_world_hash() {
-    let mut hint_executor = BuiltinHintProcessor::new_empty();
-    cairo_run::cairo_run(
-        Path::new("cairo_programs/blake2s_hello_world_hash.json"),
-        "main",
-        false,
-        false,
-        "all",
-        false,
-        &mut hint_executor,
-    )
-    .expect("Couldn't run program");
+    run_program("cairo_programs/blake2s_hello_world_hash.json", "all")
+        .expect("Couldn't run program");
 }
 
 #[test]
 fn cairo_run_finalize_blake2s() {
-    let mut hint_executor = BuiltinHintProcessor::new_empty();
-    cairo_run::cairo_run(
-        Path::new("cairo_programs/finalize_blake2s.json"),
-        "main",
-        false,
-        false,
-        "all",
-        false,
-        &mut hint_executor,
-    )
-    .expect("Couldn't run program");
+    run_program("cairo_programs/finalize_blake2s.json", "all").expect("Couldn't run program");
 }
 #[test]
 fn cairo_run_unsafe_keccak() {
-    let mut hint_executor = BuiltinHintProcessor::new_empty();
-    cairo_run::cairo_run(
-        Path::new("cairo_programs/unsafe_keccak.json"),
-        "main",
-        false,
-        false,
-        "all",
-        false,
-        &mut hint_executor,
-    )
-    .expect("Couldn't run program");
+    run_program("cairo_programs/unsafe_keccak.json", "all").expect("Couldn't run program");
 }
 
 #[test]
 fn cairo_run_blake2s_felts() {
-    let mut hint_executor = BuiltinHintProcessor::new_empty();
-    cairo_run::cairo_run(
-        Path::new("cairo_programs/blake2s_felts.json"),
-        "main",
-        false,
-        false,
-        "all",
-        false,
-        &mut hint_executor,
-    )
-    .expect("Couldn't run program");
+    run_program("cairo_programs/blake2s_felts.json", "all").expect("Couldn't run program");
 }
 
 #[test]
 fn cairo_run_unsafe_keccak_finalize() {
-    let mut hint_executor = BuiltinHintProcessor::new_empty();
-    cairo_run::cairo_run(
-        Path::new("cairo_programs/unsafe_keccak_finalize.json"),
-        "main",
-        false,
-        false,
-        "all",
-        false,
-        &mut hint_executor,
-    )
-    .expect("Couldn't run program");
+    run_program("cairo_programs/unsafe_keccak_finalize.json", "all").expect("Couldn't run program");
 }
 
 #[test]
 fn cairo_run_keccak_add_uint256() {
-    let mut hint_executor = BuiltinHintProcessor::new_empty();
-    cairo_run::cairo_run(
-        Path::new("cairo_programs/keccak_add_uint256.json"),
-        "main",
-        false,
-        false,
-        "all",
-        false,
-        &mut hint_executor,
-    )
-    .expect("Couldn't run program");
+    run_program("cairo_programs/keccak_add_uint256.json", "all").expect("Couldn't run program");
 }
 
 #[test]
 fn cairo_run_private_keccak() {
-    let mut hint_executor = BuiltinHintProcessor::new_empty();
-    cairo_run::cairo_run(
-        Path::new("cairo_programs/_keccak.json"),
-        "main",
-        false,
-        false,
-        "all",
-        false,
-        &mut hint_executor,
-    )
-    .expect("Couldn't run program");
+    run_program("cairo_programs/_keccak.json", "all").expect("Couldn't run program");
 }
 
 #[test]
 fn cairo_run_keccak_copy_inputs() {
-    let mut hint_executor = BuiltinHintProcessor::new_empty();
-    cairo_run::cairo_run(
-        Path::new("cairo_programs/keccak_copy_inputs.json"),
-        "main",
-        false,
-        false,
-        "all",
-        false,
-        &mut hint_executor,
-    )
-    .expect("Couldn't run program");
+    run_program("cairo_programs/keccak_copy_inputs.json", "all").expect("Couldn't run program");
 }
 
 #[test]
 fn cairo_run_finalize_keccak() {
-    let mut hint_executor = BuiltinHintProcessor::new_empty();
-    cairo_run::cairo_run(
-        Path::new("cairo_programs/cairo_finalize_keccak.json"),
-        "main",
-        false,
-        false,
-        "all",
-        false,
-        &mut hint_executor,
-    )
-    .expect("Couldn't run program");
+    run_program("cairo_programs/cairo_finalize_keccak.json", "all").expect("Couldn't run program");
 }
 
 #[test]
 fn cairo_run_operations_with_data() {
-    let mut hint_executor = BuiltinHintProcessor::new_empty();
-    cairo_run::cairo_run(
-        Path::new("cairo_programs/operations_with_data_structures.json"),
-        "main",
-        false,
-        false,
-        "all",
-        false,
-        &mut hint_executor,
-    )
-    .expect("Couldn't run program");
+    run_program("cairo_programs/operations_with_data_structures.json", "all")
+        .expect("Couldn't run program");
 }
 
 #[test]
 fn cairo_run_sha256() {
-    let mut hint_executor = BuiltinHintProcessor::new_empty();
-    cairo_run::cairo_run(
-        Path::new("cairo_programs/sha256.json"),
-        "main",
-        false,
-        false,
-        "all",
-        false,
-        &mut hint_executor,
-    )
-    .expect("Couldn't run program");
+    run_program("cairo_programs/sha256.json", "all").expect("Couldn't run program");
 }
 
 #[test]
 fn cairo_run_math_cmp_and_pow_integration() {
-    let mut hint_executor = BuiltinHintProcessor::new_empty();
-    cairo_run::cairo_run(
-        Path::new("cairo_programs/math_cmp_and_pow_integration_tests.json"),
-        "main",
-        false,
-        false,
+    run_program(
+        "cairo_programs/math_cmp_and_pow_integration_tests.json",
         "all",
-        false,
-        &mut hint_executor,
     )
     .expect("Couldn't run program");
 }
 
 #[test]
 fn cairo_run_uint256_integration() {
-    let mut hint_executor = BuiltinHintProcessor::new_empty();
-    cairo_run::cairo_run(
-        Path::new("cairo_programs/uint256_integration_tests.json"),
-        "main",
-        false,
-        false,
-        "all",
-        false,
-        &mut hint_executor,
-    )
-    .expect("Couldn't run program");
+    run_program("cairo_programs/uint256_integration_tests.json", "all")
+        .expect("Couldn't run program");
 }
 
 #[test]
 fn cairo_run_set_integration() {
-    let mut hint_executor = BuiltinHintProcessor::new_empty();
-    cairo_run::cairo_run(
-        Path::new("cairo_programs/set_integration_tests.json"),
-        "main",
-        false,
-        false,
-        "all",
-        false,
-        &mut hint_executor,
-    )
-    .expect("Couldn't run program");
+    run_program("cairo_programs/set_integration_tests.json", "all").expect("Couldn't run program");
 }
 
 #[test]
 fn cairo_run_memory_module_integration() {
-    let mut hint_executor = BuiltinHintProcessor::new_empty();
-    cairo_run::cairo_run(
-        Path::new("cairo_programs/memory_integration_tests.json"),
-        "main",
-        false,
-        false,
-        "all",
-        false,
-        &mut hint_executor,
-    )
-    .expect("Couldn't run program");
+    run_program("cairo_programs/memory_integration_tests.json", "all")
+        .expect("Couldn't run program");
 }
 
 #[test]
 fn cairo_run_dict_integration() {
-    let mut hint_executor = BuiltinHintProcessor::new_empty();
-    cairo_run::cairo_run(
-        Path::new("cairo_programs/dict_integration_tests.json"),
-        "main",
-        false,
-        false,
-        "all",
-        false,
-        &mut hint_executor,
-    )
-    .expect("Couldn't run program");
+    run_program("cairo_programs/dict_integration_tests.json", "all").expect("Couldn't run program");
 }
 
 #[test]
 fn cairo_run_secp_integration() {
-    let mut hint_executor = BuiltinHintProcessor::new_empty();
-    cairo_run::cairo_run(
-        Path::new("cairo_programs/secp_integration_tests.json"),
-        "main",
-        false,
-        false,
-        "all",
-        false,
-        &mut hint_executor,
-    )
-    .expect("Couldn't run program");
+    run_program("cairo_programs/secp_integration_tests.json", "all").expect("Couldn't run program");
 }
 
 #[test]
 fn cairo_run_keccak_integration() {
-    let mut hint_executor = BuiltinHintProcessor::new_empty();
-    cairo_run::cairo_run(
-        Path::new("cairo_programs/keccak_integration_tests.json"),
-        "main",
-        false,
-        false,
-        "all",
-        false,
-        &mut hint_executor,
-    )
-    .expect("Couldn't run program");
+    run_program("cairo_programs/keccak_integration_tests.json", "all")
+        .expect("Couldn't run program");
 }
 
 #[test]
 fn cairo_run_blake2s_integration() {
-    let mut hint_executor = BuiltinHintProcessor::new_empty();
-    cairo_run::cairo_run(
-        Path::new("cairo_programs/blake2s_integration_tests.json"),
-        "main",
-        false,
-        false,
-        "all",
-        false,
-        &mut hint_executor,
-    )
-    .expect("Couldn't run program");
+    run_program("cairo_programs/blake2s_integration_tests.json", "all")
+        .expect("Couldn't run program");
 }
 
 #[test]
 fn cairo_run_relocate_segments() {
-    let mut hint_executor = BuiltinHintProcessor::new_empty();
-    cairo_run::cairo_run(
-        Path::new("cairo_programs/relocate_segments.json"),
-        "main",
-        false,
-        false,
-        "small",
-        false,
-        &mut hint_executor,
-    )
-    .expect("Couldn't run program");
+    run_program("cairo_programs/relocate_segments.json", "small").expect("Couldn't run program");
 }
 
 #[test]
 fn cairo_run_error_msg_attr() {
-    let mut hint_executor = BuiltinHintProcessor::new_empty();
-    let err = cairo_run::cairo_run(
-        Path::new("cairo_programs/bad_programs/error_msg_attr.json"),
-        "main",
-        false,
-        false,
-        "all",
-        false,
-        &mut hint_executor,
-    )
-    .err()
-    .unwrap();
+    let err = run_program("cairo_programs/bad_programs/error_msg_attr.json", "all")
+        .err()
+        .unwrap();
 
     assert!(err.to_string().contains("SafeUint256: addition overflow"));
 }
 
 #[test]
 fn cairo_run_error_msg_attr_ap_based_reference() {
-    let mut hint_executor = BuiltinHintProcessor::new_empty();
-    let err = cairo_run::cairo_run(
-        Path::new("cairo_programs/bad_programs/error_msg_attr_tempvar.json"),
-        "main",
-        false,
-        false,
+    let err = run_program(
+        "cairo_programs/bad_programs/error_msg_attr_tempvar.json",
         "all",
-        false,
-        &mut hint_executor,
     )
     .err()
     .unwrap();
@@ -1272,15 +475,9 @@ fn cairo_run_error_msg_attr_ap_based_reference() {
 
 #[test]
 fn cairo_run_error_msg_attr_complex_reference() {
-    let mut hint_executor = BuiltinHintProcessor::new_empty();
-    let err = cairo_run::cairo_run(
-        Path::new("cairo_programs/bad_programs/error_msg_attr_struct.json"),
-        "main",
-        false,
-        false,
+    let err = run_program(
+        "cairo_programs/bad_programs/error_msg_attr_struct.json",
         "all",
-        false,
-        &mut hint_executor,
     )
     .err()
     .unwrap();
@@ -1289,15 +486,10 @@ fn cairo_run_error_msg_attr_complex_reference() {
 
 #[test]
 fn cairo_run_dict_store_cast_pointer() {
-    let mut hint_executor = BuiltinHintProcessor::new_empty();
-    cairo_run::cairo_run(
-        Path::new("cairo_programs/dict_store_cast_ptr.json"),
-        "main",
-        false,
-        false,
-        "small",
-        false,
-        &mut hint_executor,
-    )
-    .expect("Couldn't run program");
+    run_program("cairo_programs/dict_store_cast_ptr.json", "small").expect("Couldn't run program");
+}
+
+#[test]
+fn cairo_run_ec_op() {
+    run_program("cairo_programs/ec_op.json", "all").expect("Couldn't run program");
 }
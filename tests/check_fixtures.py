@@ -0,0 +1,53 @@
+#!/usr/bin/env python3
+"""
+Checks that the programs embedded as test fixtures (cairo_programs/fixtures) match the output
+of cairo-compile for their sources.
+
+The committed JSON files only keep the fields read by the VM, so the bytecode, builtins, hints
+and main pc are compared instead of the whole files.
+"""
+
+import json
+import os
+import subprocess
+import sys
+import tempfile
+
+FIXTURES_DIR = os.path.join(os.path.dirname(__file__), '..', 'cairo_programs', 'fixtures')
+
+
+def normalize(program):
+    return {
+        'data': [int(word, 16) for word in program['data']],
+        'builtins': program['builtins'],
+        'hints': {pc: [hint['code'] for hint in hints] for pc, hints in program['hints'].items()},
+        'main': program['identifiers']['__main__.main']['pc'],
+    }
+
+
+def check_fixture(name):
+    with tempfile.TemporaryDirectory() as tmp_dir:
+        compiled_path = os.path.join(tmp_dir, name + '.json')
+        subprocess.run(
+            ['cairo-compile', os.path.join(FIXTURES_DIR, name + '.cairo'), '--output', compiled_path],
+            check=True)
+        with open(compiled_path) as f:
+            compiled = normalize(json.load(f))
+    with open(os.path.join(FIXTURES_DIR, name + '.json')) as f:
+        committed = normalize(json.load(f))
+    mismatches = [key for key in compiled if compiled[key] != committed[key]]
+    for key in mismatches:
+        print(f'{name}: {key} differs, expected {compiled[key]}, got {committed[key]}')
+    return not mismatches
+
+
+def main():
+    names = sorted(
+        file_name[:-len('.cairo')] for file_name in os.listdir(FIXTURES_DIR) if file_name.endswith('.cairo'))
+    results = [check_fixture(name) for name in names]
+    print(f'{results.count(True)}/{len(results)} fixtures match their sources')
+    sys.exit(0 if all(results) else 1)
+
+
+if __name__ == '__main__':
+    main()
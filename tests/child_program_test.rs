@@ -0,0 +1,86 @@
+use std::{collections::HashMap, path::Path, rc::Rc};
+
+use cairo_vm::{
+    hint_processor::{
+        builtin_hint_processor::builtin_hint_processor_definition::{
+            BuiltinHintProcessor, HintFunc,
+        },
+        hint_processor_definition::HintReference,
+    },
+    serde::deserialize_program::ApTracking,
+    types::{exec_scope::ExecutionScopes, program::Program, relocatable::Relocatable},
+    vm::{
+        errors::hint_errors::HintError,
+        runners::cairo_runner::{CairoRunner, RunResources},
+        vm_core::VirtualMachine,
+    },
+};
+use felt::Felt;
+
+// Returns a hint counting its executions in the `name` variable of the execution scopes
+fn counter_hint(name: &'static str) -> Rc<HintFunc> {
+    Rc::new(HintFunc(Box::new(
+        move |_vm: &mut VirtualMachine,
+              exec_scopes: &mut ExecutionScopes,
+              _ids_data: &HashMap<String, HintReference>,
+              _ap_tracking: &ApTracking,
+              _constants: &HashMap<String, Felt>|
+              -> Result<(), HintError> {
+            let count = exec_scopes.get::<usize>(name).unwrap_or(0);
+            exec_scopes.insert_value(name, count + 1);
+            Ok(())
+        },
+    )))
+}
+
+// The parent program calls the main of the child program, which is loaded right after it and
+// computes the 10th fibonacci number
+#[test]
+fn run_child_fibonacci_in_parent_program_segment() {
+    let parent = Program::from_file(
+        Path::new("cairo_programs/manually_compiled/child_caller.json"),
+        Some("main"),
+    )
+    .expect("Failed to deserialize program");
+    let child = Program::from_file(
+        Path::new("cairo_programs/manually_compiled/fib_child.json"),
+        Some("main"),
+    )
+    .expect("Failed to deserialize program");
+    let mut hint_processor = BuiltinHintProcessor::new_empty();
+    hint_processor.add_hint(
+        String::from("count_parent_call"),
+        counter_hint("parent_calls"),
+    );
+    hint_processor.add_hint(String::from("count_fib_step"), counter_hint("fib_steps"));
+
+    let mut cairo_runner = CairoRunner::new(&parent, "all", false).unwrap();
+    let mut vm = VirtualMachine::new(false);
+    cairo_runner.initialize_builtins(&mut vm).unwrap();
+    cairo_runner
+        .initialize_segments(&mut vm, None, None)
+        .unwrap();
+    let loaded = cairo_runner
+        .load_child_program(&child, None, &mut vm)
+        .unwrap();
+    assert_eq!(loaded.base, Relocatable::from((0, 5)));
+    assert_eq!(loaded.size, 24);
+    assert_eq!(loaded.entrypoints.get("__main__.main"), Some(&5));
+    assert_eq!(loaded.entrypoints.get("__main__.fib"), Some(&16));
+
+    cairo_runner
+        .run_from_entrypoint(
+            0,
+            &[],
+            true,
+            &mut RunResources::default(),
+            &mut vm,
+            &mut hint_processor,
+        )
+        .unwrap();
+
+    assert_eq!(cairo_runner.exec_scopes.get::<usize>("parent_calls"), Ok(1));
+    assert_eq!(cairo_runner.exec_scopes.get::<usize>("fib_steps"), Ok(10));
+    // The child's main asserted that the result is 144
+    assert_eq!(vm.get_return_values(1).unwrap()[0], 144.into());
+}
@@ -0,0 +1,65 @@
+use std::path::Path;
+
+use cairo_vm::{
+    hint_processor::builtin_hint_processor::builtin_hint_processor_definition::BuiltinHintProcessor,
+    types::{
+        exec_scope::{ExecutionScopes, SerializedExecutionScopes},
+        program::Program,
+    },
+    vm::{
+        runners::cairo_runner::{CairoRunner, RunResources},
+        vm_core::VirtualMachine,
+    },
+};
+use felt::Felt;
+
+// Runs the dict integration program and returns its relocated memory and number of steps. When
+// given a step, the execution scopes are serialized to JSON at that step and replaced by the ones
+// restored from it, as a run resumed from a snapshot would do.
+fn run_dict_program(interrupt_at: Option<usize>) -> (Vec<Option<Felt>>, usize) {
+    let program = Program::from_file(
+        Path::new("cairo_programs/dict_integration_tests.json"),
+        Some("main"),
+    )
+    .expect("Failed to deserialize program");
+    let mut hint_processor = BuiltinHintProcessor::new_empty();
+    let mut cairo_runner = CairoRunner::new(&program, "all", false).unwrap();
+    let mut vm = VirtualMachine::new(false);
+    let end = cairo_runner.initialize(&mut vm).unwrap();
+
+    if let Some(steps) = interrupt_at {
+        cairo_runner
+            .run_for_steps(steps, &mut vm, &mut hint_processor)
+            .unwrap();
+        let serialized = cairo_runner.exec_scopes.serialize_known_types();
+        assert!(serialized.non_serializable.is_empty());
+        assert!(!serialized.dict_managers.is_empty());
+        let json = serde_json::to_string(&serialized).unwrap();
+        drop(std::mem::take(&mut cairo_runner.exec_scopes));
+
+        let serialized: SerializedExecutionScopes = serde_json::from_str(&json).unwrap();
+        cairo_runner.exec_scopes = ExecutionScopes::restore_known_types(serialized).unwrap();
+    }
+    cairo_runner
+        .run_until_pc(
+            end,
+            &mut RunResources::default(),
+            &mut vm,
+            &mut hint_processor,
+        )
+        .unwrap();
+    cairo_runner
+        .end_run(false, false, &mut vm, &mut hint_processor)
+        .unwrap();
+    let n_steps = cairo_runner.get_execution_resources(&vm).unwrap().n_steps;
+    cairo_runner.relocate(&mut vm, None).unwrap();
+    (cairo_runner.relocated_memory, n_steps)
+}
+
+#[test]
+fn dict_program_resumed_from_serialized_scopes() {
+    let (memory, n_steps) = run_dict_program(None);
+    let (resumed_memory, resumed_n_steps) = run_dict_program(Some(n_steps / 2));
+    assert_eq!(resumed_n_steps, n_steps);
+    assert_eq!(resumed_memory, memory);
+}
@@ -0,0 +1,28 @@
+#![cfg(feature = "hint-profiling")]
+
+use cairo_vm::cairo_run;
+use cairo_vm::hint_processor::builtin_hint_processor::builtin_hint_processor_definition::BuiltinHintProcessor;
+
+#[test]
+fn hint_profile_of_dict_program() {
+    let mut hint_executor = BuiltinHintProcessor::new_empty();
+    let program_content = std::fs::read("cairo_programs/dict_integration_tests.json").unwrap();
+    let cairo_run_config = cairo_run::CairoRunConfig {
+        layout: "all",
+        ..Default::default()
+    };
+    cairo_run::cairo_run(&program_content, &cairo_run_config, &mut hint_executor)
+        .expect("Couldn't run program");
+
+    let profile = hint_executor.get_profile();
+    let dict_hints: Vec<_> = profile
+        .sorted_by_total_time()
+        .into_iter()
+        .filter(|(hint_code, _)| hint_code.contains("__dict_manager"))
+        .collect();
+    // dict_new, dict_write, dict_read, dict_update and the squash hints
+    assert!(dict_hints.len() >= 5);
+    for (hint_code, stats) in dict_hints {
+        assert!(stats.count > 0, "{hint_code} has no executions");
+    }
+}
@@ -0,0 +1,68 @@
+mod support;
+
+use std::path::Path;
+use support::{compare_against_oracle, run_against_oracle};
+
+// The oracle traces of the programs in cairo_programs are written by cairo-run
+// (`make test` builds them along with the compiled programs)
+
+#[test]
+fn oracle_arithmetic() {
+    run_against_oracle(
+        Path::new("cairo_programs/oracle_arith.json"),
+        Path::new("cairo_programs/oracle_arith.trace"),
+    );
+}
+
+#[test]
+fn oracle_function_calls() {
+    run_against_oracle(
+        Path::new("cairo_programs/oracle_call.json"),
+        Path::new("cairo_programs/oracle_call.trace"),
+    );
+}
+
+#[test]
+fn oracle_conditional_jumps() {
+    run_against_oracle(
+        Path::new("cairo_programs/oracle_loop.json"),
+        Path::new("cairo_programs/oracle_loop.trace"),
+    );
+}
+
+// cairo_trace_struct was written by cairo-run for struct.cairo, whose data is copied into
+// manually_compiled/struct.json from the memory dump cairo-run wrote along with it
+
+#[test]
+fn oracle_struct() {
+    assert_eq!(
+        compare_against_oracle(
+            Path::new("cairo_programs/manually_compiled/struct.json"),
+            Path::new("cairo_programs/trace_memory/cairo_trace_struct"),
+        ),
+        Ok(1)
+    );
+}
+
+#[test]
+fn oracle_reports_run_past_the_end_of_the_trace() {
+    // Same initial state as struct.cairo, but main doesn't return
+    assert_eq!(
+        compare_against_oracle(
+            Path::new("cairo_programs/manually_compiled/copy_fp_arg.json"),
+            Path::new("cairo_programs/trace_memory/cairo_trace_struct"),
+        ),
+        Err("the oracle trace ended after step 0 (`[ap] = [fp + (-2)]; ap++`) but the run continues at (pc: 2, ap: 5, fp: 4)".to_string())
+    );
+}
+
+#[test]
+fn oracle_reports_initial_state_mismatch() {
+    assert_eq!(
+        compare_against_oracle(
+            Path::new("cairo_programs/manually_compiled/valid_program_a.json"),
+            Path::new("cairo_programs/trace_memory/cairo_trace_struct"),
+        ),
+        Err("registers diverge at the initial state: got (pc: 1, ap: 9, fp: 9), oracle has (pc: 1, ap: 4, fp: 4)".to_string())
+    );
+}
@@ -4,7 +4,10 @@ use cairo_vm::{
     hint_processor::builtin_hint_processor::builtin_hint_processor_definition::BuiltinHintProcessor,
     types::program::Program,
     vm::trace::trace_entry::RelocatedTraceEntry,
-    vm::{runners::cairo_runner::CairoRunner, vm_core::VirtualMachine},
+    vm::{
+        runners::cairo_runner::{CairoRunner, RunResources},
+        vm_core::VirtualMachine,
+    },
 };
 
 #[test]
@@ -16,10 +19,18 @@ fn pedersen_integration_test() {
     let mut vm = VirtualMachine::new(true);
     let end = cairo_runner.initialize(&mut vm).unwrap();
     assert_eq!(
-        cairo_runner.run_until_pc(end, &mut vm, &mut hint_processor),
+        cairo_runner.run_until_pc(
+            end,
+            &mut RunResources::default(),
+            &mut vm,
+            &mut hint_processor
+        ),
         Ok(())
     );
-    assert!(cairo_runner.relocate(&mut vm) == Ok(()), "Execution failed");
+    assert!(
+        cairo_runner.relocate(&mut vm, None) == Ok(()),
+        "Execution failed"
+    );
 
     let python_vm_relocated_trace: Vec<RelocatedTraceEntry> = vec![
         RelocatedTraceEntry {
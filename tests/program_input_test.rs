@@ -0,0 +1,79 @@
+use std::{collections::HashMap, path::Path, rc::Rc};
+
+use cairo_vm::{
+    hint_processor::{
+        builtin_hint_processor::{
+            builtin_hint_processor_definition::{BuiltinHintProcessor, HintFunc},
+            hint_utils::{get_program_input_as_bigint, insert_value_into_ap},
+        },
+        hint_processor_definition::HintReference,
+    },
+    serde::deserialize_program::ApTracking,
+    types::{exec_scope::ExecutionScopes, program::Program},
+    vm::{
+        errors::{hint_errors::HintError, vm_errors::VirtualMachineError},
+        runners::cairo_runner::{CairoRunner, RunResources},
+        vm_core::VirtualMachine,
+    },
+};
+use felt::Felt;
+use serde_json::json;
+
+// Runs a program which writes n, n - 1, ..., 1 to the output, reading n from the program input
+fn run_countdown(program_input: serde_json::Value) -> Result<String, VirtualMachineError> {
+    let program = Program::from_file(
+        Path::new("cairo_programs/manually_compiled/program_input_countdown.json"),
+        Some("main"),
+    )
+    .expect("Failed to deserialize program");
+    let mut hint_processor = BuiltinHintProcessor::new_empty();
+    hint_processor.add_hint(
+        String::from("memory[ap] = program_input['n']"),
+        Rc::new(HintFunc(Box::new(
+            |vm: &mut VirtualMachine,
+             exec_scopes: &mut ExecutionScopes,
+             _ids_data: &HashMap<String, HintReference>,
+             _ap_tracking: &ApTracking,
+             _constants: &HashMap<String, Felt>|
+             -> Result<(), HintError> {
+                let n = get_program_input_as_bigint(exec_scopes, "n")?;
+                insert_value_into_ap(vm, Felt::from(n))
+            },
+        ))),
+    );
+    let mut cairo_runner = CairoRunner::new(&program, "all", false).unwrap();
+    cairo_runner.set_program_input(program_input);
+    let mut vm = VirtualMachine::new(false);
+    let end = cairo_runner.initialize(&mut vm).unwrap();
+    cairo_runner.run_until_pc(
+        end,
+        &mut RunResources::default(),
+        &mut vm,
+        &mut hint_processor,
+    )?;
+    Ok(cairo_runner.get_output(&mut vm).unwrap())
+}
+
+#[test]
+fn program_output_depends_on_program_input() {
+    assert_eq!(
+        run_countdown(json!({ "n": 3 })),
+        Ok(String::from("3\n2\n1\n"))
+    );
+    assert_eq!(
+        run_countdown(json!({ "n": 5 })),
+        Ok(String::from("5\n4\n3\n2\n1\n"))
+    );
+    assert_eq!(run_countdown(json!({ "n": 0 })), Ok(String::new()));
+}
+
+#[test]
+fn program_input_missing_key() {
+    assert_eq!(
+        run_countdown(json!({ "m": 3 })),
+        Err(VirtualMachineError::Hint(
+            0,
+            Box::new(HintError::InvalidProgramInput(String::from("n")))
+        ))
+    );
+}
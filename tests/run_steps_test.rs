@@ -0,0 +1,197 @@
+use std::{collections::HashMap, path::Path, rc::Rc};
+
+use cairo_vm::{
+    hint_processor::{
+        builtin_hint_processor::builtin_hint_processor_definition::{
+            BuiltinHintProcessor, HintFunc,
+        },
+        hint_processor_definition::HintReference,
+    },
+    serde::deserialize_program::ApTracking,
+    types::{exec_scope::ExecutionScopes, program::Program, relocatable::Relocatable},
+    vm::{
+        errors::{hint_errors::HintError, vm_errors::VirtualMachineError},
+        runners::cairo_runner::{CairoRunner, RunResources},
+        vm_core::VirtualMachine,
+    },
+};
+use felt::Felt;
+
+// Computing the 10th fibonacci number takes 4 steps in main before the call, 6 steps per
+// recursive call to fib, 4 steps in the last one, 10 rets and 2 steps in main after the call
+const FIB_STEPS: usize = 80;
+
+// Returns a runner for the fibonacci program, its vm, a hint processor counting the executions
+// of the hint in the body of fib in the `fib_steps` variable, and the final pc of the run
+fn fib_runner() -> (
+    CairoRunner,
+    VirtualMachine,
+    BuiltinHintProcessor,
+    Relocatable,
+) {
+    let program = Program::from_file(
+        Path::new("cairo_programs/manually_compiled/fib_child.json"),
+        Some("main"),
+    )
+    .expect("Failed to deserialize program");
+    let mut hint_processor = BuiltinHintProcessor::new_empty();
+    hint_processor.add_hint(
+        String::from("count_fib_step"),
+        Rc::new(HintFunc(Box::new(
+            |_vm: &mut VirtualMachine,
+             exec_scopes: &mut ExecutionScopes,
+             _ids_data: &HashMap<String, HintReference>,
+             _ap_tracking: &ApTracking,
+             _constants: &HashMap<String, Felt>|
+             -> Result<(), HintError> {
+                let count = exec_scopes.get::<usize>("fib_steps").unwrap_or(0);
+                exec_scopes.insert_value("fib_steps", count + 1);
+                Ok(())
+            },
+        ))),
+    );
+    let mut cairo_runner = CairoRunner::new(&program, "all", false).unwrap();
+    let mut vm = VirtualMachine::new(true);
+    let end = cairo_runner.initialize(&mut vm).unwrap();
+    (cairo_runner, vm, hint_processor, end)
+}
+
+#[test]
+fn run_fibonacci_mixing_run_methods() {
+    let (mut cairo_runner, mut vm, mut hint_processor, end) = fib_runner();
+
+    assert_eq!(
+        cairo_runner.run_for_steps(20, &mut vm, &mut hint_processor),
+        Ok(false)
+    );
+    assert_eq!(vm.get_current_step(), 20);
+    // The hint in the body of fib ran on steps 5, 11 and 17
+    assert_eq!(cairo_runner.exec_scopes.get::<usize>("fib_steps"), Ok(3));
+
+    assert_eq!(
+        cairo_runner.run_until_steps(50, &mut vm, &mut hint_processor),
+        Ok(())
+    );
+    assert_eq!(vm.get_current_step(), 50);
+    // Running until a step that was already reached doesn't execute anything
+    assert_eq!(
+        cairo_runner.run_until_steps(30, &mut vm, &mut hint_processor),
+        Ok(())
+    );
+    assert_eq!(vm.get_current_step(), 50);
+
+    assert_eq!(
+        cairo_runner.run_until_pc(
+            end,
+            &mut RunResources::default(),
+            &mut vm,
+            &mut hint_processor
+        ),
+        Ok(())
+    );
+    assert_eq!(vm.get_current_step(), FIB_STEPS);
+    assert_eq!(cairo_runner.exec_scopes.get::<usize>("fib_steps"), Ok(10));
+    assert_eq!(vm.get_return_values(1).unwrap()[0], 144.into());
+
+    // The program already ended
+    assert_eq!(
+        cairo_runner.run_for_steps(3, &mut vm, &mut hint_processor),
+        Ok(true)
+    );
+    assert_eq!(
+        cairo_runner.run_until_steps(FIB_STEPS + 1, &mut vm, &mut hint_processor),
+        Err(VirtualMachineError::EndOfProgram(1))
+    );
+    assert_eq!(vm.get_current_step(), FIB_STEPS);
+}
+
+#[test]
+fn run_fibonacci_past_its_end() {
+    let (mut cairo_runner, mut vm, mut hint_processor, _) = fib_runner();
+
+    assert_eq!(
+        cairo_runner.run_until_steps(FIB_STEPS + 5, &mut vm, &mut hint_processor),
+        Err(VirtualMachineError::EndOfProgram(5))
+    );
+    assert_eq!(vm.get_current_step(), FIB_STEPS);
+    assert_eq!(cairo_runner.exec_scopes.get::<usize>("fib_steps"), Ok(10));
+}
+
+#[test]
+fn run_fibonacci_step_by_step() {
+    let (mut full_runner, mut full_vm, mut full_hint_processor, end) = fib_runner();
+    full_runner
+        .run_until_pc(
+            end,
+            &mut RunResources::default(),
+            &mut full_vm,
+            &mut full_hint_processor,
+        )
+        .unwrap();
+
+    let (mut cairo_runner, mut vm, mut hint_processor, _) = fib_runner();
+    for step in 1..=FIB_STEPS {
+        assert_eq!(
+            cairo_runner.run_for_steps(1, &mut vm, &mut hint_processor),
+            Ok(step == FIB_STEPS)
+        );
+    }
+    assert_eq!(vm.get_pc(), &end);
+    assert_eq!(vm.get_ap(), full_vm.get_ap());
+    assert_eq!(
+        cairo_runner.exec_scopes.get::<usize>("fib_steps"),
+        full_runner.exec_scopes.get::<usize>("fib_steps")
+    );
+    cairo_runner.relocate(&mut vm, None).unwrap();
+    full_runner.relocate(&mut full_vm, None).unwrap();
+    assert_eq!(cairo_runner.relocated_memory, full_runner.relocated_memory);
+    assert_eq!(cairo_runner.relocated_trace, full_runner.relocated_trace);
+}
+
+#[test]
+fn run_fibonacci_in_two_halves() {
+    let (mut full_runner, mut full_vm, mut full_hint_processor, end) = fib_runner();
+    full_runner
+        .run_until_pc(
+            end,
+            &mut RunResources::default(),
+            &mut full_vm,
+            &mut full_hint_processor,
+        )
+        .unwrap();
+
+    // Run until the first call to fib, at pc 11
+    let (mut cairo_runner, mut vm, mut hint_processor, _) = fib_runner();
+    let fib = Relocatable::from((0, 11));
+    assert_eq!(
+        cairo_runner.run_until_pc(
+            fib,
+            &mut RunResources::default(),
+            &mut vm,
+            &mut hint_processor
+        ),
+        Ok(())
+    );
+    assert_eq!(cairo_runner.get_pc(&vm), fib);
+    // main pushed the 3 arguments of fib and called it, which starts its frame on top of them
+    assert_eq!(vm.get_current_step(), 4);
+    assert_eq!(cairo_runner.get_fp(&vm), cairo_runner.get_ap(&vm));
+    assert!(cairo_runner.exec_scopes.get::<usize>("fib_steps").is_err());
+
+    assert_eq!(
+        cairo_runner.run_until_pc(
+            end,
+            &mut RunResources::default(),
+            &mut vm,
+            &mut hint_processor
+        ),
+        Ok(())
+    );
+    assert_eq!(vm.get_current_step(), FIB_STEPS);
+    assert_eq!(cairo_runner.exec_scopes.get::<usize>("fib_steps"), Ok(10));
+    assert_eq!(cairo_runner.get_ap(&vm), full_vm.get_ap());
+    cairo_runner.relocate(&mut vm, None).unwrap();
+    full_runner.relocate(&mut full_vm, None).unwrap();
+    assert_eq!(cairo_runner.relocated_memory, full_runner.relocated_memory);
+    assert_eq!(cairo_runner.relocated_trace, full_runner.relocated_trace);
+}
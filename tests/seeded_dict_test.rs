@@ -0,0 +1,82 @@
+use std::{cell::RefCell, collections::HashMap, path::Path, rc::Rc};
+
+use cairo_vm::{
+    hint_processor::builtin_hint_processor::{
+        builtin_hint_processor_definition::BuiltinHintProcessor, dict_manager::DictManager,
+    },
+    types::{exec_scope::ExecutionScopes, program::Program, relocatable::MaybeRelocatable},
+    vm::{
+        runners::cairo_runner::{CairoArg, CairoRunner, RunResources},
+        vm_core::VirtualMachine,
+    },
+};
+use felt::{Felt, NewFelt};
+use num_bigint::BigInt;
+
+#[test]
+fn seeded_dict_integration_test() {
+    let program = Program::from_file(Path::new("cairo_programs/seeded_dict.json"), None)
+        .expect("Failed to deserialize program");
+    let mut hint_processor = BuiltinHintProcessor::new_empty();
+    let mut cairo_runner = CairoRunner::new(&program, "all", false).unwrap();
+    let mut vm = VirtualMachine::new(false);
+    cairo_runner.initialize_builtins(&mut vm).unwrap();
+    cairo_runner
+        .initialize_segments(&mut vm, None, None)
+        .unwrap();
+
+    // Seed the dictionary from Rust, as a storage snapshot would be
+    let mut dict_manager = DictManager::new();
+    let dict_start = dict_manager
+        .new_dict_from(
+            &mut vm,
+            HashMap::from([
+                (BigInt::from(7), MaybeRelocatable::from(Felt::new(21))),
+                (BigInt::from(8), MaybeRelocatable::from(Felt::new(0))),
+            ]),
+        )
+        .unwrap();
+    let dict_manager = Rc::new(RefCell::new(dict_manager));
+    let mut exec_scopes = ExecutionScopes::new();
+    exec_scopes.insert_dict_manager(dict_manager.clone());
+    cairo_runner.set_initial_exec_scopes(exec_scopes);
+
+    let entrypoint = program
+        .identifiers
+        .get("__main__.update")
+        .and_then(|identifier| identifier.pc)
+        .unwrap();
+    let args = [
+        CairoArg::from(MaybeRelocatable::from((2, 0))),
+        CairoArg::from(MaybeRelocatable::from(dict_start)),
+        CairoArg::from(MaybeRelocatable::from(Felt::new(7))),
+    ];
+    cairo_runner
+        .run_from_entrypoint(
+            entrypoint,
+            &args.iter().collect::<Vec<_>>(),
+            true,
+            &mut RunResources::default(),
+            &mut vm,
+            &mut hint_processor,
+        )
+        .unwrap();
+
+    // Read the seeded dictionary back, one read and two writes later
+    let dict_manager = dict_manager.borrow();
+    let tracker = &dict_manager.trackers[&dict_start.segment_index];
+    assert_eq!(tracker.current_ptr, (dict_start + 9_usize));
+    assert_eq!(
+        tracker.get_dictionary_copy(),
+        HashMap::from([
+            (
+                MaybeRelocatable::from(Felt::new(7)),
+                MaybeRelocatable::from(Felt::new(0))
+            ),
+            (
+                MaybeRelocatable::from(Felt::new(8)),
+                MaybeRelocatable::from(Felt::new(42))
+            ),
+        ])
+    );
+}
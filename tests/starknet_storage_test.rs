@@ -0,0 +1,52 @@
+use std::path::Path;
+
+use cairo_vm::{
+    hint_processor::builtin_hint_processor::{
+        builtin_hint_processor_definition::BuiltinHintProcessor,
+        starknet_hint_utils::get_storage_var_address,
+    },
+    types::program::Program,
+    vm::{
+        runners::cairo_runner::{CairoRunner, RunResources},
+        vm_core::VirtualMachine,
+    },
+};
+use felt::{Felt, NewFelt};
+
+#[test]
+fn storage_var_address_integration_test() {
+    let program = Program::from_file(
+        Path::new("cairo_programs/storage_var_address.json"),
+        Some("main"),
+    )
+    .expect("Failed to deserialize program");
+    let mut hint_processor = BuiltinHintProcessor::new_empty();
+    let mut cairo_runner = CairoRunner::new(&program, "all", false).unwrap();
+    let mut vm = VirtualMachine::new(false);
+    let end = cairo_runner.initialize(&mut vm).unwrap();
+    assert_eq!(
+        cairo_runner.run_until_pc(
+            end,
+            &mut RunResources::default(),
+            &mut vm,
+            &mut hint_processor
+        ),
+        Ok(())
+    );
+
+    // pedersen(starknet_keccak("balance"), 1234) % ADDR_BOUND, the address of balance[1234]
+    let expected_addr =
+        "1035422661094663773330112341472955475987635999270873299513199164702838799389";
+    assert_eq!(
+        get_storage_var_address("balance", &[Felt::new(1234)])
+            .unwrap()
+            .to_string(),
+        expected_addr
+    );
+    // PRIME - 1 - ADDR_BOUND
+    let wrapped_addr = "106710729501573572985208420194530329073740042555888586719488";
+    assert_eq!(
+        cairo_runner.get_output(&mut vm).unwrap(),
+        format!("{expected_addr}\n{wrapped_addr}\n")
+    );
+}
@@ -6,7 +6,10 @@ use std::path::Path;
 
 use cairo_vm::{
     types::program::Program,
-    vm::{runners::cairo_runner::CairoRunner, trace::trace_entry::RelocatedTraceEntry},
+    vm::{
+        runners::cairo_runner::{CairoRunner, RunResources},
+        trace::trace_entry::RelocatedTraceEntry,
+    },
 };
 
 #[test]
@@ -19,10 +22,18 @@ fn struct_integration_test() {
     let end = cairo_runner.initialize(&mut vm).unwrap();
 
     assert!(
-        cairo_runner.run_until_pc(end, &mut vm, &mut hint_processor) == Ok(()),
+        cairo_runner.run_until_pc(
+            end,
+            &mut RunResources::default(),
+            &mut vm,
+            &mut hint_processor
+        ) == Ok(()),
+        "Execution failed"
+    );
+    assert!(
+        cairo_runner.relocate(&mut vm, None) == Ok(()),
         "Execution failed"
     );
-    assert!(cairo_runner.relocate(&mut vm) == Ok(()), "Execution failed");
     let relocated_entry = RelocatedTraceEntry {
         pc: 1,
         ap: 4,
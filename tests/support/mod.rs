@@ -0,0 +1,107 @@
+//! Differential testing against reference traces, stored in the relocated binary format
+//! written by the original Cairo VM's `--trace_file` option.
+//!
+//! The program is run one instruction at a time and, after every step, the registers are
+//! compared against the matching entry of the oracle trace.
+
+use cairo_vm::{
+    cairo_run::read_binary_trace,
+    hint_processor::builtin_hint_processor::builtin_hint_processor_definition::BuiltinHintProcessor,
+    types::{program::Program, relocatable::Relocatable},
+    vm::{
+        runners::cairo_runner::{CairoRunner, RunResources},
+        trace::trace_entry::RelocatedTraceEntry,
+        vm_core::VirtualMachine,
+    },
+};
+use std::path::Path;
+
+/// Runs the program at `program_json` and panics at the first step where the registers
+/// diverge from the relocated trace at `trace_bin`.
+pub fn run_against_oracle(program_json: &Path, trace_bin: &Path) {
+    if let Err(mismatch) = compare_against_oracle(program_json, trace_bin) {
+        panic!("{}: {mismatch}", program_json.display());
+    }
+}
+
+/// Same as `run_against_oracle`, but returns the number of executed steps, or a description
+/// of the first mismatch: the step index, both sets of registers and the instruction that
+/// was executed.
+pub fn compare_against_oracle(program_json: &Path, trace_bin: &Path) -> Result<usize, String> {
+    let program = Program::from_file(program_json, Some("main")).map_err(|e| e.to_string())?;
+    let oracle = read_binary_trace(trace_bin).map_err(|e| e.to_string())?;
+
+    let mut hint_processor = BuiltinHintProcessor::new_empty();
+    let mut cairo_runner = CairoRunner::new(&program, "plain", false).map_err(|e| e.to_string())?;
+    let mut vm = VirtualMachine::new(true);
+    let end = cairo_runner
+        .initialize(&mut vm)
+        .map_err(|e| e.to_string())?;
+    let references = cairo_runner.get_reference_list();
+    let hint_data_dictionary = cairo_runner
+        .get_hint_data_dictionary(&references, &mut hint_processor)
+        .map_err(|e| e.to_string())?;
+    let constants = cairo_runner.get_constants().clone();
+
+    // Registers only ever point to the program and execution segments, whose relocated
+    // bases are already known: the program segment always holds exactly the program data.
+    let relocation_table = [1, 1 + program.data.len()];
+    let relocate = |value: &Relocatable| -> Result<usize, String> {
+        usize::try_from(value.segment_index)
+            .ok()
+            .and_then(|index| relocation_table.get(index))
+            .map(|base| base + value.offset)
+            .ok_or_else(|| format!("register points to unexpected segment: {value}"))
+    };
+
+    let mut step = 0;
+    let mut last_instruction = None;
+    while *vm.get_pc() != end {
+        let registers = RelocatedTraceEntry {
+            pc: relocate(vm.get_pc())?,
+            ap: relocate(&vm.get_ap())?,
+            fp: relocate(&vm.get_fp())?,
+        };
+        let expected = oracle.get(step);
+
+        if expected != Some(&registers) {
+            let after = match last_instruction {
+                Some(ref instruction) => format!("after step {} (`{instruction}`)", step - 1),
+                None => "at the initial state".to_string(),
+            };
+            return Err(match expected {
+                Some(expected) => format!(
+                    "registers diverge {after}: got (pc: {}, ap: {}, fp: {}), oracle has (pc: {}, ap: {}, fp: {})",
+                    registers.pc, registers.ap, registers.fp, expected.pc, expected.ap, expected.fp
+                ),
+                None => format!(
+                    "the oracle trace ended {after} but the run continues at (pc: {}, ap: {}, fp: {})",
+                    registers.pc, registers.ap, registers.fp
+                ),
+            });
+        }
+
+        let instruction = vm
+            .decode_current_instruction()
+            .map(|instruction| instruction.to_string())
+            .map_err(|e| format!("step {step}: failed to decode instruction: {e}"))?;
+        vm.step(
+            &mut hint_processor,
+            &mut cairo_runner.exec_scopes,
+            &hint_data_dictionary,
+            &constants,
+            &mut RunResources::default(),
+        )
+        .map_err(|e| format!("step {step} (`{instruction}`) failed: {e}"))?;
+        last_instruction = Some(instruction);
+        step += 1;
+    }
+
+    if step != oracle.len() {
+        return Err(format!(
+            "the run finished after {step} steps but the oracle trace has {} entries",
+            oracle.len()
+        ));
+    }
+    Ok(step)
+}
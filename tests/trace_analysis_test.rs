@@ -0,0 +1,54 @@
+use std::path::Path;
+
+use cairo_vm::{
+    hint_processor::builtin_hint_processor::builtin_hint_processor_definition::BuiltinHintProcessor,
+    types::{instruction::Opcode, program::Program},
+    vm::{
+        runners::cairo_runner::{CairoRunner, RunResources},
+        vm_core::VirtualMachine,
+    },
+};
+
+// Counts the executed instructions of the factorial program by opcode, as a coverage analyzer
+// built on the resolved trace would
+#[test]
+fn factorial_instruction_frequencies() {
+    let program = Program::from_file(Path::new("cairo_programs/factorial.json"), Some("main"))
+        .expect("Failed to deserialize program");
+    let mut hint_processor = BuiltinHintProcessor::new_empty();
+    let mut cairo_runner = CairoRunner::new(&program, "all", false).unwrap();
+    let mut vm = VirtualMachine::new(true);
+    let end = cairo_runner.initialize(&mut vm).unwrap();
+    cairo_runner
+        .run_until_pc(
+            end,
+            &mut RunResources::default(),
+            &mut vm,
+            &mut hint_processor,
+        )
+        .unwrap();
+
+    // [nop, assert_eq, call, ret]
+    let mut frequencies = [0_usize; 4];
+    let mut n_steps = 0;
+    for step in vm.iter_trace_with_instructions() {
+        let step = step.unwrap();
+        let index = match step.instruction.opcode {
+            Opcode::NOp => 0,
+            Opcode::AssertEq => 1,
+            Opcode::Call => 2,
+            Opcode::Ret => 3,
+        };
+        frequencies[index] += 1;
+        n_steps += 1;
+    }
+
+    assert_eq!(
+        n_steps,
+        cairo_runner.get_execution_resources(&vm).unwrap().n_steps
+    );
+    // factorial is called for n = 10 down to 1, and each call returns, as does main
+    assert_eq!(frequencies[2], 10);
+    assert_eq!(frequencies[3], 11);
+    assert!(frequencies[1] > 0);
+}